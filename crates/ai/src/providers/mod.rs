@@ -1 +1,346 @@
+pub mod ollama;
 pub mod open_ai;
+
+use std::sync::Arc;
+
+use gpui::BackgroundExecutor;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::http::HttpClient;
+
+use crate::{completion::CompletionProvider, embedding::EmbeddingProvider};
+
+use ollama::{OllamaCompletionProvider, OllamaEmbeddingProvider, OLLAMA_API_URL};
+use open_ai::{OpenAiCompletionProvider, OpenAiEmbeddingProvider, OPEN_AI_API_URL};
+
+/// The service that completions and embeddings are requested from.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "name")]
+pub enum AiProvider {
+    /// OpenAI's hosted API, authenticated with an API key.
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// A server implementing OpenAI's API, such as llama.cpp's server or
+    /// Ollama, running at `api_url`. An API key is only sent if one has been
+    /// saved for that URL.
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible { api_url: String },
+    /// An Ollama server, talked to through its native API rather than its
+    /// OpenAI-compatible one.
+    #[serde(rename = "ollama")]
+    Ollama {
+        #[serde(default = "default_ollama_api_url")]
+        api_url: String,
+    },
+}
+
+fn default_ollama_api_url() -> String {
+    OLLAMA_API_URL.to_string()
+}
+
+impl AiProvider {
+    pub fn api_url(&self) -> &str {
+        match self {
+            AiProvider::OpenAi => OPEN_AI_API_URL,
+            AiProvider::OpenAiCompatible { api_url } | AiProvider::Ollama { api_url } => {
+                api_url.trim_end_matches('/')
+            }
+        }
+    }
+
+    pub async fn completion_provider(
+        &self,
+        model_name: String,
+        max_tokens: Option<usize>,
+        executor: BackgroundExecutor,
+    ) -> Arc<dyn CompletionProvider> {
+        match self {
+            AiProvider::OpenAi | AiProvider::OpenAiCompatible { .. } => Arc::new(
+                OpenAiCompletionProvider::new(
+                    self.api_url().to_string(),
+                    self.requires_api_key(),
+                    model_name,
+                    max_tokens,
+                    executor,
+                )
+                .await,
+            ),
+            AiProvider::Ollama { .. } => Arc::new(
+                OllamaCompletionProvider::new(
+                    self.api_url().to_string(),
+                    model_name,
+                    max_tokens,
+                    executor,
+                )
+                .await,
+            ),
+        }
+    }
+
+    pub async fn embedding_provider(
+        &self,
+        model_name: String,
+        max_tokens: Option<usize>,
+        client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Arc<dyn EmbeddingProvider> {
+        match self {
+            AiProvider::OpenAi | AiProvider::OpenAiCompatible { .. } => Arc::new(
+                OpenAiEmbeddingProvider::new(
+                    self.api_url().to_string(),
+                    self.requires_api_key(),
+                    model_name,
+                    max_tokens,
+                    client,
+                    executor,
+                )
+                .await,
+            ),
+            AiProvider::Ollama { .. } => Arc::new(
+                OllamaEmbeddingProvider::new(
+                    self.api_url().to_string(),
+                    model_name,
+                    max_tokens,
+                    client,
+                    executor,
+                )
+                .await,
+            ),
+        }
+    }
+
+    fn requires_api_key(&self) -> bool {
+        matches!(self, AiProvider::OpenAi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        embedding::Embedding,
+        providers::open_ai::{OpenAiRequest, RequestMessage, Role},
+    };
+    use futures::StreamExt;
+    use gpui::TestAppContext;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    #[gpui::test]
+    async fn test_openai_compatible_completion(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let (server_url, requests) = serve_once(concat!(
+            r#"data: {"object":"chat.completion.chunk","created":0,"model":"codellama","choices":[{"index":0,"delta":{"role":"assistant","content":"Hello"},"finish_reason":null}]}"#,
+            "\n\n",
+            r#"data: {"object":"chat.completion.chunk","created":0,"model":"codellama","choices":[{"index":0,"delta":{"content":" world"},"finish_reason":"stop"}]}"#,
+            "\n\n",
+        ));
+        let provider = AiProvider::OpenAiCompatible {
+            api_url: format!("{server_url}/v1"),
+        };
+        let completion_provider = provider
+            .completion_provider("codellama".into(), Some(16384), cx.executor())
+            .await;
+        cx.update(|cx| completion_provider.retrieve_credentials(cx))
+            .await;
+        assert!(completion_provider.has_credentials());
+        assert_eq!(completion_provider.base_model().capacity().unwrap(), 16384);
+
+        let request = Box::new(OpenAiRequest {
+            model: "codellama".into(),
+            messages: vec![RequestMessage {
+                role: Role::User,
+                content: "Say hello".into(),
+            }],
+            stream: true,
+            stop: Vec::new(),
+            temperature: 1.0,
+        });
+        let completion = completion_provider
+            .complete(request)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<String, _>>()
+            .unwrap();
+        assert_eq!(completion, "Hello world");
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /v1/chat/completions "));
+        assert!(request.contains(r#""model":"codellama""#));
+        assert!(!request.to_lowercase().contains("authorization:"));
+    }
+
+    #[gpui::test]
+    async fn test_openai_compatible_embeddings(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let (server_url, requests) = serve_once(
+            r#"{"data":[{"embedding":[0.5,0.25],"index":0,"object":"embedding"}],"usage":{"prompt_tokens":2,"total_tokens":2}}"#,
+        );
+        let provider = AiProvider::OpenAiCompatible {
+            api_url: format!("{server_url}/v1"),
+        };
+        let embedding_provider = provider
+            .embedding_provider(
+                "nomic-embed-text".into(),
+                Some(8192),
+                util::http::client(),
+                cx.executor(),
+            )
+            .await;
+        cx.update(|cx| embedding_provider.retrieve_credentials(cx))
+            .await;
+        assert!(embedding_provider.has_credentials());
+
+        let embeddings = embedding_provider
+            .embed_batch(vec!["fn main() {}".into()])
+            .await
+            .unwrap();
+        assert_eq!(embeddings, vec![Embedding::from(vec![0.5, 0.25])]);
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /v1/embeddings "));
+        assert!(request.contains(r#""model":"nomic-embed-text""#));
+    }
+
+    #[gpui::test]
+    async fn test_ollama_completion(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let (server_url, requests) = serve_once(concat!(
+            r#"{"model":"codellama","created_at":"2024-01-01T00:00:00Z","message":{"role":"assistant","content":"Hello"},"done":false}"#,
+            "\n",
+            r#"{"model":"codellama","created_at":"2024-01-01T00:00:00Z","message":{"role":"assistant","content":" world"},"done":false}"#,
+            "\n",
+            r#"{"model":"codellama","created_at":"2024-01-01T00:00:00Z","message":{"role":"assistant","content":""},"done":true}"#,
+            "\n",
+        ));
+        let provider = AiProvider::Ollama {
+            api_url: format!("{server_url}/"),
+        };
+        let completion_provider = provider
+            .completion_provider("codellama".into(), Some(16384), cx.executor())
+            .await;
+        cx.update(|cx| completion_provider.retrieve_credentials(cx))
+            .await;
+        assert!(completion_provider.has_credentials());
+        assert_eq!(completion_provider.base_model().capacity().unwrap(), 16384);
+
+        let request = Box::new(OpenAiRequest {
+            model: "codellama".into(),
+            messages: vec![RequestMessage {
+                role: Role::User,
+                content: "Say hello".into(),
+            }],
+            stream: true,
+            stop: vec!["\n\n".into()],
+            temperature: 0.5,
+        });
+        let completion = completion_provider
+            .complete(request)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<String, _>>()
+            .unwrap();
+        assert_eq!(completion, "Hello world");
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /api/chat "));
+        let body: serde_json::Value =
+            serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "model": "codellama",
+                "messages": [{ "role": "user", "content": "Say hello" }],
+                "stream": true,
+                "options": { "stop": ["\n\n"], "temperature": 0.5 },
+            })
+        );
+    }
+
+    #[gpui::test]
+    async fn test_ollama_embeddings(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let (server_url, requests) = serve_once(r#"{"embedding":[0.5,0.25]}"#);
+        let provider = AiProvider::Ollama {
+            api_url: server_url,
+        };
+        let embedding_provider = provider
+            .embedding_provider(
+                "nomic-embed-text".into(),
+                Some(8192),
+                util::http::client(),
+                cx.executor(),
+            )
+            .await;
+        assert!(embedding_provider.has_credentials());
+
+        let embeddings = embedding_provider
+            .embed_batch(vec!["fn main() {}".into()])
+            .await
+            .unwrap();
+        assert_eq!(embeddings, vec![Embedding::from(vec![0.5, 0.25])]);
+
+        let request = requests.recv().unwrap();
+        assert!(request.starts_with("POST /api/embeddings "));
+        assert!(request.ends_with(r#"{"model":"nomic-embed-text","prompt":"fn main() {}"}"#));
+    }
+
+    #[test]
+    fn test_ollama_default_api_url() {
+        let provider: AiProvider = serde_json::from_str(r#"{ "name": "ollama" }"#).unwrap();
+        assert_eq!(provider.api_url(), OLLAMA_API_URL);
+    }
+
+    /// Starts a server that answers a single request with the given body, and
+    /// returns its URL along with a receiver for the request it got.
+    fn serve_once(response_body: &'static str) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server_url = format!("http://{}", listener.local_addr().unwrap());
+        let (request_tx, request_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            request.push_str(&String::from_utf8(body).unwrap());
+            request_tx.send(request).unwrap();
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response_body.len(),
+                response_body
+            )
+            .unwrap();
+        });
+        (server_url, request_rx)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod completion;
+pub mod embedding;
+
+pub use completion::*;
+pub use embedding::*;
+
+pub const OLLAMA_API_URL: &'static str = "http://localhost:11434";
@@ -0,0 +1,220 @@
+use anyhow::{anyhow, Result};
+use futures::{
+    future::BoxFuture, io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, FutureExt,
+    Stream, StreamExt,
+};
+use gpui::{AppContext, BackgroundExecutor};
+use isahc::{http::StatusCode, Request, RequestExt};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use crate::{
+    auth::{CredentialProvider, ProviderCredential},
+    completion::{CompletionProvider, CompletionRequest},
+    models::LanguageModel,
+    providers::open_ai::{OpenAiLanguageModel, RequestMessage},
+};
+
+/// The parts of an [`OpenAiRequest`](crate::providers::open_ai::OpenAiRequest)
+/// that Ollama's chat API understands. Completion requests are built in
+/// OpenAI's format, so they're translated from their JSON.
+#[derive(Deserialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<RequestMessage>,
+    #[serde(default)]
+    stop: Vec<String>,
+    temperature: f32,
+}
+
+#[derive(Serialize)]
+struct OllamaChatRequest {
+    model: String,
+    messages: Vec<RequestMessage>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[derive(Serialize)]
+struct OllamaOptions {
+    stop: Vec<String>,
+    temperature: f32,
+}
+
+impl OllamaChatRequest {
+    fn from_completion_request(request: &dyn CompletionRequest) -> Result<Self> {
+        let request: ChatRequest = serde_json::from_str(&request.data()?)?;
+        Ok(Self {
+            model: request.model,
+            messages: request.messages,
+            stream: true,
+            options: OllamaOptions {
+                stop: request.stop,
+                temperature: request.temperature,
+            },
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OllamaResponseMessage {
+    pub content: String,
+}
+
+/// A line of a streamed chat response, the last of which is `done`.
+#[derive(Deserialize, Debug)]
+pub struct OllamaResponseStreamEvent {
+    pub message: Option<OllamaResponseMessage>,
+    #[serde(default)]
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+pub async fn stream_chat(
+    api_url: String,
+    executor: BackgroundExecutor,
+    request: Box<dyn CompletionRequest>,
+) -> Result<impl Stream<Item = Result<OllamaResponseStreamEvent>>> {
+    let (tx, rx) = futures::channel::mpsc::unbounded::<Result<OllamaResponseStreamEvent>>();
+
+    let json_data = serde_json::to_string(&OllamaChatRequest::from_completion_request(
+        request.as_ref(),
+    )?)?;
+    let mut response = Request::post(format!("{api_url}/api/chat"))
+        .header("Content-Type", "application/json")
+        .body(json_data)?
+        .send_async()
+        .await?;
+
+    let status = response.status();
+    if status == StatusCode::OK {
+        executor
+            .spawn(async move {
+                let mut lines = BufReader::new(response.body_mut()).lines();
+
+                fn parse_line(
+                    line: Result<String, io::Error>,
+                ) -> Result<Option<OllamaResponseStreamEvent>> {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        return Ok(None);
+                    }
+                    let event: OllamaResponseStreamEvent = serde_json::from_str(&line)?;
+                    match event.error {
+                        Some(error) => Err(anyhow!("Ollama failed to respond: {error}")),
+                        None => Ok(Some(event)),
+                    }
+                }
+
+                while let Some(line) = lines.next().await {
+                    let Some(event) = parse_line(line).transpose() else {
+                        continue;
+                    };
+                    let done = event.as_ref().map_or(true, |event| event.done);
+                    if tx.unbounded_send(event).is_err() || done {
+                        break;
+                    }
+                }
+
+                anyhow::Ok(())
+            })
+            .detach();
+
+        Ok(rx)
+    } else {
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        #[derive(Deserialize)]
+        struct OllamaError {
+            error: String,
+        }
+
+        match serde_json::from_str::<OllamaError>(&body) {
+            Ok(response) => Err(anyhow!("Failed to connect to Ollama: {}", response.error)),
+            Err(_) => Err(anyhow!(
+                "Failed to connect to Ollama: {} {}",
+                response.status(),
+                body,
+            )),
+        }
+    }
+}
+
+/// Requests completions from Ollama's native chat API, which doesn't need
+/// an API key.
+#[derive(Clone)]
+pub struct OllamaCompletionProvider {
+    api_url: String,
+    model: OpenAiLanguageModel,
+    executor: BackgroundExecutor,
+}
+
+impl OllamaCompletionProvider {
+    pub async fn new(
+        api_url: String,
+        model_name: String,
+        max_tokens: Option<usize>,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        let model = executor
+            .spawn(async move { OpenAiLanguageModel::load_with_capacity(&model_name, max_tokens) })
+            .await;
+        Self {
+            api_url,
+            model,
+            executor,
+        }
+    }
+}
+
+impl CredentialProvider for OllamaCompletionProvider {
+    fn has_credentials(&self) -> bool {
+        true
+    }
+
+    fn retrieve_credentials(&self, _cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
+        async { ProviderCredential::NotNeeded }.boxed()
+    }
+
+    fn save_credentials(
+        &self,
+        _cx: &mut AppContext,
+        _credential: ProviderCredential,
+    ) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+
+    fn delete_credentials(&self, _cx: &mut AppContext) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+}
+
+impl CompletionProvider for OllamaCompletionProvider {
+    fn base_model(&self) -> Box<dyn LanguageModel> {
+        let model: Box<dyn LanguageModel> = Box::new(self.model.clone());
+        model
+    }
+    fn complete(
+        &self,
+        prompt: Box<dyn CompletionRequest>,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let request = stream_chat(self.api_url.clone(), self.executor.clone(), prompt);
+        async move {
+            let response = request.await?;
+            let stream = response
+                .filter_map(|response| async move {
+                    match response {
+                        Ok(response) => Some(Ok(response.message?.content)),
+                        Err(error) => Some(Err(error)),
+                    }
+                })
+                .boxed();
+            Ok(stream)
+        }
+        .boxed()
+    }
+    fn box_clone(&self) -> Box<dyn CompletionProvider> {
+        Box::new((*self).clone())
+    }
+}
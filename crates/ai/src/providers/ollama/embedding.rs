@@ -0,0 +1,129 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::{future::BoxFuture, AsyncReadExt, FutureExt};
+use gpui::{AppContext, BackgroundExecutor};
+use isahc::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Instant};
+use util::http::{HttpClient, Request};
+
+use crate::{
+    auth::{CredentialProvider, ProviderCredential},
+    embedding::{Embedding, EmbeddingProvider},
+    models::LanguageModel,
+    providers::open_ai::OpenAiLanguageModel,
+};
+
+/// Requests embeddings from Ollama's native API, which embeds one span at a
+/// time and doesn't need an API key.
+#[derive(Clone)]
+pub struct OllamaEmbeddingProvider {
+    api_url: String,
+    model: OpenAiLanguageModel,
+    pub client: Arc<dyn HttpClient>,
+    pub executor: BackgroundExecutor,
+}
+
+#[derive(Serialize)]
+struct OllamaEmbeddingRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+impl OllamaEmbeddingProvider {
+    pub async fn new(
+        api_url: String,
+        model_name: String,
+        max_tokens: Option<usize>,
+        client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Self {
+        // Loading the model is expensive, so ensure this runs off the main thread.
+        let model = executor
+            .spawn(async move { OpenAiLanguageModel::load_with_capacity(&model_name, max_tokens) })
+            .await;
+        OllamaEmbeddingProvider {
+            api_url,
+            model,
+            client,
+            executor,
+        }
+    }
+
+    async fn embed(&self, model: &str, span: &str) -> Result<Embedding> {
+        let request = Request::post(format!("{}/api/embeddings", self.api_url))
+            .header("Content-Type", "application/json")
+            .body(
+                serde_json::to_string(&OllamaEmbeddingRequest {
+                    model,
+                    prompt: span,
+                })?
+                .into(),
+            )?;
+        let mut response = self.client.send(request).await?;
+
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+        if response.status() != StatusCode::OK {
+            return Err(anyhow!(
+                "ollama bad request: {:?} {:?}",
+                response.status(),
+                body
+            ));
+        }
+        let response: OllamaEmbeddingResponse = serde_json::from_str(&body)?;
+        Ok(Embedding::from(response.embedding))
+    }
+}
+
+impl CredentialProvider for OllamaEmbeddingProvider {
+    fn has_credentials(&self) -> bool {
+        true
+    }
+
+    fn retrieve_credentials(&self, _cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
+        async { ProviderCredential::NotNeeded }.boxed()
+    }
+
+    fn save_credentials(
+        &self,
+        _cx: &mut AppContext,
+        _credential: ProviderCredential,
+    ) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+
+    fn delete_credentials(&self, _cx: &mut AppContext) -> BoxFuture<()> {
+        async {}.boxed()
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OllamaEmbeddingProvider {
+    fn base_model(&self) -> Box<dyn LanguageModel> {
+        let model: Box<dyn LanguageModel> = Box::new(self.model.clone());
+        model
+    }
+
+    fn max_tokens_per_batch(&self) -> usize {
+        50000
+    }
+
+    fn rate_limit_expiration(&self) -> Option<Instant> {
+        None
+    }
+
+    async fn embed_batch(&self, spans: Vec<String>) -> Result<Vec<Embedding>> {
+        let model = self.model.name();
+        let mut embeddings = Vec::with_capacity(spans.len());
+        for span in &spans {
+            embeddings.push(self.embed(&model, span).await?);
+        }
+        Ok(embeddings)
+    }
+}
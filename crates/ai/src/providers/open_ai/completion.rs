@@ -21,7 +21,7 @@ use crate::{
     models::LanguageModel,
 };
 
-use crate::providers::open_ai::{OpenAiLanguageModel, OPEN_AI_API_URL};
+use crate::providers::open_ai::OpenAiLanguageModel;
 
 #[derive(Clone, Copy, Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -103,13 +103,15 @@ pub struct OpenAiResponseStreamEvent {
 }
 
 pub async fn stream_completion(
+    api_url: String,
     credential: ProviderCredential,
     executor: BackgroundExecutor,
     request: Box<dyn CompletionRequest>,
 ) -> Result<impl Stream<Item = Result<OpenAiResponseStreamEvent>>> {
     let api_key = match credential {
-        ProviderCredential::Credentials { api_key } => api_key,
-        _ => {
+        ProviderCredential::Credentials { api_key } => Some(api_key),
+        ProviderCredential::NotNeeded => None,
+        ProviderCredential::NoCredentials => {
             return Err(anyhow!("no credentials provider for completion"));
         }
     };
@@ -117,12 +119,12 @@ pub async fn stream_completion(
     let (tx, rx) = futures::channel::mpsc::unbounded::<Result<OpenAiResponseStreamEvent>>();
 
     let json_data = request.data()?;
-    let mut response = Request::post(format!("{OPEN_AI_API_URL}/chat/completions"))
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .body(json_data)?
-        .send_async()
-        .await?;
+    let mut request = Request::post(format!("{api_url}/chat/completions"))
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request = request.header("Authorization", format!("Bearer {}", api_key));
+    }
+    let mut response = request.body(json_data)?.send_async().await?;
 
     let status = response.status();
     if status == StatusCode::OK {
@@ -195,53 +197,78 @@ pub async fn stream_completion(
 
 #[derive(Clone)]
 pub struct OpenAiCompletionProvider {
+    api_url: String,
+    requires_api_key: bool,
     model: OpenAiLanguageModel,
     credential: Arc<RwLock<ProviderCredential>>,
     executor: BackgroundExecutor,
 }
 
 impl OpenAiCompletionProvider {
-    pub async fn new(model_name: String, executor: BackgroundExecutor) -> Self {
+    pub async fn new(
+        api_url: String,
+        requires_api_key: bool,
+        model_name: String,
+        max_tokens: Option<usize>,
+        executor: BackgroundExecutor,
+    ) -> Self {
         let model = executor
-            .spawn(async move { OpenAiLanguageModel::load(&model_name) })
+            .spawn(async move { OpenAiLanguageModel::load_with_capacity(&model_name, max_tokens) })
             .await;
         let credential = Arc::new(RwLock::new(ProviderCredential::NoCredentials));
         Self {
+            api_url,
+            requires_api_key,
             model,
             credential,
             executor,
         }
     }
+
+    /// The credential to use when no API key has been provided, as servers
+    /// other than OpenAI's may not need one.
+    fn missing_credential(&self) -> ProviderCredential {
+        if self.requires_api_key {
+            ProviderCredential::NoCredentials
+        } else {
+            ProviderCredential::NotNeeded
+        }
+    }
 }
 
 impl CredentialProvider for OpenAiCompletionProvider {
     fn has_credentials(&self) -> bool {
         match *self.credential.read() {
-            ProviderCredential::Credentials { .. } => true,
-            _ => false,
+            ProviderCredential::Credentials { .. } | ProviderCredential::NotNeeded => true,
+            ProviderCredential::NoCredentials => false,
         }
     }
 
     fn retrieve_credentials(&self, cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
         let existing_credential = self.credential.read().clone();
+        let missing_credential = self.missing_credential();
         let retrieved_credential = match existing_credential {
             ProviderCredential::Credentials { .. } => {
                 return async move { existing_credential }.boxed()
             }
             _ => {
-                if let Some(api_key) = env::var("OPENAI_API_KEY").log_err() {
+                if let Some(api_key) = self
+                    .requires_api_key
+                    .then(|| env::var("OPENAI_API_KEY").log_err())
+                    .flatten()
+                {
                     async move { ProviderCredential::Credentials { api_key } }.boxed()
                 } else {
-                    let credentials = cx.read_credentials(OPEN_AI_API_URL);
+                    let credentials = cx.read_credentials(&self.api_url);
                     async move {
                         if let Some(Some((_, api_key))) = credentials.await.log_err() {
                             if let Some(api_key) = String::from_utf8(api_key).log_err() {
                                 ProviderCredential::Credentials { api_key }
                             } else {
-                                ProviderCredential::NoCredentials
+                                missing_credential
                             }
                         } else {
-                            ProviderCredential::NoCredentials
+                            missing_credential
                         }
                     }
                     .boxed()
@@ -266,7 +293,7 @@ impl CredentialProvider for OpenAiCompletionProvider {
         let credential = credential.clone();
         let write_credentials = match credential {
             ProviderCredential::Credentials { api_key } => {
-                Some(cx.write_credentials(OPEN_AI_API_URL, "Bearer", api_key.as_bytes()))
+                Some(cx.write_credentials(&self.api_url, "Bearer", api_key.as_bytes()))
             }
             _ => None,
         };
@@ -280,8 +307,8 @@ impl CredentialProvider for OpenAiCompletionProvider {
     }
 
     fn delete_credentials(&self, cx: &mut AppContext) -> BoxFuture<()> {
-        *self.credential.write() = ProviderCredential::NoCredentials;
-        let delete_credentials = cx.delete_credentials(OPEN_AI_API_URL);
+        *self.credential.write() = self.missing_credential();
+        let delete_credentials = cx.delete_credentials(&self.api_url);
         async move {
             delete_credentials.await.log_err();
         }
@@ -303,7 +330,12 @@ impl CompletionProvider for OpenAiCompletionProvider {
         // which is currently model based, due to the language model.
         // At some point in the future we should rectify this.
         let credential = self.credential.read().clone();
-        let request = stream_completion(credential, self.executor.clone(), prompt);
+        let request = stream_completion(
+            self.api_url.clone(),
+            credential,
+            self.executor.clone(),
+            prompt,
+        );
         async move {
             let response = request.await?;
             let stream = response
@@ -27,14 +27,14 @@ use crate::embedding::{Embedding, EmbeddingProvider};
 use crate::models::LanguageModel;
 use crate::providers::open_ai::OpenAiLanguageModel;
 
-use crate::providers::open_ai::OPEN_AI_API_URL;
-
 lazy_static! {
     pub(crate) static ref OPEN_AI_BPE_TOKENIZER: CoreBPE = cl100k_base().unwrap();
 }
 
 #[derive(Clone)]
 pub struct OpenAiEmbeddingProvider {
+    api_url: String,
+    requires_api_key: bool,
     model: OpenAiLanguageModel,
     credential: Arc<RwLock<ProviderCredential>>,
     pub client: Arc<dyn HttpClient>,
@@ -45,7 +45,7 @@ pub struct OpenAiEmbeddingProvider {
 
 #[derive(Serialize)]
 struct OpenAiEmbeddingRequest<'a> {
-    model: &'static str,
+    model: &'a str,
     input: Vec<&'a str>,
 }
 
@@ -69,17 +69,26 @@ struct OpenAiEmbeddingUsage {
 }
 
 impl OpenAiEmbeddingProvider {
-    pub async fn new(client: Arc<dyn HttpClient>, executor: BackgroundExecutor) -> Self {
+    pub async fn new(
+        api_url: String,
+        requires_api_key: bool,
+        model_name: String,
+        max_tokens: Option<usize>,
+        client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Self {
         let (rate_limit_count_tx, rate_limit_count_rx) = watch::channel_with(None);
         let rate_limit_count_tx = Arc::new(Mutex::new(rate_limit_count_tx));
 
         // Loading the model is expensive, so ensure this runs off the main thread.
         let model = executor
-            .spawn(async move { OpenAiLanguageModel::load("text-embedding-ada-002") })
+            .spawn(async move { OpenAiLanguageModel::load_with_capacity(&model_name, max_tokens) })
             .await;
         let credential = Arc::new(RwLock::new(ProviderCredential::NoCredentials));
 
         OpenAiEmbeddingProvider {
+            api_url,
+            requires_api_key,
             model,
             credential,
             client,
@@ -89,10 +98,21 @@ impl OpenAiEmbeddingProvider {
         }
     }
 
-    fn get_api_key(&self) -> Result<String> {
+    fn get_api_key(&self) -> Result<Option<String>> {
         match self.credential.read().clone() {
-            ProviderCredential::Credentials { api_key } => Ok(api_key),
-            _ => Err(anyhow!("api credentials not provided")),
+            ProviderCredential::Credentials { api_key } => Ok(Some(api_key)),
+            ProviderCredential::NotNeeded => Ok(None),
+            ProviderCredential::NoCredentials => Err(anyhow!("api credentials not provided")),
+        }
+    }
+
+    /// The credential to use when no API key has been provided, as servers
+    /// other than OpenAI's may not need one.
+    fn missing_credential(&self) -> ProviderCredential {
+        if self.requires_api_key {
+            ProviderCredential::NoCredentials
+        } else {
+            ProviderCredential::NotNeeded
         }
     }
 
@@ -130,23 +150,26 @@ impl OpenAiEmbeddingProvider {
     }
     async fn send_request(
         &self,
-        api_key: &str,
+        api_key: Option<&str>,
         spans: Vec<&str>,
         request_timeout: u64,
     ) -> Result<Response<AsyncBody>> {
-        let request = Request::post("https://api.openai.com/v1/embeddings")
+        let model = self.model.name();
+        let mut request = Request::post(format!("{}/embeddings", self.api_url))
             .redirect_policy(isahc::config::RedirectPolicy::Follow)
             .timeout(Duration::from_secs(request_timeout))
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .body(
-                serde_json::to_string(&OpenAiEmbeddingRequest {
-                    input: spans.clone(),
-                    model: "text-embedding-ada-002",
-                })
-                .unwrap()
-                .into(),
-            )?;
+            .header("Content-Type", "application/json");
+        if let Some(api_key) = api_key {
+            request = request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        let request = request.body(
+            serde_json::to_string(&OpenAiEmbeddingRequest {
+                input: spans.clone(),
+                model: &model,
+            })
+            .unwrap()
+            .into(),
+        )?;
 
         Ok(self.client.send(request).await?)
     }
@@ -155,31 +178,36 @@ impl OpenAiEmbeddingProvider {
 impl CredentialProvider for OpenAiEmbeddingProvider {
     fn has_credentials(&self) -> bool {
         match *self.credential.read() {
-            ProviderCredential::Credentials { .. } => true,
-            _ => false,
+            ProviderCredential::Credentials { .. } | ProviderCredential::NotNeeded => true,
+            ProviderCredential::NoCredentials => false,
         }
     }
 
     fn retrieve_credentials(&self, cx: &mut AppContext) -> BoxFuture<ProviderCredential> {
         let existing_credential = self.credential.read().clone();
+        let missing_credential = self.missing_credential();
         let retrieved_credential = match existing_credential {
             ProviderCredential::Credentials { .. } => {
                 return async move { existing_credential }.boxed()
             }
             _ => {
-                if let Some(api_key) = env::var("OPENAI_API_KEY").log_err() {
+                if let Some(api_key) = self
+                    .requires_api_key
+                    .then(|| env::var("OPENAI_API_KEY").log_err())
+                    .flatten()
+                {
                     async move { ProviderCredential::Credentials { api_key } }.boxed()
                 } else {
-                    let credentials = cx.read_credentials(OPEN_AI_API_URL);
+                    let credentials = cx.read_credentials(&self.api_url);
                     async move {
                         if let Some(Some((_, api_key))) = credentials.await.log_err() {
                             if let Some(api_key) = String::from_utf8(api_key).log_err() {
                                 ProviderCredential::Credentials { api_key }
                             } else {
-                                ProviderCredential::NoCredentials
+                                missing_credential
                             }
                         } else {
-                            ProviderCredential::NoCredentials
+                            missing_credential
                         }
                     }
                     .boxed()
@@ -204,7 +232,7 @@ impl CredentialProvider for OpenAiEmbeddingProvider {
         let credential = credential.clone();
         let write_credentials = match credential {
             ProviderCredential::Credentials { api_key } => {
-                Some(cx.write_credentials(OPEN_AI_API_URL, "Bearer", api_key.as_bytes()))
+                Some(cx.write_credentials(&self.api_url, "Bearer", api_key.as_bytes()))
             }
             _ => None,
         };
@@ -218,8 +246,8 @@ impl CredentialProvider for OpenAiEmbeddingProvider {
     }
 
     fn delete_credentials(&self, cx: &mut AppContext) -> BoxFuture<()> {
-        *self.credential.write() = ProviderCredential::NoCredentials;
-        let delete_credentials = cx.delete_credentials(OPEN_AI_API_URL);
+        *self.credential.write() = self.missing_credential();
+        let delete_credentials = cx.delete_credentials(&self.api_url);
         async move {
             delete_credentials.await.log_err();
         }
@@ -255,7 +283,7 @@ impl EmbeddingProvider for OpenAiEmbeddingProvider {
         while request_number < MAX_RETRIES {
             response = self
                 .send_request(
-                    &api_key,
+                    api_key.as_deref(),
                     spans.iter().map(|x| &**x).collect(),
                     request_timeout,
                 )
@@ -9,15 +9,24 @@ use super::OPEN_AI_BPE_TOKENIZER;
 pub struct OpenAiLanguageModel {
     name: String,
     bpe: Option<CoreBPE>,
+    capacity: Option<usize>,
 }
 
 impl OpenAiLanguageModel {
     pub fn load(model_name: &str) -> Self {
+        Self::load_with_capacity(model_name, None)
+    }
+
+    /// Loads a model whose context size is given explicitly, which is needed
+    /// for models that aren't OpenAI's. Tokens are counted with OpenAI's
+    /// tokenizer for those, which is only an approximation.
+    pub fn load_with_capacity(model_name: &str, capacity: Option<usize>) -> Self {
         let bpe =
             tiktoken_rs::get_bpe_from_model(model_name).unwrap_or(OPEN_AI_BPE_TOKENIZER.to_owned());
         OpenAiLanguageModel {
             name: model_name.to_string(),
             bpe: Some(bpe),
+            capacity,
         }
     }
 }
@@ -54,6 +63,9 @@ impl LanguageModel for OpenAiLanguageModel {
         }
     }
     fn capacity(&self) -> anyhow::Result<usize> {
-        anyhow::Ok(tiktoken_rs::model::get_context_size(&self.name))
+        anyhow::Ok(
+            self.capacity
+                .unwrap_or_else(|| tiktoken_rs::model::get_context_size(&self.name)),
+        )
     }
 }
@@ -2,6 +2,7 @@ pub mod assistant_panel;
 mod assistant_settings;
 mod codegen;
 mod prompts;
+mod slash_command;
 mod streaming_diff;
 
 use ai::providers::open_ai::Role;
@@ -15,8 +16,13 @@ use futures::StreamExt;
 use gpui::{actions, AppContext, SharedString};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Reverse, ffi::OsStr, path::PathBuf, sync::Arc};
-use util::paths::CONVERSATIONS_DIR;
+use std::{
+    cmp::Reverse,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::{paths::CONVERSATIONS_DIR, ResultExt};
 
 actions!(
     assistant,
@@ -31,6 +37,7 @@ actions!(
         InlineAssist,
         ToggleIncludeConversation,
         ToggleRetrieveContext,
+        ForkConversation,
     ]
 );
 
@@ -44,6 +51,16 @@ struct MessageMetadata {
     role: Role,
     sent_at: DateTime<Local>,
     status: MessageStatus,
+    #[serde(default)]
+    context: Vec<MessageContext>,
+}
+
+/// Context that a slash command in a message expanded to, which is sent
+/// before the message's text.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct MessageContext {
+    command: String,
+    text: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -59,6 +76,14 @@ struct SavedMessage {
     start: usize,
 }
 
+/// The message a conversation was forked at, which the fork contains along
+/// with all the messages before it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ForkedFrom {
+    conversation_id: String,
+    message_id: MessageId,
+}
+
 #[derive(Serialize, Deserialize)]
 struct SavedConversation {
     id: Option<String>,
@@ -69,16 +94,56 @@ struct SavedConversation {
     message_metadata: HashMap<MessageId, MessageMetadata>,
     summary: String,
     model: OpenAiModel,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    forked_from: Option<ForkedFrom>,
 }
 
 impl SavedConversation {
-    const VERSION: &'static str = "0.1.0";
+    const VERSION: &'static str = "0.3.0";
+
+    async fn load(path: &Path, fs: &dyn Fs) -> Result<Self> {
+        let saved_conversation = fs.load(path).await?;
+        Ok(serde_json::from_str(&saved_conversation)?)
+    }
+
+    /// Returns a path in the conversations directory for a conversation with
+    /// the given summary that isn't used by another conversation yet.
+    async fn unused_path(summary: &str, fs: &dyn Fs) -> PathBuf {
+        let title = summary.trim().replace(std::path::MAIN_SEPARATOR, " ");
+        let mut discriminant = 1;
+        loop {
+            let path = CONVERSATIONS_DIR.join(format!("{} - {}.zed.json", title, discriminant));
+            if fs.is_file(&path).await {
+                discriminant += 1;
+            } else {
+                return path;
+            }
+        }
+    }
+}
+
+/// The parts of a saved conversation that the conversation history shows and
+/// searches through.
+#[derive(Deserialize)]
+struct SavedConversationPreview {
+    id: Option<String>,
+    text: String,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    forked_from: Option<ForkedFrom>,
 }
 
 struct SavedConversationMetadata {
+    id: Option<String>,
     title: String,
     path: PathBuf,
     mtime: chrono::DateTime<chrono::Local>,
+    text: String,
+    pinned: bool,
+    forked_from: Option<ForkedFrom>,
 }
 
 impl SavedConversationMetadata {
@@ -102,18 +167,48 @@ impl SavedConversationMetadata {
                 .and_then(|name| name.to_str())
                 .zip(metadata)
             {
-                let title = re.replace(file_name, "");
+                let title = re.replace(file_name, "").into_owned();
+                let preview = fs.load(&path).await.and_then(|preview| {
+                    Ok(serde_json::from_str::<SavedConversationPreview>(&preview)?)
+                });
+                let Some(preview) = preview.log_err() else {
+                    continue;
+                };
                 conversations.push(Self {
-                    title: title.into_owned(),
+                    id: preview.id,
+                    title,
                     path,
                     mtime: metadata.mtime.into(),
+                    text: preview.text,
+                    pinned: preview.pinned,
+                    forked_from: preview.forked_from,
                 });
             }
         }
-        conversations.sort_unstable_by_key(|conversation| Reverse(conversation.mtime));
+        conversations.sort_unstable_by_key(|conversation| {
+            (Reverse(conversation.pinned), Reverse(conversation.mtime))
+        });
 
         Ok(conversations)
     }
+
+    /// Whether the conversation's title or any of its messages contain the
+    /// query, ignoring case.
+    fn matches(&self, query: &str) -> bool {
+        self.title.to_lowercase().contains(query) || self.text.to_lowercase().contains(query)
+    }
+
+    /// Returns the line of the conversation's messages that contains the
+    /// query, if it isn't already shown in the title.
+    fn excerpt(&self, query: &str) -> Option<&str> {
+        if query.is_empty() || self.title.to_lowercase().contains(query) {
+            return None;
+        }
+        self.text
+            .lines()
+            .find(|line| line.to_lowercase().contains(query))
+            .map(str::trim)
+    }
 }
 
 pub fn init(cx: &mut AppContext) {
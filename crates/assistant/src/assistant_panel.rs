@@ -2,15 +2,17 @@ use crate::{
     assistant_settings::{AssistantDockPosition, AssistantSettings, OpenAiModel},
     codegen::{self, Codegen, CodegenKind},
     prompts::generate_content_prompt,
-    Assist, CycleMessageRole, InlineAssist, MessageId, MessageMetadata, MessageStatus,
-    NewConversation, QuoteSelection, ResetKey, Role, SavedConversation, SavedConversationMetadata,
-    SavedMessage, Split, ToggleFocus, ToggleIncludeConversation, ToggleRetrieveContext,
+    slash_command::SlashCommand,
+    Assist, CycleMessageRole, ForkConversation, ForkedFrom, InlineAssist, MessageContext,
+    MessageId, MessageMetadata, MessageStatus, NewConversation, QuoteSelection, ResetKey, Role,
+    SavedConversation, SavedConversationMetadata, SavedMessage, Split, ToggleFocus,
+    ToggleIncludeConversation, ToggleRetrieveContext,
 };
 use ai::prompts::repository_context::PromptCodeSnippet;
 use ai::{
     auth::ProviderCredential,
     completion::{CompletionProvider, CompletionRequest},
-    providers::open_ai::{OpenAiCompletionProvider, OpenAiRequest, RequestMessage},
+    providers::open_ai::{OpenAiRequest, RequestMessage},
 };
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Local};
@@ -25,17 +27,20 @@ use editor::{
     Anchor, Editor, EditorElement, EditorEvent, EditorStyle, MultiBufferSnapshot, ToOffset,
     ToPoint,
 };
-use fs::Fs;
-use futures::StreamExt;
+use fs::{Fs, RemoveOptions};
+use futures::{future, StreamExt};
 use gpui::{
     canvas, div, point, relative, rems, uniform_list, Action, AnyElement, AppContext,
-    AsyncAppContext, AsyncWindowContext, AvailableSpace, ClipboardItem, Context, EventEmitter,
-    FocusHandle, FocusableView, FontStyle, FontWeight, HighlightStyle, InteractiveElement,
-    IntoElement, Model, ModelContext, ParentElement, Pixels, PromptLevel, Render, SharedString,
-    StatefulInteractiveElement, Styled, Subscription, Task, TextStyle, UniformListScrollHandle,
-    View, ViewContext, VisualContext, WeakModel, WeakView, WhiteSpace, WindowContext,
+    AsyncAppContext, AsyncWindowContext, AvailableSpace, ClickEvent, ClipboardItem, Context,
+    EventEmitter, FocusHandle, FocusableView, FontStyle, FontWeight, HighlightStyle,
+    InteractiveElement, IntoElement, Model, ModelContext, ParentElement, Pixels, PromptLevel,
+    Render, SharedString, StatefulInteractiveElement, Styled, Subscription, Task, TextStyle,
+    UniformListScrollHandle, View, ViewContext, VisualContext, WeakModel, WeakView, WhiteSpace,
+    WindowContext,
+};
+use language::{
+    language_settings::SoftWrap, Buffer, BufferId, LanguageRegistry, Point, ToOffset as _,
 };
-use language::{language_settings::SoftWrap, Buffer, BufferId, LanguageRegistry, ToOffset as _};
 use project::Project;
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use semantic_index::{SemanticIndex, SemanticIndexStatus};
@@ -55,7 +60,7 @@ use theme::ThemeSettings;
 use ui::{
     prelude::*,
     utils::{DateTimeType, FormatDistance},
-    ButtonLike, Tab, TabBar, Tooltip,
+    ButtonLike, Disclosure, Tab, TabBar, Tooltip,
 };
 use util::{paths::CONVERSATIONS_DIR, post_inc, ResultExt, TryFutureExt};
 use uuid::Uuid;
@@ -65,6 +70,11 @@ use workspace::{
     Save, Toast, ToggleZoom, Toolbar, Workspace,
 };
 
+const SLASH_COMMAND_ERROR_TOAST_ID: usize = 4096;
+
+/// The most rows that an expanded context block takes up in a conversation.
+const MAX_EXPANDED_CONTEXT_ROWS: usize = 24;
+
 pub fn init(cx: &mut AppContext) {
     AssistantSettings::register(cx);
     cx.observe_new_views(
@@ -90,6 +100,10 @@ pub struct AssistantPanel {
     editors: Vec<View<ConversationEditor>>,
     saved_conversations: Vec<SavedConversationMetadata>,
     saved_conversations_scroll_handle: UniformListScrollHandle,
+    history_query_editor: View<Editor>,
+    history_query: String,
+    conversation_matches: Vec<usize>,
+    renaming_conversation: Option<(PathBuf, View<Editor>)>,
     zoomed: bool,
     focus_handle: FocusHandle,
     toolbar: View<Toolbar>,
@@ -121,10 +135,20 @@ impl AssistantPanel {
                 .await
                 .log_err()
                 .unwrap_or_default();
-            // Defaulting currently to GPT4, allow for this to be set via config.
-            let completion_provider =
-                OpenAiCompletionProvider::new("gpt-4".into(), cx.background_executor().clone())
-                    .await;
+            let (provider, model) = cx.update(|cx| {
+                let settings = AssistantSettings::get_global(cx);
+                (
+                    settings.provider.clone(),
+                    settings.default_open_ai_model.clone(),
+                )
+            })?;
+            let completion_provider = provider
+                .completion_provider(
+                    model.full_name().into(),
+                    Some(model.max_tokens()),
+                    cx.background_executor().clone(),
+                )
+                .await;
 
             // TODO: deserialize state.
             let workspace_handle = workspace.clone();
@@ -142,7 +166,7 @@ impl AssistantPanel {
                                 .unwrap_or_default();
                             this.update(&mut cx, |this, cx| {
                                 this.saved_conversations = saved_conversations;
-                                cx.notify();
+                                this.update_conversation_matches(cx);
                             })
                             .ok();
                         }
@@ -159,6 +183,19 @@ impl AssistantPanel {
 
                     let semantic_index = SemanticIndex::global(cx);
 
+                    let history_query_editor = cx.new_view(|cx| {
+                        let mut editor = Editor::single_line(cx);
+                        editor.set_placeholder_text("Search conversations…", cx);
+                        editor
+                    });
+                    let subscriptions =
+                        vec![cx.subscribe(&history_query_editor, |this, _, event, cx| {
+                            if let EditorEvent::BufferEdited = event {
+                                this.update_conversation_matches(cx);
+                            }
+                        })];
+                    let conversation_matches = (0..saved_conversations.len()).collect();
+
                     let focus_handle = cx.focus_handle();
                     cx.on_focus_in(&focus_handle, Self::focus_in).detach();
                     cx.on_focus_out(&focus_handle, Self::focus_out).detach();
@@ -170,16 +207,20 @@ impl AssistantPanel {
                         editors: Default::default(),
                         saved_conversations,
                         saved_conversations_scroll_handle: Default::default(),
+                        history_query_editor,
+                        history_query: String::new(),
+                        conversation_matches,
+                        renaming_conversation: None,
                         zoomed: false,
                         focus_handle,
                         toolbar,
-                        completion_provider: Arc::new(completion_provider),
+                        completion_provider,
                         api_key_editor: None,
                         languages: workspace.app_state().languages.clone(),
                         fs: workspace.app_state().fs.clone(),
                         width: None,
                         height: None,
-                        subscriptions: Default::default(),
+                        subscriptions,
                         next_inline_assist_id: 0,
                         pending_inline_assists: Default::default(),
                         pending_inline_assist_ids_by_editor: Default::default(),
@@ -679,7 +720,7 @@ impl AssistantPanel {
         let mut model = AssistantSettings::get_global(cx)
             .default_open_ai_model
             .clone();
-        let model_name = model.full_name();
+        let prompt_model = model.clone();
 
         let prompt = cx.background_executor().spawn(async move {
             let snippets = snippets.await?;
@@ -691,7 +732,7 @@ impl AssistantPanel {
                 buffer,
                 range,
                 snippets,
-                model_name,
+                &prompt_model,
                 project_name,
             )
         });
@@ -832,6 +873,18 @@ impl AssistantPanel {
     ) {
         match event {
             ConversationEditorEvent::TabContentChanged => cx.notify(),
+            ConversationEditorEvent::Forked(fork) => {
+                let editor = cx.new_view(|cx| {
+                    ConversationEditor::for_conversation(
+                        fork.clone(),
+                        self.fs.clone(),
+                        self.workspace.clone(),
+                        cx,
+                    )
+                });
+                self.add_conversation(editor, cx);
+                fork.update(cx, |fork, cx| fork.save(None, self.fs.clone(), cx));
+            }
         }
     }
 
@@ -934,7 +987,7 @@ impl AssistantPanel {
         self.editors.get(self.active_editor_index?)
     }
 
-    fn render_api_key_editor(
+    fn render_single_line_editor(
         &self,
         editor: &View<Editor>,
         cx: &mut ViewContext<Self>,
@@ -1051,32 +1104,354 @@ impl AssistantPanel {
             })
     }
 
+    fn update_conversation_matches(&mut self, cx: &mut ViewContext<Self>) {
+        self.history_query = self
+            .history_query_editor
+            .read(cx)
+            .text(cx)
+            .trim()
+            .to_lowercase();
+        self.conversation_matches = self
+            .saved_conversations
+            .iter()
+            .enumerate()
+            .filter(|(_, conversation)| conversation.matches(&self.history_query))
+            .map(|(ix, _)| ix)
+            .collect();
+        cx.notify();
+    }
+
+    fn open_first_conversation_match(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.conversation_matches.first() {
+            let path = self.saved_conversations[*ix].path.clone();
+            self.open_conversation(path, cx).detach_and_log_err(cx);
+        }
+    }
+
+    fn render_history(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let view = cx.view().clone();
+        let scroll_handle = self.saved_conversations_scroll_handle.clone();
+        let match_count = self.conversation_matches.len();
+        v_flex()
+            .size_full()
+            .child(
+                h_flex()
+                    .on_action(cx.listener(Self::open_first_conversation_match))
+                    .gap_2()
+                    .m_2()
+                    .px_2()
+                    .py_1()
+                    .bg(cx.theme().colors().editor_background)
+                    .rounded_md()
+                    .child(
+                        Icon::new(IconName::MagnifyingGlass)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(self.render_single_line_editor(&self.history_query_editor, cx)),
+            )
+            .child(
+                canvas(move |bounds, cx| {
+                    uniform_list(
+                        view,
+                        "saved_conversations",
+                        match_count,
+                        |this, range, cx| {
+                            range
+                                .map(|ix| this.render_saved_conversation(ix, cx))
+                                .collect()
+                        },
+                    )
+                    .track_scroll(scroll_handle)
+                    .into_any_element()
+                    .draw(
+                        bounds.origin,
+                        bounds.size.map(AvailableSpace::Definite),
+                        cx,
+                    );
+                })
+                .flex_1()
+                .w_full(),
+            )
+    }
+
     fn render_saved_conversation(
         &mut self,
         index: usize,
         cx: &mut ViewContext<Self>,
     ) -> impl IntoElement {
-        let conversation = &self.saved_conversations[index];
+        let conversation = &self.saved_conversations[self.conversation_matches[index]];
         let path = conversation.path.clone();
+        let title = conversation.title.clone();
+        let pinned = conversation.pinned;
+        let forked_from_title = conversation.forked_from.as_ref().map(|forked_from| {
+            self.saved_conversations
+                .iter()
+                .find(|parent| parent.id.as_ref() == Some(&forked_from.conversation_id))
+                .map_or("a deleted conversation".into(), |parent| {
+                    parent.title.clone()
+                })
+        });
 
-        ButtonLike::new(index)
-            .on_click(cx.listener(move |this, _, cx| {
-                this.open_conversation(path.clone(), cx)
-                    .detach_and_log_err(cx)
-            }))
-            .full_width()
+        let row = if let Some((_, editor)) = self
+            .renaming_conversation
+            .as_ref()
+            .filter(|(renaming_path, _)| *renaming_path == path)
+        {
+            div()
+                .w_full()
+                .px_2()
+                .on_action(cx.listener(Self::confirm_renaming_conversation))
+                .on_action(cx.listener(Self::cancel_renaming_conversation))
+                .child(self.render_single_line_editor(editor, cx))
+                .into_any_element()
+        } else {
+            ButtonLike::new(index)
+                .on_click(cx.listener({
+                    let path = path.clone();
+                    move |this, _, cx| {
+                        this.open_conversation(path.clone(), cx)
+                            .detach_and_log_err(cx)
+                    }
+                }))
+                .full_width()
+                .child(
+                    v_flex()
+                        .w_full()
+                        .child(
+                            h_flex()
+                                .w_full()
+                                .gap_2()
+                                .when(pinned, |this| {
+                                    this.child(
+                                        Icon::new(IconName::Pin)
+                                            .size(IconSize::XSmall)
+                                            .color(Color::Accent),
+                                    )
+                                })
+                                .child(
+                                    Label::new(conversation.mtime.format("%F %I:%M%p").to_string())
+                                        .color(Color::Muted)
+                                        .size(LabelSize::Small),
+                                )
+                                .child(Label::new(title.clone()).size(LabelSize::Small))
+                                .children(forked_from_title.map(|forked_from_title| {
+                                    div()
+                                        .id(("forked_from", index))
+                                        .tooltip(move |cx| {
+                                            Tooltip::text(
+                                                format!("Forked from {forked_from_title}"),
+                                                cx,
+                                            )
+                                        })
+                                        .child(
+                                            Icon::new(IconName::GitBranch)
+                                                .size(IconSize::XSmall)
+                                                .color(Color::Muted),
+                                        )
+                                })),
+                        )
+                        .children(conversation.excerpt(&self.history_query).map(|excerpt| {
+                            Label::new(excerpt.to_string())
+                                .color(Color::Muted)
+                                .size(LabelSize::XSmall)
+                        })),
+                )
+                .into_any_element()
+        };
+
+        h_flex()
+            .w_full()
+            .gap_1()
+            .pr_2()
+            .child(div().flex_1().child(row))
             .child(
-                div()
-                    .flex()
-                    .w_full()
-                    .gap_2()
-                    .child(
-                        Label::new(conversation.mtime.format("%F %I:%M%p").to_string())
-                            .color(Color::Muted)
-                            .size(LabelSize::Small),
-                    )
-                    .child(Label::new(conversation.title.clone()).size(LabelSize::Small)),
+                IconButton::new(("pin", index), IconName::Pin)
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(if pinned { Color::Accent } else { Color::Muted })
+                    .tooltip(move |cx| Tooltip::text(if pinned { "Unpin" } else { "Pin" }, cx))
+                    .on_click(cx.listener({
+                        let path = path.clone();
+                        move |this, _, cx| {
+                            this.toggle_conversation_pinned(path.clone(), cx)
+                                .detach_and_log_err(cx)
+                        }
+                    })),
+            )
+            .child(
+                IconButton::new(("rename", index), IconName::Pencil)
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(Color::Muted)
+                    .tooltip(|cx| Tooltip::text("Rename", cx))
+                    .on_click(cx.listener({
+                        let path = path.clone();
+                        let title = title.clone();
+                        move |this, _, cx| {
+                            this.start_renaming_conversation(path.clone(), title.clone(), cx)
+                        }
+                    })),
+            )
+            .child(
+                IconButton::new(("delete", index), IconName::Trash)
+                    .icon_size(IconSize::XSmall)
+                    .icon_color(Color::Muted)
+                    .tooltip(|cx| Tooltip::text("Delete", cx))
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.delete_conversation(path.clone(), title.clone(), cx)
+                    })),
+            )
+    }
+
+    fn toggle_conversation_pinned(
+        &mut self,
+        path: PathBuf,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        if let Some(ix) = self.editor_index_for_path(&path, cx) {
+            // Open conversations are saved from memory, so they need to be
+            // updated rather than the file.
+            let conversation = self.editors[ix].read(cx).conversation.clone();
+            conversation.update(cx, |conversation, cx| {
+                conversation.pinned = !conversation.pinned;
+                conversation.save(None, fs, cx);
+            });
+            return Task::ready(Ok(()));
+        }
+
+        cx.background_executor().spawn(async move {
+            let mut saved_conversation = SavedConversation::load(&path, fs.as_ref()).await?;
+            saved_conversation.pinned = !saved_conversation.pinned;
+            fs.atomic_write(path, serde_json::to_string(&saved_conversation)?)
+                .await
+        })
+    }
+
+    fn start_renaming_conversation(
+        &mut self,
+        path: PathBuf,
+        title: String,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text(title, cx);
+            editor.select_all(&Default::default(), cx);
+            editor
+        });
+        cx.focus_view(&editor);
+        self.renaming_conversation = Some((path, editor));
+        cx.notify();
+    }
+
+    fn confirm_renaming_conversation(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let Some((path, editor)) = self.renaming_conversation.take() else {
+            return;
+        };
+        let title = editor.read(cx).text(cx).trim().to_string();
+        cx.focus(&self.focus_handle);
+        cx.notify();
+        if !title.is_empty() {
+            self.rename_conversation(path, title, cx)
+                .detach_and_log_err(cx);
+        }
+    }
+
+    fn cancel_renaming_conversation(
+        &mut self,
+        _: &editor::actions::Cancel,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.renaming_conversation = None;
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn rename_conversation(
+        &mut self,
+        path: PathBuf,
+        title: String,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        let remove_options = RemoveOptions {
+            ignore_if_not_exists: true,
+            ..Default::default()
+        };
+        if let Some(ix) = self.editor_index_for_path(&path, cx) {
+            // Clearing the path makes the conversation get saved under its new
+            // title when its summary changes.
+            let conversation = self.editors[ix].read(cx).conversation.clone();
+            return cx.spawn(|_, mut cx| async move {
+                fs.remove_file(&path, remove_options).await?;
+                conversation.update(&mut cx, |conversation, cx| {
+                    conversation.summary = Some(Summary {
+                        text: title,
+                        done: true,
+                    });
+                    conversation.path = None;
+                    cx.emit(ConversationEvent::SummaryChanged);
+                })
+            });
+        }
+
+        cx.background_executor().spawn(async move {
+            let mut saved_conversation = SavedConversation::load(&path, fs.as_ref()).await?;
+            saved_conversation.summary = title;
+            let new_path =
+                SavedConversation::unused_path(&saved_conversation.summary, fs.as_ref()).await;
+            fs.atomic_write(new_path, serde_json::to_string(&saved_conversation)?)
+                .await?;
+            fs.remove_file(&path, remove_options).await
+        })
+    }
+
+    fn delete_conversation(&mut self, path: PathBuf, title: String, cx: &mut ViewContext<Self>) {
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            &format!("Delete \"{title}\"?"),
+            None,
+            &["Delete", "Cancel"],
+        );
+        cx.spawn(|this, mut cx| async move {
+            if answer.await != Ok(0) {
+                return Ok(());
+            }
+
+            let fs = this.update(&mut cx, |this, cx| {
+                if let Some(ix) = this.editor_index_for_path(&path, cx) {
+                    this.remove_conversation_editor(ix, cx);
+                }
+                this.fs.clone()
+            })?;
+            fs.remove_file(
+                &path,
+                RemoveOptions {
+                    ignore_if_not_exists: true,
+                    ..Default::default()
+                },
             )
+            .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn remove_conversation_editor(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        self.editors.remove(ix);
+        let adjust_index = |index: Option<usize>| match index {
+            Some(index) if index == ix => None,
+            Some(index) if index > ix => Some(index - 1),
+            index => index,
+        };
+        self.prev_active_editor_index = adjust_index(self.prev_active_editor_index);
+        let active_editor_index = adjust_index(self.active_editor_index);
+        if active_editor_index == self.active_editor_index {
+            cx.notify();
+        } else {
+            let prev_active_editor_index = self.prev_active_editor_index;
+            self.set_active_editor_index(active_editor_index, cx);
+            self.prev_active_editor_index = prev_active_editor_index;
+        }
     }
 
     fn open_conversation(&mut self, path: PathBuf, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
@@ -1091,11 +1466,14 @@ impl AssistantPanel {
         let workspace = self.workspace.clone();
         let languages = self.languages.clone();
         cx.spawn(|this, mut cx| async move {
-            let saved_conversation = fs.load(&path).await?;
-            let saved_conversation = serde_json::from_str(&saved_conversation)?;
-            let conversation =
-                Conversation::deserialize(saved_conversation, path.clone(), languages, &mut cx)
-                    .await?;
+            let saved_conversation = SavedConversation::load(&path, fs.as_ref()).await?;
+            let conversation = Conversation::deserialize(
+                saved_conversation,
+                Some(path.clone()),
+                languages,
+                &mut cx,
+            )
+            .await?;
 
             this.update(&mut cx, |this, cx| {
                 // If, by the time we've loaded the conversation, the user has already opened
@@ -1171,7 +1549,7 @@ impl Render for AssistantPanel {
                         .py_1()
                         .bg(cx.theme().colors().editor_background)
                         .rounded_md()
-                        .child(self.render_api_key_editor(&api_key_editor, cx)),
+                        .child(self.render_single_line_editor(&api_key_editor, cx)),
                 )
                 .child(
                     h_flex()
@@ -1251,30 +1629,7 @@ impl Render for AssistantPanel {
                         .child(if let Some(editor) = self.active_editor() {
                             editor.clone().into_any_element()
                         } else {
-                            let view = cx.view().clone();
-                            let scroll_handle = self.saved_conversations_scroll_handle.clone();
-                            let conversation_count = self.saved_conversations.len();
-                            canvas(move |bounds, cx| {
-                                uniform_list(
-                                    view,
-                                    "saved_conversations",
-                                    conversation_count,
-                                    |this, range, cx| {
-                                        range
-                                            .map(|ix| this.render_saved_conversation(ix, cx))
-                                            .collect()
-                                    },
-                                )
-                                .track_scroll(scroll_handle)
-                                .into_any_element()
-                                .draw(
-                                    bounds.origin,
-                                    bounds.size.map(AvailableSpace::Definite),
-                                    cx,
-                                );
-                            })
-                            .size_full()
-                            .into_any_element()
+                            self.render_history(cx).into_any_element()
                         }),
                 )
         }
@@ -1376,6 +1731,7 @@ enum ConversationEvent {
     MessagesEdited,
     SummaryChanged,
     StreamedCompletion,
+    Forked(Model<Conversation>),
 }
 
 #[derive(Default)]
@@ -1387,6 +1743,7 @@ struct Summary {
 struct Conversation {
     id: Option<String>,
     buffer: Model<Buffer>,
+    language_registry: Arc<LanguageRegistry>,
     message_anchors: Vec<MessageAnchor>,
     messages_metadata: HashMap<MessageId, MessageMetadata>,
     next_message_id: MessageId,
@@ -1400,6 +1757,8 @@ struct Conversation {
     pending_token_count: Task<Option<()>>,
     pending_save: Task<Result<()>>,
     path: Option<PathBuf>,
+    pinned: bool,
+    forked_from: Option<ForkedFrom>,
     _subscriptions: Vec<Subscription>,
     completion_provider: Arc<dyn CompletionProvider>,
 }
@@ -1415,7 +1774,7 @@ impl Conversation {
         let markdown = language_registry.language_for_name("Markdown");
         let buffer = cx.new_model(|cx| {
             let mut buffer = Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), "");
-            buffer.set_language_registry(language_registry);
+            buffer.set_language_registry(language_registry.clone());
             cx.spawn(|buffer, mut cx| async move {
                 let markdown = markdown.await?;
                 buffer.update(&mut cx, |buffer: &mut Buffer, cx| {
@@ -1440,13 +1799,16 @@ impl Conversation {
             completion_count: Default::default(),
             pending_completions: Default::default(),
             token_count: None,
-            max_token_count: tiktoken_rs::model::get_context_size(&model.full_name()),
+            max_token_count: model.max_tokens(),
             pending_token_count: Task::ready(None),
             model: model.clone(),
             _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
             pending_save: Task::ready(Ok(())),
             path: None,
+            pinned: false,
+            forked_from: None,
             buffer,
+            language_registry,
             completion_provider,
         };
         let message = MessageAnchor {
@@ -1460,6 +1822,7 @@ impl Conversation {
                 role: Role::User,
                 sent_at: Local::now(),
                 status: MessageStatus::Done,
+                context: Vec::new(),
             },
         );
 
@@ -1487,12 +1850,56 @@ impl Conversation {
                 .map(|summary| summary.text.clone())
                 .unwrap_or_default(),
             model: self.model.clone(),
+            pinned: self.pinned,
+            forked_from: self.forked_from.clone(),
         }
     }
 
+    /// Returns a new conversation containing the given message and all the
+    /// messages before it, so that it can be continued differently.
+    fn serialize_fork(&self, message_id: MessageId, cx: &AppContext) -> Option<SavedConversation> {
+        let conversation_id = self.id.clone()?;
+        let mut forked_message_ids = HashSet::default();
+        let mut fork_end = None;
+        for message in self.messages(cx) {
+            forked_message_ids.insert(message.id);
+            if message.id == message_id {
+                fork_end = Some(message.offset_range.end);
+                break;
+            }
+        }
+        let fork_end = fork_end?;
+
+        let mut fork = self.serialize(cx);
+        fork.id = Some(Uuid::new_v4().to_string());
+        fork.text.truncate(fork_end);
+        fork.messages
+            .retain(|message| forked_message_ids.contains(&message.id));
+        fork.message_metadata
+            .retain(|message_id, _| forked_message_ids.contains(message_id));
+        fork.pinned = false;
+        fork.forked_from = Some(ForkedFrom {
+            conversation_id,
+            message_id,
+        });
+        Some(fork)
+    }
+
+    fn fork(&mut self, message_id: MessageId, cx: &mut ModelContext<Self>) {
+        let Some(fork) = self.serialize_fork(message_id, cx) else {
+            return;
+        };
+        let language_registry = self.language_registry.clone();
+        cx.spawn(|this, mut cx| async move {
+            let fork = Conversation::deserialize(fork, None, language_registry, &mut cx).await?;
+            this.update(&mut cx, |_, cx| cx.emit(ConversationEvent::Forked(fork)))
+        })
+        .detach_and_log_err(cx);
+    }
+
     async fn deserialize(
         saved_conversation: SavedConversation,
-        path: PathBuf,
+        path: Option<PathBuf>,
         language_registry: Arc<LanguageRegistry>,
         cx: &mut AsyncAppContext,
     ) -> Result<Model<Self>> {
@@ -1501,13 +1908,14 @@ impl Conversation {
             None => Some(Uuid::new_v4().to_string()),
         };
         let model = saved_conversation.model;
-        let completion_provider: Arc<dyn CompletionProvider> = Arc::new(
-            OpenAiCompletionProvider::new(
+        let provider = cx.update(|cx| AssistantSettings::get_global(cx).provider.clone())?;
+        let completion_provider = provider
+            .completion_provider(
                 model.full_name().into(),
+                Some(model.max_tokens()),
                 cx.background_executor().clone(),
             )
-            .await,
-        );
+            .await;
         cx.update(|cx| completion_provider.retrieve_credentials(cx))?
             .await;
 
@@ -1527,7 +1935,7 @@ impl Conversation {
                 });
                 next_message_id = cmp::max(next_message_id, MessageId(message.id.0 + 1));
             }
-            buffer.set_language_registry(language_registry);
+            buffer.set_language_registry(language_registry.clone());
             cx.spawn(|buffer, mut cx| async move {
                 let markdown = markdown.await?;
                 buffer.update(&mut cx, |buffer: &mut Buffer, cx| {
@@ -1553,13 +1961,16 @@ impl Conversation {
                 completion_count: Default::default(),
                 pending_completions: Default::default(),
                 token_count: None,
-                max_token_count: tiktoken_rs::model::get_context_size(&model.full_name()),
+                max_token_count: model.max_tokens(),
                 pending_token_count: Task::ready(None),
                 model,
                 _subscriptions: vec![cx.subscribe(&buffer, Self::handle_buffer_event)],
                 pending_save: Task::ready(Ok(())),
-                path: Some(path),
+                path,
+                pinned: saved_conversation.pinned,
+                forked_from: saved_conversation.forked_from,
                 buffer,
+                language_registry,
                 completion_provider,
             };
             this.count_remaining_tokens(cx);
@@ -1593,12 +2004,7 @@ impl Conversation {
                         Role::Assistant => "assistant".into(),
                         Role::System => "system".into(),
                     },
-                    content: Some(
-                        self.buffer
-                            .read(cx)
-                            .text_for_range(message.offset_range)
-                            .collect(),
-                    ),
+                    content: Some(message.to_open_ai_message(self.buffer.read(cx)).content),
                     name: None,
                     function_call: None,
                 })
@@ -1613,13 +2019,12 @@ impl Conversation {
                 let token_count = cx
                     .background_executor()
                     .spawn(async move {
-                        tiktoken_rs::num_tokens_from_messages(&model.full_name(), &messages)
+                        tiktoken_rs::num_tokens_from_messages(model.tokenizer_name(), &messages)
                     })
                     .await?;
 
                 this.update(&mut cx, |this, cx| {
-                    this.max_token_count =
-                        tiktoken_rs::model::get_context_size(&this.model.full_name());
+                    this.max_token_count = this.model.max_tokens();
                     this.token_count = Some(token_count);
                     cx.notify()
                 })?;
@@ -1795,6 +2200,81 @@ impl Conversation {
         }
     }
 
+    /// Returns the slash commands on their own lines in the user messages.
+    fn slash_command_invocations(&self, cx: &AppContext) -> Vec<SlashCommandInvocation> {
+        let buffer = self.buffer.read(cx);
+        let mut invocations = Vec::new();
+        for message in self.messages(cx) {
+            if message.role != Role::User {
+                continue;
+            }
+
+            let start_row = buffer.offset_to_point(message.offset_range.start).row;
+            let end_row = buffer.offset_to_point(message.offset_range.end).row;
+            for row in start_row..=end_row {
+                let line_start = buffer.point_to_offset(Point::new(row, 0));
+                let line_end = line_start + buffer.line_len(row) as usize;
+                if line_start >= message.offset_range.end {
+                    break;
+                }
+
+                let text = buffer
+                    .text_for_range(line_start..line_end)
+                    .collect::<String>();
+                let Some(command) = SlashCommand::parse(&text) else {
+                    continue;
+                };
+
+                // Remove the line along with a newline next to it, without
+                // removing the newline that separates it from the next message.
+                let range = if line_end + 1 < message.offset_range.end {
+                    line_start..line_end + 1
+                } else if line_start > message.offset_range.start {
+                    line_start - 1..line_end
+                } else {
+                    line_start..line_end
+                };
+                invocations.push(SlashCommandInvocation {
+                    message_id: message.id,
+                    range: buffer.anchor_before(range.start)..buffer.anchor_after(range.end),
+                    text: text.trim().to_string(),
+                    command,
+                });
+            }
+        }
+        invocations
+    }
+
+    /// Replaces a slash command's text with the context it expanded to.
+    fn attach_context(
+        &mut self,
+        invocation: SlashCommandInvocation,
+        context: String,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(metadata) = self.messages_metadata.get_mut(&invocation.message_id) else {
+            return;
+        };
+        metadata.context.push(MessageContext {
+            command: invocation.text,
+            text: context,
+        });
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(invocation.range, "")], None, cx)
+        });
+    }
+
+    fn remove_context(&mut self, message_id: MessageId, ix: usize, cx: &mut ModelContext<Self>) {
+        if let Some(metadata) = self.messages_metadata.get_mut(&message_id) {
+            if ix < metadata.context.len() {
+                metadata.context.remove(ix);
+                self.count_remaining_tokens(cx);
+                cx.emit(ConversationEvent::MessagesEdited);
+                cx.notify();
+            }
+        }
+    }
+
     fn insert_message_after(
         &mut self,
         message_id: MessageId,
@@ -1836,6 +2316,7 @@ impl Conversation {
                     role,
                     sent_at: Local::now(),
                     status,
+                    context: Vec::new(),
                 },
             );
             cx.emit(ConversationEvent::MessagesEdited);
@@ -1896,6 +2377,7 @@ impl Conversation {
                     role,
                     sent_at: Local::now(),
                     status: MessageStatus::Done,
+                    context: Vec::new(),
                 },
             );
 
@@ -1941,6 +2423,7 @@ impl Conversation {
                             role,
                             sent_at: Local::now(),
                             status: MessageStatus::Done,
+                            context: Vec::new(),
                         },
                     );
                     (Some(selection), Some(suffix))
@@ -2074,6 +2557,7 @@ impl Conversation {
                     role: metadata.role,
                     sent_at: metadata.sent_at,
                     status: metadata.status.clone(),
+                    context: metadata.context.clone(),
                 });
             }
             None
@@ -2110,21 +2594,7 @@ impl Conversation {
                 let path = if let Some(old_path) = old_path {
                     old_path
                 } else {
-                    let mut discriminant = 1;
-                    let mut new_path;
-                    loop {
-                        new_path = CONVERSATIONS_DIR.join(&format!(
-                            "{} - {}.zed.json",
-                            summary.trim(),
-                            discriminant
-                        ));
-                        if fs.is_file(&new_path).await {
-                            discriminant += 1;
-                        } else {
-                            break;
-                        }
-                    }
-                    new_path
+                    SavedConversation::unused_path(&summary, fs.as_ref()).await
                 };
 
                 fs.create_dir(CONVERSATIONS_DIR.as_ref()).await?;
@@ -2143,8 +2613,16 @@ struct PendingCompletion {
     _task: Task<()>,
 }
 
+struct SlashCommandInvocation {
+    message_id: MessageId,
+    range: Range<language::Anchor>,
+    text: String,
+    command: SlashCommand,
+}
+
 enum ConversationEditorEvent {
     TabContentChanged,
+    Forked(Model<Conversation>),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -2159,6 +2637,8 @@ struct ConversationEditor {
     workspace: WeakView<Workspace>,
     editor: View<Editor>,
     blocks: HashSet<BlockId>,
+    expanded_contexts: HashSet<(MessageId, usize)>,
+    pending_slash_commands: Option<Task<()>>,
     scroll_position: Option<ScrollPosition>,
     _subscriptions: Vec<Subscription>,
 }
@@ -2200,6 +2680,8 @@ impl ConversationEditor {
             conversation,
             editor,
             blocks: Default::default(),
+            expanded_contexts: Default::default(),
+            pending_slash_commands: None,
             scroll_position: None,
             fs,
             workspace,
@@ -2210,6 +2692,10 @@ impl ConversationEditor {
     }
 
     fn assist(&mut self, _: &Assist, cx: &mut ViewContext<Self>) {
+        if self.run_slash_commands(cx) {
+            return;
+        }
+
         report_assistant_event(
             self.workspace.clone(),
             self.conversation.read(cx).id.clone(),
@@ -2249,6 +2735,84 @@ impl ConversationEditor {
         }
     }
 
+    /// Replaces the slash commands in the conversation with the context they
+    /// expand to, and assists once they've all been expanded. Returns whether
+    /// there were any commands to run.
+    fn run_slash_commands(&mut self, cx: &mut ViewContext<Self>) -> bool {
+        if self.pending_slash_commands.is_some() {
+            return true;
+        }
+        let Some(workspace) = self.workspace.upgrade() else {
+            return false;
+        };
+        let invocations = self.conversation.read(cx).slash_command_invocations(cx);
+        if invocations.is_empty() {
+            return false;
+        }
+
+        let contexts = future::join_all(
+            invocations
+                .iter()
+                .map(|invocation| invocation.command.run(&workspace, cx)),
+        );
+        self.pending_slash_commands = Some(cx.spawn(|this, mut cx| async move {
+            let contexts = contexts.await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_slash_commands = None;
+                let mut errors = Vec::new();
+                this.conversation.update(cx, |conversation, cx| {
+                    for (invocation, context) in invocations.into_iter().zip(contexts) {
+                        match context {
+                            Ok(context) => conversation.attach_context(invocation, context, cx),
+                            Err(error) => errors.push(format!("{}: {error}", invocation.text)),
+                        }
+                    }
+                });
+
+                if errors.is_empty() {
+                    this.assist(&Assist, cx);
+                } else {
+                    workspace.update(cx, |workspace, cx| {
+                        workspace.show_toast(
+                            Toast::new(SLASH_COMMAND_ERROR_TOAST_ID, errors.join("\n")),
+                            cx,
+                        )
+                    });
+                }
+            })
+            .ok();
+        }));
+        true
+    }
+
+    fn toggle_context(&mut self, message_id: MessageId, ix: usize, cx: &mut ViewContext<Self>) {
+        if !self.expanded_contexts.remove(&(message_id, ix)) {
+            self.expanded_contexts.insert((message_id, ix));
+        }
+        self.update_message_headers(cx);
+    }
+
+    fn remove_context(&mut self, message_id: MessageId, ix: usize, cx: &mut ViewContext<Self>) {
+        // Contexts after the removed one shift down, so their expanded state
+        // needs to follow them.
+        self.expanded_contexts = self
+            .expanded_contexts
+            .drain()
+            .filter_map(|(id, expanded_ix)| {
+                if id != message_id || expanded_ix < ix {
+                    Some((id, expanded_ix))
+                } else if expanded_ix > ix {
+                    Some((id, expanded_ix - 1))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.conversation.update(cx, |conversation, cx| {
+            conversation.remove_context(message_id, ix, cx)
+        });
+    }
+
     fn cancel_last_assist(&mut self, _: &editor::actions::Cancel, cx: &mut ViewContext<Self>) {
         if !self
             .conversation
@@ -2270,6 +2834,15 @@ impl ConversationEditor {
         });
     }
 
+    fn fork(&mut self, _: &ForkConversation, cx: &mut ViewContext<Self>) {
+        let cursor = self.editor.read(cx).selections.newest::<usize>(cx).head();
+        self.conversation.update(cx, |conversation, cx| {
+            if let Some(message) = conversation.message_for_offset(cursor, cx) {
+                conversation.fork(message.id, cx);
+            }
+        });
+    }
+
     fn cursors(&self, cx: &AppContext) -> Vec<usize> {
         let selections = self.editor.read(cx).selections.all::<usize>(cx);
         selections
@@ -2297,6 +2870,9 @@ impl ConversationEditor {
                     conversation.save(None, self.fs.clone(), cx);
                 });
             }
+            ConversationEvent::Forked(fork) => {
+                cx.emit(ConversationEditorEvent::Forked(fork.clone()));
+            }
             ConversationEvent::StreamedCompletion => {
                 self.editor.update(cx, |editor, cx| {
                     if let Some(scroll_position) = self.scroll_position {
@@ -2359,16 +2935,19 @@ impl ConversationEditor {
     }
 
     fn update_message_headers(&mut self, cx: &mut ViewContext<Self>) {
+        let this = cx.view().downgrade();
         self.editor.update(cx, |editor, cx| {
             let buffer = editor.buffer().read(cx).snapshot(cx);
             let excerpt_id = *buffer.as_singleton().unwrap().0;
             let old_blocks = std::mem::take(&mut self.blocks);
-            let new_blocks = self
-                .conversation
-                .read(cx)
-                .messages(cx)
-                .map(|message| BlockProperties {
-                    position: buffer.anchor_in_excerpt(excerpt_id, message.anchor),
+            let mut new_blocks = Vec::new();
+            let mut context_block_ix = 0;
+            for message in self.conversation.read(cx).messages(cx) {
+                let position = buffer.anchor_in_excerpt(excerpt_id, message.anchor);
+                let message_id = message.id;
+                let contexts = message.context.clone();
+                new_blocks.push(BlockProperties {
+                    position,
                     height: 2,
                     style: BlockStyle::Sticky,
                     render: Arc::new({
@@ -2421,6 +3000,20 @@ impl ConversationEditor {
                                     .size(LabelSize::XSmall)
                                     .color(Color::Muted),
                                 )
+                                .child(
+                                    IconButton::new("fork", IconName::GitBranch)
+                                        .icon_size(IconSize::XSmall)
+                                        .icon_color(Color::Muted)
+                                        .tooltip(|cx| Tooltip::text("Fork Conversation Here", cx))
+                                        .on_click({
+                                            let conversation = conversation.clone();
+                                            move |_, cx| {
+                                                conversation.update(cx, |conversation, cx| {
+                                                    conversation.fork(message_id, cx)
+                                                })
+                                            }
+                                        }),
+                                )
                                 .children(
                                     if let MessageStatus::Error(error) = message.status.clone() {
                                         Some(
@@ -2437,8 +3030,99 @@ impl ConversationEditor {
                         }
                     }),
                     disposition: BlockDisposition::Above,
-                })
-                .collect::<Vec<_>>();
+                });
+
+                for (ix, context) in contexts.into_iter().enumerate() {
+                    let expanded = self.expanded_contexts.contains(&(message_id, ix));
+                    let line_count = context.text.lines().count();
+                    let block_ix = post_inc(&mut context_block_ix);
+                    new_blocks.push(BlockProperties {
+                        position,
+                        height: if expanded {
+                            (line_count + 1).min(MAX_EXPANDED_CONTEXT_ROWS) as u8
+                        } else {
+                            1
+                        },
+                        style: BlockStyle::Sticky,
+                        render: Arc::new({
+                            let this = this.clone();
+                            move |cx: &mut BlockContext| {
+                                let toggle: Arc<dyn Fn(&ClickEvent, &mut WindowContext)> =
+                                    Arc::new({
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.toggle_context(message_id, ix, cx)
+                                            })
+                                            .ok();
+                                        }
+                                    });
+                                let remove = {
+                                    let this = this.clone();
+                                    move |_: &ClickEvent, cx: &mut WindowContext| {
+                                        this.update(cx, |this, cx| {
+                                            this.remove_context(message_id, ix, cx)
+                                        })
+                                        .ok();
+                                    }
+                                };
+
+                                v_flex()
+                                    .id(("message_context", block_ix))
+                                    .size_full()
+                                    .child(
+                                        h_flex()
+                                            .gap_1()
+                                            .child(
+                                                Disclosure::new(
+                                                    ("message_context_disclosure", block_ix),
+                                                    expanded,
+                                                )
+                                                .on_toggle(toggle),
+                                            )
+                                            .child(
+                                                Label::new(context.command.clone())
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted),
+                                            )
+                                            .child(
+                                                Label::new(if line_count == 1 {
+                                                    "1 line".to_string()
+                                                } else {
+                                                    format!("{line_count} lines")
+                                                })
+                                                .size(LabelSize::XSmall)
+                                                .color(Color::Muted),
+                                            )
+                                            .child(
+                                                IconButton::new(
+                                                    ("remove_message_context", block_ix),
+                                                    IconName::Close,
+                                                )
+                                                .icon_size(IconSize::XSmall)
+                                                .icon_color(Color::Muted)
+                                                .tooltip(|cx| Tooltip::text("Remove Context", cx))
+                                                .on_click(remove),
+                                            ),
+                                    )
+                                    .when(expanded, |this| {
+                                        this.child(
+                                            div()
+                                                .id(("message_context_text", block_ix))
+                                                .flex_1()
+                                                .pl_6()
+                                                .overflow_y_scroll()
+                                                .text_color(cx.theme().colors().text_muted)
+                                                .child(context.text.clone()),
+                                        )
+                                    })
+                                    .into_any_element()
+                            }
+                        }),
+                        disposition: BlockDisposition::Above,
+                    });
+                }
+            }
 
             editor.remove_blocks(old_blocks, None, cx);
             let ids = editor.insert_blocks(new_blocks, None, cx);
@@ -2562,7 +3246,8 @@ impl ConversationEditor {
 
     fn cycle_model(&mut self, cx: &mut ViewContext<Self>) {
         self.conversation.update(cx, |conversation, cx| {
-            let new_model = conversation.model.cycle();
+            let available_models = &AssistantSettings::get_global(cx).available_models;
+            let new_model = conversation.model.cycle(available_models);
             conversation.set_model(new_model, cx);
         });
     }
@@ -2611,6 +3296,7 @@ impl Render for ConversationEditor {
             .capture_action(cx.listener(ConversationEditor::cycle_message_role))
             .on_action(cx.listener(ConversationEditor::assist))
             .on_action(cx.listener(ConversationEditor::split))
+            .on_action(cx.listener(ConversationEditor::fork))
             .size_full()
             .relative()
             .child(
@@ -2653,13 +3339,17 @@ pub struct Message {
     role: Role,
     sent_at: DateTime<Local>,
     status: MessageStatus,
+    context: Vec<MessageContext>,
 }
 
 impl Message {
     fn to_open_ai_message(&self, buffer: &Buffer) -> RequestMessage {
-        let content = buffer
-            .text_for_range(self.offset_range.clone())
-            .collect::<String>();
+        let mut content = String::new();
+        for context in &self.context {
+            content.push_str(context.text.trim_end());
+            content.push_str("\n\n");
+        }
+        content.extend(buffer.text_for_range(self.offset_range.clone()));
         RequestMessage {
             role: self.role,
             content: content.trim_end().into(),
@@ -3594,6 +4284,152 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_forking(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(init);
+        let registry = Arc::new(LanguageRegistry::test());
+        let completion_provider = Arc::new(FakeCompletionProvider::new());
+        let conversation =
+            cx.new_model(|cx| Conversation::new(registry.clone(), cx, completion_provider));
+        let buffer = conversation.read_with(cx, |conversation, _| conversation.buffer.clone());
+        let message_0 =
+            conversation.read_with(cx, |conversation, _| conversation.message_anchors[0].id);
+        let message_1 = conversation.update(cx, |conversation, cx| {
+            conversation
+                .insert_message_after(message_0, Role::Assistant, MessageStatus::Done, cx)
+                .unwrap()
+        });
+        let message_2 = conversation.update(cx, |conversation, cx| {
+            conversation
+                .insert_message_after(message_1.id, Role::User, MessageStatus::Done, cx)
+                .unwrap()
+        });
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "a"), (1..1, "b"), (2..2, "c")], None, cx);
+        });
+        assert_eq!(buffer.read_with(cx, |buffer, _| buffer.text()), "a\nb\nc");
+        assert_eq!(
+            cx.read(|cx| messages(&conversation, cx)),
+            [
+                (message_0, Role::User, 0..2),
+                (message_1.id, Role::Assistant, 2..4),
+                (message_2.id, Role::User, 4..5),
+            ]
+        );
+
+        let (conversation_id, fork) = conversation.read_with(cx, |conversation, cx| {
+            (
+                conversation.id.clone().unwrap(),
+                conversation.serialize_fork(message_1.id, cx).unwrap(),
+            )
+        });
+        assert_ne!(fork.id.as_ref(), Some(&conversation_id));
+        assert_eq!(
+            fork.forked_from,
+            Some(ForkedFrom {
+                conversation_id,
+                message_id: message_1.id,
+            })
+        );
+
+        let forked_conversation =
+            Conversation::deserialize(fork, None, registry.clone(), &mut cx.to_async())
+                .await
+                .unwrap();
+        let forked_buffer =
+            forked_conversation.read_with(cx, |conversation, _| conversation.buffer.clone());
+        assert_eq!(
+            forked_buffer.read_with(cx, |buffer, _| buffer.text()),
+            "a\nb\n"
+        );
+        assert_eq!(
+            cx.read(|cx| messages(&forked_conversation, cx)),
+            [
+                (message_0, Role::User, 0..2),
+                (message_1.id, Role::Assistant, 2..4),
+            ]
+        );
+        assert!(forked_conversation
+            .read_with(cx, |conversation, cx| conversation.serialize(cx))
+            .forked_from
+            .is_some());
+    }
+
+    #[gpui::test]
+    async fn test_slash_command_context(cx: &mut TestAppContext) {
+        let settings_store = cx.update(SettingsStore::test);
+        cx.set_global(settings_store);
+        cx.update(init);
+        let registry = Arc::new(LanguageRegistry::test());
+        let completion_provider = Arc::new(FakeCompletionProvider::new());
+        let conversation =
+            cx.new_model(|cx| Conversation::new(registry.clone(), cx, completion_provider));
+        let buffer = conversation.read_with(cx, |conversation, _| conversation.buffer.clone());
+        let message_0 =
+            conversation.read_with(cx, |conversation, _| conversation.message_anchors[0].id);
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(0..0, "/tab\nWhat does this do?\n/diff")], None, cx);
+        });
+
+        let invocations = conversation.read_with(cx, |conversation, cx| {
+            conversation.slash_command_invocations(cx)
+        });
+        assert_eq!(
+            invocations
+                .iter()
+                .map(|invocation| (invocation.text.as_str(), invocation.command.clone()))
+                .collect::<Vec<_>>(),
+            [("/tab", SlashCommand::Tab), ("/diff", SlashCommand::Diff)]
+        );
+
+        conversation.update(cx, |conversation, cx| {
+            let mut invocations = invocations.into_iter();
+            conversation.attach_context(invocations.next().unwrap(), "fn main() {}".into(), cx);
+            conversation.attach_context(invocations.next().unwrap(), "+ fn main() {}".into(), cx);
+        });
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "What does this do?"
+        );
+        let request_message = conversation.read_with(cx, |conversation, cx| {
+            conversation
+                .messages(cx)
+                .next()
+                .unwrap()
+                .to_open_ai_message(conversation.buffer.read(cx))
+        });
+        assert_eq!(
+            request_message.content,
+            "fn main() {}\n\n+ fn main() {}\n\nWhat does this do?"
+        );
+
+        let deserialized_conversation = Conversation::deserialize(
+            conversation.read_with(cx, |conversation, cx| conversation.serialize(cx)),
+            None,
+            registry.clone(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        deserialized_conversation.read_with(cx, |conversation, _| {
+            assert_eq!(
+                conversation.messages_metadata[&message_0].context,
+                [
+                    MessageContext {
+                        command: "/tab".into(),
+                        text: "fn main() {}".into(),
+                    },
+                    MessageContext {
+                        command: "/diff".into(),
+                        text: "+ fn main() {}".into(),
+                    },
+                ]
+            );
+        });
+    }
+
     fn messages(
         conversation: &Model<Conversation>,
         cx: &AppContext,
@@ -3623,5 +4459,5 @@ fn report_assistant_event(
         .default_open_ai_model
         .clone();
 
-    telemetry.report_assistant_event(conversation_id, assistant_kind, model.full_name())
+    telemetry.report_assistant_event(conversation_id, assistant_kind, model.telemetry_name())
 }
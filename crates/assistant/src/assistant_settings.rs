@@ -1,3 +1,4 @@
+use ai::providers::AiProvider;
 use anyhow;
 use gpui::Pixels;
 use schemars::JsonSchema;
@@ -12,32 +13,79 @@ pub enum OpenAiModel {
     Four,
     #[serde(rename = "gpt-4-1106-preview")]
     FourTurbo,
+    /// A model served by an OpenAI-compatible provider, whose context size
+    /// can't be looked up.
+    #[serde(untagged)]
+    Custom { name: String, max_tokens: usize },
 }
 
 impl OpenAiModel {
-    pub fn full_name(&self) -> &'static str {
+    const BUILT_IN: [OpenAiModel; 3] = [
+        OpenAiModel::ThreePointFiveTurbo,
+        OpenAiModel::Four,
+        OpenAiModel::FourTurbo,
+    ];
+
+    pub fn full_name(&self) -> &str {
         match self {
             OpenAiModel::ThreePointFiveTurbo => "gpt-3.5-turbo-0613",
             OpenAiModel::Four => "gpt-4-0613",
             OpenAiModel::FourTurbo => "gpt-4-1106-preview",
+            OpenAiModel::Custom { name, .. } => name,
         }
     }
 
-    pub fn short_name(&self) -> &'static str {
+    pub fn short_name(&self) -> &str {
         match self {
             OpenAiModel::ThreePointFiveTurbo => "gpt-3.5-turbo",
             OpenAiModel::Four => "gpt-4",
             OpenAiModel::FourTurbo => "gpt-4-turbo",
+            OpenAiModel::Custom { name, .. } => name,
+        }
+    }
+
+    /// The name reported in telemetry, which doesn't include the names of
+    /// custom models.
+    pub fn telemetry_name(&self) -> &'static str {
+        match self {
+            OpenAiModel::ThreePointFiveTurbo => "gpt-3.5-turbo-0613",
+            OpenAiModel::Four => "gpt-4-0613",
+            OpenAiModel::FourTurbo => "gpt-4-1106-preview",
+            OpenAiModel::Custom { .. } => "custom",
+        }
+    }
+
+    /// The model whose tokenizer is used to count tokens. Custom models use
+    /// GPT-4's, which is only an approximation.
+    pub fn tokenizer_name(&self) -> &str {
+        match self {
+            OpenAiModel::Custom { .. } => "gpt-4",
+            _ => self.full_name(),
         }
     }
 
-    pub fn cycle(&self) -> Self {
+    pub fn max_tokens(&self) -> usize {
         match self {
-            OpenAiModel::ThreePointFiveTurbo => OpenAiModel::Four,
-            OpenAiModel::Four => OpenAiModel::FourTurbo,
-            OpenAiModel::FourTurbo => OpenAiModel::ThreePointFiveTurbo,
+            OpenAiModel::Custom { max_tokens, .. } => *max_tokens,
+            _ => tiktoken_rs::model::get_context_size(self.full_name()),
         }
     }
+
+    /// Returns the model after this one in `available_models`, or in OpenAI's
+    /// models if none are configured.
+    pub fn cycle(&self, available_models: &[OpenAiModel]) -> Self {
+        let built_in = Self::BUILT_IN;
+        let models = if available_models.is_empty() {
+            &built_in[..]
+        } else {
+            available_models
+        };
+        let next_ix = models
+            .iter()
+            .position(|model| model == self)
+            .map_or(0, |ix| (ix + 1) % models.len());
+        models[next_ix].clone()
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
@@ -55,6 +103,8 @@ pub struct AssistantSettings {
     pub default_width: Pixels,
     pub default_height: Pixels,
     pub default_open_ai_model: OpenAiModel,
+    pub provider: AiProvider,
+    pub available_models: Vec<OpenAiModel>,
 }
 
 /// Assistant panel settings
@@ -77,9 +127,21 @@ pub struct AssistantSettingsContent {
     /// Default: 320
     pub default_height: Option<f32>,
     /// The default OpenAI model to use when starting new conversations.
+    /// Models served by other providers are given by name and context size,
+    /// e.g. `{ "name": "codellama", "max_tokens": 16384 }`.
     ///
     /// Default: gpt-4-1106-preview
     pub default_open_ai_model: Option<OpenAiModel>,
+    /// The service to request completions from, for both conversations and
+    /// inline assists.
+    ///
+    /// Default: { "name": "openai" }
+    pub provider: Option<AiProvider>,
+    /// The models to cycle through in conversations. OpenAI's models are used
+    /// when this is empty.
+    ///
+    /// Default: []
+    pub available_models: Option<Vec<OpenAiModel>>,
 }
 
 impl Settings for AssistantSettings {
@@ -95,3 +157,51 @@ impl Settings for AssistantSettings {
         Self::load_via_json_merge(default_value, user_values)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_models() {
+        let models: Vec<OpenAiModel> =
+            serde_json::from_str(r#"["gpt-4-0613", { "name": "codellama", "max_tokens": 16384 }]"#)
+                .unwrap();
+        assert_eq!(
+            models,
+            [
+                OpenAiModel::Four,
+                OpenAiModel::Custom {
+                    name: "codellama".into(),
+                    max_tokens: 16384
+                }
+            ]
+        );
+        assert_eq!(models[1].max_tokens(), 16384);
+        assert_eq!(
+            serde_json::to_string(&models).unwrap(),
+            r#"["gpt-4-0613",{"name":"codellama","max_tokens":16384}]"#
+        );
+    }
+
+    #[test]
+    fn test_cycle_models() {
+        assert_eq!(
+            OpenAiModel::FourTurbo.cycle(&[]),
+            OpenAiModel::ThreePointFiveTurbo
+        );
+
+        let codellama = OpenAiModel::Custom {
+            name: "codellama".into(),
+            max_tokens: 16384,
+        };
+        let mistral = OpenAiModel::Custom {
+            name: "mistral".into(),
+            max_tokens: 8192,
+        };
+        let available_models = [codellama.clone(), mistral.clone()];
+        assert_eq!(codellama.cycle(&available_models), mistral);
+        assert_eq!(mistral.cycle(&available_models), codellama);
+        assert_eq!(OpenAiModel::Four.cycle(&available_models), codellama);
+    }
+}
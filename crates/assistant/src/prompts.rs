@@ -1,3 +1,4 @@
+use crate::assistant_settings::OpenAiModel;
 use ai::models::LanguageModel;
 use ai::prompts::base::{PromptArguments, PromptChain, PromptPriority, PromptTemplate};
 use ai::prompts::file_context::FileContext;
@@ -127,11 +128,14 @@ pub fn generate_content_prompt(
     buffer: BufferSnapshot,
     range: Range<usize>,
     search_results: Vec<PromptCodeSnippet>,
-    model: &str,
+    model: &OpenAiModel,
     project_name: Option<String>,
 ) -> anyhow::Result<String> {
     // Using new Prompt Templates
-    let openai_model: Arc<dyn LanguageModel> = Arc::new(OpenAiLanguageModel::load(model));
+    let openai_model: Arc<dyn LanguageModel> = Arc::new(OpenAiLanguageModel::load_with_capacity(
+        model.full_name(),
+        Some(model.max_tokens()),
+    ));
     let lang_name = if let Some(language_name) = language_name {
         Some(language_name.to_string())
     } else {
@@ -0,0 +1,245 @@
+use ai::prompts::repository_context::PromptCodeSnippet;
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use gpui::{AppContext, AsyncAppContext, Model, Task, View};
+use language::{Anchor, Buffer, DiagnosticSeverity, Point};
+use project::{Project, ProjectPath};
+use semantic_index::SemanticIndex;
+use std::{fmt::Write, path::Path};
+use workspace::Workspace;
+
+const SEARCH_RESULT_LIMIT: usize = 10;
+
+/// A command typed on its own line in a user message, which is replaced by
+/// context that's attached to the message when the conversation is sent.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SlashCommand {
+    /// Includes the file at the given path, which is relative to one of the
+    /// project's worktrees.
+    File(String),
+    /// Includes the file that's open in the active editor.
+    Tab,
+    /// Includes the project's errors and warnings.
+    Diagnostics,
+    /// Includes the code that the semantic index finds for the given query.
+    Search(String),
+    /// Includes the uncommitted changes in the project's worktrees.
+    Diff,
+}
+
+impl SlashCommand {
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim().strip_prefix('/')?;
+        let (name, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+        match (name, argument.is_empty()) {
+            ("file", false) => Some(Self::File(argument.into())),
+            ("tab", true) => Some(Self::Tab),
+            ("diagnostics", true) => Some(Self::Diagnostics),
+            ("search", false) => Some(Self::Search(argument.into())),
+            ("diff", true) => Some(Self::Diff),
+            _ => None,
+        }
+    }
+
+    /// Returns the text that the command expands to.
+    pub fn run(&self, workspace: &View<Workspace>, cx: &mut AppContext) -> Task<Result<String>> {
+        let project = workspace.read(cx).project().clone();
+        match self {
+            Self::File(path) => {
+                let Some(project_path) = find_project_path(&project, Path::new(path), cx) else {
+                    return Task::ready(Err(anyhow!("no such file in the project: {path}")));
+                };
+                let buffer =
+                    project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+                cx.spawn(|mut cx| async move { buffer_context(buffer.await?, &mut cx) })
+            }
+            Self::Tab => {
+                let Some(buffer) = workspace
+                    .read(cx)
+                    .active_item(cx)
+                    .and_then(|item| item.act_as::<Editor>(cx))
+                    .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+                else {
+                    return Task::ready(Err(anyhow!("no file is open in the active editor")));
+                };
+                cx.spawn(|mut cx| async move { buffer_context(buffer, &mut cx) })
+            }
+            Self::Diagnostics => diagnostics_context(project, cx),
+            Self::Search(query) => {
+                let Some(semantic_index) = SemanticIndex::global(cx) else {
+                    return Task::ready(Err(anyhow!("semantic index is disabled")));
+                };
+                let search_results = semantic_index.update(cx, |index, cx| {
+                    index.search_project(
+                        project,
+                        query.clone(),
+                        SEARCH_RESULT_LIMIT,
+                        Vec::new(),
+                        Vec::new(),
+                        cx,
+                    )
+                });
+                cx.spawn(|mut cx| async move {
+                    let mut context = String::new();
+                    for result in search_results.await? {
+                        let snippet = PromptCodeSnippet::new(result.buffer, result.range, &mut cx)?;
+                        writeln!(context, "{}", snippet.to_string()).unwrap();
+                    }
+                    Ok(context)
+                })
+            }
+            Self::Diff => diff_context(project, cx),
+        }
+    }
+}
+
+/// Resolves a path that's either relative to a worktree's root, or starts with
+/// the worktree's root name.
+fn find_project_path(
+    project: &Model<Project>,
+    path: &Path,
+    cx: &AppContext,
+) -> Option<ProjectPath> {
+    project.read(cx).visible_worktrees(cx).find_map(|worktree| {
+        let worktree = worktree.read(cx);
+        let path_in_worktree = path.strip_prefix(worktree.root_name()).ok();
+        [Some(path), path_in_worktree]
+            .into_iter()
+            .flatten()
+            .find(|path| {
+                worktree
+                    .entry_for_path(path)
+                    .map_or(false, |entry| entry.is_file())
+            })
+            .map(|path| ProjectPath {
+                worktree_id: worktree.id(),
+                path: path.into(),
+            })
+    })
+}
+
+fn buffer_context(buffer: Model<Buffer>, cx: &mut AsyncAppContext) -> Result<String> {
+    let snippet = PromptCodeSnippet::new(buffer, Anchor::MIN..Anchor::MAX, cx)?;
+    Ok(snippet.to_string())
+}
+
+fn diagnostics_context(project: Model<Project>, cx: &mut AppContext) -> Task<Result<String>> {
+    let mut paths = Vec::new();
+    for (path, _, summary) in project.read(cx).diagnostic_summaries(false, cx) {
+        if (summary.error_count > 0 || summary.warning_count > 0) && !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    let buffers = paths
+        .into_iter()
+        .map(|path| project.update(cx, |project, cx| project.open_buffer(path, cx)))
+        .collect::<Vec<_>>();
+
+    cx.spawn(|cx| async move {
+        let mut diagnostics = String::new();
+        for buffer in buffers {
+            let buffer = buffer.await?;
+            buffer.read_with(&cx, |buffer, _| {
+                let path = buffer
+                    .file()
+                    .map(|file| file.path().to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let snapshot = buffer.snapshot();
+                for entry in snapshot.diagnostics_in_range::<_, Point>(0..snapshot.len(), false) {
+                    let severity = match entry.diagnostic.severity {
+                        DiagnosticSeverity::ERROR => "error",
+                        DiagnosticSeverity::WARNING => "warning",
+                        _ => continue,
+                    };
+                    if entry.diagnostic.is_primary {
+                        writeln!(
+                            diagnostics,
+                            "{path}:{}:{}: {severity}: {}",
+                            entry.range.start.row + 1,
+                            entry.range.start.column + 1,
+                            entry.diagnostic.message
+                        )
+                        .unwrap();
+                    }
+                }
+            })?;
+        }
+
+        if diagnostics.is_empty() {
+            Ok("The project has no errors or warnings.".into())
+        } else {
+            Ok(format!(
+                "The project has the following errors and warnings:\n```\n{diagnostics}```"
+            ))
+        }
+    })
+}
+
+fn diff_context(project: Model<Project>, cx: &mut AppContext) -> Task<Result<String>> {
+    if project.read(cx).is_remote() {
+        return Task::ready(Err(anyhow!("diffs can only be included in local projects")));
+    }
+
+    // Worktrees that aren't in a repository have no changes to include.
+    let mut repositories = Vec::new();
+    for worktree in project.read(cx).visible_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        let Some(local_worktree) = worktree.as_local() else {
+            continue;
+        };
+        for (work_directory, _) in worktree.repositories() {
+            if let Some((_, local_repo)) = local_worktree.local_repo_for_path(work_directory) {
+                repositories.push(local_repo.repo().clone());
+            }
+        }
+    }
+
+    cx.background_executor().spawn(async move {
+        let mut diffs = String::new();
+        for repository in repositories {
+            let diff = repository.lock().diff_head()?;
+            if !diff.trim().is_empty() {
+                write!(diffs, "```diff\n{diff}```\n").unwrap();
+            }
+        }
+
+        if diffs.is_empty() {
+            Ok("The project has no uncommitted changes.".into())
+        } else {
+            Ok(format!(
+                "The project has the following uncommitted changes:\n{diffs}"
+            ))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing() {
+        assert_eq!(
+            SlashCommand::parse("/file src/main.rs"),
+            Some(SlashCommand::File("src/main.rs".into()))
+        );
+        assert_eq!(SlashCommand::parse("  /tab  "), Some(SlashCommand::Tab));
+        assert_eq!(
+            SlashCommand::parse("/diagnostics"),
+            Some(SlashCommand::Diagnostics)
+        );
+        assert_eq!(
+            SlashCommand::parse("/search where are settings loaded"),
+            Some(SlashCommand::Search("where are settings loaded".into()))
+        );
+        assert_eq!(SlashCommand::parse("/diff"), Some(SlashCommand::Diff));
+
+        // Commands that are missing an argument, or take none, are left as text.
+        assert_eq!(SlashCommand::parse("/file"), None);
+        assert_eq!(SlashCommand::parse("/search "), None);
+        assert_eq!(SlashCommand::parse("/tab main.rs"), None);
+        assert_eq!(SlashCommand::parse("/unknown"), None);
+        assert_eq!(SlashCommand::parse("and/or"), None);
+    }
+}
@@ -253,6 +253,7 @@ struct ClientState {
     entity_id_extractors: HashMap<TypeId, fn(&dyn AnyTypedEnvelope) -> u64>,
     _reconnect_task: Option<Task<()>>,
     reconnect_interval: Duration,
+    connected_directly: bool,
     entities_by_type_and_remote_id: HashMap<(TypeId, u64), WeakSubscriber>,
     models_by_message_type: HashMap<TypeId, AnyWeakModel>,
     entity_types_by_message_type: HashMap<TypeId, TypeId>,
@@ -291,6 +292,7 @@ impl Default for ClientState {
             entity_id_extractors: Default::default(),
             _reconnect_task: None,
             reconnect_interval: Duration::from_secs(5),
+            connected_directly: false,
             models_by_message_type: Default::default(),
             entities_by_type_and_remote_id: Default::default(),
             entity_types_by_message_type: Default::default(),
@@ -523,6 +525,10 @@ impl Client {
             Status::Connected { .. } => {
                 state._reconnect_task = None;
             }
+            // Direct connections can't be re-established through the server.
+            Status::ConnectionLost if state.connected_directly => {
+                state._reconnect_task.take();
+            }
             Status::ConnectionLost => {
                 let this = self.clone();
                 let reconnect_interval = state.reconnect_interval;
@@ -749,6 +755,10 @@ impl Client {
         try_keychain: bool,
         cx: &AsyncAppContext,
     ) -> anyhow::Result<()> {
+        if self.state.read().connected_directly && !self.status().borrow().is_connected() {
+            return Err(anyhow!("the direct connection to the host was closed"));
+        }
+
         let was_disconnected = match *self.status().borrow() {
             Status::SignedOut => true,
             Status::ConnectionError
@@ -848,6 +858,23 @@ impl Client {
         }
     }
 
+    /// Connects over an already established connection rather than to the collab server,
+    /// for example to a headless host on the other end of a child process's stdio. The
+    /// client won't try to reconnect if this connection is lost.
+    pub async fn connect_with(
+        self: &Arc<Self>,
+        connection: Connection,
+        cx: &AsyncAppContext,
+    ) -> Result<()> {
+        self.state.write().connected_directly = true;
+        self.set_status(Status::Connecting, cx);
+        let result = self.set_connection(connection, cx).await;
+        if result.is_err() {
+            self.set_status(Status::ConnectionError, cx);
+        }
+        result
+    }
+
     async fn set_connection(
         self: &Arc<Self>,
         conn: Connection,
@@ -113,6 +113,12 @@ CREATE TABLE "worktree_settings_files" (
 CREATE INDEX "index_worktree_settings_files_on_project_id" ON "worktree_settings_files" ("project_id");
 CREATE INDEX "index_worktree_settings_files_on_project_id_and_worktree_id" ON "worktree_settings_files" ("project_id", "worktree_id");
 
+CREATE TABLE "writable_terminals" (
+    "project_id" INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    "terminal_id" INTEGER NOT NULL,
+    PRIMARY KEY(project_id, terminal_id)
+);
+
 CREATE TABLE "worktree_diagnostic_summaries" (
     "project_id" INTEGER NOT NULL,
     "worktree_id" INTEGER NOT NULL,
@@ -130,6 +136,7 @@ CREATE TABLE "language_servers" (
     "id" INTEGER NOT NULL,
     "project_id" INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
     "name" VARCHAR NOT NULL,
+    "signature_help_trigger_characters" VARCHAR,
     PRIMARY KEY(project_id, id)
 );
 CREATE INDEX "index_language_servers_on_project_id" ON "language_servers" ("project_id");
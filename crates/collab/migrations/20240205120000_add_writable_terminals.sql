@@ -0,0 +1,5 @@
+CREATE TABLE "writable_terminals" (
+    "project_id" INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    "terminal_id" INTEGER NOT NULL,
+    PRIMARY KEY(project_id, terminal_id)
+);
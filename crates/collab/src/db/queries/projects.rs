@@ -434,14 +434,19 @@ impl Database {
                 project_id: ActiveValue::set(project_id),
                 id: ActiveValue::set(server.id as i64),
                 name: ActiveValue::set(server.name.clone()),
-                ..Default::default()
+                signature_help_trigger_characters: ActiveValue::set(Some(serde_json::to_string(
+                    &server.signature_help_trigger_characters,
+                )?)),
             })
             .on_conflict(
                 OnConflict::columns([
                     language_server::Column::ProjectId,
                     language_server::Column::Id,
                 ])
-                .update_column(language_server::Column::Name)
+                .update_columns([
+                    language_server::Column::Name,
+                    language_server::Column::SignatureHelpTriggerCharacters,
+                ])
                 .to_owned(),
             )
             .exec(&*tx)
@@ -506,6 +511,56 @@ impl Database {
         .await
     }
 
+    /// Records whether guests may type into one of the host's shared terminals.
+    pub async fn set_terminal_writable(
+        &self,
+        update: &proto::SetTerminalWritable,
+        connection: ConnectionId,
+    ) -> Result<RoomGuard<Vec<ConnectionId>>> {
+        let project_id = ProjectId::from_proto(update.project_id);
+        let room_id = self.room_id_for_project(project_id).await?;
+        self.room_transaction(room_id, |tx| async move {
+            // Ensure the update comes from the host.
+            let project = project::Entity::find_by_id(project_id)
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("no such project"))?;
+            if project.host_connection()? != connection {
+                return Err(anyhow!("can't update a project hosted by someone else"))?;
+            }
+
+            if update.writable {
+                writable_terminal::Entity::insert(writable_terminal::ActiveModel {
+                    project_id: ActiveValue::Set(project_id),
+                    terminal_id: ActiveValue::Set(update.terminal_id as i64),
+                })
+                .on_conflict(
+                    OnConflict::columns([
+                        writable_terminal::Column::ProjectId,
+                        writable_terminal::Column::TerminalId,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .exec_without_returning(&*tx)
+                .await?;
+            } else {
+                writable_terminal::Entity::delete_many()
+                    .filter(
+                        writable_terminal::Column::ProjectId.eq(project_id).and(
+                            writable_terminal::Column::TerminalId.eq(update.terminal_id as i64),
+                        ),
+                    )
+                    .exec(&*tx)
+                    .await?;
+            }
+
+            let connection_ids = self.project_guest_connection_ids(project_id, &tx).await?;
+            Ok(connection_ids)
+        })
+        .await
+    }
+
     /// Adds the given connection to the specified project.
     pub async fn join_project(
         &self,
@@ -701,10 +756,7 @@ impl Database {
                 worktrees,
                 language_servers: language_servers
                     .into_iter()
-                    .map(|language_server| proto::LanguageServer {
-                        id: language_server.id as u64,
-                        name: language_server.name,
-                    })
+                    .map(language_server::Model::to_proto)
                     .collect(),
             };
             Ok((project, replica_id as ReplicaId))
@@ -890,6 +942,27 @@ impl Database {
         .map(|guard| guard.into_inner())
     }
 
+    /// Returns the host connection for a request to type into one of the
+    /// host's shared terminals, which the host must have made writable.
+    pub async fn host_for_terminal_input(
+        &self,
+        project_id: ProjectId,
+        terminal_id: u64,
+        connection_id: ConnectionId,
+    ) -> Result<ConnectionId> {
+        let host_connection_id = self
+            .host_for_mutating_project_request(project_id, connection_id)
+            .await?;
+        self.transaction(|tx| async move {
+            writable_terminal::Entity::find_by_id((project_id, terminal_id as i64))
+                .one(&*tx)
+                .await?
+                .ok_or_else(|| anyhow!("terminal is read-only"))?;
+            Ok(host_connection_id)
+        })
+        .await
+    }
+
     pub async fn project_collaborators_for_buffer_update(
         &self,
         project_id: ProjectId,
@@ -31,3 +31,4 @@ pub mod worktree_entry;
 pub mod worktree_repository;
 pub mod worktree_repository_statuses;
 pub mod worktree_settings_file;
+pub mod writable_terminal;
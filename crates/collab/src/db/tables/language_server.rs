@@ -1,4 +1,5 @@
 use crate::db::ProjectId;
+use rpc::proto;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
@@ -9,6 +10,8 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
     pub name: String,
+    /// A JSON array of the characters that trigger signature help.
+    pub signature_help_trigger_characters: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -28,3 +31,16 @@ impl Related<super::project::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn to_proto(self) -> proto::LanguageServer {
+        proto::LanguageServer {
+            id: self.id as u64,
+            name: self.name,
+            signature_help_trigger_characters: self
+                .signature_help_trigger_characters
+                .and_then(|characters| serde_json::from_str(&characters).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{db::ChannelId, tests::TestServer};
 use call::ActiveCall;
 use editor::Editor;
-use gpui::{BackgroundExecutor, TestAppContext};
+use futures::channel::mpsc;
+use gpui::{BackgroundExecutor, Context, TestAppContext};
+use project::terminals::SHARED_TERMINAL_UPDATE_INTERVAL;
 use rpc::proto;
+use terminal::{ScreenSnapshot, TerminalBuilder};
 
 #[gpui::test]
 async fn test_channel_guests(
@@ -160,6 +163,166 @@ async fn test_channel_guest_promotion(cx_a: &mut TestAppContext, cx_b: &mut Test
         .is_err());
 }
 
+#[gpui::test]
+async fn test_channel_guest_shared_terminal(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let mut server = TestServer::start(cx_a.executor()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+
+    let project_a = client_a.build_test_project(cx_a).await;
+    cx_a.update(|cx| workspace::join_channel(channel_id, client_a.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+
+    // Client A shares a terminal, whose input goes to a fake shell.
+    let (input_tx, mut input_rx) = mpsc::unbounded();
+    let terminal_a =
+        cx_a.new_model(|cx| TerminalBuilder::new_remote("zsh".into(), input_tx).subscribe(cx));
+    terminal_a.update(cx_a, |terminal, cx| {
+        terminal.set_remote_screen(
+            "zsh".into(),
+            &ScreenSnapshot {
+                columns: 20,
+                screen_lines: 3,
+                text: "$ cargo test".into(),
+            },
+            cx,
+        )
+    });
+    let terminal_id = project_a
+        .update(cx_a, |project, cx| project.share_terminal(&terminal_a, cx))
+        .unwrap();
+    cx_a.run_until_parked();
+
+    // Client B joins channel A as a guest, and sees the terminal's screen.
+    cx_b.update(|cx| workspace::join_channel(channel_id, client_b.app_state.clone(), None, cx))
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+
+    let active_call_b = cx_b.read(ActiveCall::global);
+    let project_b =
+        active_call_b.read_with(cx_b, |call, _| call.location().unwrap().upgrade().unwrap());
+    assert!(project_b.read_with(cx_b, |project, _| project.is_read_only()));
+    assert_eq!(
+        project_b.read_with(cx_b, |project, _| project
+            .remote_terminal_ids()
+            .collect::<Vec<_>>()),
+        [terminal_id]
+    );
+    let terminal_b = project_b
+        .update(cx_b, |project, cx| {
+            project.open_shared_terminal(terminal_id, cx)
+        })
+        .unwrap();
+    let screen_a = terminal_a.read_with(cx_a, |terminal, _| terminal.screen_snapshot());
+    terminal_b.read_with(cx_b, |terminal, _| {
+        assert_eq!(terminal.title(false), "zsh");
+        assert_eq!(terminal.screen_snapshot(), screen_a);
+    });
+
+    // As a guest, B can't type into the terminal.
+    terminal_b.update(cx_b, |terminal, _| terminal.input("ls\r".into()));
+    cx_a.run_until_parked();
+    assert!(input_rx.try_next().is_err());
+
+    // The terminal's output is streamed to B.
+    terminal_a.update(cx_a, |terminal, cx| {
+        terminal.set_remote_screen(
+            "cargo".into(),
+            &ScreenSnapshot {
+                columns: 20,
+                screen_lines: 3,
+                text: "$ cargo test\r\nrunning 1 test".into(),
+            },
+            cx,
+        )
+    });
+    cx_a.executor()
+        .advance_clock(SHARED_TERMINAL_UPDATE_INTERVAL);
+    cx_a.run_until_parked();
+    let screen_a = terminal_a.read_with(cx_a, |terminal, _| terminal.screen_snapshot());
+    terminal_b.read_with(cx_b, |terminal, _| {
+        assert_eq!(terminal.title(false), "cargo");
+        assert_eq!(terminal.screen_snapshot(), screen_a);
+    });
+
+    // B is promoted, but can't type into the terminal until A allows it.
+    active_call_a
+        .update(cx_a, |call, cx| {
+            call.room().unwrap().update(cx, |room, cx| {
+                room.set_participant_role(
+                    client_b.user_id().unwrap(),
+                    proto::ChannelRole::Member,
+                    cx,
+                )
+            })
+        })
+        .await
+        .unwrap();
+    cx_a.run_until_parked();
+    terminal_b.update(cx_b, |terminal, _| terminal.input("ls\r".into()));
+    cx_a.run_until_parked();
+    assert!(input_rx.try_next().is_err());
+
+    // Input sent for a terminal that isn't writable is rejected.
+    project_b
+        .update(cx_b, |project, _| {
+            project.client().request(proto::TerminalInput {
+                project_id: project.remote_id().unwrap(),
+                terminal_id,
+                input: b"ls\r".to_vec(),
+            })
+        })
+        .await
+        .unwrap_err();
+    cx_a.run_until_parked();
+    assert!(input_rx.try_next().is_err());
+
+    project_a
+        .update(cx_a, |project, cx| {
+            project.set_terminal_writable(terminal_id, true, cx)
+        })
+        .unwrap();
+    cx_a.run_until_parked();
+    assert!(project_b.read_with(cx_b, |project, _| project.is_terminal_writable(terminal_id)));
+    terminal_b.update(cx_b, |terminal, _| terminal.input("ls\r".into()));
+    cx_a.run_until_parked();
+    assert_eq!(input_rx.try_next().unwrap().unwrap(), b"ls\r");
+
+    // When A stops sharing the terminal, B's copy is closed.
+    let closed = cx_b.new_model(|_| false);
+    cx_b.update(|cx| {
+        let closed = closed.clone();
+        cx.subscribe(&terminal_b, move |_, event, cx| {
+            if let terminal::Event::CloseTerminal = event {
+                closed.update(cx, |closed, _| *closed = true);
+            }
+        })
+        .detach();
+    });
+    project_a
+        .update(cx_a, |project, cx| {
+            project.unshare_terminal(terminal_id, cx)
+        })
+        .unwrap();
+    cx_a.run_until_parked();
+    assert!(closed.read_with(cx_b, |closed, _| *closed));
+    assert_eq!(
+        project_b.read_with(cx_b, |project, _| project.remote_terminal_ids().count()),
+        0
+    );
+}
+
 #[gpui::test]
 async fn test_channel_requires_zed_cla(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
     let mut server = TestServer::start(cx_a.executor()).await;
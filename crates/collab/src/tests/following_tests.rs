@@ -5,6 +5,7 @@ use collab_ui::{
     notifications::project_shared_notification::ProjectSharedNotification,
 };
 use editor::{Editor, ExcerptRange, MultiBuffer};
+use futures::channel::mpsc;
 use gpui::{
     point, BackgroundExecutor, Context, Entity, SharedString, TestAppContext, View, VisualContext,
     VisualTestContext,
@@ -15,6 +16,8 @@ use project::project_settings::ProjectSettings;
 use rpc::proto::PeerId;
 use serde_json::json;
 use settings::SettingsStore;
+use terminal::{ScreenSnapshot, TerminalBuilder};
+use terminal_view::TerminalView;
 use workspace::{
     dock::{test::TestPanel, DockPosition},
     item::{test::TestItem, ItemHandle as _},
@@ -1996,3 +1999,87 @@ async fn test_following_to_channel_notes_without_a_shared_project(
         );
     });
 }
+
+#[gpui::test]
+async fn test_following_shared_terminal(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    let (_server, client_a, client_b, channel_id) = TestServer::start2(cx_a, cx_b).await;
+    cx_a.update(terminal_view::init);
+    cx_b.update(terminal_view::init);
+
+    let (workspace_a, cx_a) = client_a.build_test_workspace(cx_a).await;
+    client_a
+        .host_workspace(&workspace_a, channel_id, cx_a)
+        .await;
+
+    // Client A shares a terminal, whose input goes to a fake shell, and
+    // opens it.
+    let (input_tx, mut input_rx) = mpsc::unbounded();
+    let terminal_a =
+        cx_a.new_model(|cx| TerminalBuilder::new_remote("zsh".into(), input_tx).subscribe(cx));
+    terminal_a.update(cx_a, |terminal, cx| {
+        terminal.set_remote_screen(
+            "zsh".into(),
+            &ScreenSnapshot {
+                columns: 20,
+                screen_lines: 3,
+                text: "$ cargo test".into(),
+            },
+            cx,
+        )
+    });
+    let project_a = workspace_a.update(cx_a, |workspace, _| workspace.project().clone());
+    let terminal_id = project_a
+        .update(cx_a, |project, cx| project.share_terminal(&terminal_a, cx))
+        .unwrap();
+    workspace_a.update(cx_a, |workspace, cx| {
+        let view = cx.new_view(|cx| {
+            TerminalView::new(
+                terminal_a.clone(),
+                workspace.weak_handle(),
+                workspace.database_id(),
+                cx,
+            )
+        });
+        workspace.add_item(Box::new(view), cx);
+    });
+    cx_a.run_until_parked();
+
+    // Client B joins and follows A into the terminal.
+    let (workspace_b, cx_b) = client_b.join_workspace(channel_id, cx_b).await;
+    cx_a.run_until_parked();
+    let terminal_b = workspace_b.update(cx_b, |workspace, cx| {
+        workspace
+            .active_item_as::<TerminalView>(cx)
+            .unwrap()
+            .read(cx)
+            .terminal()
+            .clone()
+    });
+    let screen_a = terminal_a.read_with(cx_a, |terminal, _| terminal.screen_snapshot());
+    terminal_b.read_with(cx_b, |terminal, _| {
+        assert!(terminal.is_remote());
+        assert_eq!(terminal.title(false), "zsh");
+        assert_eq!(terminal.screen_snapshot(), screen_a);
+    });
+    let project_b = workspace_b.update(cx_b, |workspace, _| workspace.project().clone());
+    assert_eq!(
+        project_b.read_with(cx_b, |project, _| project.shared_terminal_id(&terminal_b)),
+        Some(terminal_id)
+    );
+
+    // B is a member of the channel, so they can type into the terminal.
+    terminal_b.update(cx_b, |terminal, _| terminal.input("ls\r".into()));
+    cx_a.run_until_parked();
+    assert_eq!(input_rx.try_next().unwrap().unwrap(), b"ls\r");
+
+    // When A stops sharing the terminal, B's terminal is closed.
+    project_a
+        .update(cx_a, |project, cx| {
+            project.unshare_terminal(terminal_id, cx)
+        })
+        .unwrap();
+    cx_a.run_until_parked();
+    workspace_b.update(cx_b, |workspace, cx| {
+        assert!(workspace.active_item_as::<TerminalView>(cx).is_none());
+    });
+}
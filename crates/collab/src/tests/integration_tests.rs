@@ -5,7 +5,10 @@ use crate::{
 use call::{room, ActiveCall, ParticipantLocation, Room};
 use client::{User, RECEIVE_TIMEOUT};
 use collections::{HashMap, HashSet};
-use fs::{repository::GitFileStatus, FakeFs, Fs as _, RemoveOptions};
+use fs::{
+    repository::{Blame, BlameEntry, GitFileStatus},
+    FakeFs, Fs as _, RemoveOptions,
+};
 use futures::StreamExt as _;
 use gpui::{
     px, size, AppContext, BackgroundExecutor, Model, Modifiers, MouseButton, MouseDownEvent,
@@ -19,7 +22,8 @@ use language::{
 use live_kit_client::MacOSDisplay;
 use lsp::LanguageServerId;
 use project::{
-    search::SearchQuery, DiagnosticSummary, FormatTrigger, HoverBlockKind, Project, ProjectPath,
+    search::SearchQuery, DiagnosticSummary, FormatTrigger, HierarchyKind, HoverBlockKind, Project,
+    ProjectPath,
 };
 use rand::prelude::*;
 use rpc::proto::ChannelRole;
@@ -2700,6 +2704,192 @@ async fn test_git_diff_base_change(
     });
 }
 
+#[gpui::test]
+async fn test_git_stage_and_unstage_hunks(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    let head_text = "
+        one
+        two
+        three
+        four
+    "
+    .unindent();
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "
+                    one
+                    TWO
+                    three
+                    FOUR
+                ".unindent(),
+            }),
+        )
+        .await;
+    client_a.fs().set_head_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), head_text.clone())],
+    );
+    client_a.fs().set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), head_text.clone())],
+    );
+
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    let buffer_a = project_a
+        .update(cx_a, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    // The guest stages the first hunk in the host's repository.
+    let staged_text = buffer_b.read_with(cx_b, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let hunks = snapshot.git_diff_hunks_in_row_range(1..2);
+        git::diff::apply_hunks_to_diff_base(buffer.diff_base().unwrap(), &snapshot, hunks)
+    });
+    assert_eq!(staged_text, "one\nTWO\nthree\nfour\n");
+    project_b
+        .update(cx_b, |project, cx| {
+            project.set_index_text(&buffer_b, Some(staged_text.clone()), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            assert_eq!(state.index_contents[Path::new("a.txt")], staged_text);
+        });
+    buffer_a.read_with(cx_a, |buffer, _| {
+        assert_eq!(buffer.diff_base(), Some(staged_text.as_ref()));
+    });
+    buffer_b.read_with(cx_b, |buffer, _| {
+        assert_eq!(buffer.diff_base(), Some(staged_text.as_ref()));
+    });
+
+    // The guest unstages it again using the host's HEAD.
+    let loaded_head_text = project_b
+        .update(cx_b, |project, cx| project.load_head_text(&buffer_b, cx))
+        .await
+        .unwrap();
+    assert_eq!(loaded_head_text.as_ref(), Some(&head_text));
+    let unstaged_text = git::diff::revert_changes_in_rows(&head_text, &staged_text, &[1..2]);
+    assert_eq!(unstaged_text, head_text);
+    project_b
+        .update(cx_b, |project, cx| {
+            project.set_index_text(&buffer_b, Some(unstaged_text), cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            assert_eq!(state.index_contents[Path::new("a.txt")], head_text);
+        });
+    buffer_b.read_with(cx_b, |buffer, _| {
+        assert_eq!(buffer.diff_base(), Some(head_text.as_ref()));
+    });
+}
+
+#[gpui::test]
+async fn test_git_blame(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "one\ntwo\nthree\n",
+            }),
+        )
+        .await;
+    let blame = Blame {
+        entries: vec![BlameEntry {
+            sha: "3a3a3a".to_string(),
+            range: 0..3,
+            author: Some("user_a".to_string()),
+            author_mail: Some("user_a@example.com".to_string()),
+            author_time: Some(1_700_000_000),
+            author_offset_minutes: Some(0),
+            summary: Some("Add a.txt".to_string()),
+        }],
+        messages: [(
+            "3a3a3a".to_string(),
+            "Add a.txt\n\nMore details".to_string(),
+        )]
+        .into_iter()
+        .collect(),
+        remote_url: Some("git@github.com:zed-industries/zed.git".to_string()),
+    };
+    client_a.fs().set_head_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), "one\ntwo\nthree\n".to_string())],
+    );
+    client_a.fs().set_blame_for_repo(
+        Path::new("/dir/.git"),
+        vec![(Path::new("a.txt"), blame.clone())],
+    );
+
+    let (project_a, worktree_id) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // The guest's blame is computed by the host.
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| p.open_buffer((worktree_id, "a.txt"), cx))
+        .await
+        .unwrap();
+    let blame_b = project_b
+        .update(cx_b, |project, cx| project.blame_buffer(&buffer_b, cx))
+        .await
+        .unwrap();
+    assert_eq!(blame_b, blame);
+}
+
 #[gpui::test]
 async fn test_git_branch_name(
     executor: BackgroundExecutor,
@@ -3238,6 +3428,7 @@ async fn test_local_settings(
                     "a.txt": "a-contents",
                 },
                 "b": {
+                    ".editorconfig": "[*]\nindent_size = 3\n",
                     "b.txt": "b-contents",
                 }
             }),
@@ -3266,7 +3457,13 @@ async fn test_local_settings(
                 (Path::new("").into(), r#"{"tab_size":2}"#.to_string()),
                 (Path::new("a").into(), r#"{"tab_size":8}"#.to_string()),
             ]
-        )
+        );
+        assert_eq!(
+            store
+                .editorconfigs(worktree_b.read(cx).id().to_usize())
+                .collect::<Vec<_>>(),
+            &[(Path::new("b").into(), "[*]\nindent_size = 3\n")]
+        );
     });
 
     // As client A, update a settings file. As Client B, see the changed settings.
@@ -4375,6 +4572,48 @@ async fn test_formatting_buffer(
         buffer_b.read_with(cx_b, |buffer, _| buffer.text()),
         format!("let honey = \"{}/a.rs\"\n", directory.to_str().unwrap())
     );
+
+    // Ensure a list of formatters runs in order on the host, and that the guest
+    // can undo all of their edits at once.
+    cx_a.update(|cx| {
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<AllLanguageSettings>(cx, |file| {
+                file.defaults.formatter = Some(Formatter::List(
+                    vec![
+                        Formatter::External {
+                            command: "sed".into(),
+                            arguments: vec!["-e".to_string(), "s/honey/money/".to_string()].into(),
+                        },
+                        Formatter::External {
+                            command: "sed".into(),
+                            arguments: vec!["-e".to_string(), "s/money/monkey/".to_string()].into(),
+                        },
+                    ]
+                    .into(),
+                ));
+            });
+        });
+    });
+    project_b
+        .update(cx_b, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer_b.clone()]),
+                true,
+                FormatTrigger::Save,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer_b.read_with(cx_b, |buffer, _| buffer.text()),
+        format!("let monkey = \"{}/a.rs\"\n", directory.to_str().unwrap())
+    );
+    buffer_b.update(cx_b, |buffer, cx| buffer.undo(cx));
+    assert_eq!(
+        buffer_b.read_with(cx_b, |buffer, _| buffer.text()),
+        format!("let honey = \"{}/a.rs\"\n", directory.to_str().unwrap())
+    );
 }
 
 #[gpui::test(iterations = 10)]
@@ -4443,7 +4682,7 @@ async fn test_prettier_formatting_buffer(
     cx_b.update(|cx| {
         cx.update_global(|store: &mut SettingsStore, cx| {
             store.update_user_settings::<AllLanguageSettings>(cx, |file| {
-                file.defaults.formatter = Some(Formatter::LanguageServer);
+                file.defaults.formatter = Some(Formatter::LanguageServer { name: None });
             });
         });
     });
@@ -5033,6 +5272,274 @@ async fn test_lsp_hover(
     });
 }
 
+#[gpui::test(iterations = 10)]
+async fn test_lsp_signature_help(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "fn main() { add(1, 2) }",
+            }),
+        )
+        .await;
+
+    // Set up a fake language server.
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            path_suffixes: vec!["rs".to_string()],
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_language_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), "<".to_string()]),
+                    retrigger_characters: Some(vec![",".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+    let language = Arc::new(language);
+    client_a.language_registry().add(Arc::clone(&language));
+    client_b.language_registry().add(language);
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // Open the file as the guest
+    let open_buffer = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "main.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_buffer).await.unwrap();
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    executor.run_until_parked();
+
+    // The guest triggers signature help with the host's server's characters.
+    assert_eq!(
+        project_b.read_with(cx_b, |project, cx| {
+            project.signature_help_trigger_characters(&buffer_b, cx)
+        }),
+        ["(", "<", ","]
+    );
+
+    // Request signature help as the guest.
+    fake_language_server.handle_request::<lsp::request::SignatureHelpRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 19)
+            );
+            let context = params.context.unwrap();
+            assert_eq!(context.trigger_character.as_deref(), Some(","));
+            Ok(Some(lsp::SignatureHelp {
+                signatures: vec![lsp::SignatureInformation {
+                    label: "fn add(a: i32, b: i32) -> i32".to_string(),
+                    documentation: Some(lsp::Documentation::String(
+                        "Adds two numbers.".to_string(),
+                    )),
+                    parameters: Some(vec![
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::LabelOffsets([7, 13]),
+                            documentation: None,
+                        },
+                        lsp::ParameterInformation {
+                            label: lsp::ParameterLabel::LabelOffsets([15, 21]),
+                            documentation: None,
+                        },
+                    ]),
+                    active_parameter: None,
+                }],
+                active_signature: Some(0),
+                active_parameter: Some(1),
+            }))
+        },
+    );
+
+    let signature_help = project_b
+        .update(cx_b, |p, cx| {
+            p.signature_help(&buffer_b, 19, Some(",".to_string()), false, cx)
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(signature_help.active_signature, 0);
+    assert_eq!(signature_help.signatures.len(), 1);
+    let signature = &signature_help.signatures[0];
+    assert_eq!(signature.label, "fn add(a: i32, b: i32) -> i32");
+    assert_eq!(
+        signature
+            .documentation
+            .as_ref()
+            .map(|doc| doc.value.as_str()),
+        Some("Adds two numbers.")
+    );
+    assert_eq!(
+        signature_help
+            .active_parameter_for(0)
+            .map(|parameter| parameter.label_range.clone()),
+        Some(15..21)
+    );
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_lsp_call_hierarchy(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/root-1",
+            json!({
+                "main.rs": "fn main() { one() }",
+                "one.rs": "fn one() {}",
+            }),
+        )
+        .await;
+
+    // Set up a fake language server.
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            path_suffixes: vec!["rs".to_string()],
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_language_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+    client_a.language_registry().add(Arc::new(language));
+
+    let (project_a, worktree_id) = client_a.build_local_project("/root-1", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.build_remote_project(project_id, cx_b).await;
+
+    // Open the file as the guest
+    let open_buffer = project_b.update(cx_b, |p, cx| p.open_buffer((worktree_id, "one.rs"), cx));
+    let buffer_b = cx_b.executor().spawn(open_buffer).await.unwrap();
+
+    let fake_language_server = fake_language_servers.next().await.unwrap();
+    let one = lsp::CallHierarchyItem {
+        name: "one".to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path("/root-1/one.rs").unwrap(),
+        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 11)),
+        selection_range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 6)),
+        data: None,
+    };
+    fake_language_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let one = one.clone();
+        move |params, _| {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(0, 4)
+            );
+            let one = one.clone();
+            async move { Ok(Some(vec![one])) }
+        }
+    });
+    fake_language_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.name, "one");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp::CallHierarchyItem {
+                    name: "main".to_string(),
+                    kind: lsp::SymbolKind::FUNCTION,
+                    tags: None,
+                    detail: None,
+                    uri: lsp::Url::from_file_path("/root-1/main.rs").unwrap(),
+                    range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 19)),
+                    selection_range: lsp::Range::new(
+                        lsp::Position::new(0, 3),
+                        lsp::Position::new(0, 7),
+                    ),
+                    data: None,
+                },
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 12),
+                    lsp::Position::new(0, 15),
+                )],
+            }]))
+        },
+    );
+
+    // Prepare the hierarchy and expand it as the guest.
+    let roots = project_b
+        .update(cx_b, |p, cx| {
+            p.prepare_hierarchy(&buffer_b, 4, HierarchyKind::IncomingCalls, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(roots[0].name, "one");
+    assert_eq!(roots[0].location.buffer, buffer_b);
+
+    let callers = project_b
+        .update(cx_b, |p, cx| {
+            p.hierarchy_children(&roots[0], HierarchyKind::IncomingCalls, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(callers.len(), 1);
+    assert_eq!(callers[0].name, "main");
+    cx_b.read(|cx| {
+        let caller = &callers[0];
+        let buffer = caller.location.buffer.read(cx);
+        assert_eq!(buffer.file().unwrap().path().as_ref(), Path::new("main.rs"));
+        assert_eq!(caller.call_sites.len(), 1);
+        let call_site = &caller.call_sites[0];
+        assert_eq!(
+            buffer
+                .text_for_range(call_site.range.clone())
+                .collect::<String>(),
+            "one"
+        );
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_project_symbols(
     executor: BackgroundExecutor,
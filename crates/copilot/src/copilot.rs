@@ -428,6 +428,7 @@ impl Copilot {
                 let binary = LanguageServerBinary {
                     path: node_path,
                     arguments,
+                    env: None,
                 };
 
                 let server = LanguageServer::new(
@@ -127,12 +127,17 @@ gpui::actions!(
         ExpandMacroRecursively,
         FindAllReferences,
         Fold,
+        FoldAllComments,
+        FoldAllImports,
+        FoldAllRegions,
         FoldSelectedRanges,
         Format,
         GoToDefinition,
         GoToDefinitionSplit,
         GoToDiagnostic,
         GoToHunk,
+        GoToImplementation,
+        GoToImplementationSplit,
         GoToPrevDiagnostic,
         GoToPrevHunk,
         GoToTypeDefinition,
@@ -176,6 +181,7 @@ gpui::actions!(
         RestartLanguageServer,
         RevealInFinder,
         ReverseLines,
+        RevertHunk,
         ScrollCursorBottom,
         ScrollCursorCenter,
         ScrollCursorTop,
@@ -198,17 +204,24 @@ gpui::actions!(
         SelectUp,
         ShowCharacterPalette,
         ShowCompletions,
+        ShowSignatureHelp,
         ShuffleLines,
+        SignatureHelpNext,
+        SignatureHelpPrev,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageHunk,
         Tab,
         TabPrev,
+        ToggleGitBlame,
+        ToggleHunkDiff,
         ToggleInlayHints,
         ToggleSoftWrap,
         Transpose,
         Undo,
         UndoSelection,
         UnfoldLines,
+        UnstageHunk,
     ]
 );
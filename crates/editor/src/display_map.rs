@@ -34,7 +34,8 @@ use fold_map::FoldMap;
 use gpui::{Font, HighlightStyle, Hsla, LineLayout, Model, ModelContext, Pixels, UnderlineStyle};
 use inlay_map::InlayMap;
 use language::{
-    language_settings::language_settings, OffsetUtf16, Point, Subscription as BufferSubscription,
+    language_settings::language_settings, FoldingRange, OffsetUtf16, Point,
+    Subscription as BufferSubscription,
 };
 use lsp::DiagnosticSeverity;
 use std::{any::TypeId, borrow::Cow, fmt::Debug, num::NonZeroU32, ops::Range, sync::Arc};
@@ -847,6 +848,10 @@ impl DisplaySnapshot {
     }
 
     pub fn is_foldable(self: &Self, buffer_row: u32) -> bool {
+        if let Some(folds) = self.folding_ranges_for_buffer_row(buffer_row) {
+            return !folds.is_empty();
+        }
+
         let max_row = self.buffer_snapshot.max_buffer_row();
         if buffer_row >= max_row {
             return false;
@@ -870,8 +875,20 @@ impl DisplaySnapshot {
     }
 
     pub fn foldable_range(self: &Self, buffer_row: u32) -> Option<Range<Point>> {
+        if self.is_line_folded(buffer_row) {
+            return None;
+        }
+
+        if let Some(folds) = self.folding_ranges_for_buffer_row(buffer_row) {
+            let fold = folds.first()?;
+            return Some(
+                fold.range.start.to_point(&self.buffer_snapshot)
+                    ..fold.range.end.to_point(&self.buffer_snapshot),
+            );
+        }
+
         let start = Point::new(buffer_row, self.buffer_snapshot.line_len(buffer_row));
-        if self.is_foldable(start.row) && !self.is_line_folded(start.row) {
+        if self.is_foldable(start.row) {
             let (start_indent, _) = self.line_indent_for_buffer_row(buffer_row);
             let max_point = self.buffer_snapshot.max_point();
             let mut end = None;
@@ -894,6 +911,15 @@ impl DisplaySnapshot {
         }
     }
 
+    /// Returns the folding ranges starting on the given row, from the outermost
+    /// to the innermost, or `None` if folds should be derived from indentation
+    /// because neither a language server nor a `folds.scm` query provides them.
+    fn folding_ranges_for_buffer_row(&self, buffer_row: u32) -> Option<Vec<FoldingRange<usize>>> {
+        let start = Point::new(buffer_row, 0);
+        let end = Point::new(buffer_row, self.buffer_snapshot.line_len(buffer_row));
+        self.buffer_snapshot.folding_ranges(start..end)
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn text_highlight_ranges<Tag: ?Sized + 'static>(
         &self,
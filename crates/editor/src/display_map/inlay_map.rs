@@ -463,6 +463,8 @@ impl InlayMap {
                     != buffer_snapshot.diagnostics_update_count()
                 || snapshot.buffer.git_diff_update_count()
                     != buffer_snapshot.git_diff_update_count()
+                || snapshot.buffer.semantic_tokens_update_count()
+                    != buffer_snapshot.semantic_tokens_update_count()
                 || snapshot.buffer.trailing_excerpt_update_count()
                     != buffer_snapshot.trailing_excerpt_update_count()
             {
@@ -30,6 +30,7 @@ mod persistence;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod signature_help_popover;
 
 #[cfg(test)]
 mod editor_tests;
@@ -52,7 +53,10 @@ use element::LineWithInvisibles;
 pub use element::{Cursor, EditorElement, HighlightedRange, HighlightedRangeLine};
 use futures::FutureExt;
 use fuzzy::{StringMatch, StringMatchCandidate};
-use git::diff_hunk_to_display;
+use git::{
+    blame::{blame_entry_author_and_date, GitBlame},
+    diff_hunk_to_display,
+};
 use gpui::{
     div, impl_actions, point, prelude::*, px, relative, rems, size, uniform_list, Action,
     AnyElement, AppContext, AsyncWindowContext, BackgroundExecutor, Bounds, ClipboardItem, Context,
@@ -71,8 +75,9 @@ use language::{char_kind, CharKind};
 use language::{
     language_settings::{self, all_language_settings, InlayHintSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CodeAction,
-    CodeLabel, Completion, CursorShape, Diagnostic, Documentation, IndentKind, IndentSize,
-    Language, LanguageServerName, OffsetRangeExt, Point, Selection, SelectionGoal, TransactionId,
+    CodeLabel, Completion, CursorShape, Diagnostic, Documentation, FoldKind, IndentKind,
+    IndentSize, Language, LanguageServerName, OffsetRangeExt, Point, Selection, SelectionGoal,
+    TransactionId,
 };
 
 use link_go_to_definition::{GoToDefinitionLink, InlayHighlight, LinkGoToDefinitionState};
@@ -86,13 +91,21 @@ pub use multi_buffer::{
 };
 use ordered_float::OrderedFloat;
 use parking_lot::RwLock;
-use project::{FormatTrigger, Location, Project, ProjectPath, ProjectTransaction};
+use persistence::DB;
+use project::{
+    project_settings::ProjectSettings, FormatTrigger, Location, Project, ProjectPath,
+    ProjectTransaction,
+};
 use rand::prelude::*;
 use rpc::proto::*;
 use scroll::{Autoscroll, OngoingScroll, ScrollAnchor, ScrollManager, ScrollbarAutoHide};
 use selections_collection::{resolve_multiple, MutableSelectionsCollection, SelectionsCollection};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
+use signature_help_popover::{
+    hide_signature_help, refresh_signature_help, trigger_signature_help_on_input,
+    SignatureHelpState,
+};
 use smallvec::SmallVec;
 use snippet::Snippet;
 use std::{
@@ -118,12 +131,17 @@ use ui::{
     Tooltip,
 };
 use util::{maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
-use workspace::{searchable::SearchEvent, ItemNavHistory, Pane, SplitDirection, ViewId, Workspace};
+use workspace::{
+    searchable::SearchEvent, ItemId, ItemNavHistory, NavigationMode, Pane, SplitDirection, ViewId,
+    Workspace, WorkspaceId,
+};
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_LINE_LEN: usize = 1024;
 const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
+const GIT_BLAME_GUTTER_WIDTH_CHARS: f32 = 32.;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
+const SERIALIZATION_THROTTLE_TIME: Duration = Duration::from_millis(100);
 const COPILOT_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(75);
 pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 #[doc(hidden)]
@@ -398,6 +416,7 @@ pub struct Editor {
     leader_peer_id: Option<PeerId>,
     remote_id: Option<ViewId>,
     hover_state: HoverState,
+    signature_help_state: SignatureHelpState,
     gutter_hovered: bool,
     link_go_to_definition_state: LinkGoToDefinitionState,
     copilot_state: CopilotState,
@@ -410,11 +429,25 @@ pub struct Editor {
     editor_actions: Vec<Box<dyn Fn(&mut ViewContext<Self>)>>,
     show_copilot_suggestions: bool,
     use_autoclose: bool,
+    serialize_selections: Task<()>,
+    serialize_folds: Task<()>,
+    expanded_hunks: Vec<ExpandedHunk>,
+    blame: Option<Model<GitBlame>>,
+    show_git_blame_gutter: bool,
+    blame_subscription: Option<Subscription>,
+}
+
+/// A git hunk whose diff base text is shown inline above it.
+#[derive(Debug)]
+struct ExpandedHunk {
+    position: Anchor,
+    block_id: BlockId,
 }
 
 pub struct EditorSnapshot {
     pub mode: EditorMode,
     show_gutter: bool,
+    show_git_blame_gutter: bool,
     pub display_snapshot: DisplaySnapshot,
     pub placeholder_text: Option<Arc<str>>,
     is_focused: bool,
@@ -426,6 +459,7 @@ pub struct GutterDimensions {
     pub padding: Pixels,
     pub width: Pixels,
     pub margin: Pixels,
+    pub git_blame_entries_width: Option<Pixels>,
 }
 
 impl Default for GutterDimensions {
@@ -434,6 +468,7 @@ impl Default for GutterDimensions {
             padding: Pixels::ZERO,
             width: Pixels::ZERO,
             margin: Pixels::ZERO,
+            git_blame_entries_width: None,
         }
     }
 }
@@ -1257,11 +1292,22 @@ pub(crate) struct NavigationData {
     cursor_position: Point,
     scroll_anchor: ScrollAnchor,
     scroll_top_row: u32,
+    closed_state: Option<SerializedEditorState>,
+}
+
+/// Selections and folds of a singleton editor, as buffer offsets so that they can
+/// be applied to a buffer that has been reloaded since.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SerializedEditorState {
+    /// Ranges from each selection's tail to its head.
+    pub selections: Vec<Range<usize>>,
+    pub folds: Vec<Range<usize>>,
 }
 
 enum GotoDefinitionKind {
     Symbol,
     Type,
+    Implementation,
 }
 
 #[derive(Debug, Clone)]
@@ -1451,6 +1497,7 @@ impl Editor {
             leader_peer_id: None,
             remote_id: None,
             hover_state: Default::default(),
+            signature_help_state: Default::default(),
             link_go_to_definition_state: Default::default(),
             copilot_state: Default::default(),
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
@@ -1462,6 +1509,12 @@ impl Editor {
             hovered_cursors: Default::default(),
             editor_actions: Default::default(),
             show_copilot_suggestions: mode == EditorMode::Full,
+            serialize_selections: Task::ready(()),
+            serialize_folds: Task::ready(()),
+            expanded_hunks: Vec::new(),
+            blame: None,
+            show_git_blame_gutter: false,
+            blame_subscription: None,
             _subscriptions: vec![
                 cx.observe(&buffer, Self::on_buffer_changed),
                 cx.subscribe(&buffer, Self::on_buffer_event),
@@ -1491,6 +1544,10 @@ impl Editor {
         if mode == EditorMode::Full {
             let should_auto_hide_scrollbars = cx.should_auto_hide_scrollbars();
             cx.set_global(ScrollbarAutoHide(should_auto_hide_scrollbars));
+
+            if ProjectSettings::get_global(cx).git.inline_blame_enabled() {
+                this.start_git_blame(cx);
+            }
         }
 
         this.report_editor_event("open", None, cx);
@@ -1522,6 +1579,9 @@ impl Editor {
                 None => {}
             }
         }
+        if self.signature_help_state.is_shown() {
+            key_context.add("showing_signature_help");
+        }
 
         for layer in self.keymap_context_layers.values() {
             key_context.extend(layer);
@@ -1606,6 +1666,7 @@ impl Editor {
         EditorSnapshot {
             mode: self.mode,
             show_gutter: self.show_gutter,
+            show_git_blame_gutter: self.show_git_blame_gutter,
             display_snapshot: self.display_map.update(cx, |map, cx| map.snapshot(cx)),
             scroll_anchor: self.scroll_manager.anchor(),
             ongoing_scroll: self.scroll_manager.ongoing_scroll(),
@@ -1774,6 +1835,7 @@ impl Editor {
             Some(new_cursor_position.to_point(buffer)),
             cx,
         );
+        self.serialize_selections(cx);
 
         if local {
             let new_cursor_position = self.selections.newest_anchor().head();
@@ -1830,6 +1892,7 @@ impl Editor {
             }
 
             hide_hover(self, cx);
+            refresh_signature_help(self, cx);
 
             if old_cursor_position.to_display_point(&display_map).row()
                 != new_cursor_position.to_display_point(&display_map).row()
@@ -2251,6 +2314,10 @@ impl Editor {
             return;
         }
 
+        if hide_signature_help(self, cx) {
+            return;
+        }
+
         if self.discard_copilot_suggestion(cx) {
             return;
         }
@@ -2467,6 +2534,8 @@ impl Editor {
                 this.trigger_completion_on_input(&text, cx);
                 this.refresh_copilot_suggestions(true, cx);
             }
+
+            trigger_signature_help_on_input(this, &text, cx);
         });
     }
 
@@ -5893,26 +5962,38 @@ impl Editor {
         new_position: Option<Point>,
         cx: &mut ViewContext<Self>,
     ) {
-        if let Some(nav_history) = self.nav_history.as_mut() {
-            let buffer = self.buffer.read(cx).read(cx);
-            let cursor_position = cursor_anchor.to_point(&buffer);
-            let scroll_state = self.scroll_manager.anchor();
-            let scroll_top_row = scroll_state.top_row(&buffer);
-            drop(buffer);
-
-            if let Some(new_position) = new_position {
-                let row_delta = (new_position.row as i64 - cursor_position.row as i64).abs();
-                if row_delta < MIN_NAVIGATION_HISTORY_ROW_DELTA {
-                    return;
-                }
+        let Some(mode) = self.nav_history.as_ref().map(ItemNavHistory::mode) else {
+            return;
+        };
+
+        let buffer = self.buffer.read(cx).read(cx);
+        let cursor_position = cursor_anchor.to_point(&buffer);
+        let scroll_state = self.scroll_manager.anchor();
+        let scroll_top_row = scroll_state.top_row(&buffer);
+        drop(buffer);
+
+        if let Some(new_position) = new_position {
+            let row_delta = (new_position.row as i64 - cursor_position.row as i64).abs();
+            if row_delta < MIN_NAVIGATION_HISTORY_ROW_DELTA {
+                return;
             }
+        }
+
+        // Remember the full state of a closed editor, to restore it if it's reopened.
+        let closed_state = if matches!(mode, NavigationMode::ClosingItem) {
+            self.serialized_state(cx)
+        } else {
+            None
+        };
 
+        if let Some(nav_history) = self.nav_history.as_mut() {
             nav_history.push(
                 Some(NavigationData {
                     cursor_anchor,
                     cursor_position,
                     scroll_anchor: scroll_state,
                     scroll_top_row,
+                    closed_state,
                 }),
                 cx,
             );
@@ -7035,6 +7116,329 @@ impl Editor {
         }
     }
 
+    pub fn stage_hunk(&mut self, _: &StageHunk, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        for (buffer, rows) in self.selected_rows_by_buffer(cx) {
+            let buffer_ref = buffer.read(cx);
+            let Some(diff_base) = buffer_ref.diff_base() else {
+                continue;
+            };
+            let snapshot = buffer_ref.snapshot();
+            let hunks = hunks_for_rows(&snapshot, &rows);
+            if hunks.is_empty() {
+                continue;
+            }
+            let index_text = ::git::diff::apply_hunks_to_diff_base(diff_base, &snapshot, hunks);
+            project
+                .update(cx, |project, cx| {
+                    project.set_index_text(&buffer, Some(index_text), cx)
+                })
+                .detach_and_log_err(cx);
+        }
+    }
+
+    pub fn unstage_hunk(&mut self, _: &UnstageHunk, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        for (buffer, rows) in self.selected_rows_by_buffer(cx) {
+            let buffer_ref = buffer.read(cx);
+            let Some(index_text) = buffer_ref.diff_base().map(str::to_string) else {
+                continue;
+            };
+            let snapshot = buffer_ref.snapshot();
+            let hunks = snapshot
+                .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                .collect::<Vec<_>>();
+            let index_rows = rows
+                .into_iter()
+                .map(|rows| {
+                    ::git::diff::diff_base_rows_for_buffer_rows(
+                        rows,
+                        &index_text,
+                        hunks.iter().cloned(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let head_text = project.update(cx, |project, cx| project.load_head_text(&buffer, cx));
+            let project = project.downgrade();
+            cx.spawn(|_, mut cx| async move {
+                let head_text = head_text.await?;
+                let new_index_text = ::git::diff::revert_changes_in_rows(
+                    head_text.as_deref().unwrap_or(""),
+                    &index_text,
+                    &index_rows,
+                );
+                if new_index_text == index_text {
+                    return Ok(());
+                }
+                // Unstaging everything from a file that isn't in HEAD removes it from the index.
+                let new_index_text = if head_text.is_none() && new_index_text.is_empty() {
+                    None
+                } else {
+                    Some(new_index_text)
+                };
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.set_index_text(&buffer, new_index_text, cx)
+                    })?
+                    .await
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    pub fn revert_hunk(&mut self, _: &RevertHunk, cx: &mut ViewContext<Self>) {
+        let multi_buffer_snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut edits = Vec::new();
+        for (buffer, excerpt_id, rows) in self.selected_buffer_rows(cx) {
+            let buffer = buffer.read(cx);
+            let Some(diff_base) = buffer.diff_base() else {
+                continue;
+            };
+            let snapshot = buffer.snapshot();
+            let max_point = snapshot.max_point();
+            for hunk in hunks_for_rows(&snapshot, &[rows]) {
+                let start = Point::new(hunk.buffer_range.start, 0).min(max_point);
+                let end = Point::new(hunk.buffer_range.end, 0).min(max_point);
+                let start = multi_buffer_snapshot
+                    .anchor_in_excerpt(excerpt_id, snapshot.anchor_before(start));
+                let end =
+                    multi_buffer_snapshot.anchor_in_excerpt(excerpt_id, snapshot.anchor_after(end));
+                edits.push((start..end, diff_base[hunk.diff_base_byte_range].to_string()));
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(cx, |this, cx| {
+            this.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        });
+    }
+
+    pub fn toggle_hunk_diff(&mut self, _: &ToggleHunkDiff, cx: &mut ViewContext<Self>) {
+        let multi_buffer_snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut blocks_to_remove = HashSet::default();
+        let mut hunks_to_expand = Vec::new();
+        for (buffer, excerpt_id, rows) in self.selected_buffer_rows(cx) {
+            let buffer = buffer.read(cx);
+            let Some(diff_base) = buffer.diff_base() else {
+                continue;
+            };
+            let snapshot = buffer.snapshot();
+            for hunk in hunks_for_rows(&snapshot, &[rows]) {
+                let position = multi_buffer_snapshot.anchor_in_excerpt(
+                    excerpt_id,
+                    snapshot.anchor_before(Point::new(hunk.buffer_range.start, 0)),
+                );
+                if hunks_to_expand
+                    .iter()
+                    .any(|(expanded, _)| position.cmp(expanded, &multi_buffer_snapshot).is_eq())
+                {
+                    continue;
+                }
+                if let Some(ix) = self.expanded_hunks.iter().position(|expanded| {
+                    position
+                        .cmp(&expanded.position, &multi_buffer_snapshot)
+                        .is_eq()
+                }) {
+                    blocks_to_remove.insert(self.expanded_hunks.remove(ix).block_id);
+                    continue;
+                }
+
+                let deleted_text = &diff_base[hunk.diff_base_byte_range];
+                if !deleted_text.is_empty() {
+                    hunks_to_expand.push((position, deleted_text.to_string()));
+                }
+            }
+        }
+
+        self.remove_blocks(blocks_to_remove, None, cx);
+        let block_ids = self.insert_blocks(
+            hunks_to_expand
+                .iter()
+                .map(|(position, deleted_text)| BlockProperties {
+                    position: *position,
+                    height: deleted_text.lines().count().min(u8::MAX as usize) as u8,
+                    style: BlockStyle::Flex,
+                    render: deleted_hunk_block_renderer(deleted_text.clone()),
+                    disposition: BlockDisposition::Above,
+                }),
+            None,
+            cx,
+        );
+        self.expanded_hunks.extend(
+            hunks_to_expand
+                .into_iter()
+                .zip(block_ids)
+                .map(|((position, _), block_id)| ExpandedHunk { position, block_id }),
+        );
+    }
+
+    pub fn toggle_git_blame(&mut self, _: &ToggleGitBlame, cx: &mut ViewContext<Self>) {
+        self.show_git_blame_gutter = !self.show_git_blame_gutter;
+        if self.show_git_blame_gutter {
+            self.start_git_blame(cx);
+        }
+        cx.notify();
+    }
+
+    fn start_git_blame(&mut self, cx: &mut ViewContext<Self>) {
+        if self.blame.is_some() {
+            return;
+        }
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+
+        let blame = cx.new_model(|cx| GitBlame::new(buffer, project, cx));
+        self.blame_subscription = Some(cx.observe(&blame, |_, _, cx| cx.notify()));
+        self.blame = Some(blame);
+    }
+
+    /// Renders the "author, date" of the commit that last changed each of the
+    /// given buffer rows, omitting rows that belong to the same entry as the
+    /// row above them.
+    pub fn render_git_blame_gutter(
+        &self,
+        buffer_rows: impl Iterator<Item = Option<u32>>,
+        style: &EditorStyle,
+        cx: &mut ViewContext<Self>,
+    ) -> Vec<Option<AnyElement>> {
+        let Some(blame) = self.blame.clone() else {
+            return Vec::new();
+        };
+
+        let mut previous_sha = None;
+        buffer_rows
+            .map(|buffer_row| {
+                let blame = blame.read(cx);
+                let entry = buffer_row.and_then(|row| blame.entry_for_row(row, cx))?;
+                if previous_sha.as_ref() == Some(&entry.sha) {
+                    return None;
+                }
+                previous_sha = Some(entry.sha.clone());
+
+                let label = blame_entry_author_and_date(entry);
+                let message: SharedString = blame
+                    .message_for_entry(entry)
+                    .or(entry.summary.as_deref())
+                    .unwrap_or_default()
+                    .to_string()
+                    .into();
+                let permalink = blame.permalink_for_entry(entry);
+                let meta: SharedString = permalink
+                    .as_ref()
+                    .map_or_else(|| entry.sha.clone(), |url| url.to_string())
+                    .into();
+
+                Some(
+                    h_flex()
+                        .id(("blame", buffer_row.unwrap_or_default() as usize))
+                        .w_full()
+                        .font(style.text.font_family.clone())
+                        .text_color(cx.theme().status().hint)
+                        .overflow_hidden()
+                        .whitespace_nowrap()
+                        .child(label)
+                        .tooltip(move |cx| {
+                            Tooltip::with_meta(message.clone(), None, meta.clone(), cx)
+                        })
+                        .when_some(permalink, |this, permalink| {
+                            this.cursor_pointer()
+                                .on_click(move |_, cx| cx.open_url(permalink.as_str()))
+                        })
+                        .into_any_element(),
+                )
+            })
+            .collect()
+    }
+
+    /// Renders the "author, date - summary" annotation shown at the end of the
+    /// cursor's line when inline blame is enabled.
+    pub fn render_inline_blame(
+        &self,
+        buffer_row: u32,
+        style: &EditorStyle,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<AnyElement> {
+        if !ProjectSettings::get_global(cx).git.inline_blame_enabled()
+            || !self.focus_handle.is_focused(cx)
+        {
+            return None;
+        }
+
+        let blame = self.blame.as_ref()?.read(cx);
+        let entry = blame.entry_for_row(buffer_row, cx)?;
+        let mut text = blame_entry_author_and_date(entry);
+        if let Some(summary) = entry.summary.as_deref() {
+            text.push_str(" - ");
+            text.push_str(summary);
+        }
+
+        Some(
+            div()
+                .font(style.text.font_family.clone())
+                .text_color(cx.theme().status().hint)
+                .whitespace_nowrap()
+                .child(text)
+                .into_any_element(),
+        )
+    }
+
+    fn collapse_hunk_diffs(&mut self, cx: &mut ViewContext<Self>) {
+        if self.expanded_hunks.is_empty() {
+            return;
+        }
+        let block_ids = self
+            .expanded_hunks
+            .drain(..)
+            .map(|expanded| expanded.block_id)
+            .collect();
+        self.remove_blocks(block_ids, None, cx);
+    }
+
+    /// Returns the rows of each buffer touched by each selection, along with the
+    /// excerpt in which they were selected.
+    fn selected_buffer_rows(&self, cx: &AppContext) -> Vec<(Model<Buffer>, ExcerptId, Range<u32>)> {
+        let selections = self.selections.all::<usize>(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let mut result = Vec::new();
+        for selection in selections {
+            for (buffer, range, excerpt_id) in
+                multi_buffer.range_to_buffer_ranges(selection.range(), cx)
+            {
+                let snapshot = buffer.read(cx);
+                let start = snapshot.offset_to_point(range.start).row;
+                let end = snapshot.offset_to_point(range.end).row;
+                result.push((buffer, excerpt_id, start..end + 1));
+            }
+        }
+        result
+    }
+
+    fn selected_rows_by_buffer(&self, cx: &AppContext) -> Vec<(Model<Buffer>, Vec<Range<u32>>)> {
+        let mut rows_by_buffer = Vec::<(Model<Buffer>, Vec<Range<u32>>)>::new();
+        for (buffer, _, rows) in self.selected_buffer_rows(cx) {
+            match rows_by_buffer
+                .iter_mut()
+                .find(|(existing, _)| *existing == buffer)
+            {
+                Some((_, buffer_rows)) => buffer_rows.push(rows),
+                None => rows_by_buffer.push((buffer, vec![rows])),
+            }
+        }
+        rows_by_buffer
+    }
+
     fn seek_in_direction(
         &mut self,
         snapshot: &DisplaySnapshot,
@@ -7076,6 +7480,10 @@ impl Editor {
         self.go_to_definition_of_kind(GotoDefinitionKind::Type, false, cx);
     }
 
+    pub fn go_to_implementation(&mut self, _: &GoToImplementation, cx: &mut ViewContext<Self>) {
+        self.go_to_definition_of_kind(GotoDefinitionKind::Implementation, false, cx);
+    }
+
     pub fn go_to_definition_split(&mut self, _: &GoToDefinitionSplit, cx: &mut ViewContext<Self>) {
         self.go_to_definition_of_kind(GotoDefinitionKind::Symbol, true, cx);
     }
@@ -7088,6 +7496,14 @@ impl Editor {
         self.go_to_definition_of_kind(GotoDefinitionKind::Type, true, cx);
     }
 
+    pub fn go_to_implementation_split(
+        &mut self,
+        _: &GoToImplementationSplit,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.go_to_definition_of_kind(GotoDefinitionKind::Implementation, true, cx);
+    }
+
     fn go_to_definition_of_kind(
         &mut self,
         kind: GotoDefinitionKind,
@@ -7109,6 +7525,7 @@ impl Editor {
         let definitions = project.update(cx, |project, cx| match kind {
             GotoDefinitionKind::Symbol => project.definition(&buffer, head, cx),
             GotoDefinitionKind::Type => project.type_definition(&buffer, head, cx),
+            GotoDefinitionKind::Implementation => project.implementation(&buffer, head, cx),
         });
 
         cx.spawn(|editor, mut cx| async move {
@@ -7917,6 +8334,30 @@ impl Editor {
         }
     }
 
+    pub fn fold_all_comments(&mut self, _: &FoldAllComments, cx: &mut ViewContext<Self>) {
+        self.fold_all_of_kind(FoldKind::Comment, cx);
+    }
+
+    pub fn fold_all_imports(&mut self, _: &FoldAllImports, cx: &mut ViewContext<Self>) {
+        self.fold_all_of_kind(FoldKind::Imports, cx);
+    }
+
+    pub fn fold_all_regions(&mut self, _: &FoldAllRegions, cx: &mut ViewContext<Self>) {
+        self.fold_all_of_kind(FoldKind::Region, cx);
+    }
+
+    fn fold_all_of_kind(&mut self, kind: FoldKind, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let fold_ranges = display_map
+            .buffer_snapshot
+            .all_folding_ranges()
+            .into_iter()
+            .filter(|fold| fold.kind == Some(kind))
+            .map(|fold| fold.range)
+            .collect::<Vec<_>>();
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
     pub fn unfold_lines(&mut self, _: &UnfoldLines, cx: &mut ViewContext<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
@@ -7984,6 +8425,7 @@ impl Editor {
                 self.request_autoscroll(Autoscroll::fit(), cx);
             }
 
+            self.serialize_folds(cx);
             cx.notify();
         }
     }
@@ -8003,10 +8445,143 @@ impl Editor {
                 self.request_autoscroll(Autoscroll::fit(), cx);
             }
 
+            self.serialize_folds(cx);
             cx.notify();
         }
     }
 
+    /// Returns the selections and folds of a singleton editor as buffer offsets.
+    pub(crate) fn serialized_state(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<SerializedEditorState> {
+        if !self.buffer.read(cx).is_singleton() {
+            return None;
+        }
+
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let buffer = &display_map.buffer_snapshot;
+        let selections = self
+            .selections
+            .disjoint_anchors()
+            .iter()
+            .map(|selection| selection.tail().to_offset(buffer)..selection.head().to_offset(buffer))
+            .collect();
+        let folds = display_map
+            .folds_in_range(0..buffer.len())
+            .map(|fold| fold.range.start.to_offset(buffer)..fold.range.end.to_offset(buffer))
+            .collect();
+        Some(SerializedEditorState { selections, folds })
+    }
+
+    /// Applies selections and folds captured by `serialized_state`, clipping them
+    /// to the buffer in case it changed in the meantime.
+    pub(crate) fn restore_serialized_state(
+        &mut self,
+        state: SerializedEditorState,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let clip = |offset: usize| buffer.clip_offset(offset.min(buffer.len()), Bias::Left);
+
+        let folds = state
+            .folds
+            .into_iter()
+            .map(|range| clip(range.start)..clip(range.end))
+            .filter(|range| !range.is_empty())
+            .collect::<Vec<_>>();
+        let selections = state
+            .selections
+            .into_iter()
+            .map(|range| clip(range.start)..clip(range.end))
+            .collect::<Vec<_>>();
+
+        self.fold_ranges(folds, false, cx);
+        if !selections.is_empty() {
+            self.change_selections(None, cx, |s| s.select_ranges(selections));
+        }
+    }
+
+    pub(crate) fn read_serialized_state_from_db(
+        &mut self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let selections = DB.get_selections(item_id, workspace_id).log_err();
+        let folds = DB.get_folds(item_id, workspace_id).log_err();
+        let state = SerializedEditorState {
+            selections: selections
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(tail, head)| tail..head)
+                .collect(),
+            folds: folds
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(start, end)| start..end)
+                .collect(),
+        };
+        self.restore_serialized_state(state, cx);
+    }
+
+    pub(crate) fn serialize_selections(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace_id) = self.workspace.as_ref().map(|(_, id)| *id) else {
+            return;
+        };
+        if !self.buffer.read(cx).is_singleton() {
+            return;
+        }
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self
+            .selections
+            .disjoint_anchors()
+            .iter()
+            .map(|selection| {
+                (
+                    selection.tail().to_offset(&buffer),
+                    selection.head().to_offset(&buffer),
+                )
+            })
+            .collect::<Vec<_>>();
+        let item_id = cx.view().item_id().as_u64() as ItemId;
+        let executor = cx.background_executor().clone();
+        self.serialize_selections = cx.background_executor().spawn(async move {
+            executor.timer(SERIALIZATION_THROTTLE_TIME).await;
+            DB.save_selections(item_id, workspace_id, selections)
+                .await
+                .log_err();
+        });
+    }
+
+    pub(crate) fn serialize_folds(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace_id) = self.workspace.as_ref().map(|(_, id)| *id) else {
+            return;
+        };
+        if !self.buffer.read(cx).is_singleton() {
+            return;
+        }
+
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let buffer = &display_map.buffer_snapshot;
+        let folds = display_map
+            .folds_in_range(0..buffer.len())
+            .map(|fold| {
+                (
+                    fold.range.start.to_offset(buffer),
+                    fold.range.end.to_offset(buffer),
+                )
+            })
+            .collect::<Vec<_>>();
+        let item_id = cx.view().item_id().as_u64() as ItemId;
+        let executor = cx.background_executor().clone();
+        self.serialize_folds = cx.background_executor().spawn(async move {
+            executor.timer(SERIALIZATION_THROTTLE_TIME).await;
+            DB.save_folds(item_id, workspace_id, folds).await.log_err();
+        });
+    }
+
     pub fn set_gutter_hovered(&mut self, hovered: bool, cx: &mut ViewContext<Self>) {
         if hovered != self.gutter_hovered {
             self.gutter_hovered = hovered;
@@ -8588,7 +9163,10 @@ impl Editor {
             multi_buffer::Event::FileHandleChanged | multi_buffer::Event::Reloaded => {
                 cx.emit(EditorEvent::TitleChanged)
             }
-            multi_buffer::Event::DiffBaseChanged => cx.emit(EditorEvent::DiffBaseChanged),
+            multi_buffer::Event::DiffBaseChanged => {
+                self.collapse_hunk_diffs(cx);
+                cx.emit(EditorEvent::DiffBaseChanged)
+            }
             multi_buffer::Event::Closed => cx.emit(EditorEvent::Closed),
             multi_buffer::Event::DiagnosticsUpdated => {
                 self.refresh_active_diagnostics(cx);
@@ -8603,6 +9181,11 @@ impl Editor {
 
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         self.refresh_copilot_suggestions(true, cx);
+        if self.mode == EditorMode::Full
+            && ProjectSettings::get_global(cx).git.inline_blame_enabled()
+        {
+            self.start_git_blame(cx);
+        }
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
                 self.selections.newest_anchor().head(),
@@ -9003,6 +9586,7 @@ impl Editor {
             .update(cx, |buffer, cx| buffer.remove_active_selections(cx));
         self.hide_context_menu(cx);
         hide_hover(self, cx);
+        hide_signature_help(self, cx);
         cx.emit(EditorEvent::Blurred);
         cx.notify();
     }
@@ -9222,14 +9806,19 @@ impl EditorSnapshot {
             let gutter_padding = (em_width * gutter_padding_factor).round();
             // Avoid flicker-like gutter resizes when the line number gains another digit and only resize the gutter on files with N*10^5 lines.
             let min_width_for_number_on_gutter = em_width * 4.0;
-            let gutter_width =
-                max_line_number_width.max(min_width_for_number_on_gutter) + gutter_padding * 2.0;
+            let git_blame_entries_width = self
+                .show_git_blame_gutter
+                .then(|| em_width * GIT_BLAME_GUTTER_WIDTH_CHARS);
+            let gutter_width = max_line_number_width.max(min_width_for_number_on_gutter)
+                + gutter_padding * 2.0
+                + git_blame_entries_width.unwrap_or_default();
             let gutter_margin = -descent;
 
             GutterDimensions {
                 padding: gutter_padding,
                 width: gutter_width,
                 margin: gutter_margin,
+                git_blame_entries_width,
             }
         } else {
             GutterDimensions::default()
@@ -9758,6 +10347,35 @@ pub fn diagnostic_block_renderer(diagnostic: Diagnostic, _is_valid: bool) -> Ren
     })
 }
 
+fn hunks_for_rows(snapshot: &language::BufferSnapshot, rows: &[Range<u32>]) -> Vec<DiffHunk<u32>> {
+    let mut hunks = Vec::<DiffHunk<u32>>::new();
+    for rows in rows {
+        for hunk in snapshot.git_diff_hunks_in_row_range(rows.clone()) {
+            if hunk.intersects_rows(rows) && !hunks.contains(&hunk) {
+                hunks.push(hunk);
+            }
+        }
+    }
+    hunks
+}
+
+fn deleted_hunk_block_renderer(deleted_text: String) -> RenderBlock {
+    let deleted_text: SharedString = deleted_text.trim_end_matches('\n').to_string().into();
+    Arc::new(move |cx: &mut BlockContext| {
+        let mut text_style = cx.editor_style.text.clone();
+        text_style.color = cx.theme().status().deleted;
+
+        div()
+            .id(cx.block_id)
+            .size_full()
+            .pl(cx.gutter_width)
+            .w(cx.max_width + cx.gutter_width)
+            .bg(cx.theme().status().deleted_background)
+            .child(StyledText::new(deleted_text.clone()).with_highlights(&text_style, []))
+            .into_any_element()
+    })
+}
+
 pub fn highlight_diagnostic_message(diagnostic: &Diagnostic) -> (SharedString, Vec<Range<usize>>) {
     let mut text_without_backticks = String::new();
     let mut code_ranges = Vec::new();
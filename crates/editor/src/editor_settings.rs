@@ -12,6 +12,7 @@ pub struct EditorSettings {
     pub scrollbar: Scrollbar,
     pub relative_line_numbers: bool,
     pub seed_search_query_from_cursor: SeedQuerySetting,
+    pub persistent_undo: bool,
 }
 
 /// When to populate a new search's query based on the text under the cursor.
@@ -87,6 +88,12 @@ pub struct EditorSettingsContent {
     ///
     /// Default: always
     pub seed_search_query_from_cursor: Option<SeedQuerySetting>,
+    /// Whether to keep the undo history of files across sessions. The history
+    /// is stored when a file is saved, and discarded if the file is changed
+    /// outside of Zed.
+    ///
+    /// Default: false
+    pub persistent_undo: Option<bool>,
 }
 
 /// Scrollbar related settings
@@ -644,6 +644,7 @@ async fn test_navigation_history(cx: &mut TestAppContext) {
                         offset: Default::default(),
                     },
                     scroll_top_row: invalid_point.row,
+                    closed_state: None,
                 }),
                 cx,
             );
@@ -661,6 +662,72 @@ async fn test_navigation_history(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_reopening_closed_editor_restores_state(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/a", json!({ "main.rs": sample_text(20, 5, 'a') }))
+        .await;
+    let project = Project::test(fs, ["/a".as_ref()], cx).await;
+    let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let worktree_id = workspace
+        .update(cx, |workspace, cx| {
+            workspace.project().update(cx, |project, cx| {
+                project.worktrees().next().unwrap().read(cx).id()
+            })
+        })
+        .unwrap();
+    let editor = workspace
+        .update(cx, |workspace, cx| {
+            workspace.open_path((worktree_id, "main.rs"), None, true, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap()
+        .downcast::<Editor>()
+        .unwrap();
+
+    let state = editor.update(cx, |editor, cx| {
+        editor.fold_ranges([Point::new(2, 0)..Point::new(5, 0)], false, cx);
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([
+                Point::new(8, 1)..Point::new(8, 3),
+                Point::new(12, 4)..Point::new(10, 0),
+            ])
+        });
+        editor.serialized_state(cx).unwrap()
+    });
+    assert_eq!(state.folds.len(), 1);
+    assert_eq!(state.selections.len(), 2);
+
+    workspace
+        .update(cx, |workspace, cx| {
+            workspace.active_pane().update(cx, |pane, cx| {
+                pane.close_active_item(&Default::default(), cx).unwrap()
+            })
+        })
+        .unwrap()
+        .await
+        .unwrap();
+    workspace
+        .update(cx, |workspace, cx| workspace.reopen_closed_item(cx))
+        .unwrap()
+        .await
+        .unwrap();
+
+    let reopened_editor = workspace
+        .update(cx, |workspace, cx| workspace.active_item_as::<Editor>(cx))
+        .unwrap()
+        .unwrap();
+    assert_ne!(reopened_editor.entity_id(), editor.entity_id());
+    reopened_editor.update(cx, |editor, cx| {
+        assert_eq!(editor.serialized_state(cx).unwrap(), state);
+    });
+}
+
 #[gpui::test]
 fn test_cancel(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -804,6 +871,139 @@ fn test_fold_action(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_fold_with_folds_query(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_folds_query(
+            r#"
+            (block) @fold
+            (block_comment) @fold.comment
+            (use_declaration)+ @fold.imports
+            "#,
+        )
+        .unwrap(),
+    );
+
+    let text = r#"
+        use a::b;
+        use c::d;
+
+        /* A comment
+           spanning lines */
+        fn e() {
+        if true {
+        f();
+        }
+        }
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(language, cx)
+    });
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (view, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+    view.condition::<crate::EditorEvent>(&cx, |view, cx| !view.buffer.read(cx).is_parsing(cx))
+        .await;
+
+    _ = view.update(cx, |view, cx| {
+        // Folds come from the syntax tree rather than from indentation.
+        view.change_selections(None, cx, |s| {
+            s.select_display_ranges([DisplayPoint::new(7, 0)..DisplayPoint::new(7, 0)]);
+        });
+        view.fold(&Fold, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                use a::b;
+                use c::d;
+
+                /* A comment
+                   spanning lines */
+                fn e() {
+                if true {⋯}
+                }
+            "
+            .unindent(),
+        );
+
+        view.fold_all_imports(&FoldAllImports, cx);
+        view.fold_all_comments(&FoldAllComments, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                use a::b;⋯
+
+                /* A comment⋯
+                fn e() {
+                if true {⋯}
+                }
+            "
+            .unindent(),
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_fold_all_regions_without_language_server(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_folds_query(
+            r#"
+            (block) @fold
+            ((line_comment) @fold.region.start
+             (#match? @fold.region.start "^//\\s*#region\\b"))
+            ((line_comment) @fold.region.end
+             (#match? @fold.region.end "^//\\s*#endregion\\b"))
+            "#,
+        )
+        .unwrap(),
+    );
+
+    let text = r#"
+        // #region helpers
+        fn a() {
+        b();
+        }
+        // #endregion
+        fn c() {}
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(language, cx)
+    });
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (view, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+    view.condition::<crate::EditorEvent>(&cx, |view, cx| !view.buffer.read(cx).is_parsing(cx))
+        .await;
+
+    _ = view.update(cx, |view, cx| {
+        view.fold_all_regions(&FoldAllRegions, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                // #region helpers⋯
+                fn c() {}
+            "
+            .unindent(),
+        );
+    });
+}
+
 #[gpui::test]
 fn test_move_cursor(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -5312,7 +5512,8 @@ async fn test_range_format_during_save(cx: &mut gpui::TestAppContext) {
 #[gpui::test]
 async fn test_document_format_manual_trigger(cx: &mut gpui::TestAppContext) {
     init_test(cx, |settings| {
-        settings.defaults.formatter = Some(language_settings::Formatter::LanguageServer)
+        settings.defaults.formatter =
+            Some(language_settings::Formatter::LanguageServer { name: None })
     });
 
     let mut language = Language::new(
@@ -7156,6 +7357,58 @@ async fn go_to_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_revert_and_expand_hunks(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    let diff_base = "
+        one
+        two
+        three
+        four
+        five
+    "
+    .unindent();
+
+    cx.set_state(
+        &"
+        one
+        TWˇO
+        three
+        FOUR
+        five
+        "
+        .unindent(),
+    );
+    cx.set_diff_base(Some(&diff_base));
+    executor.run_until_parked();
+
+    // Only the hunk containing the cursor is reverted.
+    cx.update_editor(|editor, cx| editor.revert_hunk(&RevertHunk, cx));
+    assert_eq!(cx.buffer_text(), "one\ntwo\nthree\nFOUR\nfive\n");
+
+    cx.update_editor(|editor, cx| editor.undo(&Undo, cx));
+    assert_eq!(cx.buffer_text(), "one\nTWO\nthree\nFOUR\nfive\n");
+    executor.run_until_parked();
+
+    cx.update_editor(|editor, cx| {
+        editor.toggle_hunk_diff(&ToggleHunkDiff, cx);
+        assert_eq!(editor.expanded_hunks.len(), 1);
+
+        editor.toggle_hunk_diff(&ToggleHunkDiff, cx);
+        assert!(editor.expanded_hunks.is_empty());
+
+        editor.toggle_hunk_diff(&ToggleHunkDiff, cx);
+        assert_eq!(editor.expanded_hunks.len(), 1);
+    });
+
+    // Expanded hunks are collapsed when the diff base changes.
+    cx.set_diff_base(Some(&diff_base));
+    cx.update_editor(|editor, _| assert!(editor.expanded_hunks.is_empty()));
+}
+
 #[test]
 fn test_split_words() {
     fn split<'a>(text: &'a str) -> Vec<&'a str> {
@@ -7927,6 +8180,7 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
                 initialization_options: Some(json!({
                     "some other init value": false
                 })),
+                ..Default::default()
             },
         );
     });
@@ -7944,6 +8198,7 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
                 initialization_options: Some(json!({
                     "anotherInitValue": false
                 })),
+                ..Default::default()
             },
         );
     });
@@ -7961,6 +8216,7 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
                 initialization_options: Some(json!({
                     "anotherInitValue": false
                 })),
+                ..Default::default()
             },
         );
     });
@@ -7976,6 +8232,7 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
             language_server_name.into(),
             LspSettings {
                 initialization_options: None,
+                ..Default::default()
             },
         );
     });
@@ -16,10 +16,10 @@ use crate::{
     },
     mouse_context_menu,
     scroll::scroll_amount::ScrollAmount,
-    CursorShape, DisplayPoint, DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode,
-    EditorSettings, EditorSnapshot, EditorStyle, HalfPageDown, HalfPageUp, HoveredCursor, LineDown,
-    LineUp, OpenExcerpts, PageDown, PageUp, Point, SelectPhase, Selection, SoftWrap, ToPoint,
-    CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
+    signature_help_popover, CursorShape, DisplayPoint, DocumentHighlightRead,
+    DocumentHighlightWrite, Editor, EditorMode, EditorSettings, EditorSnapshot, EditorStyle,
+    HalfPageDown, HalfPageUp, HoveredCursor, LineDown, LineUp, OpenExcerpts, PageDown, PageUp,
+    Point, SelectPhase, Selection, SoftWrap, ToPoint, CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
 };
 use anyhow::Result;
 use collections::{BTreeMap, HashMap};
@@ -58,6 +58,8 @@ use ui::{h_flex, ButtonLike, ButtonStyle, IconButton, Tooltip};
 use util::ResultExt;
 use workspace::item::Item;
 
+const INLINE_BLAME_PADDING_EM_WIDTHS: f32 = 6.;
+
 struct SelectionLayout {
     head: DisplayPoint,
     cursor_shape: CursorShape,
@@ -258,21 +260,34 @@ impl EditorElement {
         register_action(view, cx, Editor::go_to_prev_diagnostic);
         register_action(view, cx, Editor::go_to_hunk);
         register_action(view, cx, Editor::go_to_prev_hunk);
+        register_action(view, cx, Editor::stage_hunk);
+        register_action(view, cx, Editor::unstage_hunk);
+        register_action(view, cx, Editor::revert_hunk);
+        register_action(view, cx, Editor::toggle_git_blame);
+        register_action(view, cx, Editor::toggle_hunk_diff);
         register_action(view, cx, Editor::go_to_definition);
         register_action(view, cx, Editor::go_to_definition_split);
         register_action(view, cx, Editor::go_to_type_definition);
         register_action(view, cx, Editor::go_to_type_definition_split);
+        register_action(view, cx, Editor::go_to_implementation);
+        register_action(view, cx, Editor::go_to_implementation_split);
         register_action(view, cx, Editor::fold);
         register_action(view, cx, Editor::fold_at);
         register_action(view, cx, Editor::unfold_lines);
         register_action(view, cx, Editor::unfold_at);
         register_action(view, cx, Editor::fold_selected_ranges);
+        register_action(view, cx, Editor::fold_all_comments);
+        register_action(view, cx, Editor::fold_all_imports);
+        register_action(view, cx, Editor::fold_all_regions);
         register_action(view, cx, Editor::show_completions);
         register_action(view, cx, Editor::toggle_code_actions);
         register_action(view, cx, Editor::open_excerpts);
         register_action(view, cx, Editor::toggle_soft_wrap);
         register_action(view, cx, Editor::toggle_inlay_hints);
         register_action(view, cx, hover_popover::hover);
+        register_action(view, cx, signature_help_popover::show_signature_help);
+        register_action(view, cx, signature_help_popover::signature_help_next);
+        register_action(view, cx, signature_help_popover::signature_help_prev);
         register_action(view, cx, Editor::reveal_in_finder);
         register_action(view, cx, Editor::copy_path);
         register_action(view, cx, Editor::copy_relative_path);
@@ -792,6 +807,22 @@ impl EditorElement {
 
                 button.draw(bounds.origin + point(x, y), available_space, cx);
             }
+
+            if let Some(blame_width) = layout.git_blame_entries_width {
+                for (ix, blame_entry) in layout.blame_entries.drain(..).enumerate() {
+                    if let Some(mut blame_entry) = blame_entry {
+                        let available_space = size(
+                            AvailableSpace::Definite(blame_width),
+                            AvailableSpace::Definite(line_height),
+                        );
+                        let position = point(
+                            layout.gutter_padding,
+                            ix as f32 * line_height - (scroll_top % line_height),
+                        );
+                        blame_entry.draw(bounds.origin + position, available_space, cx);
+                    }
+                }
+            }
         });
     }
 
@@ -1153,6 +1184,21 @@ impl EditorElement {
                     )
                 }
 
+                if let Some(mut inline_blame) = layout.inline_blame.take() {
+                    let line_height = layout.position_map.line_height;
+                    let origin = content_origin
+                        + point(
+                            inline_blame.x - layout.position_map.scroll_position.x,
+                            inline_blame.display_row as f32 * line_height
+                                - layout.position_map.scroll_position.y,
+                        );
+                    let available_space = size(
+                        AvailableSpace::MinContent,
+                        AvailableSpace::Definite(line_height),
+                    );
+                    inline_blame.element.draw(origin, available_space, cx);
+                }
+
                 cx.with_z_index(0, |cx| {
                     for cursor in cursors {
                         cursor.paint(content_origin, cx);
@@ -1197,6 +1243,39 @@ impl EditorElement {
             cx.break_content_mask(|cx| context_menu.draw(list_origin, available_space, cx));
         }
 
+        if let Some((position, mut signature_help)) = layout.signature_help_popover.take() {
+            let available_space = size(AvailableSpace::MinContent, AvailableSpace::MinContent);
+            let signature_help_size = signature_help.measure(available_space, cx);
+
+            // This is safe because we check on layout whether the required row is available
+            let row_layout =
+                &layout.position_map.line_layouts[(position.row() - start_row) as usize].line;
+            let x = row_layout.x_for_index(position.column() as usize)
+                - layout.position_map.scroll_position.x;
+            let y = position.row() as f32 * layout.position_map.line_height
+                - layout.position_map.scroll_position.y;
+            let cursor_point = content_origin + point(x, y);
+
+            // Prefer rendering above the cursor, so that the popover doesn't obscure the
+            // completions menu.
+            let mut popover_origin = if cursor_point.y - signature_help_size.height > Pixels::ZERO {
+                point(cursor_point.x, cursor_point.y - signature_help_size.height)
+            } else {
+                point(
+                    cursor_point.x,
+                    cursor_point.y + layout.position_map.line_height,
+                )
+            };
+
+            let x_out_of_bounds =
+                text_bounds.upper_right().x - (popover_origin.x + signature_help_size.width);
+            if x_out_of_bounds < Pixels::ZERO {
+                popover_origin.x = popover_origin.x + x_out_of_bounds;
+            }
+
+            cx.break_content_mask(|cx| signature_help.draw(popover_origin, available_space, cx));
+        }
+
         if let Some((position, mut hover_popovers)) = layout.hover_popovers.take() {
             let available_space = size(AvailableSpace::MinContent, AvailableSpace::MinContent);
 
@@ -2119,6 +2198,31 @@ impl EditorElement {
                 }
             }
 
+            let blame_entries = if snapshot.show_git_blame_gutter {
+                editor.render_git_blame_gutter(
+                    snapshot
+                        .buffer_rows(start_row)
+                        .take((end_row - start_row) as usize),
+                    &style,
+                    cx,
+                )
+            } else {
+                Vec::new()
+            };
+
+            let inline_blame = newest_selection_head
+                .filter(|head| (start_row..end_row).contains(&head.row()))
+                .and_then(|head| {
+                    let buffer_row = head.to_point(&snapshot.display_snapshot).row;
+                    let element = editor.render_inline_blame(buffer_row, &style, cx)?;
+                    let line = &line_layouts[(head.row() - start_row) as usize].line;
+                    Some(InlineBlameLayout {
+                        display_row: head.row(),
+                        x: line.width + INLINE_BLAME_PADDING_EM_WIDTHS * em_width,
+                        element,
+                    })
+                });
+
             let longest_line_width = layout_line(snapshot.longest_row(), &snapshot, &style, cx)
                 .unwrap()
                 .width;
@@ -2217,13 +2321,22 @@ impl EditorElement {
                 editor.hover_state.render(
                 &snapshot,
                 &style,
-                visible_rows,
+                visible_rows.clone(),
                 max_size,
                 editor.workspace.as_ref().map(|(w, _)| w.clone()),
                 cx,
             )
             };
 
+            let signature_help = editor.signature_help_state.render(
+                &snapshot,
+                &style,
+                visible_rows,
+                max_size,
+                editor.workspace.as_ref().map(|(w, _)| w.clone()),
+                cx,
+            );
+
             let editor_view = cx.view().clone();
             let fold_indicators = cx.with_element_context(|cx| {
 
@@ -2305,9 +2418,13 @@ impl EditorElement {
                 context_menu,
                 code_actions_indicator,
                 fold_indicators,
+                git_blame_entries_width: gutter_dimensions.git_blame_entries_width,
+                blame_entries,
+                inline_blame,
                 tab_invisible,
                 space_invisible,
                 hover_popovers: hover,
+                signature_help_popover: signature_help,
             }
         })
     }
@@ -3090,7 +3207,11 @@ pub struct LayoutState {
     context_menu: Option<(DisplayPoint, AnyElement)>,
     code_actions_indicator: Option<CodeActionsIndicator>,
     hover_popovers: Option<(DisplayPoint, Vec<AnyElement>)>,
+    signature_help_popover: Option<(DisplayPoint, AnyElement)>,
     fold_indicators: Vec<Option<IconButton>>,
+    git_blame_entries_width: Option<Pixels>,
+    blame_entries: Vec<Option<AnyElement>>,
+    inline_blame: Option<InlineBlameLayout>,
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
 }
@@ -3100,6 +3221,12 @@ struct CodeActionsIndicator {
     button: IconButton,
 }
 
+struct InlineBlameLayout {
+    display_row: u32,
+    x: Pixels,
+    element: AnyElement,
+}
+
 struct PositionMap {
     size: Size<Pixels>,
     line_height: Pixels,
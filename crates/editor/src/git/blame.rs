@@ -0,0 +1,241 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashMap;
+use gpui::{AppContext, Model, ModelContext, Subscription, Task};
+use language::{Anchor, Buffer, Point, ToPoint as _};
+use project::{repository::BlameEntry, Project};
+use time::{OffsetDateTime, UtcOffset};
+use url::Url;
+
+use super::permalink::{build_commit_permalink, BuildCommitPermalinkParams};
+
+const REGENERATE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The git blame of a buffer, which is regenerated whenever the buffer or
+/// its repository changes.
+pub struct GitBlame {
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    entries: Vec<(Range<Anchor>, BlameEntry)>,
+    messages: HashMap<String, String>,
+    remote_url: Option<String>,
+    generate_task: Task<()>,
+    _subscription: Subscription,
+}
+
+impl GitBlame {
+    pub fn new(
+        buffer: Model<Buffer>,
+        project: Model<Project>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&buffer, |this, _, event, cx| match event {
+            language::Event::Edited
+            | language::Event::Saved
+            | language::Event::Reloaded
+            | language::Event::FileHandleChanged
+            | language::Event::DiffBaseChanged => this.schedule_generate(cx),
+            _ => {}
+        });
+
+        let mut this = Self {
+            project,
+            buffer,
+            entries: Vec::new(),
+            messages: HashMap::default(),
+            remote_url: None,
+            generate_task: Task::ready(()),
+            _subscription: subscription,
+        };
+        this.generate(cx);
+        this
+    }
+
+    pub fn has_entries(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    /// Returns the blame entry covering the given row of the buffer.
+    pub fn entry_for_row(&self, row: u32, cx: &AppContext) -> Option<&BlameEntry> {
+        let buffer = self.buffer.read(cx);
+        let point = Point::new(row, 0);
+        let ix = self
+            .entries
+            .partition_point(|(range, _)| range.end.to_point(buffer) <= point);
+        let (range, entry) = self.entries.get(ix)?;
+        (range.start.to_point(buffer) <= point).then_some(entry)
+    }
+
+    /// Returns the full message of the entry's commit.
+    pub fn message_for_entry(&self, entry: &BlameEntry) -> Option<&str> {
+        self.messages.get(&entry.sha).map(String::as_str)
+    }
+
+    /// Returns a link to the entry's commit on the repository's hosting provider.
+    pub fn permalink_for_entry(&self, entry: &BlameEntry) -> Option<Url> {
+        if entry.is_uncommitted() {
+            return None;
+        }
+        build_commit_permalink(BuildCommitPermalinkParams {
+            remote_url: self.remote_url.as_deref()?,
+            sha: &entry.sha,
+        })
+        .ok()
+    }
+
+    fn schedule_generate(&mut self, cx: &mut ModelContext<Self>) {
+        self.generate_task = cx.spawn(|this, mut cx| async move {
+            cx.background_executor().timer(REGENERATE_DEBOUNCE).await;
+            this.update(&mut cx, |this, cx| this.generate(cx)).ok();
+        });
+    }
+
+    fn generate(&mut self, cx: &mut ModelContext<Self>) {
+        let snapshot = self.buffer.read(cx).text_snapshot();
+        let blame = self
+            .project
+            .update(cx, |project, cx| project.blame_buffer(&self.buffer, cx));
+        self.generate_task = cx.spawn(|this, mut cx| async move {
+            let blame = match blame.await {
+                Ok(blame) => blame,
+                Err(error) => {
+                    log::debug!("failed to blame buffer: {:?}", error);
+                    return;
+                }
+            };
+            this.update(&mut cx, |this, cx| {
+                this.entries = blame
+                    .entries
+                    .into_iter()
+                    .map(|entry| {
+                        let start = snapshot.anchor_before(Point::new(entry.range.start, 0));
+                        let end = snapshot.anchor_before(Point::new(entry.range.end, 0));
+                        (start..end, entry)
+                    })
+                    .collect();
+                this.messages = blame.messages;
+                this.remote_url = blame.remote_url;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+/// Formats the entry as "author, date", as shown in the gutter and inline.
+pub fn blame_entry_author_and_date(entry: &BlameEntry) -> String {
+    if entry.is_uncommitted() {
+        return "You, Uncommitted changes".to_string();
+    }
+
+    let author = entry.author.as_deref().unwrap_or("Unknown author");
+    let date = entry.author_time.and_then(|time| {
+        let offset = UtcOffset::from_whole_seconds(entry.author_offset_minutes? * 60).ok()?;
+        let date = OffsetDateTime::from_unix_timestamp(time).ok()?;
+        Some(date.to_offset(offset).date())
+    });
+    match date {
+        Some(date) => format!("{author}, {date}"),
+        None => author.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use gpui::Context as _;
+    use project::{repository::Blame, FakeFs};
+    use serde_json::json;
+    use std::path::Path;
+
+    fn blame_entry(sha: &str, range: Range<u32>, summary: &str) -> BlameEntry {
+        BlameEntry {
+            sha: sha.to_string(),
+            range,
+            author: Some("Someone".to_string()),
+            author_mail: Some("someone@example.com".to_string()),
+            author_time: Some(1_700_000_000),
+            author_offset_minutes: Some(60),
+            summary: Some(summary.to_string()),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_git_blame(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "file.txt": "one\ntwo\nthree\nfour\n",
+            }),
+        )
+        .await;
+        fs.set_head_for_repo(
+            Path::new("/root/.git"),
+            &[(Path::new("file.txt"), "one\ntwo\nthree\nfour\n".to_string())],
+        );
+        fs.set_blame_for_repo(
+            Path::new("/root/.git"),
+            vec![(
+                Path::new("file.txt"),
+                Blame {
+                    entries: vec![
+                        blame_entry("1b1b1b", 0..2, "First commit"),
+                        blame_entry("2c2c2c", 2..4, "Second commit"),
+                    ],
+                    messages: [("2c2c2c".to_string(), "Second commit\n\nDetails".to_string())]
+                        .into_iter()
+                        .collect(),
+                    remote_url: Some("git@github.com:zed-industries/zed.git".to_string()),
+                },
+            )],
+        );
+
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/file.txt", cx)
+            })
+            .await
+            .unwrap();
+
+        let blame = cx.new_model(|cx| GitBlame::new(buffer.clone(), project, cx));
+        cx.executor().run_until_parked();
+
+        blame.read_with(cx, |blame, cx| {
+            assert_eq!(blame.entry_for_row(1, cx).unwrap().sha, "1b1b1b");
+            let entry = blame.entry_for_row(3, cx).unwrap();
+            assert_eq!(entry.sha, "2c2c2c");
+            assert_eq!(
+                blame.message_for_entry(entry),
+                Some("Second commit\n\nDetails")
+            );
+            assert_eq!(
+                blame.permalink_for_entry(entry).unwrap().to_string(),
+                "https://github.com/zed-industries/zed/commit/2c2c2c"
+            );
+            assert_eq!(blame_entry_author_and_date(entry), "Someone, 2023-11-14");
+        });
+
+        // Entries follow the lines they were computed for as the buffer is edited.
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        blame.read_with(cx, |blame, cx| {
+            assert_eq!(blame.entry_for_row(4, cx).unwrap().sha, "2c2c2c");
+        });
+
+        // Once regenerated, the new line is uncommitted and the others keep their commits.
+        cx.executor().advance_clock(REGENERATE_DEBOUNCE);
+        cx.executor().run_until_parked();
+        blame.read_with(cx, |blame, cx| {
+            assert!(blame.entry_for_row(0, cx).unwrap().is_uncommitted());
+            assert_eq!(blame.entry_for_row(1, cx).unwrap().sha, "1b1b1b");
+            assert_eq!(blame.entry_for_row(2, cx).unwrap().sha, "1b1b1b");
+            assert_eq!(blame.entry_for_row(3, cx).unwrap().sha, "2c2c2c");
+            assert_eq!(blame.entry_for_row(4, cx).unwrap().sha, "2c2c2c");
+        });
+    }
+}
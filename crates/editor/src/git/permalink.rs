@@ -78,6 +78,30 @@ pub fn build_permalink(params: BuildPermalinkParams) -> Result<Url> {
     Ok(permalink)
 }
 
+pub struct BuildCommitPermalinkParams<'a> {
+    pub remote_url: &'a str,
+    pub sha: &'a str,
+}
+
+pub fn build_commit_permalink(params: BuildCommitPermalinkParams) -> Result<Url> {
+    let BuildCommitPermalinkParams { remote_url, sha } = params;
+
+    let ParsedGitRemote {
+        provider,
+        owner,
+        repo,
+    } = parse_git_remote_url(remote_url)
+        .ok_or_else(|| anyhow!("failed to parse Git remote URL"))?;
+
+    let path = match provider {
+        GitHostingProvider::Github => format!("{owner}/{repo}/commit/{sha}"),
+        GitHostingProvider::Gitlab => format!("{owner}/{repo}/-/commit/{sha}"),
+        GitHostingProvider::Gitee => format!("{owner}/{repo}/commit/{sha}"),
+    };
+
+    Ok(provider.base_url().join(&path).unwrap())
+}
+
 struct ParsedGitRemote<'a> {
     pub provider: GitHostingProvider,
     pub owner: &'a str,
@@ -137,6 +161,31 @@ fn parse_git_remote_url(url: &str) -> Option<ParsedGitRemote> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_build_github_commit_permalink() {
+        let permalink = build_commit_permalink(BuildCommitPermalinkParams {
+            remote_url: "git@github.com:zed-industries/zed.git",
+            sha: "e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7",
+        })
+        .unwrap();
+
+        let expected_url =
+            "https://github.com/zed-industries/zed/commit/e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7";
+        assert_eq!(permalink.to_string(), expected_url.to_string())
+    }
+
+    #[test]
+    fn test_build_gitlab_commit_permalink() {
+        let permalink = build_commit_permalink(BuildCommitPermalinkParams {
+            remote_url: "https://gitlab.com/zed-industries/zed.git",
+            sha: "e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7",
+        })
+        .unwrap();
+
+        let expected_url = "https://gitlab.com/zed-industries/zed/-/commit/e6ebe7974deb6bb6cc0e2595c8ec31f0c71084b7";
+        assert_eq!(permalink.to_string(), expected_url.to_string())
+    }
+
     #[test]
     fn test_build_github_permalink_from_ssh_url() {
         let permalink = build_permalink(BuildPermalinkParams {
@@ -13,7 +13,7 @@ use gpui::{
 };
 use language::{
     proto::serialize_anchor as serialize_text_anchor, Bias, Buffer, CharKind, OffsetRangeExt,
-    Point, SelectionGoal,
+    Point, SelectionGoal, SerializedHistory,
 };
 use project::repository::GitFileStatus;
 use project::{search::SearchQuery, FormatTrigger, Item as _, Project, ProjectPath};
@@ -29,6 +29,7 @@ use std::{
     ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, UNIX_EPOCH},
 };
 use text::{BufferId, Selection};
 use theme::Theme;
@@ -552,6 +553,14 @@ impl Item for Editor {
 
             drop(buffer);
 
+            if let Some(closed_state) = data.closed_state {
+                let nav_history = self.nav_history.take();
+                self.set_scroll_anchor(scroll_anchor, cx);
+                self.restore_serialized_state(closed_state, cx);
+                self.nav_history = nav_history;
+                return true;
+            }
+
             if newest_selection.head() == offset {
                 false
             } else {
@@ -675,6 +684,18 @@ impl Item for Editor {
         self.push_to_nav_history(selection.head(), None, cx);
     }
 
+    fn closed(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace_id) = self.workspace.as_ref().map(|(_, id)| *id) else {
+            return;
+        };
+        let item_id = cx.view().item_id().as_u64() as ItemId;
+        self.serialize_selections = Task::ready(());
+        self.serialize_folds = Task::ready(());
+        cx.background_executor()
+            .spawn(async move { DB.delete_editor(item_id, workspace_id).await.log_err() })
+            .detach();
+    }
+
     fn workspace_deactivated(&mut self, cx: &mut ViewContext<Self>) {
         hide_link_definition(self, cx);
         self.link_go_to_definition_state.last_trigger_point = None;
@@ -706,8 +727,11 @@ impl Item for Editor {
 
             if buffers.len() == 1 {
                 project
-                    .update(&mut cx, |project, cx| project.save_buffers(buffers, cx))?
+                    .update(&mut cx, |project, cx| {
+                        project.save_buffers(buffers.clone(), cx)
+                    })?
                     .await?;
+                cx.update(|cx| serialize_undo_history(&buffers[0], cx))?;
             } else {
                 // For multi-buffers, only save those ones that contain changes. For clean buffers
                 // we simulate saving by calling `Buffer::did_save`, so that language servers or
@@ -758,8 +782,12 @@ impl Item for Editor {
             .map(|a| a.to_string_lossy().to_string());
         self.report_editor_event("save", file_extension, cx);
 
-        project.update(cx, |project, cx| {
-            project.save_buffer_as(buffer, abs_path, cx)
+        let save = project.update(cx, |project, cx| {
+            project.save_buffer_as(buffer.clone(), abs_path, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            save.await?;
+            cx.update(|cx| serialize_undo_history(&buffer, cx))
         })
     }
 
@@ -839,22 +867,33 @@ impl Item for Editor {
             workspace_id: WorkspaceId,
             item_id: ItemId,
             cx: &mut AppContext,
-        ) {
+        ) -> Task<()> {
             if let Some(file) = buffer.read(cx).file().and_then(|file| file.as_local()) {
                 let path = file.abs_path(cx);
 
-                cx.background_executor()
-                    .spawn(async move {
-                        DB.save_path(item_id, workspace_id, path.clone())
-                            .await
-                            .log_err()
-                    })
-                    .detach();
+                cx.background_executor().spawn(async move {
+                    DB.save_path(item_id, workspace_id, path.clone())
+                        .await
+                        .log_err();
+                })
+            } else {
+                Task::ready(())
             }
         }
 
         if let Some(buffer) = self.buffer().read(cx).as_singleton() {
-            serialize(buffer.clone(), workspace_id, item_id, cx);
+            let save_path = serialize(buffer.clone(), workspace_id, item_id, cx);
+
+            // Store any state restored before the editor was added, under its new item id.
+            // The scroll position is stored in the row inserted along with the path.
+            cx.spawn(|this, mut cx| async move {
+                save_path.await;
+                this.update(&mut cx, |this, cx| this.serialize_scroll_position(cx))
+                    .ok();
+            })
+            .detach();
+            self.serialize_selections(cx);
+            self.serialize_folds(cx);
 
             cx.subscribe(&buffer, |this, buffer, event, cx| {
                 if let Some((_, workspace_id)) = this.workspace.as_ref() {
@@ -864,7 +903,8 @@ impl Item for Editor {
                             *workspace_id,
                             cx.view().item_id().as_u64() as ItemId,
                             cx,
-                        );
+                        )
+                        .detach();
                     }
                 }
             })
@@ -876,6 +916,53 @@ impl Item for Editor {
         Some("Editor")
     }
 
+    fn serialize_unsaved_changes(
+        &mut self,
+        workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        let buffer = self.buffer().read(cx).as_singleton()?;
+        let buffer = buffer.read(cx);
+        let path = match buffer.file() {
+            // Only restore files whose worktree will be reopened with the workspace.
+            Some(file) => {
+                let file = project::File::from_dyn(Some(file))?;
+                if !file.worktree.read(cx).is_visible() {
+                    return None;
+                }
+                Some(file.as_local()?.abs_path(cx))
+            }
+            None => None,
+        };
+        let contents = buffer.text();
+        // Remember the state of the file the contents are based on, so that changes
+        // made to the file in the meantime can be detected when restoring them.
+        let (saved_fingerprint, saved_mtime) = if path.is_some() {
+            (
+                Some(language::proto::serialize_fingerprint(
+                    buffer.saved_version_fingerprint(),
+                )),
+                buffer.saved_mtime().duration_since(UNIX_EPOCH).ok(),
+            )
+        } else {
+            (None, None)
+        };
+        let item_id = cx.view().item_id().as_u64() as ItemId;
+
+        Some(cx.background_executor().spawn(async move {
+            DB.save_contents(
+                item_id,
+                workspace_id,
+                path,
+                contents,
+                saved_fingerprint,
+                saved_mtime.map(|mtime| mtime.as_secs() as i64),
+                saved_mtime.map(|mtime| mtime.subsec_nanos()),
+            )
+            .await
+        }))
+    }
+
     fn to_item_events(event: &EditorEvent, mut f: impl FnMut(ItemEvent)) {
         match event {
             EditorEvent::Closed => f(ItemEvent::CloseItem),
@@ -917,11 +1004,22 @@ impl Item for Editor {
         item_id: ItemId,
         cx: &mut ViewContext<Pane>,
     ) -> Task<Result<View<Self>>> {
+        let contents = match DB.get_contents(item_id, workspace_id) {
+            Ok(contents) => contents,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
         let project_item: Result<_> = project.update(cx, |project, cx| {
             // Look up the path with this key associated, create a self with that path
-            let path = DB
-                .get_path(item_id, workspace_id)?
-                .context("No path stored for this editor")?;
+            let path = match DB.get_path(item_id, workspace_id)? {
+                Some(path) => path,
+                // Untitled editors are only stored when they have unsaved contents.
+                None if contents.is_some() => {
+                    let buffer = project.create_buffer("", None, cx)?;
+                    return Ok(Task::ready(Ok((None, buffer.into()))));
+                }
+                None => return Err(anyhow!("No path stored for this editor")),
+            };
 
             let (worktree, path) = project
                 .find_local_worktree(&path, cx)
@@ -941,14 +1039,59 @@ impl Item for Editor {
                     let buffer = project_item
                         .downcast::<Buffer>()
                         .map_err(|_| anyhow!("Project item at stored path was not a buffer"))?;
-                    Ok(pane.update(&mut cx, |_, cx| {
-                        cx.new_view(|cx| {
-                            let mut editor = Editor::for_buffer(buffer, Some(project), cx);
+                    // Restore the undo history before the unsaved changes are applied,
+                    // since it must lead to the contents on disk.
+                    cx.update(|cx| restore_undo_history(&project, &buffer, cx))?
+                        .await;
+                    let editor = pane.update(&mut cx, |_, cx| {
+                        cx.new_view(|cx| Editor::for_buffer(buffer.clone(), Some(project), cx))
+                    })?;
+
+                    // Bring back the unsaved changes as edits on top of the file on disk,
+                    // so the buffer is restored dirty.
+                    if let Some((contents, saved_fingerprint, mtime_seconds, mtime_nanos)) =
+                        contents
+                    {
+                        let saved_fingerprint = saved_fingerprint.and_then(|fingerprint| {
+                            language::proto::deserialize_fingerprint(&fingerprint).log_err()
+                        });
+                        let saved_mtime = mtime_seconds.zip(mtime_nanos).map(|(seconds, nanos)| {
+                            UNIX_EPOCH + Duration::new(seconds as u64, nanos)
+                        });
+                        let diff = buffer
+                            .update(&mut cx, |buffer, cx| buffer.diff(contents, cx))?
+                            .await;
+                        buffer.update(&mut cx, |buffer, cx| {
+                            let disk_fingerprint = buffer.saved_version_fingerprint();
+                            buffer.apply_diff(diff, cx);
+
+                            // If the file changed on disk after the contents were stored, keep
+                            // them based on the old file, so that the buffer has a conflict
+                            // instead of silently reverting the file's changes when saved.
+                            if let Some((saved_fingerprint, saved_mtime)) =
+                                saved_fingerprint.zip(saved_mtime)
+                            {
+                                if saved_fingerprint != disk_fingerprint {
+                                    let saved_version = buffer.saved_version().clone();
+                                    let line_ending = buffer.line_ending();
+                                    buffer.did_reload(
+                                        saved_version,
+                                        saved_fingerprint,
+                                        line_ending,
+                                        saved_mtime,
+                                        cx,
+                                    );
+                                }
+                            }
+                        })?;
+                        DB.clear_contents(item_id, workspace_id).await.log_err();
+                    }
 
-                            editor.read_scroll_position_from_db(item_id, workspace_id, cx);
-                            editor
-                        })
-                    })?)
+                    editor.update(&mut cx, |editor, cx| {
+                        editor.read_serialized_state_from_db(item_id, workspace_id, cx);
+                        editor.read_scroll_position_from_db(item_id, workspace_id, cx);
+                    })?;
+                    Ok(editor)
                 })
             })
             .unwrap_or_else(|error| Task::ready(Err(error)))
@@ -963,10 +1106,106 @@ impl ProjectItem for Editor {
         buffer: Model<Buffer>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
+        restore_undo_history(&project, &buffer, cx).detach();
         Self::for_buffer(buffer, Some(project), cx)
     }
 }
 
+/// Stores the undo history of a saved buffer, keyed by its path and the fingerprint
+/// of its contents on disk.
+fn serialize_undo_history(buffer: &Model<Buffer>, cx: &mut AppContext) {
+    if !EditorSettings::get_global(cx).persistent_undo {
+        return;
+    }
+
+    let buffer = buffer.read(cx);
+    let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+        return;
+    };
+    if buffer.is_dirty() {
+        return;
+    }
+    let path = file.abs_path(cx);
+    let fingerprint = language::proto::serialize_fingerprint(buffer.saved_version_fingerprint());
+    let history = buffer.history_snapshot();
+
+    cx.background_executor()
+        .spawn(async move {
+            let history = serde_json::to_string(&history.serialize()?)?;
+            DB.save_undo_history(path, fingerprint, history).await
+        })
+        .detach_and_log_err(cx);
+}
+
+/// Restores the stored undo history of a freshly opened buffer, unless the file has
+/// changed since the history was stored. The history is loaded and parsed in the
+/// background, and only applied if the buffer hasn't been edited in the meantime.
+fn restore_undo_history(
+    project: &Model<Project>,
+    buffer: &Model<Buffer>,
+    cx: &mut AppContext,
+) -> Task<Option<()>> {
+    if !EditorSettings::get_global(cx).persistent_undo {
+        return Task::ready(None);
+    }
+
+    let (path, fingerprint, version) = {
+        let buffer = buffer.read(cx);
+        let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+            return Task::ready(None);
+        };
+        if buffer.is_dirty()
+            || buffer.peek_undo_stack().is_some()
+            || project.read(cx).is_buffer_shared(buffer.remote_id())
+        {
+            return Task::ready(None);
+        }
+        (
+            file.abs_path(cx),
+            language::proto::serialize_fingerprint(buffer.saved_version_fingerprint()),
+            buffer.version(),
+        )
+    };
+
+    let project = project.downgrade();
+    let buffer = buffer.downgrade();
+    cx.spawn(|mut cx| {
+        async move {
+            let history = cx
+                .background_executor()
+                .spawn(async move {
+                    let Some((stored_fingerprint, history)) = DB.get_undo_history(path.clone())?
+                    else {
+                        return Ok(None);
+                    };
+                    if stored_fingerprint != fingerprint {
+                        DB.delete_undo_history(path).await?;
+                        return Ok(None);
+                    }
+                    anyhow::Ok(Some(serde_json::from_str::<SerializedHistory>(&history)?))
+                })
+                .await?;
+            let Some(history) = history else {
+                return Ok(());
+            };
+
+            let project = project.upgrade().context("project was dropped")?;
+            buffer.update(&mut cx, |buffer, cx| {
+                // The restored history isn't sent to collaborators, who would otherwise
+                // receive operations depending on it.
+                if buffer.version() != version
+                    || buffer.peek_undo_stack().is_some()
+                    || project.read(cx).is_buffer_shared(buffer.remote_id())
+                {
+                    return Ok(());
+                }
+                buffer.restore_history(history)
+            })?
+        }
+        .log_err()
+    })
+}
+
 impl EventEmitter<SearchEvent> for Editor {}
 
 pub(crate) enum BufferSearchHighlights {}
@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use anyhow::Result;
+
 use db::sqlez_macros::sql;
 use db::{define_connection, query};
 
@@ -10,10 +12,34 @@ define_connection!(
     // editors(
     //   item_id: usize,
     //   workspace_id: usize,
-    //   path: PathBuf,
+    //   path: Option<PathBuf>,
     //   scroll_top_row: usize,
     //   scroll_vertical_offset: f32,
     //   scroll_horizontal_offset: f32,
+    //   contents: Option<String>,
+    //   saved_fingerprint: Option<String>,
+    //   saved_mtime_seconds: Option<i64>,
+    //   saved_mtime_nanos: Option<u32>,
+    // )
+    //
+    // editor_selections(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   start: usize,
+    //   end: usize,
+    // )
+    //
+    // editor_folds(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   start: usize,
+    //   end: usize,
+    // )
+    //
+    // undo_histories(
+    //   path: PathBuf,
+    //   fingerprint: String,
+    //   history: String,
     // )
     pub static ref DB: EditorDb<WorkspaceDb> =
         &[sql! (
@@ -31,6 +57,68 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN scroll_top_row INTEGER NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_horizontal_offset REAL NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_vertical_offset REAL NOT NULL DEFAULT 0;
+        ),
+        // Allow untitled editors to be stored, and keep their unsaved contents.
+        sql! (
+            CREATE TABLE editors_2(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                path BLOB,
+                scroll_top_row INTEGER NOT NULL DEFAULT 0,
+                scroll_horizontal_offset REAL NOT NULL DEFAULT 0,
+                scroll_vertical_offset REAL NOT NULL DEFAULT 0,
+                contents TEXT,
+                PRIMARY KEY(item_id, workspace_id),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+            INSERT INTO editors_2 (item_id, workspace_id, path, scroll_top_row, scroll_horizontal_offset, scroll_vertical_offset)
+            SELECT item_id, workspace_id, path, scroll_top_row, scroll_horizontal_offset, scroll_vertical_offset FROM editors;
+            DROP TABLE editors;
+            ALTER TABLE editors_2 RENAME TO editors;
+        ),
+        // Selections and folds are stored as buffer offsets, which are clipped when
+        // the file has changed on disk by the time the editor is restored.
+        sql! (
+            CREATE TABLE editor_selections(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                start INTEGER NOT NULL,
+                end INTEGER NOT NULL,
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+            CREATE TABLE editor_folds(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                start INTEGER NOT NULL,
+                end INTEGER NOT NULL,
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
+        // Undo histories are keyed by file rather than by editor, and only apply to the
+        // saved contents of the file with the given fingerprint.
+        sql! (
+            CREATE TABLE undo_histories(
+                path BLOB NOT NULL PRIMARY KEY,
+                fingerprint TEXT NOT NULL,
+                history TEXT NOT NULL
+            ) STRICT;
+        ),
+        // Unsaved contents are stored along with the state of the file on disk that
+        // they were based on, to detect changes made to the file in the meantime.
+        sql! (
+            ALTER TABLE editors ADD COLUMN saved_fingerprint TEXT;
+            ALTER TABLE editors ADD COLUMN saved_mtime_seconds INTEGER;
+            ALTER TABLE editors ADD COLUMN saved_mtime_nanos INTEGER;
+        ),
+        sql! (
+            CREATE INDEX editor_selections_item ON editor_selections(item_id, workspace_id);
+            CREATE INDEX editor_folds_item ON editor_folds(item_id, workspace_id);
         )];
 );
 
@@ -38,7 +126,7 @@ impl EditorDb {
     query! {
         pub fn get_path(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
             SELECT path FROM editors
-            WHERE item_id = ? AND workspace_id = ?
+            WHERE item_id = ? AND workspace_id = ? AND path IS NOT NULL
         }
     }
 
@@ -55,6 +143,49 @@ impl EditorDb {
         }
     }
 
+    // Returns the unsaved contents, and the fingerprint and mtime of the file they were based on
+    query! {
+        pub fn get_contents(
+            item_id: ItemId,
+            workspace_id: WorkspaceId
+        ) -> Result<Option<(String, Option<String>, Option<i64>, Option<u32>)>> {
+            SELECT contents, saved_fingerprint, saved_mtime_seconds, saved_mtime_nanos
+            FROM editors
+            WHERE item_id = ? AND workspace_id = ? AND contents IS NOT NULL
+        }
+    }
+
+    query! {
+        pub async fn save_contents(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            path: Option<PathBuf>,
+            contents: String,
+            saved_fingerprint: Option<String>,
+            saved_mtime_seconds: Option<i64>,
+            saved_mtime_nanos: Option<u32>
+        ) -> Result<()> {
+            INSERT INTO editors
+                (item_id, workspace_id, path, contents, saved_fingerprint, saved_mtime_seconds, saved_mtime_nanos)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT DO UPDATE SET
+                path = ?3,
+                contents = ?4,
+                saved_fingerprint = ?5,
+                saved_mtime_seconds = ?6,
+                saved_mtime_nanos = ?7
+        }
+    }
+
+    query! {
+        pub async fn clear_contents(item_id: ItemId, workspace_id: WorkspaceId) -> Result<()> {
+            UPDATE editors
+            SET contents = NULL, saved_fingerprint = NULL, saved_mtime_seconds = NULL, saved_mtime_nanos = NULL
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
     // Returns the scroll top row, and offset
     query! {
         pub fn get_scroll_position(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(u32, f32, f32)>> {
@@ -80,4 +211,99 @@ impl EditorDb {
             WHERE item_id = ?1 AND workspace_id = ?2
         }
     }
+
+    query! {
+        pub async fn delete_editor(item_id: ItemId, workspace_id: WorkspaceId) -> Result<()> {
+            DELETE FROM editors WHERE item_id = ?1 AND workspace_id = ?2;
+            DELETE FROM editor_selections WHERE item_id = ?1 AND workspace_id = ?2;
+            DELETE FROM editor_folds WHERE item_id = ?1 AND workspace_id = ?2;
+        }
+    }
+
+    // Returns the selections as (tail, head) offsets
+    query! {
+        pub fn get_selections(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Vec<(usize, usize)>> {
+            SELECT start, end FROM editor_selections
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    pub async fn save_selections(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        selections: Vec<(usize, usize)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_editor_selections", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_selections WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO editor_selections (item_id, workspace_id, start, end)
+                    VALUES (?, ?, ?, ?)
+                ))?;
+                for (start, end) in selections {
+                    insert((item_id, workspace_id, start, end))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_folds(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Vec<(usize, usize)>> {
+            SELECT start, end FROM editor_folds
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
+
+    pub async fn save_folds(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        folds: Vec<(usize, usize)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_editor_folds", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_folds WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))?;
+                let mut insert = conn.exec_bound(sql!(
+                    INSERT INTO editor_folds (item_id, workspace_id, start, end)
+                    VALUES (?, ?, ?, ?)
+                ))?;
+                for (start, end) in folds {
+                    insert((item_id, workspace_id, start, end))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    // Returns the fingerprint of the saved contents, and the serialized history
+    query! {
+        pub fn get_undo_history(path: PathBuf) -> Result<Option<(String, String)>> {
+            SELECT fingerprint, history FROM undo_histories
+            WHERE path = ?
+        }
+    }
+
+    query! {
+        pub async fn save_undo_history(path: PathBuf, fingerprint: String, history: String) -> Result<()> {
+            INSERT OR REPLACE INTO undo_histories
+                (path, fingerprint, history)
+            VALUES
+                (?1, ?2, ?3)
+        }
+    }
+
+    query! {
+        pub async fn delete_undo_history(path: PathBuf) -> Result<()> {
+            DELETE FROM undo_histories
+            WHERE path = ?
+        }
+    }
 }
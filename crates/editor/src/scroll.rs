@@ -466,11 +466,12 @@ impl Editor {
     ) {
         let scroll_position = DB.get_scroll_position(item_id, workspace_id);
         if let Ok(Some((top_row, x, y))) = scroll_position {
-            let top_anchor = self
-                .buffer()
-                .read(cx)
-                .snapshot(cx)
-                .anchor_at(Point::new(top_row as u32, 0), Bias::Left);
+            let buffer = self.buffer().read(cx).snapshot(cx);
+            // The file may have been shortened since the position was stored.
+            let top_anchor = buffer.anchor_at(
+                buffer.clip_point(Point::new(top_row, 0), Bias::Left),
+                Bias::Left,
+            );
             let scroll_anchor = ScrollAnchor {
                 offset: gpui::Point::new(x, y),
                 anchor: top_anchor,
@@ -478,4 +479,27 @@ impl Editor {
             self.set_scroll_anchor(scroll_anchor, cx);
         }
     }
+
+    pub(crate) fn serialize_scroll_position(&mut self, cx: &mut ViewContext<Editor>) {
+        let Some(workspace_id) = self.workspace.as_ref().map(|workspace| workspace.1) else {
+            return;
+        };
+        let item_id = cx.view().entity_id().as_u64() as ItemId;
+        let anchor = self.scroll_manager.anchor();
+        let top_row = anchor.top_row(&self.buffer().read(cx).snapshot(cx));
+
+        cx.background_executor()
+            .spawn(async move {
+                DB.save_scroll_position(
+                    item_id,
+                    workspace_id,
+                    top_row,
+                    anchor.offset.x,
+                    anchor.offset.y,
+                )
+                .await
+                .log_err()
+            })
+            .detach()
+    }
 }
@@ -0,0 +1,567 @@
+use crate::{
+    display_map::ToDisplayPoint, hover_popover::parse_blocks, Anchor, DisplayPoint, Editor,
+    EditorSnapshot, EditorStyle, ShowSignatureHelp, SignatureHelpNext, SignatureHelpPrev,
+};
+use gpui::{
+    div, AnyElement, FontWeight, HighlightStyle, InteractiveElement, IntoElement, ParentElement,
+    Pixels, Size, StatefulInteractiveElement, Styled, StyledText, Task, ViewContext, WeakView,
+};
+use language::{Language, LanguageRegistry, ParsedMarkdown, ToPointUtf16};
+use project::{HoverBlock, MarkupContent, SignatureHelp};
+use std::{ops::Range, sync::Arc, time::Duration};
+use ui::{prelude::*, IconButton};
+use util::TryFutureExt;
+use workspace::Workspace;
+
+/// How long the cursor has to rest before signature help is re-queried for its new position.
+pub const SIGNATURE_HELP_REFRESH_DELAY_MILLIS: u64 = 100;
+
+/// Bindable action which requests signature help for the call surrounding the newest cursor.
+pub fn show_signature_help(
+    editor: &mut Editor,
+    _: &ShowSignatureHelp,
+    cx: &mut ViewContext<Editor>,
+) {
+    request_signature_help(editor, None, false, cx);
+}
+
+/// Requests signature help after `text` was typed, if it's one of the language server's
+/// trigger or retrigger characters. When signature help is already being displayed, the
+/// request is sent as a retrigger right away, instead of waiting for the cursor to rest.
+pub fn trigger_signature_help_on_input(
+    editor: &mut Editor,
+    text: &str,
+    cx: &mut ViewContext<Editor>,
+) {
+    if text.is_empty() {
+        return;
+    }
+
+    let Some(project) = editor.project.as_ref() else {
+        return;
+    };
+    let position = editor.selections.newest_anchor().head();
+    let Some((buffer, _)) = editor
+        .buffer
+        .read(cx)
+        .text_anchor_for_position(position, cx)
+    else {
+        return;
+    };
+
+    let is_trigger = project
+        .read(cx)
+        .signature_help_trigger_characters(&buffer, cx)
+        .iter()
+        .any(|trigger| text.ends_with(trigger.as_str()));
+    if is_trigger {
+        let trigger_character = text.chars().last().map(|ch| ch.to_string());
+        let is_retrigger = editor.signature_help_state.is_shown();
+        request_signature_help(editor, trigger_character, is_retrigger, cx);
+    }
+}
+
+/// Re-queries signature help after the cursor moved while the popover is visible, so that the
+/// active parameter follows the cursor and the popover is dismissed once the cursor leaves
+/// the call. The request is debounced, so that moving the cursor quickly only sends one.
+pub fn refresh_signature_help(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.signature_help_state.is_shown() {
+        request_signature_help(editor, None, true, cx);
+    }
+}
+
+/// Hides the signature help popover, returning whether it was visible.
+pub fn hide_signature_help(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> bool {
+    editor.signature_help_state.task = None;
+    let did_hide = editor.signature_help_state.popover.take().is_some();
+    if did_hide {
+        cx.notify();
+    }
+    did_hide
+}
+
+pub fn signature_help_next(
+    editor: &mut Editor,
+    _: &SignatureHelpNext,
+    cx: &mut ViewContext<Editor>,
+) {
+    cycle_signature(editor, 1, cx);
+}
+
+pub fn signature_help_prev(
+    editor: &mut Editor,
+    _: &SignatureHelpPrev,
+    cx: &mut ViewContext<Editor>,
+) {
+    cycle_signature(editor, -1, cx);
+}
+
+fn cycle_signature(editor: &mut Editor, delta: isize, cx: &mut ViewContext<Editor>) {
+    let Some(popover) = editor.signature_help_state.popover.as_mut() else {
+        cx.propagate();
+        return;
+    };
+    let count = popover.signature_help.signatures.len() as isize;
+    if count > 1 {
+        popover.active_signature =
+            (popover.active_signature as isize + delta).rem_euclid(count) as usize;
+        cx.notify();
+    }
+}
+
+fn request_signature_help(
+    editor: &mut Editor,
+    trigger_character: Option<String>,
+    is_retrigger: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    if editor.pending_rename.is_some() {
+        return;
+    }
+
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let position = editor.selections.newest_anchor().head();
+    let Some((buffer, buffer_position)) = editor
+        .buffer
+        .read(cx)
+        .text_anchor_for_position(position, cx)
+    else {
+        return;
+    };
+
+    let buffer_position = buffer_position.to_point_utf16(buffer.read(cx));
+    let follows_cursor = is_retrigger && trigger_character.is_none();
+    let task = cx.spawn(|this, mut cx| {
+        async move {
+            // Retriggers that follow the cursor wait for it to rest before querying the server.
+            if follows_cursor {
+                cx.background_executor()
+                    .timer(Duration::from_millis(SIGNATURE_HELP_REFRESH_DELAY_MILLIS))
+                    .await;
+            }
+
+            let signature_help_request = project.update(&mut cx, |project, cx| {
+                project.signature_help(
+                    &buffer,
+                    buffer_position,
+                    trigger_character,
+                    is_retrigger,
+                    cx,
+                )
+            })?;
+            let signature_help = signature_help_request.await.ok().flatten();
+            let Some(signature_help) = signature_help.filter(|help| !help.is_empty()) else {
+                this.update(&mut cx, |this, cx| {
+                    hide_signature_help(this, cx);
+                })?;
+                return Ok(());
+            };
+
+            let language_registry = project.update(&mut cx, |p, _| p.languages().clone())?;
+            let language = buffer.update(&mut cx, |buffer, _| buffer.language().cloned())?;
+            let mut parsed_documentation = Vec::with_capacity(signature_help.signatures.len());
+            let mut parsed_parameter_documentation =
+                Vec::with_capacity(signature_help.signatures.len());
+            for signature in &signature_help.signatures {
+                parsed_documentation.push(
+                    parse_documentation(
+                        signature.documentation.as_ref(),
+                        &language_registry,
+                        language.clone(),
+                    )
+                    .await,
+                );
+                let mut parameters = Vec::with_capacity(signature.parameters.len());
+                for parameter in &signature.parameters {
+                    parameters.push(
+                        parse_documentation(
+                            parameter.documentation.as_ref(),
+                            &language_registry,
+                            language.clone(),
+                        )
+                        .await,
+                    );
+                }
+                parsed_parameter_documentation.push(parameters);
+            }
+
+            this.update(&mut cx, |this, cx| {
+                // Keep the overload the user cycled to while the call is being edited.
+                let active_signature = this
+                    .signature_help_state
+                    .popover
+                    .as_ref()
+                    .filter(|previous| {
+                        is_retrigger
+                            && previous.signature_help.signatures.len()
+                                == signature_help.signatures.len()
+                            && previous
+                                .signature_help
+                                .signatures
+                                .iter()
+                                .zip(&signature_help.signatures)
+                                .all(|(previous, new)| previous.label == new.label)
+                    })
+                    .map(|previous| previous.active_signature)
+                    .unwrap_or(signature_help.active_signature);
+
+                this.signature_help_state.popover = Some(SignatureHelpPopover {
+                    anchor: position,
+                    active_signature,
+                    signature_help,
+                    parsed_documentation,
+                    parsed_parameter_documentation,
+                });
+                cx.notify();
+            })?;
+
+            anyhow::Ok(())
+        }
+        .log_err()
+    });
+
+    editor.signature_help_state.task = Some(task);
+}
+
+async fn parse_documentation(
+    documentation: Option<&MarkupContent>,
+    language_registry: &Arc<LanguageRegistry>,
+    language: Option<Arc<Language>>,
+) -> Option<ParsedMarkdown> {
+    let documentation = documentation?;
+    let block = HoverBlock {
+        text: documentation.value.clone(),
+        kind: documentation.kind.clone(),
+    };
+    Some(parse_blocks(&[block], language_registry, language).await)
+}
+
+#[derive(Default)]
+pub struct SignatureHelpState {
+    popover: Option<SignatureHelpPopover>,
+    task: Option<Task<Option<()>>>,
+}
+
+impl SignatureHelpState {
+    pub fn is_shown(&self) -> bool {
+        self.popover.is_some()
+    }
+
+    pub fn popover(&self) -> Option<&SignatureHelpPopover> {
+        self.popover.as_ref()
+    }
+
+    pub fn render(
+        &mut self,
+        snapshot: &EditorSnapshot,
+        style: &EditorStyle,
+        visible_rows: Range<u32>,
+        max_size: Size<Pixels>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Option<(DisplayPoint, AnyElement)> {
+        let popover = self.popover.as_mut()?;
+        let point = popover.anchor.to_display_point(&snapshot.display_snapshot);
+        if !visible_rows.contains(&point.row()) {
+            return None;
+        }
+
+        Some((point, popover.render(style, max_size, workspace, cx)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SignatureHelpPopover {
+    anchor: Anchor,
+    active_signature: usize,
+    pub signature_help: SignatureHelp,
+    parsed_documentation: Vec<Option<ParsedMarkdown>>,
+    /// The parsed documentation of each parameter, for each signature.
+    parsed_parameter_documentation: Vec<Vec<Option<ParsedMarkdown>>>,
+}
+
+impl SignatureHelpPopover {
+    pub fn active_signature(&self) -> usize {
+        self.active_signature
+    }
+
+    /// The label of the displayed signature and the byte range of its active parameter.
+    pub fn active_label(&self) -> Option<(&str, Option<Range<usize>>)> {
+        let signature = self.signature_help.signatures.get(self.active_signature)?;
+        let parameter = self
+            .signature_help
+            .active_parameter_for(self.active_signature)
+            .map(|parameter| parameter.label_range.clone());
+        Some((signature.label.as_str(), parameter))
+    }
+
+    pub fn render(
+        &mut self,
+        style: &EditorStyle,
+        max_size: Size<Pixels>,
+        workspace: Option<WeakView<Workspace>>,
+        cx: &mut ViewContext<Editor>,
+    ) -> AnyElement {
+        let signature_count = self.signature_help.signatures.len();
+        let (label, parameter_range) = self.active_label().unwrap_or_default();
+        let highlights = parameter_range
+            .map(|range| {
+                (
+                    range,
+                    HighlightStyle {
+                        font_weight: Some(FontWeight::BOLD),
+                        color: Some(cx.theme().colors().text_accent),
+                        ..Default::default()
+                    },
+                )
+            })
+            .into_iter();
+        let label = StyledText::new(label.to_string()).with_highlights(&style.text, highlights);
+
+        let parameter_documentation = self
+            .signature_help
+            .active_parameter_ix_for(self.active_signature)
+            .and_then(|parameter_ix| {
+                self.parsed_parameter_documentation
+                    .get(self.active_signature)?
+                    .get(parameter_ix)?
+                    .clone()
+            });
+        let signature_documentation = self
+            .parsed_documentation
+            .get(self.active_signature)
+            .cloned()
+            .flatten();
+
+        div()
+            .id("signature_help_popover")
+            .elevation_2(cx)
+            .p_2()
+            .overflow_y_scroll()
+            .max_w(max_size.width)
+            .max_h(max_size.height)
+            // Prevent a mouse move on the popover from being propagated to the editor,
+            // because that would dismiss the hover popover.
+            .on_mouse_move(|_, cx| cx.stop_propagation())
+            .child(
+                h_flex()
+                    .gap_1()
+                    .when(signature_count > 1, |this| {
+                        this.child(
+                            IconButton::new("signature_help_prev", IconName::ChevronUp)
+                                .icon_size(IconSize::Small)
+                                .on_click(cx.listener(|editor, _, cx| {
+                                    signature_help_prev(editor, &SignatureHelpPrev, cx)
+                                })),
+                        )
+                        .child(
+                            Label::new(format!(
+                                "{}/{}",
+                                self.active_signature + 1,
+                                signature_count
+                            ))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        )
+                        .child(
+                            IconButton::new("signature_help_next", IconName::ChevronDown)
+                                .icon_size(IconSize::Small)
+                                .on_click(cx.listener(|editor, _, cx| {
+                                    signature_help_next(editor, &SignatureHelpNext, cx)
+                                })),
+                        )
+                    })
+                    .child(label),
+            )
+            .children(parameter_documentation.map(|documentation| {
+                div().pt_1().child(crate::render_parsed_markdown(
+                    "parameter_documentation",
+                    &documentation,
+                    style,
+                    workspace.clone(),
+                    cx,
+                ))
+            }))
+            .children(signature_documentation.map(|documentation| {
+                div().pt_1().child(crate::render_parsed_markdown(
+                    "signature_documentation",
+                    &documentation,
+                    style,
+                    workspace,
+                    cx,
+                ))
+            }))
+            .into_any_element()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_lsp_test_context::EditorLspTestContext};
+    use futures::StreamExt;
+    use indoc::indoc;
+    use parking_lot::Mutex;
+
+    #[gpui::test]
+    async fn test_signature_help_on_trigger_character(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                signature_help_provider: Some(lsp::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string()]),
+                    retrigger_characters: Some(vec![",".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn main() { foˇ }
+        "});
+
+        let contexts = Arc::new(Mutex::new(Vec::new()));
+        let mut requests = cx.handle_request::<lsp::request::SignatureHelpRequest, _, _>({
+            let contexts = contexts.clone();
+            move |_, params, _| {
+                let context = params.context.unwrap();
+                let active_parameter = if context.trigger_character.as_deref() == Some(",") {
+                    1
+                } else {
+                    0
+                };
+                contexts.lock().push((
+                    context.trigger_kind,
+                    context.trigger_character,
+                    context.is_retrigger,
+                ));
+                async move {
+                    Ok(Some(lsp::SignatureHelp {
+                        signatures: vec![
+                            lsp::SignatureInformation {
+                                label: "fn foo(a: u32, b: u32)".to_string(),
+                                documentation: None,
+                                parameters: Some(vec![
+                                    lsp::ParameterInformation {
+                                        label: lsp::ParameterLabel::LabelOffsets([7, 13]),
+                                        documentation: Some(lsp::Documentation::MarkupContent(
+                                            lsp::MarkupContent {
+                                                kind: lsp::MarkupKind::Markdown,
+                                                value: "The **first** number.".to_string(),
+                                            },
+                                        )),
+                                    },
+                                    lsp::ParameterInformation {
+                                        label: lsp::ParameterLabel::Simple("b: u32".to_string()),
+                                        documentation: None,
+                                    },
+                                ]),
+                                active_parameter: None,
+                            },
+                            lsp::SignatureInformation {
+                                label: "fn foo(a: u32)".to_string(),
+                                documentation: None,
+                                parameters: None,
+                                active_parameter: None,
+                            },
+                        ],
+                        active_signature: Some(0),
+                        active_parameter: Some(active_parameter),
+                    }))
+                }
+            }
+        });
+
+        cx.update_editor(|editor, cx| editor.handle_input("o(", cx));
+        requests.next().await;
+        cx.run_until_parked();
+        cx.editor(|editor, _| {
+            let popover = editor.signature_help_state.popover().unwrap();
+            assert_eq!(
+                popover.active_label(),
+                Some(("fn foo(a: u32, b: u32)", Some(7..13)))
+            );
+            // Parameter documentation is rendered as markdown.
+            assert_eq!(
+                popover.parsed_parameter_documentation[0][0]
+                    .as_ref()
+                    .map(|documentation| documentation.text.as_str()),
+                Some("The first number.")
+            );
+        });
+
+        assert_eq!(
+            contexts.lock().drain(..).collect::<Vec<_>>(),
+            [(
+                lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER,
+                Some("(".to_string()),
+                false
+            )]
+        );
+
+        // Typing while the popover is visible re-queries the server once the cursor rests.
+        cx.update_editor(|editor, cx| editor.handle_input("1", cx));
+        cx.update_editor(|editor, cx| editor.handle_input(" ", cx));
+        cx.run_until_parked();
+        assert!(requests.try_next().is_err());
+        cx.executor()
+            .advance_clock(Duration::from_millis(SIGNATURE_HELP_REFRESH_DELAY_MILLIS));
+        requests.next().await;
+        cx.run_until_parked();
+        assert!(requests.try_next().is_err());
+        assert_eq!(
+            contexts.lock().drain(..).collect::<Vec<_>>(),
+            [(lsp::SignatureHelpTriggerKind::CONTENT_CHANGE, None, true)]
+        );
+
+        // Typing a retrigger character re-queries the server right away, updating the
+        // active parameter.
+        cx.update_editor(|editor, cx| editor.handle_input(",", cx));
+        requests.next().await;
+        cx.run_until_parked();
+        assert_eq!(
+            contexts.lock().drain(..).collect::<Vec<_>>(),
+            [(
+                lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER,
+                Some(",".to_string()),
+                true
+            )]
+        );
+        cx.editor(|editor, _| {
+            let popover = editor.signature_help_state.popover().unwrap();
+            assert_eq!(
+                popover.active_label(),
+                Some(("fn foo(a: u32, b: u32)", Some(15..21)))
+            );
+        });
+
+        // Overloads can be cycled through.
+        cx.update_editor(|editor, cx| signature_help_next(editor, &SignatureHelpNext, cx));
+        cx.editor(|editor, _| {
+            let popover = editor.signature_help_state.popover().unwrap();
+            assert_eq!(popover.active_signature(), 1);
+            assert_eq!(popover.active_label(), Some(("fn foo(a: u32)", None)));
+        });
+        cx.update_editor(|editor, cx| signature_help_next(editor, &SignatureHelpNext, cx));
+        cx.editor(|editor, _| {
+            assert_eq!(
+                editor
+                    .signature_help_state
+                    .popover()
+                    .unwrap()
+                    .active_signature(),
+                0
+            );
+        });
+
+        // Cancelling dismisses the popover.
+        cx.update_editor(|editor, cx| editor.cancel(&crate::Cancel, cx));
+        cx.editor(|editor, _| assert!(!editor.signature_help_state.is_shown()));
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use collections::HashMap;
 use git2::{BranchType, StatusShow};
 use parking_lot::Mutex;
@@ -6,6 +6,7 @@ use serde_derive::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     ffi::OsStr,
+    ops::Range,
     os::unix::prelude::OsStrExt,
     path::{Component, Path, PathBuf},
     sync::Arc,
@@ -23,10 +24,179 @@ pub struct Branch {
     pub unix_timestamp: Option<i64>,
 }
 
+/// A range of lines in a file that were last changed by the same commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlameEntry {
+    /// The SHA of the commit, which is all zeros for lines that haven't been committed.
+    pub sha: String,
+    /// The zero-based rows of the blamed text that are covered by this entry.
+    pub range: Range<u32>,
+    pub author: Option<String>,
+    pub author_mail: Option<String>,
+    /// Timestamp of the commit, normalized to Unix Epoch format.
+    pub author_time: Option<i64>,
+    /// Offset of the author's timezone from UTC, in minutes.
+    pub author_offset_minutes: Option<i32>,
+    pub summary: Option<String>,
+}
+
+impl BlameEntry {
+    pub fn is_uncommitted(&self) -> bool {
+        self.sha.bytes().all(|byte| byte == b'0')
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Blame {
+    pub entries: Vec<BlameEntry>,
+    /// The full message of each blamed commit, keyed by SHA.
+    pub messages: HashMap<String, String>,
+    /// The URL of the `origin` remote, used to build permalinks to commits.
+    pub remote_url: Option<String>,
+}
+
+/// The blame of a file's contents in the HEAD commit, from which the blame of other
+/// contents of the file is computed without walking the history again.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeadBlame {
+    /// The SHA of the HEAD commit that was blamed.
+    pub head_sha: Option<String>,
+    /// The contents of the file in the HEAD commit.
+    pub text: String,
+    pub blame: Blame,
+}
+
+impl HeadBlame {
+    /// Returns the blame of the given contents of the file. Lines that differ from
+    /// HEAD are attributed to an uncommitted entry.
+    pub fn blame_content(&self, content: &str) -> Result<Blame> {
+        let mut options = git2::DiffOptions::new();
+        options.context_lines(0);
+        let patch = git2::Patch::from_buffers(
+            self.text.as_bytes(),
+            None,
+            content.as_bytes(),
+            None,
+            Some(&mut options),
+        )?;
+
+        // The row in HEAD that each row of the content comes from, if it's unchanged.
+        let row_count = content.lines().count();
+        let mut head_rows = Vec::with_capacity(row_count);
+        let mut head_row = 0;
+        for hunk_ix in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(hunk_ix)?;
+            // Hunks without lines on one side start after the row they refer to.
+            let new_start = hunk
+                .new_start()
+                .saturating_sub((hunk.new_lines() > 0) as u32);
+            while (head_rows.len() as u32) < new_start {
+                head_rows.push(Some(head_row));
+                head_row += 1;
+            }
+            head_rows.extend((0..hunk.new_lines()).map(|_| None));
+            head_row += hunk.old_lines();
+        }
+        while head_rows.len() < row_count {
+            head_rows.push(Some(head_row));
+            head_row += 1;
+        }
+
+        let entries = &self.blame.entries;
+        let mut blame_entries = Vec::<BlameEntry>::new();
+        let mut last_entry_ix = None;
+        for (row, head_row) in head_rows.into_iter().enumerate() {
+            let row = row as u32;
+            let entry_ix = head_row.and_then(|head_row| {
+                let ix = entries.partition_point(|entry| entry.range.end <= head_row);
+                entries
+                    .get(ix)
+                    .filter(|entry| entry.range.start <= head_row)
+                    .map(|_| ix)
+            });
+            if let Some(last_entry) = blame_entries.last_mut() {
+                if last_entry_ix == Some(entry_ix) && last_entry.range.end == row {
+                    last_entry.range.end += 1;
+                    continue;
+                }
+            }
+
+            let mut entry = match entry_ix {
+                Some(ix) => entries[ix].clone(),
+                None => BlameEntry {
+                    sha: git2::Oid::zero().to_string(),
+                    range: 0..0,
+                    author: None,
+                    author_mail: None,
+                    author_time: None,
+                    author_offset_minutes: None,
+                    summary: None,
+                },
+            };
+            entry.range = row..row + 1;
+            blame_entries.push(entry);
+            last_entry_ix = Some(entry_ix);
+        }
+
+        Ok(Blame {
+            entries: blame_entries,
+            messages: self.blame.messages.clone(),
+            remote_url: self.blame.remote_url.clone(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommitOptions {
+    /// Replace the HEAD commit instead of creating a new commit on top of it.
+    pub amend: bool,
+    /// Append a `Signed-off-by` trailer for the committer to the message.
+    pub signoff: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub sha: String,
+    /// The first line of the commit message.
+    pub subject: String,
+    pub author_name: String,
+    /// Timestamp of the commit, normalized to Unix Epoch format.
+    pub commit_timestamp: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitDetails {
+    pub summary: CommitSummary,
+    pub message: String,
+    /// The files changed by the commit with respect to its first parent.
+    pub changes: Vec<CommitFileChange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFileChange {
+    pub path: RepoPath,
+    /// The contents of the file before the commit, if it existed and was text.
+    pub old_text: Option<String>,
+    /// The contents of the file after the commit, if it still exists and is text.
+    pub new_text: Option<String>,
+}
+
 pub trait GitRepository: Send {
     fn reload_index(&self);
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Returns the contents of the given file in the HEAD commit, or `None` if it
+    /// doesn't exist there.
+    fn load_head_text(&self, relative_file_path: &Path) -> Result<Option<String>>;
+
+    /// Writes the given contents to the index for the given file, or removes the
+    /// file from the index if the contents are `None`.
+    fn set_index_text(&self, relative_file_path: &Path, content: Option<String>) -> Result<()>;
+
+    /// Resets the index entry of the given file to the one in the HEAD commit, or
+    /// removes it from the index if the file doesn't exist there.
+    fn unstage(&self, relative_file_path: &Path) -> Result<()>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -55,6 +225,26 @@ pub trait GitRepository: Send {
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
     fn create_branch(&self, _: &str) -> Result<()>;
+
+    /// Returns the commit that last changed each line of a file in the HEAD commit.
+    /// This walks the file's history, so callers should reuse the result with
+    /// [`HeadBlame::blame_content`] until HEAD changes.
+    fn blame_head(&self, relative_file_path: &Path) -> Result<HeadBlame>;
+
+    /// Commits the contents of the index, returning the SHA of the new commit.
+    fn commit(&self, message: &str, options: CommitOptions) -> Result<String>;
+
+    /// Returns up to `limit` commits reachable from HEAD, newest first, after
+    /// skipping the first `skip`. When a path is given, only the commits that
+    /// changed that path are returned.
+    fn log(&self, path: Option<&Path>, skip: usize, limit: usize) -> Result<Vec<CommitSummary>>;
+
+    /// Returns the full message of the given commit and the files it changed.
+    fn show(&self, sha: &str) -> Result<CommitDetails>;
+
+    /// Returns the changes to tracked files in the working directory, staged or
+    /// not, with respect to the HEAD commit as a unified diff.
+    fn diff_head(&self) -> Result<String>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -94,6 +284,70 @@ impl GitRepository for LibGitRepository {
         None
     }
 
+    fn load_head_text(&self, relative_file_path: &Path) -> Result<Option<String>> {
+        check_path_to_repo_path_errors(relative_file_path)?;
+
+        let head = match self.head() {
+            Ok(head) => head,
+            Err(err) if err.code() == git2::ErrorCode::UnbornBranch => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let oid = match head.peel_to_tree()?.get_path(relative_file_path) {
+            Ok(entry) => entry.id(),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let content = self.find_blob(oid)?.content().to_owned();
+        Ok(Some(String::from_utf8(content)?))
+    }
+
+    fn set_index_text(&self, relative_file_path: &Path, content: Option<String>) -> Result<()> {
+        const STAGE_NORMAL: i32 = 0;
+        check_path_to_repo_path_errors(relative_file_path)?;
+
+        let mut index = self.index()?;
+        if let Some(content) = content {
+            let mode = index
+                .get_path(relative_file_path, STAGE_NORMAL)
+                .map_or(0o100644, |entry| entry.mode);
+            // The stat information is left empty so that git re-examines the
+            // working copy instead of assuming it matches the staged contents.
+            let entry = git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode,
+                uid: 0,
+                gid: 0,
+                file_size: content.len() as u32,
+                id: git2::Oid::zero(),
+                flags: 0,
+                flags_extended: 0,
+                path: relative_file_path.as_os_str().as_bytes().to_vec(),
+            };
+            index.add_frombuffer(&entry, content.as_bytes())?;
+        } else {
+            index.remove_path(relative_file_path)?;
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn unstage(&self, relative_file_path: &Path) -> Result<()> {
+        check_path_to_repo_path_errors(relative_file_path)?;
+
+        // Without a HEAD commit, resetting removes the file from the index.
+        let head = match self.head() {
+            Ok(head) => Some(head.peel_to_commit()?.into_object()),
+            Err(err) if err.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(err) => return Err(err.into()),
+        };
+        self.reset_default(head.as_ref(), [relative_file_path])?;
+        Ok(())
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let remote = self.find_remote(name).ok()?;
         remote.url().map(|url| url.to_string())
@@ -211,6 +465,240 @@ impl GitRepository for LibGitRepository {
 
         Ok(())
     }
+
+    fn blame_head(&self, relative_file_path: &Path) -> Result<HeadBlame> {
+        check_path_to_repo_path_errors(relative_file_path)?;
+
+        let head_sha = GitRepository::head_sha(self);
+        // A file that isn't committed yet has no history, so all of its lines are
+        // blamed as uncommitted.
+        let Some(text) = GitRepository::load_head_text(self, relative_file_path)? else {
+            return Ok(HeadBlame {
+                head_sha,
+                text: String::new(),
+                blame: Blame {
+                    entries: Vec::new(),
+                    messages: HashMap::default(),
+                    remote_url: GitRepository::remote_url(self, "origin"),
+                },
+            });
+        };
+        let blame = self.blame_file(relative_file_path, None)?;
+
+        let mut entries = Vec::new();
+        let mut messages = HashMap::default();
+        for hunk in blame.iter() {
+            let oid = hunk.final_commit_id();
+            let start = (hunk.final_start_line() as u32).saturating_sub(1);
+            let mut entry = BlameEntry {
+                sha: oid.to_string(),
+                range: start..start + hunk.lines_in_hunk() as u32,
+                author: None,
+                author_mail: None,
+                author_time: None,
+                author_offset_minutes: None,
+                summary: None,
+            };
+
+            // Uncommitted lines have no signature or commit to describe them.
+            if !oid.is_zero() {
+                let signature = hunk.final_signature();
+                entry.author = signature.name().map(str::to_string);
+                entry.author_mail = signature.email().map(str::to_string);
+                entry.author_time = Some(signature.when().seconds());
+                entry.author_offset_minutes = Some(signature.when().offset_minutes());
+
+                if let Some(commit) = self.find_commit(oid).log_err() {
+                    entry.summary = commit.summary().map(str::to_string);
+                    if let Some(message) = commit.message() {
+                        messages
+                            .entry(entry.sha.clone())
+                            .or_insert_with(|| message.to_string());
+                    }
+                }
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(HeadBlame {
+            head_sha,
+            text,
+            blame: Blame {
+                entries,
+                messages,
+                remote_url: GitRepository::remote_url(self, "origin"),
+            },
+        })
+    }
+
+    fn commit(&self, message: &str, options: CommitOptions) -> Result<String> {
+        let signature = self.signature()?;
+        let message = if options.signoff {
+            signed_off_message(
+                message,
+                signature.name().unwrap_or_default(),
+                signature.email().unwrap_or_default(),
+            )
+        } else {
+            message.to_string()
+        };
+
+        let tree = self.find_tree(self.index()?.write_tree()?)?;
+        let head_commit = match self.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(err) if err.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        let oid = if options.amend {
+            let head_commit = head_commit.ok_or_else(|| anyhow!("there is no commit to amend"))?;
+            head_commit.amend(
+                Some("HEAD"),
+                None,
+                Some(&signature),
+                None,
+                Some(&message),
+                Some(&tree),
+            )?
+        } else {
+            let parents = head_commit.iter().collect::<Vec<_>>();
+            self.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                &message,
+                &tree,
+                &parents,
+            )?
+        };
+        Ok(oid.to_string())
+    }
+
+    fn log(
+        &self,
+        path: Option<&Path>,
+        mut skip: usize,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>> {
+        if let Some(path) = path {
+            check_path_to_repo_path_errors(path)?;
+        }
+        if self.is_empty()? {
+            return Ok(Vec::new());
+        }
+
+        let mut revwalk = self.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        for oid in revwalk {
+            if commits.len() >= limit {
+                break;
+            }
+            let commit = self.find_commit(oid?)?;
+            if let Some(path) = path {
+                if !commit_changed_path(&commit, path)? {
+                    continue;
+                }
+            }
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            commits.push(commit_summary(&commit));
+        }
+        Ok(commits)
+    }
+
+    fn show(&self, sha: &str) -> Result<CommitDetails> {
+        let commit = self.find_commit(git2::Oid::from_str(sha)?)?;
+        let new_tree = commit.tree()?;
+        let old_tree = commit
+            .parents()
+            .next()
+            .map(|parent| parent.tree())
+            .transpose()?;
+        let diff = self.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+
+        let load_text = |oid: git2::Oid| {
+            if oid.is_zero() {
+                return None;
+            }
+            let blob = self.find_blob(oid).log_err()?;
+            String::from_utf8(blob.content().to_owned()).ok()
+        };
+        let changes = diff
+            .deltas()
+            .filter_map(|delta| {
+                let path = delta.new_file().path().or(delta.old_file().path())?;
+                Some(CommitFileChange {
+                    path: RepoPath::new(path.to_path_buf()),
+                    old_text: load_text(delta.old_file().id()),
+                    new_text: load_text(delta.new_file().id()),
+                })
+            })
+            .collect();
+
+        Ok(CommitDetails {
+            summary: commit_summary(&commit),
+            message: commit.message().unwrap_or_default().to_string(),
+            changes,
+        })
+    }
+
+    fn diff_head(&self) -> Result<String> {
+        // Repositories without any commits diff against an empty tree.
+        let head_tree = match self.head() {
+            Ok(head) => Some(head.peel_to_tree()?),
+            Err(error) if error.code() == git2::ErrorCode::UnbornBranch => None,
+            Err(error) => return Err(error.into()),
+        };
+        let diff = self.diff_tree_to_workdir_with_index(head_tree.as_ref(), None)?;
+
+        let mut patch = Vec::new();
+        diff.print(git2::DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), ' ' | '+' | '-') {
+                patch.push(line.origin() as u8);
+            }
+            patch.extend_from_slice(line.content());
+            true
+        })?;
+        Ok(String::from_utf8_lossy(&patch).into_owned())
+    }
+}
+
+fn commit_summary(commit: &git2::Commit) -> CommitSummary {
+    CommitSummary {
+        sha: commit.id().to_string(),
+        subject: commit.summary().unwrap_or_default().to_string(),
+        author_name: commit.author().name().unwrap_or_default().to_string(),
+        commit_timestamp: commit.time().seconds(),
+    }
+}
+
+fn commit_changed_path(commit: &git2::Commit, path: &Path) -> Result<bool> {
+    let entry_id = |tree: git2::Tree| tree.get_path(path).ok().map(|entry| entry.id());
+    let new_id = entry_id(commit.tree()?);
+    let old_id = match commit.parents().next() {
+        Some(parent) => entry_id(parent.tree()?),
+        None => None,
+    };
+    Ok(new_id != old_id)
+}
+
+/// Appends a `Signed-off-by` trailer to the message, unless it already has one
+/// for the same person.
+fn signed_off_message(message: &str, name: &str, email: &str) -> String {
+    let trailer = format!("Signed-off-by: {name} <{email}>");
+    let mut message = message.trim_end().to_string();
+    if !message.lines().any(|line| line.trim() == trailer) {
+        message.push_str("\n\n");
+        message.push_str(&trailer);
+    }
+    message.push('\n');
+    message
 }
 
 fn matches_index(repo: &LibGitRepository, path: &RepoPath, mtime: SystemTime) -> bool {
@@ -253,6 +741,10 @@ pub struct FakeGitRepository {
 #[derive(Debug, Clone, Default)]
 pub struct FakeGitRepositoryState {
     pub index_contents: HashMap<PathBuf, String>,
+    pub head_contents: HashMap<PathBuf, String>,
+    pub blames: HashMap<PathBuf, Blame>,
+    /// The commits made with [`GitRepository::commit`], oldest first.
+    pub commits: Vec<CommitDetails>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
 }
@@ -271,6 +763,31 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_head_text(&self, path: &Path) -> Result<Option<String>> {
+        let state = self.state.lock();
+        Ok(state.head_contents.get(path).cloned())
+    }
+
+    fn set_index_text(&self, path: &Path, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        if let Some(content) = content {
+            state.index_contents.insert(path.to_path_buf(), content);
+        } else {
+            state.index_contents.remove(path);
+        }
+        Ok(())
+    }
+
+    fn unstage(&self, path: &Path) -> Result<()> {
+        let mut state = self.state.lock();
+        if let Some(content) = state.head_contents.get(path).cloned() {
+            state.index_contents.insert(path.to_path_buf(), content);
+        } else {
+            state.index_contents.remove(path);
+        }
+        Ok(())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -319,6 +836,149 @@ impl GitRepository for FakeGitRepository {
         state.branch_name = Some(name.to_owned());
         Ok(())
     }
+
+    fn blame_head(&self, path: &Path) -> Result<HeadBlame> {
+        let state = self.state.lock();
+        let head_sha = state
+            .commits
+            .last()
+            .map(|commit| commit.summary.sha.clone());
+        let Some(text) = state.head_contents.get(path).cloned() else {
+            return Ok(HeadBlame {
+                head_sha,
+                ..Default::default()
+            });
+        };
+        let blame = state
+            .blames
+            .get(path)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no blame for {}", path.display()))?;
+        Ok(HeadBlame {
+            head_sha,
+            text,
+            blame,
+        })
+    }
+
+    fn commit(&self, message: &str, options: CommitOptions) -> Result<String> {
+        let mut state = self.state.lock();
+        let message = if options.signoff {
+            signed_off_message(message, "Fake User", "fake@example.com")
+        } else {
+            message.to_string()
+        };
+
+        let mut changes = state
+            .index_contents
+            .iter()
+            .filter(|(path, text)| state.head_contents.get(*path) != Some(*text))
+            .map(|(path, text)| CommitFileChange {
+                path: RepoPath::new(path.clone()),
+                old_text: state.head_contents.get(path).cloned(),
+                new_text: Some(text.clone()),
+            })
+            .chain(
+                state
+                    .head_contents
+                    .iter()
+                    .filter(|(path, _)| !state.index_contents.contains_key(*path))
+                    .map(|(path, text)| CommitFileChange {
+                        path: RepoPath::new(path.clone()),
+                        old_text: Some(text.clone()),
+                        new_text: None,
+                    }),
+            )
+            .collect::<Vec<_>>();
+        if options.amend {
+            let amended = state
+                .commits
+                .pop()
+                .ok_or_else(|| anyhow!("there is no commit to amend"))?;
+            for previous_change in amended.changes {
+                match changes
+                    .iter_mut()
+                    .find(|change| change.path == previous_change.path)
+                {
+                    Some(change) => change.old_text = previous_change.old_text,
+                    None => changes.push(previous_change),
+                }
+            }
+        }
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let sha = format!("{:040x}", state.commits.len() + 1);
+        state.head_contents = state.index_contents.clone();
+        state.commits.push(CommitDetails {
+            summary: CommitSummary {
+                sha: sha.clone(),
+                subject: message.lines().next().unwrap_or_default().to_string(),
+                author_name: "Fake User".to_string(),
+                commit_timestamp: 0,
+            },
+            message,
+            changes,
+        });
+        Ok(sha)
+    }
+
+    fn log(&self, path: Option<&Path>, skip: usize, limit: usize) -> Result<Vec<CommitSummary>> {
+        let state = self.state.lock();
+        Ok(state
+            .commits
+            .iter()
+            .rev()
+            .filter(|commit| {
+                path.map_or(true, |path| {
+                    commit.changes.iter().any(|change| change.path.0 == path)
+                })
+            })
+            .skip(skip)
+            .take(limit)
+            .map(|commit| commit.summary.clone())
+            .collect())
+    }
+
+    fn show(&self, sha: &str) -> Result<CommitDetails> {
+        let state = self.state.lock();
+        state
+            .commits
+            .iter()
+            .find(|commit| commit.summary.sha == sha)
+            .cloned()
+            .ok_or_else(|| anyhow!("no commit {sha}"))
+    }
+
+    /// Diffs the index contents against HEAD, since the fake repository has no
+    /// working directory.
+    fn diff_head(&self) -> Result<String> {
+        let state = self.state.lock();
+        let mut paths = state
+            .head_contents
+            .keys()
+            .chain(state.index_contents.keys())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+
+        let mut diff = String::new();
+        for path in paths {
+            let old_text = state.head_contents.get(path);
+            let new_text = state.index_contents.get(path);
+            if old_text == new_text {
+                continue;
+            }
+            let mut patch = git2::Patch::from_buffers(
+                old_text.map_or(&[][..], |text| text.as_bytes()),
+                old_text.map(|_| path.as_path()),
+                new_text.map_or(&[][..], |text| text.as_bytes()),
+                new_text.map(|_| path.as_path()),
+                None,
+            )?;
+            diff.push_str(&String::from_utf8_lossy(&patch.to_buf()?));
+        }
+        Ok(diff)
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -437,3 +1097,40 @@ impl<'a> MapSeekTarget<RepoPath> for RepoPathDescendants<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blame_uncommitted_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = LibGitRepository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("committed.txt"), "one\ntwo\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("committed.txt")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@zed.dev").unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )
+        .unwrap();
+
+        let content = "one\ntwo\nthree\n";
+        std::fs::write(dir.path().join("new.txt"), content).unwrap();
+        let head_blame = repo.blame_head(Path::new("new.txt")).unwrap();
+        assert_eq!(head_blame.text, "");
+        assert!(head_blame.blame.entries.is_empty());
+
+        let blame = head_blame.blame_content(content).unwrap();
+        assert_eq!(blame.entries.len(), 1);
+        assert_eq!(blame.entries[0].range, 0..3);
+        assert!(blame.entries[0].is_uncommitted());
+    }
+}
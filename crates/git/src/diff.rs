@@ -28,6 +28,12 @@ impl DiffHunk<u32> {
             DiffHunkStatus::Modified
         }
     }
+
+    /// Returns whether the hunk touches the given buffer rows. Removed hunks are
+    /// empty, so they touch the rows on either side of them.
+    pub fn intersects_rows(&self, rows: &Range<u32>) -> bool {
+        rows_intersect(&self.buffer_range, rows)
+    }
 }
 
 impl sum_tree::Item for DiffHunk<Anchor> {
@@ -170,7 +176,13 @@ impl BufferDiff {
         if let Some(patch) = patch {
             let mut divergence = 0;
             for hunk_index in 0..patch.num_hunks() {
-                let hunk = Self::process_patch_hunk(&patch, hunk_index, buffer, &mut divergence);
+                let hunk = Self::process_patch_hunk(
+                    &patch,
+                    hunk_index,
+                    diff_base,
+                    buffer,
+                    &mut divergence,
+                );
                 tree.push(hunk, buffer);
             }
         }
@@ -211,6 +223,7 @@ impl BufferDiff {
     fn process_patch_hunk<'a>(
         patch: &GitPatch<'a>,
         hunk_index: usize,
+        diff_base: &str,
         buffer: &text::BufferSnapshot,
         buffer_row_divergence: &mut i64,
     ) -> DiffHunk<Anchor> {
@@ -262,8 +275,12 @@ impl BufferDiff {
             row..row
         });
 
-        //unwrap_or addition without deletion
-        let diff_base_byte_range = diff_base_byte_range.unwrap_or(0..0);
+        //unwrap_or addition without deletion, positioned where the rows were inserted
+        let diff_base_byte_range = diff_base_byte_range.unwrap_or_else(|| {
+            let (hunk, _) = patch.hunk(hunk_index).unwrap();
+            let offset = row_offset(diff_base, hunk.old_start());
+            offset..offset
+        });
 
         let start = Point::new(buffer_row_range.start, 0);
         let end = Point::new(buffer_row_range.end, 0);
@@ -275,6 +292,137 @@ impl BufferDiff {
     }
 }
 
+/// Returns the diff base with the given hunks replaced by the corresponding rows
+/// of the buffer, which is what the index should contain after staging them.
+pub fn apply_hunks_to_diff_base(
+    diff_base: &str,
+    buffer: &BufferSnapshot,
+    hunks: impl IntoIterator<Item = DiffHunk<u32>>,
+) -> String {
+    let mut hunks = hunks.into_iter().collect::<Vec<_>>();
+    hunks.sort_by_key(|hunk| hunk.diff_base_byte_range.start);
+    hunks.dedup();
+
+    let max_point = buffer.max_point();
+    let mut result = String::with_capacity(diff_base.len());
+    let mut last_end = 0;
+    for hunk in hunks {
+        if hunk.diff_base_byte_range.start < last_end {
+            continue;
+        }
+        result.push_str(&diff_base[last_end..hunk.diff_base_byte_range.start]);
+        let start = Point::new(hunk.buffer_range.start, 0).min(max_point);
+        let end = Point::new(hunk.buffer_range.end, 0).min(max_point);
+        result.extend(buffer.text_for_range(start..end));
+        last_end = hunk.diff_base_byte_range.end;
+    }
+    result.push_str(&diff_base[last_end..]);
+    result
+}
+
+/// Maps a range of buffer rows to the corresponding rows of the diff base, given
+/// all of the buffer's hunks in order. A row inside a hunk maps to the whole hunk.
+pub fn diff_base_rows_for_buffer_rows(
+    rows: Range<u32>,
+    diff_base: &str,
+    hunks: impl IntoIterator<Item = DiffHunk<u32>>,
+) -> Range<u32> {
+    let mut divergence: i64 = 0;
+    let mut start = None;
+    let mut end = None;
+    for hunk in hunks {
+        let base_start = row_for_offset(diff_base, hunk.diff_base_byte_range.start);
+        let base_end = row_for_offset(diff_base, hunk.diff_base_byte_range.end);
+
+        if start.is_none() {
+            if rows.start < hunk.buffer_range.start {
+                start = Some((rows.start as i64 + divergence) as u32);
+            } else if rows.start < hunk.buffer_range.end {
+                start = Some(base_start);
+            }
+        }
+        if end.is_none() {
+            if rows.end <= hunk.buffer_range.start {
+                end = Some((rows.end as i64 + divergence) as u32);
+            } else if rows.end <= hunk.buffer_range.end {
+                end = Some(base_end);
+            }
+        }
+        if start.is_some() && end.is_some() {
+            break;
+        }
+
+        divergence = base_end as i64 - hunk.buffer_range.end as i64;
+    }
+
+    let start = start.unwrap_or_else(|| (rows.start as i64 + divergence) as u32);
+    let end = end.unwrap_or_else(|| (rows.end as i64 + divergence) as u32);
+    start..end.max(start)
+}
+
+/// Returns `new_text` with every change from `old_text` that touches the given
+/// rows of `new_text` reverted, which is how hunks are unstaged from the index.
+pub fn revert_changes_in_rows(old_text: &str, new_text: &str, rows: &[Range<u32>]) -> String {
+    let Some(patch) = BufferDiff::diff(old_text, new_text) else {
+        return new_text.to_string();
+    };
+
+    let mut result = String::with_capacity(new_text.len());
+    let mut last_end = 0;
+    for hunk_index in 0..patch.num_hunks() {
+        let Ok((hunk, _)) = patch.hunk(hunk_index) else {
+            continue;
+        };
+        let old_rows = hunk_rows(hunk.old_start(), hunk.old_lines());
+        let new_rows = hunk_rows(hunk.new_start(), hunk.new_lines());
+
+        if !rows.iter().any(|rows| rows_intersect(&new_rows, rows)) {
+            continue;
+        }
+
+        let new_start = row_offset(new_text, new_rows.start);
+        let new_end = row_offset(new_text, new_rows.end);
+        result.push_str(&new_text[last_end..new_start]);
+        result.push_str(
+            &old_text[row_offset(old_text, old_rows.start)..row_offset(old_text, old_rows.end)],
+        );
+        last_end = new_end;
+    }
+    result.push_str(&new_text[last_end..]);
+    result
+}
+
+fn rows_intersect(hunk_rows: &Range<u32>, rows: &Range<u32>) -> bool {
+    if hunk_rows.is_empty() {
+        rows.start <= hunk_rows.start && hunk_rows.start <= rows.end
+    } else {
+        hunk_rows.start < rows.end && rows.start < hunk_rows.end
+    }
+}
+
+/// Converts a hunk header's one-based line numbers into zero-based rows. An empty
+/// range starts at the row before which the lines were inserted or removed.
+fn hunk_rows(start: u32, lines: u32) -> Range<u32> {
+    if lines == 0 {
+        start..start
+    } else {
+        start - 1..start - 1 + lines
+    }
+}
+
+fn row_offset(text: &str, row: u32) -> usize {
+    if row == 0 {
+        return 0;
+    }
+    text.match_indices('\n')
+        .nth(row as usize - 1)
+        .map_or(text.len(), |(ix, _)| ix + 1)
+}
+
+fn row_for_offset(text: &str, offset: usize) -> u32 {
+    text[..offset].matches('\n').count() as u32
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -409,4 +557,66 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_staging_and_unstaging_hunks() {
+        let head_text = "
+            one
+            two
+            three
+            four
+        "
+        .unindent();
+
+        let index_text = "
+            one
+            TWO
+            three
+            four
+        "
+        .unindent();
+
+        let buffer_text = "
+            zero
+            one
+            TWO
+            three
+            FOUR
+        "
+        .unindent();
+
+        let buffer = Buffer::new(0, BufferId::new(1).unwrap(), buffer_text);
+        let mut diff = BufferDiff::new();
+        smol::block_on(diff.update(&index_text, &buffer));
+        let hunks = diff.hunks(&buffer).collect::<Vec<_>>();
+        assert_eq!(hunks.len(), 2);
+
+        // Added rows are staged at the position where they were inserted.
+        assert_eq!(
+            apply_hunks_to_diff_base(&index_text, &buffer, hunks[..1].iter().cloned()),
+            "zero\none\nTWO\nthree\nfour\n"
+        );
+        assert_eq!(
+            apply_hunks_to_diff_base(&index_text, &buffer, hunks[1..].iter().cloned()),
+            "one\nTWO\nthree\nFOUR\n"
+        );
+
+        // The buffer's third row corresponds to the index's second row.
+        let index_rows = diff_base_rows_for_buffer_rows(2..3, &index_text, hunks.iter().cloned());
+        assert_eq!(index_rows, 1..2);
+        assert_eq!(
+            revert_changes_in_rows(&head_text, &index_text, &[index_rows]),
+            head_text
+        );
+        assert_eq!(
+            revert_changes_in_rows(&head_text, &index_text, &[3..4]),
+            index_text
+        );
+
+        // A row inside of a hunk maps to the whole hunk in the diff base.
+        assert_eq!(
+            diff_base_rows_for_buffer_rows(4..5, &index_text, hunks.iter().cloned()),
+            3..4
+        );
+    }
 }
@@ -0,0 +1,983 @@
+mod git_panel_settings;
+
+use anyhow::{anyhow, Result};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{Editor, ExcerptRange, MultiBuffer};
+use fs::repository::{CommitOptions, CommitSummary, GitFileStatus, GitRepository, RepoPath};
+use git_panel_settings::GitPanelSettings;
+use gpui::{
+    actions, px, uniform_list, Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle,
+    FocusableView, Model, Pixels, Render, Subscription, Task, UniformListScrollHandle, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::Capability;
+use parking_lot::Mutex;
+use project::{Fs, Project, ProjectPath, WorktreeId};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use time::OffsetDateTime;
+use ui::{
+    h_flex, prelude::*, v_flex, Button, Checkbox, Icon, IconName, Label, ListHeader, ListItem,
+    Selection,
+};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Toast, Workspace,
+};
+
+const GIT_PANEL_KEY: &'static str = "GitPanel";
+const GIT_OPERATION_FAILURE_ID: usize = 2049;
+const COMMIT_EDITOR_MAX_LINES: usize = 6;
+const LOG_PAGE_SIZE: usize = 50;
+
+actions!(git_panel, [ToggleFocus, Commit]);
+
+pub fn init_settings(cx: &mut AppContext) {
+    GitPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<GitPanel>(cx);
+        });
+    })
+    .detach();
+}
+
+/// A panel for staging changes, committing them and browsing the history of
+/// the repository that contains the active file.
+pub struct GitPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    focus_handle: FocusHandle,
+    width: Option<Pixels>,
+    pending_serialization: Task<Option<()>>,
+    repository: Option<PanelRepository>,
+    changed_files: Vec<ChangedFile>,
+    commit_editor: View<Editor>,
+    amend: bool,
+    signoff: bool,
+    log_scope: LogScope,
+    /// The path whose history is shown, relative to the repository's working
+    /// directory, or `None` when showing the history of the whole repository.
+    log_path: Option<PathBuf>,
+    commits: Vec<CommitSummary>,
+    has_more_commits: bool,
+    log_scroll_handle: UniformListScrollHandle,
+    pending_refresh: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Clone)]
+struct PanelRepository {
+    worktree_id: WorktreeId,
+    /// The repository's working directory, relative to the root of its worktree.
+    work_directory: Arc<Path>,
+    branch: Option<Arc<str>>,
+    repo: Arc<Mutex<dyn GitRepository>>,
+}
+
+struct ChangedFile {
+    project_path: ProjectPath,
+    repo_path: RepoPath,
+    status: GitFileStatus,
+    staged: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LogScope {
+    Repository,
+    ActiveFile,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedGitPanel {
+    width: Option<Pixels>,
+}
+
+impl GitPanel {
+    pub fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let project = workspace.project().clone();
+        let fs = project.read(cx).fs().clone();
+        let workspace_handle = workspace.weak_handle();
+
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::auto_height(COMMIT_EDITOR_MAX_LINES, cx);
+                editor.set_placeholder_text("Commit message", cx);
+                editor
+            });
+
+            let subscriptions = vec![
+                cx.subscribe(&project, |this, _, event, cx| match event {
+                    project::Event::WorktreeAdded
+                    | project::Event::WorktreeRemoved(_)
+                    | project::Event::WorktreeUpdatedEntries(_, _)
+                    | project::Event::ActiveEntryChanged(_) => this.refresh(cx),
+                    _ => {}
+                }),
+                cx.observe(&commit_editor, |_, _, cx| cx.notify()),
+            ];
+
+            let mut this = Self {
+                workspace: workspace_handle,
+                project,
+                fs,
+                focus_handle: cx.focus_handle(),
+                width: None,
+                pending_serialization: Task::ready(None),
+                repository: None,
+                changed_files: Vec::new(),
+                commit_editor,
+                amend: false,
+                signoff: false,
+                log_scope: LogScope::Repository,
+                log_path: None,
+                commits: Vec::new(),
+                has_more_commits: false,
+                log_scroll_handle: UniformListScrollHandle::new(),
+                pending_refresh: Task::ready(()),
+                _subscriptions: subscriptions,
+            };
+            this.refresh(cx);
+            this
+        })
+    }
+
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(GIT_PANEL_KEY) })
+            .await
+            .map_err(|e| anyhow!("Failed to load git panel: {}", e))
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedGitPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = GitPanel::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width;
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        GIT_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedGitPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn active_project_path(&self, cx: &AppContext) -> Option<ProjectPath> {
+        let project = self.project.read(cx);
+        project.path_for_entry(project.active_entry()?, cx)
+    }
+
+    /// Returns the repository containing the active file, falling back to the
+    /// first repository in the project.
+    fn active_repository(
+        &self,
+        active_path: Option<&ProjectPath>,
+        cx: &AppContext,
+    ) -> Option<PanelRepository> {
+        let project = self.project.read(cx);
+        let worktrees = active_path
+            .and_then(|path| project.worktree_for_id(path.worktree_id, cx))
+            .into_iter()
+            .chain(project.visible_worktrees(cx));
+        for worktree in worktrees {
+            let worktree = worktree.read(cx);
+            let Some(local_worktree) = worktree.as_local() else {
+                continue;
+            };
+            let path = active_path
+                .filter(|path| path.worktree_id == worktree.id())
+                .map_or(Path::new(""), |path| path.path.as_ref());
+            let Some((work_directory, repository_entry)) = worktree
+                .repository_and_work_directory_for_path(path)
+                .or_else(|| {
+                    let (work_directory, _) = worktree.repositories().next()?;
+                    worktree.repository_and_work_directory_for_path(work_directory)
+                })
+            else {
+                continue;
+            };
+            let Some((_, local_repo)) = local_worktree.local_repo_for_path(work_directory.as_ref())
+            else {
+                continue;
+            };
+            return Some(PanelRepository {
+                worktree_id: worktree.id(),
+                work_directory: Arc::from(work_directory.as_ref()),
+                branch: repository_entry.branch(),
+                repo: local_repo.repo().clone(),
+            });
+        }
+        None
+    }
+
+    fn refresh(&mut self, cx: &mut ViewContext<Self>) {
+        let active_path = self.active_project_path(cx);
+        let Some(repository) = self.active_repository(active_path.as_ref(), cx) else {
+            self.repository = None;
+            self.changed_files.clear();
+            self.commits.clear();
+            self.has_more_commits = false;
+            self.pending_refresh = Task::ready(());
+            cx.notify();
+            return;
+        };
+        let Some(worktree) = self
+            .project
+            .read(cx)
+            .worktree_for_id(repository.worktree_id, cx)
+        else {
+            return;
+        };
+
+        let work_directory = repository.work_directory.clone();
+        let changed_paths = worktree
+            .read(cx)
+            .descendent_entries(false, false, &work_directory)
+            .filter_map(|entry| Some((entry.path.clone(), entry.git_status?)))
+            .collect::<Vec<_>>();
+
+        let log_path = match self.log_scope {
+            LogScope::Repository => None,
+            LogScope::ActiveFile => Some(
+                active_path
+                    .filter(|path| path.worktree_id == repository.worktree_id)
+                    .and_then(|path| {
+                        Some(path.path.strip_prefix(&work_directory).ok()?.to_path_buf())
+                    }),
+            ),
+        };
+
+        let repo = repository.repo.clone();
+        let log_scope = self.log_scope;
+        self.pending_refresh = cx.spawn(|this, mut cx| async move {
+            let (staged_statuses, commits, log_path) = cx
+                .background_executor()
+                .spawn(async move {
+                    let repo = repo.lock();
+                    let staged_statuses = repo.staged_statuses(Path::new(""));
+                    let (commits, log_path) = match log_path {
+                        // There is no history to show when no file is active.
+                        Some(None) => (Vec::new(), None),
+                        Some(Some(path)) => (
+                            repo.log(Some(&path), 0, LOG_PAGE_SIZE)
+                                .log_err()
+                                .unwrap_or_default(),
+                            Some(path),
+                        ),
+                        None => (
+                            repo.log(None, 0, LOG_PAGE_SIZE)
+                                .log_err()
+                                .unwrap_or_default(),
+                            None,
+                        ),
+                    };
+                    (staged_statuses, commits, log_path)
+                })
+                .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.changed_files = changed_paths
+                    .into_iter()
+                    .filter_map(|(path, status)| {
+                        let repo_path = RepoPath::new(
+                            path.strip_prefix(&repository.work_directory)
+                                .ok()?
+                                .to_path_buf(),
+                        );
+                        Some(ChangedFile {
+                            project_path: ProjectPath {
+                                worktree_id: repository.worktree_id,
+                                path,
+                            },
+                            staged: staged_statuses.get(&repo_path).is_some(),
+                            repo_path,
+                            status,
+                        })
+                    })
+                    .collect();
+                this.has_more_commits = commits.len() == LOG_PAGE_SIZE;
+                this.commits = commits;
+                this.log_path = log_path.filter(|_| log_scope == LogScope::ActiveFile);
+                this.repository = Some(repository);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn load_more_commits(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.repository.as_ref() else {
+            return;
+        };
+        let repo = repository.repo.clone();
+        let log_path = self.log_path.clone();
+        let skip = self.commits.len();
+        let commits = cx
+            .background_executor()
+            .spawn(async move { repo.lock().log(log_path.as_deref(), skip, LOG_PAGE_SIZE) });
+        cx.spawn(|this, mut cx| async move {
+            let commits = commits.await?;
+            this.update(&mut cx, |this, cx| {
+                this.has_more_commits = commits.len() == LOG_PAGE_SIZE;
+                this.commits.extend(commits);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_log_scope(&mut self, log_scope: LogScope, cx: &mut ViewContext<Self>) {
+        if self.log_scope != log_scope {
+            self.log_scope = log_scope;
+            self.refresh(cx);
+        }
+    }
+
+    fn toggle_staged(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.repository.as_ref() else {
+            return;
+        };
+        let Some(file) = self.changed_files.get_mut(ix) else {
+            return;
+        };
+        let Some(abs_path) = self.project.read(cx).absolute_path(&file.project_path, cx) else {
+            return;
+        };
+
+        file.staged = !file.staged;
+        let stage = file.staged;
+        let repo_path = file.repo_path.clone();
+        let repo = repository.repo.clone();
+        let fs = self.fs.clone();
+        let task = cx.background_executor().spawn(async move {
+            if !stage {
+                repo.lock().unstage(&repo_path)
+            } else if fs.metadata(&abs_path).await?.is_some() {
+                let text = fs.load(&abs_path).await?;
+                repo.lock().set_index_text(&repo_path, Some(text))
+            } else {
+                // Staging a deleted file removes it from the index.
+                repo.lock().set_index_text(&repo_path, None)
+            }
+        });
+        cx.notify();
+
+        cx.spawn(|this, mut cx| async move {
+            let result = task.await;
+            this.update(&mut cx, |this, cx| {
+                if let Err(error) = result {
+                    this.show_error(format!("Failed to update the index: {error}"), cx);
+                }
+                this.refresh(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn toggle_amend(&mut self, cx: &mut ViewContext<Self>) {
+        self.amend = !self.amend;
+        cx.notify();
+
+        // Start from the message of the commit being amended.
+        let Some(repository) = self.repository.as_ref() else {
+            return;
+        };
+        if !self.amend || !self.commit_editor.read(cx).text(cx).is_empty() {
+            return;
+        }
+        let repo = repository.repo.clone();
+        let message = cx.background_executor().spawn(async move {
+            let repo = repo.lock();
+            let head = repo
+                .log(None, 0, 1)?
+                .pop()
+                .ok_or_else(|| anyhow!("there is no commit to amend"))?;
+            anyhow::Ok(repo.show(&head.sha)?.message)
+        });
+        cx.spawn(|this, mut cx| async move {
+            let message = message.await?;
+            this.update(&mut cx, |this, cx| {
+                this.commit_editor.update(cx, |editor, cx| {
+                    if editor.text(cx).is_empty() {
+                        editor.set_text(message.trim_end(), cx);
+                    }
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn can_commit(&self, cx: &AppContext) -> bool {
+        self.repository.is_some()
+            && !self.commit_editor.read(cx).text(cx).trim().is_empty()
+            && (self.amend || self.changed_files.iter().any(|file| file.staged))
+    }
+
+    fn commit(&mut self, _: &Commit, cx: &mut ViewContext<Self>) {
+        if !self.can_commit(cx) {
+            return;
+        }
+        let Some(repository) = self.repository.as_ref() else {
+            return;
+        };
+
+        let message = self.commit_editor.read(cx).text(cx);
+        let options = CommitOptions {
+            amend: self.amend,
+            signoff: self.signoff,
+        };
+        let repo = repository.repo.clone();
+        let task = cx
+            .background_executor()
+            .spawn(async move { repo.lock().commit(&message, options) });
+
+        cx.spawn(|this, mut cx| async move {
+            let result = task.await;
+            this.update(&mut cx, |this, cx| {
+                match result {
+                    Ok(_) => {
+                        this.commit_editor.update(cx, |editor, cx| editor.clear(cx));
+                        this.amend = false;
+                    }
+                    Err(error) => this.show_error(format!("Failed to commit: {error}"), cx),
+                }
+                this.refresh(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Opens the changes made by the given commit in a multibuffer, with the
+    /// contents of each file before the commit as its diff base.
+    fn open_commit(&mut self, sha: String, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.repository.as_ref() else {
+            return;
+        };
+        let repo = repository.repo.clone();
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+        let details = cx
+            .background_executor()
+            .spawn(async move { repo.lock().show(&sha) });
+
+        cx.spawn(|_, mut cx| async move {
+            let details = details.await?;
+            let languages = project.update(&mut cx, |project, _| project.languages().clone())?;
+
+            let mut buffers = Vec::new();
+            for change in details.changes {
+                let language = languages.language_for_file(&change.path, None).await.ok();
+                let buffer = project.update(&mut cx, |project, cx| {
+                    project.create_buffer(change.new_text.as_deref().unwrap_or(""), language, cx)
+                })??;
+                buffer.update(&mut cx, |buffer, cx| {
+                    buffer.set_diff_base(Some(change.old_text.unwrap_or_default()), cx)
+                })?;
+                buffers.push(buffer);
+            }
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let summary = details.summary;
+                let short_sha = &summary.sha[..summary.sha.len().min(7)];
+                let title = format!("{short_sha} {}", summary.subject);
+                let multibuffer = cx.new_model(|cx| {
+                    let mut multibuffer = MultiBuffer::new(0, Capability::ReadOnly);
+                    for buffer in buffers {
+                        let len = buffer.read(cx).len();
+                        multibuffer.push_excerpts(
+                            buffer,
+                            [ExcerptRange {
+                                context: 0..len,
+                                primary: None,
+                            }],
+                            cx,
+                        );
+                    }
+                    multibuffer.with_title(title)
+                });
+                let editor =
+                    cx.new_view(|cx| Editor::for_multibuffer(multibuffer, Some(project), cx));
+                workspace.add_item(Box::new(editor), cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn open_file(&mut self, project_path: ProjectPath, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_path(project_path, None, true, cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+    }
+
+    fn show_error(&self, message: String, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.show_toast(Toast::new(GIT_OPERATION_FAILURE_ID, message), cx)
+            })
+            .ok();
+    }
+
+    fn render_changed_file(
+        &self,
+        ix: usize,
+        file: &ChangedFile,
+        cx: &mut ViewContext<Self>,
+    ) -> ListItem {
+        let color = match file.status {
+            GitFileStatus::Added => Color::Created,
+            GitFileStatus::Modified => Color::Modified,
+            GitFileStatus::Conflict => Color::Conflict,
+        };
+        let project_path = file.project_path.clone();
+
+        ListItem::new(("changed_file", ix))
+            .start_slot(
+                Checkbox::new(
+                    ("stage_file", ix),
+                    if file.staged {
+                        Selection::Selected
+                    } else {
+                        Selection::Unselected
+                    },
+                )
+                .on_click(cx.listener(move |this, _, cx| this.toggle_staged(ix, cx))),
+            )
+            .child(Label::new(file.repo_path.to_string_lossy().to_string()).color(color))
+            .on_click(cx.listener(move |this, _, cx| this.open_file(project_path.clone(), cx)))
+    }
+
+    fn render_commit(
+        &self,
+        ix: usize,
+        commit: &CommitSummary,
+        cx: &mut ViewContext<Self>,
+    ) -> ListItem {
+        let date = OffsetDateTime::from_unix_timestamp(commit.commit_timestamp)
+            .map(|timestamp| timestamp.date().to_string())
+            .unwrap_or_default();
+        let sha = commit.sha.clone();
+
+        ListItem::new(("commit", ix))
+            .child(
+                v_flex().child(Label::new(commit.subject.clone())).child(
+                    Label::new(format!("{} · {}", commit.author_name, date))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .on_click(cx.listener(move |this, _, cx| this.open_commit(sha.clone(), cx)))
+    }
+
+    fn render_commit_composer(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let selection = |selected: bool| {
+            if selected {
+                Selection::Selected
+            } else {
+                Selection::Unselected
+            }
+        };
+
+        v_flex()
+            .gap_1()
+            .p_2()
+            .border_y_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                div()
+                    .p_1()
+                    .rounded_md()
+                    .bg(cx.theme().colors().editor_background)
+                    .child(self.commit_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(
+                                Checkbox::new("amend", selection(self.amend))
+                                    .on_click(cx.listener(|this, _, cx| this.toggle_amend(cx))),
+                            )
+                            .child(Label::new("Amend").size(LabelSize::Small))
+                            .child(Checkbox::new("signoff", selection(self.signoff)).on_click(
+                                cx.listener(|this, _, cx| {
+                                    this.signoff = !this.signoff;
+                                    cx.notify();
+                                }),
+                            ))
+                            .child(Label::new("Sign off").size(LabelSize::Small)),
+                    )
+                    .child(
+                        Button::new("commit", if self.amend { "Amend" } else { "Commit" })
+                            .style(ButtonStyle::Filled)
+                            .disabled(!self.can_commit(cx))
+                            .on_click(cx.listener(|this, _, cx| this.commit(&Commit, cx))),
+                    ),
+            )
+    }
+
+    fn render_log(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let view = cx.view().clone();
+        let item_count = self.commits.len() + self.has_more_commits as usize;
+
+        v_flex()
+            .flex_1()
+            .child(
+                ListHeader::new("History").end_slot(
+                    h_flex()
+                        .child(
+                            Button::new("log_repository", "Repository")
+                                .selected(self.log_scope == LogScope::Repository)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.set_log_scope(LogScope::Repository, cx)
+                                })),
+                        )
+                        .child(
+                            Button::new("log_active_file", "Current File")
+                                .selected(self.log_scope == LogScope::ActiveFile)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.set_log_scope(LogScope::ActiveFile, cx)
+                                })),
+                        ),
+                ),
+            )
+            .child(
+                uniform_list(view, "git_log", item_count, |this, range, cx| {
+                    range
+                        .map(|ix| match this.commits.get(ix) {
+                            Some(commit) => this.render_commit(ix, commit, cx).into_any_element(),
+                            None => ListItem::new("load_more_commits")
+                                .child(Label::new("Load More").color(Color::Muted))
+                                .on_click(cx.listener(|this, _, cx| this.load_more_commits(cx)))
+                                .into_any_element(),
+                        })
+                        .collect()
+                })
+                .flex_1()
+                .track_scroll(self.log_scroll_handle.clone()),
+            )
+    }
+}
+
+impl Render for GitPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let Some(repository) = self.repository.as_ref() else {
+            return v_flex()
+                .key_context("GitPanel")
+                .track_focus(&self.focus_handle)
+                .size_full()
+                .p_4()
+                .child(
+                    Label::new("No git repository found in this project.")
+                        .color(Color::Muted)
+                        .size(LabelSize::Small),
+                );
+        };
+
+        v_flex()
+            .key_context("GitPanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::commit))
+            .size_full()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .px_2()
+                    .py_1()
+                    // Match the height of the tab bar so they line up.
+                    .h(rems(ui::Tab::CONTAINER_HEIGHT_IN_REMS))
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Icon::new(IconName::FileGit))
+                    .child(Label::new(
+                        repository
+                            .branch
+                            .as_deref()
+                            .unwrap_or("detached HEAD")
+                            .to_string(),
+                    )),
+            )
+            .child(ListHeader::new("Changes"))
+            .child(
+                v_flex()
+                    .id("changed_files")
+                    .max_h(px(320.))
+                    .overflow_y_scroll()
+                    .children(
+                        self.changed_files
+                            .iter()
+                            .enumerate()
+                            .map(|(ix, file)| self.render_changed_file(ix, file, cx)),
+                    ),
+            )
+            .child(self.render_commit_composer(cx))
+            .child(self.render_log(cx))
+    }
+}
+
+impl FocusableView for GitPanel {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for GitPanel {}
+
+impl Panel for GitPanel {
+    fn persistent_name() -> &'static str {
+        "GitPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        GitPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<GitPanelSettings>(self.fs.clone(), cx, move |settings| {
+            settings.dock = Some(position)
+        });
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| GitPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        GitPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::FileGit)
+    }
+
+    fn icon_tooltip(&self, _cx: &WindowContext) -> Option<&'static str> {
+        Some("Git Panel")
+    }
+
+    fn icon_label(&self, _: &WindowContext) -> Option<String> {
+        (!self.changed_files.is_empty()).then(|| self.changed_files.len().to_string())
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_commit_and_log(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "a.txt": "two\n",
+                "b.txt": "unchanged\n",
+            }),
+        )
+        .await;
+        fs.set_head_for_repo(
+            Path::new("/root/.git"),
+            &[
+                (Path::new("a.txt"), "one\n".to_string()),
+                (Path::new("b.txt"), "unchanged\n".to_string()),
+            ],
+        );
+        fs.set_index_for_repo(
+            Path::new("/root/.git"),
+            &[
+                (Path::new("a.txt"), "two\n".into()),
+                (Path::new("b.txt"), "unchanged\n".into()),
+            ],
+        );
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/root/.git"),
+            &[(Path::new("a.txt"), GitFileStatus::Modified)],
+        );
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| GitPanel::new(workspace, cx))
+            .unwrap();
+        cx.executor().run_until_parked();
+
+        panel.update(cx, |panel, cx| {
+            assert_eq!(changed_files(panel), [("a.txt".to_string(), true)]);
+            assert!(panel.commits.is_empty());
+            assert!(!panel.can_commit(cx));
+
+            panel.signoff = true;
+            panel.commit_editor.update(cx, |editor, cx| {
+                editor.set_text("Change a.txt\n\nBecause it was wrong.", cx)
+            });
+            assert!(panel.can_commit(cx));
+            panel.commit(&Commit, cx);
+        });
+        cx.executor().run_until_parked();
+
+        panel.update(cx, |panel, cx| {
+            assert!(panel.commit_editor.read(cx).text(cx).is_empty());
+            assert_eq!(
+                panel
+                    .commits
+                    .iter()
+                    .map(|commit| commit.subject.as_str())
+                    .collect::<Vec<_>>(),
+                ["Change a.txt"]
+            );
+
+            let repo = panel.repository.as_ref().unwrap().repo.lock();
+            let details = repo.show(&panel.commits[0].sha).unwrap();
+            assert_eq!(
+                details.message,
+                "Change a.txt\n\nBecause it was wrong.\n\nSigned-off-by: Fake User <fake@example.com>\n"
+            );
+            assert_eq!(details.changes.len(), 1);
+            assert_eq!(details.changes[0].path.0, Path::new("a.txt"));
+            assert_eq!(details.changes[0].old_text.as_deref(), Some("one\n"));
+            assert_eq!(details.changes[0].new_text.as_deref(), Some("two\n"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_toggle_staged(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "a.txt": "two\n",
+            }),
+        )
+        .await;
+        fs.set_head_for_repo(
+            Path::new("/root/.git"),
+            &[(Path::new("a.txt"), "one\n".to_string())],
+        );
+        fs.set_index_for_repo(
+            Path::new("/root/.git"),
+            &[(Path::new("a.txt"), "two\n".into())],
+        );
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/root/.git"),
+            &[(Path::new("a.txt"), GitFileStatus::Modified)],
+        );
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| GitPanel::new(workspace, cx))
+            .unwrap();
+        cx.executor().run_until_parked();
+        let index_text = |panel: &GitPanel| {
+            let repo = panel.repository.as_ref().unwrap().repo.lock();
+            repo.load_index_text(Path::new("a.txt"))
+        };
+
+        // Unstaging resets the index to HEAD.
+        panel.update(cx, |panel, cx| {
+            assert_eq!(changed_files(panel), [("a.txt".to_string(), true)]);
+            panel.toggle_staged(0, cx);
+        });
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, _| {
+            assert_eq!(index_text(panel).as_deref(), Some("one\n"))
+        });
+
+        // Staging a file that was deleted removes it from the index.
+        fs.remove_file(Path::new("/root/a.txt"), Default::default())
+            .await
+            .unwrap();
+        panel.update(cx, |panel, cx| {
+            // The fake repository's statuses don't follow its index.
+            panel.changed_files[0].staged = false;
+            panel.toggle_staged(0, cx);
+        });
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, _| assert_eq!(index_text(panel), None));
+    }
+
+    fn changed_files(panel: &GitPanel) -> Vec<(String, bool)> {
+        panel
+            .changed_files
+            .iter()
+            .map(|file| (file.repo_path.to_string_lossy().to_string(), file.staged))
+            .collect()
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init_settings(cx);
+            crate::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
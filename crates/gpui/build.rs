@@ -7,6 +7,12 @@ use std::{
 use cbindgen::Config;
 
 fn main() {
+    // The dispatch bindings and Metal shaders are only needed by the macOS
+    // platform. Other platforms render with the software renderer.
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("macos") {
+        return;
+    }
+
     generate_dispatch_bindings();
     let header_path = generate_shader_bindings();
     compile_metal_shaders(&header_path);
@@ -1,7 +1,11 @@
 mod app_menu;
 mod keystroke;
+#[cfg(target_os = "linux")]
+mod linux;
 #[cfg(target_os = "macos")]
 mod mac;
+#[cfg(any(target_os = "linux", test, feature = "test-support"))]
+mod software;
 #[cfg(any(test, feature = "test-support"))]
 mod test;
 
@@ -34,6 +38,8 @@ use uuid::Uuid;
 
 pub use app_menu::*;
 pub use keystroke::*;
+#[cfg(target_os = "linux")]
+pub(crate) use linux::*;
 #[cfg(target_os = "macos")]
 pub(crate) use mac::*;
 #[cfg(any(test, feature = "test-support"))]
@@ -45,6 +51,11 @@ pub(crate) fn current_platform() -> Rc<dyn Platform> {
     Rc::new(MacPlatform::new())
 }
 
+#[cfg(target_os = "linux")]
+pub(crate) fn current_platform() -> Rc<dyn Platform> {
+    Rc::new(LinuxPlatform::new())
+}
+
 pub(crate) trait Platform: 'static {
     fn background_executor(&self) -> BackgroundExecutor;
     fn foreground_executor(&self) -> ForegroundExecutor;
@@ -178,6 +189,14 @@ pub(crate) trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn invalidate(&self);
     fn draw(&self, scene: &Scene);
 
+    /// Rasterizes the given scene into an image the size of the window's
+    /// content, in device pixels.
+    fn render_to_image(&self, _scene: &Scene) -> Result<image::RgbaImage> {
+        Err(anyhow!(
+            "rendering to an image isn't supported on this platform"
+        ))
+    }
+
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas>;
 
     #[cfg(any(test, feature = "test-support"))]
@@ -0,0 +1,14 @@
+//! A headless platform, which doesn't depend on a window server or a GPU.
+//! Windows are rendered with the software renderer when an image of them is
+//! requested.
+mod dispatcher;
+mod display;
+mod platform;
+mod text_system;
+mod window;
+
+pub(crate) use dispatcher::*;
+pub(crate) use display::*;
+pub(crate) use platform::*;
+pub(crate) use text_system::*;
+pub(crate) use window::*;
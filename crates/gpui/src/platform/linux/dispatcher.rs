@@ -0,0 +1,141 @@
+use crate::{PlatformDispatcher, TaskLabel};
+use async_task::Runnable;
+use collections::VecDeque;
+use parking::{Parker, Unparker};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    sync::Arc,
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+/// Runs background tasks on a pool of threads, and main thread tasks on the
+/// thread that created the dispatcher, whenever it calls
+/// [`LinuxDispatcher::run_main_thread_tasks`].
+pub(crate) struct LinuxDispatcher {
+    main_thread_id: ThreadId,
+    main_thread_tasks: Mutex<VecDeque<Runnable>>,
+    main_thread_unparker: Unparker,
+    background_sender: smol::channel::Sender<Runnable>,
+    timers: Arc<Timers>,
+    parker: Arc<Mutex<Parker>>,
+}
+
+#[derive(Default)]
+struct Timers {
+    pending: Mutex<Vec<(Instant, Runnable)>>,
+    changed: Condvar,
+}
+
+impl LinuxDispatcher {
+    /// Creates a dispatcher whose main thread is the current thread. The given
+    /// unparker is used to wake it up when tasks are dispatched to it.
+    pub fn new(main_thread_unparker: Unparker) -> Self {
+        let (background_sender, background_receiver) = smol::channel::unbounded::<Runnable>();
+        for ix in 0..num_cpus::get() {
+            let receiver = background_receiver.clone();
+            thread::Builder::new()
+                .name(format!("gpui-background-{ix}"))
+                .spawn(move || {
+                    while let Ok(runnable) = smol::block_on(receiver.recv()) {
+                        runnable.run();
+                    }
+                })
+                .expect("failed to spawn background thread");
+        }
+
+        let timers = Arc::new(Timers::default());
+        thread::Builder::new()
+            .name("gpui-timers".into())
+            .spawn({
+                let timers = timers.clone();
+                let background_sender = background_sender.clone();
+                move || timers.run(background_sender)
+            })
+            .expect("failed to spawn timer thread");
+
+        Self {
+            main_thread_id: thread::current().id(),
+            main_thread_tasks: Mutex::new(VecDeque::new()),
+            main_thread_unparker,
+            background_sender,
+            timers,
+            parker: Arc::new(Mutex::new(Parker::new())),
+        }
+    }
+
+    /// Runs the tasks that have been dispatched to the main thread, returning
+    /// whether there were any.
+    pub fn run_main_thread_tasks(&self) -> bool {
+        debug_assert!(self.is_main_thread());
+        let mut ran_any = false;
+        loop {
+            let runnable = self.main_thread_tasks.lock().pop_front();
+            let Some(runnable) = runnable else {
+                return ran_any;
+            };
+            runnable.run();
+            ran_any = true;
+        }
+    }
+}
+
+impl Timers {
+    fn run(&self, background_sender: smol::channel::Sender<Runnable>) {
+        let mut pending = self.pending.lock();
+        loop {
+            let now = Instant::now();
+            let mut ix = 0;
+            while ix < pending.len() {
+                if pending[ix].0 <= now {
+                    let (_, runnable) = pending.swap_remove(ix);
+                    background_sender.try_send(runnable).ok();
+                } else {
+                    ix += 1;
+                }
+            }
+
+            match pending.iter().map(|(deadline, _)| *deadline).min() {
+                Some(deadline) => {
+                    self.changed.wait_until(&mut pending, deadline);
+                }
+                None => self.changed.wait(&mut pending),
+            }
+        }
+    }
+}
+
+impl PlatformDispatcher for LinuxDispatcher {
+    fn is_main_thread(&self) -> bool {
+        thread::current().id() == self.main_thread_id
+    }
+
+    fn dispatch(&self, runnable: Runnable, _: Option<TaskLabel>) {
+        self.background_sender.try_send(runnable).ok();
+    }
+
+    fn dispatch_on_main_thread(&self, runnable: Runnable) {
+        self.main_thread_tasks.lock().push_back(runnable);
+        self.main_thread_unparker.unpark();
+    }
+
+    fn dispatch_after(&self, duration: Duration, runnable: Runnable) {
+        self.timers
+            .pending
+            .lock()
+            .push((Instant::now() + duration, runnable));
+        self.timers.changed.notify_one();
+    }
+
+    fn tick(&self, _background_only: bool) -> bool {
+        false
+    }
+
+    fn park(&self) {
+        self.parker.lock().park()
+    }
+
+    fn unparker(&self) -> Unparker {
+        self.parker.lock().unparker()
+    }
+}
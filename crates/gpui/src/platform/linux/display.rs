@@ -0,0 +1,37 @@
+use crate::{Bounds, DisplayId, GlobalPixels, PlatformDisplay, Point};
+use anyhow::Result;
+use uuid::Uuid;
+
+/// The display that headless windows are placed on. It isn't backed by a
+/// monitor, so it has a fixed size.
+#[derive(Debug)]
+pub(crate) struct LinuxDisplay {
+    id: DisplayId,
+    bounds: Bounds<GlobalPixels>,
+}
+
+impl LinuxDisplay {
+    pub fn new() -> Self {
+        Self {
+            id: DisplayId(0),
+            bounds: Bounds::from_corners(
+                Point::default(),
+                Point::new(GlobalPixels(1920.), GlobalPixels(1080.)),
+            ),
+        }
+    }
+}
+
+impl PlatformDisplay for LinuxDisplay {
+    fn id(&self) -> DisplayId {
+        self.id
+    }
+
+    fn uuid(&self) -> Result<Uuid> {
+        Ok(Uuid::from_u128(self.id.0 as u128))
+    }
+
+    fn bounds(&self) -> Bounds<GlobalPixels> {
+        self.bounds
+    }
+}
@@ -0,0 +1,307 @@
+use super::{LinuxDispatcher, LinuxDisplay, LinuxTextSystem, LinuxWindow};
+use crate::{
+    Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
+    ForegroundExecutor, Keymap, Menu, PathPromptOptions, Platform, PlatformDisplay, PlatformInput,
+    PlatformTextSystem, PlatformWindow, SemanticVersion, Task, WindowOptions,
+};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use futures::channel::oneshot;
+use parking::Parker;
+use parking_lot::Mutex;
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
+use time::UtcOffset;
+
+/// The interval at which display links fire, as there's no monitor to sync to.
+const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// A platform without a window server. Windows are never shown on screen, but
+/// their scenes can be rendered to images with a software rasterizer, which is
+/// what screenshot tests and headless tools need.
+pub(crate) struct LinuxPlatform(RefCell<LinuxPlatformState>);
+
+struct LinuxPlatformState {
+    background_executor: BackgroundExecutor,
+    foreground_executor: ForegroundExecutor,
+    dispatcher: Arc<LinuxDispatcher>,
+    main_thread_parker: Option<Parker>,
+    text_system: Arc<LinuxTextSystem>,
+    display: Rc<LinuxDisplay>,
+    display_links: Arc<Mutex<HashMap<DisplayId, DisplayLink>>>,
+    active_window: Option<AnyWindowHandle>,
+    clipboard: Option<ClipboardItem>,
+    quitting: bool,
+    quit_callbacks: Vec<Box<dyn FnMut()>>,
+}
+
+struct DisplayLink {
+    output_callback: Box<dyn FnMut() + Send>,
+    task: Option<Task<()>>,
+}
+
+impl LinuxPlatform {
+    pub(crate) fn new() -> Self {
+        let (main_thread_parker, main_thread_unparker) = parking::pair();
+        let dispatcher = Arc::new(LinuxDispatcher::new(main_thread_unparker));
+        Self(RefCell::new(LinuxPlatformState {
+            background_executor: BackgroundExecutor::new(dispatcher.clone()),
+            foreground_executor: ForegroundExecutor::new(dispatcher.clone()),
+            dispatcher,
+            main_thread_parker: Some(main_thread_parker),
+            text_system: Arc::new(LinuxTextSystem::new()),
+            display: Rc::new(LinuxDisplay::new()),
+            display_links: Default::default(),
+            active_window: None,
+            clipboard: None,
+            quitting: false,
+            quit_callbacks: Vec::new(),
+        }))
+    }
+}
+
+impl Platform for LinuxPlatform {
+    fn background_executor(&self) -> BackgroundExecutor {
+        self.0.borrow().background_executor.clone()
+    }
+
+    fn foreground_executor(&self) -> ForegroundExecutor {
+        self.0.borrow().foreground_executor.clone()
+    }
+
+    fn text_system(&self) -> Arc<dyn PlatformTextSystem> {
+        self.0.borrow().text_system.clone()
+    }
+
+    fn run(&self, on_finish_launching: Box<dyn FnOnce()>) {
+        let (dispatcher, parker) = {
+            let mut state = self.0.borrow_mut();
+            let parker = state
+                .main_thread_parker
+                .take()
+                .expect("the platform can only be run once");
+            (state.dispatcher.clone(), parker)
+        };
+
+        on_finish_launching();
+        loop {
+            dispatcher.run_main_thread_tasks();
+            if self.0.borrow().quitting {
+                break;
+            }
+            parker.park();
+        }
+
+        let mut quit_callbacks = std::mem::take(&mut self.0.borrow_mut().quit_callbacks);
+        for callback in &mut quit_callbacks {
+            callback();
+        }
+    }
+
+    fn quit(&self) {
+        // The run loop checks this flag once the task calling this method has
+        // finished, so that nothing is borrowed while the app shuts down.
+        self.0.borrow_mut().quitting = true;
+    }
+
+    fn restart(&self) {
+        log::error!("restarting isn't supported on headless platforms");
+    }
+
+    fn activate(&self, _ignoring_other_apps: bool) {}
+
+    fn hide(&self) {}
+
+    fn hide_other_apps(&self) {}
+
+    fn unhide_other_apps(&self) {}
+
+    fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
+        vec![self.0.borrow().display.clone()]
+    }
+
+    fn display(&self, id: DisplayId) -> Option<Rc<dyn PlatformDisplay>> {
+        self.displays()
+            .into_iter()
+            .find(|display| display.id() == id)
+    }
+
+    fn active_window(&self) -> Option<AnyWindowHandle> {
+        self.0.borrow().active_window
+    }
+
+    fn open_window(
+        &self,
+        handle: AnyWindowHandle,
+        options: WindowOptions,
+    ) -> Box<dyn PlatformWindow> {
+        let mut state = self.0.borrow_mut();
+        state.active_window = Some(handle);
+        Box::new(LinuxWindow::new(
+            options,
+            state.display.clone(),
+            state.foreground_executor.clone(),
+        ))
+    }
+
+    fn set_display_link_output_callback(
+        &self,
+        display_id: DisplayId,
+        callback: Box<dyn FnMut() + Send>,
+    ) {
+        self.0.borrow().display_links.lock().insert(
+            display_id,
+            DisplayLink {
+                output_callback: callback,
+                task: None,
+            },
+        );
+    }
+
+    fn start_display_link(&self, display_id: DisplayId) {
+        let state = self.0.borrow();
+        let mut display_links = state.display_links.lock();
+        let Some(display_link) = display_links.get_mut(&display_id) else {
+            return;
+        };
+        if display_link.task.is_some() {
+            return;
+        }
+
+        let executor = state.background_executor.clone();
+        let all_display_links = state.display_links.clone();
+        display_link.task = Some(state.background_executor.spawn(async move {
+            loop {
+                executor.timer(FRAME_INTERVAL).await;
+                let mut display_links = all_display_links.lock();
+                let Some(display_link) = display_links.get_mut(&display_id) else {
+                    break;
+                };
+                (display_link.output_callback)();
+            }
+        }));
+    }
+
+    fn stop_display_link(&self, display_id: DisplayId) {
+        let task = self
+            .0
+            .borrow()
+            .display_links
+            .lock()
+            .get_mut(&display_id)
+            .and_then(|display_link| display_link.task.take());
+        drop(task);
+    }
+
+    fn open_url(&self, url: &str) {
+        log::info!("not opening {url:?} on a headless platform");
+    }
+
+    fn on_open_urls(&self, _callback: Box<dyn FnMut(Vec<String>)>) {}
+
+    fn prompt_for_paths(
+        &self,
+        _options: PathPromptOptions,
+    ) -> oneshot::Receiver<Option<Vec<PathBuf>>> {
+        let (done_tx, done_rx) = oneshot::channel();
+        done_tx.send(None).ok();
+        done_rx
+    }
+
+    fn prompt_for_new_path(&self, _directory: &Path) -> oneshot::Receiver<Option<PathBuf>> {
+        let (done_tx, done_rx) = oneshot::channel();
+        done_tx.send(None).ok();
+        done_rx
+    }
+
+    fn reveal_path(&self, _path: &Path) {}
+
+    fn on_become_active(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_resign_active(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_quit(&self, callback: Box<dyn FnMut()>) {
+        self.0.borrow_mut().quit_callbacks.push(callback);
+    }
+
+    fn on_reopen(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_event(&self, _callback: Box<dyn FnMut(PlatformInput) -> bool>) {}
+
+    fn set_menus(&self, _menus: Vec<Menu>, _keymap: &Keymap) {}
+
+    fn on_app_menu_action(&self, _callback: Box<dyn FnMut(&dyn Action)>) {}
+
+    fn on_will_open_app_menu(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_validate_app_menu_command(&self, _callback: Box<dyn FnMut(&dyn Action) -> bool>) {}
+
+    fn os_name(&self) -> &'static str {
+        "Linux"
+    }
+
+    fn os_version(&self) -> Result<SemanticVersion> {
+        let release = std::fs::read_to_string("/proc/sys/kernel/osrelease")?;
+        let version = release
+            .trim()
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()
+            .unwrap_or_default();
+        version.parse()
+    }
+
+    fn app_version(&self) -> Result<SemanticVersion> {
+        Err(anyhow!("app version is unknown on headless platforms"))
+    }
+
+    fn app_path(&self) -> Result<PathBuf> {
+        Ok(std::env::current_exe()?)
+    }
+
+    fn local_timezone(&self) -> UtcOffset {
+        UtcOffset::UTC
+    }
+
+    fn double_click_interval(&self) -> Duration {
+        Duration::from_millis(500)
+    }
+
+    fn path_for_auxiliary_executable(&self, name: &str) -> Result<PathBuf> {
+        let app_path = self.app_path()?;
+        let directory = app_path
+            .parent()
+            .ok_or_else(|| anyhow!("no parent directory for {app_path:?}"))?;
+        Ok(directory.join(name))
+    }
+
+    fn set_cursor_style(&self, _style: CursorStyle) {}
+
+    fn should_auto_hide_scrollbars(&self) -> bool {
+        false
+    }
+
+    fn write_to_clipboard(&self, item: ClipboardItem) {
+        self.0.borrow_mut().clipboard = Some(item);
+    }
+
+    fn read_from_clipboard(&self) -> Option<ClipboardItem> {
+        self.0.borrow().clipboard.clone()
+    }
+
+    fn write_credentials(&self, _url: &str, _username: &str, _password: &[u8]) -> Task<Result<()>> {
+        Task::ready(Err(anyhow!("headless platforms have no credential store")))
+    }
+
+    fn read_credentials(&self, _url: &str) -> Task<Result<Option<(String, Vec<u8>)>>> {
+        Task::ready(Ok(None))
+    }
+
+    fn delete_credentials(&self, _url: &str) -> Task<Result<()>> {
+        Task::ready(Ok(()))
+    }
+}
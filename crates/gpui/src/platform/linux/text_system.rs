@@ -0,0 +1,578 @@
+use crate::{
+    point, px, Bounds, DevicePixels, Font, FontId, FontMetrics, FontRun, FontStyle, GlyphId,
+    LineLayout, Pixels, PlatformTextSystem, Point, RenderGlyphParams, Result, ShapedGlyph,
+    ShapedRun, Size, SUBPIXEL_VARIANTS,
+};
+use anyhow::anyhow;
+use collections::{BTreeSet, HashMap};
+use parking_lot::{RwLock, RwLockUpgradableReadGuard};
+use smallvec::SmallVec;
+use std::{
+    borrow::Cow,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use ttf_parser::{name_id, Face, OutlineBuilder};
+
+/// The families used in place of the ones that aren't installed, such as the
+/// `Helvetica` in GPUI's fallback font stack.
+const FALLBACK_FAMILIES: &[&str] = &["DejaVu Sans", "Liberation Sans", "Noto Sans", "FreeSans"];
+
+/// A text system that doesn't rely on any system libraries. Fonts are parsed
+/// and rasterized on the CPU, and text is laid out one character at a time,
+/// without shaping.
+pub(crate) struct LinuxTextSystem(RwLock<LinuxTextSystemState>);
+
+struct LinuxTextSystemState {
+    /// The faces that can be loaded, starting with the ones that have been
+    /// added in memory. System faces are only discovered when a family that
+    /// hasn't been added is requested.
+    faces: Vec<FaceInfo>,
+    system_faces_discovered: bool,
+    fonts: Vec<LoadedFont>,
+    font_ids_by_face: HashMap<usize, FontId>,
+    font_selections: HashMap<Font, FontId>,
+}
+
+struct FaceInfo {
+    family: String,
+    weight: u16,
+    italic: bool,
+    source: FaceSource,
+    index: u32,
+}
+
+enum FaceSource {
+    Memory(Arc<Cow<'static, [u8]>>),
+    File(PathBuf),
+}
+
+struct LoadedFont {
+    data: Arc<Cow<'static, [u8]>>,
+    index: u32,
+    metrics: FontMetrics,
+}
+
+impl LinuxTextSystem {
+    pub(crate) fn new() -> Self {
+        Self(RwLock::new(LinuxTextSystemState {
+            faces: Vec::new(),
+            system_faces_discovered: false,
+            fonts: Vec::new(),
+            font_ids_by_face: HashMap::default(),
+            font_selections: HashMap::default(),
+        }))
+    }
+}
+
+impl Default for LinuxTextSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlatformTextSystem for LinuxTextSystem {
+    fn add_fonts(&self, fonts: Vec<Cow<'static, [u8]>>) -> Result<()> {
+        let mut state = self.0.write();
+        for bytes in fonts {
+            let data = Arc::new(bytes);
+            let faces = describe_faces(&data, || FaceSource::Memory(data.clone()));
+            if faces.is_empty() {
+                return Err(anyhow!("could not parse an embedded font"));
+            }
+            state.faces.extend(faces);
+        }
+        state.font_selections.clear();
+        Ok(())
+    }
+
+    fn all_font_names(&self) -> Vec<String> {
+        self.all_font_families()
+    }
+
+    fn all_font_families(&self) -> Vec<String> {
+        let mut state = self.0.write();
+        state.discover_system_faces();
+        state
+            .faces
+            .iter()
+            .map(|face| face.family.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    fn font_id(&self, font: &Font) -> Result<FontId> {
+        let lock = self.0.upgradable_read();
+        if let Some(font_id) = lock.font_selections.get(font) {
+            return Ok(*font_id);
+        }
+
+        let mut lock = RwLockUpgradableReadGuard::upgrade(lock);
+        let face_ix = match lock.select_face(font) {
+            Some(face_ix) => face_ix,
+            None => {
+                lock.discover_system_faces();
+                lock.select_face(font)
+                    .ok_or_else(|| anyhow!("could not find font family {:?}", font.family))?
+            }
+        };
+        let font_id = lock.load_face(face_ix)?;
+        lock.font_selections.insert(font.clone(), font_id);
+        Ok(font_id)
+    }
+
+    fn font_metrics(&self, font_id: FontId) -> FontMetrics {
+        self.0.read().fonts[font_id.0].metrics
+    }
+
+    fn typographic_bounds(&self, font_id: FontId, glyph_id: GlyphId) -> Result<Bounds<f32>> {
+        self.0.read().fonts[font_id.0].with_face(|face| {
+            Ok(face
+                .glyph_bounding_box(ttf_parser::GlyphId(glyph_id.0 as u16))
+                .map(|rect| Bounds {
+                    origin: point(rect.x_min as f32, rect.y_min as f32),
+                    size: Size {
+                        width: (rect.x_max - rect.x_min) as f32,
+                        height: (rect.y_max - rect.y_min) as f32,
+                    },
+                })
+                .unwrap_or_default())
+        })
+    }
+
+    fn advance(&self, font_id: FontId, glyph_id: GlyphId) -> Result<Size<f32>> {
+        self.0.read().fonts[font_id.0].with_face(|face| {
+            Ok(Size {
+                width: face
+                    .glyph_hor_advance(ttf_parser::GlyphId(glyph_id.0 as u16))
+                    .unwrap_or(0) as f32,
+                height: 0.,
+            })
+        })
+    }
+
+    fn glyph_for_char(&self, font_id: FontId, ch: char) -> Option<GlyphId> {
+        self.0.read().fonts[font_id.0]
+            .with_face(|face| Ok(face.glyph_index(ch)))
+            .ok()
+            .flatten()
+            .map(|glyph_id| GlyphId(glyph_id.0 as u32))
+    }
+
+    fn glyph_raster_bounds(&self, params: &RenderGlyphParams) -> Result<Bounds<DevicePixels>> {
+        self.0.read().fonts[params.font_id.0].raster_bounds(params)
+    }
+
+    fn rasterize_glyph(
+        &self,
+        params: &RenderGlyphParams,
+        raster_bounds: Bounds<DevicePixels>,
+    ) -> Result<(Size<DevicePixels>, Vec<u8>)> {
+        self.0.read().fonts[params.font_id.0].rasterize_glyph(params, raster_bounds)
+    }
+
+    fn layout_line(&self, text: &str, font_size: Pixels, font_runs: &[FontRun]) -> LineLayout {
+        self.0.read().layout_line(text, font_size, font_runs)
+    }
+
+    fn wrap_line(
+        &self,
+        text: &str,
+        font_id: FontId,
+        font_size: Pixels,
+        width: Pixels,
+    ) -> Vec<usize> {
+        self.0.read().fonts[font_id.0].wrap_line(text, font_size, width)
+    }
+}
+
+impl LinuxTextSystemState {
+    /// Finds the face that best matches the given font among those of its
+    /// family, falling back to a common sans-serif family if none of the faces
+    /// are from it.
+    fn select_face(&self, font: &Font) -> Option<usize> {
+        let best_match = |family: &str| {
+            self.faces
+                .iter()
+                .enumerate()
+                .filter(|(_, face)| face.family.eq_ignore_ascii_case(family))
+                .min_by_key(|(_, face)| {
+                    // Added fonts take precedence over system fonts.
+                    let source_penalty = match face.source {
+                        FaceSource::Memory(_) => 0,
+                        FaceSource::File(_) => 10_000,
+                    };
+                    let italic = font.style != FontStyle::Normal;
+                    let style_penalty = if face.italic == italic { 0 } else { 1000 };
+                    source_penalty
+                        + style_penalty
+                        + (face.weight as i32 - font.weight.0 as i32).abs()
+                })
+                .map(|(ix, _)| ix)
+        };
+
+        best_match(font.family.as_ref()).or_else(|| {
+            if !self.system_faces_discovered {
+                return None;
+            }
+            FALLBACK_FAMILIES
+                .iter()
+                .find_map(|family| best_match(family))
+                .or_else(|| (!self.faces.is_empty()).then_some(0))
+        })
+    }
+
+    fn discover_system_faces(&mut self) {
+        if self.system_faces_discovered {
+            return;
+        }
+        self.system_faces_discovered = true;
+
+        let mut directories = vec![
+            PathBuf::from("/usr/share/fonts"),
+            PathBuf::from("/usr/local/share/fonts"),
+        ];
+        if let Some(home) = std::env::var_os("HOME") {
+            let home = PathBuf::from(home);
+            directories.push(home.join(".local/share/fonts"));
+            directories.push(home.join(".fonts"));
+        }
+        for directory in directories {
+            self.discover_faces_in(&directory);
+        }
+    }
+
+    fn discover_faces_in(&mut self, directory: &Path) {
+        let Ok(entries) = fs::read_dir(directory) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                self.discover_faces_in(&path);
+                continue;
+            }
+
+            let is_font = path.extension().map_or(false, |extension| {
+                ["ttf", "otf", "ttc", "otc"]
+                    .iter()
+                    .any(|font_extension| extension.eq_ignore_ascii_case(font_extension))
+            });
+            if !is_font {
+                continue;
+            }
+            match fs::read(&path) {
+                Ok(data) => self
+                    .faces
+                    .extend(describe_faces(&data, || FaceSource::File(path.clone()))),
+                Err(error) => log::warn!("failed to read font {path:?}: {error}"),
+            }
+        }
+    }
+
+    fn load_face(&mut self, face_ix: usize) -> Result<FontId> {
+        if let Some(font_id) = self.font_ids_by_face.get(&face_ix) {
+            return Ok(*font_id);
+        }
+
+        let face = &self.faces[face_ix];
+        let data = match &face.source {
+            FaceSource::Memory(data) => data.clone(),
+            FaceSource::File(path) => Arc::new(Cow::Owned(fs::read(path)?)),
+        };
+        let metrics = font_metrics(&Face::from_slice(&data, face.index)?);
+        let font_id = FontId(self.fonts.len());
+        self.fonts.push(LoadedFont {
+            data,
+            index: face.index,
+            metrics,
+        });
+        self.font_ids_by_face.insert(face_ix, font_id);
+        Ok(font_id)
+    }
+
+    fn layout_line(&self, text: &str, font_size: Pixels, font_runs: &[FontRun]) -> LineLayout {
+        let mut runs = Vec::new();
+        let mut x = 0.;
+        let mut ascent: f32 = 0.;
+        let mut descent: f32 = 0.;
+        let mut run_start = 0;
+        for run in font_runs {
+            let run_end = (run_start + run.len).min(text.len());
+            let font = &self.fonts[run.font_id.0];
+            let scale = font_size.0 / font.metrics.units_per_em as f32;
+            ascent = ascent.max(font.metrics.ascent * scale);
+            descent = descent.max(-font.metrics.descent * scale);
+
+            let mut glyphs = SmallVec::new();
+            font.with_face(|face| {
+                for (ix, ch) in text[run_start..run_end].char_indices() {
+                    let glyph_id = face.glyph_index(ch).unwrap_or(ttf_parser::GlyphId(0));
+                    glyphs.push(ShapedGlyph {
+                        id: GlyphId(glyph_id.0 as u32),
+                        position: point(px(x), px(0.)),
+                        index: run_start + ix,
+                        is_emoji: false,
+                    });
+                    x += face.glyph_hor_advance(glyph_id).unwrap_or(0) as f32 * scale;
+                }
+                Ok(())
+            })
+            .ok();
+            runs.push(ShapedRun {
+                font_id: run.font_id,
+                glyphs,
+            });
+
+            run_start = run_end;
+            if run_start == text.len() {
+                break;
+            }
+        }
+
+        LineLayout {
+            font_size,
+            width: px(x),
+            ascent: px(ascent),
+            descent: px(descent),
+            runs,
+            len: text.len(),
+        }
+    }
+}
+
+impl LoadedFont {
+    fn with_face<R>(&self, f: impl FnOnce(&Face) -> Result<R>) -> Result<R> {
+        f(&Face::from_slice(&self.data, self.index)?)
+    }
+
+    /// The bounds of the given glyph's pixels relative to its origin on the
+    /// baseline, with y pointing down.
+    fn raster_bounds(&self, params: &RenderGlyphParams) -> Result<Bounds<DevicePixels>> {
+        let scale = self.scale(params);
+        self.with_face(|face| {
+            let Some(rect) = face.glyph_bounding_box(ttf_parser::GlyphId(params.glyph_id.0 as u16))
+            else {
+                return Ok(Bounds::default());
+            };
+            let left = (rect.x_min as f32 * scale).floor() as i32;
+            let right = (rect.x_max as f32 * scale).ceil() as i32;
+            let top = (-rect.y_max as f32 * scale).floor() as i32;
+            let bottom = (-rect.y_min as f32 * scale).ceil() as i32;
+            Ok(Bounds {
+                origin: point(DevicePixels(left), DevicePixels(top)),
+                size: Size {
+                    width: DevicePixels(right - left),
+                    height: DevicePixels(bottom - top),
+                },
+            })
+        })
+    }
+
+    fn rasterize_glyph(
+        &self,
+        params: &RenderGlyphParams,
+        glyph_bounds: Bounds<DevicePixels>,
+    ) -> Result<(Size<DevicePixels>, Vec<u8>)> {
+        if glyph_bounds.size.width.0 == 0 || glyph_bounds.size.height.0 == 0 {
+            return Err(anyhow!("glyph bounds are empty"));
+        }
+
+        // Add an extra pixel when the subpixel variant isn't zero to make room for anti-aliasing.
+        let mut bitmap_size = glyph_bounds.size;
+        if params.subpixel_variant.x > 0 {
+            bitmap_size.width += DevicePixels(1);
+        }
+        if params.subpixel_variant.y > 0 {
+            bitmap_size.height += DevicePixels(1);
+        }
+
+        let subpixel_shift = params
+            .subpixel_variant
+            .map(|v| v as f32 / SUBPIXEL_VARIANTS as f32);
+        let mut outline = OutlinePath {
+            builder: tiny_skia::PathBuilder::new(),
+            scale: self.scale(params),
+            offset: point(
+                subpixel_shift.x - glyph_bounds.origin.x.0 as f32,
+                subpixel_shift.y - glyph_bounds.origin.y.0 as f32,
+            ),
+        };
+        self.with_face(|face| {
+            face.outline_glyph(ttf_parser::GlyphId(params.glyph_id.0 as u16), &mut outline);
+            Ok(())
+        })?;
+
+        let mut pixmap =
+            tiny_skia::Pixmap::new(bitmap_size.width.0 as u32, bitmap_size.height.0 as u32)
+                .ok_or_else(|| anyhow!("invalid glyph size {bitmap_size:?}"))?;
+        if let Some(path) = outline.builder.finish() {
+            let mut paint = tiny_skia::Paint::default();
+            paint.anti_alias = true;
+            pixmap.fill_path(
+                &path,
+                &paint,
+                tiny_skia::FillRule::Winding,
+                tiny_skia::Transform::identity(),
+                None,
+            );
+        }
+
+        let alpha_mask = pixmap.pixels().iter().map(|p| p.alpha()).collect();
+        Ok((bitmap_size, alpha_mask))
+    }
+
+    fn wrap_line(&self, text: &str, font_size: Pixels, width: Pixels) -> Vec<usize> {
+        let scale = font_size.0 / self.metrics.units_per_em as f32;
+        let characters = self
+            .with_face(|face| {
+                Ok(text
+                    .char_indices()
+                    .map(|(ix, ch)| {
+                        let advance = face
+                            .glyph_index(ch)
+                            .and_then(|glyph_id| face.glyph_hor_advance(glyph_id))
+                            .unwrap_or(0);
+                        (ix, ch, advance as f32 * scale)
+                    })
+                    .collect::<Vec<_>>())
+            })
+            .unwrap_or_default();
+
+        // Break after the last whitespace that fits on the line, or in the
+        // middle of a word that doesn't fit on a line of its own.
+        let mut break_indices = Vec::new();
+        let mut line_start = 0;
+        let mut line_width = 0.;
+        let mut last_break = None;
+        for (character_ix, &(_, ch, advance)) in characters.iter().enumerate() {
+            if line_width + advance > width.0 && character_ix > line_start && !ch.is_whitespace() {
+                let break_ix = last_break.take().unwrap_or(character_ix);
+                break_indices.push(characters[break_ix].0);
+                line_start = break_ix;
+                line_width = characters[break_ix..character_ix]
+                    .iter()
+                    .map(|(_, _, advance)| advance)
+                    .sum();
+            }
+            line_width += advance;
+            if ch.is_whitespace() {
+                last_break = Some(character_ix + 1);
+            }
+        }
+        break_indices
+    }
+
+    /// The scale from font units to device pixels.
+    fn scale(&self, params: &RenderGlyphParams) -> f32 {
+        params.font_size.0 * params.scale_factor / self.metrics.units_per_em as f32
+    }
+}
+
+/// Converts glyph outlines from font units, with y pointing up, to a path in
+/// device pixels with y pointing down.
+struct OutlinePath {
+    builder: tiny_skia::PathBuilder,
+    scale: f32,
+    offset: Point<f32>,
+}
+
+impl OutlinePath {
+    fn transform(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * self.scale + self.offset.x,
+            -y * self.scale + self.offset.y,
+        )
+    }
+}
+
+impl OutlineBuilder for OutlinePath {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.transform(x, y);
+        self.builder.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let (x, y) = self.transform(x, y);
+        self.builder.line_to(x, y);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (x1, y1) = self.transform(x1, y1);
+        let (x, y) = self.transform(x, y);
+        self.builder.quad_to(x1, y1, x, y);
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (x1, y1) = self.transform(x1, y1);
+        let (x2, y2) = self.transform(x2, y2);
+        let (x, y) = self.transform(x, y);
+        self.builder.cubic_to(x1, y1, x2, y2, x, y);
+    }
+
+    fn close(&mut self) {
+        self.builder.close();
+    }
+}
+
+/// Describes the faces in a font file, which can be a collection.
+fn describe_faces(data: &[u8], source: impl Fn() -> FaceSource) -> Vec<FaceInfo> {
+    (0..faces_len(data))
+        .filter_map(|index| {
+            let face = Face::from_slice(data, index).ok()?;
+            // Skip symbol fonts and the like, as they can't be used for text.
+            face.glyph_index('m')?;
+            let family = face_name(&face, name_id::TYPOGRAPHIC_FAMILY)
+                .or_else(|| face_name(&face, name_id::FAMILY))?;
+            Some(FaceInfo {
+                family,
+                weight: face.weight().to_number(),
+                italic: face.is_italic() || face.is_oblique(),
+                source: source(),
+                index,
+            })
+        })
+        .collect()
+}
+
+fn faces_len(data: &[u8]) -> u32 {
+    ttf_parser::fonts_in_collection(data).unwrap_or(1)
+}
+
+fn face_name(face: &Face, name_id: u16) -> Option<String> {
+    face.names()
+        .filter(|name| name.name_id() == name_id)
+        .find_map(|name| name.to_string())
+}
+
+fn font_metrics(face: &Face) -> FontMetrics {
+    let units_per_em = face.units_per_em().unwrap_or(1000);
+    let ascent = face.ascender() as f32;
+    let descent = face.descender() as f32;
+    let underline = face.underline_metrics();
+    FontMetrics {
+        units_per_em: units_per_em as u32,
+        ascent,
+        descent,
+        line_gap: face.line_gap() as f32,
+        underline_position: underline.map_or(-(units_per_em as f32) / 10., |metrics| {
+            metrics.position as f32
+        }),
+        underline_thickness: underline.map_or(units_per_em as f32 / 20., |metrics| {
+            metrics.thickness as f32
+        }),
+        cap_height: face.capital_height().unwrap_or(0) as f32,
+        x_height: face.x_height().unwrap_or(0) as f32,
+        // Glyphs may extend past the em square horizontally, so leave room for
+        // them to be as wide as it is tall.
+        bounding_box: Bounds {
+            origin: point(-(units_per_em as f32), descent),
+            size: Size {
+                width: 3. * units_per_em as f32,
+                height: ascent - descent,
+            },
+        },
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    platform::software::{SoftwareAtlas, SoftwareRenderer},
+    px, DevicePixels, ForegroundExecutor, Modifiers, Pixels, PlatformAtlas, PlatformDisplay,
+    PlatformInput, PlatformInputHandler, PlatformWindow, Point, PromptLevel, Scene, Size,
+    WindowAppearance, WindowBounds, WindowOptions,
+};
+use anyhow::Result;
+use futures::channel::oneshot;
+use image::RgbaImage;
+use raw_window_handle::{
+    DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle,
+};
+use std::{any::Any, cell::RefCell, rc::Rc, sync::Arc};
+
+/// A window that isn't shown on any screen. Its scenes are only rasterized
+/// when they're rendered to an image.
+pub(crate) struct LinuxWindow(Rc<RefCell<LinuxWindowState>>);
+
+struct LinuxWindowState {
+    bounds: WindowBounds,
+    display: Rc<dyn PlatformDisplay>,
+    executor: ForegroundExecutor,
+    sprite_atlas: Arc<SoftwareAtlas>,
+    frame_requested: bool,
+    input_handler: Option<PlatformInputHandler>,
+    request_frame_callback: Option<Box<dyn FnMut()>>,
+    active_status_change_callback: Option<Box<dyn FnMut(bool)>>,
+}
+
+impl LinuxWindow {
+    pub fn new(
+        options: WindowOptions,
+        display: Rc<dyn PlatformDisplay>,
+        executor: ForegroundExecutor,
+    ) -> Self {
+        let window = Self(Rc::new(RefCell::new(LinuxWindowState {
+            bounds: options.bounds,
+            display,
+            executor,
+            sprite_atlas: Arc::new(SoftwareAtlas::new()),
+            frame_requested: false,
+            input_handler: None,
+            request_frame_callback: None,
+            active_status_change_callback: None,
+        })));
+        // Draw the first frame as soon as the window has been set up, like
+        // platforms with a real window server would.
+        window.invalidate();
+        window
+    }
+}
+
+impl HasWindowHandle for LinuxWindow {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        Err(HandleError::NotSupported)
+    }
+}
+
+impl HasDisplayHandle for LinuxWindow {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        Err(HandleError::NotSupported)
+    }
+}
+
+impl PlatformWindow for LinuxWindow {
+    fn bounds(&self) -> WindowBounds {
+        self.0.borrow().bounds
+    }
+
+    fn content_size(&self) -> Size<Pixels> {
+        let bounds = match self.bounds() {
+            WindowBounds::Fixed(bounds) => bounds,
+            WindowBounds::Maximized | WindowBounds::Fullscreen => self.display().bounds(),
+        };
+        bounds.size.map(|pixels| px(pixels.0))
+    }
+
+    fn scale_factor(&self) -> f32 {
+        1.0
+    }
+
+    fn titlebar_height(&self) -> Pixels {
+        px(0.)
+    }
+
+    fn appearance(&self) -> WindowAppearance {
+        WindowAppearance::Light
+    }
+
+    fn display(&self) -> Rc<dyn PlatformDisplay> {
+        self.0.borrow().display.clone()
+    }
+
+    fn mouse_position(&self) -> Point<Pixels> {
+        Point::default()
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        Modifiers::default()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn set_input_handler(&mut self, input_handler: PlatformInputHandler) {
+        self.0.borrow_mut().input_handler = Some(input_handler);
+    }
+
+    fn take_input_handler(&mut self) -> Option<PlatformInputHandler> {
+        self.0.borrow_mut().input_handler.take()
+    }
+
+    fn prompt(
+        &self,
+        _level: PromptLevel,
+        msg: &str,
+        _detail: Option<&str>,
+        answers: &[&str],
+    ) -> oneshot::Receiver<usize> {
+        // There's nobody to answer, so pick the first answer, which is the
+        // default one on other platforms.
+        log::info!("answering prompt {msg:?} with {:?}", answers.first());
+        let (done_tx, done_rx) = oneshot::channel();
+        done_tx.send(0).ok();
+        done_rx
+    }
+
+    fn activate(&self) {
+        let callback = self.0.borrow_mut().active_status_change_callback.take();
+        if let Some(mut callback) = callback {
+            callback(true);
+            self.0.borrow_mut().active_status_change_callback = Some(callback);
+        }
+    }
+
+    fn set_title(&mut self, _title: &str) {}
+
+    fn set_edited(&mut self, _edited: bool) {}
+
+    fn show_character_palette(&self) {}
+
+    fn minimize(&self) {}
+
+    fn zoom(&self) {}
+
+    fn toggle_full_screen(&self) {}
+
+    fn on_request_frame(&self, callback: Box<dyn FnMut()>) {
+        self.0.borrow_mut().request_frame_callback = Some(callback);
+    }
+
+    // Headless windows never receive input, and never get moved, resized or
+    // closed by a window manager, so the callbacks for those are dropped.
+    fn on_input(&self, _callback: Box<dyn FnMut(PlatformInput) -> bool>) {}
+
+    fn on_active_status_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.borrow_mut().active_status_change_callback = Some(callback);
+    }
+
+    fn on_resize(&self, _callback: Box<dyn FnMut(Size<Pixels>, f32)>) {}
+
+    fn on_fullscreen(&self, _callback: Box<dyn FnMut(bool)>) {}
+
+    fn on_moved(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn on_should_close(&self, _callback: Box<dyn FnMut() -> bool>) {}
+
+    fn on_close(&self, _callback: Box<dyn FnOnce()>) {}
+
+    fn on_appearance_changed(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn is_topmost_for_position(&self, _position: Point<Pixels>) -> bool {
+        true
+    }
+
+    fn invalidate(&self) {
+        let mut state = self.0.borrow_mut();
+        if state.frame_requested {
+            return;
+        }
+        state.frame_requested = true;
+
+        // Frames are requested while the app is being updated, so they're
+        // drawn once the current update has finished.
+        let this = Rc::downgrade(&self.0);
+        state
+            .executor
+            .spawn(async move {
+                let Some(this) = this.upgrade() else {
+                    return;
+                };
+                let callback = {
+                    let mut state = this.borrow_mut();
+                    state.frame_requested = false;
+                    state.request_frame_callback.take()
+                };
+                if let Some(mut callback) = callback {
+                    callback();
+                    this.borrow_mut().request_frame_callback = Some(callback);
+                }
+            })
+            .detach();
+    }
+
+    fn draw(&self, _scene: &Scene) {}
+
+    fn render_to_image(&self, scene: &Scene) -> Result<RgbaImage> {
+        let state = self.0.borrow();
+        let size = self
+            .content_size()
+            .map(|pixels| DevicePixels((pixels.0 * self.scale_factor()).ceil() as i32));
+        Ok(SoftwareRenderer::render(scene, &state.sprite_atlas, size))
+    }
+
+    fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
+        self.0.borrow().sprite_atlas.clone()
+    }
+}
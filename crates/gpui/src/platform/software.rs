@@ -0,0 +1,5 @@
+mod atlas;
+mod renderer;
+
+pub(crate) use atlas::*;
+pub(crate) use renderer::*;
@@ -0,0 +1,129 @@
+use crate::{
+    AtlasKey, AtlasTextureId, AtlasTextureKind, AtlasTile, Bounds, DevicePixels, PlatformAtlas,
+    Point, Size, TileId,
+};
+use anyhow::Result;
+use collections::HashMap;
+use parking_lot::Mutex;
+use std::borrow::Cow;
+
+/// An atlas that keeps its tiles in memory, so that they can be sampled by the
+/// [`SoftwareRenderer`](super::SoftwareRenderer). Every tile gets a texture of
+/// its own, as there's no GPU memory to economize on.
+#[derive(Default)]
+pub(crate) struct SoftwareAtlas(Mutex<SoftwareAtlasState>);
+
+#[derive(Default)]
+struct SoftwareAtlasState {
+    tiles_by_key: HashMap<AtlasKey, AtlasTile>,
+    textures: Vec<SoftwareTexture>,
+}
+
+/// The pixels of a tile: one alpha byte per pixel for monochrome textures,
+/// and four BGRA bytes per pixel for polychrome ones.
+pub(crate) struct SoftwareTexture {
+    pub(crate) size: Size<DevicePixels>,
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl SoftwareAtlas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls the given function with the pixels of the given texture, if it exists.
+    pub fn with_texture<R>(
+        &self,
+        id: AtlasTextureId,
+        f: impl FnOnce(&SoftwareTexture) -> R,
+    ) -> Option<R> {
+        let state = self.0.lock();
+        state.textures.get(id.index as usize).map(f)
+    }
+}
+
+impl PlatformAtlas for SoftwareAtlas {
+    fn get_or_insert_with<'a>(
+        &self,
+        key: &AtlasKey,
+        build: &mut dyn FnMut() -> Result<(Size<DevicePixels>, Cow<'a, [u8]>)>,
+    ) -> Result<AtlasTile> {
+        if let Some(tile) = self.0.lock().tiles_by_key.get(key) {
+            return Ok(tile.clone());
+        }
+
+        let (size, bytes) = build()?;
+        let mut state = self.0.lock();
+        let index = state.textures.len() as u32;
+        state.textures.push(SoftwareTexture {
+            size,
+            bytes: bytes.into_owned(),
+        });
+        let tile = AtlasTile {
+            texture_id: AtlasTextureId {
+                index,
+                kind: key.texture_kind(),
+            },
+            tile_id: TileId(index),
+            bounds: Bounds {
+                origin: Point::default(),
+                size,
+            },
+        };
+        state.tiles_by_key.insert(key.clone(), tile.clone());
+        Ok(tile)
+    }
+}
+
+impl SoftwareTexture {
+    /// Samples the texture at the given position with bilinear filtering,
+    /// returning straight RGBA components between 0 and 1. Monochrome
+    /// textures only carry alpha.
+    pub(crate) fn sample(&self, kind: AtlasTextureKind, position: Point<f32>) -> [f32; 4] {
+        let width = self.size.width.0;
+        let height = self.size.height.0;
+        if width == 0 || height == 0 {
+            return [0.; 4];
+        }
+
+        let x = position.x - 0.5;
+        let y = position.y - 0.5;
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let tx = x - x0;
+        let ty = y - y0;
+
+        let mut result = [0.; 4];
+        for (dx, dy, weight) in [
+            (0, 0, (1. - tx) * (1. - ty)),
+            (1, 0, tx * (1. - ty)),
+            (0, 1, (1. - tx) * ty),
+            (1, 1, tx * ty),
+        ] {
+            let texel_x = (x0 as i32 + dx).clamp(0, width - 1) as usize;
+            let texel_y = (y0 as i32 + dy).clamp(0, height - 1) as usize;
+            let texel = self.texel(kind, texel_x, texel_y);
+            for (component, value) in result.iter_mut().zip(texel) {
+                *component += value * weight;
+            }
+        }
+        result
+    }
+
+    fn texel(&self, kind: AtlasTextureKind, x: usize, y: usize) -> [f32; 4] {
+        let width = self.size.width.0 as usize;
+        match kind {
+            AtlasTextureKind::Polychrome => {
+                let ix = (y * width + x) * 4;
+                let Some([b, g, r, a]) = self.bytes.get(ix..ix + 4) else {
+                    return [0.; 4];
+                };
+                [*r, *g, *b, *a].map(|component| component as f32 / 255.)
+            }
+            AtlasTextureKind::Monochrome | AtlasTextureKind::Path => {
+                let alpha = self.bytes.get(y * width + x).copied().unwrap_or(0);
+                [0., 0., 0., alpha as f32 / 255.]
+            }
+        }
+    }
+}
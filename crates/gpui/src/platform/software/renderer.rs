@@ -0,0 +1,742 @@
+use super::SoftwareAtlas;
+use crate::{
+    point, AtlasTextureKind, Bounds, ContentMask, Corners, DevicePixels, Hsla, MonochromeSprite,
+    Path, Point, PolychromeSprite, PrimitiveBatch, Quad, Rgba, ScaledPixels, Scene, Shadow, Size,
+    Underline,
+};
+use image::RgbaImage;
+use std::f32::consts::PI;
+
+/// Rasterizes scenes on the CPU, mirroring what the Metal shaders do on macOS,
+/// so that windows can be rendered without a GPU.
+pub(crate) struct SoftwareRenderer;
+
+impl SoftwareRenderer {
+    /// Renders the given scene into an image of the given size, on top of the
+    /// same opaque black that the Metal renderer clears its drawables with.
+    pub fn render(scene: &Scene, atlas: &SoftwareAtlas, size: Size<DevicePixels>) -> RgbaImage {
+        let width = size.width.0.max(0) as usize;
+        let height = size.height.0.max(0) as usize;
+        let mut canvas = Canvas {
+            width,
+            height,
+            pixels: vec![[0., 0., 0., 1.]; width * height],
+        };
+
+        for batch in scene.batches() {
+            match batch {
+                PrimitiveBatch::Shadows(shadows) => {
+                    for shadow in shadows {
+                        canvas.draw_shadow(shadow);
+                    }
+                }
+                PrimitiveBatch::Quads(quads) => {
+                    for quad in quads {
+                        canvas.draw_quad(quad);
+                    }
+                }
+                PrimitiveBatch::Paths(paths) => {
+                    for path in paths {
+                        canvas.draw_path(path);
+                    }
+                }
+                PrimitiveBatch::Underlines(underlines) => {
+                    for underline in underlines {
+                        canvas.draw_underline(underline);
+                    }
+                }
+                PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                    for sprite in sprites {
+                        canvas.draw_monochrome_sprite(sprite, atlas);
+                    }
+                }
+                PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                    for sprite in sprites {
+                        canvas.draw_polychrome_sprite(sprite, atlas);
+                    }
+                }
+                PrimitiveBatch::Surfaces(_) => {
+                    // Surfaces are backed by Core Video buffers, which only the
+                    // Metal renderer can draw.
+                }
+            }
+        }
+
+        canvas.into_image()
+    }
+}
+
+/// Premultiplied RGBA pixels.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    /// Calls the given function with the center of every pixel that lies
+    /// within both the given bounds and the content mask, and blends the color
+    /// it returns into that pixel.
+    fn fill(
+        &mut self,
+        bounds: &Bounds<ScaledPixels>,
+        content_mask: &ContentMask<ScaledPixels>,
+        mut shade: impl FnMut(f32, f32) -> [f32; 4],
+    ) {
+        let clip = bounds.intersect(&content_mask.bounds);
+        let left = clip.origin.x.0;
+        let top = clip.origin.y.0;
+        let right = left + clip.size.width.0;
+        let bottom = top + clip.size.height.0;
+
+        let start_x = left.floor().max(0.) as usize;
+        let start_y = top.floor().max(0.) as usize;
+        let end_x = (right.ceil().max(0.) as usize).min(self.width);
+        let end_y = (bottom.ceil().max(0.) as usize).min(self.height);
+        for y in start_y..end_y {
+            let center_y = y as f32 + 0.5;
+            if center_y < top || center_y >= bottom {
+                continue;
+            }
+            for x in start_x..end_x {
+                let center_x = x as f32 + 0.5;
+                if center_x < left || center_x >= right {
+                    continue;
+                }
+                let color = shade(center_x, center_y);
+                self.blend(x, y, color);
+            }
+        }
+    }
+
+    /// Blends a straight RGBA color over the given pixel.
+    fn blend(&mut self, x: usize, y: usize, [r, g, b, a]: [f32; 4]) {
+        let a = a.clamp(0., 1.);
+        if a == 0. {
+            return;
+        }
+        let pixel = &mut self.pixels[y * self.width + x];
+        pixel[0] = r * a + pixel[0] * (1. - a);
+        pixel[1] = g * a + pixel[1] * (1. - a);
+        pixel[2] = b * a + pixel[2] * (1. - a);
+        pixel[3] = a + pixel[3] * (1. - a);
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut bytes = Vec::with_capacity(self.pixels.len() * 4);
+        for [r, g, b, a] in self.pixels {
+            let unpremultiply = if a > 0. { 1. / a } else { 0. };
+            bytes.extend(
+                [r * unpremultiply, g * unpremultiply, b * unpremultiply, a]
+                    .map(|component| (component.clamp(0., 1.) * 255.).round() as u8),
+            );
+        }
+        RgbaImage::from_raw(self.width as u32, self.height as u32, bytes)
+            .expect("the buffer matches the image size")
+    }
+
+    fn draw_quad(&mut self, quad: &Quad) {
+        let background = rgba(quad.background);
+        let border_color = rgba(quad.border_color);
+        let half_size = point(quad.bounds.size.width.0, quad.bounds.size.height.0) / 2.;
+        let center = point(quad.bounds.origin.x.0, quad.bounds.origin.y.0) + half_size;
+
+        self.fill(&quad.bounds, &quad.content_mask, |x, y| {
+            let center_to_point = point(x, y) - center;
+            let corner_radius =
+                corner_radius(&quad.corner_radii, center_to_point.x, center_to_point.y);
+            let distance = rounded_rect_distance(center_to_point, half_size, corner_radius);
+
+            let vertical_border = if center_to_point.x <= 0. {
+                quad.border_widths.left.0
+            } else {
+                quad.border_widths.right.0
+            };
+            let horizontal_border = if center_to_point.y <= 0. {
+                quad.border_widths.top.0
+            } else {
+                quad.border_widths.bottom.0
+            };
+            let inset_x = half_size.x - corner_radius - vertical_border;
+            let inset_y = half_size.y - corner_radius - horizontal_border;
+            let to_inset_x = center_to_point.x.abs() - inset_x;
+            let to_inset_y = center_to_point.y.abs() - inset_y;
+            let border_width = if to_inset_x < 0. && to_inset_y < 0. {
+                0.
+            } else if to_inset_y > to_inset_x {
+                horizontal_border
+            } else {
+                vertical_border
+            };
+
+            let mut color = if border_width == 0. {
+                background
+            } else {
+                // Blend the border on top of the background and then linearly
+                // interpolate between the two as we slide inside the background.
+                let inset_distance = distance + border_width;
+                mix(
+                    over(background, border_color),
+                    background,
+                    saturate(0.5 - inset_distance),
+                )
+            };
+            color[3] *= saturate(0.5 - distance);
+            color
+        });
+    }
+
+    fn draw_shadow(&mut self, shadow: &Shadow) {
+        let color = rgba(shadow.color);
+        let blur_radius = shadow.blur_radius.0;
+        let half_size = point(shadow.bounds.size.width.0, shadow.bounds.size.height.0) / 2.;
+        let center = point(shadow.bounds.origin.x.0, shadow.bounds.origin.y.0) + half_size;
+
+        // The shadow's bounds include its blur, but the blur is computed
+        // relative to the shape that casts it.
+        let blurred_bounds = Bounds {
+            origin: point(
+                ScaledPixels(shadow.bounds.origin.x.0 - 3. * blur_radius),
+                ScaledPixels(shadow.bounds.origin.y.0 - 3. * blur_radius),
+            ),
+            size: Size {
+                width: ScaledPixels(shadow.bounds.size.width.0 + 6. * blur_radius),
+                height: ScaledPixels(shadow.bounds.size.height.0 + 6. * blur_radius),
+            },
+        };
+        self.fill(&blurred_bounds, &shadow.content_mask, |x, y| {
+            let point = point(x, y) - center;
+            let corner_radius = corner_radius(&shadow.corner_radii, point.x, point.y);
+            let alpha = if blur_radius <= 0. {
+                saturate(0.5 - rounded_rect_distance(point, half_size, corner_radius))
+            } else {
+                // The signal is only non-zero in a limited range, so we
+                // only sample that.
+                let low = point.y - half_size.y;
+                let high = point.y + half_size.y;
+                let start = (-3. * blur_radius).clamp(low, high);
+                let end = (3. * blur_radius).clamp(low, high);
+
+                let step = (end - start) / 4.;
+                let mut y = start + step * 0.5;
+                let mut alpha = 0.;
+                for _ in 0..4 {
+                    alpha +=
+                        blur_along_x(point.x, point.y - y, blur_radius, corner_radius, half_size)
+                            * gaussian(y, blur_radius)
+                            * step;
+                    y += step;
+                }
+                alpha
+            };
+            [color[0], color[1], color[2], color[3] * alpha]
+        });
+    }
+
+    fn draw_underline(&mut self, underline: &Underline) {
+        let color = rgba(underline.color);
+        let origin = point(underline.bounds.origin.x.0, underline.bounds.origin.y.0);
+        let height = underline.bounds.size.height.0;
+        let thickness = underline.thickness.0;
+
+        self.fill(&underline.bounds, &underline.content_mask, |x, y| {
+            if !underline.wavy {
+                return color;
+            }
+
+            let half_thickness = thickness * 0.5;
+            let st_x = (x - origin.x) / height;
+            let st_y = (y - origin.y) / height - 0.5;
+            let frequency = (PI * (3. * thickness)) / 8.;
+            let amplitude = 1. / (2. * thickness);
+            let sine = (st_x * frequency).sin() * amplitude;
+            let d_sine = (st_x * frequency).cos() * amplitude * frequency;
+            let distance = (st_y - sine) / (1. + d_sine * d_sine).sqrt();
+            let distance_in_pixels = distance * height;
+            let distance_from_top_border = distance_in_pixels - half_thickness;
+            let distance_from_bottom_border = distance_in_pixels + half_thickness;
+            let alpha =
+                saturate(0.5 - (-distance_from_bottom_border).max(distance_from_top_border));
+            [color[0], color[1], color[2], color[3] * alpha]
+        });
+    }
+
+    fn draw_monochrome_sprite(&mut self, sprite: &MonochromeSprite, atlas: &SoftwareAtlas) {
+        let color = rgba(sprite.color);
+        atlas.with_texture(sprite.tile.texture_id, |texture| {
+            self.fill(&sprite.bounds, &sprite.content_mask, |x, y| {
+                let position = tile_position(&sprite.bounds, &sprite.tile.bounds, x, y);
+                let sample = texture.sample(AtlasTextureKind::Monochrome, position);
+                [color[0], color[1], color[2], color[3] * sample[3]]
+            });
+        });
+    }
+
+    fn draw_polychrome_sprite(&mut self, sprite: &PolychromeSprite, atlas: &SoftwareAtlas) {
+        let half_size = point(sprite.bounds.size.width.0, sprite.bounds.size.height.0) / 2.;
+        let center = point(sprite.bounds.origin.x.0, sprite.bounds.origin.y.0) + half_size;
+        atlas.with_texture(sprite.tile.texture_id, |texture| {
+            self.fill(&sprite.bounds, &sprite.content_mask, |x, y| {
+                let position = tile_position(&sprite.bounds, &sprite.tile.bounds, x, y);
+                let mut color = texture.sample(AtlasTextureKind::Polychrome, position);
+                if sprite.grayscale {
+                    let grayscale = 0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2];
+                    color[0] = grayscale;
+                    color[1] = grayscale;
+                    color[2] = grayscale;
+                }
+                let center_to_point = point(x, y) - center;
+                let corner_radius =
+                    corner_radius(&sprite.corner_radii, center_to_point.x, center_to_point.y);
+                let distance = rounded_rect_distance(center_to_point, half_size, corner_radius);
+                color[3] *= saturate(0.5 - distance);
+                color
+            });
+        });
+    }
+
+    /// Paths are made of triangles whose texture coordinates describe either
+    /// a solid fill or a quadratic curve. The coverage of overlapping
+    /// triangles is accumulated before filling, just like in the path atlas of
+    /// the Metal renderer.
+    fn draw_path(&mut self, path: &Path<ScaledPixels>) {
+        let clip = path.bounds.intersect(&path.content_mask.bounds);
+        let left = clip.origin.x.0.floor().max(0.) as usize;
+        let top = clip.origin.y.0.floor().max(0.) as usize;
+        let right = ((clip.origin.x.0 + clip.size.width.0).ceil().max(0.) as usize).min(self.width);
+        let bottom =
+            ((clip.origin.y.0 + clip.size.height.0).ceil().max(0.) as usize).min(self.height);
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let coverage_width = right - left;
+        let mut coverage = vec![0f32; coverage_width * (bottom - top)];
+        for triangle in path.vertices.chunks_exact(3) {
+            let [a, b, c] = [&triangle[0], &triangle[1], &triangle[2]]
+                .map(|vertex| point(vertex.xy_position.x.0, vertex.xy_position.y.0));
+            let [st_a, st_b, st_c] =
+                [&triangle[0], &triangle[1], &triangle[2]].map(|vertex| vertex.st_position);
+            let area = edge(a, b, c);
+            if area == 0. {
+                continue;
+            }
+
+            // The texture coordinates vary linearly across the triangle, so
+            // their derivatives are constant.
+            let d_st_dx = (st_a * (b.y - c.y) + st_b * (c.y - a.y) + st_c * (a.y - b.y)) / area;
+            let d_st_dy = (st_a * (c.x - b.x) + st_b * (a.x - c.x) + st_c * (b.x - a.x)) / area;
+
+            let min_x = a.x.min(b.x).min(c.x).floor().max(left as f32) as usize;
+            let min_y = a.y.min(b.y).min(c.y).floor().max(top as f32) as usize;
+            let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.) as usize).min(right);
+            let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.) as usize).min(bottom);
+            for y in min_y..max_y {
+                for x in min_x..max_x {
+                    let p = point(x as f32 + 0.5, y as f32 + 0.5);
+                    let w_a = edge(b, c, p) / area;
+                    let w_b = edge(c, a, p) / area;
+                    let w_c = edge(a, b, p) / area;
+                    if w_a < 0. || w_b < 0. || w_c < 0. {
+                        continue;
+                    }
+
+                    let st = st_a * w_a + st_b * w_b + st_c * w_c;
+                    let gradient_x = 2. * st.x * d_st_dx.x - d_st_dx.y;
+                    let gradient_y = 2. * st.x * d_st_dy.x - d_st_dy.y;
+                    let gradient_length =
+                        (gradient_x * gradient_x + gradient_y * gradient_y).sqrt();
+                    let f = st.x * st.x - st.y;
+                    let distance = if gradient_length == 0. {
+                        f * f32::INFINITY
+                    } else {
+                        f / gradient_length
+                    };
+                    coverage[(y - top) * coverage_width + (x - left)] += saturate(0.5 - distance);
+                }
+            }
+        }
+
+        let color = rgba(path.color);
+        for y in top..bottom {
+            for x in left..right {
+                let sample = coverage[(y - top) * coverage_width + (x - left)];
+                let mask = 1. - (1. - sample % 2.).abs();
+                self.blend(x, y, [color[0], color[1], color[2], color[3] * mask]);
+            }
+        }
+    }
+}
+
+fn rgba(color: Hsla) -> [f32; 4] {
+    let Rgba { r, g, b, a } = color.into();
+    [r, g, b, a]
+}
+
+fn saturate(value: f32) -> f32 {
+    value.clamp(0., 1.)
+}
+
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|ix| a[ix] + (b[ix] - a[ix]) * t)
+}
+
+fn over(below: [f32; 4], above: [f32; 4]) -> [f32; 4] {
+    let alpha = above[3] + below[3] * (1. - above[3]);
+    if alpha == 0. {
+        return [0.; 4];
+    }
+    let [r, g, b] =
+        [0, 1, 2].map(|ix| (above[ix] * above[3] + below[ix] * below[3] * (1. - above[3])) / alpha);
+    [r, g, b, alpha]
+}
+
+fn edge(a: Point<f32>, b: Point<f32>, p: Point<f32>) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+fn corner_radius(corner_radii: &Corners<ScaledPixels>, x: f32, y: f32) -> f32 {
+    match (x < 0., y < 0.) {
+        (true, true) => corner_radii.top_left.0,
+        (true, false) => corner_radii.bottom_left.0,
+        (false, true) => corner_radii.top_right.0,
+        (false, false) => corner_radii.bottom_right.0,
+    }
+}
+
+/// The signed distance from a point, relative to the center of a rounded
+/// rectangle, to the rectangle's edge.
+fn rounded_rect_distance(
+    center_to_point: Point<f32>,
+    half_size: Point<f32>,
+    corner_radius: f32,
+) -> f32 {
+    let edge_x = center_to_point.x.abs() - half_size.x + corner_radius;
+    let edge_y = center_to_point.y.abs() - half_size.y + corner_radius;
+    let outside = (edge_x.max(0.).powi(2) + edge_y.max(0.).powi(2)).sqrt();
+    outside + edge_x.max(edge_y).min(0.) - corner_radius
+}
+
+/// Maps a point on screen to the corresponding position within a sprite's tile.
+fn tile_position(
+    bounds: &Bounds<ScaledPixels>,
+    tile_bounds: &Bounds<DevicePixels>,
+    x: f32,
+    y: f32,
+) -> Point<f32> {
+    let unit_x = (x - bounds.origin.x.0) / bounds.size.width.0;
+    let unit_y = (y - bounds.origin.y.0) / bounds.size.height.0;
+    point(
+        tile_bounds.origin.x.0 as f32 + unit_x * tile_bounds.size.width.0 as f32,
+        tile_bounds.origin.y.0 as f32 + unit_y * tile_bounds.size.height.0 as f32,
+    )
+}
+
+/// A standard gaussian function, used for weighting samples.
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-(x * x) / (2. * sigma * sigma)).exp() / ((2. * PI).sqrt() * sigma)
+}
+
+/// Approximates the error function, which is needed for the gaussian integral.
+fn erf(x: f32) -> f32 {
+    let sign = x.signum();
+    let a = x.abs();
+    let mut x = 1. + (0.278393 + (0.230389 + 0.078108 * (a * a)) * a) * a;
+    x *= x;
+    sign - sign / (x * x)
+}
+
+fn blur_along_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: Point<f32>) -> f32 {
+    let delta = (half_size.y - corner - y.abs()).min(0.);
+    let curved = half_size.x - corner + (corner * corner - delta * delta).max(0.).sqrt();
+    let scale = 0.5f32.sqrt() / sigma;
+    let start = 0.5 + 0.5 * erf((x - curved) * scale);
+    let end = 0.5 + 0.5 * erf((x + curved) * scale);
+    end - start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        blue, green, px, red, size, white, AtlasKey, AtlasTile, Edges, FontId, GlyphId,
+        PlatformAtlas, RenderGlyphParams, StackingOrder, ViewId,
+    };
+    use std::borrow::Cow;
+
+    fn bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<ScaledPixels> {
+        Bounds {
+            origin: point(ScaledPixels(x), ScaledPixels(y)),
+            size: size(ScaledPixels(width), ScaledPixels(height)),
+        }
+    }
+
+    fn content_mask(width: f32, height: f32) -> ContentMask<ScaledPixels> {
+        ContentMask {
+            bounds: bounds(0., 0., width, height),
+        }
+    }
+
+    fn render(scene: &mut Scene, atlas: &SoftwareAtlas, width: i32, height: i32) -> RgbaImage {
+        scene.finish();
+        SoftwareRenderer::render(
+            scene,
+            atlas,
+            size(DevicePixels(width), DevicePixels(height)),
+        )
+    }
+
+    /// The bytes that the given color is expected to be rendered as.
+    fn rgba_bytes(color: Hsla) -> [u8; 4] {
+        rgba(color).map(|component| (component * 255.).round() as u8)
+    }
+
+    /// Inserts a texture into the atlas, keyed by a glyph that is only used
+    /// to tell textures apart.
+    fn insert_texture(
+        atlas: &SoftwareAtlas,
+        glyph_id: u32,
+        polychrome: bool,
+        texture_size: Size<DevicePixels>,
+        bytes: Vec<u8>,
+    ) -> AtlasTile {
+        let key = AtlasKey::Glyph(RenderGlyphParams {
+            font_id: FontId(0),
+            glyph_id: GlyphId(glyph_id),
+            font_size: px(16.),
+            subpixel_variant: Point::default(),
+            scale_factor: 1.,
+            is_emoji: polychrome,
+        });
+        let mut bytes = Some(bytes);
+        atlas
+            .get_or_insert_with(&key, &mut || {
+                Ok((texture_size, Cow::Owned(bytes.take().unwrap())))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_quad() {
+        let mut scene = Scene::default();
+        scene.insert(
+            &StackingOrder::default(),
+            Quad {
+                bounds: bounds(2., 2., 6., 4.),
+                content_mask: content_mask(10., 10.),
+                background: red(),
+                border_color: blue(),
+                border_widths: Edges {
+                    top: ScaledPixels(1.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let image = render(&mut scene, &SoftwareAtlas::new(), 10, 10);
+        let pixel = |x, y| image.get_pixel(x, y).0;
+        // Outside of the quad, the background is cleared to black.
+        assert_eq!(pixel(0, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(8, 4), [0, 0, 0, 255]);
+        // The top row of the quad is its border.
+        assert_eq!(pixel(4, 2), rgba_bytes(blue()));
+        assert_eq!(pixel(4, 4), rgba_bytes(red()));
+    }
+
+    #[test]
+    fn test_render_shadow() {
+        let mut scene = Scene::default();
+        scene.insert(
+            &StackingOrder::default(),
+            Shadow {
+                view_id: ViewId::default(),
+                layer_id: 0,
+                order: 0,
+                bounds: bounds(4., 4., 8., 8.),
+                corner_radii: Corners::default(),
+                content_mask: content_mask(20., 16.),
+                color: red(),
+                blur_radius: ScaledPixels(2.),
+            },
+        );
+
+        let image = render(&mut scene, &SoftwareAtlas::new(), 20, 16);
+        let red_at = |x, y| image.get_pixel(x, y).0[0];
+        // The shadow is nearly opaque at its center and fades out past its edges,
+        // up to three times the blur radius away from them.
+        assert!(red_at(8, 8) > 200);
+        assert!((50..200).contains(&red_at(12, 8)));
+        assert!(red_at(12, 8) > red_at(14, 8));
+        assert_eq!(image.get_pixel(19, 8).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(8, 8).0[1..3], [0, 0]);
+    }
+
+    #[test]
+    fn test_render_path() {
+        // A right triangle with its right angle at the top left.
+        let mut path = Path::new(point(px(2.), px(2.)));
+        path.line_to(point(px(14.), px(2.)));
+        path.line_to(point(px(2.), px(14.)));
+        path.color = blue();
+        path.content_mask = ContentMask {
+            bounds: Bounds {
+                origin: Point::default(),
+                size: size(px(16.), px(16.)),
+            },
+        };
+        let mut scene = Scene::default();
+        scene.insert(&StackingOrder::default(), path.scale(1.));
+
+        let image = render(&mut scene, &SoftwareAtlas::new(), 16, 16);
+        let pixel = |x, y| image.get_pixel(x, y).0;
+        assert_eq!(pixel(3, 3), rgba_bytes(blue()));
+        assert_eq!(pixel(10, 3), rgba_bytes(blue()));
+        assert_eq!(pixel(3, 10), rgba_bytes(blue()));
+        assert_eq!(pixel(12, 12), [0, 0, 0, 255]);
+        assert_eq!(pixel(1, 1), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_render_monochrome_sprite() {
+        let atlas = SoftwareAtlas::new();
+        // A 2x2 alpha mask whose diagonal is opaque, drawn at twice its size.
+        let tile = insert_texture(
+            &atlas,
+            0,
+            false,
+            size(DevicePixels(2), DevicePixels(2)),
+            vec![255, 0, 0, 255],
+        );
+        let mut scene = Scene::default();
+        scene.insert(
+            &StackingOrder::default(),
+            MonochromeSprite {
+                view_id: ViewId::default(),
+                layer_id: 0,
+                order: 0,
+                bounds: bounds(2., 2., 4., 4.),
+                content_mask: content_mask(8., 8.),
+                color: green(),
+                tile,
+            },
+        );
+
+        let image = render(&mut scene, &atlas, 8, 8);
+        let pixel = |x, y| image.get_pixel(x, y).0;
+        let green = rgba_bytes(green());
+        assert_eq!(pixel(2, 2), green);
+        assert_eq!(pixel(5, 5), green);
+        assert_eq!(pixel(5, 2), [0, 0, 0, 255]);
+        assert_eq!(pixel(2, 5), [0, 0, 0, 255]);
+        assert_eq!(pixel(0, 0), [0, 0, 0, 255]);
+        assert_eq!(pixel(7, 7), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_render_polychrome_sprites() {
+        let atlas = SoftwareAtlas::new();
+        // A red texel next to a blue one, in BGRA.
+        let tile = insert_texture(
+            &atlas,
+            1,
+            true,
+            size(DevicePixels(2), DevicePixels(1)),
+            vec![0, 0, 255, 255, 255, 0, 0, 255],
+        );
+        let mut scene = Scene::default();
+        for (y, grayscale) in [(0., false), (2., true)] {
+            scene.insert(
+                &StackingOrder::default(),
+                PolychromeSprite {
+                    view_id: ViewId::default(),
+                    layer_id: 0,
+                    order: 0,
+                    bounds: bounds(0., y, 4., 2.),
+                    content_mask: content_mask(4., 4.),
+                    corner_radii: Corners::default(),
+                    tile: tile.clone(),
+                    grayscale,
+                },
+            );
+        }
+
+        let image = render(&mut scene, &atlas, 4, 4);
+        let pixel = |x, y| image.get_pixel(x, y).0;
+        assert_eq!(pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(3, 1), [0, 0, 255, 255]);
+        // Grayscale sprites use the luminance of their colors.
+        assert_eq!(pixel(0, 2), [54, 54, 54, 255]);
+        assert_eq!(pixel(3, 3), [18, 18, 18, 255]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_render_text() {
+        use crate::{font, platform::LinuxTextSystem, PlatformTextSystem};
+
+        let text_system = LinuxTextSystem::new();
+        text_system
+            .add_fonts(vec![Cow::Borrowed(include_bytes!(
+                "../../../../../assets/fonts/zed-mono/zed-mono-extended.ttf"
+            ))])
+            .unwrap();
+        let font_id = text_system.font_id(&font("Zed Mono")).unwrap();
+        let params = RenderGlyphParams {
+            font_id,
+            glyph_id: text_system.glyph_for_char(font_id, 'M').unwrap(),
+            font_size: px(24.),
+            subpixel_variant: Point::default(),
+            scale_factor: 1.,
+            is_emoji: false,
+        };
+        let raster_bounds = text_system.glyph_raster_bounds(&params).unwrap();
+        let atlas = SoftwareAtlas::new();
+        let tile = atlas
+            .get_or_insert_with(&params.clone().into(), &mut || {
+                let (size, bytes) = text_system.rasterize_glyph(&params, raster_bounds)?;
+                Ok((size, Cow::Owned(bytes)))
+            })
+            .unwrap();
+        let glyph_size = tile.bounds.size;
+
+        let mut scene = Scene::default();
+        scene.insert(
+            &StackingOrder::default(),
+            MonochromeSprite {
+                view_id: ViewId::default(),
+                layer_id: 0,
+                order: 0,
+                bounds: bounds(
+                    4.,
+                    4.,
+                    glyph_size.width.0 as f32,
+                    glyph_size.height.0 as f32,
+                ),
+                content_mask: content_mask(32., 32.),
+                color: white(),
+                tile,
+            },
+        );
+
+        let image = render(&mut scene, &atlas, 32, 32);
+        let glyph_right = 4 + glyph_size.width.0 as u32;
+        let glyph_bottom = 4 + glyph_size.height.0 as u32;
+        let mut lit_pixels = 0;
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let [r, g, b, a] = pixel.0;
+            assert_eq!([r, a], [g, 255]);
+            assert_eq!(g, b);
+            if r > 0 {
+                assert!(
+                    (4..glyph_right).contains(&x) && (4..glyph_bottom).contains(&y),
+                    "pixel ({x}, {y}) is lit outside of the glyph"
+                );
+            }
+            if r > 200 {
+                lit_pixels += 1;
+            }
+        }
+        assert!(lit_pixels > 20, "only {lit_pixels} pixels are lit");
+    }
+}
@@ -118,7 +118,11 @@ impl Platform for TestPlatform {
     }
 
     fn text_system(&self) -> Arc<dyn PlatformTextSystem> {
-        Arc::new(crate::platform::mac::MacTextSystem::new())
+        #[cfg(target_os = "macos")]
+        let text_system = crate::platform::mac::MacTextSystem::new();
+        #[cfg(target_os = "linux")]
+        let text_system = crate::platform::linux::LinuxTextSystem::new();
+        Arc::new(text_system)
     }
 
     fn run(&self, _on_finish_launching: Box<dyn FnOnce()>) {
@@ -1,9 +1,10 @@
 use crate::{
-    px, AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, KeyDownEvent, Keystroke,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, Size, TestPlatform, TileId, WindowAppearance, WindowBounds, WindowOptions,
+    platform::software::{SoftwareAtlas, SoftwareRenderer},
+    px, AnyWindowHandle, Bounds, DevicePixels, KeyDownEvent, Keystroke, Pixels, PlatformAtlas,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, Scene, Size,
+    TestPlatform, WindowAppearance, WindowBounds, WindowOptions,
 };
-use collections::HashMap;
+use image::RgbaImage;
 use parking_lot::Mutex;
 use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use std::{
@@ -18,7 +19,7 @@ pub(crate) struct TestWindowState {
     pub(crate) title: Option<String>,
     pub(crate) edited: bool,
     platform: Weak<TestPlatform>,
-    sprite_atlas: Arc<dyn PlatformAtlas>,
+    sprite_atlas: Arc<SoftwareAtlas>,
     pub(crate) should_close_handler: Option<Box<dyn FnMut() -> bool>>,
     input_callback: Option<Box<dyn FnMut(PlatformInput) -> bool>>,
     active_status_change_callback: Option<Box<dyn FnMut(bool)>>,
@@ -58,7 +59,7 @@ impl TestWindow {
             display,
             platform,
             handle,
-            sprite_atlas: Arc::new(TestAtlas::new()),
+            sprite_atlas: Arc::new(SoftwareAtlas::new()),
             title: Default::default(),
             edited: false,
             should_close_handler: None,
@@ -288,6 +289,17 @@ impl PlatformWindow for TestWindow {
 
     fn draw(&self, _scene: &crate::Scene) {}
 
+    fn render_to_image(&self, scene: &Scene) -> anyhow::Result<RgbaImage> {
+        let size = self
+            .content_size()
+            .map(|pixels| DevicePixels((pixels.0 * self.scale_factor()).ceil() as i32));
+        Ok(SoftwareRenderer::render(
+            scene,
+            &self.0.lock().sprite_atlas,
+            size,
+        ))
+    }
+
     fn sprite_atlas(&self) -> sync::Arc<dyn crate::PlatformAtlas> {
         self.0.lock().sprite_atlas.clone()
     }
@@ -297,60 +309,55 @@ impl PlatformWindow for TestWindow {
     }
 }
 
-pub(crate) struct TestAtlasState {
-    next_id: u32,
-    tiles: HashMap<AtlasKey, AtlasTile>,
-}
-
-pub(crate) struct TestAtlas(Mutex<TestAtlasState>);
-
-impl TestAtlas {
-    pub fn new() -> Self {
-        TestAtlas(Mutex::new(TestAtlasState {
-            next_id: 0,
-            tiles: HashMap::default(),
-        }))
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as gpui, blue, div, point, px, red, size, Bounds, GlobalPixels, Hsla, IntoElement,
+        ParentElement, Render, Styled, TestAppContext, ViewContext, VisualContext, WindowBounds,
+        WindowOptions,
+    };
+
+    struct Swatch;
+
+    impl Render for Swatch {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .bg(blue())
+                .child(div().size(px(4.)).bg(red()))
+        }
     }
-}
 
-impl PlatformAtlas for TestAtlas {
-    fn get_or_insert_with<'a>(
-        &self,
-        key: &crate::AtlasKey,
-        build: &mut dyn FnMut() -> anyhow::Result<(
-            Size<crate::DevicePixels>,
-            std::borrow::Cow<'a, [u8]>,
-        )>,
-    ) -> anyhow::Result<crate::AtlasTile> {
-        let mut state = self.0.lock();
-        if let Some(tile) = state.tiles.get(key) {
-            return Ok(tile.clone());
-        }
+    fn rgba_bytes(color: Hsla) -> [u8; 4] {
+        let color = color.to_rgb();
+        [color.r, color.g, color.b, color.a].map(|component| (component * 255.).round() as u8)
+    }
 
-        state.next_id += 1;
-        let texture_id = state.next_id;
-        state.next_id += 1;
-        let tile_id = state.next_id;
-
-        drop(state);
-        let (size, _) = build()?;
-        let mut state = self.0.lock();
-
-        state.tiles.insert(
-            key.clone(),
-            crate::AtlasTile {
-                texture_id: AtlasTextureId {
-                    index: texture_id,
-                    kind: crate::AtlasTextureKind::Path,
+    #[gpui::test]
+    fn test_render_view_to_image(cx: &mut TestAppContext) {
+        let window = cx.update(|cx| {
+            cx.open_window(
+                WindowOptions {
+                    bounds: WindowBounds::Fixed(Bounds {
+                        origin: point(GlobalPixels(0.), GlobalPixels(0.)),
+                        size: size(GlobalPixels(10.), GlobalPixels(8.)),
+                    }),
+                    ..Default::default()
                 },
-                tile_id: TileId(tile_id),
-                bounds: crate::Bounds {
-                    origin: Point::default(),
-                    size,
-                },
-            },
-        );
+                |cx| cx.new_view(|_| Swatch),
+            )
+        });
 
-        Ok(state.tiles[key].clone())
+        let image = window
+            .update(cx, |_, cx| cx.render_to_image())
+            .unwrap()
+            .unwrap();
+        // Test windows have a scale factor of 2.
+        assert_eq!(image.dimensions(), (20, 16));
+        let pixel = |x, y| image.get_pixel(x, y).0;
+        assert_eq!(pixel(0, 0), rgba_bytes(red()));
+        assert_eq!(pixel(7, 7), rgba_bytes(red()));
+        assert_eq!(pixel(8, 8), rgba_bytes(blue()));
+        assert_eq!(pixel(19, 15), rgba_bytes(blue()));
     }
 }
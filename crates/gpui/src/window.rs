@@ -1078,6 +1078,15 @@ impl<'a> WindowContext<'a> {
         self.window.drawing = false;
     }
 
+    /// Render the most recently drawn frame of this window into an image, in device pixels.
+    /// This is supported by the headless Linux platform and by test windows, which makes it
+    /// possible to write screenshot tests.
+    pub fn render_to_image(&self) -> Result<image::RgbaImage> {
+        self.window
+            .platform_window
+            .render_to_image(&self.window.rendered_frame.scene)
+    }
+
     /// Dispatch a mouse or keyboard event on the window.
     pub fn dispatch_event(&mut self, event: PlatformInput) -> bool {
         // Handlers may set this to false by calling `stop_propagation`.
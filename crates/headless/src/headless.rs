@@ -0,0 +1,339 @@
+//! Serves a project without a UI to a single client, and lets the editor join such a
+//! project by running a command (e.g. `ssh box zed --headless ~/project`) whose stdin and
+//! stdout carry the connection.
+//!
+//! The headless process stands in for the collab server: the local project is shared over an
+//! in-memory connection, and the host's updates are acknowledged and relayed to the client.
+//! Updates sent before the client joins are replayed to it once it does.
+
+use anyhow::{anyhow, Context as _, Result};
+use client::{Client, UserStore};
+use fs::Fs;
+use futures::{FutureExt as _, StreamExt as _};
+use gpui::{AppContext, AsyncAppContext, BackgroundExecutor, Model, Task};
+use language::LanguageRegistry;
+use project::Project;
+use rpc::{
+    proto::{self, AnyTypedEnvelope, EnvelopedMessage, RequestMessage},
+    Connection, ConnectionId, ErrorCode, ErrorCodeExt, ErrorExt, Peer, TypedEnvelope,
+};
+use smol::process::{Command, Stdio};
+use std::{any::TypeId, sync::Arc};
+use util::{http::ZedHttpClient, ResultExt};
+
+#[cfg(test)]
+mod headless_tests;
+
+/// The remote id under which a headless host shares its project.
+pub const PROJECT_ID: u64 = 1;
+
+const HOST_USER_ID: u64 = 1;
+const GUEST_USER_ID: u64 = 2;
+const GUEST_REPLICA_ID: u32 = 1;
+
+/// Shares `project` with the single client on the other end of `connection`. The project's
+/// client is connected to an in-process relay, so it shouldn't be connected to anything else.
+/// Resolves once the client leaves the project or disconnects.
+pub fn serve(
+    project: Model<Project>,
+    connection: Connection,
+    cx: &mut AppContext,
+) -> Task<Result<()>> {
+    let client = project.read(cx).client();
+    cx.spawn(|mut cx| async move {
+        let executor = cx.background_executor().clone();
+        let peer = Peer::new(0);
+        let create_timer = {
+            let executor = executor.clone();
+            move |duration| executor.timer(duration)
+        };
+        let (host_connection, relay_connection) = Connection::pair();
+        let (host_id, host_io, mut host_incoming) =
+            peer.add_connection(relay_connection, create_timer.clone());
+        let (guest_id, guest_io, mut guest_incoming) =
+            peer.add_connection(connection, create_timer);
+        executor.spawn(host_io).detach();
+        let guest_io = executor.spawn(guest_io);
+
+        peer.send(
+            host_id,
+            proto::Hello {
+                peer_id: Some(host_id.into()),
+            },
+        )?;
+        client.connect_with(host_connection, &cx).await?;
+        project.update(&mut cx, |project, cx| project.shared(PROJECT_ID, cx))??;
+        peer.send(
+            guest_id,
+            proto::Hello {
+                peer_id: Some(guest_id.into()),
+            },
+        )?;
+
+        let mut relay = Relay {
+            peer,
+            host_id,
+            guest_id,
+            worktrees: Vec::new(),
+            pending_updates: Some(Vec::new()),
+        };
+        let result = loop {
+            futures::select_biased! {
+                message = host_incoming.next().fuse() => match message {
+                    Some(message) => relay.handle_host_message(message)?,
+                    None => break Err(anyhow!("the host project's connection was closed")),
+                },
+                message = guest_incoming.next().fuse() => match message {
+                    Some(message) => {
+                        if !relay.handle_guest_message(message, &executor)? {
+                            break Ok(());
+                        }
+                    }
+                    None => break guest_io.await.context("connection to the client failed"),
+                },
+            }
+        };
+
+        project
+            .update(&mut cx, |project, cx| project.unshare(cx))?
+            .log_err();
+        client.disconnect(&cx);
+        result
+    })
+}
+
+/// Runs `command` through the shell and joins the project served by the headless host it
+/// starts, communicating over the command's stdin and stdout.
+pub fn connect(
+    command: String,
+    http: Arc<ZedHttpClient>,
+    languages: Arc<LanguageRegistry>,
+    fs: Arc<dyn Fs>,
+    cx: &mut AppContext,
+) -> Task<Result<Model<Project>>> {
+    let client = Client::new(http, cx);
+    Project::init(&client, cx);
+    let user_store = cx.new_model(|cx| UserStore::new(client.clone(), cx));
+    cx.spawn(|cx| async move {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to run {command:?}"))?;
+        let connection = Connection::framed(
+            child.stdout.take().context("missing stdout")?,
+            child.stdin.take().context("missing stdin")?,
+        );
+
+        let project = join(connection, client, user_store, languages, fs, cx.clone()).await?;
+        cx.background_executor()
+            .spawn(async move {
+                match child.status().await {
+                    Ok(status) if !status.success() => {
+                        log::error!("headless host {command:?} exited with {status}")
+                    }
+                    Ok(_) => log::info!("headless host {command:?} exited"),
+                    Err(error) => log::error!("failed to wait for {command:?}: {error}"),
+                }
+            })
+            .detach();
+        Ok(project)
+    })
+}
+
+/// Joins the project served by a headless host on the other end of `connection`.
+pub async fn join(
+    connection: Connection,
+    client: Arc<Client>,
+    user_store: Model<UserStore>,
+    languages: Arc<LanguageRegistry>,
+    fs: Arc<dyn Fs>,
+    cx: AsyncAppContext,
+) -> Result<Model<Project>> {
+    client.connect_with(connection, &cx).await?;
+    Project::remote(
+        PROJECT_ID,
+        client,
+        user_store,
+        languages,
+        fs,
+        proto::ChannelRole::Member,
+        cx,
+    )
+    .await
+}
+
+struct Relay {
+    peer: Arc<Peer>,
+    host_id: ConnectionId,
+    guest_id: ConnectionId,
+    worktrees: Vec<proto::WorktreeMetadata>,
+    pending_updates: Option<Vec<Box<dyn AnyTypedEnvelope>>>,
+}
+
+impl Relay {
+    fn handle_host_message(&mut self, message: Box<dyn AnyTypedEnvelope>) -> Result<()> {
+        if let Some(update) = message
+            .as_any()
+            .downcast_ref::<TypedEnvelope<proto::UpdateProject>>()
+        {
+            self.worktrees = update.payload.worktrees.clone();
+        }
+
+        // The host addresses these requests to the server, which acknowledges them before
+        // relaying them to the project's guests.
+        let acknowledged = self.acknowledge::<proto::UpdateProject>(message.as_ref())?
+            || self.acknowledge::<proto::UpdateWorktree>(message.as_ref())?
+            || self.acknowledge::<proto::UpdateBuffer>(message.as_ref())?;
+        if !acknowledged && proto::is_request(message.as_ref()) {
+            return self.peer.respond_with_unhandled_message(message);
+        }
+
+        match self.pending_updates.as_mut() {
+            Some(pending_updates) => {
+                pending_updates.push(message);
+                Ok(())
+            }
+            None => self
+                .peer
+                .forward_any_send(self.host_id, self.guest_id, message),
+        }
+    }
+
+    /// Returns whether the client is still in the project.
+    fn handle_guest_message(
+        &mut self,
+        message: Box<dyn AnyTypedEnvelope>,
+        executor: &BackgroundExecutor,
+    ) -> Result<bool> {
+        let message = match downcast::<proto::JoinProject>(message) {
+            Ok(request) => {
+                self.join_project(request)?;
+                return Ok(true);
+            }
+            Err(message) => message,
+        };
+        let message = match downcast::<proto::GetUsers>(message) {
+            Ok(request) => {
+                self.get_users(request)?;
+                return Ok(true);
+            }
+            Err(message) => message,
+        };
+        if message.payload_type_id() == TypeId::of::<proto::LeaveProject>() {
+            return Ok(false);
+        }
+
+        if proto::is_request(message.as_ref()) {
+            let message_id = message.message_id();
+            let response = self
+                .peer
+                .forward_any_request(self.guest_id, self.host_id, message);
+            let peer = self.peer.clone();
+            let guest_id = self.guest_id;
+            executor
+                .spawn(async move {
+                    if let Some(response) = response.await.log_err() {
+                        peer.respond_with_envelope(guest_id, message_id, response)
+                            .log_err();
+                    }
+                })
+                .detach();
+        } else {
+            self.peer
+                .forward_any_send(self.guest_id, self.host_id, message)?;
+        }
+        Ok(true)
+    }
+
+    fn join_project(&mut self, request: TypedEnvelope<proto::JoinProject>) -> Result<()> {
+        let Some(pending_updates) = self.pending_updates.take() else {
+            return self.peer.respond_with_error(
+                request.receipt(),
+                ErrorCode::Internal
+                    .message("already joined the project".into())
+                    .to_proto(),
+            );
+        };
+        if request.payload.project_id != PROJECT_ID {
+            self.pending_updates = Some(pending_updates);
+            return self.peer.respond_with_error(
+                request.receipt(),
+                ErrorCode::Internal
+                    .message(format!("no such project: {}", request.payload.project_id))
+                    .to_proto(),
+            );
+        }
+
+        self.peer.respond(
+            request.receipt(),
+            proto::JoinProjectResponse {
+                replica_id: GUEST_REPLICA_ID,
+                worktrees: self.worktrees.clone(),
+                collaborators: vec![proto::Collaborator {
+                    peer_id: Some(self.host_id.into()),
+                    replica_id: 0,
+                    user_id: HOST_USER_ID,
+                }],
+                language_servers: Vec::new(),
+            },
+        )?;
+        self.peer.send(
+            self.host_id,
+            proto::AddProjectCollaborator {
+                project_id: PROJECT_ID,
+                collaborator: Some(proto::Collaborator {
+                    peer_id: Some(self.guest_id.into()),
+                    replica_id: GUEST_REPLICA_ID,
+                    user_id: GUEST_USER_ID,
+                }),
+            },
+        )?;
+        for update in pending_updates {
+            self.peer
+                .forward_any_send(self.host_id, self.guest_id, update)?;
+        }
+        Ok(())
+    }
+
+    fn get_users(&self, request: TypedEnvelope<proto::GetUsers>) -> Result<()> {
+        let users = request
+            .payload
+            .user_ids
+            .iter()
+            .map(|&id| proto::User {
+                id,
+                github_login: if id == HOST_USER_ID { "host" } else { "guest" }.into(),
+                avatar_url: String::new(),
+            })
+            .collect();
+        self.peer
+            .respond(request.receipt(), proto::UsersResponse { users })
+    }
+
+    fn acknowledge<T>(&self, message: &dyn AnyTypedEnvelope) -> Result<bool>
+    where
+        T: RequestMessage<Response = proto::Ack>,
+    {
+        if let Some(request) = message.as_any().downcast_ref::<TypedEnvelope<T>>() {
+            self.peer.respond(request.receipt(), proto::Ack {})?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+fn downcast<T: EnvelopedMessage>(
+    message: Box<dyn AnyTypedEnvelope>,
+) -> Result<TypedEnvelope<T>, Box<dyn AnyTypedEnvelope>> {
+    if message.payload_type_id() == TypeId::of::<T>() {
+        Ok(*message.into_any().downcast::<TypedEnvelope<T>>().unwrap())
+    } else {
+        Err(message)
+    }
+}
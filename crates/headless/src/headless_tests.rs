@@ -0,0 +1,128 @@
+use super::*;
+use fs::FakeFs;
+use gpui::{Context as _, TestAppContext};
+use serde_json::json;
+use settings::SettingsStore;
+use std::path::Path;
+use util::http::FakeHttpClient;
+
+#[gpui::test]
+async fn test_headless_project(cx_a: &mut TestAppContext, cx_b: &mut TestAppContext) {
+    init_test(cx_a);
+    init_test(cx_b);
+    let executor = cx_a.executor();
+
+    // The headless host opens a worktree and serves it over a connection.
+    let fs = FakeFs::new(cx_a.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "a.txt": "alpha",
+            "dir": { "b.txt": "beta" },
+        }),
+    )
+    .await;
+    let project_a = Project::test(fs, [Path::new("/code")], cx_a).await;
+    let client_a = project_a.read_with(cx_a, |project, _| project.client());
+    cx_a.update(|cx| Project::init(&client_a, cx));
+    let (host_connection, guest_connection) = Connection::pair();
+    let serve_task = cx_a.update(|cx| serve(project_a.clone(), host_connection, cx));
+
+    // The client joins it directly, without a collab server.
+    let client_b = cx_b.update(|cx| Client::new(FakeHttpClient::with_404_response(), cx));
+    cx_b.update(|cx| Project::init(&client_b, cx));
+    let user_store_b = cx_b.new_model(|cx| UserStore::new(client_b.clone(), cx));
+    let mut languages = LanguageRegistry::test();
+    languages.set_executor(cx_b.executor());
+    let project_b = join(
+        guest_connection,
+        client_b.clone(),
+        user_store_b,
+        Arc::new(languages),
+        FakeFs::new(cx_b.executor()),
+        cx_b.to_async(),
+    )
+    .await
+    .unwrap();
+    executor.run_until_parked();
+
+    let worktree_id = project_b.read_with(cx_b, |project, cx| {
+        assert_eq!(project.replica_id(), GUEST_REPLICA_ID as u16);
+        assert!(project
+            .collaborators()
+            .values()
+            .any(|collaborator| collaborator.replica_id == 0));
+        let worktree = project.worktrees().next().unwrap().read(cx);
+        assert_eq!(worktree.root_name(), "code");
+        assert_eq!(
+            worktree
+                .paths()
+                .map(|path| path.to_str().unwrap())
+                .collect::<Vec<_>>(),
+            ["a.txt", "dir", "dir/b.txt"]
+        );
+        worktree.id()
+    });
+    project_a.read_with(cx_a, |project, _| {
+        assert_eq!(project.collaborators().len(), 1);
+    });
+
+    // Edits from either side are relayed to the other.
+    let buffer_b = project_b
+        .update(cx_b, |project, cx| {
+            project.open_buffer((worktree_id, "a.txt"), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(buffer_b.read_with(cx_b, |buffer, _| buffer.text()), "alpha");
+    buffer_b.update(cx_b, |buffer, cx| buffer.edit([(5..5, " beta")], None, cx));
+    executor.run_until_parked();
+
+    let buffer_a = project_a
+        .update(cx_a, |project, cx| {
+            project.open_buffer((worktree_id, "a.txt"), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer_a.read_with(cx_a, |buffer, _| buffer.text()),
+        "alpha beta"
+    );
+    buffer_a.update(cx_a, |buffer, cx| buffer.edit([(0..0, "> ")], None, cx));
+    executor.run_until_parked();
+    assert_eq!(
+        buffer_b.read_with(cx_b, |buffer, _| buffer.text()),
+        "> alpha beta"
+    );
+
+    // Guests' requests are answered by the host project.
+    project_b
+        .update(cx_b, |project, cx| {
+            project.create_entry((worktree_id, "dir/c.txt"), false, cx)
+        })
+        .await
+        .unwrap();
+    executor.run_until_parked();
+    project_b.read_with(cx_b, |project, cx| {
+        let worktree = project.worktrees().next().unwrap().read(cx);
+        assert!(worktree.entry_for_path("dir/c.txt").is_some());
+    });
+
+    // When the client leaves, the host stops serving the project.
+    cx_b.update(|_| drop(project_b));
+    executor.run_until_parked();
+    serve_task.await.unwrap();
+    project_a.read_with(cx_a, |project, _| assert!(!project.is_shared()));
+}
+
+fn init_test(cx: &mut TestAppContext) {
+    if std::env::var("RUST_LOG").is_ok() {
+        env_logger::try_init().ok();
+    }
+
+    cx.update(|cx| {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        language::init(cx);
+    });
+}
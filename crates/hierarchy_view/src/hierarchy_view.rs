@@ -0,0 +1,681 @@
+use anyhow::Result;
+use editor::{scroll::Autoscroll, Editor, EditorEvent, MultiBuffer};
+use gpui::{
+    actions, div, px, uniform_list, AnyElement, AnyView, AppContext, Context, EventEmitter,
+    FocusHandle, FocusableView, InteractiveElement, IntoElement, Model, ParentElement, Render,
+    SharedString, Styled, Subscription, Task, UniformListScrollHandle, View, ViewContext,
+    VisualContext, WindowContext,
+};
+use language::ToPoint;
+use project::{HierarchyItem, HierarchyKind, Location, Project};
+use std::{
+    any::{Any, TypeId},
+    ops::Range,
+    path::PathBuf,
+};
+use theme::ActiveTheme;
+use ui::{h_flex, prelude::*, Label, ListItem};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent, ItemHandle},
+    ItemNavHistory, Workspace,
+};
+
+actions!(
+    hierarchy_view,
+    [
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ShowSupertypes,
+        ShowSubtypes
+    ]
+);
+
+const CONTEXT_LINE_COUNT: u32 = 1;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(HierarchyView::register).detach();
+}
+
+/// Shows a call or type hierarchy as a tree of symbols next to a multibuffer
+/// containing the code for every symbol that is currently visible in the tree.
+pub struct HierarchyView {
+    project: Model<Project>,
+    kind: HierarchyKind,
+    title: SharedString,
+    entries: Vec<Entry>,
+    next_entry_id: usize,
+    selected_entry: Option<usize>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    excerpts: Model<MultiBuffer>,
+    editor: View<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct Entry {
+    id: usize,
+    item: HierarchyItem,
+    depth: usize,
+    state: EntryState,
+    /// The ranges of this entry's code in the multibuffer, populated when the excerpts are rebuilt.
+    excerpt_ranges: Vec<Range<editor::Anchor>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EntryState {
+    Collapsed,
+    Loading,
+    Expanded,
+    /// The entry was expanded and the language server reported no children.
+    Leaf,
+}
+
+impl HierarchyView {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ShowIncomingCalls, cx| {
+            Self::deploy(workspace, HierarchyKind::IncomingCalls, cx)
+        });
+        workspace.register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+            Self::deploy(workspace, HierarchyKind::OutgoingCalls, cx)
+        });
+        workspace.register_action(|workspace, _: &ShowSupertypes, cx| {
+            Self::deploy(workspace, HierarchyKind::Supertypes, cx)
+        });
+        workspace.register_action(|workspace, _: &ShowSubtypes, cx| {
+            Self::deploy(workspace, HierarchyKind::Subtypes, cx)
+        });
+    }
+
+    fn deploy(workspace: &mut Workspace, kind: HierarchyKind, cx: &mut ViewContext<Workspace>) {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return;
+        };
+        let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+            let head = editor.selections.newest_anchor().head();
+            editor.buffer().read(cx).text_anchor_for_position(head, cx)
+        }) else {
+            return;
+        };
+
+        let project = workspace.project().clone();
+        let roots = project.update(cx, |project, cx| {
+            project.prepare_hierarchy(&buffer, position, kind, cx)
+        });
+        cx.spawn(|workspace, mut cx| async move {
+            let roots = roots.await?;
+            if roots.is_empty() {
+                return Ok(());
+            }
+            workspace.update(&mut cx, |workspace, cx| {
+                let view = cx.new_view(|cx| HierarchyView::new(roots, kind, project, cx));
+                workspace.add_item(Box::new(view), cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn new(
+        roots: Vec<HierarchyItem>,
+        kind: HierarchyKind,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let title = match kind {
+            HierarchyKind::IncomingCalls => "Incoming Calls",
+            HierarchyKind::OutgoingCalls => "Outgoing Calls",
+            HierarchyKind::Supertypes => "Supertypes",
+            HierarchyKind::Subtypes => "Subtypes",
+        };
+        let title = match roots.as_slice() {
+            [root] => format!("{title}: {}", root.name),
+            _ => title.to_string(),
+        };
+
+        let focus_handle = cx.focus_handle();
+        let excerpts = cx.new_model(|cx| {
+            MultiBuffer::new(project.read(cx).replica_id(), project.read(cx).capability())
+        });
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(excerpts.clone(), Some(project.clone()), cx);
+            editor.set_vertical_scroll_margin(5, cx);
+            editor
+        });
+        let editor_event_subscription = cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        });
+
+        let mut this = Self {
+            project,
+            kind,
+            title: title.into(),
+            entries: Vec::new(),
+            next_entry_id: 0,
+            selected_entry: None,
+            focus_handle,
+            scroll_handle: UniformListScrollHandle::new(),
+            excerpts,
+            editor,
+            _subscriptions: vec![editor_event_subscription],
+        };
+        this.insert_entries(0, 0, roots);
+        for root_id in this
+            .entries
+            .iter()
+            .map(|entry| entry.id)
+            .collect::<Vec<_>>()
+        {
+            this.expand_entry(root_id, cx);
+        }
+        this.selected_entry = this.entries.first().map(|entry| entry.id);
+        this.update_excerpts(cx);
+        this
+    }
+
+    fn insert_entries(&mut self, ix: usize, depth: usize, items: Vec<HierarchyItem>) {
+        let entries = items
+            .into_iter()
+            .map(|item| {
+                let id = self.next_entry_id;
+                self.next_entry_id += 1;
+                Entry {
+                    id,
+                    item,
+                    depth,
+                    state: EntryState::Collapsed,
+                    excerpt_ranges: Vec::new(),
+                }
+            })
+            .collect::<Vec<_>>();
+        self.entries.splice(ix..ix, entries);
+    }
+
+    fn entry_ix(&self, entry_id: usize) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.id == entry_id)
+    }
+
+    fn toggle_entry(&mut self, entry_id: usize, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.entry_ix(entry_id) else {
+            return;
+        };
+        match self.entries[ix].state {
+            EntryState::Collapsed => self.expand_entry(entry_id, cx),
+            EntryState::Expanded => {
+                let depth = self.entries[ix].depth;
+                let descendant_count = self.entries[ix + 1..]
+                    .iter()
+                    .take_while(|entry| entry.depth > depth)
+                    .count();
+                self.entries.drain(ix + 1..ix + 1 + descendant_count);
+                self.entries[ix].state = EntryState::Collapsed;
+                if self
+                    .selected_entry
+                    .map_or(false, |selected| self.entry_ix(selected).is_none())
+                {
+                    self.selected_entry = Some(entry_id);
+                }
+                self.update_excerpts(cx);
+            }
+            EntryState::Loading | EntryState::Leaf => {}
+        }
+        cx.notify();
+    }
+
+    fn expand_entry(&mut self, entry_id: usize, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.entry_ix(entry_id) else {
+            return;
+        };
+        let kind = self.kind;
+        let entry = &mut self.entries[ix];
+        if entry.state != EntryState::Collapsed {
+            return;
+        }
+        entry.state = EntryState::Loading;
+        let children = self.project.update(cx, |project, cx| {
+            project.hierarchy_children(&entry.item, kind, cx)
+        });
+        cx.spawn(|this, mut cx| async move {
+            let children = children.await;
+            this.update(&mut cx, |this, cx| {
+                let Some(ix) = this.entry_ix(entry_id) else {
+                    return;
+                };
+                let children = children.log_err().unwrap_or_default();
+                let depth = this.entries[ix].depth;
+                this.entries[ix].state = if children.is_empty() {
+                    EntryState::Leaf
+                } else {
+                    EntryState::Expanded
+                };
+                this.insert_entries(ix + 1, depth + 1, children);
+                this.update_excerpts(cx);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn select_entry(&mut self, entry_id: usize, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.entry_ix(entry_id) else {
+            return;
+        };
+        self.selected_entry = Some(entry_id);
+        self.scroll_handle.scroll_to_item(ix);
+        if let Some(range) = self.entries[ix].excerpt_ranges.first() {
+            let position = range.start;
+            self.editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                    selections.select_anchor_ranges([position..position])
+                });
+            });
+        }
+        cx.notify();
+    }
+
+    /// Rebuilds the multibuffer so that it contains, in tree order, the code for every visible entry.
+    fn update_excerpts(&mut self, cx: &mut ViewContext<Self>) {
+        let kind = self.kind;
+        self.excerpts.update(cx, |excerpts, cx| {
+            excerpts.clear(cx);
+            for entry in &mut self.entries {
+                let (buffer, ranges) = entry_ranges(&entry.item, kind);
+                entry.excerpt_ranges = excerpts.push_excerpts_with_context_lines(
+                    buffer,
+                    ranges,
+                    CONTEXT_LINE_COUNT,
+                    cx,
+                );
+            }
+        });
+
+        let highlights = self
+            .entries
+            .iter()
+            .flat_map(|entry| entry.excerpt_ranges.iter().cloned())
+            .collect();
+        self.editor.update(cx, |editor, cx| {
+            editor.highlight_background::<Self>(
+                highlights,
+                |theme| theme.editor_document_highlight_read_background,
+                cx,
+            );
+        });
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let entry = &self.entries[ix];
+        let entry_id = entry.id;
+        let toggle = match entry.state {
+            EntryState::Collapsed => Some(false),
+            EntryState::Loading | EntryState::Expanded => Some(true),
+            EntryState::Leaf => None,
+        };
+        let location = entry.item.location.buffer.read(cx).file().map(|file| {
+            let point = entry
+                .item
+                .selection_range
+                .start
+                .to_point(&entry.item.location.buffer.read(cx));
+            format!("{}:{}", file.path().display(), point.row + 1)
+        });
+
+        ListItem::new(entry_id)
+            .indent_level(entry.depth)
+            .indent_step_size(px(12.))
+            .selected(self.selected_entry == Some(entry_id))
+            .toggle(toggle)
+            .on_toggle(cx.listener(move |this, _, cx| this.toggle_entry(entry_id, cx)))
+            .on_click(cx.listener(move |this, _, cx| this.select_entry(entry_id, cx)))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(entry.item.name.clone()))
+                    .children(entry.item.detail.clone().map(|detail| {
+                        Label::new(detail)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                    }))
+                    .children(location.map(|location| {
+                        Label::new(location)
+                            .size(LabelSize::Small)
+                            .color(Color::Placeholder)
+                    })),
+            )
+    }
+}
+
+/// For call hierarchies an entry shows the calls that connect it to its parent,
+/// falling back to the symbol's name for roots.
+fn entry_ranges(
+    item: &HierarchyItem,
+    kind: HierarchyKind,
+) -> (Model<language::Buffer>, Vec<Range<language::Anchor>>) {
+    if kind.is_call_hierarchy() {
+        if let Some(Location { buffer, .. }) = item.call_sites.first() {
+            let ranges = item
+                .call_sites
+                .iter()
+                .filter(|location| location.buffer == *buffer)
+                .map(|location| location.range.clone())
+                .collect();
+            return (buffer.clone(), ranges);
+        }
+    }
+    (
+        item.location.buffer.clone(),
+        vec![item.selection_range.clone()],
+    )
+}
+
+impl EventEmitter<EditorEvent> for HierarchyView {}
+
+impl FocusableView for HierarchyView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HierarchyView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .child(
+                div()
+                    .w(rems(20.))
+                    .h_full()
+                    .flex_none()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border)
+                    .bg(cx.theme().colors().panel_background)
+                    .child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "hierarchy-entries",
+                            self.entries.len(),
+                            |this, range, cx| {
+                                range
+                                    .map(|ix| this.render_entry(ix, cx).into_any_element())
+                                    .collect()
+                            },
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    ),
+            )
+            .child(div().flex_1().h_full().child(self.editor.clone()))
+    }
+}
+
+impl Item for HierarchyView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn tab_content(&self, _detail: Option<usize>, selected: bool, _: &WindowContext) -> AnyElement {
+        Label::new(self.title.clone())
+            .color(if selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("hierarchy view")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        self.editor.for_each_project_item(cx, f)
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.excerpts.read(cx).is_dirty(cx)
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.excerpts.read(cx).has_conflict(cx)
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.editor.save(project, cx)
+    }
+
+    fn save_as(
+        &mut self,
+        _: Model<Project>,
+        _: PathBuf,
+        _: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        unreachable!()
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.editor.reload(project, cx)
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use gpui::TestAppContext;
+    use language::{FakeLspAdapter, Language, LanguageConfig};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_incoming_calls(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let mut language = Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            None,
+        );
+        let mut fake_servers = language
+            .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }))
+            .await;
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "main.rs": "fn main() {\n    one();\n    two();\n}\n\nfn two() {\n    one();\n}\n\nfn one() {}\n",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        project.update(cx, |project, _| project.languages().add(Arc::new(language)));
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path("/dir/main.rs".into(), true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+
+        let uri = lsp::Url::from_file_path("/dir/main.rs").unwrap();
+        let item = |name: &str, line: u32| lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: uri.clone(),
+            range: lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line, 11)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(line, 3),
+                lsp::Position::new(line, 6),
+            ),
+            data: None,
+        };
+        let one = item("one", 9);
+        let main = item("main", 0);
+        let two = item("two", 5);
+        fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+            let one = one.clone();
+            move |_, _| {
+                let one = one.clone();
+                async move { Ok(Some(vec![one])) }
+            }
+        });
+        fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+            move |params, _| {
+                let callers = if params.item.name == "one" {
+                    vec![
+                        lsp::CallHierarchyIncomingCall {
+                            from: main.clone(),
+                            from_ranges: vec![lsp::Range::new(
+                                lsp::Position::new(1, 4),
+                                lsp::Position::new(1, 7),
+                            )],
+                        },
+                        lsp::CallHierarchyIncomingCall {
+                            from: two.clone(),
+                            from_ranges: vec![lsp::Range::new(
+                                lsp::Position::new(6, 4),
+                                lsp::Position::new(6, 7),
+                            )],
+                        },
+                    ]
+                } else {
+                    Vec::new()
+                };
+                async move { Ok(Some(callers)) }
+            },
+        );
+
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.select_ranges([language::Point::new(9, 4)..language::Point::new(9, 4)])
+            });
+        });
+        cx.dispatch_action(ShowIncomingCalls);
+        cx.run_until_parked();
+
+        let hierarchy = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<HierarchyView>(cx).unwrap()
+        });
+        hierarchy.update(cx, |hierarchy, cx| {
+            assert_eq!(
+                entries(hierarchy),
+                [
+                    ("one", 0, EntryState::Expanded),
+                    ("main", 1, EntryState::Collapsed),
+                    ("two", 1, EntryState::Collapsed),
+                ]
+            );
+            let two = hierarchy.entries[2].id;
+            hierarchy.toggle_entry(two, cx);
+        });
+        cx.run_until_parked();
+
+        hierarchy.update(cx, |hierarchy, cx| {
+            assert_eq!(
+                entries(hierarchy),
+                [
+                    ("one", 0, EntryState::Expanded),
+                    ("main", 1, EntryState::Collapsed),
+                    ("two", 1, EntryState::Leaf),
+                ]
+            );
+            let one = hierarchy.entries[0].id;
+            hierarchy.toggle_entry(one, cx);
+            assert_eq!(entries(hierarchy), [("one", 0, EntryState::Collapsed)]);
+        });
+    }
+
+    fn entries(hierarchy: &HierarchyView) -> Vec<(&str, usize, EntryState)> {
+        hierarchy
+            .entries
+            .iter()
+            .map(|entry| (entry.item.name.as_str(), entry.depth, entry.state))
+            .collect()
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+}
@@ -27,7 +27,7 @@ use smallvec::SmallVec;
 use smol::future::yield_now;
 use std::{
     any::Any,
-    cmp::{self, Ordering},
+    cmp::{self, Ordering, Reverse},
     collections::BTreeMap,
     ffi::OsStr,
     future::Future,
@@ -36,7 +36,7 @@ use std::{
     ops::{Deref, Range},
     path::{Path, PathBuf},
     str,
-    sync::Arc,
+    sync::{Arc, OnceLock},
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
     vec,
 };
@@ -45,9 +45,9 @@ use text::operation_queue::OperationQueue;
 use text::*;
 pub use text::{
     Anchor, Bias, Buffer as TextBuffer, BufferId, BufferSnapshot as TextBufferSnapshot, Edit,
-    OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, RopeFingerprint, Selection,
-    SelectionGoal, Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint,
-    ToPointUtf16, Transaction, TransactionId, Unclipped,
+    HistorySnapshot, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, RopeFingerprint,
+    Selection, SelectionGoal, SerializedHistory, Subscription, TextDimension, TextSummary,
+    ToOffset, ToOffsetUtf16, ToPoint, ToPointUtf16, Transaction, TransactionId, Unclipped,
 };
 use theme::SyntaxTheme;
 #[cfg(any(test, feature = "test-support"))]
@@ -106,6 +106,11 @@ pub struct Buffer {
     diagnostics_timestamp: clock::Lamport,
     file_update_count: usize,
     git_diff_update_count: usize,
+    semantic_tokens: Arc<[SemanticToken]>,
+    semantic_tokens_update_count: usize,
+    lsp_folding_ranges: Option<Arc<[FoldingRange<Anchor>]>>,
+    syntax_folding_ranges: Mutex<SyntaxFoldingRanges>,
+    folding_ranges_update_count: usize,
     completion_triggers: Vec<String>,
     completion_triggers_timestamp: clock::Lamport,
     deferred_ops: OperationQueue<Operation>,
@@ -123,6 +128,11 @@ pub struct BufferSnapshot {
     diagnostics_update_count: usize,
     file_update_count: usize,
     git_diff_update_count: usize,
+    semantic_tokens: Arc<[SemanticToken]>,
+    semantic_tokens_update_count: usize,
+    lsp_folding_ranges: Option<Arc<[FoldingRange<Anchor>]>>,
+    syntax_folding_ranges: Arc<OnceLock<Arc<[FoldingRange<usize>]>>>,
+    folding_ranges_update_count: usize,
     remote_selections: TreeMap<ReplicaId, SelectionSet>,
     selections_update_count: usize,
     language: Option<Arc<Language>>,
@@ -468,6 +478,49 @@ pub struct BufferChunks<'a> {
     hint_depth: usize,
     unnecessary_depth: usize,
     highlights: Option<BufferChunkHighlights<'a>>,
+    semantic_tokens: Vec<(Range<usize>, HighlightId)>,
+    semantic_token_ix: usize,
+}
+
+/// A range of a buffer that a language server has classified, for example
+/// as a mutable variable or as an item generated by a macro.
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    /// The range of the token.
+    pub range: Range<Anchor>,
+    /// The name used to look up the token's style in the syntax theme,
+    /// such as `variable.mutable`.
+    pub highlight_name: Arc<str>,
+}
+
+/// The folding ranges captured by the `folds.scm` query, computed lazily and shared
+/// by the snapshots taken while neither the text nor the syntax tree changes.
+#[derive(Default)]
+struct SyntaxFoldingRanges {
+    version: clock::Global,
+    parse_count: usize,
+    folds: Arc<OnceLock<Arc<[FoldingRange<usize>]>>>,
+}
+
+/// The kind of a [FoldingRange], used to fold every range of that kind at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldKind {
+    /// A comment spanning multiple lines.
+    Comment,
+    /// A block of imports.
+    Imports,
+    /// A region delimited by marker comments, such as `// #region`.
+    Region,
+}
+
+/// A range of a buffer that can be folded, either captured by the language's
+/// `folds.scm` query or reported by a language server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoldingRange<T> {
+    /// The range that is hidden when folded.
+    pub range: Range<T>,
+    /// The kind of the range, if known.
+    pub kind: Option<FoldKind>,
 }
 
 /// A chunk of a buffer's text, along with its syntax highlight and
@@ -687,6 +740,11 @@ impl Buffer {
             diagnostics_timestamp: Default::default(),
             file_update_count: 0,
             git_diff_update_count: 0,
+            semantic_tokens: Arc::from([]),
+            semantic_tokens_update_count: 0,
+            lsp_folding_ranges: None,
+            syntax_folding_ranges: Default::default(),
+            folding_ranges_update_count: 0,
             completion_triggers: Default::default(),
             completion_triggers_timestamp: Default::default(),
             deferred_ops: OperationQueue::new(),
@@ -700,6 +758,17 @@ impl Buffer {
         let mut syntax_map = self.syntax_map.lock();
         syntax_map.interpolate(&text);
         let syntax = syntax_map.snapshot();
+        let syntax_folding_ranges = {
+            let mut cache = self.syntax_folding_ranges.lock();
+            if cache.parse_count != self.parse_count || cache.version != *text.version() {
+                *cache = SyntaxFoldingRanges {
+                    version: text.version().clone(),
+                    parse_count: self.parse_count,
+                    folds: Default::default(),
+                };
+            }
+            cache.folds.clone()
+        };
 
         BufferSnapshot {
             text,
@@ -711,6 +780,11 @@ impl Buffer {
             diagnostics_update_count: self.diagnostics_update_count,
             file_update_count: self.file_update_count,
             git_diff_update_count: self.git_diff_update_count,
+            semantic_tokens: self.semantic_tokens.clone(),
+            semantic_tokens_update_count: self.semantic_tokens_update_count,
+            lsp_folding_ranges: self.lsp_folding_ranges.clone(),
+            syntax_folding_ranges,
+            folding_ranges_update_count: self.folding_ranges_update_count,
             language: self.language.clone(),
             parse_count: self.parse_count,
             selections_update_count: self.selections_update_count,
@@ -751,6 +825,7 @@ impl Buffer {
     /// Assign a language to the buffer.
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut ModelContext<Self>) {
         self.syntax_map.lock().clear();
+        *self.syntax_folding_ranges.get_mut() = Default::default();
         self.language = language;
         self.reparse(cx);
         cx.emit(Event::LanguageChanged);
@@ -975,6 +1050,16 @@ impl Buffer {
         self.git_diff_update_count
     }
 
+    /// The number of times the semantic tokens were updated.
+    pub fn semantic_tokens_update_count(&self) -> usize {
+        self.semantic_tokens_update_count
+    }
+
+    /// The number of times the language server's folding ranges were updated.
+    pub fn folding_ranges_update_count(&self) -> usize {
+        self.folding_ranges_update_count
+    }
+
     /// Whether the buffer is being parsed in the background.
     #[cfg(any(test, feature = "test-support"))]
     pub fn is_parsing(&self) -> bool {
@@ -1106,6 +1191,36 @@ impl Buffer {
         self.send_operation(op, cx);
     }
 
+    /// The semantic tokens that are layered on top of this buffer's syntax highlighting.
+    pub fn semantic_tokens(&self) -> &Arc<[SemanticToken]> {
+        &self.semantic_tokens
+    }
+
+    /// Replaces the semantic tokens that are layered on top of this buffer's
+    /// syntax highlighting. The tokens must be sorted and must not overlap.
+    pub fn set_semantic_tokens(
+        &mut self,
+        tokens: Arc<[SemanticToken]>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.semantic_tokens = tokens;
+        self.semantic_tokens_update_count += 1;
+        cx.notify();
+    }
+
+    /// Replaces the folding ranges reported by the buffer's language server,
+    /// which take precedence over the ones captured by the language's
+    /// `folds.scm` query. The ranges must be sorted by their start.
+    pub fn set_lsp_folding_ranges(
+        &mut self,
+        ranges: Option<Arc<[FoldingRange<Anchor>]>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.lsp_folding_ranges = ranges;
+        self.folding_ranges_update_count += 1;
+        cx.notify();
+    }
+
     fn request_autoindent(&mut self, cx: &mut ModelContext<Self>) {
         if let Some(indent_sizes) = self.compute_autoindents() {
             let indent_sizes = cx.background_executor().spawn(indent_sizes);
@@ -1602,6 +1717,13 @@ impl Buffer {
         self.text.merge_transactions(transaction, destination);
     }
 
+    /// Rebuilds the undo and redo stacks of a buffer without history, see
+    /// [`TextBuffer::restore_history`]. The buffer's text is unchanged, so no events
+    /// are emitted and no operations are sent to other replicas.
+    pub fn restore_history(&mut self, history: SerializedHistory) -> Result<()> {
+        self.text.restore_history(history)
+    }
+
     /// Waits for the buffer to receive operations with the given timestamps.
     pub fn wait_for_edits(
         &mut self,
@@ -2454,7 +2576,31 @@ impl BufferSnapshot {
                 .sort_unstable_by_key(|endpoint| (endpoint.offset, !endpoint.is_start));
         }
 
-        BufferChunks::new(self.text.as_rope(), range, syntax, diagnostic_endpoints)
+        let mut semantic_tokens = Vec::new();
+        if let Some(language) = self.language.as_ref().filter(|_| language_aware) {
+            let start_ix = self
+                .semantic_tokens
+                .partition_point(|token| token.range.end.to_offset(self) <= range.start);
+            for token in &self.semantic_tokens[start_ix..] {
+                let token_range =
+                    token.range.start.to_offset(self)..token.range.end.to_offset(self);
+                if token_range.start >= range.end {
+                    break;
+                }
+                let highlight_id = language.highlight_id_for_semantic_token(&token.highlight_name);
+                if !token_range.is_empty() && !highlight_id.is_default() {
+                    semantic_tokens.push((token_range, highlight_id));
+                }
+            }
+        }
+
+        BufferChunks::new(
+            self.text.as_rope(),
+            range,
+            syntax,
+            diagnostic_endpoints,
+            semantic_tokens,
+        )
     }
 
     /// Invokes the given callback for each line of text in the given range of the buffer.
@@ -2877,6 +3023,217 @@ impl BufferSnapshot {
         })
     }
 
+    /// Whether this buffer's folding ranges come from its language server or
+    /// its language's `folds.scm` query, rather than from indentation.
+    pub fn has_folding_ranges(&self) -> bool {
+        self.lsp_folding_ranges.is_some()
+            || self
+                .language
+                .as_ref()
+                .and_then(|language| language.grammar())
+                .map_or(false, |grammar| grammar.folds_config.is_some())
+    }
+
+    /// Returns the folding ranges that start within the given range, ordered
+    /// by their start and, for ranges starting at the same position, from the
+    /// outermost to the innermost. Every folding range starts at the end of
+    /// its first line, so that line stays visible when the range is folded.
+    pub fn folding_ranges<T: ToOffset>(&self, range: Range<T>) -> Vec<FoldingRange<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        if let Some(lsp_folding_ranges) = &self.lsp_folding_ranges {
+            let mut folds = Vec::new();
+            let start_ix = lsp_folding_ranges
+                .partition_point(|fold| fold.range.start.to_offset(self) < range.start);
+            for fold in &lsp_folding_ranges[start_ix..] {
+                let start_row = fold.range.start.to_point(self).row;
+                let end_row = fold.range.end.to_point(self).row;
+                let start = self.point_to_offset(Point::new(start_row, self.line_len(start_row)));
+                if start > range.end {
+                    break;
+                }
+                if end_row > start_row && start >= range.start {
+                    let end = self.point_to_offset(Point::new(end_row, self.line_len(end_row)));
+                    folds.push(FoldingRange {
+                        range: start..end,
+                        kind: fold.kind,
+                    });
+                }
+            }
+            folds.sort_unstable_by_key(|fold| (fold.range.start, Reverse(fold.range.end)));
+            folds.dedup_by_key(|fold| fold.range.clone());
+            return folds;
+        }
+
+        // Folds are looked up for every visible row, so the `folds.scm` query is
+        // run over the whole buffer once and the result is cached.
+        let folds = self
+            .syntax_folding_ranges
+            .get_or_init(|| self.query_folding_ranges().into());
+        let start_ix = folds.partition_point(|fold| fold.range.start < range.start);
+        folds[start_ix..]
+            .iter()
+            .take_while(|fold| fold.range.start <= range.end)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns every folding range captured by the `folds.scm` query, sorted
+    /// like the result of [`Self::folding_ranges`].
+    fn query_folding_ranges(&self) -> Vec<FoldingRange<usize>> {
+        let mut folds = Vec::new();
+        let mut matches = self.syntax.matches(0..self.len(), &self.text, |grammar| {
+            grammar.folds_config.as_ref().map(|config| &config.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.folds_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+        while let Some(mat) = matches.peek() {
+            let config = &configs[mat.grammar_index];
+            let mut fold: Option<FoldingRange<usize>> = None;
+            let mut last_node: Option<tree_sitter::Node> = None;
+            for capture in mat.captures {
+                let Some(kind) = config.kind_for_capture(capture.index) else {
+                    continue;
+                };
+                if last_node.map_or(true, |node| capture.node.end_byte() > node.end_byte()) {
+                    last_node = Some(capture.node);
+                }
+                let node_range = capture.node.byte_range();
+                fold = Some(match fold {
+                    Some(fold) => FoldingRange {
+                        range: fold.range.start.min(node_range.start)
+                            ..fold.range.end.max(node_range.end),
+                        kind: fold.kind,
+                    },
+                    None => FoldingRange {
+                        range: node_range,
+                        kind,
+                    },
+                });
+            }
+            matches.advance();
+
+            // The last token of a node, like `}` or `end`, stays visible if it
+            // starts its own line.
+            let closing_token_start = last_node
+                .and_then(|node| node.child(node.child_count().checked_sub(1)?))
+                .filter(|child| !child.is_named())
+                .map(|child| child.start_byte());
+            if let Some(fold) = fold {
+                if let Some(fold_range) =
+                    self.fold_range_for_syntax_node(fold.range, closing_token_start)
+                {
+                    folds.push(FoldingRange {
+                        range: fold_range,
+                        kind: fold.kind,
+                    });
+                }
+            }
+        }
+        folds.extend(self.region_folding_ranges());
+        folds.sort_unstable_by_key(|fold| (fold.range.start, Reverse(fold.range.end)));
+        folds.dedup_by_key(|fold| fold.range.clone());
+        folds
+    }
+
+    /// Pairs the `#region` / `#endregion` marker comments captured by the folds
+    /// query. Like language server folds, a region hides everything after its
+    /// start line up to the end of its end line.
+    fn region_folding_ranges(&self) -> Vec<FoldingRange<usize>> {
+        let mut matches = self.syntax.matches(0..self.len(), &self.text, |grammar| {
+            grammar
+                .folds_config
+                .as_ref()
+                .filter(|config| config.region_start_capture_ix.is_some())
+                .map(|config| &config.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.folds_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+        let mut markers = Vec::new();
+        while let Some(mat) = matches.peek() {
+            let config = &configs[mat.grammar_index];
+            for capture in mat.captures {
+                let is_start = if Some(capture.index) == config.region_start_capture_ix {
+                    true
+                } else if Some(capture.index) == config.region_end_capture_ix {
+                    false
+                } else {
+                    continue;
+                };
+                markers.push((capture.node.start_byte(), is_start));
+            }
+            matches.advance();
+        }
+        markers.sort_unstable();
+        markers.dedup();
+
+        let mut folds = Vec::new();
+        let mut open_regions = Vec::new();
+        for (offset, is_start) in markers {
+            if is_start {
+                open_regions.push(offset);
+                continue;
+            }
+            let Some(start) = open_regions.pop() else {
+                continue;
+            };
+            let start_row = self.offset_to_point(start).row;
+            let end_row = self.offset_to_point(offset).row;
+            if end_row > start_row {
+                folds.push(FoldingRange {
+                    range: self.point_to_offset(Point::new(start_row, self.line_len(start_row)))
+                        ..self.point_to_offset(Point::new(end_row, self.line_len(end_row))),
+                    kind: Some(FoldKind::Region),
+                });
+            }
+        }
+        folds
+    }
+
+    /// Converts the range of a syntax node into the range hidden when folding it.
+    /// If the node ends with a line containing only its closing token, or only
+    /// delimiters such as `*/`, that line stays visible.
+    fn fold_range_for_syntax_node(
+        &self,
+        range: Range<usize>,
+        closing_token_start: Option<usize>,
+    ) -> Option<Range<usize>> {
+        let start = self.offset_to_point(range.start);
+        let mut end = self.offset_to_point(range.end);
+        if end.column == 0 && end.row > start.row {
+            end = Point::new(end.row - 1, self.line_len(end.row - 1));
+        }
+        if end.row <= start.row {
+            return None;
+        }
+
+        let indent = self.indent_size_for_line(end.row).len;
+        let ends_with_closing_line = match closing_token_start {
+            Some(closing_token_start) => {
+                self.offset_to_point(closing_token_start) == Point::new(end.row, indent)
+            }
+            None => {
+                end.column > indent
+                    && self
+                        .text_for_range(Point::new(end.row, indent)..end)
+                        .flat_map(str::chars)
+                        .all(|c| !c.is_alphanumeric() && c != '_')
+            }
+        };
+        if ends_with_closing_line {
+            end.column = indent;
+        }
+
+        let start = self.point_to_offset(Point::new(start.row, self.line_len(start.row)));
+        let end = self.point_to_offset(end);
+        (start < end).then_some(start..end)
+    }
+
     /// Returns selections for remote peers intersecting the given range.
     #[allow(clippy::type_complexity)]
     pub fn remote_selections_in_range(
@@ -3066,6 +3423,16 @@ impl BufferSnapshot {
     pub fn git_diff_update_count(&self) -> usize {
         self.git_diff_update_count
     }
+
+    /// The number of times the semantic tokens were updated.
+    pub fn semantic_tokens_update_count(&self) -> usize {
+        self.semantic_tokens_update_count
+    }
+
+    /// The number of times the language server's folding ranges were updated.
+    pub fn folding_ranges_update_count(&self) -> usize {
+        self.folding_ranges_update_count
+    }
 }
 
 fn indent_size_for_line(text: &text::BufferSnapshot, row: u32) -> IndentSize {
@@ -3101,6 +3468,11 @@ impl Clone for BufferSnapshot {
             diagnostics_update_count: self.diagnostics_update_count,
             file_update_count: self.file_update_count,
             git_diff_update_count: self.git_diff_update_count,
+            semantic_tokens: self.semantic_tokens.clone(),
+            semantic_tokens_update_count: self.semantic_tokens_update_count,
+            lsp_folding_ranges: self.lsp_folding_ranges.clone(),
+            syntax_folding_ranges: self.syntax_folding_ranges.clone(),
+            folding_ranges_update_count: self.folding_ranges_update_count,
             language: self.language.clone(),
             parse_count: self.parse_count,
         }
@@ -3123,6 +3495,7 @@ impl<'a> BufferChunks<'a> {
         range: Range<usize>,
         syntax: Option<(SyntaxMapCaptures<'a>, Vec<HighlightMap>)>,
         diagnostic_endpoints: Vec<DiagnosticEndpoint>,
+        semantic_tokens: Vec<(Range<usize>, HighlightId)>,
     ) -> Self {
         let mut highlights = None;
         if let Some((captures, highlight_maps)) = syntax {
@@ -3147,6 +3520,8 @@ impl<'a> BufferChunks<'a> {
             hint_depth: 0,
             unnecessary_depth: 0,
             highlights,
+            semantic_tokens,
+            semantic_token_ix: 0,
         }
     }
 
@@ -3172,6 +3547,9 @@ impl<'a> BufferChunks<'a> {
             }
             highlights.captures.set_byte_range(self.range.clone());
         }
+        self.semantic_token_ix = self
+            .semantic_tokens
+            .partition_point(|(range, _)| range.end <= offset);
     }
 
     /// The current byte offset in the buffer.
@@ -3266,11 +3644,31 @@ impl<'a> Iterator for BufferChunks<'a> {
             }
         }
 
+        // Semantic tokens take precedence over the syntax highlighting of the text they cover.
+        let mut semantic_highlight_id = None;
+        let mut next_semantic_token_boundary = usize::MAX;
+        while let Some((token_range, highlight_id)) =
+            self.semantic_tokens.get(self.semantic_token_ix)
+        {
+            if token_range.end <= self.range.start {
+                self.semantic_token_ix += 1;
+            } else {
+                if token_range.start <= self.range.start {
+                    semantic_highlight_id = Some(*highlight_id);
+                    next_semantic_token_boundary = token_range.end;
+                } else {
+                    next_semantic_token_boundary = token_range.start;
+                }
+                break;
+            }
+        }
+
         if let Some(chunk) = self.chunks.peek() {
             let chunk_start = self.range.start;
             let mut chunk_end = (self.chunks.offset() + chunk.len())
                 .min(next_capture_start)
-                .min(next_diagnostic_endpoint);
+                .min(next_diagnostic_endpoint)
+                .min(next_semantic_token_boundary);
             let mut highlight_id = None;
             if let Some(highlights) = self.highlights.as_ref() {
                 if let Some((parent_capture_end, parent_highlight_id)) = highlights.stack.last() {
@@ -3278,6 +3676,9 @@ impl<'a> Iterator for BufferChunks<'a> {
                     highlight_id = Some(*parent_highlight_id);
                 }
             }
+            if semantic_highlight_id.is_some() {
+                highlight_id = semantic_highlight_id;
+            }
 
             let slice =
                 &chunk[chunk_start - self.chunks.offset()..chunk_end - self.chunks.offset()];
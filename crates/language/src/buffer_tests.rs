@@ -245,6 +245,72 @@ async fn test_apply_diff(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+fn test_serialize_and_restore_history(cx: &mut AppContext) {
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "abc\ndef\n",
+        )
+    });
+    let history = buffer.update(cx, |buffer, cx| {
+        for (range, text) in [(0..0, "1"), (5..8, "XYZ"), (9..9, "!")] {
+            buffer.edit([(range, text)], None, cx);
+            buffer.finalize_last_transaction();
+        }
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "1abc\nXYZ\n");
+        buffer.history_snapshot().serialize().unwrap()
+    });
+    assert_eq!(history.base_text, "abc\ndef\n");
+    assert_eq!(history.transactions.len(), 3);
+    assert_eq!(history.undone_count, 1);
+
+    let restored = cx.new_model(|cx| {
+        Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "1abc\nXYZ\n",
+        )
+    });
+    let events = Arc::new(Mutex::new(Vec::new()));
+    cx.subscribe(&restored, {
+        let events = events.clone();
+        move |_, event: &Event, _| events.lock().push(event.clone())
+    })
+    .detach();
+    restored.update(cx, |buffer, cx| {
+        // The text doesn't change, so nothing is emitted or sent to other replicas.
+        buffer.restore_history(history.clone()).unwrap();
+        assert_eq!(buffer.text(), "1abc\nXYZ\n");
+        assert!(!buffer.is_dirty());
+        assert!(events.lock().is_empty());
+        buffer.redo(cx);
+        assert_eq!(buffer.text(), "1abc\nXYZ\n!");
+        buffer.undo(cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "1abc\ndef\n");
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "abc\ndef\n");
+        assert!(buffer.undo(cx).is_none());
+    });
+
+    // A history that doesn't lead to the buffer's text is rejected.
+    let changed = cx.new_model(|cx| {
+        Buffer::new(
+            0,
+            BufferId::new(cx.entity_id().as_u64()).unwrap(),
+            "1abc\nXY\n",
+        )
+    });
+    changed.update(cx, |buffer, cx| {
+        assert!(buffer.restore_history(history).is_err());
+        assert_eq!(buffer.text(), "1abc\nXY\n");
+        assert!(buffer.undo(cx).is_none());
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_normalize_whitespace(cx: &mut gpui::TestAppContext) {
     let text = [
@@ -931,6 +997,151 @@ fn test_range_for_syntax_ancestor(cx: &mut AppContext) {
     }
 }
 
+#[gpui::test]
+fn test_folding_ranges(cx: &mut AppContext) {
+    let language = rust_lang()
+        .with_folds_query(
+            r#"
+            (block) @fold
+            (block_comment) @fold.comment
+            (use_declaration)+ @fold.imports
+            "#,
+        )
+        .unwrap();
+    let text = r#"
+        use std::fmt;
+        use std::io;
+
+        /* a long
+           comment */
+        fn a() {
+            if true {
+                b();
+            }
+        }
+    "#
+    .unindent();
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(language), cx)
+    });
+
+    let snapshot = buffer.read(cx).snapshot();
+    assert!(snapshot.has_folding_ranges());
+    assert_eq!(
+        folding_ranges(&snapshot),
+        [
+            (
+                Point::new(0, 13)..Point::new(1, 12),
+                Some(FoldKind::Imports)
+            ),
+            (Point::new(3, 9)..Point::new(4, 13), Some(FoldKind::Comment)),
+            (Point::new(5, 8)..Point::new(9, 0), None),
+            (Point::new(6, 13)..Point::new(8, 4), None),
+        ]
+    );
+
+    // The cached folds are recomputed once the buffer changes.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    let snapshot = buffer.read(cx).snapshot();
+    assert_eq!(
+        folding_ranges(&snapshot),
+        [
+            (
+                Point::new(1, 13)..Point::new(2, 12),
+                Some(FoldKind::Imports)
+            ),
+            (Point::new(4, 9)..Point::new(5, 13), Some(FoldKind::Comment)),
+            (Point::new(6, 8)..Point::new(10, 0), None),
+            (Point::new(7, 13)..Point::new(9, 4), None),
+        ]
+    );
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..1, "")], None, cx));
+
+    // Folding ranges reported by a language server replace the syntactic ones.
+    buffer.update(cx, |buffer, cx| {
+        let range = buffer.anchor_before(Point::new(5, 8))..buffer.anchor_before(Point::new(8, 5));
+        buffer.set_lsp_folding_ranges(
+            Some(Arc::from([FoldingRange {
+                range,
+                kind: Some(FoldKind::Region),
+            }])),
+            cx,
+        );
+    });
+    let snapshot = buffer.read(cx).snapshot();
+    assert_eq!(
+        folding_ranges(&snapshot),
+        [(Point::new(5, 8)..Point::new(8, 5), Some(FoldKind::Region))]
+    );
+
+    fn folding_ranges(snapshot: &BufferSnapshot) -> Vec<(Range<Point>, Option<FoldKind>)> {
+        snapshot
+            .folding_ranges(0..snapshot.len())
+            .into_iter()
+            .map(|fold| (fold.range.to_point(snapshot), fold.kind))
+            .collect()
+    }
+}
+
+#[gpui::test]
+fn test_region_folding_ranges(cx: &mut AppContext) {
+    let language = rust_lang()
+        .with_folds_query(
+            r#"
+            ((line_comment) @fold.region.start
+             (#match? @fold.region.start "^//\\s*#region\\b"))
+            ((line_comment) @fold.region.end
+             (#match? @fold.region.end "^//\\s*#endregion\\b"))
+            "#,
+        )
+        .unwrap();
+    let text = r#"
+        // #region outer
+        fn a() {}
+        // #region inner
+        fn b() {}
+        // #endregion
+        // #endregion
+        // #endregion without a start
+        // #region without an end
+        fn c() {}
+    "#
+    .unindent();
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(language), cx)
+    });
+
+    let snapshot = buffer.read(cx).snapshot();
+    assert!(snapshot.has_folding_ranges());
+    let folds = snapshot
+        .folding_ranges(0..snapshot.len())
+        .into_iter()
+        .map(|fold| (fold.range.to_point(&snapshot), fold.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        folds,
+        [
+            (Point::new(0, 16)..Point::new(5, 13), Some(FoldKind::Region)),
+            (Point::new(2, 16)..Point::new(4, 13), Some(FoldKind::Region)),
+        ]
+    );
+
+    // Regions are found from their start row even when their end lies outside
+    // the requested range.
+    let start = snapshot.point_to_offset(Point::new(2, 0));
+    let end = snapshot.point_to_offset(Point::new(3, 0));
+    assert_eq!(
+        snapshot
+            .folding_ranges(start..end)
+            .into_iter()
+            .map(|fold| fold.range.to_point(&snapshot))
+            .collect::<Vec<_>>(),
+        [Point::new(2, 16)..Point::new(4, 13)]
+    );
+}
+
 #[gpui::test]
 fn test_autoindent_with_soft_tabs(cx: &mut AppContext) {
     init_settings(cx, |_| {});
@@ -141,7 +141,7 @@ pub struct CachedLspAdapter {
 }
 
 impl CachedLspAdapter {
-    pub async fn new(adapter: Arc<dyn LspAdapter>) -> Arc<Self> {
+    pub fn new(adapter: Arc<dyn LspAdapter>) -> Arc<Self> {
         let name = adapter.name();
         let short_name = adapter.short_name();
         let disk_based_diagnostic_sources = adapter.disk_based_diagnostic_sources();
@@ -453,6 +453,7 @@ pub struct LanguageQueries {
     pub embedding: Option<Cow<'static, str>>,
     pub injections: Option<Cow<'static, str>>,
     pub overrides: Option<Cow<'static, str>>,
+    pub folds: Option<Cow<'static, str>>,
 }
 
 /// Represents a language for the given range. Some languages (e.g. HTML)
@@ -609,6 +610,7 @@ pub struct Language {
     pub(crate) config: LanguageConfig,
     pub(crate) grammar: Option<Arc<Grammar>>,
     pub(crate) adapters: Vec<Arc<CachedLspAdapter>>,
+    semantic_token_highlights: Mutex<SemanticTokenHighlights>,
 
     #[cfg(any(test, feature = "test-support"))]
     fake_adapter: Option<(
@@ -617,6 +619,13 @@ pub struct Language {
     )>,
 }
 
+/// Caches the syntax theme styles that semantic token names resolve to.
+#[derive(Default)]
+struct SemanticTokenHighlights {
+    theme: Option<SyntaxTheme>,
+    ids: HashMap<Arc<str>, HighlightId>,
+}
+
 pub struct Grammar {
     id: usize,
     pub ts_language: tree_sitter::Language,
@@ -628,6 +637,7 @@ pub struct Grammar {
     pub embedding_config: Option<EmbeddingConfig>,
     pub(crate) injection_config: Option<InjectionConfig>,
     pub(crate) override_config: Option<OverrideConfig>,
+    pub(crate) folds_config: Option<FoldConfig>,
     pub(crate) highlight_map: Mutex<HighlightMap>,
 }
 
@@ -675,6 +685,24 @@ struct InjectionPatternConfig {
     combined: bool,
 }
 
+pub(crate) struct FoldConfig {
+    pub(crate) query: Query,
+    kinds_by_capture_ix: Vec<(u32, Option<FoldKind>)>,
+    pub(crate) region_start_capture_ix: Option<u32>,
+    pub(crate) region_end_capture_ix: Option<u32>,
+}
+
+impl FoldConfig {
+    /// Returns the kind of fold for the given capture, or `None` if the
+    /// capture does not denote a fold.
+    pub(crate) fn kind_for_capture(&self, capture_ix: u32) -> Option<Option<FoldKind>> {
+        self.kinds_by_capture_ix
+            .iter()
+            .find(|(ix, _)| *ix == capture_ix)
+            .map(|(_, kind)| *kind)
+    }
+}
+
 struct BracketConfig {
     query: Query,
     open_capture_ix: u32,
@@ -1027,6 +1055,7 @@ impl LanguageRegistry {
         adapter: Arc<CachedLspAdapter>,
         root_path: Arc<Path>,
         delegate: Arc<dyn LspAdapterDelegate>,
+        binary_override: Option<LanguageServerBinary>,
         cx: &mut AppContext,
     ) -> Option<PendingLanguageServer> {
         let server_id = self.state.write().next_language_server_id();
@@ -1072,6 +1101,33 @@ impl LanguageRegistry {
             });
         }
 
+        // A binary configured by the user is launched as-is, without
+        // downloading, caching or reinstalling anything.
+        if let Some(binary) = binary_override {
+            let root_path = root_path.clone();
+            let code_action_kinds = adapter.code_action_kinds();
+            let task = cx.spawn(move |mut cx| async move {
+                if let Some(task) = adapter.will_start_server(&delegate, &mut cx) {
+                    task.await?;
+                }
+
+                lsp::LanguageServer::new(
+                    stderr_capture,
+                    server_id,
+                    binary,
+                    &root_path,
+                    code_action_kinds,
+                    cx,
+                )
+            });
+
+            return Some(PendingLanguageServer {
+                server_id,
+                task,
+                container_dir: None,
+            });
+        }
+
         let download_dir = self
             .language_server_download_dir
             .clone()
@@ -1303,12 +1359,14 @@ impl Language {
                     indents_config: None,
                     injection_config: None,
                     override_config: None,
+                    folds_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
                 })
             }),
             adapters: Vec::new(),
+            semantic_token_highlights: Default::default(),
 
             #[cfg(any(test, feature = "test-support"))]
             fake_adapter: None,
@@ -1359,6 +1417,11 @@ impl Language {
                 .with_override_query(query.as_ref())
                 .context("Error loading override query")?;
         }
+        if let Some(query) = queries.folds {
+            self = self
+                .with_folds_query(query.as_ref())
+                .context("Error loading folds query")?;
+        }
         Ok(self)
     }
 
@@ -1396,6 +1459,44 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_folds_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self.grammar_mut();
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut kinds_by_capture_ix = Vec::new();
+        let mut region_start_capture_ix = None;
+        let mut region_end_capture_ix = None;
+        for (ix, name) in query.capture_names().iter().enumerate() {
+            let kind = match *name {
+                "fold" => None,
+                "fold.comment" => Some(FoldKind::Comment),
+                "fold.imports" => Some(FoldKind::Imports),
+                "fold.region" => Some(FoldKind::Region),
+                "fold.region.start" => {
+                    region_start_capture_ix = Some(ix as u32);
+                    continue;
+                }
+                "fold.region.end" => {
+                    region_end_capture_ix = Some(ix as u32);
+                    continue;
+                }
+                _ => continue,
+            };
+            kinds_by_capture_ix.push((ix as u32, kind));
+        }
+        if !kinds_by_capture_ix.is_empty()
+            || region_start_capture_ix.is_some()
+            || region_end_capture_ix.is_some()
+        {
+            grammar.folds_config = Some(FoldConfig {
+                query,
+                kinds_by_capture_ix,
+                region_start_capture_ix,
+                region_end_capture_ix,
+            });
+        }
+        Ok(self)
+    }
+
     pub fn with_embedding_query(mut self, source: &str) -> Result<Self> {
         let grammar = self.grammar_mut();
         let query = Query::new(&grammar.ts_language, source)?;
@@ -1595,7 +1696,7 @@ impl Language {
 
     pub async fn with_lsp_adapters(mut self, lsp_adapters: Vec<Arc<dyn LspAdapter>>) -> Self {
         for adapter in lsp_adapters {
-            self.adapters.push(CachedLspAdapter::new(adapter).await);
+            self.adapters.push(CachedLspAdapter::new(adapter));
         }
         self
     }
@@ -1607,7 +1708,7 @@ impl Language {
     ) -> mpsc::UnboundedReceiver<lsp::FakeLanguageServer> {
         let (servers_tx, servers_rx) = mpsc::unbounded();
         self.fake_adapter = Some((servers_tx, fake_lsp_adapter.clone()));
-        let adapter = CachedLspAdapter::new(Arc::new(fake_lsp_adapter)).await;
+        let adapter = CachedLspAdapter::new(Arc::new(fake_lsp_adapter));
         self.adapters = vec![adapter];
         servers_rx
     }
@@ -1677,7 +1778,13 @@ impl Language {
                 });
             let highlight_maps = vec![grammar.highlight_map()];
             let mut offset = 0;
-            for chunk in BufferChunks::new(text, range, Some((captures, highlight_maps)), vec![]) {
+            for chunk in BufferChunks::new(
+                text,
+                range,
+                Some((captures, highlight_maps)),
+                vec![],
+                vec![],
+            ) {
                 let end_offset = offset + chunk.text.len();
                 if let Some(highlight_id) = chunk.syntax_highlight_id {
                     if !highlight_id.is_default() {
@@ -1705,6 +1812,21 @@ impl Language {
                     HighlightMap::new(highlights_query.capture_names(), theme);
             }
         }
+        *self.semantic_token_highlights.lock() = SemanticTokenHighlights {
+            theme: Some(theme.clone()),
+            ids: HashMap::default(),
+        };
+    }
+
+    /// Returns the syntax theme style for a semantic token with the given highlight name.
+    pub fn highlight_id_for_semantic_token(&self, highlight_name: &Arc<str>) -> HighlightId {
+        let mut highlights = self.semantic_token_highlights.lock();
+        let SemanticTokenHighlights { theme, ids } = &mut *highlights;
+        let Some(theme) = theme.as_ref() else {
+            return HighlightId::default();
+        };
+        *ids.entry(highlight_name.clone())
+            .or_insert_with(|| HighlightId::for_name(highlight_name, theme))
     }
 
     pub fn grammar(&self) -> Option<&Arc<Grammar>> {
@@ -1,6 +1,6 @@
 //! Provides `language`-related settings.
 
-use crate::{File, Language};
+use crate::{File, Language, LanguageServerName};
 use anyhow::Result;
 use collections::{HashMap, HashSet};
 use globset::GlobMatcher;
@@ -10,12 +10,20 @@ use schemars::{
     JsonSchema,
 };
 use serde::{Deserialize, Serialize};
-use settings::Settings;
+use settings::{EditorconfigProperties, Settings, SettingsStore};
 use std::{num::NonZeroU32, path::Path, sync::Arc};
 
 /// Initializes the language settings.
 pub fn init(cx: &mut AppContext) {
     AllLanguageSettings::register(cx);
+    cx.update_global(|store: &mut SettingsStore, _| {
+        store.set_editorconfig_callback::<AllLanguageSettings>(|properties| {
+            AllLanguageSettingsContent {
+                defaults: properties.into(),
+                ..Default::default()
+            }
+        });
+    });
 }
 
 /// Returns the settings for the specified language from the provided file.
@@ -82,6 +90,8 @@ pub struct LanguageSettings {
     pub prettier: HashMap<String, serde_json::Value>,
     /// Whether to use language servers to provide code intelligence.
     pub enable_language_server: bool,
+    /// The list of language servers to use (or disable) for this language.
+    pub language_servers: Vec<Arc<str>>,
     /// Controls whether Copilot provides suggestion immediately (true)
     /// or waits for a `copilot::Toggle` (false).
     pub show_copilot_suggestions: bool,
@@ -91,10 +101,58 @@ pub struct LanguageSettings {
     pub extend_comment_on_newline: bool,
     /// Inlay hint related settings.
     pub inlay_hints: InlayHintSettings,
+    /// Semantic token related settings.
+    pub semantic_tokens: SemanticTokenSettings,
     /// Whether to automatically close brackets.
     pub use_autoclose: bool,
 }
 
+impl LanguageSettings {
+    /// A token representing the rest of the available language servers.
+    const REST_OF_LANGUAGE_SERVERS: &'static str = "...";
+
+    /// Returns the names of the language servers to start for this language,
+    /// in order, given the names of all the available language servers.
+    pub fn customized_language_servers(
+        &self,
+        available_language_servers: &[LanguageServerName],
+    ) -> Vec<LanguageServerName> {
+        let (disabled_language_servers, enabled_language_servers): (Vec<_>, Vec<_>) = self
+            .language_servers
+            .iter()
+            .partition(|name| name.starts_with('!'));
+        let disabled_language_servers = disabled_language_servers
+            .into_iter()
+            .map(|name| &name[1..])
+            .collect::<HashSet<_>>();
+
+        let rest = available_language_servers
+            .iter()
+            .filter(|available| {
+                let name = available.0.as_ref();
+                !disabled_language_servers.contains(name)
+                    && !enabled_language_servers
+                        .iter()
+                        .any(|enabled| enabled.as_ref() == name)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        enabled_language_servers
+            .into_iter()
+            .flat_map(|name| {
+                if name.as_ref() == Self::REST_OF_LANGUAGE_SERVERS {
+                    rest.clone()
+                } else if disabled_language_servers.contains(name.as_ref()) {
+                    Vec::new()
+                } else {
+                    vec![LanguageServerName(name.clone())]
+                }
+            })
+            .collect()
+    }
+}
+
 /// The settings for [GitHub Copilot](https://github.com/features/copilot).
 #[derive(Clone, Debug, Default)]
 pub struct CopilotSettings {
@@ -193,6 +251,17 @@ pub struct LanguageSettingsContent {
     /// Default: true
     #[serde(default)]
     pub enable_language_server: Option<bool>,
+    /// The list of language servers to use (or disable) for this language.
+    ///
+    /// This array should consist of language server names, as well as the
+    /// special string "...", which refers to the rest of the language servers
+    /// that are registered for this language. Language servers are started in
+    /// the order in which they appear, and a name prefixed with `!` disables
+    /// that language server.
+    ///
+    /// Default: ["..."]
+    #[serde(default)]
+    pub language_servers: Option<Vec<Arc<str>>>,
     /// Controls whether Copilot provides suggestion immediately (true)
     /// or waits for a `copilot::Toggle` (false).
     ///
@@ -210,6 +279,9 @@ pub struct LanguageSettingsContent {
     /// Inlay hint related settings.
     #[serde(default)]
     pub inlay_hints: Option<InlayHintSettings>,
+    /// Semantic token related settings.
+    #[serde(default)]
+    pub semantic_tokens: Option<SemanticTokenSettingsContent>,
     /// Whether to automatically type closing characters for you. For example,
     /// when you type (, Zed will automatically add a closing ) at the correct position.
     ///
@@ -277,15 +349,19 @@ pub enum ShowWhitespaceSetting {
 }
 
 /// Controls which formatter should be used when formatting code.
-#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "FormatterContent", into = "FormatterContent")]
 pub enum Formatter {
     /// Format files using Zed's Prettier integration (if applicable),
     /// or falling back to formatting via language server.
     #[default]
     Auto,
-    /// Format code using the current language server.
-    LanguageServer,
+    /// Format code using a language server.
+    LanguageServer {
+        /// The name of the language server to format with. When not set,
+        /// the buffer's primary language server is used.
+        name: Option<Arc<str>>,
+    },
     /// Format code using Zed's Prettier integration.
     Prettier,
     /// Format code using an external command.
@@ -295,6 +371,142 @@ pub enum Formatter {
         /// The arguments to pass to the program.
         arguments: Arc<[String]>,
     },
+    /// Format code by applying the code actions of the given kinds, such as
+    /// `source.organizeImports`, in order.
+    CodeActions(Arc<[String]>),
+    /// Format code by running each of the formatters in order.
+    List(Arc<[Formatter]>),
+}
+
+impl Formatter {
+    /// Returns the formatting steps to run, in order, with any lists flattened.
+    pub fn steps(&self) -> Vec<&Formatter> {
+        match self {
+            Formatter::List(formatters) => formatters.iter().flat_map(Formatter::steps).collect(),
+            formatter => vec![formatter],
+        }
+    }
+}
+
+impl JsonSchema for Formatter {
+    fn schema_name() -> String {
+        FormatterContent::schema_name()
+    }
+
+    fn json_schema(generator: &mut schemars::gen::SchemaGenerator) -> Schema {
+        FormatterContent::json_schema(generator)
+    }
+}
+
+/// How a [`Formatter`] is written in the settings: either a single
+/// formatter, or a list of them.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum FormatterContent {
+    List(Vec<FormatterStepContent>),
+    Step(FormatterStepContent),
+}
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum FormatterStepContent {
+    Named(NamedFormatterContent),
+    Configured(ConfiguredFormatterContent),
+}
+
+/// The formatters that are written as a plain string.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum NamedFormatterContent {
+    Auto,
+    LanguageServer,
+    Prettier,
+}
+
+/// The formatters that are written as an object with options.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum ConfiguredFormatterContent {
+    LanguageServer {
+        #[serde(default)]
+        name: Option<Arc<str>>,
+    },
+    External {
+        command: Arc<str>,
+        arguments: Arc<[String]>,
+    },
+    CodeActions(Arc<[String]>),
+}
+
+impl From<FormatterContent> for Formatter {
+    fn from(content: FormatterContent) -> Self {
+        match content {
+            FormatterContent::List(steps) => {
+                Formatter::List(steps.into_iter().map(Formatter::from).collect())
+            }
+            FormatterContent::Step(step) => step.into(),
+        }
+    }
+}
+
+impl From<FormatterStepContent> for Formatter {
+    fn from(content: FormatterStepContent) -> Self {
+        match content {
+            FormatterStepContent::Named(NamedFormatterContent::Auto) => Formatter::Auto,
+            FormatterStepContent::Named(NamedFormatterContent::LanguageServer) => {
+                Formatter::LanguageServer { name: None }
+            }
+            FormatterStepContent::Named(NamedFormatterContent::Prettier) => Formatter::Prettier,
+            FormatterStepContent::Configured(ConfiguredFormatterContent::LanguageServer {
+                name,
+            }) => Formatter::LanguageServer { name },
+            FormatterStepContent::Configured(ConfiguredFormatterContent::External {
+                command,
+                arguments,
+            }) => Formatter::External { command, arguments },
+            FormatterStepContent::Configured(ConfiguredFormatterContent::CodeActions(kinds)) => {
+                Formatter::CodeActions(kinds)
+            }
+        }
+    }
+}
+
+impl From<Formatter> for FormatterContent {
+    fn from(formatter: Formatter) -> Self {
+        let is_list = matches!(formatter, Formatter::List(_));
+        let mut steps = formatter
+            .steps()
+            .into_iter()
+            .map(|step| match step.clone() {
+                Formatter::Auto | Formatter::List(_) => {
+                    FormatterStepContent::Named(NamedFormatterContent::Auto)
+                }
+                Formatter::LanguageServer { name: None } => {
+                    FormatterStepContent::Named(NamedFormatterContent::LanguageServer)
+                }
+                Formatter::LanguageServer { name } => {
+                    FormatterStepContent::Configured(ConfiguredFormatterContent::LanguageServer {
+                        name,
+                    })
+                }
+                Formatter::Prettier => FormatterStepContent::Named(NamedFormatterContent::Prettier),
+                Formatter::External { command, arguments } => {
+                    FormatterStepContent::Configured(ConfiguredFormatterContent::External {
+                        command,
+                        arguments,
+                    })
+                }
+                Formatter::CodeActions(kinds) => {
+                    FormatterStepContent::Configured(ConfiguredFormatterContent::CodeActions(kinds))
+                }
+            })
+            .collect::<Vec<_>>();
+        if is_list || steps.len() != 1 {
+            FormatterContent::List(steps)
+        } else {
+            FormatterContent::Step(steps.remove(0))
+        }
+    }
 }
 
 /// The settings for inlay hints.
@@ -322,6 +534,70 @@ pub struct InlayHintSettings {
     pub show_other_hints: bool,
 }
 
+/// The settings for semantic tokens.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct SemanticTokenSettings {
+    /// Whether to request semantic tokens from language servers and layer
+    /// them on top of the tree-sitter highlighting.
+    pub enabled: bool,
+    /// Maps semantic token selectors to the names of syntax theme styles.
+    pub rules: HashMap<String, String>,
+}
+
+/// The contents of the semantic token settings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SemanticTokenSettingsContent {
+    /// Whether to request semantic tokens from language servers and layer
+    /// them on top of the tree-sitter highlighting.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Maps semantic token selectors to the names of syntax theme styles.
+    /// A selector is a token type, optionally followed by one of the token's
+    /// modifiers, such as `variable.mutable`. Mapping a selector to an empty
+    /// string leaves the token's highlighting to tree-sitter.
+    ///
+    /// Rules are merged with the ones from less specific settings.
+    #[serde(default)]
+    pub rules: Option<HashMap<String, String>>,
+}
+
+impl SemanticTokenSettings {
+    /// Returns the name of the syntax theme style for a token with the given
+    /// type and modifiers, or `None` if the token should not be highlighted.
+    ///
+    /// Rules for the token type qualified by one of its modifiers take
+    /// precedence over a rule for the bare token type. Tokens without a
+    /// matching rule are looked up in the theme by their type and modifiers.
+    pub fn highlight_name(
+        &self,
+        token_type: &str,
+        modifiers: &[impl AsRef<str>],
+    ) -> Option<String> {
+        let rule = modifiers
+            .iter()
+            .find_map(|modifier| {
+                self.rules
+                    .get(&format!("{token_type}.{}", modifier.as_ref()))
+            })
+            .or_else(|| self.rules.get(token_type));
+        let name = match rule {
+            Some(name) => name.clone(),
+            None => modifiers
+                .iter()
+                .fold(token_type.to_string(), |name, modifier| {
+                    format!("{name}.{}", modifier.as_ref())
+                }),
+        };
+        if name.is_empty() {
+            None
+        } else {
+            Some(name)
+        }
+    }
+}
+
 fn default_true() -> bool {
     true
 }
@@ -543,6 +819,19 @@ impl settings::Settings for AllLanguageSettings {
     }
 }
 
+impl From<EditorconfigProperties> for LanguageSettingsContent {
+    fn from(properties: EditorconfigProperties) -> Self {
+        Self {
+            tab_size: properties.indent_size,
+            hard_tabs: properties.indent_with_tabs,
+            preferred_line_length: properties.max_line_length,
+            remove_trailing_whitespace_on_save: properties.trim_trailing_whitespace,
+            ensure_final_newline_on_save: properties.insert_final_newline,
+            ..Default::default()
+        }
+    }
+}
+
 fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent) {
     merge(&mut settings.tab_size, src.tab_size);
     merge(&mut settings.hard_tabs, src.hard_tabs);
@@ -570,6 +859,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         &mut settings.enable_language_server,
         src.enable_language_server,
     );
+    merge(&mut settings.language_servers, src.language_servers.clone());
     merge(
         &mut settings.show_copilot_suggestions,
         src.show_copilot_suggestions,
@@ -580,9 +870,63 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.extend_comment_on_newline,
     );
     merge(&mut settings.inlay_hints, src.inlay_hints);
+    if let Some(semantic_tokens) = &src.semantic_tokens {
+        merge(
+            &mut settings.semantic_tokens.enabled,
+            semantic_tokens.enabled,
+        );
+        if let Some(rules) = &semantic_tokens.rules {
+            settings.semantic_tokens.rules.extend(
+                rules
+                    .iter()
+                    .map(|(selector, name)| (selector.clone(), name.clone())),
+            );
+        }
+    }
     fn merge<T>(target: &mut T, value: Option<T>) {
         if let Some(value) = value {
             *target = value;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    fn test_customized_language_servers(cx: &mut AppContext) {
+        let settings_store = settings::SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        crate::init(cx);
+
+        let available = ["rust-analyzer", "tailwindcss-language-server", "ctags"]
+            .into_iter()
+            .map(|name| LanguageServerName(name.into()))
+            .collect::<Vec<_>>();
+        let customized = |language_servers: &[&str]| {
+            let mut settings = all_language_settings(None, cx).language(None).clone();
+            settings.language_servers = language_servers.iter().map(|&name| name.into()).collect();
+            settings
+                .customized_language_servers(&available)
+                .into_iter()
+                .map(|name| name.0.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            customized(&["..."]),
+            ["rust-analyzer", "tailwindcss-language-server", "ctags"]
+        );
+        assert_eq!(
+            customized(&["ctags", "..."]),
+            ["ctags", "rust-analyzer", "tailwindcss-language-server"]
+        );
+        assert_eq!(
+            customized(&["!tailwindcss-language-server", "..."]),
+            ["rust-analyzer", "ctags"]
+        );
+        assert_eq!(customized(&["my-dsl-server"]), ["my-dsl-server"]);
+        assert_eq!(customized(&[]), Vec::<String>::new());
+    }
+}
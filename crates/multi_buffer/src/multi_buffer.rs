@@ -12,8 +12,8 @@ use language::{
     char_kind,
     language_settings::{language_settings, LanguageSettings},
     AutoindentMode, Buffer, BufferChunks, BufferSnapshot, Capability, CharKind, Chunk, CursorShape,
-    DiagnosticEntry, File, IndentSize, Language, LanguageScope, OffsetRangeExt, OffsetUtf16,
-    Outline, OutlineItem, Point, PointUtf16, Selection, TextDimension, ToOffset as _,
+    DiagnosticEntry, File, FoldingRange, IndentSize, Language, LanguageScope, OffsetRangeExt,
+    OffsetUtf16, Outline, OutlineItem, Point, PointUtf16, Selection, TextDimension, ToOffset as _,
     ToOffsetUtf16 as _, ToPoint as _, ToPointUtf16 as _, TransactionId, Unclipped,
 };
 use std::{
@@ -131,6 +131,8 @@ struct BufferState {
     last_diagnostics_update_count: usize,
     last_file_update_count: usize,
     last_git_diff_update_count: usize,
+    last_semantic_tokens_update_count: usize,
+    last_folding_ranges_update_count: usize,
     excerpts: Vec<Locator>,
     _subscriptions: [gpui::Subscription; 2],
 }
@@ -144,6 +146,7 @@ pub struct MultiBufferSnapshot {
     diagnostics_update_count: usize,
     trailing_excerpt_update_count: usize,
     git_diff_update_count: usize,
+    semantic_tokens_update_count: usize,
     edit_count: usize,
     is_dirty: bool,
     has_conflict: bool,
@@ -260,6 +263,9 @@ impl MultiBuffer {
                     last_diagnostics_update_count: buffer_state.last_diagnostics_update_count,
                     last_file_update_count: buffer_state.last_file_update_count,
                     last_git_diff_update_count: buffer_state.last_git_diff_update_count,
+                    last_semantic_tokens_update_count: buffer_state
+                        .last_semantic_tokens_update_count,
+                    last_folding_ranges_update_count: buffer_state.last_folding_ranges_update_count,
                     excerpts: buffer_state.excerpts.clone(),
                     _subscriptions: [
                         new_cx.observe(&buffer_state.buffer, |_, _, cx| cx.notify()),
@@ -1034,6 +1040,8 @@ impl MultiBuffer {
             last_diagnostics_update_count: buffer_snapshot.diagnostics_update_count(),
             last_file_update_count: buffer_snapshot.file_update_count(),
             last_git_diff_update_count: buffer_snapshot.git_diff_update_count(),
+            last_semantic_tokens_update_count: buffer_snapshot.semantic_tokens_update_count(),
+            last_folding_ranges_update_count: buffer_snapshot.folding_ranges_update_count(),
             excerpts: Default::default(),
             _subscriptions: [
                 cx.observe(&buffer, |_, _, cx| cx.notify()),
@@ -1537,6 +1545,7 @@ impl MultiBuffer {
         let mut reparsed = false;
         let mut diagnostics_updated = false;
         let mut git_diff_updated = false;
+        let mut semantic_tokens_updated = false;
         let mut is_dirty = false;
         let mut has_conflict = false;
         let mut edited = false;
@@ -1549,6 +1558,8 @@ impl MultiBuffer {
             let diagnostics_update_count = buffer.diagnostics_update_count();
             let file_update_count = buffer.file_update_count();
             let git_diff_update_count = buffer.git_diff_update_count();
+            let semantic_tokens_update_count = buffer.semantic_tokens_update_count();
+            let folding_ranges_update_count = buffer.folding_ranges_update_count();
 
             let buffer_edited = version.changed_since(&buffer_state.last_version);
             let buffer_reparsed = parse_count > buffer_state.last_parse_count;
@@ -1559,12 +1570,18 @@ impl MultiBuffer {
             let buffer_file_updated = file_update_count > buffer_state.last_file_update_count;
             let buffer_git_diff_updated =
                 git_diff_update_count > buffer_state.last_git_diff_update_count;
+            let buffer_semantic_tokens_updated =
+                semantic_tokens_update_count > buffer_state.last_semantic_tokens_update_count;
+            let buffer_folding_ranges_updated =
+                folding_ranges_update_count > buffer_state.last_folding_ranges_update_count;
             if buffer_edited
                 || buffer_reparsed
                 || buffer_selections_updated
                 || buffer_diagnostics_updated
                 || buffer_file_updated
                 || buffer_git_diff_updated
+                || buffer_semantic_tokens_updated
+                || buffer_folding_ranges_updated
             {
                 buffer_state.last_version = version;
                 buffer_state.last_parse_count = parse_count;
@@ -1572,6 +1589,8 @@ impl MultiBuffer {
                 buffer_state.last_diagnostics_update_count = diagnostics_update_count;
                 buffer_state.last_file_update_count = file_update_count;
                 buffer_state.last_git_diff_update_count = git_diff_update_count;
+                buffer_state.last_semantic_tokens_update_count = semantic_tokens_update_count;
+                buffer_state.last_folding_ranges_update_count = folding_ranges_update_count;
                 excerpts_to_edit.extend(
                     buffer_state
                         .excerpts
@@ -1584,6 +1603,7 @@ impl MultiBuffer {
             reparsed |= buffer_reparsed;
             diagnostics_updated |= buffer_diagnostics_updated;
             git_diff_updated |= buffer_git_diff_updated;
+            semantic_tokens_updated |= buffer_semantic_tokens_updated;
             is_dirty |= buffer.is_dirty();
             has_conflict |= buffer.has_conflict();
         }
@@ -1599,6 +1619,9 @@ impl MultiBuffer {
         if git_diff_updated {
             snapshot.git_diff_update_count += 1;
         }
+        if semantic_tokens_updated {
+            snapshot.semantic_tokens_update_count += 1;
+        }
         snapshot.is_dirty = is_dirty;
         snapshot.has_conflict = has_conflict;
 
@@ -2950,6 +2973,10 @@ impl MultiBufferSnapshot {
         self.git_diff_update_count
     }
 
+    pub fn semantic_tokens_update_count(&self) -> usize {
+        self.semantic_tokens_update_count
+    }
+
     pub fn trailing_excerpt_update_count(&self) -> usize {
         self.trailing_excerpt_update_count
     }
@@ -3167,6 +3194,68 @@ impl MultiBufferSnapshot {
         .flatten()
     }
 
+    /// Returns the folding ranges that start within `range`, or `None` if `range` spans
+    /// multiple excerpts or its buffer has no syntactic or language server folding ranges.
+    pub fn folding_ranges<T: ToOffset>(&self, range: Range<T>) -> Option<Vec<FoldingRange<usize>>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let (excerpt, excerpt_offset) = self.excerpt_containing(range.clone())?;
+        Self::excerpt_folding_ranges(excerpt, excerpt_offset, range)
+    }
+
+    /// Returns the folding ranges of every excerpt whose buffer provides them.
+    pub fn all_folding_ranges(&self) -> Vec<FoldingRange<usize>> {
+        let mut folds = Vec::new();
+        let mut cursor = self.excerpts.cursor::<usize>();
+        cursor.next(&());
+        while let Some(excerpt) = cursor.item() {
+            let excerpt_offset = *cursor.start();
+            let excerpt_range = excerpt_offset..excerpt_offset + excerpt.text_summary.len;
+            if let Some(excerpt_folds) =
+                Self::excerpt_folding_ranges(excerpt, excerpt_offset, excerpt_range)
+            {
+                folds.extend(excerpt_folds);
+            }
+            cursor.next(&());
+        }
+        folds
+    }
+
+    fn excerpt_folding_ranges(
+        excerpt: &Excerpt,
+        excerpt_offset: usize,
+        range: Range<usize>,
+    ) -> Option<Vec<FoldingRange<usize>>> {
+        if !excerpt.buffer.has_folding_ranges() {
+            return None;
+        }
+
+        let excerpt_buffer_start = excerpt.range.context.start.to_offset(&excerpt.buffer);
+        let excerpt_buffer_end = excerpt_buffer_start + excerpt.text_summary.len;
+        let start_in_buffer = excerpt_buffer_start + range.start.saturating_sub(excerpt_offset);
+        let end_in_buffer = excerpt_buffer_start + range.end.saturating_sub(excerpt_offset);
+        Some(
+            excerpt
+                .buffer
+                .folding_ranges(start_in_buffer..end_in_buffer)
+                .into_iter()
+                .filter_map(|fold| {
+                    if fold.range.start < excerpt_buffer_start
+                        || fold.range.start >= excerpt_buffer_end
+                    {
+                        return None;
+                    }
+
+                    let end = cmp::min(fold.range.end, excerpt_buffer_end);
+                    Some(FoldingRange {
+                        range: excerpt_offset + (fold.range.start - excerpt_buffer_start)
+                            ..excerpt_offset + (end - excerpt_buffer_start),
+                        kind: fold.kind,
+                    })
+                })
+                .collect(),
+        )
+    }
+
     pub fn range_for_syntax_ancestor<T: ToOffset>(&self, range: Range<T>) -> Option<Range<usize>> {
         let range = range.start.to_offset(self)..range.end.to_offset(self);
 
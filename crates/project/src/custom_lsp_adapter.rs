@@ -0,0 +1,89 @@
+use crate::project_settings::LspSettings;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use language::{CachedLspAdapter, LanguageServerName, LspAdapter, LspAdapterDelegate};
+use lsp::LanguageServerBinary;
+use std::{any::Any, path::PathBuf, sync::Arc};
+
+/// A language server that is defined entirely in the user's settings, and
+/// started for the languages listed there.
+pub(crate) struct CustomLanguageServer {
+    pub languages: Vec<Arc<str>>,
+    pub adapter: Arc<CachedLspAdapter>,
+}
+
+impl CustomLanguageServer {
+    /// Collects the user-defined language servers from the `lsp` settings:
+    /// those entries that list the languages they should be started for.
+    pub fn from_settings(lsp_settings: &HashMap<Arc<str>, LspSettings>) -> Vec<Self> {
+        let mut servers = lsp_settings
+            .iter()
+            .filter_map(|(name, settings)| {
+                Some(Self {
+                    languages: settings.languages.clone()?,
+                    adapter: CachedLspAdapter::new(Arc::new(CustomLspAdapter {
+                        name: LanguageServerName(name.clone()),
+                    })),
+                })
+            })
+            .collect::<Vec<_>>();
+        servers.sort_by(|a, b| a.adapter.name.0.cmp(&b.adapter.name.0));
+        servers
+    }
+}
+
+/// An adapter for a user-defined language server. Its binary always comes
+/// from the settings, so there is nothing for it to download.
+struct CustomLspAdapter {
+    name: LanguageServerName,
+}
+
+#[async_trait]
+impl LspAdapter for CustomLspAdapter {
+    fn name(&self) -> LanguageServerName {
+        self.name.clone()
+    }
+
+    fn short_name(&self) -> &'static str {
+        "custom"
+    }
+
+    async fn fetch_latest_server_version(
+        &self,
+        _: &dyn LspAdapterDelegate,
+    ) -> Result<Box<dyn 'static + Send + Any>> {
+        Err(anyhow!(
+            "language server {:?} has no binary path configured",
+            self.name.0
+        ))
+    }
+
+    async fn fetch_server_binary(
+        &self,
+        _: Box<dyn 'static + Send + Any>,
+        _: PathBuf,
+        _: &dyn LspAdapterDelegate,
+    ) -> Result<LanguageServerBinary> {
+        Err(anyhow!(
+            "custom language server {} has no installer",
+            self.name.0
+        ))
+    }
+
+    async fn cached_server_binary(
+        &self,
+        _: PathBuf,
+        _: &dyn LspAdapterDelegate,
+    ) -> Option<LanguageServerBinary> {
+        None
+    }
+
+    fn can_be_reinstalled(&self) -> bool {
+        false
+    }
+
+    async fn installation_test_binary(&self, _: PathBuf) -> Option<LanguageServerBinary> {
+        None
+    }
+}
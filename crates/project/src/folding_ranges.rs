@@ -0,0 +1,70 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use gpui::{Model, ModelContext, Task};
+use language::{Anchor, Buffer, FoldingRange};
+use text::BufferId;
+use util::TryFutureExt;
+
+use crate::{lsp_command::GetFoldingRanges, LanguageServerToQuery, Project};
+
+/// How long to wait after a buffer changes before requesting its folding ranges.
+const FOLDING_RANGES_DEBOUNCE: Duration = Duration::from_millis(250);
+
+impl Project {
+    /// Requests the buffer's folding ranges from its primary language server
+    /// after a short delay. While the server doesn't provide any, the buffer
+    /// falls back to the folds captured by its language's `folds.scm` query.
+    pub(crate) fn refresh_folding_ranges(
+        &mut self,
+        buffer: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let task = cx.spawn(move |this, mut cx| {
+            async move {
+                cx.background_executor()
+                    .timer(FOLDING_RANGES_DEBOUNCE)
+                    .await;
+                let ranges = this
+                    .update(&mut cx, |this, cx| this.folding_ranges(&buffer, cx))?
+                    .await?;
+                buffer.update(&mut cx, |buffer, cx| {
+                    buffer.set_lsp_folding_ranges(ranges.map(Arc::from), cx)
+                })
+            }
+            .log_err()
+        });
+        self.folding_range_tasks.insert(buffer_id, task);
+    }
+
+    pub(crate) fn refresh_all_folding_ranges(&mut self, cx: &mut ModelContext<Self>) {
+        let buffers = self
+            .opened_buffers
+            .values()
+            .filter_map(|buffer| buffer.upgrade())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.refresh_folding_ranges(buffer, cx);
+        }
+    }
+
+    /// Returns the folding ranges reported by the buffer's primary language
+    /// server, or `None` if the server doesn't support folding ranges.
+    pub fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<Vec<FoldingRange<Anchor>>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    pub(crate) fn forget_folding_ranges(&mut self, buffer_id: BufferId) {
+        self.folding_range_tasks.remove(&buffer_id);
+    }
+}
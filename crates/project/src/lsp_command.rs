@@ -1,7 +1,8 @@
 use crate::{
-    DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint, InlayHintLabel,
+    DocumentHighlight, HierarchyItem, Hover, HoverBlock, HoverBlockKind, InlayHint, InlayHintLabel,
     InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location, LocationLink,
-    MarkupContent, Project, ProjectTransaction, ResolveState,
+    MarkupContent, ParameterInformation, Project, ProjectTransaction, ResolveState, SignatureHelp,
+    SignatureInformation,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -13,14 +14,14 @@ use language::{
     point_from_lsp, point_to_lsp, prepare_completion_documentation,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    CodeAction, Completion, OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction,
-    Unclipped,
+    CodeAction, Completion, FoldKind, FoldingRange, OffsetRangeExt, Point, PointUtf16, ToOffset,
+    ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     CompletionListItemDefaultsEditRange, DocumentHighlightKind, LanguageServer, LanguageServerId,
     OneOf, ServerCapabilities,
 };
-use std::{cmp::Reverse, ops::Range, path::Path, sync::Arc};
+use std::{cmp::Reverse, mem, ops::Range, path::Path, sync::Arc};
 use text::{BufferId, LineEnding};
 
 pub fn lsp_formatting_options(tab_size: u32) -> lsp::FormattingOptions {
@@ -105,6 +106,10 @@ pub(crate) struct GetTypeDefinition {
     pub position: PointUtf16,
 }
 
+pub(crate) struct GetImplementation {
+    pub position: PointUtf16,
+}
+
 pub(crate) struct GetReferences {
     pub position: PointUtf16,
 }
@@ -117,6 +122,12 @@ pub(crate) struct GetHover {
     pub position: PointUtf16,
 }
 
+pub(crate) struct GetSignatureHelp {
+    pub position: PointUtf16,
+    pub trigger_character: Option<String>,
+    pub is_retrigger: bool,
+}
+
 pub(crate) struct GetCompletions {
     pub position: PointUtf16,
 }
@@ -136,6 +147,32 @@ pub(crate) struct InlayHints {
     pub range: Range<Anchor>,
 }
 
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetIncomingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+pub(crate) struct GetOutgoingCalls {
+    pub item: lsp::CallHierarchyItem,
+}
+
+pub(crate) struct GetSupertypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+pub(crate) struct GetSubtypes {
+    pub item: lsp::TypeHierarchyItem,
+}
+
+pub(crate) struct GetFoldingRanges;
+
 pub(crate) struct FormattingOptions {
     tab_size: u32,
 }
@@ -592,6 +629,107 @@ impl LspCommand for GetTypeDefinition {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetImplementation {
+    type Response = Vec<LocationLink>;
+    type LspRequest = lsp::request::GotoImplementation;
+    type ProtoRequest = proto::GetImplementation;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.implementation_provider {
+            None => false,
+            Some(lsp::ImplementationProviderCapability::Simple(false)) => false,
+            _ => true,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::request::GotoImplementationParams {
+        lsp::request::GotoImplementationParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::request::GotoImplementationResponse>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<LocationLink>> {
+        location_links_from_lsp(message, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetImplementation {
+        proto::GetImplementation {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetImplementation,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<LocationLink>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetImplementationResponse {
+        let links = location_links_to_proto(response, project, peer_id, cx);
+        proto::GetImplementationResponse { links }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetImplementationResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<LocationLink>> {
+        location_links_from_proto(message.links, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetImplementation) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
 fn language_server_for_buffer(
     project: &Model<Project>,
     buffer: &Model<Buffer>,
@@ -1320,6 +1458,296 @@ impl LspCommand for GetHover {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetSignatureHelp {
+    type Response = Option<SignatureHelp>;
+    type LspRequest = lsp::request::SignatureHelpRequest;
+    type ProtoRequest = proto::GetSignatureHelp;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        capabilities.signature_help_provider.is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SignatureHelpParams {
+        let trigger_kind = if self.trigger_character.is_some() {
+            lsp::SignatureHelpTriggerKind::TRIGGER_CHARACTER
+        } else if self.is_retrigger {
+            lsp::SignatureHelpTriggerKind::CONTENT_CHANGE
+        } else {
+            lsp::SignatureHelpTriggerKind::INVOKED
+        };
+        lsp::SignatureHelpParams {
+            context: Some(lsp::SignatureHelpContext {
+                trigger_kind,
+                trigger_character: self.trigger_character.clone(),
+                is_retrigger: self.is_retrigger,
+                active_signature_help: None,
+            }),
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SignatureHelp>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        let Some(message) = message else {
+            return Ok(None);
+        };
+
+        fn markup_content_from_lsp(documentation: lsp::Documentation) -> Option<MarkupContent> {
+            let content = match documentation {
+                lsp::Documentation::String(value) => MarkupContent {
+                    kind: HoverBlockKind::PlainText,
+                    value,
+                },
+                lsp::Documentation::MarkupContent(markup_content) => MarkupContent {
+                    kind: match markup_content.kind {
+                        lsp::MarkupKind::PlainText => HoverBlockKind::PlainText,
+                        lsp::MarkupKind::Markdown => HoverBlockKind::Markdown,
+                    },
+                    value: markup_content.value,
+                },
+            };
+            if content.value.trim().is_empty() {
+                None
+            } else {
+                Some(content)
+            }
+        }
+
+        let signatures = message
+            .signatures
+            .into_iter()
+            .map(|signature| {
+                let parameters = signature
+                    .parameters
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|parameter| {
+                        let label_range = match parameter.label {
+                            lsp::ParameterLabel::Simple(label) => {
+                                let start = signature.label.find(&label)?;
+                                start..start + label.len()
+                            }
+                            lsp::ParameterLabel::LabelOffsets([start, end]) => {
+                                utf16_range_to_byte_range(&signature.label, start, end)?
+                            }
+                        };
+                        Some(ParameterInformation {
+                            label_range,
+                            documentation: parameter
+                                .documentation
+                                .and_then(markup_content_from_lsp),
+                        })
+                    })
+                    .collect();
+                SignatureInformation {
+                    documentation: signature.documentation.and_then(markup_content_from_lsp),
+                    label: signature.label,
+                    parameters,
+                    active_parameter: signature.active_parameter.map(|ix| ix as usize),
+                }
+            })
+            .collect::<Vec<_>>();
+        if signatures.is_empty() {
+            return Ok(None);
+        }
+
+        let active_signature = message
+            .active_signature
+            .map(|ix| ix as usize)
+            .filter(|ix| *ix < signatures.len())
+            .unwrap_or(0);
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature,
+            active_parameter: message.active_parameter.map(|ix| ix as usize),
+        }))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> Self::ProtoRequest {
+        proto::GetSignatureHelp {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            trigger_character: self.trigger_character.clone(),
+            is_retrigger: self.is_retrigger,
+            version: serialize_version(&buffer.version),
+        }
+    }
+
+    async fn from_proto(
+        message: Self::ProtoRequest,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+            trigger_character: message.trigger_character,
+            is_retrigger: message.is_retrigger,
+        })
+    }
+
+    fn response_to_proto(
+        response: Self::Response,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSignatureHelpResponse {
+        fn serialize_documentation(documentation: MarkupContent) -> proto::HoverBlock {
+            proto::HoverBlock {
+                text: documentation.value,
+                is_markdown: documentation.kind == HoverBlockKind::Markdown,
+                language: None,
+            }
+        }
+
+        let Some(response) = response else {
+            return proto::GetSignatureHelpResponse {
+                signatures: Vec::new(),
+                active_signature: 0,
+                active_parameter: None,
+            };
+        };
+
+        proto::GetSignatureHelpResponse {
+            signatures: response
+                .signatures
+                .into_iter()
+                .map(|signature| proto::SignatureInformation {
+                    label: signature.label,
+                    documentation: signature.documentation.map(serialize_documentation),
+                    parameters: signature
+                        .parameters
+                        .into_iter()
+                        .map(|parameter| proto::ParameterInformation {
+                            label_start: parameter.label_range.start as u32,
+                            label_end: parameter.label_range.end as u32,
+                            documentation: parameter.documentation.map(serialize_documentation),
+                        })
+                        .collect(),
+                    active_parameter: signature.active_parameter.map(|ix| ix as u32),
+                })
+                .collect(),
+            active_signature: response.active_signature as u32,
+            active_parameter: response.active_parameter.map(|ix| ix as u32),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSignatureHelpResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        fn deserialize_documentation(block: proto::HoverBlock) -> MarkupContent {
+            MarkupContent {
+                kind: if block.is_markdown {
+                    HoverBlockKind::Markdown
+                } else {
+                    HoverBlockKind::PlainText
+                },
+                value: block.text,
+            }
+        }
+
+        if message.signatures.is_empty() {
+            return Ok(None);
+        }
+
+        let signatures = message
+            .signatures
+            .into_iter()
+            .map(|signature| {
+                let parameters = signature
+                    .parameters
+                    .into_iter()
+                    .filter(|parameter| {
+                        parameter.label_start <= parameter.label_end
+                            && signature
+                                .label
+                                .is_char_boundary(parameter.label_start as usize)
+                            && signature
+                                .label
+                                .is_char_boundary(parameter.label_end as usize)
+                    })
+                    .map(|parameter| ParameterInformation {
+                        label_range: parameter.label_start as usize..parameter.label_end as usize,
+                        documentation: parameter.documentation.map(deserialize_documentation),
+                    })
+                    .collect();
+                SignatureInformation {
+                    label: signature.label,
+                    documentation: signature.documentation.map(deserialize_documentation),
+                    parameters,
+                    active_parameter: signature.active_parameter.map(|ix| ix as usize),
+                }
+            })
+            .collect::<Vec<_>>();
+        let active_signature = (message.active_signature as usize).min(signatures.len() - 1);
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature,
+            active_parameter: message.active_parameter.map(|ix| ix as usize),
+        }))
+    }
+
+    fn buffer_id_from_proto(message: &Self::ProtoRequest) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+/// Converts a range of UTF-16 code units within `text` into a byte range, returning `None`
+/// if either end falls outside of the text or in the middle of a character.
+fn utf16_range_to_byte_range(text: &str, start: u32, end: u32) -> Option<Range<usize>> {
+    let mut utf16_offset = 0;
+    let mut byte_start = None;
+    let mut byte_end = None;
+    for (byte_offset, ch) in text.char_indices().chain([(text.len(), '\0')]) {
+        if utf16_offset == start as usize {
+            byte_start = Some(byte_offset);
+        }
+        if utf16_offset == end as usize {
+            byte_end = Some(byte_offset);
+            break;
+        }
+        utf16_offset += ch.len_utf16();
+    }
+    let range = byte_start?..byte_end?;
+    (range.start <= range.end).then_some(range)
+}
+
 #[async_trait(?Send)]
 impl LspCommand for GetCompletions {
     type Response = Vec<Completion>;
@@ -2369,3 +2797,912 @@ impl LspCommand for InlayHints {
         BufferId::new(message.buffer_id)
     }
 }
+
+/// A call or type hierarchy item reported by a language server, before its ranges have been
+/// resolved into buffer anchors.
+struct UnresolvedHierarchyItem {
+    name: String,
+    kind: lsp::SymbolKind,
+    detail: Option<String>,
+    uri: lsp::Url,
+    range: lsp::Range,
+    selection_range: lsp::Range,
+    call_site_ranges: Vec<lsp::Range>,
+    /// Whether `call_site_ranges` refer to this item's document (incoming calls) or to the
+    /// document of the item being expanded (outgoing calls).
+    call_sites_in_item: bool,
+    lsp_item: serde_json::Value,
+}
+
+impl UnresolvedHierarchyItem {
+    fn from_call_hierarchy_item(item: lsp::CallHierarchyItem) -> Result<Self> {
+        Ok(Self {
+            lsp_item: serde_json::to_value(&item)?,
+            name: item.name,
+            kind: item.kind,
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range,
+            selection_range: item.selection_range,
+            call_site_ranges: Vec::new(),
+            call_sites_in_item: false,
+        })
+    }
+
+    fn from_type_hierarchy_item(item: lsp::TypeHierarchyItem) -> Result<Self> {
+        Ok(Self {
+            lsp_item: serde_json::to_value(&item)?,
+            name: item.name,
+            kind: item.kind,
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range,
+            selection_range: item.selection_range,
+            call_site_ranges: Vec::new(),
+            call_sites_in_item: false,
+        })
+    }
+}
+
+async fn hierarchy_items_from_lsp(
+    items: Vec<UnresolvedHierarchyItem>,
+    project: Model<Project>,
+    buffer: Model<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<HierarchyItem>> {
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (lsp_adapter, language_server) =
+        language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+    let mut resolved_items = Vec::with_capacity(items.len());
+    for item in items {
+        let item_buffer = project
+            .update(&mut cx, |this, cx| {
+                this.open_local_buffer_via_lsp(
+                    item.uri.clone(),
+                    language_server.server_id(),
+                    lsp_adapter.name.clone(),
+                    cx,
+                )
+            })?
+            .await?;
+
+        cx.update(|cx| {
+            fn anchor_range_from_lsp(buffer: &Buffer, range: lsp::Range) -> Range<Anchor> {
+                let start = buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+                let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+                buffer.anchor_after(start)..buffer.anchor_before(end)
+            }
+
+            let call_sites_buffer = if item.call_sites_in_item {
+                item_buffer.clone()
+            } else {
+                buffer.clone()
+            };
+            let call_sites = item
+                .call_site_ranges
+                .iter()
+                .map(|range| Location {
+                    range: anchor_range_from_lsp(call_sites_buffer.read(cx), *range),
+                    buffer: call_sites_buffer.clone(),
+                })
+                .collect();
+
+            let snapshot = item_buffer.read(cx);
+            resolved_items.push(HierarchyItem {
+                name: item.name,
+                kind: item.kind,
+                detail: item.detail,
+                selection_range: anchor_range_from_lsp(snapshot, item.selection_range),
+                location: Location {
+                    range: anchor_range_from_lsp(snapshot, item.range),
+                    buffer: item_buffer.clone(),
+                },
+                call_sites,
+                lsp_item: item.lsp_item,
+            });
+        })?;
+    }
+
+    Ok(resolved_items)
+}
+
+fn serialize_location(
+    location: &Location,
+    project: &mut Project,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::Location {
+    let buffer_id = project.create_buffer_for_peer(&location.buffer, peer_id, cx);
+    proto::Location {
+        start: Some(serialize_anchor(&location.range.start)),
+        end: Some(serialize_anchor(&location.range.end)),
+        buffer_id: buffer_id.into(),
+    }
+}
+
+async fn deserialize_location(
+    location: proto::Location,
+    project: &Model<Project>,
+    cx: &mut AsyncAppContext,
+) -> Result<Location> {
+    let buffer_id = BufferId::new(location.buffer_id)?;
+    let buffer = project
+        .update(cx, |this, cx| this.wait_for_remote_buffer(buffer_id, cx))?
+        .await?;
+    let start = location
+        .start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location start"))?;
+    let end = location
+        .end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location end"))?;
+    buffer
+        .update(cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+        .await?;
+    Ok(Location {
+        buffer,
+        range: start..end,
+    })
+}
+
+fn hierarchy_items_to_proto(
+    items: Vec<HierarchyItem>,
+    project: &mut Project,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::HierarchyItemsResponse {
+    let items = items
+        .into_iter()
+        .map(|item| proto::HierarchyItem {
+            name: item.name,
+            kind: unsafe { mem::transmute(item.kind) },
+            detail: item.detail,
+            location: Some(serialize_location(&item.location, project, peer_id, cx)),
+            selection_start: Some(serialize_anchor(&item.selection_range.start)),
+            selection_end: Some(serialize_anchor(&item.selection_range.end)),
+            call_sites: item
+                .call_sites
+                .iter()
+                .map(|location| serialize_location(location, project, peer_id, cx))
+                .collect(),
+            lsp_item: serde_json::to_vec(&item.lsp_item).unwrap_or_default(),
+        })
+        .collect();
+    proto::HierarchyItemsResponse { items }
+}
+
+async fn hierarchy_items_from_proto(
+    message: proto::HierarchyItemsResponse,
+    project: Model<Project>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<HierarchyItem>> {
+    let mut items = Vec::with_capacity(message.items.len());
+    for item in message.items {
+        let location = deserialize_location(
+            item.location
+                .ok_or_else(|| anyhow!("missing item location"))?,
+            &project,
+            &mut cx,
+        )
+        .await?;
+        let selection_start = item
+            .selection_start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing selection start"))?;
+        let selection_end = item
+            .selection_end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("missing selection end"))?;
+        let mut call_sites = Vec::with_capacity(item.call_sites.len());
+        for call_site in item.call_sites {
+            call_sites.push(deserialize_location(call_site, &project, &mut cx).await?);
+        }
+        items.push(HierarchyItem {
+            name: item.name,
+            kind: unsafe { mem::transmute(item.kind) },
+            detail: item.detail,
+            location,
+            selection_range: selection_start..selection_end,
+            call_sites,
+            lsp_item: serde_json::from_slice(&item.lsp_item)?,
+        });
+    }
+    Ok(items)
+}
+
+async fn wait_for_position(
+    position: Option<proto::Anchor>,
+    version: &[proto::VectorClockEntry],
+    buffer: &Model<Buffer>,
+    cx: &mut AsyncAppContext,
+) -> Result<PointUtf16> {
+    let position = position
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("invalid position"))?;
+    buffer
+        .update(cx, |buffer, _| {
+            buffer.wait_for_version(deserialize_version(version))
+        })?
+        .await?;
+    buffer.update(cx, |buffer, _| position.to_point_utf16(buffer))
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.call_hierarchy_provider {
+            None => false,
+            Some(lsp::CallHierarchyServerCapability::Simple(false)) => false,
+            _ => true,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(UnresolvedHierarchyItem::from_call_hierarchy_item)
+            .collect::<Result<_>>()?;
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            position: wait_for_position(message.position, &message.version, &buffer, &mut cx)
+                .await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(UnresolvedHierarchyItem::from_type_hierarchy_item)
+            .collect::<Result<_>>()?;
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            position: wait_for_position(message.position, &message.version, &buffer, &mut cx)
+                .await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyIncomingCallsParams {
+        lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| {
+                let mut item = UnresolvedHierarchyItem::from_call_hierarchy_item(call.from)?;
+                item.call_site_ranges = call.from_ranges;
+                item.call_sites_in_item = true;
+                Ok(item)
+            })
+            .collect::<Result<_>>()?;
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyOutgoingCallsParams {
+        lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| {
+                let mut item = UnresolvedHierarchyItem::from_call_hierarchy_item(call.to)?;
+                item.call_site_ranges = call.from_ranges;
+                item.call_sites_in_item = false;
+                Ok(item)
+            })
+            .collect::<Result<_>>()?;
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySupertypesParams {
+        lsp::TypeHierarchySupertypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(UnresolvedHierarchyItem::from_type_hierarchy_item)
+            .collect::<Result<_>>()?;
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<HierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySubtypesParams {
+        lsp::TypeHierarchySubtypesParams {
+            item: self.item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        let items = message
+            .unwrap_or_default()
+            .into_iter()
+            .map(UnresolvedHierarchyItem::from_type_hierarchy_item)
+            .collect::<Result<_>>()?;
+        hierarchy_items_from_lsp(items, project, buffer, server_id, cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.item).unwrap_or_default(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<HierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::HierarchyItemsResponse {
+        hierarchy_items_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::HierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<HierarchyItem>> {
+        hierarchy_items_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Option<Vec<FoldingRange<Anchor>>>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(enabled)) => *enabled,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::FoldingRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Option<Vec<FoldingRange<Anchor>>>> {
+        let Some(mut lsp_ranges) = message else {
+            return Ok(None);
+        };
+        lsp_ranges.sort_by_key(|range| (range.start_line, Reverse(range.end_line)));
+        buffer.update(&mut cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            Some(
+                lsp_ranges
+                    .into_iter()
+                    .filter_map(|lsp_range| {
+                        let start_row = lsp_range.start_line;
+                        let end_row = lsp_range.end_line.min(max_row);
+                        if end_row <= start_row {
+                            return None;
+                        }
+
+                        let start = Point::new(start_row, buffer.line_len(start_row));
+                        let end = Point::new(end_row, buffer.line_len(end_row));
+                        Some(FoldingRange {
+                            range: buffer.anchor_before(start)..buffer.anchor_before(end),
+                            kind: lsp_range.kind.map(|kind| match kind {
+                                lsp::FoldingRangeKind::Comment => FoldKind::Comment,
+                                lsp::FoldingRangeKind::Imports => FoldKind::Imports,
+                                lsp::FoldingRangeKind::Region => FoldKind::Region,
+                            }),
+                        })
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Option<Vec<FoldingRange<Anchor>>>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            supported: response.is_some(),
+            ranges: response
+                .unwrap_or_default()
+                .into_iter()
+                .map(|fold| proto::FoldingRange {
+                    start: Some(serialize_anchor(&fold.range.start)),
+                    end: Some(serialize_anchor(&fold.range.end)),
+                    kind: fold.kind.map(|kind| match kind {
+                        FoldKind::Comment => proto::folding_range::Kind::Comment.into(),
+                        FoldKind::Imports => proto::folding_range::Kind::Imports.into(),
+                        FoldKind::Region => proto::folding_range::Kind::Region.into(),
+                    }),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Option<Vec<FoldingRange<Anchor>>>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        if !message.supported {
+            return Ok(None);
+        }
+
+        message
+            .ranges
+            .into_iter()
+            .map(|fold| {
+                let start = fold
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = fold
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                let kind = fold.kind.and_then(proto::folding_range::Kind::from_i32);
+                Ok(FoldingRange {
+                    range: start..end,
+                    kind: kind.map(|kind| match kind {
+                        proto::folding_range::Kind::Comment => FoldKind::Comment,
+                        proto::folding_range::Kind::Imports => FoldKind::Imports,
+                        proto::folding_range::Kind::Region => FoldKind::Region,
+                    }),
+                })
+            })
+            .collect::<Result<_>>()
+            .map(Some)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
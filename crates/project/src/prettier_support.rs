@@ -29,10 +29,14 @@ pub fn prettier_plugins_for_language(
     language: &Language,
     language_settings: &LanguageSettings,
 ) -> Option<HashSet<&'static str>> {
-    match &language_settings.formatter {
-        Formatter::Prettier { .. } | Formatter::Auto => {}
-        Formatter::LanguageServer | Formatter::External { .. } => return None,
-    };
+    let uses_prettier = language_settings
+        .formatter
+        .steps()
+        .into_iter()
+        .any(|formatter| matches!(formatter, Formatter::Prettier | Formatter::Auto));
+    if !uses_prettier {
+        return None;
+    }
     let mut prettier_plugins = None;
     if language.prettier_parser_name().is_some() {
         prettier_plugins
@@ -1,9 +1,13 @@
+mod custom_lsp_adapter;
+mod folding_ranges;
 mod ignore;
 pub mod lsp_command;
 pub mod lsp_ext_command;
 mod prettier_support;
 pub mod project_settings;
 pub mod search;
+mod semantic_tokens;
+pub mod task_inventory;
 pub mod terminals;
 pub mod worktree;
 
@@ -17,6 +21,7 @@ use client::{proto, Client, Collaborator, TypedEnvelope, UserStore};
 use clock::ReplicaId;
 use collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque};
 use copilot::Copilot;
+use custom_lsp_adapter::CustomLanguageServer;
 use futures::{
     channel::{
         mpsc::{self, UnboundedReceiver},
@@ -33,7 +38,9 @@ use gpui::{
 };
 use itertools::Itertools;
 use language::{
-    language_settings::{language_settings, FormatOnSave, Formatter, InlayHintKind},
+    language_settings::{
+        language_settings, FormatOnSave, Formatter, InlayHintKind, LanguageSettings,
+    },
     markdown, point_to_lsp,
     proto::{
         deserialize_anchor, deserialize_fingerprint, deserialize_line_ending, deserialize_version,
@@ -58,8 +65,9 @@ use prettier_support::{DefaultPrettier, PrettierInstance};
 use project_settings::{LspSettings, ProjectSettings};
 use rand::prelude::*;
 use search::SearchQuery;
+use semantic_tokens::BufferSemanticTokens;
 use serde::Serialize;
-use settings::{Settings, SettingsStore};
+use settings::{Settings, SettingsStore, EDITORCONFIG_FILE_NAME};
 use sha2::{Digest, Sha256};
 use similar::{ChangeTag, TextDiff};
 use smol::channel::{Receiver, Sender};
@@ -67,6 +75,7 @@ use smol::lock::Semaphore;
 use std::{
     cmp::{self, Ordering},
     convert::TryInto,
+    ffi::OsStr,
     hash::Hash,
     mem,
     num::NonZeroU32,
@@ -80,16 +89,21 @@ use std::{
     },
     time::{Duration, Instant},
 };
+use task_inventory::Inventory;
 use terminals::Terminals;
-use text::{Anchor, BufferId};
+use text::{Anchor, BufferId, TransactionId};
 use util::{
-    debug_panic, defer, http::HttpClient, merge_json_value_into,
-    paths::LOCAL_SETTINGS_RELATIVE_PATH, post_inc, ResultExt, TryFutureExt as _,
+    debug_panic, defer,
+    http::HttpClient,
+    merge_json_value_into,
+    paths::{LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH},
+    post_inc, ResultExt, TryFutureExt as _,
 };
 
 pub use fs::*;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
+pub use semantic_tokens::SemanticToken;
 pub use worktree::*;
 
 const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
@@ -123,6 +137,8 @@ pub struct Project {
     next_buffer_id: BufferId,
     opened_buffer: (watch::Sender<()>, watch::Receiver<()>),
     shared_buffers: HashMap<proto::PeerId, HashSet<BufferId>>,
+    /// The blame of each file in HEAD, reused until HEAD or the file's repository changes.
+    head_blames: HashMap<(WorktreeId, Arc<Path>), Arc<repository::HeadBlame>>,
     #[allow(clippy::type_complexity)]
     loading_buffers_by_path: HashMap<
         ProjectPath,
@@ -139,15 +155,23 @@ pub struct Project {
     incomplete_remote_buffers: HashMap<BufferId, Option<Model<Buffer>>>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
     buffers_being_formatted: HashSet<BufferId>,
+    buffer_semantic_tokens: HashMap<BufferId, BufferSemanticTokens>,
+    /// The paths for which the settings store resolves `.editorconfig` properties
+    /// on behalf of each buffer, keyed by the settings store's worktree id.
+    buffer_editorconfig_paths: HashMap<BufferId, (usize, Arc<Path>)>,
+    semantic_token_tasks: HashMap<BufferId, Task<Option<()>>>,
+    folding_range_tasks: HashMap<BufferId, Task<Option<()>>>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
     git_diff_debouncer: DelayedDebounced,
     nonce: u128,
     _maintain_buffer_languages: Task<()>,
     _maintain_workspace_config: Task<Result<()>>,
     terminals: Terminals,
+    task_inventory: Model<Inventory>,
     copilot_lsp_subscription: Option<gpui::Subscription>,
     copilot_log_subscription: Option<lsp::Subscription>,
     current_lsp_settings: HashMap<Arc<str>, LspSettings>,
+    custom_language_servers: Vec<CustomLanguageServer>,
     node: Option<Arc<dyn NodeRuntime>>,
     default_prettier: DefaultPrettier,
     prettiers_per_worktree: HashMap<WorktreeId, HashSet<Option<PathBuf>>>,
@@ -309,6 +333,9 @@ pub struct LanguageServerStatus {
     pub pending_work: BTreeMap<String, LanguageServerProgress>,
     pub has_pending_diagnostic_updates: bool,
     progress_tokens: HashSet<String>,
+    /// The characters that trigger or retrigger signature help, which guests
+    /// can't read from the server's capabilities.
+    signature_help_trigger_characters: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -445,6 +472,81 @@ impl Hover {
     }
 }
 
+/// The relationship explored by a call or type hierarchy request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HierarchyKind {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyKind {
+    pub fn is_call_hierarchy(&self) -> bool {
+        matches!(self, Self::IncomingCalls | Self::OutgoingCalls)
+    }
+}
+
+/// A symbol in a call or type hierarchy.
+#[derive(Clone, Debug)]
+pub struct HierarchyItem {
+    pub name: String,
+    pub kind: lsp::SymbolKind,
+    pub detail: Option<String>,
+    /// The full extent of the symbol, e.g. a function including its body.
+    pub location: Location,
+    /// The range to reveal when the symbol is selected, e.g. the function's name.
+    pub selection_range: Range<language::Anchor>,
+    /// For call hierarchies, the calls that connect this item to the item it was expanded from.
+    pub call_sites: Vec<Location>,
+    /// The item as reported by the language server, sent back verbatim when expanding it.
+    pub(crate) lsp_item: serde_json::Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureHelp {
+    pub signatures: Vec<SignatureInformation>,
+    pub active_signature: usize,
+    pub active_parameter: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureInformation {
+    pub label: String,
+    pub documentation: Option<MarkupContent>,
+    pub parameters: Vec<ParameterInformation>,
+    pub active_parameter: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParameterInformation {
+    /// The byte range of this parameter within its signature's label.
+    pub label_range: Range<usize>,
+    pub documentation: Option<MarkupContent>,
+}
+
+impl SignatureHelp {
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Returns the parameter to highlight in the given signature, preferring the
+    /// signature's own active parameter over the one reported for the whole response.
+    pub fn active_parameter_for(&self, signature_ix: usize) -> Option<&ParameterInformation> {
+        let parameter_ix = self.active_parameter_ix_for(signature_ix)?;
+        self.signatures
+            .get(signature_ix)?
+            .parameters
+            .get(parameter_ix)
+    }
+
+    /// Returns the index of the parameter returned by [`Self::active_parameter_for`].
+    pub fn active_parameter_ix_for(&self, signature_ix: usize) -> Option<usize> {
+        let signature = self.signatures.get(signature_ix)?;
+        signature.active_parameter.or(self.active_parameter)
+    }
+}
+
 #[derive(Default)]
 pub struct ProjectTransaction(pub HashMap<Model<Buffer>, language::Transaction>);
 
@@ -596,14 +698,25 @@ impl Project {
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_model_request_handler(Self::handle_get_semantic_tokens);
+        client.add_model_message_handler(Self::handle_refresh_semantic_tokens);
         client.add_model_request_handler(Self::handle_reload_buffers);
         client.add_model_request_handler(Self::handle_synchronize_buffers);
         client.add_model_request_handler(Self::handle_format_buffers);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCodeActions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCompletions>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetHover>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSignatureHelp>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetImplementation>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetReferences>);
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
@@ -615,6 +728,13 @@ impl Project {
         client.add_model_request_handler(Self::handle_open_buffer_by_path);
         client.add_model_request_handler(Self::handle_save_buffer);
         client.add_model_message_handler(Self::handle_update_diff_base);
+        client.add_model_request_handler(Self::handle_set_index_text);
+        client.add_model_request_handler(Self::handle_load_head_text);
+        client.add_model_request_handler(Self::handle_blame_buffer);
+        client.add_model_message_handler(Self::handle_update_terminal);
+        client.add_model_message_handler(Self::handle_unshare_terminal);
+        client.add_model_message_handler(Self::handle_set_terminal_writable);
+        client.add_model_request_handler(Self::handle_terminal_input);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
     }
 
@@ -639,6 +759,7 @@ impl Project {
                 next_buffer_id: BufferId::new(1).unwrap(),
                 opened_buffers: Default::default(),
                 shared_buffers: Default::default(),
+                head_blames: Default::default(),
                 incomplete_remote_buffers: Default::default(),
                 loading_buffers_by_path: Default::default(),
                 loading_local_worktrees: Default::default(),
@@ -669,15 +790,21 @@ impl Project {
                 language_server_statuses: Default::default(),
                 last_workspace_edits_by_language_server: Default::default(),
                 buffers_being_formatted: Default::default(),
+                buffer_semantic_tokens: Default::default(),
+                buffer_editorconfig_paths: Default::default(),
+                semantic_token_tasks: Default::default(),
+                folding_range_tasks: Default::default(),
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DelayedDebounced::new(),
                 nonce: StdRng::from_entropy().gen(),
-                terminals: Terminals {
-                    local_handles: Vec::new(),
-                },
+                terminals: Terminals::default(),
+                task_inventory: cx.new_model(|_| Inventory::default()),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                custom_language_servers: CustomLanguageServer::from_settings(
+                    &ProjectSettings::get_global(cx).lsp,
+                ),
                 node: Some(node),
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
@@ -725,6 +852,7 @@ impl Project {
                 next_buffer_id: BufferId::new(1).unwrap(),
                 opened_buffer: watch::channel(),
                 shared_buffers: Default::default(),
+                head_blames: Default::default(),
                 incomplete_remote_buffers: Default::default(),
                 loading_local_worktrees: Default::default(),
                 local_buffer_ids_by_path: Default::default(),
@@ -766,6 +894,8 @@ impl Project {
                                 pending_work: Default::default(),
                                 has_pending_diagnostic_updates: false,
                                 progress_tokens: Default::default(),
+                                signature_help_trigger_characters: server
+                                    .signature_help_trigger_characters,
                             },
                         )
                     })
@@ -773,16 +903,22 @@ impl Project {
                 last_workspace_edits_by_language_server: Default::default(),
                 opened_buffers: Default::default(),
                 buffers_being_formatted: Default::default(),
+                buffer_semantic_tokens: Default::default(),
+                buffer_editorconfig_paths: Default::default(),
+                semantic_token_tasks: Default::default(),
+                folding_range_tasks: Default::default(),
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DelayedDebounced::new(),
                 buffer_snapshots: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
-                terminals: Terminals {
-                    local_handles: Vec::new(),
-                },
+                terminals: Terminals::default(),
+                task_inventory: cx.new_model(|_| Inventory::default()),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                custom_language_servers: CustomLanguageServer::from_settings(
+                    &ProjectSettings::get_global(cx).lsp,
+                ),
                 node: None,
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
@@ -916,11 +1052,13 @@ impl Project {
 
         let new_lsp_settings = ProjectSettings::get_global(cx).lsp.clone();
         let current_lsp_settings = &self.current_lsp_settings;
+        let previous_custom_language_servers = mem::replace(
+            &mut self.custom_language_servers,
+            CustomLanguageServer::from_settings(&new_lsp_settings),
+        );
         for (worktree_id, started_lsp_name) in self.language_server_ids.keys() {
             let language = languages.iter().find_map(|l| {
-                let adapter = l
-                    .lsp_adapters()
-                    .iter()
+                let adapter = language_lsp_adapters(l, &previous_custom_language_servers)
                     .find(|adapter| &adapter.name == started_lsp_name)?;
                 Some((l, adapter))
             });
@@ -929,7 +1067,13 @@ impl Project {
                 let file = worktree.as_ref().and_then(|tree| {
                     tree.update(cx, |tree, cx| tree.root_file(cx).map(|f| f as _))
                 });
-                if !language_settings(Some(language), file.as_ref(), cx).enable_language_server {
+                let settings = language_settings(Some(language), file.as_ref(), cx);
+                if !settings.enable_language_server
+                    || !self
+                        .enabled_lsp_adapters(language, settings)
+                        .iter()
+                        .any(|adapter| &adapter.name == started_lsp_name)
+                {
                     language_servers_to_stop.push((*worktree_id, started_lsp_name.clone()));
                 } else if let Some(worktree) = worktree {
                     let server_name = &adapter.name.0;
@@ -983,6 +1127,8 @@ impl Project {
             self.restart_language_servers(worktree, language, cx);
         }
 
+        self.refresh_all_semantic_tokens(cx);
+
         if self.copilot_lsp_subscription.is_none() {
             if let Some(copilot) = Copilot::global(cx) {
                 for buffer in self.opened_buffers.values() {
@@ -1045,6 +1191,10 @@ impl Project {
         &self.fs
     }
 
+    pub fn task_inventory(&self) -> &Model<Inventory> {
+        &self.task_inventory
+    }
+
     pub fn remote_id(&self) -> Option<u64> {
         match self.client_state {
             ProjectClientState::Local => None,
@@ -1390,6 +1540,9 @@ impl Project {
                     server: Some(proto::LanguageServer {
                         id: server_id.0 as u64,
                         name: status.name.clone(),
+                        signature_help_trigger_characters: status
+                            .signature_help_trigger_characters
+                            .clone(),
                     }),
                 })
                 .log_err();
@@ -1398,7 +1551,8 @@ impl Project {
         let store = cx.global::<SettingsStore>();
         for worktree in self.worktrees() {
             let worktree_id = worktree.read(cx).id().to_proto();
-            for (path, content) in store.local_settings(worktree.entity_id().as_u64() as usize) {
+            let root_id = worktree.entity_id().as_u64() as usize;
+            for (path, content) in store.local_settings(root_id) {
                 self.client
                     .send(proto::UpdateWorktreeSettings {
                         project_id,
@@ -1408,6 +1562,16 @@ impl Project {
                     })
                     .log_err();
             }
+            for (path, content) in store.editorconfigs(root_id) {
+                self.client
+                    .send(proto::UpdateWorktreeSettings {
+                        project_id,
+                        worktree_id,
+                        path: path.join(EDITORCONFIG_FILE_NAME).to_string_lossy().into(),
+                        content: Some(content.to_string()),
+                    })
+                    .log_err();
+            }
         }
 
         let (updates_tx, mut updates_rx) = mpsc::unbounded();
@@ -1544,6 +1708,7 @@ impl Project {
                         pending_work: Default::default(),
                         has_pending_diagnostic_updates: false,
                         progress_tokens: Default::default(),
+                        signature_help_trigger_characters: server.signature_help_trigger_characters,
                     },
                 )
             })
@@ -1571,6 +1736,7 @@ impl Project {
             self.client_state = ProjectClientState::Local;
             self.collaborators.clear();
             self.shared_buffers.clear();
+            self.terminals.shared.clear();
             self.client_subscriptions.clear();
 
             for worktree_handle in self.worktrees.iter_mut() {
@@ -1985,6 +2151,146 @@ impl Project {
         })
     }
 
+    /// Writes the given text to the git index as the staged contents of the buffer's
+    /// file, or removes the file from the index if the text is `None`.
+    pub fn set_index_text(
+        &self,
+        buffer: &Model<Buffer>,
+        text: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if self.is_local() {
+            let Some((repo, relative_path)) = self.local_repo_for_buffer(buffer, cx) else {
+                return Task::ready(Err(anyhow!("buffer is not in a git repository")));
+            };
+            let buffer = buffer.clone();
+            let remote_id = self.remote_id();
+            let client = self.client.clone();
+            cx.spawn(move |_, mut cx| async move {
+                cx.background_executor()
+                    .spawn({
+                        let text = text.clone();
+                        async move { repo.lock().set_index_text(&relative_path, text) }
+                    })
+                    .await?;
+
+                // Update the diff base right away rather than waiting for the
+                // repository to be rescanned.
+                let buffer_id = buffer.update(&mut cx, |buffer, cx| {
+                    buffer.set_diff_base(text.clone(), cx);
+                    buffer.remote_id().into()
+                })?;
+                if let Some(project_id) = remote_id {
+                    client
+                        .send(proto::UpdateDiffBase {
+                            project_id,
+                            buffer_id,
+                            diff_base: text,
+                        })
+                        .log_err();
+                }
+                Ok(())
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let request = self.client.request(proto::SetIndexText {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                text,
+            });
+            cx.background_executor().spawn(async move {
+                request.await?;
+                Ok(())
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    /// Loads the contents of the buffer's file in the git HEAD commit.
+    pub fn load_head_text(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<String>>> {
+        if self.is_local() {
+            let Some((repo, relative_path)) = self.local_repo_for_buffer(buffer, cx) else {
+                return Task::ready(Err(anyhow!("buffer is not in a git repository")));
+            };
+            cx.background_executor()
+                .spawn(async move { repo.lock().load_head_text(&relative_path) })
+        } else if let Some(project_id) = self.remote_id() {
+            let request = self.client.request(proto::LoadHeadText {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+            });
+            cx.background_executor()
+                .spawn(async move { Ok(request.await?.text) })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    /// Computes git blame for the current contents of the buffer.
+    pub fn blame_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<repository::Blame>> {
+        if self.is_local() {
+            let Some((repo, relative_path)) = self.local_repo_for_buffer(buffer, cx) else {
+                return Task::ready(Err(anyhow!("buffer is not in a git repository")));
+            };
+            let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
+                return Task::ready(Err(anyhow!("buffer has no file")));
+            };
+            let key = (file.worktree.read(cx).id(), file.path.clone());
+            let head_blame = self.head_blames.get(&key).cloned();
+            let content = buffer.read(cx).as_rope().clone();
+            cx.spawn(|this, mut cx| async move {
+                let (blame, head_blame) = cx
+                    .background_executor()
+                    .spawn(async move {
+                        // Only walk the file's history again when HEAD has moved.
+                        let head_sha = repo.lock().head_sha();
+                        let head_blame = match head_blame {
+                            Some(head_blame) if head_blame.head_sha == head_sha => head_blame,
+                            _ => Arc::new(repo.lock().blame_head(&relative_path)?),
+                        };
+                        let blame = head_blame.blame_content(&content.to_string())?;
+                        anyhow::Ok((blame, head_blame))
+                    })
+                    .await?;
+                this.update(&mut cx, |this, _| {
+                    this.head_blames.insert(key, head_blame);
+                })?;
+                Ok(blame)
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let buffer = buffer.read(cx);
+            let request = self.client.request(proto::BlameBuffer {
+                project_id,
+                buffer_id: buffer.remote_id().into(),
+                version: serialize_version(&buffer.version()),
+            });
+            cx.background_executor()
+                .spawn(async move { Ok(deserialize_blame(request.await?)) })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    fn local_repo_for_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Option<(Arc<Mutex<dyn repository::GitRepository>>, PathBuf)> {
+        let file = File::from_dyn(buffer.read(cx).file())?;
+        let worktree = file.worktree.read(cx).as_local()?;
+        let (work_directory, repo) = worktree.local_repo_for_path(&file.path)?;
+        let relative_path = file.path.strip_prefix(&work_directory).ok()?;
+        Some((repo.repo_ptr.clone(), relative_path.to_path_buf()))
+    }
+
     pub fn get_open_buffer(
         &mut self,
         path: &ProjectPath,
@@ -2060,10 +2366,22 @@ impl Project {
             }
         }
 
+        self.update_buffer_editorconfig_path(buffer, cx);
         self.detect_language_for_buffer(buffer, cx);
         self.register_buffer_with_language_servers(buffer, cx);
         self.register_buffer_with_copilot(buffer, cx);
+        self.refresh_semantic_tokens(buffer.clone(), cx);
+        self.refresh_folding_ranges(buffer.clone(), cx);
         cx.observe_release(buffer, |this, buffer, cx| {
+            this.forget_semantic_tokens(buffer.remote_id());
+            this.forget_folding_ranges(buffer.remote_id());
+            if let Some((root_id, path)) =
+                this.buffer_editorconfig_paths.remove(&buffer.remote_id())
+            {
+                cx.update_global::<SettingsStore, _>(|store, _| {
+                    store.remove_editorconfig_file_path(root_id, &path)
+                });
+            }
             if let Some(file) = File::from_dyn(buffer.file()) {
                 if file.is_local() {
                     let uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
@@ -2086,6 +2404,36 @@ impl Project {
         Ok(())
     }
 
+    /// Makes the settings store resolve the `.editorconfig` properties for the
+    /// buffer's current file, so that its language settings include them.
+    fn update_buffer_editorconfig_path(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer = buffer.read(cx);
+        let buffer_id = buffer.remote_id();
+        let new_path = buffer
+            .file()
+            .map(|file| (file.worktree_id(), file.path().clone()));
+        let old_path = match new_path.clone() {
+            Some(new_path) => self.buffer_editorconfig_paths.insert(buffer_id, new_path),
+            None => self.buffer_editorconfig_paths.remove(&buffer_id),
+        };
+        if old_path == new_path {
+            return;
+        }
+
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            if let Some((root_id, path)) = old_path {
+                store.remove_editorconfig_file_path(root_id, &path);
+            }
+            if let Some((root_id, path)) = new_path {
+                store.add_editorconfig_file_path(root_id, path, cx);
+            }
+        });
+    }
+
     fn register_buffer_with_language_servers(
         &mut self,
         buffer_handle: &Model<Buffer>,
@@ -2114,7 +2462,7 @@ impl Project {
             }
 
             if let Some(language) = language {
-                for adapter in language.lsp_adapters() {
+                for adapter in self.all_lsp_adapters(&language) {
                     let language_id = adapter.language_ids.get(language.name().as_ref()).cloned();
                     let server = self
                         .language_server_ids
@@ -2185,9 +2533,14 @@ impl Project {
             let worktree_id = old_file.worktree_id(cx);
             let ids = &self.language_server_ids;
 
-            let language = buffer.language().cloned();
-            let adapters = language.iter().flat_map(|language| language.lsp_adapters());
-            for &server_id in adapters.flat_map(|a| ids.get(&(worktree_id, a.name.clone()))) {
+            let adapters = buffer
+                .language()
+                .map(|language| self.all_lsp_adapters(language))
+                .unwrap_or_default();
+            for &server_id in adapters
+                .iter()
+                .flat_map(|a| ids.get(&(worktree_id, a.name.clone())))
+            {
                 buffer.update_diagnostics(server_id, Default::default(), cx);
             }
 
@@ -2335,6 +2688,10 @@ impl Project {
         ) {
             self.request_buffer_diff_recalculation(&buffer, cx);
         }
+        if matches!(event, BufferEvent::Edited { .. } | BufferEvent::Reloaded) {
+            self.refresh_semantic_tokens(buffer.clone(), cx);
+            self.refresh_folding_ranges(buffer.clone(), cx);
+        }
 
         match event {
             BufferEvent::Operation(operation) => {
@@ -2507,6 +2864,7 @@ impl Project {
                 }
             }
             BufferEvent::FileHandleChanged => {
+                self.update_buffer_editorconfig_path(&buffer, cx);
                 let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
                     return None;
                 };
@@ -2793,11 +3151,46 @@ impl Project {
             return;
         }
 
-        for adapter in language.lsp_adapters() {
-            self.start_language_server(worktree, adapter.clone(), language.clone(), cx);
+        let adapters = self.enabled_lsp_adapters(&language, settings);
+        for adapter in adapters {
+            self.start_language_server(worktree, adapter, language.clone(), cx);
         }
     }
 
+    /// Returns the adapters of every language server that can run for the
+    /// given language, including the user-defined ones.
+    fn all_lsp_adapters(&self, language: &Arc<Language>) -> Vec<Arc<CachedLspAdapter>> {
+        language_lsp_adapters(language, &self.custom_language_servers)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the adapters of the language servers that the `language_servers`
+    /// setting enables for the given language, in the order they should be used.
+    fn enabled_lsp_adapters(
+        &self,
+        language: &Arc<Language>,
+        settings: &LanguageSettings,
+    ) -> Vec<Arc<CachedLspAdapter>> {
+        let available_adapters = self.all_lsp_adapters(language);
+        let mut available_names = Vec::new();
+        for adapter in &available_adapters {
+            if !available_names.contains(&adapter.name) {
+                available_names.push(adapter.name.clone());
+            }
+        }
+        settings
+            .customized_language_servers(&available_names)
+            .into_iter()
+            .filter_map(|name| {
+                available_adapters
+                    .iter()
+                    .find(|adapter| adapter.name == name)
+                    .cloned()
+            })
+            .collect()
+    }
+
     fn start_language_server(
         &mut self,
         worktree: &Model<Worktree>,
@@ -2817,6 +3210,13 @@ impl Project {
             return;
         }
 
+        let project_settings = ProjectSettings::get_global(cx);
+        let lsp = project_settings.lsp.get(&adapter.name.0);
+        let override_options = lsp.map(|s| s.initialization_options.clone()).flatten();
+        let binary_override = lsp
+            .and_then(|s| s.binary.as_ref())
+            .and_then(|binary| binary.language_server_binary());
+
         let stderr_capture = Arc::new(Mutex::new(Some(String::new())));
         let pending_server = match self.languages.create_pending_language_server(
             stderr_capture.clone(),
@@ -2824,16 +3224,13 @@ impl Project {
             adapter.clone(),
             Arc::clone(&worktree_path),
             ProjectLspAdapterDelegate::new(self, cx),
+            binary_override,
             cx,
         ) {
             Some(pending_server) => pending_server,
             None => return,
         };
 
-        let project_settings = ProjectSettings::get_global(cx);
-        let lsp = project_settings.lsp.get(&adapter.name.0);
-        let override_options = lsp.map(|s| s.initialization_options.clone()).flatten();
-
         let server_id = pending_server.server_id;
         let container_dir = pending_server.container_dir.clone();
         let state = LanguageServerState::Starting({
@@ -3147,6 +3544,27 @@ impl Project {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            project.refresh_all_semantic_tokens(cx);
+                            project.remote_id().map(|project_id| {
+                                project
+                                    .client
+                                    .send(proto::RefreshSemanticTokens { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         let disk_based_diagnostics_progress_token =
             adapter.disk_based_diagnostics_progress_token.clone();
 
@@ -3226,6 +3644,9 @@ impl Project {
                 pending_work: Default::default(),
                 has_pending_diagnostic_updates: false,
                 progress_tokens: Default::default(),
+                signature_help_trigger_characters: signature_help_trigger_characters(
+                    &language_server,
+                ),
             },
         );
 
@@ -3237,6 +3658,9 @@ impl Project {
                 server: Some(proto::LanguageServer {
                     id: server_id.0 as u64,
                     name: language_server.name().to_string(),
+                    signature_help_trigger_characters: signature_help_trigger_characters(
+                        &language_server,
+                    ),
                 }),
             })?;
         }
@@ -3255,7 +3679,8 @@ impl Project {
                 };
 
                 if file.worktree.read(cx).id() != key.0
-                    || !language.lsp_adapters().iter().any(|a| a.name == key.1)
+                    || !language_lsp_adapters(language, &self.custom_language_servers)
+                        .any(|a| a.name == key.1)
                 {
                     continue;
                 }
@@ -3310,6 +3735,8 @@ impl Project {
             }
         }
 
+        self.refresh_all_semantic_tokens(cx);
+        self.refresh_all_folding_ranges(cx);
         cx.notify();
         Ok(())
     }
@@ -3420,9 +3847,9 @@ impl Project {
     ) {
         let worktree_id = worktree.read(cx).id();
 
-        let stop_tasks = language
-            .lsp_adapters()
-            .iter()
+        let stop_tasks = self
+            .all_lsp_adapters(&language)
+            .into_iter()
             .map(|adapter| {
                 let stop_task = self.stop_language_server(worktree_id, adapter.name.clone(), cx);
                 (stop_task, adapter.name.clone())
@@ -4128,10 +4555,11 @@ impl Project {
                     let buffer = buffer_handle.read(cx);
                     let file = File::from_dyn(buffer.file())?;
                     let buffer_abs_path = file.as_local().map(|f| f.abs_path(cx));
-                    let server = self
-                        .primary_language_server_for_buffer(buffer, cx)
-                        .map(|s| s.1.clone());
-                    Some((buffer_handle, buffer_abs_path, server))
+                    let servers = self
+                        .language_servers_for_buffer(buffer, cx)
+                        .map(|(adapter, server)| (adapter.clone(), server.clone()))
+                        .collect::<Vec<_>>();
+                    Some((buffer_handle, buffer_abs_path, servers))
                 })
                 .collect::<Vec<_>>();
 
@@ -4161,7 +4589,7 @@ impl Project {
                 });
 
                 let mut project_transaction = ProjectTransaction::default();
-                for (buffer, buffer_abs_path, language_server) in &buffers_with_paths_and_servers {
+                for (buffer, buffer_abs_path, language_servers) in &buffers_with_paths_and_servers {
                     let settings = buffer.update(&mut cx, |buffer, cx| {
                         language_settings(buffer.language(), buffer.file(), cx).clone()
                     })?;
@@ -4192,118 +4620,156 @@ impl Project {
                         buffer.end_transaction(cx)
                     })?;
 
-                    // Apply language-specific formatting using either a language server
-                    // or external command.
-                    let mut format_operation = None;
-                    match (&settings.formatter, &settings.format_on_save) {
-                        (_, FormatOnSave::Off) if trigger == FormatTrigger::Save => {}
-
-                        (Formatter::LanguageServer, FormatOnSave::On | FormatOnSave::Off)
-                        | (_, FormatOnSave::LanguageServer) => {
-                            if let Some((language_server, buffer_abs_path)) =
-                                language_server.as_ref().zip(buffer_abs_path.as_ref())
-                            {
-                                format_operation = Some(FormatOperation::Lsp(
-                                    Self::format_via_lsp(
-                                        &project,
-                                        buffer,
-                                        buffer_abs_path,
-                                        language_server,
-                                        tab_size,
-                                        &mut cx,
-                                    )
-                                    .await
-                                    .context("failed to format via language server")?,
-                                ));
-                            }
+                    // Apply language-specific formatting, running each of the configured
+                    // formatters in order.
+                    let formatters = match (&settings.formatter, &settings.format_on_save) {
+                        (_, FormatOnSave::Off) if trigger == FormatTrigger::Save => Vec::new(),
+                        (_, FormatOnSave::LanguageServer) => {
+                            vec![Formatter::LanguageServer { name: None }]
+                        }
+                        (_, FormatOnSave::External { command, arguments }) => {
+                            vec![Formatter::External {
+                                command: command.clone(),
+                                arguments: arguments.clone(),
+                            }]
                         }
+                        (formatter, FormatOnSave::On | FormatOnSave::Off) => {
+                            formatter.steps().into_iter().cloned().collect()
+                        }
+                    };
 
-                        (
-                            Formatter::External { command, arguments },
-                            FormatOnSave::On | FormatOnSave::Off,
-                        )
-                        | (_, FormatOnSave::External { command, arguments }) => {
-                            if let Some(buffer_abs_path) = buffer_abs_path {
-                                format_operation = Self::format_via_external_command(
-                                    buffer,
-                                    buffer_abs_path,
-                                    command,
-                                    arguments,
-                                    &mut cx,
-                                )
-                                .await
-                                .context(format!(
-                                    "failed to format via external command {:?}",
-                                    command
-                                ))?
-                                .map(FormatOperation::External);
+                    let primary_language_server =
+                        language_servers.first().map(|(_, server)| server);
+                    let mut formatting_transaction_id = whitespace_transaction_id;
+                    'formatters: for formatter in &formatters {
+                        let format_operation = match formatter {
+                            Formatter::Auto => {
+                                if let Some(operation) =
+                                    prettier_support::format_with_prettier(&project, buffer, &mut cx)
+                                        .await
+                                {
+                                    Some(operation)
+                                } else if let Some((language_server, buffer_abs_path)) =
+                                    primary_language_server.zip(buffer_abs_path.as_ref())
+                                {
+                                    Some(FormatOperation::Lsp(
+                                        Self::format_via_lsp(
+                                            &project,
+                                            buffer,
+                                            buffer_abs_path,
+                                            language_server,
+                                            tab_size,
+                                            &mut cx,
+                                        )
+                                        .await
+                                        .context("failed to format via language server")?,
+                                    ))
+                                } else {
+                                    None
+                                }
                             }
-                        }
-                        (Formatter::Auto, FormatOnSave::On | FormatOnSave::Off) => {
-                            if let Some(new_operation) =
+                            Formatter::LanguageServer { name } => {
+                                let language_server = match name {
+                                    Some(name) => language_servers
+                                        .iter()
+                                        .find(|(adapter, _)| adapter.name.0 == *name)
+                                        .map(|(_, server)| server),
+                                    None => primary_language_server,
+                                };
+                                if let Some((language_server, buffer_abs_path)) =
+                                    language_server.zip(buffer_abs_path.as_ref())
+                                {
+                                    Some(FormatOperation::Lsp(
+                                        Self::format_via_lsp(
+                                            &project,
+                                            buffer,
+                                            buffer_abs_path,
+                                            language_server,
+                                            tab_size,
+                                            &mut cx,
+                                        )
+                                        .await
+                                        .context("failed to format via language server")?,
+                                    ))
+                                } else {
+                                    if let Some(name) = name {
+                                        log::warn!(
+                                            "language server {name:?} is not running for the formatted buffer"
+                                        );
+                                    }
+                                    None
+                                }
+                            }
+                            Formatter::Prettier => {
                                 prettier_support::format_with_prettier(&project, buffer, &mut cx)
                                     .await
-                            {
-                                format_operation = Some(new_operation);
-                            } else if let Some((language_server, buffer_abs_path)) =
-                                language_server.as_ref().zip(buffer_abs_path.as_ref())
-                            {
-                                format_operation = Some(FormatOperation::Lsp(
-                                    Self::format_via_lsp(
-                                        &project,
+                            }
+                            Formatter::External { command, arguments } => {
+                                if let Some(buffer_abs_path) = buffer_abs_path {
+                                    Self::format_via_external_command(
                                         buffer,
                                         buffer_abs_path,
-                                        language_server,
-                                        tab_size,
+                                        command,
+                                        arguments,
                                         &mut cx,
                                     )
                                     .await
-                                    .context("failed to format via language server")?,
-                                ));
-                            }
-                        }
-                        (Formatter::Prettier, FormatOnSave::On | FormatOnSave::Off) => {
-                            if let Some(new_operation) =
-                                prettier_support::format_with_prettier(&project, buffer, &mut cx)
-                                    .await
-                            {
-                                format_operation = Some(new_operation);
-                            }
-                        }
-                    };
-
-                    buffer.update(&mut cx, |b, cx| {
-                        // If the buffer had its whitespace formatted and was edited while the language-specific
-                        // formatting was being computed, avoid applying the language-specific formatting, because
-                        // it can't be grouped with the whitespace formatting in the undo history.
-                        if let Some(transaction_id) = whitespace_transaction_id {
-                            if b.peek_undo_stack()
-                                .map_or(true, |e| e.transaction_id() != transaction_id)
-                            {
-                                format_operation.take();
-                            }
-                        }
-
-                        // Apply any language-specific formatting, and group the two formatting operations
-                        // in the buffer's undo history.
-                        if let Some(operation) = format_operation {
-                            match operation {
-                                FormatOperation::Lsp(edits) => {
-                                    b.edit(edits, None, cx);
-                                }
-                                FormatOperation::External(diff) => {
-                                    b.apply_diff(diff, cx);
+                                    .context(format!(
+                                        "failed to format via external command {:?}",
+                                        command
+                                    ))?
+                                    .map(FormatOperation::External)
+                                } else {
+                                    None
                                 }
-                                FormatOperation::Prettier(diff) => {
-                                    b.apply_diff(diff, cx);
+                            }
+                            Formatter::CodeActions(kinds) => {
+                                // Each code action is applied before requesting the next one,
+                                // so that servers compute their edits against the latest text.
+                                if let Some(buffer_abs_path) = buffer_abs_path {
+                                    for kind in kinds.iter() {
+                                        let operation = Self::format_via_code_action(
+                                            &project,
+                                            buffer,
+                                            buffer_abs_path,
+                                            &language_servers,
+                                            kind,
+                                            &mut cx,
+                                        )
+                                        .await
+                                        .context(format!(
+                                            "failed to apply code action {kind:?}"
+                                        ))?;
+                                        if let Some(operation) = operation {
+                                            if !Self::apply_format_operation(
+                                                buffer,
+                                                operation,
+                                                &mut formatting_transaction_id,
+                                                &mut cx,
+                                            )? {
+                                                break 'formatters;
+                                            }
+                                        }
+                                    }
                                 }
+                                None
                             }
+                            Formatter::List(_) => None,
+                        };
 
-                            if let Some(transaction_id) = whitespace_transaction_id {
-                                b.group_until_transaction(transaction_id);
+                        if let Some(operation) = format_operation {
+                            if !Self::apply_format_operation(
+                                buffer,
+                                operation,
+                                &mut formatting_transaction_id,
+                                &mut cx,
+                            )? {
+                                break;
                             }
                         }
+                    }
 
+                    buffer.update(&mut cx, |b, cx| {
                         if let Some(transaction) = b.finalize_last_transaction().cloned() {
                             if !push_to_history {
                                 b.forget_transaction(transaction.id);
@@ -4396,6 +4862,153 @@ impl Project {
         }
     }
 
+    /// Applies the result of a formatting step to the buffer, grouping it with
+    /// the previous steps in the undo history. Returns `false`, discarding the
+    /// step, if the buffer was edited since those steps were applied.
+    fn apply_format_operation(
+        buffer: &Model<Buffer>,
+        operation: FormatOperation,
+        formatting_transaction_id: &mut Option<TransactionId>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<bool> {
+        buffer.update(cx, |buffer, cx| {
+            if let Some(transaction_id) = *formatting_transaction_id {
+                if buffer
+                    .peek_undo_stack()
+                    .map_or(true, |entry| entry.transaction_id() != transaction_id)
+                {
+                    return false;
+                }
+            }
+
+            buffer.start_transaction();
+            match operation {
+                FormatOperation::Lsp(edits) => {
+                    buffer.edit(edits, None, cx);
+                }
+                FormatOperation::External(diff) | FormatOperation::Prettier(diff) => {
+                    buffer.apply_diff(diff, cx);
+                }
+            }
+            let transaction_id = buffer.end_transaction(cx);
+
+            match *formatting_transaction_id {
+                Some(formatting_transaction_id) => {
+                    buffer.group_until_transaction(formatting_transaction_id);
+                }
+                None => *formatting_transaction_id = transaction_id,
+            }
+            true
+        })
+    }
+
+    /// Requests the code actions of the given kind for the whole buffer from
+    /// its language servers, and returns the buffer edits of the first one.
+    async fn format_via_code_action(
+        this: &WeakModel<Self>,
+        buffer: &Model<Buffer>,
+        abs_path: &Path,
+        language_servers: &[(Arc<CachedLspAdapter>, Arc<LanguageServer>)],
+        kind: &str,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Option<FormatOperation>> {
+        let uri = lsp::Url::from_file_path(abs_path)
+            .map_err(|_| anyhow!("failed to convert abs path to uri"))?;
+        let kind = lsp::CodeActionKind::from(kind.to_string());
+
+        for (_, language_server) in language_servers {
+            match language_server.capabilities().code_action_provider {
+                None | Some(lsp::CodeActionProviderCapability::Simple(false)) => continue,
+                _ => {}
+            }
+
+            let range = buffer.update(cx, |buffer, _| {
+                range_to_lsp(PointUtf16::zero()..buffer.max_point_utf16())
+            })?;
+            let actions = language_server
+                .request::<lsp::request::CodeActionRequest>(lsp::CodeActionParams {
+                    text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                    range,
+                    context: lsp::CodeActionContext {
+                        only: Some(vec![kind.clone()]),
+                        ..Default::default()
+                    },
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                })
+                .await?
+                .unwrap_or_default();
+
+            for action in actions {
+                let lsp::CodeActionOrCommand::CodeAction(mut action) = action else {
+                    continue;
+                };
+                if action.edit.is_none() && action.data.is_some() {
+                    action = language_server
+                        .request::<lsp::request::CodeActionResolveRequest>(action)
+                        .await?;
+                }
+                let Some(edit) = action.edit else {
+                    log::warn!(
+                        "skipping code action {:?}, which has no edits to apply",
+                        action.title
+                    );
+                    continue;
+                };
+
+                // Only the edits to the formatted buffer are applied.
+                let document_edits = match edit.document_changes {
+                    Some(lsp::DocumentChanges::Edits(edits)) => edits,
+                    Some(lsp::DocumentChanges::Operations(operations)) => operations
+                        .into_iter()
+                        .filter_map(|operation| match operation {
+                            lsp::DocumentChangeOperation::Edit(edit) => Some(edit),
+                            lsp::DocumentChangeOperation::Op(_) => None,
+                        })
+                        .collect(),
+                    None => edit
+                        .changes
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(uri, edits)| lsp::TextDocumentEdit {
+                            text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                                uri,
+                                version: None,
+                            },
+                            edits: edits.into_iter().map(OneOf::Left).collect(),
+                        })
+                        .collect(),
+                };
+                let mut version = None;
+                let mut lsp_edits = Vec::new();
+                for edit in document_edits {
+                    if edit.text_document.uri == uri {
+                        version = edit.text_document.version;
+                        lsp_edits.extend(edit.edits.into_iter().map(|edit| match edit {
+                            OneOf::Left(edit) => edit,
+                            OneOf::Right(edit) => edit.text_edit,
+                        }));
+                    }
+                }
+
+                let edits = this
+                    .update(cx, |this, cx| {
+                        this.edits_from_lsp(
+                            buffer,
+                            lsp_edits,
+                            language_server.server_id(),
+                            version,
+                            cx,
+                        )
+                    })?
+                    .await?;
+                return Ok(Some(FormatOperation::Lsp(edits)));
+            }
+        }
+
+        Ok(None)
+    }
+
     async fn format_via_external_command(
         buffer: &Model<Buffer>,
         buffer_abs_path: &Path,
@@ -4485,6 +5098,98 @@ impl Project {
         )
     }
 
+    pub fn implementation<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<LocationLink>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetImplementation { position },
+            cx,
+        )
+    }
+
+    /// Returns the hierarchy items for the symbol at the given position, which can then be
+    /// expanded with [`Project::hierarchy_children`].
+    pub fn prepare_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        kind: HierarchyKind,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        if kind.is_call_hierarchy() {
+            self.request_lsp(
+                buffer.clone(),
+                LanguageServerToQuery::Primary,
+                PrepareCallHierarchy { position },
+                cx,
+            )
+        } else {
+            self.request_lsp(
+                buffer.clone(),
+                LanguageServerToQuery::Primary,
+                PrepareTypeHierarchy { position },
+                cx,
+            )
+        }
+    }
+
+    /// Returns the callers, callees, supertypes or subtypes of an item obtained from
+    /// [`Project::prepare_hierarchy`] with a matching kind of hierarchy.
+    pub fn hierarchy_children(
+        &self,
+        item: &HierarchyItem,
+        kind: HierarchyKind,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<HierarchyItem>>> {
+        let buffer = item.location.buffer.clone();
+        let lsp_item = item.lsp_item.clone();
+        match kind {
+            HierarchyKind::IncomingCalls => match serde_json::from_value(lsp_item) {
+                Ok(item) => self.request_lsp(
+                    buffer,
+                    LanguageServerToQuery::Primary,
+                    GetIncomingCalls { item },
+                    cx,
+                ),
+                Err(error) => Task::ready(Err(error.into())),
+            },
+            HierarchyKind::OutgoingCalls => match serde_json::from_value(lsp_item) {
+                Ok(item) => self.request_lsp(
+                    buffer,
+                    LanguageServerToQuery::Primary,
+                    GetOutgoingCalls { item },
+                    cx,
+                ),
+                Err(error) => Task::ready(Err(error.into())),
+            },
+            HierarchyKind::Supertypes => match serde_json::from_value(lsp_item) {
+                Ok(item) => self.request_lsp(
+                    buffer,
+                    LanguageServerToQuery::Primary,
+                    GetSupertypes { item },
+                    cx,
+                ),
+                Err(error) => Task::ready(Err(error.into())),
+            },
+            HierarchyKind::Subtypes => match serde_json::from_value(lsp_item) {
+                Ok(item) => self.request_lsp(
+                    buffer,
+                    LanguageServerToQuery::Primary,
+                    GetSubtypes { item },
+                    cx,
+                ),
+                Err(error) => Task::ready(Err(error.into())),
+            },
+        }
+    }
+
     pub fn references<T: ToPointUtf16>(
         &self,
         buffer: &Model<Buffer>,
@@ -4750,6 +5455,59 @@ impl Project {
         )
     }
 
+    pub fn signature_help<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        trigger_character: Option<String>,
+        is_retrigger: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<SignatureHelp>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSignatureHelp {
+                position,
+                trigger_character,
+                is_retrigger,
+            },
+            cx,
+        )
+    }
+
+    /// Returns the characters that should trigger a signature help request when typed in
+    /// the given buffer. Guests can't inspect the host's language server capabilities, so
+    /// they fall back to the characters that open and continue an argument list.
+    pub fn signature_help_trigger_characters(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Vec<String> {
+        let buffer = buffer.read(cx);
+        if self.is_local() {
+            self.primary_language_server_for_buffer(buffer, cx)
+                .map(|(_, server)| signature_help_trigger_characters(server))
+                .unwrap_or_default()
+        } else {
+            // Guests use the characters the host sent for the server that the
+            // host would pick for the buffer.
+            let Some(language) = buffer.language() else {
+                return Vec::new();
+            };
+            let settings = language_settings(Some(language), buffer.file(), cx);
+            self.enabled_lsp_adapters(language, settings)
+                .iter()
+                .find_map(|adapter| {
+                    self.language_server_statuses
+                        .values()
+                        .find(|status| status.name == adapter.name.0.as_ref())
+                })
+                .map(|status| status.signature_help_trigger_characters.clone())
+                .unwrap_or_default()
+        }
+    }
+
     pub fn completions<T: ToOffset + ToPointUtf16>(
         &self,
         buffer: &Model<Buffer>,
@@ -6299,6 +7057,13 @@ impl Project {
         }
     }
 
+    /// Returns whether the buffer has been sent to any of the project's guests.
+    pub fn is_buffer_shared(&self, buffer_id: BufferId) -> bool {
+        self.shared_buffers
+            .values()
+            .any(|buffer_ids| buffer_ids.contains(&buffer_id))
+    }
+
     fn create_local_worktree(
         &mut self,
         abs_path: impl AsRef<Path>,
@@ -6397,6 +7162,10 @@ impl Project {
         })
         .detach();
 
+        self.task_inventory.update(cx, |inventory, cx| {
+            inventory.remove_worktree(id_to_remove, cx)
+        });
+
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
                 let id = worktree.read(cx).id();
@@ -6650,6 +7419,15 @@ impl Project {
     ) {
         debug_assert!(worktree_handle.read(cx).is_local());
 
+        // Blame the files of the changed repositories from scratch next time.
+        let worktree_id = worktree_handle.read(cx).id();
+        self.head_blames.retain(|(blame_worktree_id, path), _| {
+            *blame_worktree_id != worktree_id
+                || !changed_repos
+                    .iter()
+                    .any(|(work_dir, _)| path.starts_with(work_dir))
+        });
+
         // Identify the loading buffers whose containing repository that has changed.
         let future_buffers = self
             .loading_buffers_by_path
@@ -6757,19 +7535,20 @@ impl Project {
         let remote_worktree_id = worktree.id();
 
         let mut settings_contents = Vec::new();
+        let mut tasks_contents = Vec::new();
         for (path, _, change) in changes.iter() {
-            if path.ends_with(&*LOCAL_SETTINGS_RELATIVE_PATH) {
-                let settings_dir = Arc::from(
+            if path.ends_with(&*LOCAL_TASKS_RELATIVE_PATH) {
+                let tasks_dir: Arc<Path> = Arc::from(
                     path.ancestors()
-                        .nth(LOCAL_SETTINGS_RELATIVE_PATH.components().count())
+                        .nth(LOCAL_TASKS_RELATIVE_PATH.components().count())
                         .unwrap(),
                 );
                 let fs = self.fs.clone();
                 let removed = *change == PathChange::Removed;
                 let abs_path = worktree.absolutize(path);
-                settings_contents.push(async move {
+                tasks_contents.push(async move {
                     (
-                        settings_dir,
+                        tasks_dir,
                         if removed {
                             None
                         } else {
@@ -6777,7 +7556,60 @@ impl Project {
                         },
                     )
                 });
+                continue;
             }
+
+            let (settings_dir, is_editorconfig) = if path.ends_with(&*LOCAL_SETTINGS_RELATIVE_PATH)
+            {
+                let settings_dir = path
+                    .ancestors()
+                    .nth(LOCAL_SETTINGS_RELATIVE_PATH.components().count())
+                    .unwrap();
+                (settings_dir, false)
+            } else if path.file_name() == Some(OsStr::new(EDITORCONFIG_FILE_NAME)) {
+                (path.parent().unwrap(), true)
+            } else {
+                continue;
+            };
+            let settings_dir = Arc::from(settings_dir);
+            let fs = self.fs.clone();
+            let removed = *change == PathChange::Removed;
+            let abs_path = worktree.absolutize(path);
+            settings_contents.push(async move {
+                (
+                    settings_dir,
+                    is_editorconfig,
+                    if removed {
+                        None
+                    } else {
+                        Some(async move { fs.load(&abs_path?).await }.await)
+                    },
+                )
+            });
+        }
+
+        if !tasks_contents.is_empty() {
+            let task_inventory = self.task_inventory.clone();
+            cx.spawn(move |_, mut cx| async move {
+                let tasks_contents: Vec<(Arc<Path>, _)> =
+                    futures::future::join_all(tasks_contents).await;
+                task_inventory
+                    .update(&mut cx, |inventory, cx| {
+                        for (directory, file_content) in tasks_contents {
+                            let file_content = file_content.and_then(|content| content.log_err());
+                            inventory
+                                .set_worktree_templates(
+                                    remote_worktree_id,
+                                    directory,
+                                    file_content.as_deref(),
+                                    cx,
+                                )
+                                .log_err();
+                        }
+                    })
+                    .ok();
+            })
+            .detach();
         }
 
         if settings_contents.is_empty() {
@@ -6786,26 +7618,37 @@ impl Project {
 
         let client = self.client.clone();
         cx.spawn(move |_, cx| async move {
-            let settings_contents: Vec<(Arc<Path>, _)> =
+            let settings_contents: Vec<(Arc<Path>, bool, _)> =
                 futures::future::join_all(settings_contents).await;
             cx.update(|cx| {
                 cx.update_global::<SettingsStore, _>(|store, cx| {
-                    for (directory, file_content) in settings_contents {
+                    for (directory, is_editorconfig, file_content) in settings_contents {
                         let file_content = file_content.and_then(|content| content.log_err());
-                        store
-                            .set_local_settings(
+                        let path = if is_editorconfig {
+                            store.set_editorconfig(
                                 worktree_id.as_u64() as usize,
                                 directory.clone(),
-                                file_content.as_ref().map(String::as_str),
+                                file_content.as_deref(),
                                 cx,
-                            )
-                            .log_err();
+                            );
+                            directory.join(EDITORCONFIG_FILE_NAME)
+                        } else {
+                            store
+                                .set_local_settings(
+                                    worktree_id.as_u64() as usize,
+                                    directory.clone(),
+                                    file_content.as_ref().map(String::as_str),
+                                    cx,
+                                )
+                                .log_err();
+                            directory.to_path_buf()
+                        };
                         if let Some(remote_id) = project_id {
                             client
                                 .send(proto::UpdateWorktreeSettings {
                                     project_id: remote_id,
                                     worktree_id: remote_worktree_id.to_proto(),
-                                    path: directory.to_string_lossy().into_owned(),
+                                    path: path.to_string_lossy().into_owned(),
                                     content: file_content,
                                 })
                                 .log_err();
@@ -6965,6 +7808,7 @@ impl Project {
             cx.emit(Event::CollaboratorJoined(collaborator.peer_id));
             this.collaborators
                 .insert(collaborator.peer_id, collaborator);
+            this.resend_shared_terminals(cx);
             cx.notify();
         })?;
 
@@ -7092,15 +7936,20 @@ impl Project {
         this.update(&mut cx, |this, cx| {
             let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
             if let Some(worktree) = this.worktree_for_id(worktree_id, cx) {
+                let root_id = worktree.entity_id().as_u64() as usize;
+                let path = Path::new(&envelope.payload.path);
+                let content = envelope.payload.content.as_deref();
                 cx.update_global::<SettingsStore, _>(|store, cx| {
-                    store
-                        .set_local_settings(
-                            worktree.entity_id().as_u64() as usize,
-                            PathBuf::from(&envelope.payload.path).into(),
-                            envelope.payload.content.as_ref().map(String::as_str),
-                            cx,
-                        )
-                        .log_err();
+                    // `.editorconfig` files are sent by their own path, while
+                    // other settings are sent by the directory they apply to.
+                    if path.file_name() == Some(OsStr::new(EDITORCONFIG_FILE_NAME)) {
+                        let directory = path.parent().unwrap_or(Path::new(""));
+                        store.set_editorconfig(root_id, directory.into(), content, cx);
+                    } else {
+                        store
+                            .set_local_settings(root_id, path.into(), content, cx)
+                            .log_err();
+                    }
                 });
             }
             Ok(())
@@ -7287,6 +8136,7 @@ impl Project {
                     pending_work: Default::default(),
                     has_pending_diagnostic_updates: false,
                     progress_tokens: Default::default(),
+                    signature_help_trigger_characters: server.signature_help_trigger_characters,
                 },
             );
             cx.notify();
@@ -7546,6 +8396,68 @@ impl Project {
         })?)
     }
 
+    async fn handle_set_index_text(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SetIndexText>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        this.update(&mut cx, |this, cx| {
+            let buffer = this
+                .opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))?;
+            anyhow::Ok(this.set_index_text(&buffer, envelope.payload.text, cx))
+        })??
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_load_head_text(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::LoadHeadText>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::LoadHeadTextResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let text = this
+            .update(&mut cx, |this, cx| {
+                let buffer = this
+                    .opened_buffers
+                    .get(&buffer_id)
+                    .and_then(|buffer| buffer.upgrade())
+                    .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))?;
+                anyhow::Ok(this.load_head_text(&buffer, cx))
+            })??
+            .await?;
+        Ok(proto::LoadHeadTextResponse { text })
+    }
+
+    async fn handle_blame_buffer(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::BlameBuffer>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::BlameBufferResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let version = deserialize_version(&envelope.payload.version);
+        let buffer = this.update(&mut cx, |this, _| {
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| buffer.wait_for_version(version))?
+            .await?;
+        let blame = this
+            .update(&mut cx, |this, cx| this.blame_buffer(&buffer, cx))?
+            .await?;
+        Ok(serialize_blame(blame))
+    }
+
     async fn handle_reload_buffers(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::ReloadBuffers>,
@@ -8735,8 +9647,8 @@ impl Project {
     ) -> Vec<LanguageServerId> {
         if let Some((file, language)) = File::from_dyn(buffer.file()).zip(buffer.language()) {
             let worktree_id = file.worktree_id(cx);
-            language
-                .lsp_adapters()
+            let settings = language_settings(Some(language), buffer.file(), cx);
+            self.enabled_lsp_adapters(language, settings)
                 .iter()
                 .flat_map(|adapter| {
                     let key = (worktree_id, adapter.name.clone());
@@ -8749,6 +9661,38 @@ impl Project {
     }
 }
 
+/// Returns the characters that trigger or retrigger signature help for the given
+/// language server.
+fn signature_help_trigger_characters(server: &LanguageServer) -> Vec<String> {
+    server
+        .capabilities()
+        .signature_help_provider
+        .map(|provider| {
+            provider
+                .trigger_characters
+                .into_iter()
+                .flatten()
+                .chain(provider.retrigger_characters.into_iter().flatten())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the adapters of the built-in and user-defined language servers
+/// for the given language.
+fn language_lsp_adapters<'a>(
+    language: &'a Arc<Language>,
+    custom_language_servers: &'a [CustomLanguageServer],
+) -> impl Iterator<Item = &'a Arc<CachedLspAdapter>> {
+    let language_name = language.name();
+    language.lsp_adapters().iter().chain(
+        custom_language_servers
+            .iter()
+            .filter(move |server| server.languages.contains(&language_name))
+            .map(|server| &server.adapter),
+    )
+}
+
 fn subscribe_for_copilot_events(
     copilot: &Model<Copilot>,
     cx: &mut ModelContext<'_, Project>,
@@ -8938,6 +9882,55 @@ fn serialize_symbol(symbol: &Symbol) -> proto::Symbol {
     }
 }
 
+fn serialize_blame(blame: repository::Blame) -> proto::BlameBufferResponse {
+    proto::BlameBufferResponse {
+        entries: blame
+            .entries
+            .into_iter()
+            .map(|entry| proto::BlameEntry {
+                sha: entry.sha,
+                start_row: entry.range.start,
+                end_row: entry.range.end,
+                author: entry.author,
+                author_mail: entry.author_mail,
+                author_time: entry.author_time,
+                author_offset_minutes: entry.author_offset_minutes,
+                summary: entry.summary,
+            })
+            .collect(),
+        messages: blame
+            .messages
+            .into_iter()
+            .map(|(sha, message)| proto::CommitMessage { sha, message })
+            .collect(),
+        remote_url: blame.remote_url,
+    }
+}
+
+fn deserialize_blame(response: proto::BlameBufferResponse) -> repository::Blame {
+    repository::Blame {
+        entries: response
+            .entries
+            .into_iter()
+            .map(|entry| repository::BlameEntry {
+                sha: entry.sha,
+                range: entry.start_row..entry.end_row,
+                author: entry.author,
+                author_mail: entry.author_mail,
+                author_time: entry.author_time,
+                author_offset_minutes: entry.author_offset_minutes,
+                summary: entry.summary,
+            })
+            .collect(),
+        messages: response
+            .messages
+            .into_iter()
+            .map(|message| (message.sha, message.message))
+            .collect(),
+        remote_url: response.remote_url,
+    }
+}
+
 fn relativize_path(base: &Path, path: &Path) -> PathBuf {
     let mut path_components = path.components();
     let mut base_components = base.components();
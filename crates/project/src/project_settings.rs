@@ -1,18 +1,21 @@
 use collections::HashMap;
 use gpui::AppContext;
+use lsp::LanguageServerBinary;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
-use std::sync::Arc;
+use std::{ffi::OsString, path::PathBuf, sync::Arc};
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct ProjectSettings {
     /// Configuration for language servers.
     ///
     /// The following settings can be overridden for specific language servers:
+    /// - binary
     /// - initialization_options
     /// To override settings for a language, add an entry for that language server's
-    /// name to the lsp value.
+    /// name to the lsp value. An entry that specifies `languages` defines a new
+    /// language server, which is started for those languages.
     /// Default: null
     #[serde(default)]
     pub lsp: HashMap<Arc<str>, LspSettings>,
@@ -43,6 +46,17 @@ pub struct GitSettings {
     /// Default: tracked_files
     pub git_gutter: Option<GitGutterSetting>,
     pub gutter_debounce: Option<u64>,
+    /// Whether or not to show git blame information for the cursor's line
+    /// at the end of that line.
+    ///
+    /// Default: true
+    pub inline_blame: Option<bool>,
+}
+
+impl GitSettings {
+    pub fn inline_blame_enabled(&self) -> bool {
+        self.inline_blame.unwrap_or(true)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -55,10 +69,42 @@ pub enum GitGutterSetting {
     Hide,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct LspSettings {
+    /// The binary to launch the language server with, instead of the one
+    /// that Zed downloads.
+    pub binary: Option<BinarySettings>,
     pub initialization_options: Option<serde_json::Value>,
+    /// The names of the languages to start a user-defined language server for.
+    pub languages: Option<Vec<Arc<str>>>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub struct BinarySettings {
+    /// The path to the language server's executable. Paths without a
+    /// directory are looked up in the `PATH`.
+    pub path: Option<String>,
+    /// The arguments to pass to the executable.
+    pub arguments: Option<Vec<String>>,
+    /// Environment variables to set for the language server process.
+    pub env: Option<HashMap<String, String>>,
+}
+
+impl BinarySettings {
+    /// Returns the binary to launch, if a path to an executable was provided.
+    pub fn language_server_binary(&self) -> Option<LanguageServerBinary> {
+        Some(LanguageServerBinary {
+            path: PathBuf::from(self.path.as_ref()?),
+            arguments: self
+                .arguments
+                .iter()
+                .flatten()
+                .map(OsString::from)
+                .collect(),
+            env: self.env.clone(),
+        })
+    }
 }
 
 impl Settings for ProjectSettings {
@@ -3,7 +3,9 @@ use fs::FakeFs;
 use futures::{future, StreamExt};
 use gpui::AppContext;
 use language::{
-    language_settings::{AllLanguageSettings, LanguageSettingsContent},
+    language_settings::{
+        AllLanguageSettings, Formatter, LanguageSettingsContent, SemanticTokenSettingsContent,
+    },
     tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
     LineEnding, OffsetRangeExt, Point, ToPoint,
 };
@@ -142,6 +144,178 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     });
 }
 
+#[gpui::test]
+async fn test_editorconfig_settings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".editorconfig": "root = true\n[*]\nindent_size = 3\n[*.md]\ntrim_trailing_whitespace = false\n",
+            "a": {
+                "a.rs": "fn a() {\n   A\n}",
+                "README.md": "",
+            },
+            "b": {
+                ".editorconfig": "[*.rs]\nindent_style = tab\n",
+                ".zed": {
+                    "settings.json": r#"{ "tab_size": 2 }"#
+                },
+                "b.rs": "fn b() {\n\tB\n}"
+            }
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    let open_buffer = |path: &str, cx: &mut gpui::TestAppContext| {
+        project.update(cx, |project, cx| project.open_local_buffer(path, cx))
+    };
+    let buffer_a = open_buffer("/the-root/a/a.rs", cx).await.unwrap();
+    let readme = open_buffer("/the-root/a/README.md", cx).await.unwrap();
+    let buffer_b = open_buffer("/the-root/b/b.rs", cx).await.unwrap();
+
+    // Settings are resolved for the files of open buffers.
+    let settings_for = |buffer: &Model<Buffer>, cx: &mut gpui::TestAppContext| {
+        cx.update(|cx| language_settings(None, buffer.read(cx).file(), cx).clone())
+    };
+
+    cx.executor().run_until_parked();
+    let settings_a = settings_for(&buffer_a, cx);
+    assert_eq!(settings_a.tab_size.get(), 3);
+    assert!(!settings_a.hard_tabs);
+    assert!(settings_a.remove_trailing_whitespace_on_save);
+    assert!(!settings_for(&readme, cx).remove_trailing_whitespace_on_save);
+
+    // Project settings take precedence over `.editorconfig` files.
+    let settings_b = settings_for(&buffer_b, cx);
+    assert_eq!(settings_b.tab_size.get(), 2);
+    assert!(settings_b.hard_tabs);
+
+    fs.save(
+        "/the-root/.editorconfig".as_ref(),
+        &"root = true\n[*]\nindent_size = 5\n".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    fs.remove_file("/the-root/b/.editorconfig".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(settings_for(&buffer_a, cx).tab_size.get(), 5);
+    let settings_b = settings_for(&buffer_b, cx);
+    assert_eq!(settings_b.tab_size.get(), 2);
+    assert!(!settings_b.hard_tabs);
+}
+
+#[gpui::test]
+async fn test_managing_project_specific_tasks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            ".zed": {
+                "tasks.json": r#"[{ "label": "build", "command": "cargo", "args": ["build"] }]"#
+            },
+            "b": {
+                ".zed": {
+                    "tasks.json": r#"[{ "label": "serve", "command": "npm", "args": ["start"] }]"#
+                },
+                "b.rs": "fn b() {}"
+            }
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees().next().unwrap().read(cx).id()
+    });
+    let inventory = project.update(cx, |project, _| project.task_inventory().clone());
+    inventory.update(cx, |inventory, cx| {
+        inventory
+            .set_global_templates(
+                Some(
+                    r#"// Global tasks
+                [{ "label": "list", "command": "ls" }]"#,
+                ),
+                cx,
+            )
+            .unwrap()
+    });
+    cx.executor().run_until_parked();
+
+    let labels = |worktree_id, cx: &mut gpui::TestAppContext| {
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .list_templates(worktree_id)
+                .into_iter()
+                .map(|(kind, template)| (kind.label(), template.label))
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        labels(Some(worktree_id), cx),
+        [
+            (".zed/tasks.json".to_string(), "build".to_string()),
+            ("b/.zed/tasks.json".to_string(), "serve".to_string()),
+            ("global".to_string(), "list".to_string()),
+        ]
+    );
+    assert_eq!(
+        labels(None, cx),
+        [("global".to_string(), "list".to_string())]
+    );
+
+    // Scheduled tasks are listed first.
+    inventory.update(cx, |inventory, _| {
+        let (kind, template) = inventory.list_templates(Some(worktree_id)).pop().unwrap();
+        let task = template
+            .resolve(kind.task_id(&template), &task::TaskContext::default())
+            .unwrap();
+        inventory.task_scheduled(task);
+        assert_eq!(inventory.last_scheduled_task().unwrap().command, "ls");
+    });
+    assert_eq!(
+        labels(Some(worktree_id), cx),
+        [
+            ("global".to_string(), "list".to_string()),
+            (".zed/tasks.json".to_string(), "build".to_string()),
+            ("b/.zed/tasks.json".to_string(), "serve".to_string()),
+        ]
+    );
+
+    // Tasks files are reloaded when they change.
+    fs.save(
+        "/the-root/.zed/tasks.json".as_ref(),
+        &r#"[{ "label": "check", "command": "cargo", "args": ["check"] }]"#.into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    fs.remove_file("/the-root/b/.zed/tasks.json".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        labels(Some(worktree_id), cx),
+        [
+            ("global".to_string(), "list".to_string()),
+            (".zed/tasks.json".to_string(), "check".to_string()),
+        ]
+    );
+
+    project.update(cx, |project, cx| project.remove_worktree(worktree_id, cx));
+    assert_eq!(
+        labels(Some(worktree_id), cx),
+        [("global".to_string(), "list".to_string())]
+    );
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1444,6 +1618,192 @@ async fn test_toggling_enable_language_server(cx: &mut gpui::TestAppContext) {
         .await;
 }
 
+#[gpui::test]
+async fn test_excluding_language_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut rust = Language::new(
+        LanguageConfig {
+            name: Arc::from("Rust"),
+            path_suffixes: vec!["rs".to_string()],
+            ..Default::default()
+        },
+        None,
+    );
+    let mut fake_rust_servers = rust
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            name: "rust-lsp",
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "" })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(rust)));
+
+    let rs_buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let mut fake_rust_server_1 = fake_rust_servers.next().await.unwrap();
+    fake_rust_server_1
+        .receive_notification::<lsp::notification::DidOpenTextDocument>()
+        .await;
+
+    // Excluding the server by name stops it.
+    let set_language_servers = |language_servers: &[&str], cx: &mut gpui::TestAppContext| {
+        let language_servers = language_servers.iter().map(|&name| name.into()).collect();
+        cx.update(|cx| {
+            cx.update_global(|settings: &mut SettingsStore, cx| {
+                settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings.languages.insert(
+                        Arc::from("Rust"),
+                        LanguageSettingsContent {
+                            language_servers: Some(language_servers),
+                            ..Default::default()
+                        },
+                    );
+                });
+            })
+        });
+    };
+    set_language_servers(&["!rust-lsp", "..."], cx);
+    fake_rust_server_1
+        .receive_notification::<lsp::notification::Exit>()
+        .await;
+    project.update(cx, |project, cx| {
+        let buffer = rs_buffer.read(cx);
+        assert_eq!(project.language_servers_for_buffer(buffer, cx).count(), 0);
+    });
+
+    // Including it again starts a new instance.
+    set_language_servers(&["..."], cx);
+    let mut fake_rust_server_2 = fake_rust_servers.next().await.unwrap();
+    assert_eq!(
+        fake_rust_server_2
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri
+            .as_str(),
+        "file:///dir/a.rs"
+    );
+}
+
+#[gpui::test]
+async fn test_formatting_with_a_list_of_formatters(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut rust = Language::new(
+        LanguageConfig {
+            name: Arc::from("Rust"),
+            path_suffixes: vec!["rs".to_string()],
+            ..Default::default()
+        },
+        None,
+    );
+    let mut fake_rust_servers = rust
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            name: "rust-lsp",
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                document_formatting_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "use b;\nuse a;\nfn main() {}\n" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(rust)));
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_rust_servers.next().await.unwrap();
+
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.formatter = Some(Formatter::List(
+                    vec![
+                        Formatter::CodeActions(vec!["source.organizeImports".to_string()].into()),
+                        Formatter::LanguageServer {
+                            name: Some("rust-lsp".into()),
+                        },
+                    ]
+                    .into(),
+                ));
+            });
+        })
+    });
+
+    // The code action sorts the imports.
+    fake_server.handle_request::<lsp::request::CodeActionRequest, _, _>(|params, _| async move {
+        assert_eq!(
+            params.context.only,
+            Some(vec![lsp::CodeActionKind::SOURCE_ORGANIZE_IMPORTS])
+        );
+        Ok(Some(vec![lsp::CodeActionOrCommand::CodeAction(
+            lsp::CodeAction {
+                title: "Organize Imports".to_string(),
+                kind: Some(lsp::CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+                edit: Some(lsp::WorkspaceEdit {
+                    changes: Some(
+                        [(
+                            params.text_document.uri,
+                            vec![lsp::TextEdit::new(
+                                lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(2, 0)),
+                                "use a;\nuse b;\n".to_string(),
+                            )],
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )]))
+    });
+    // The formatter then runs on the result of the code action.
+    fake_server.handle_request::<lsp::request::Formatting, _, _>(|_, _| async move {
+        Ok(Some(vec![lsp::TextEdit::new(
+            lsp::Range::new(lsp::Position::new(2, 11), lsp::Position::new(2, 11)),
+            "\n".to_string(),
+        )]))
+    });
+
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                true,
+                FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "use a;\nuse b;\nfn main() {\n}\n"
+    );
+
+    // All of the formatting is undone at once.
+    buffer.update(cx, |buffer, cx| buffer.undo(cx));
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "use b;\nuse a;\nfn main() {}\n"
+    );
+}
+
 #[gpui::test(iterations = 3)]
 async fn test_transforming_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2343,6 +2703,227 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.semantic_tokens = Some(SemanticTokenSettingsContent {
+                    enabled: Some(true),
+                    rules: None,
+                });
+            });
+        })
+    });
+
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            path_suffixes: vec!["rs".to_string()],
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::FUNCTION,
+                                    lsp::SemanticTokenType::VARIABLE,
+                                ],
+                                token_modifiers: vec![lsp::SemanticTokenModifier::new("mutable")],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Bool(true)),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() { let mut b = a(); }",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document.uri.to_file_path().unwrap(),
+                Path::new("/dir/a.rs"),
+            );
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: None,
+                    data: vec![
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 3,
+                            length: 1,
+                            token_type: 0,
+                            token_modifiers_bitset: 0,
+                        },
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 14,
+                            length: 1,
+                            token_type: 1,
+                            token_modifiers_bitset: 1,
+                        },
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 4,
+                            length: 1,
+                            token_type: 0,
+                            token_modifiers_bitset: 0,
+                        },
+                    ],
+                },
+            )))
+        },
+    );
+
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let tokens = buffer
+            .semantic_tokens()
+            .iter()
+            .map(|token| {
+                (
+                    token.range.to_offset(buffer),
+                    token.highlight_name.to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            [
+                (3..4, "function".to_string()),
+                (17..18, "variable.special".to_string()),
+                (21..22, "function".to_string()),
+            ]
+        );
+    });
+
+    // Disabling semantic tokens clears them from the buffer.
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.semantic_tokens = Some(SemanticTokenSettingsContent {
+                    enabled: Some(false),
+                    rules: None,
+                });
+            });
+        })
+    });
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| assert!(buffer.semantic_tokens().is_empty()));
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let mut language = Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            path_suffixes: vec!["rs".to_string()],
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    );
+    let mut fake_servers = language
+        .set_fake_lsp_adapter(Arc::new(FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+        .await;
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "// region: a\nfn a() {\n    b();\n}\n// endregion\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    project.update(cx, |project, _| project.languages.add(Arc::new(language)));
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|params, _| async move {
+        assert_eq!(
+            params.text_document.uri.to_file_path().unwrap(),
+            Path::new("/dir/a.rs"),
+        );
+        Ok(Some(vec![
+            lsp::FoldingRange {
+                start_line: 1,
+                end_line: 2,
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 0,
+                end_line: 4,
+                kind: Some(lsp::FoldingRangeKind::Region),
+                ..Default::default()
+            },
+        ]))
+    });
+
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let folds = snapshot
+            .folding_ranges(0..snapshot.len())
+            .into_iter()
+            .map(|fold| (fold.range.to_point(&snapshot), fold.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            folds,
+            [
+                (
+                    Point::new(0, 12)..Point::new(4, 12),
+                    Some(language::FoldKind::Region)
+                ),
+                (Point::new(1, 8)..Point::new(2, 8), None),
+            ]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -0,0 +1,385 @@
+use std::{cmp::Reverse, ops::Range, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+use client::proto;
+use gpui::{AsyncAppContext, Model, ModelContext, Task};
+use language::{
+    language_settings::language_settings,
+    proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
+    Anchor, Bias, Buffer, BufferSnapshot, LocalFile, PointUtf16, Unclipped,
+};
+use lsp::{LanguageServer, LanguageServerId};
+use rpc::TypedEnvelope;
+use text::BufferId;
+use util::TryFutureExt;
+
+use crate::{File, Project};
+
+/// How long to wait after a buffer changes before requesting its semantic tokens.
+const SEMANTIC_TOKENS_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// A range of a buffer classified by a language server, using the token types
+/// and modifiers from the server's legend.
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    pub range: Range<Anchor>,
+    pub token_type: Arc<str>,
+    pub modifiers: Vec<Arc<str>>,
+}
+
+/// The last semantic tokens a language server reported for a buffer, kept so
+/// that the next request can ask the server for a delta.
+pub(crate) struct BufferSemanticTokens {
+    server_id: LanguageServerId,
+    result_id: Option<String>,
+    data: Vec<lsp::SemanticToken>,
+}
+
+impl Project {
+    /// Requests new semantic tokens for the buffer after a short delay, and
+    /// layers them on top of the buffer's syntax highlighting.
+    pub(crate) fn refresh_semantic_tokens(
+        &mut self,
+        buffer: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer_id = buffer.read(cx).remote_id();
+        let enabled = {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language(), buffer.file(), cx)
+                .semantic_tokens
+                .enabled
+        };
+        if !enabled {
+            self.semantic_token_tasks.remove(&buffer_id);
+            self.buffer_semantic_tokens.remove(&buffer_id);
+            if !buffer.read(cx).semantic_tokens().is_empty() {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_semantic_tokens(Arc::from([]), cx)
+                });
+            }
+            return;
+        }
+
+        let task = cx.spawn(move |this, mut cx| {
+            async move {
+                cx.background_executor()
+                    .timer(SEMANTIC_TOKENS_DEBOUNCE)
+                    .await;
+                let tokens = this
+                    .update(&mut cx, |this, cx| this.semantic_tokens(&buffer, cx))?
+                    .await?;
+                this.update(&mut cx, |_, cx| {
+                    let settings = {
+                        let buffer = buffer.read(cx);
+                        language_settings(buffer.language(), buffer.file(), cx)
+                            .semantic_tokens
+                            .clone()
+                    };
+                    let tokens = tokens
+                        .into_iter()
+                        .filter_map(|token| {
+                            let highlight_name =
+                                settings.highlight_name(&token.token_type, &token.modifiers)?;
+                            Some(language::SemanticToken {
+                                range: token.range,
+                                highlight_name: highlight_name.into(),
+                            })
+                        })
+                        .collect();
+                    buffer.update(cx, |buffer, cx| buffer.set_semantic_tokens(tokens, cx));
+                })
+            }
+            .log_err()
+        });
+        self.semantic_token_tasks.insert(buffer_id, task);
+    }
+
+    pub(crate) fn refresh_all_semantic_tokens(&mut self, cx: &mut ModelContext<Self>) {
+        let buffers = self
+            .opened_buffers
+            .values()
+            .filter_map(|buffer| buffer.upgrade())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.refresh_semantic_tokens(buffer, cx);
+        }
+    }
+
+    /// Returns the semantic tokens reported by the buffer's primary language server.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<SemanticToken>>> {
+        if self.is_local() {
+            self.semantic_tokens_locally(buffer, cx)
+        } else if let Some(project_id) = self.remote_id() {
+            let buffer_ref = buffer.read(cx);
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer_ref.remote_id().into(),
+                version: serialize_version(&buffer_ref.version()),
+            };
+            let client = self.client.clone();
+            let buffer = buffer.clone();
+            cx.spawn(move |_, mut cx| async move {
+                let response = client.request(request).await?;
+                buffer
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_version(deserialize_version(&response.version))
+                    })?
+                    .await?;
+                response
+                    .tokens
+                    .into_iter()
+                    .map(|token| {
+                        let start = token
+                            .start
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid start"))?;
+                        let end = token
+                            .end
+                            .and_then(deserialize_anchor)
+                            .ok_or_else(|| anyhow!("invalid end"))?;
+                        Ok(SemanticToken {
+                            range: start..end,
+                            token_type: token.token_type.into(),
+                            modifiers: token.modifiers.into_iter().map(Into::into).collect(),
+                        })
+                    })
+                    .collect()
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    fn semantic_tokens_locally(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<SemanticToken>>> {
+        let buffer = buffer.read(cx);
+        let buffer_id = buffer.remote_id();
+        let Some(file) = File::from_dyn(buffer.file()).filter(|file| file.is_local) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some((_, server)) = self.primary_language_server_for_buffer(buffer, cx) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some(options) = semantic_tokens_options(server) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let supports_delta = match options.full {
+            Some(lsp::SemanticTokensFullOptions::Bool(true)) => false,
+            Some(lsp::SemanticTokensFullOptions::Delta { delta }) => delta.unwrap_or(false),
+            Some(lsp::SemanticTokensFullOptions::Bool(false)) | None => {
+                return Task::ready(Ok(Vec::new()))
+            }
+        };
+
+        let server = server.clone();
+        let server_id = server.server_id();
+        let legend = options.legend.clone();
+        let text_document =
+            lsp::TextDocumentIdentifier::new(lsp::Url::from_file_path(file.abs_path(cx)).unwrap());
+        let snapshot = buffer.snapshot();
+        let previous = self
+            .buffer_semantic_tokens
+            .get(&buffer_id)
+            .filter(|previous| supports_delta && previous.server_id == server_id)
+            .and_then(|previous| Some((previous.result_id.clone()?, previous.data.clone())));
+
+        cx.spawn(move |this, mut cx| async move {
+            let (result_id, data) = if let Some((previous_result_id, mut data)) = previous {
+                let response = server
+                    .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                        lsp::SemanticTokensDeltaParams {
+                            text_document,
+                            previous_result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await?;
+                match response {
+                    Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                        (tokens.result_id, tokens.data)
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                        apply_semantic_token_edits(&mut data, delta.edits);
+                        (delta.result_id, data)
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                        apply_semantic_token_edits(&mut data, edits);
+                        (None, data)
+                    }
+                    None => (None, Vec::new()),
+                }
+            } else {
+                let response = server
+                    .request::<lsp::request::SemanticTokensFullRequest>(lsp::SemanticTokensParams {
+                        text_document,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    })
+                    .await?;
+                match response {
+                    Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                        (tokens.result_id, tokens.data)
+                    }
+                    Some(lsp::SemanticTokensResult::Partial(partial)) => (None, partial.data),
+                    None => (None, Vec::new()),
+                }
+            };
+
+            let (tokens, data) = cx
+                .background_executor()
+                .spawn(async move {
+                    let tokens = decode_semantic_tokens(&data, &legend, &snapshot);
+                    (tokens, data)
+                })
+                .await;
+            this.update(&mut cx, |this, _| {
+                this.buffer_semantic_tokens.insert(
+                    buffer_id,
+                    BufferSemanticTokens {
+                        server_id,
+                        result_id,
+                        data,
+                    },
+                );
+            })?;
+            Ok(tokens)
+        })
+    }
+
+    pub(crate) fn forget_semantic_tokens(&mut self, buffer_id: BufferId) {
+        self.semantic_token_tasks.remove(&buffer_id);
+        self.buffer_semantic_tokens.remove(&buffer_id);
+    }
+
+    pub(crate) async fn handle_get_semantic_tokens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        _: Arc<client::Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, _| {
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", envelope.payload.buffer_id))
+        })??;
+        let version = deserialize_version(&envelope.payload.version);
+        buffer
+            .update(&mut cx, |buffer, _| buffer.wait_for_version(version))?
+            .await
+            .context("waiting for buffer version")?;
+
+        let tokens = this
+            .update(&mut cx, |this, cx| this.semantic_tokens(&buffer, cx))?
+            .await?;
+        buffer.update(&mut cx, |buffer, _| proto::GetSemanticTokensResponse {
+            tokens: tokens
+                .into_iter()
+                .map(|token| proto::SemanticToken {
+                    start: Some(serialize_anchor(&token.range.start)),
+                    end: Some(serialize_anchor(&token.range.end)),
+                    token_type: token.token_type.to_string(),
+                    modifiers: token
+                        .modifiers
+                        .iter()
+                        .map(|modifier| modifier.to_string())
+                        .collect(),
+                })
+                .collect(),
+            version: serialize_version(&buffer.version()),
+        })
+    }
+
+    pub(crate) async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        _: Arc<client::Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| this.refresh_all_semantic_tokens(cx))
+    }
+}
+
+fn semantic_tokens_options(server: &LanguageServer) -> Option<&lsp::SemanticTokensOptions> {
+    match server.capabilities().semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => Some(options),
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(&options.semantic_tokens_options)
+        }
+    }
+}
+
+/// Applies the edits from a delta response to the previously reported tokens.
+/// Edit offsets refer to the protocol's integer encoding, in which each token
+/// occupies five integers.
+fn apply_semantic_token_edits(
+    data: &mut Vec<lsp::SemanticToken>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) {
+    edits.sort_by_key(|edit| Reverse(edit.start));
+    for edit in edits {
+        let start = (edit.start as usize / 5).min(data.len());
+        let end = (start + edit.delete_count as usize / 5).min(data.len());
+        data.splice(start..end, edit.data.unwrap_or_default());
+    }
+}
+
+fn decode_semantic_tokens(
+    data: &[lsp::SemanticToken],
+    legend: &lsp::SemanticTokensLegend,
+    snapshot: &BufferSnapshot,
+) -> Vec<SemanticToken> {
+    let token_types = legend
+        .token_types
+        .iter()
+        .map(|token_type| Arc::from(token_type.as_str()))
+        .collect::<Vec<Arc<str>>>();
+    let token_modifiers = legend
+        .token_modifiers
+        .iter()
+        .map(|modifier| Arc::from(modifier.as_str()))
+        .collect::<Vec<Arc<str>>>();
+
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut row = 0;
+    let mut column = 0;
+    for token in data {
+        if token.delta_line > 0 {
+            row += token.delta_line;
+            column = token.delta_start;
+        } else {
+            column += token.delta_start;
+        }
+
+        let Some(token_type) = token_types.get(token.token_type as usize) else {
+            continue;
+        };
+        let modifiers = token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| *ix < 32 && token.token_modifiers_bitset & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.clone())
+            .collect();
+        let start = snapshot.clip_point_utf16(Unclipped(PointUtf16::new(row, column)), Bias::Left);
+        let end = snapshot.clip_point_utf16(
+            Unclipped(PointUtf16::new(row, column + token.length)),
+            Bias::Left,
+        );
+        tokens.push(SemanticToken {
+            range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+            token_type: token_type.clone(),
+            modifiers,
+        });
+    }
+    tokens
+}
@@ -0,0 +1,166 @@
+use crate::WorktreeId;
+use anyhow::Result;
+use collections::{BTreeMap, VecDeque};
+use gpui::ModelContext;
+use std::{path::Path, sync::Arc};
+use task::{SpawnInTerminal, TaskId, TaskTemplate, TaskTemplates};
+
+/// The number of scheduled tasks that are remembered for ordering the task
+/// list by recency.
+const MAX_SCHEDULED_TASKS: usize = 50;
+
+/// Where a task template was defined.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TaskSourceKind {
+    /// The `tasks.json` file in the config directory.
+    Global,
+    /// A `.zed/tasks.json` file in the given directory of a worktree.
+    Worktree {
+        id: WorktreeId,
+        directory: Arc<Path>,
+    },
+}
+
+impl TaskSourceKind {
+    /// Describes the source for listing it next to its tasks.
+    pub fn label(&self) -> String {
+        match self {
+            TaskSourceKind::Global => "global".into(),
+            TaskSourceKind::Worktree { directory, .. } => {
+                let tasks_path = directory.join(&*util::paths::LOCAL_TASKS_RELATIVE_PATH);
+                tasks_path.to_string_lossy().into_owned()
+            }
+        }
+    }
+
+    /// Identifies a task by its source and label, so that scheduling it again
+    /// can reuse the terminal it ran in before.
+    pub fn task_id(&self, template: &TaskTemplate) -> TaskId {
+        match self {
+            TaskSourceKind::Global => TaskId(format!("global:{}", template.label)),
+            TaskSourceKind::Worktree { id, directory } => TaskId(format!(
+                "worktree-{}:{}:{}",
+                id.to_usize(),
+                directory.to_string_lossy(),
+                template.label
+            )),
+        }
+    }
+}
+
+/// The task templates that are defined for a project, along with the tasks
+/// that have been scheduled from them.
+#[derive(Default)]
+pub struct Inventory {
+    global_templates: Vec<TaskTemplate>,
+    worktree_templates: BTreeMap<(WorktreeId, Arc<Path>), Vec<TaskTemplate>>,
+    /// The most recently scheduled tasks, with the latest one at the back.
+    scheduled_tasks: VecDeque<SpawnInTerminal>,
+}
+
+impl Inventory {
+    /// Replaces the global templates with the ones in the given `tasks.json`
+    /// contents. The templates are kept as they were if the contents are
+    /// invalid.
+    pub fn set_global_templates(
+        &mut self,
+        content: Option<&str>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        self.global_templates = parse_templates(content)?;
+        cx.notify();
+        Ok(())
+    }
+
+    /// Replaces the templates defined in the `.zed/tasks.json` file in the
+    /// given directory of a worktree.
+    pub fn set_worktree_templates(
+        &mut self,
+        worktree_id: WorktreeId,
+        directory: Arc<Path>,
+        content: Option<&str>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let templates = parse_templates(content)?;
+        if templates.is_empty() {
+            self.worktree_templates.remove(&(worktree_id, directory));
+        } else {
+            self.worktree_templates
+                .insert((worktree_id, directory), templates);
+        }
+        cx.notify();
+        Ok(())
+    }
+
+    pub fn remove_worktree(&mut self, worktree_id: WorktreeId, cx: &mut ModelContext<Self>) {
+        self.worktree_templates
+            .retain(|(id, _), _| *id != worktree_id);
+        cx.notify();
+    }
+
+    /// Lists the templates that can be spawned in the given worktree, along
+    /// with where they were defined. Recently scheduled tasks come first,
+    /// followed by the worktree's templates and then the global ones.
+    pub fn list_templates(
+        &self,
+        worktree_id: Option<WorktreeId>,
+    ) -> Vec<(TaskSourceKind, TaskTemplate)> {
+        let worktree_templates = self
+            .worktree_templates
+            .iter()
+            .filter(|((id, _), _)| Some(*id) == worktree_id)
+            .flat_map(|((id, directory), templates)| {
+                templates.iter().map(|template| {
+                    let kind = TaskSourceKind::Worktree {
+                        id: *id,
+                        directory: directory.clone(),
+                    };
+                    (kind, template.clone())
+                })
+            });
+        let global_templates = self
+            .global_templates
+            .iter()
+            .map(|template| (TaskSourceKind::Global, template.clone()));
+
+        let mut templates = worktree_templates
+            .chain(global_templates)
+            .collect::<Vec<_>>();
+        // The sort is stable, so templates that haven't been scheduled keep
+        // the order they were defined in.
+        templates.sort_by_key(|(kind, template)| {
+            let id = kind.task_id(template);
+            self.scheduled_tasks
+                .iter()
+                .rev()
+                .position(|task| task.id == id)
+                .unwrap_or(usize::MAX)
+        });
+        templates
+    }
+
+    /// Records that a task was spawned, so that it can be rerun and is listed
+    /// first.
+    pub fn task_scheduled(&mut self, task: SpawnInTerminal) {
+        self.scheduled_tasks
+            .retain(|scheduled| scheduled.id != task.id);
+        if self.scheduled_tasks.len() == MAX_SCHEDULED_TASKS {
+            self.scheduled_tasks.pop_front();
+        }
+        self.scheduled_tasks.push_back(task);
+    }
+
+    pub fn last_scheduled_task(&self) -> Option<&SpawnInTerminal> {
+        self.scheduled_tasks.back()
+    }
+}
+
+fn parse_templates(content: Option<&str>) -> Result<Vec<TaskTemplate>> {
+    match content {
+        Some(content) if !content.trim().is_empty() => {
+            let templates = settings::parse_json_with_comments::<TaskTemplates>(content)?;
+            Ok(templates.0)
+        }
+        _ => Ok(Vec::new()),
+    }
+}
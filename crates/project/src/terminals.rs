@@ -1,23 +1,62 @@
 use crate::Project;
-use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use anyhow::{anyhow, bail, Result};
+use client::{proto, Client, TypedEnvelope};
+use collections::HashMap;
+use futures::{channel::mpsc::unbounded, StreamExt};
+use gpui::{
+    AnyWindowHandle, AsyncAppContext, Context, Entity, Model, ModelContext, Subscription, Task,
+    WeakModel,
+};
 use settings::Settings;
-use std::path::{Path, PathBuf};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use task::SpawnInTerminal;
 use terminal::{
-    terminal_settings::{self, TerminalSettings, VenvSettingsContent},
-    Terminal, TerminalBuilder,
+    terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
+    ScreenSnapshot, TaskState, Terminal, TerminalBuilder,
 };
+use util::ResultExt;
 
 #[cfg(target_os = "macos")]
 use std::os::unix::ffi::OsStrExt;
 
+/// How often the screen of a shared terminal is sent to guests while it
+/// changes.
+pub const SHARED_TERMINAL_UPDATE_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Default)]
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    /// The terminals that the host has shared with guests, by their id.
+    pub(crate) shared: HashMap<u64, SharedTerminal>,
+    /// The terminals that the host has shared, as seen by a guest.
+    pub(crate) remote: HashMap<u64, RemoteTerminal>,
+}
+
+pub(crate) struct SharedTerminal {
+    terminal: WeakModel<Terminal>,
+    /// Whether guests that can edit the project may type into this terminal.
+    writable: bool,
+    pending_update: Option<Task<()>>,
+    _subscriptions: [Subscription; 2],
+}
+
+pub(crate) struct RemoteTerminal {
+    title: String,
+    screen: ScreenSnapshot,
+    writable: bool,
+    terminal: Option<WeakModel<Terminal>>,
 }
 
 impl Project {
     pub fn create_terminal(
         &mut self,
         working_directory: Option<PathBuf>,
+        spawn_task: Option<SpawnInTerminal>,
+        restored_scrollback: Option<String>,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
     ) -> anyhow::Result<Model<Terminal>> {
@@ -27,15 +66,36 @@ impl Project {
             ));
         } else {
             let settings = TerminalSettings::get_global(cx);
-            let python_settings = settings.detect_venv.clone();
-            let shell = settings.shell.clone();
+            let mut python_settings = settings.detect_venv.clone();
+            let mut working_directory = working_directory;
+            let mut shell = settings.shell.clone();
+            let mut env = settings.env.clone();
+            let mut task_state = None;
+
+            // Tasks run their command directly instead of the user's shell.
+            if let Some(spawn_task) = spawn_task {
+                task_state = Some(TaskState {
+                    id: spawn_task.id,
+                    label: spawn_task.label,
+                });
+                shell = Shell::WithArguments {
+                    program: spawn_task.command,
+                    args: spawn_task.args,
+                };
+                env.extend(spawn_task.env);
+                working_directory = spawn_task.cwd.or(working_directory);
+                python_settings = terminal_settings::VenvSettings::Off;
+            }
 
             let terminal = TerminalBuilder::new(
                 working_directory.clone(),
-                shell.clone(),
-                settings.env.clone(),
+                shell,
+                env,
                 Some(settings.blinking.clone()),
                 settings.alternate_scroll,
+                settings.shell_integration,
+                task_state,
+                restored_scrollback,
                 window,
             )
             .map(|builder| {
@@ -134,6 +194,362 @@ impl Project {
     pub fn local_terminal_handles(&self) -> &Vec<WeakModel<terminal::Terminal>> {
         &self.terminals.local_handles
     }
+
+    /// Shares a terminal with the guests of this project, who are sent its
+    /// screen whenever it changes. Returns the id that guests know it by.
+    pub fn share_terminal(
+        &mut self,
+        terminal: &Model<Terminal>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<u64> {
+        if !self.is_shared() {
+            bail!("terminals can only be shared in a shared project");
+        }
+
+        let terminal_id = terminal.entity_id().as_u64();
+        if self.terminals.shared.contains_key(&terminal_id) {
+            return Ok(terminal_id);
+        }
+
+        let subscription = cx.subscribe(terminal, move |this, _, event, cx| match event {
+            terminal::Event::Wakeup | terminal::Event::TitleChanged => {
+                this.schedule_shared_terminal_update(terminal_id, cx)
+            }
+            _ => {}
+        });
+        let release_subscription = cx.observe_release(terminal, move |this, _, cx| {
+            this.unshare_terminal(terminal_id, cx).log_err();
+        });
+        self.terminals.shared.insert(
+            terminal_id,
+            SharedTerminal {
+                terminal: terminal.downgrade(),
+                writable: false,
+                pending_update: None,
+                _subscriptions: [subscription, release_subscription],
+            },
+        );
+        self.send_shared_terminal_update(terminal_id, cx)?;
+        cx.notify();
+        Ok(terminal_id)
+    }
+
+    pub fn unshare_terminal(
+        &mut self,
+        terminal_id: u64,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        if self.terminals.shared.remove(&terminal_id).is_none() {
+            bail!("attempted to unshare a terminal that isn't shared");
+        }
+        cx.notify();
+        if let Some(project_id) = self.remote_id() {
+            self.client.send(proto::UnshareTerminal {
+                project_id,
+                terminal_id,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Allows or disallows guests that can edit the project from typing into
+    /// a shared terminal. Shared terminals are read-only until allowed.
+    pub fn set_terminal_writable(
+        &mut self,
+        terminal_id: u64,
+        writable: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let project_id = self
+            .remote_id()
+            .ok_or_else(|| anyhow!("project is not shared"))?;
+        let shared = self
+            .terminals
+            .shared
+            .get_mut(&terminal_id)
+            .ok_or_else(|| anyhow!("attempted to change a terminal that isn't shared"))?;
+        shared.writable = writable;
+        cx.notify();
+        self.client.send(proto::SetTerminalWritable {
+            project_id,
+            terminal_id,
+            writable,
+        })?;
+        Ok(())
+    }
+
+    /// Whether guests are allowed to type into the given shared terminal.
+    pub fn is_terminal_writable(&self, terminal_id: u64) -> bool {
+        self.terminals
+            .shared
+            .get(&terminal_id)
+            .map(|shared| shared.writable)
+            .or_else(|| {
+                self.terminals
+                    .remote
+                    .get(&terminal_id)
+                    .map(|remote| remote.writable)
+            })
+            .unwrap_or(false)
+    }
+
+    /// The id that collaborators know a terminal by, if it's shared.
+    pub fn shared_terminal_id(&self, terminal: &Model<Terminal>) -> Option<u64> {
+        let terminal_id = terminal.entity_id().as_u64();
+        if self.terminals.shared.contains_key(&terminal_id) {
+            return Some(terminal_id);
+        }
+        self.terminals
+            .remote
+            .iter()
+            .find(|(_, remote)| {
+                remote
+                    .terminal
+                    .as_ref()
+                    .map_or(false, |remote| remote.entity_id() == terminal.entity_id())
+            })
+            .map(|(terminal_id, _)| *terminal_id)
+    }
+
+    /// The ids of the terminals that the host has shared, as seen by a guest.
+    pub fn remote_terminal_ids(&self) -> impl Iterator<Item = u64> + '_ {
+        self.terminals.remote.keys().copied()
+    }
+
+    /// Opens a terminal that the host of this project has shared, which shows
+    /// the host's screen and sends input to the host's shell.
+    pub fn open_shared_terminal(
+        &mut self,
+        terminal_id: u64,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<Model<Terminal>> {
+        let project_id = self
+            .remote_id()
+            .filter(|_| self.is_remote())
+            .ok_or_else(|| anyhow!("only guests can open shared terminals"))?;
+        let remote = self
+            .terminals
+            .remote
+            .get_mut(&terminal_id)
+            .ok_or_else(|| anyhow!("unknown terminal id {terminal_id}"))?;
+        if let Some(terminal) = remote.terminal.as_ref().and_then(|t| t.upgrade()) {
+            return Ok(terminal);
+        }
+
+        let (input_tx, mut input_rx) = unbounded::<Vec<u8>>();
+        let terminal = cx.new_model(|cx| {
+            let mut terminal =
+                TerminalBuilder::new_remote(remote.title.clone(), input_tx).subscribe(cx);
+            terminal.set_remote_screen(remote.title.clone(), &remote.screen, cx);
+            terminal
+        });
+        remote.terminal = Some(terminal.downgrade());
+
+        cx.spawn(|this, mut cx| async move {
+            while let Some(mut input) = input_rx.next().await {
+                while let Ok(Some(more_input)) = input_rx.try_next() {
+                    input.extend(more_input);
+                }
+                // Guests without write access to the project, or to this
+                // terminal, can watch it but can't type into it.
+                let request = this.update(&mut cx, |this, _| {
+                    (!this.is_read_only() && this.is_terminal_writable(terminal_id)).then(|| {
+                        this.client.request(proto::TerminalInput {
+                            project_id,
+                            terminal_id,
+                            input,
+                        })
+                    })
+                })?;
+                if let Some(request) = request {
+                    request.await.log_err();
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach();
+
+        Ok(terminal)
+    }
+
+    /// Records the latest state of a terminal that the host has shared,
+    /// redrawing it if it's open.
+    pub fn update_remote_terminal(
+        &mut self,
+        terminal_id: u64,
+        title: String,
+        screen: ScreenSnapshot,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let remote = self
+            .terminals
+            .remote
+            .entry(terminal_id)
+            .or_insert_with(|| RemoteTerminal {
+                title: String::new(),
+                screen: screen.clone(),
+                writable: false,
+                terminal: None,
+            });
+        if let Some(terminal) = remote.terminal.as_ref().and_then(|t| t.upgrade()) {
+            terminal.update(cx, |terminal, cx| {
+                terminal.set_remote_screen(title.clone(), &screen, cx)
+            });
+        }
+        remote.title = title;
+        remote.screen = screen;
+        cx.notify();
+    }
+
+    /// Sends the screens of all shared terminals to the guests, such as when
+    /// a guest joins the project.
+    pub(crate) fn resend_shared_terminals(&self, cx: &ModelContext<Self>) {
+        for (terminal_id, shared) in &self.terminals.shared {
+            self.send_shared_terminal_update(*terminal_id, cx).log_err();
+            if let Some(project_id) = self.remote_id().filter(|_| shared.writable) {
+                self.client
+                    .send(proto::SetTerminalWritable {
+                        project_id,
+                        terminal_id: *terminal_id,
+                        writable: true,
+                    })
+                    .log_err();
+            }
+        }
+    }
+
+    fn schedule_shared_terminal_update(&mut self, terminal_id: u64, cx: &mut ModelContext<Self>) {
+        let Some(shared) = self.terminals.shared.get_mut(&terminal_id) else {
+            return;
+        };
+        if shared.pending_update.is_some() {
+            return;
+        }
+
+        shared.pending_update = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(SHARED_TERMINAL_UPDATE_INTERVAL)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(shared) = this.terminals.shared.get_mut(&terminal_id) {
+                    shared.pending_update = None;
+                    this.send_shared_terminal_update(terminal_id, cx).log_err();
+                }
+            })
+            .ok();
+        }));
+    }
+
+    fn send_shared_terminal_update(&self, terminal_id: u64, cx: &ModelContext<Self>) -> Result<()> {
+        let project_id = self
+            .remote_id()
+            .ok_or_else(|| anyhow!("project is not shared"))?;
+        let terminal = self
+            .terminals
+            .shared
+            .get(&terminal_id)
+            .and_then(|shared| shared.terminal.upgrade())
+            .ok_or_else(|| anyhow!("unknown terminal id {terminal_id}"))?;
+        let terminal = terminal.read(cx);
+        self.client.send(proto::UpdateTerminal {
+            project_id,
+            terminal_id,
+            title: terminal.title(false),
+            screen: Some(serialize_terminal_screen(terminal.screen_snapshot())),
+        })
+    }
+
+    pub(crate) async fn handle_update_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateTerminal>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let payload = envelope.payload;
+        let screen = payload
+            .screen
+            .ok_or_else(|| anyhow!("missing terminal screen"))?;
+        this.update(&mut cx, |this, cx| {
+            this.update_remote_terminal(
+                payload.terminal_id,
+                payload.title,
+                deserialize_terminal_screen(screen),
+                cx,
+            )
+        })
+    }
+
+    pub(crate) async fn handle_unshare_terminal(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UnshareTerminal>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            let remote = this.terminals.remote.remove(&envelope.payload.terminal_id);
+            if let Some(terminal) = remote.and_then(|remote| remote.terminal?.upgrade()) {
+                terminal.update(cx, |_, cx| cx.emit(terminal::Event::CloseTerminal));
+            }
+            cx.notify();
+        })
+    }
+
+    pub(crate) async fn handle_set_terminal_writable(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SetTerminalWritable>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            if let Some(remote) = this.terminals.remote.get_mut(&envelope.payload.terminal_id) {
+                remote.writable = envelope.payload.writable;
+                cx.notify();
+            }
+        })
+    }
+
+    pub(crate) async fn handle_terminal_input(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::TerminalInput>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let terminal_id = envelope.payload.terminal_id;
+        this.update(&mut cx, |this, cx| {
+            let shared = this
+                .terminals
+                .shared
+                .get(&terminal_id)
+                .ok_or_else(|| anyhow!("unknown terminal id {terminal_id}"))?;
+            if !shared.writable {
+                bail!("terminal {terminal_id} is read-only");
+            }
+            let terminal = shared
+                .terminal
+                .upgrade()
+                .ok_or_else(|| anyhow!("unknown terminal id {terminal_id}"))?;
+            terminal.update(cx, |terminal, _| {
+                terminal.input_bytes(envelope.payload.input)
+            });
+            anyhow::Ok(proto::Ack {})
+        })?
+    }
+}
+
+pub fn serialize_terminal_screen(screen: ScreenSnapshot) -> proto::TerminalScreen {
+    proto::TerminalScreen {
+        columns: screen.columns as u32,
+        lines: screen.screen_lines as u32,
+        text: screen.text,
+    }
+}
+
+pub fn deserialize_terminal_screen(screen: proto::TerminalScreen) -> ScreenSnapshot {
+    ScreenSnapshot {
+        columns: screen.columns as usize,
+        screen_lines: screen.lines as usize,
+        text: screen.text,
+    }
 }
 
 // TODO: Add a few tests for adding and removing terminal tabs
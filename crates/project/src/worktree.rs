@@ -255,6 +255,12 @@ pub struct LocalRepositoryEntry {
     pub(crate) git_dir_path: Arc<Path>,
 }
 
+impl LocalRepositoryEntry {
+    pub fn repo(&self) -> &Arc<Mutex<dyn GitRepository>> {
+        &self.repo_ptr
+    }
+}
+
 impl Deref for LocalSnapshot {
     type Target = Snapshot;
 
@@ -2025,7 +2031,7 @@ impl LocalSnapshot {
         self.git_repositories.get(&repo.work_directory.0)
     }
 
-    pub(crate) fn local_repo_for_path(
+    pub fn local_repo_for_path(
         &self,
         path: &Path,
     ) -> Option<(RepositoryWorkDirectory, &LocalRepositoryEntry)> {
@@ -1,5 +1,17 @@
+use anyhow::{anyhow, Context as _};
 use async_tungstenite::tungstenite::Message as WebSocketMessage;
-use futures::{SinkExt as _, StreamExt as _};
+use futures::{
+    channel::mpsc, AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _, SinkExt as _,
+    StreamExt as _,
+};
+use std::io;
+
+const FRAME_HEADER_LEN: usize = 5;
+const FRAME_BINARY: u8 = 0;
+const FRAME_PING: u8 = 1;
+const FRAME_PONG: u8 = 2;
+const FRAME_CLOSE: u8 = 3;
+const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
 
 pub struct Connection {
     pub(crate) tx:
@@ -32,6 +44,82 @@ impl Connection {
         self.tx.send(message).await
     }
 
+    /// Creates a connection over a pair of byte streams, such as the stdin and stdout of a
+    /// child process. Each message is written as a frame consisting of a one byte kind, a
+    /// little-endian `u32` length and the payload.
+    pub fn framed<R, W>(reader: R, writer: W) -> Self
+    where
+        R: 'static + Send + Unpin + AsyncRead,
+        W: 'static + Send + Unpin + AsyncWrite,
+    {
+        let tx = futures::sink::unfold(writer, |mut writer, message| async move {
+            let (kind, payload) = match message {
+                WebSocketMessage::Binary(payload) => (FRAME_BINARY, payload),
+                WebSocketMessage::Ping(payload) => (FRAME_PING, payload),
+                WebSocketMessage::Pong(payload) => (FRAME_PONG, payload),
+                WebSocketMessage::Close(_) => (FRAME_CLOSE, Vec::new()),
+                WebSocketMessage::Text(_) => return Err(anyhow!("text frames are not supported")),
+            };
+            let len = u32::try_from(payload.len()).context("frame is too large")?;
+            let mut header = [0; FRAME_HEADER_LEN];
+            header[0] = kind;
+            header[1..].copy_from_slice(&len.to_le_bytes());
+            writer.write_all(&header).await?;
+            writer.write_all(&payload).await?;
+            writer.flush().await?;
+            anyhow::Ok(writer)
+        });
+
+        let rx = futures::stream::unfold(Some(reader), |reader| async move {
+            let mut reader = reader?;
+            let mut header = [0; FRAME_HEADER_LEN];
+            match reader.read_exact(&mut header).await {
+                Ok(()) => {}
+                Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return None,
+                Err(error) => return Some((Err(error.into()), None)),
+            }
+
+            let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+            if len > MAX_FRAME_LEN {
+                return Some((Err(anyhow!("frame of {len} bytes is too large")), None));
+            }
+            let mut payload = vec![0; len];
+            if let Err(error) = reader.read_exact(&mut payload).await {
+                return Some((Err(error.into()), None));
+            }
+
+            let message = match header[0] {
+                FRAME_BINARY => WebSocketMessage::Binary(payload),
+                FRAME_PING => WebSocketMessage::Ping(payload),
+                FRAME_PONG => WebSocketMessage::Pong(payload),
+                FRAME_CLOSE => WebSocketMessage::Close(None),
+                kind => return Some((Err(anyhow!("invalid frame kind {kind}")), None)),
+            };
+            Some((Ok(message), Some(reader)))
+        });
+
+        Self {
+            tx: Box::new(Box::pin(tx)),
+            rx: Box::new(Box::pin(rx)),
+        }
+    }
+
+    /// Creates two connections whose messages are delivered to each other in memory.
+    pub fn pair() -> (Self, Self) {
+        let (a_tx, a_rx) = mpsc::unbounded::<WebSocketMessage>();
+        let (b_tx, b_rx) = mpsc::unbounded::<WebSocketMessage>();
+        (
+            Self {
+                tx: Box::new(a_tx.sink_map_err(|error| anyhow!(error))),
+                rx: Box::new(b_rx.map(Ok)),
+            },
+            Self {
+                tx: Box::new(b_tx.sink_map_err(|error| anyhow!(error))),
+                rx: Box::new(a_rx.map(Ok)),
+            },
+        )
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn in_memory(
         executor: gpui::BackgroundExecutor,
@@ -58,8 +146,6 @@ impl Connection {
             Box<dyn Send + Unpin + futures::Sink<WebSocketMessage, Error = anyhow::Error>>,
             Box<dyn Send + Unpin + futures::Stream<Item = Result<WebSocketMessage, anyhow::Error>>>,
         ) {
-            use anyhow::anyhow;
-            use futures::channel::mpsc;
             use std::io::{Error, ErrorKind};
 
             let (tx, rx) = mpsc::unbounded::<WebSocketMessage>();
@@ -106,3 +192,46 @@ impl Connection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use smol::process::{Command, Stdio};
+
+    #[test]
+    fn test_framed_connection_over_subprocess() {
+        smol::block_on(async {
+            // `cat` echoes every frame back, so the connection receives what it sent.
+            let mut child = Command::new("cat")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            let Connection { mut tx, mut rx } =
+                Connection::framed(child.stdout.take().unwrap(), child.stdin.take().unwrap());
+
+            let messages = [
+                WebSocketMessage::Binary(vec![1, 2, 3]),
+                WebSocketMessage::Ping(Vec::new()),
+                WebSocketMessage::Binary(vec![0; 100_000]),
+                WebSocketMessage::Pong(vec![4]),
+                WebSocketMessage::Close(None),
+            ];
+            for message in messages.clone() {
+                tx.send(message).await.unwrap();
+            }
+            for message in messages {
+                assert_eq!(rx.next().await.unwrap().unwrap(), message);
+            }
+            assert!(tx
+                .send(WebSocketMessage::Text("hello".into()))
+                .await
+                .is_err());
+
+            // Closing the child's stdin ends the stream once `cat` exits.
+            drop(tx);
+            assert!(rx.next().await.is_none());
+            assert!(child.status().await.unwrap().success());
+        });
+    }
+}
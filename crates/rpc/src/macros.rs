@@ -52,6 +52,11 @@ macro_rules! messages {
 #[macro_export]
 macro_rules! request_messages {
     ($(($request_name:ident, $response_name:ident)),* $(,)?) => {
+        pub fn is_request(envelope: &dyn AnyTypedEnvelope) -> bool {
+            let type_id = envelope.payload_type_id();
+            $(type_id == TypeId::of::<$request_name>())||*
+        }
+
         $(impl RequestMessage for $request_name {
             type Response = $response_name;
         })*
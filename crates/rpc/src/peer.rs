@@ -471,6 +471,83 @@ impl Peer {
         Ok(())
     }
 
+    /// Forwards a message without knowing its type, preserving the connection it came from
+    /// as the original sender.
+    pub fn forward_any_send(
+        &self,
+        sender_id: ConnectionId,
+        receiver_id: ConnectionId,
+        message: Box<dyn AnyTypedEnvelope>,
+    ) -> Result<()> {
+        let connection = self.connection_state(receiver_id)?;
+        let message_id = connection
+            .next_message_id
+            .fetch_add(1, atomic::Ordering::SeqCst);
+        connection
+            .outgoing_tx
+            .unbounded_send(proto::Message::Envelope(message.into_envelope(
+                message_id,
+                None,
+                Some(sender_id.into()),
+            )))?;
+        Ok(())
+    }
+
+    /// Forwards a request without knowing its type. The raw response envelope is returned,
+    /// including errors, so that it can be relayed with [`Peer::respond_with_envelope`].
+    pub fn forward_any_request(
+        &self,
+        sender_id: ConnectionId,
+        receiver_id: ConnectionId,
+        request: Box<dyn AnyTypedEnvelope>,
+    ) -> impl Future<Output = Result<proto::Envelope>> {
+        let (tx, rx) = oneshot::channel();
+        let send = self.connection_state(receiver_id).and_then(|connection| {
+            let message_id = connection.next_message_id.fetch_add(1, SeqCst);
+            connection
+                .response_channels
+                .lock()
+                .as_mut()
+                .ok_or_else(|| anyhow!("connection was closed"))?
+                .insert(message_id, tx);
+            connection
+                .outgoing_tx
+                .unbounded_send(proto::Message::Envelope(request.into_envelope(
+                    message_id,
+                    None,
+                    Some(sender_id.into()),
+                )))
+                .map_err(|_| anyhow!("connection was closed"))?;
+            Ok(())
+        });
+        async move {
+            send?;
+            let (response, _barrier) = rx.await.map_err(|_| anyhow!("connection was closed"))?;
+            Ok(response)
+        }
+    }
+
+    pub fn respond_with_envelope(
+        &self,
+        receiver_id: ConnectionId,
+        responding_to: u32,
+        response: proto::Envelope,
+    ) -> Result<()> {
+        let connection = self.connection_state(receiver_id)?;
+        let message_id = connection
+            .next_message_id
+            .fetch_add(1, atomic::Ordering::SeqCst);
+        connection
+            .outgoing_tx
+            .unbounded_send(proto::Message::Envelope(proto::Envelope {
+                id: message_id,
+                responding_to: Some(responding_to),
+                original_sender_id: None,
+                payload: response.payload,
+            }))?;
+        Ok(())
+    }
+
     pub fn respond<T: RequestMessage>(
         &self,
         receipt: Receipt<T>,
@@ -48,6 +48,12 @@ pub trait AnyTypedEnvelope: 'static + Send + Sync {
     fn original_sender_id(&self) -> Option<PeerId>;
     fn sender_id(&self) -> ConnectionId;
     fn message_id(&self) -> u32;
+    fn into_envelope(
+        self: Box<Self>,
+        id: u32,
+        responding_to: Option<u32>,
+        original_sender_id: Option<PeerId>,
+    ) -> Envelope;
 }
 
 pub enum MessagePriority {
@@ -87,6 +93,16 @@ impl<T: EnvelopedMessage> AnyTypedEnvelope for TypedEnvelope<T> {
     fn message_id(&self) -> u32 {
         self.message_id
     }
+
+    fn into_envelope(
+        self: Box<Self>,
+        id: u32,
+        responding_to: Option<u32>,
+        original_sender_id: Option<PeerId>,
+    ) -> Envelope {
+        self.payload
+            .into_envelope(id, responding_to, original_sender_id)
+    }
 }
 
 impl PeerId {
@@ -142,6 +158,8 @@ messages!(
     (ApplyCodeActionResponse, Background),
     (ApplyCompletionAdditionalEdits, Background),
     (ApplyCompletionAdditionalEditsResponse, Background),
+    (BlameBuffer, Background),
+    (BlameBufferResponse, Background),
     (BufferReloaded, Foreground),
     (BufferSaved, Foreground),
     (Call, Foreground),
@@ -180,20 +198,33 @@ messages!(
     (GetDefinitionResponse, Background),
     (GetDocumentHighlights, Background),
     (GetDocumentHighlightsResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
     (GetHover, Background),
     (GetHoverResponse, Background),
+    (GetImplementation, Background),
+    (GetImplementationResponse, Background),
+    (GetIncomingCalls, Background),
     (GetNotifications, Foreground),
     (GetNotificationsResponse, Foreground),
+    (GetOutgoingCalls, Background),
     (GetPrivateUserInfo, Foreground),
     (GetPrivateUserInfoResponse, Foreground),
     (GetProjectSymbols, Background),
     (GetProjectSymbolsResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (GetSignatureHelp, Background),
+    (GetSignatureHelpResponse, Background),
+    (GetSubtypes, Background),
+    (GetSupertypes, Background),
     (GetReferences, Background),
     (GetReferencesResponse, Background),
     (GetTypeDefinition, Background),
     (GetTypeDefinitionResponse, Background),
     (GetUsers, Foreground),
     (Hello, Foreground),
+    (HierarchyItemsResponse, Background),
     (IncomingCall, Foreground),
     (InlayHints, Background),
     (InlayHintsResponse, Background),
@@ -211,6 +242,8 @@ messages!(
     (LeaveChannelChat, Foreground),
     (LeaveProject, Foreground),
     (LeaveRoom, Foreground),
+    (LoadHeadText, Background),
+    (LoadHeadTextResponse, Background),
     (MarkNotificationRead, Foreground),
     (MoveChannel, Foreground),
     (OnTypeFormatting, Background),
@@ -223,10 +256,13 @@ messages!(
     (PerformRename, Background),
     (PerformRenameResponse, Background),
     (Ping, Foreground),
+    (PrepareCallHierarchy, Background),
     (PrepareRename, Background),
     (PrepareRenameResponse, Background),
+    (PrepareTypeHierarchy, Background),
     (ProjectEntryResponse, Foreground),
     (RefreshInlayHints, Foreground),
+    (RefreshSemanticTokens, Foreground),
     (RejoinChannelBuffers, Foreground),
     (RejoinChannelBuffersResponse, Foreground),
     (RejoinRoom, Foreground),
@@ -250,6 +286,7 @@ messages!(
     (RoomUpdated, Foreground),
     (SaveBuffer, Foreground),
     (SetChannelMemberRole, Foreground),
+    (SetIndexText, Foreground),
     (SetChannelVisibility, Foreground),
     (SearchProject, Background),
     (SearchProjectResponse, Background),
@@ -285,6 +322,10 @@ messages!(
     (LspExtExpandMacro, Background),
     (LspExtExpandMacroResponse, Background),
     (SetRoomParticipantRole, Foreground),
+    (UpdateTerminal, Foreground),
+    (UnshareTerminal, Foreground),
+    (TerminalInput, Foreground),
+    (SetTerminalWritable, Foreground),
 );
 
 request_messages!(
@@ -293,6 +334,7 @@ request_messages!(
         ApplyCompletionAdditionalEdits,
         ApplyCompletionAdditionalEditsResponse
     ),
+    (BlameBuffer, BlameBufferResponse),
     (Call, Ack),
     (CancelCall, Ack),
     (CopyProjectEntry, ProjectEntryResponse),
@@ -314,6 +356,16 @@ request_messages!(
     (GetDefinition, GetDefinitionResponse),
     (GetDocumentHighlights, GetDocumentHighlightsResponse),
     (GetHover, GetHoverResponse),
+    (GetSignatureHelp, GetSignatureHelpResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetImplementation, GetImplementationResponse),
+    (PrepareCallHierarchy, HierarchyItemsResponse),
+    (PrepareTypeHierarchy, HierarchyItemsResponse),
+    (GetIncomingCalls, HierarchyItemsResponse),
+    (GetOutgoingCalls, HierarchyItemsResponse),
+    (GetSupertypes, HierarchyItemsResponse),
+    (GetSubtypes, HierarchyItemsResponse),
     (GetNotifications, GetNotificationsResponse),
     (GetPrivateUserInfo, GetPrivateUserInfoResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
@@ -330,6 +382,7 @@ request_messages!(
     (JoinRoom, JoinRoomResponse),
     (LeaveChannelBuffer, Ack),
     (LeaveRoom, Ack),
+    (LoadHeadText, LoadHeadTextResponse),
     (MarkNotificationRead, Ack),
     (MoveChannel, Ack),
     (OnTypeFormatting, OnTypeFormattingResponse),
@@ -360,6 +413,7 @@ request_messages!(
     (SearchProject, SearchProjectResponse),
     (SendChannelMessage, SendChannelMessageResponse),
     (SetChannelMemberRole, Ack),
+    (SetIndexText, Ack),
     (SetChannelVisibility, Ack),
     (ShareProject, ShareProjectResponse),
     (SynchronizeBuffers, SynchronizeBuffersResponse),
@@ -370,6 +424,7 @@ request_messages!(
     (UpdateWorktree, Ack),
     (LspExtExpandMacro, LspExtExpandMacroResponse),
     (SetRoomParticipantRole, Ack),
+    (TerminalInput, Ack),
 );
 
 entity_messages!(
@@ -377,6 +432,7 @@ entity_messages!(
     AddProjectCollaborator,
     ApplyCodeAction,
     ApplyCompletionAdditionalEdits,
+    BlameBuffer,
     BufferReloaded,
     BufferSaved,
     CopyProjectEntry,
@@ -390,19 +446,31 @@ entity_messages!(
     GetDefinition,
     GetDocumentHighlights,
     GetHover,
+    GetImplementation,
+    GetIncomingCalls,
+    GetOutgoingCalls,
     GetProjectSymbols,
     GetReferences,
+    GetSemanticTokens,
+    GetFoldingRanges,
+    GetSignatureHelp,
+    GetSubtypes,
+    GetSupertypes,
     GetTypeDefinition,
     InlayHints,
     JoinProject,
     LeaveProject,
+    LoadHeadText,
     OnTypeFormatting,
     OpenBufferById,
     OpenBufferByPath,
     OpenBufferForSymbol,
     PerformRename,
+    PrepareCallHierarchy,
     PrepareRename,
+    PrepareTypeHierarchy,
     RefreshInlayHints,
+    RefreshSemanticTokens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
@@ -410,6 +478,7 @@ entity_messages!(
     ResolveInlayHint,
     SaveBuffer,
     SearchProject,
+    SetIndexText,
     StartLanguageServer,
     SynchronizeBuffers,
     UnshareProject,
@@ -423,6 +492,10 @@ entity_messages!(
     UpdateWorktree,
     UpdateWorktreeSettings,
     LspExtExpandMacro,
+    UpdateTerminal,
+    UnshareTerminal,
+    TerminalInput,
+    SetTerminalWritable,
 );
 
 entity_messages!(
@@ -8,7 +8,6 @@ mod semantic_index_tests;
 
 use crate::semantic_index_settings::SemanticIndexSettings;
 use ai::embedding::{Embedding, EmbeddingProvider};
-use ai::providers::open_ai::OpenAiEmbeddingProvider;
 use anyhow::{anyhow, Context as _, Result};
 use collections::{BTreeMap, HashMap, HashSet};
 use db::VectorDatabase;
@@ -45,6 +44,7 @@ use workspace::Workspace;
 const SEMANTIC_INDEX_VERSION: usize = 11;
 const BACKGROUND_INDEXING_DELAY: Duration = Duration::from_secs(5 * 60);
 const EMBEDDING_QUEUE_FLUSH_TIMEOUT: Duration = Duration::from_millis(250);
+const DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-ada-002";
 
 lazy_static! {
     static ref OPENAI_API_KEY: Option<String> = env::var("OPENAI_API_KEY").ok();
@@ -58,9 +58,23 @@ pub fn init(
 ) {
     SemanticIndexSettings::register(cx);
 
+    let settings = SemanticIndexSettings::get_global(cx);
+    let provider = settings.provider.clone();
+    let embedding_model = settings.embedding_model.clone();
+
+    // Embeddings from different models can't be compared, so models other than
+    // the default one store them in their own database.
+    let db_file_name = if embedding_model.name == DEFAULT_EMBEDDING_MODEL {
+        "embeddings_db".to_string()
+    } else {
+        let model_name = embedding_model
+            .name
+            .replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        format!("embeddings_db_{model_name}")
+    };
     let db_file_path = EMBEDDINGS_DIR
         .join(Path::new(ReleaseChannel::global(cx).dev_name()))
-        .join("embeddings_db");
+        .join(db_file_name);
 
     cx.observe_new_views(
         |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
@@ -91,12 +105,18 @@ pub fn init(
     .detach();
 
     cx.spawn(move |cx| async move {
-        let embedding_provider =
-            OpenAiEmbeddingProvider::new(http_client, cx.background_executor().clone()).await;
+        let embedding_provider = provider
+            .embedding_provider(
+                embedding_model.name,
+                embedding_model.max_tokens,
+                http_client,
+                cx.background_executor().clone(),
+            )
+            .await;
         let semantic_index = SemanticIndex::new(
             fs,
             db_file_path,
-            Arc::new(embedding_provider),
+            embedding_provider,
             language_registry,
             cx.clone(),
         )
@@ -1,3 +1,4 @@
+use ai::providers::AiProvider;
 use anyhow;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -6,6 +7,17 @@ use settings::Settings;
 #[derive(Deserialize, Debug)]
 pub struct SemanticIndexSettings {
     pub enabled: bool,
+    pub provider: AiProvider,
+    pub embedding_model: EmbeddingModel,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct EmbeddingModel {
+    pub name: String,
+    /// The number of tokens the model accepts at once, which is looked up for
+    /// OpenAI's models.
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
 }
 
 /// Configuration of semantic index, an alternate search engine available in
@@ -16,6 +28,16 @@ pub struct SemanticIndexSettingsContent {
     ///
     /// Default: true
     pub enabled: Option<bool>,
+    /// The service to request embeddings from. Changing this setting requires
+    /// a restart to take effect.
+    ///
+    /// Default: { "name": "openai" }
+    pub provider: Option<AiProvider>,
+    /// The model to embed code with. Changing this setting requires a restart
+    /// to take effect.
+    ///
+    /// Default: { "name": "text-embedding-ada-002" }
+    pub embedding_model: Option<EmbeddingModel>,
 }
 
 impl Settings for SemanticIndexSettings {
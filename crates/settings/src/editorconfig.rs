@@ -0,0 +1,218 @@
+use collections::HashMap;
+use globset::{GlobBuilder, GlobMatcher};
+use std::{num::NonZeroU32, path::Path};
+
+/// The name of an [EditorConfig](https://editorconfig.org) file.
+pub const EDITORCONFIG_FILE_NAME: &str = ".editorconfig";
+
+/// A parsed `.editorconfig` file.
+#[derive(Debug, Default)]
+pub struct Editorconfig {
+    root: bool,
+    sections: Vec<EditorconfigSection>,
+}
+
+#[derive(Debug)]
+struct EditorconfigSection {
+    /// Matches the paths the section applies to, relative to the directory
+    /// containing the file. `None` if the section's glob is invalid.
+    matcher: Option<GlobMatcher>,
+    properties: Vec<(String, String)>,
+}
+
+/// The EditorConfig properties that apply to a file, limited to those that
+/// correspond to one of Zed's language settings.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EditorconfigProperties {
+    /// Whether to indent with tabs (`indent_style = tab`) or spaces.
+    pub indent_with_tabs: Option<bool>,
+    /// The width of an indentation level, from `indent_size` or `tab_width`.
+    pub indent_size: Option<NonZeroU32>,
+    pub insert_final_newline: Option<bool>,
+    pub trim_trailing_whitespace: Option<bool>,
+    /// The column to wrap at, from `max_line_length`.
+    pub max_line_length: Option<u32>,
+}
+
+impl Editorconfig {
+    /// Parses the contents of an `.editorconfig` file. Lines that can't be
+    /// parsed are ignored, as the EditorConfig specification requires.
+    pub fn parse(content: &str) -> Self {
+        let mut editorconfig = Self::default();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(glob) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+            {
+                editorconfig.sections.push(EditorconfigSection {
+                    matcher: section_matcher(glob),
+                    properties: Vec::new(),
+                });
+            } else if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim().to_lowercase();
+                let value = value.trim().to_lowercase();
+                match editorconfig.sections.last_mut() {
+                    Some(section) => section.properties.push((key, value)),
+                    None if key == "root" => editorconfig.root = value == "true",
+                    None => {}
+                }
+            }
+        }
+        editorconfig
+    }
+
+    /// Whether this file stops the search for `.editorconfig` files in
+    /// parent directories.
+    pub fn is_root(&self) -> bool {
+        self.root
+    }
+
+    /// Applies the properties of the sections matching the given path, which
+    /// is relative to the directory containing this file.
+    fn apply(&self, relative_path: &Path, properties: &mut HashMap<String, String>) {
+        for section in &self.sections {
+            let Some(matcher) = &section.matcher else {
+                continue;
+            };
+            if !matcher.is_match(relative_path) {
+                continue;
+            }
+            for (key, value) in &section.properties {
+                if value == "unset" {
+                    properties.remove(key);
+                } else {
+                    properties.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+impl EditorconfigProperties {
+    /// Resolves the properties for a file, given the `.editorconfig` files that
+    /// apply to it from the outermost directory inward, each with the file's
+    /// path relative to that `.editorconfig`'s directory.
+    pub fn resolve<'a>(
+        editorconfigs: impl IntoIterator<Item = (&'a Editorconfig, &'a Path)>,
+    ) -> Option<Self> {
+        let mut raw_properties = HashMap::default();
+        for (editorconfig, relative_path) in editorconfigs {
+            editorconfig.apply(relative_path, &mut raw_properties);
+        }
+        if raw_properties.is_empty() {
+            return None;
+        }
+
+        let bool_property = |key: &str| match raw_properties.get(key)?.as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        };
+        let size_property = |key: &str| raw_properties.get(key)?.parse::<NonZeroU32>().ok();
+
+        let indent_size = match raw_properties.get("indent_size").map(String::as_str) {
+            Some("tab") | None => size_property("tab_width"),
+            Some(_) => size_property("indent_size"),
+        };
+        let indent_with_tabs = match raw_properties.get("indent_style").map(String::as_str) {
+            Some("tab") => Some(true),
+            Some("space") => Some(false),
+            _ => None,
+        };
+        Some(Self {
+            indent_with_tabs,
+            indent_size,
+            insert_final_newline: bool_property("insert_final_newline"),
+            trim_trailing_whitespace: bool_property("trim_trailing_whitespace"),
+            max_line_length: size_property("max_line_length").map(NonZeroU32::get),
+        })
+    }
+}
+
+/// Builds the matcher for a section's glob. Globs without a `/` match files
+/// in any subdirectory, while the others are relative to the `.editorconfig`.
+fn section_matcher(glob: &str) -> Option<GlobMatcher> {
+    let pattern = if glob.contains('/') {
+        glob.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{glob}")
+    };
+    let glob = GlobBuilder::new(&pattern)
+        .literal_separator(true)
+        .build()
+        .ok()?;
+    Some(glob.compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_resolving_editorconfig_properties() {
+        let outer = Editorconfig::parse(indoc! {"
+            root = true
+
+            [*]
+            indent_style = space
+            indent_size = 4
+            insert_final_newline = true
+            trim_trailing_whitespace = true
+
+            [*.{md,txt}]
+            trim_trailing_whitespace = false
+
+            [Makefile]
+            indent_style = tab
+            indent_size = tab
+            tab_width = 8
+
+            [/src/generated/**]
+            max_line_length = 120
+        "});
+        let inner = Editorconfig::parse(indoc! {"
+            # Two-space indentation in here.
+            [*.rs]
+            indent_size = 2
+            insert_final_newline = unset
+        "});
+        assert!(outer.is_root());
+        assert!(!inner.is_root());
+
+        let resolve =
+            |path: &str| EditorconfigProperties::resolve([(&outer, Path::new(path))]).unwrap();
+        assert_eq!(
+            resolve("src/main.rs"),
+            EditorconfigProperties {
+                indent_with_tabs: Some(false),
+                indent_size: NonZeroU32::new(4),
+                insert_final_newline: Some(true),
+                trim_trailing_whitespace: Some(true),
+                max_line_length: None,
+            }
+        );
+        assert_eq!(
+            resolve("docs/README.md").trim_trailing_whitespace,
+            Some(false)
+        );
+        assert_eq!(resolve("Makefile").indent_with_tabs, Some(true));
+        assert_eq!(resolve("Makefile").indent_size, NonZeroU32::new(8));
+        assert_eq!(resolve("src/generated/a/b.rs").max_line_length, Some(120));
+        assert_eq!(resolve("lib/src/generated/b.rs").max_line_length, None);
+
+        let properties = EditorconfigProperties::resolve([
+            (&outer, Path::new("crates/a/lib.rs")),
+            (&inner, Path::new("lib.rs")),
+        ])
+        .unwrap();
+        assert_eq!(properties.indent_size, NonZeroU32::new(2));
+        assert_eq!(properties.insert_final_newline, None);
+        assert_eq!(properties.trim_trailing_whitespace, Some(true));
+    }
+}
@@ -1,5 +1,6 @@
+use crate::{Editorconfig, EditorconfigProperties};
 use anyhow::{anyhow, Context, Result};
-use collections::{btree_map, hash_map, BTreeMap, HashMap};
+use collections::{btree_map, hash_map, BTreeMap, BTreeSet, HashMap};
 use gpui::{AppContext, AsyncAppContext, Global};
 use lazy_static::lazy_static;
 use schemars::{gen::SchemaGenerator, schema::RootSchema, JsonSchema};
@@ -131,10 +132,17 @@ pub struct SettingsStore {
     raw_default_settings: serde_json::Value,
     raw_user_settings: serde_json::Value,
     raw_local_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
+    raw_editorconfigs: BTreeMap<(usize, Arc<Path>), (String, Editorconfig)>,
+    /// The files whose `.editorconfig` properties are resolved ahead of time.
+    editorconfig_file_paths: BTreeSet<(usize, Arc<Path>)>,
     tab_size_callback: Option<(
         TypeId,
         Box<dyn Fn(&dyn Any) -> Option<usize> + Send + Sync + 'static>,
     )>,
+    editorconfig_callback: Option<(
+        TypeId,
+        Box<dyn Fn(EditorconfigProperties) -> Box<dyn Any> + Send + Sync + 'static>,
+    )>,
 }
 
 impl Global for SettingsStore {}
@@ -146,7 +154,10 @@ impl Default for SettingsStore {
             raw_default_settings: serde_json::json!({}),
             raw_user_settings: serde_json::json!({}),
             raw_local_settings: Default::default(),
+            raw_editorconfigs: Default::default(),
+            editorconfig_file_paths: Default::default(),
             tab_size_callback: Default::default(),
+            editorconfig_callback: Default::default(),
         }
     }
 }
@@ -155,6 +166,8 @@ impl Default for SettingsStore {
 struct SettingValue<T> {
     global_value: Option<T>,
     local_values: Vec<(usize, Arc<Path>, T)>,
+    /// Values for individual files, which take precedence over the local values.
+    file_values: HashMap<usize, HashMap<Arc<Path>, T>>,
 }
 
 trait AnySettingValue: 'static + Send + Sync {
@@ -170,6 +183,8 @@ trait AnySettingValue: 'static + Send + Sync {
     fn value_for_path(&self, path: Option<(usize, &Path)>) -> &dyn Any;
     fn set_global_value(&mut self, value: Box<dyn Any>);
     fn set_local_value(&mut self, root_id: usize, path: Arc<Path>, value: Box<dyn Any>);
+    fn set_file_value(&mut self, root_id: usize, path: Arc<Path>, value: Option<Box<dyn Any>>);
+    fn clear_file_values(&mut self, root_id: usize);
     fn json_schema(
         &self,
         generator: &mut SchemaGenerator,
@@ -192,6 +207,7 @@ impl SettingsStore {
         let setting_value = entry.or_insert(Box::new(SettingValue::<T> {
             global_value: None,
             local_values: Vec::new(),
+            file_values: HashMap::default(),
         }));
 
         if let Some(default_settings) = setting_value
@@ -353,6 +369,19 @@ impl SettingsStore {
         ));
     }
 
+    /// Sets how the properties from `.editorconfig` files are applied to a
+    /// setting. The files' properties take precedence over the user's settings,
+    /// but not over local settings.
+    pub fn set_editorconfig_callback<T: Settings>(
+        &mut self,
+        from_properties: fn(EditorconfigProperties) -> T::FileContent,
+    ) {
+        self.editorconfig_callback = Some((
+            TypeId::of::<T>(),
+            Box::new(move |properties| Box::new(from_properties(properties))),
+        ));
+    }
+
     fn json_tab_size(&self) -> usize {
         const DEFAULT_JSON_TAB_SIZE: usize = 2;
 
@@ -422,6 +451,11 @@ impl SettingsStore {
     /// Add or remove a set of local settings via a JSON string.
     pub fn clear_local_settings(&mut self, root_id: usize, cx: &mut AppContext) -> Result<()> {
         self.raw_local_settings.retain(|k, _| k.0 != root_id);
+        self.raw_editorconfigs.retain(|k, _| k.0 != root_id);
+        self.editorconfig_file_paths.retain(|k| k.0 != root_id);
+        for setting_value in self.setting_values.values_mut() {
+            setting_value.clear_file_values(root_id);
+        }
         self.recompute_values(Some((root_id, "".as_ref())), cx)?;
         Ok(())
     }
@@ -432,6 +466,80 @@ impl SettingsStore {
             .map(|((_, path), content)| (path.clone(), serde_json::to_string(content).unwrap()))
     }
 
+    /// Returns the contents of the `.editorconfig` files in the given worktree,
+    /// along with the directories that contain them.
+    pub fn editorconfigs(&self, root_id: usize) -> impl '_ + Iterator<Item = (Arc<Path>, &str)> {
+        self.raw_editorconfigs
+            .range((root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
+            .map(|((_, path), (content, _))| (path.clone(), content.as_str()))
+    }
+
+    /// Add or remove an `.editorconfig` file in the given directory.
+    pub fn set_editorconfig(
+        &mut self,
+        root_id: usize,
+        path: Arc<Path>,
+        content: Option<&str>,
+        cx: &mut AppContext,
+    ) {
+        if let Some(content) = content {
+            self.raw_editorconfigs.insert(
+                (root_id, path.clone()),
+                (content.to_string(), Editorconfig::parse(content)),
+            );
+        } else {
+            self.raw_editorconfigs.remove(&(root_id, path.clone()));
+        }
+        self.recompute_editorconfig_values(Some((root_id, &path)), cx);
+    }
+
+    /// Starts resolving the `.editorconfig` properties for the file at the
+    /// given path, so that the settings retrieved for it include them.
+    pub fn add_editorconfig_file_path(
+        &mut self,
+        root_id: usize,
+        path: Arc<Path>,
+        cx: &mut AppContext,
+    ) {
+        if self.editorconfig_file_paths.insert((root_id, path.clone())) {
+            self.recompute_editorconfig_values(Some((root_id, &path)), cx);
+        }
+    }
+
+    /// Stops resolving the `.editorconfig` properties for the file at the given path.
+    pub fn remove_editorconfig_file_path(&mut self, root_id: usize, path: &Path) {
+        let path: Arc<Path> = path.into();
+        self.editorconfig_file_paths
+            .remove(&(root_id, path.clone()));
+        for setting_value in self.setting_values.values_mut() {
+            setting_value.set_file_value(root_id, path.clone(), None);
+        }
+    }
+
+    /// Returns the properties from the `.editorconfig` files that apply to the
+    /// given path, stopping at the innermost file that is marked as `root`.
+    fn editorconfig_properties(
+        &self,
+        root_id: usize,
+        path: &Path,
+    ) -> Option<EditorconfigProperties> {
+        let mut editorconfigs = self
+            .raw_editorconfigs
+            .range((root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
+            .filter_map(|((_, directory_path), (_, editorconfig))| {
+                let relative_path = path.strip_prefix(directory_path).ok()?;
+                Some((editorconfig, relative_path))
+            })
+            .collect::<Vec<_>>();
+        if let Some(root_ix) = editorconfigs
+            .iter()
+            .rposition(|(editorconfig, _)| editorconfig.is_root())
+        {
+            editorconfigs.drain(..root_ix);
+        }
+        EditorconfigProperties::resolve(editorconfigs)
+    }
+
     pub fn json_schema(
         &self,
         schema_params: &SettingsJsonSchemaParams,
@@ -600,8 +708,82 @@ impl SettingsStore {
                 }
             }
         }
+        self.recompute_editorconfig_values(changed_local_path, cx);
         Ok(())
     }
+
+    /// Reloads the values of the files whose `.editorconfig` properties are
+    /// resolved, limited to those within the given path if one is specified.
+    fn recompute_editorconfig_values(
+        &mut self,
+        changed_path: Option<(usize, &Path)>,
+        cx: &mut AppContext,
+    ) {
+        let Some((setting_type_id, from_properties)) = &self.editorconfig_callback else {
+            return;
+        };
+        let Some(setting_value) = self.setting_values.get(setting_type_id) else {
+            return;
+        };
+        let Some(default_settings) = setting_value
+            .deserialize_setting(&self.raw_default_settings)
+            .log_err()
+        else {
+            return;
+        };
+
+        let mut new_values = Vec::new();
+        for (root_id, path) in &self.editorconfig_file_paths {
+            if changed_path.map_or(false, |(changed_root_id, changed_path)| {
+                *root_id != changed_root_id || !path.starts_with(changed_path)
+            }) {
+                continue;
+            }
+            let Some(properties) = self.editorconfig_properties(*root_id, path) else {
+                new_values.push((*root_id, path.clone(), None));
+                continue;
+            };
+
+            // Apply the properties on top of the user's settings, followed by
+            // the local settings of the directories containing the file.
+            let mut settings_stack = Vec::new();
+            settings_stack.extend(
+                setting_value
+                    .deserialize_setting(&self.raw_user_settings)
+                    .log_err(),
+            );
+            if let Some(release_settings) = self
+                .raw_user_settings
+                .get(&*release_channel::RELEASE_CHANNEL_NAME)
+            {
+                settings_stack.extend(
+                    setting_value
+                        .deserialize_setting(release_settings)
+                        .log_err(),
+                );
+            }
+            settings_stack.push(DeserializedSetting(from_properties(properties)));
+            for ((_, directory_path), local_settings) in self
+                .raw_local_settings
+                .range((*root_id, Path::new("").into())..(root_id + 1, Path::new("").into()))
+            {
+                if path.starts_with(directory_path) {
+                    settings_stack
+                        .extend(setting_value.deserialize_setting(local_settings).log_err());
+                }
+            }
+
+            let value = setting_value
+                .load_setting(&default_settings, &settings_stack, cx)
+                .log_err();
+            new_values.push((*root_id, path.clone(), value));
+        }
+
+        let setting_value = self.setting_values.get_mut(setting_type_id).unwrap();
+        for (root_id, path, value) in new_values {
+            setting_value.set_file_value(root_id, path, value);
+        }
+    }
 }
 
 impl Debug for SettingsStore {
@@ -660,6 +842,13 @@ impl<T: Settings> AnySettingValue for SettingValue<T> {
 
     fn value_for_path(&self, path: Option<(usize, &Path)>) -> &dyn Any {
         if let Some((root_id, path)) = path {
+            if let Some(value) = self
+                .file_values
+                .get(&root_id)
+                .and_then(|values| values.get(path))
+            {
+                return value;
+            }
             for (settings_root_id, settings_path, value) in self.local_values.iter().rev() {
                 if root_id == *settings_root_id && path.starts_with(&settings_path) {
                     return value;
@@ -686,6 +875,19 @@ impl<T: Settings> AnySettingValue for SettingValue<T> {
         }
     }
 
+    fn set_file_value(&mut self, root_id: usize, path: Arc<Path>, value: Option<Box<dyn Any>>) {
+        let values = self.file_values.entry(root_id).or_default();
+        if let Some(value) = value {
+            values.insert(path, *value.downcast().unwrap());
+        } else {
+            values.remove(&path);
+        }
+    }
+
+    fn clear_file_values(&mut self, root_id: usize) {
+        self.file_values.remove(&root_id);
+    }
+
     fn json_schema(
         &self,
         generator: &mut SchemaGenerator,
@@ -6,14 +6,16 @@ use clap::Parser;
 use dialoguer::FuzzySelect;
 use gpui::{
     div, px, size, AnyView, AppContext, Bounds, Render, ViewContext, VisualContext, WindowBounds,
-    WindowOptions,
+    WindowContext, WindowOptions,
 };
 use log::LevelFilter;
 use settings::{default_settings, Settings, SettingsStore};
 use simplelog::SimpleLogger;
+use std::path::{Path, PathBuf};
 use strum::IntoEnumIterator;
 use theme::{ThemeRegistry, ThemeSettings};
 use ui::prelude::*;
+use util::ResultExt;
 
 use crate::assets::Assets;
 use crate::story_selector::{ComponentStory, StorySelector};
@@ -30,6 +32,13 @@ struct Args {
     /// If not provided, the default theme will be used.
     #[arg(long)]
     theme: Option<String>,
+
+    /// Render the first frame of the story to a PNG file at this path, then exit.
+    ///
+    /// Only supported on platforms that can render windows to images, such as
+    /// the headless Linux platform.
+    #[arg(long)]
+    screenshot: Option<PathBuf>,
 }
 
 fn main() {
@@ -57,6 +66,7 @@ fn main() {
         StorySelector::Component(stories[selection])
     });
     let theme_name = args.theme.unwrap_or("One Dark".to_string());
+    let screenshot_path = args.screenshot;
 
     gpui::App::new().with_assets(Assets).run(move |cx| {
         load_embedded_fonts(cx).unwrap();
@@ -79,7 +89,7 @@ fn main() {
         language::init(cx);
         editor::init(cx);
 
-        let _window = cx.open_window(
+        let window = cx.open_window(
             WindowOptions {
                 bounds: WindowBounds::Fixed(Bounds {
                     origin: Default::default(),
@@ -95,6 +105,19 @@ fn main() {
             },
         );
 
+        if let Some(screenshot_path) = screenshot_path {
+            window
+                .update(cx, |_, cx| {
+                    cx.on_next_frame(move |_, cx| {
+                        if let Err(error) = save_screenshot(&screenshot_path, cx) {
+                            log::error!("failed to save screenshot: {error:?}");
+                        }
+                        cx.quit();
+                    })
+                })
+                .log_err();
+        }
+
         cx.activate(true);
     });
 }
@@ -121,6 +144,13 @@ impl Render for StoryWrapper {
     }
 }
 
+fn save_screenshot(path: &Path, cx: &mut WindowContext) -> anyhow::Result<()> {
+    let image = cx.render_to_image()?;
+    image.save(path)?;
+    log::info!("saved screenshot to {path:?}");
+    Ok(())
+}
+
 fn load_embedded_fonts(cx: &AppContext) -> gpui::Result<()> {
     let font_paths = cx.asset_source().list("fonts")?;
     let mut embedded_fonts = Vec::new();
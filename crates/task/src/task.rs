@@ -0,0 +1,301 @@
+//! Tasks are named commands that are defined in `tasks.json` files, and get
+//! spawned in a terminal with details of the editor's state, such as the
+//! current file, substituted into them.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf};
+
+/// A task as it's defined in a `tasks.json` file, which may refer to
+/// variables such as `$ZED_FILE` in its command, arguments, working directory
+/// and environment.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TaskTemplate {
+    /// The name that the task is listed under.
+    pub label: String,
+    /// The program to run.
+    pub command: String,
+    /// The arguments to pass to the program.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The directory to run the program in. Relative paths are resolved
+    /// against the root of the worktree that the task is spawned for.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Environment variables to set for the program.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+/// The contents of a `tasks.json` file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct TaskTemplates(pub Vec<TaskTemplate>);
+
+/// A variable that tasks can refer to, either as `$ZED_ROW` or `${ZED_ROW}`.
+/// Variables are also passed to the task's program as environment variables.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum VariableName {
+    /// The absolute path of the file open in the active editor.
+    File,
+    /// The text selected in the active editor.
+    SelectedText,
+    /// The row of the cursor in the active editor, starting at 1.
+    Row,
+    /// The column of the cursor in the active editor, starting at 1.
+    Column,
+    /// The absolute path of the root of the worktree containing the active
+    /// file, or of the project's first worktree.
+    WorktreeRoot,
+}
+
+impl VariableName {
+    pub const ALL: [VariableName; 5] = [
+        VariableName::File,
+        VariableName::SelectedText,
+        VariableName::Row,
+        VariableName::Column,
+        VariableName::WorktreeRoot,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VariableName::File => "ZED_FILE",
+            VariableName::SelectedText => "ZED_SELECTED_TEXT",
+            VariableName::Row => "ZED_ROW",
+            VariableName::Column => "ZED_COLUMN",
+            VariableName::WorktreeRoot => "ZED_WORKTREE_ROOT",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|variable| variable.name() == name)
+    }
+}
+
+/// The values of the variables that are available to a task.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskVariables(HashMap<VariableName, String>);
+
+impl TaskVariables {
+    pub fn insert(&mut self, variable: VariableName, value: String) {
+        self.0.insert(variable, value);
+    }
+
+    pub fn get(&self, variable: VariableName) -> Option<&str> {
+        self.0.get(&variable).map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (VariableName, &str)> {
+        self.0
+            .iter()
+            .map(|(variable, value)| (*variable, value.as_str()))
+    }
+}
+
+impl FromIterator<(VariableName, String)> for TaskVariables {
+    fn from_iter<T: IntoIterator<Item = (VariableName, String)>>(iter: T) -> Self {
+        Self(HashMap::from_iter(iter))
+    }
+}
+
+/// The state of the editor that a task is spawned for.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskContext {
+    /// The directory to run tasks in that don't specify one, and to resolve
+    /// relative working directories against.
+    pub cwd: Option<PathBuf>,
+    pub variables: TaskVariables,
+}
+
+/// Identifies a task across runs, so that running it again can reuse the
+/// terminal it ran in before.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaskId(pub String);
+
+/// A task with its variables substituted, ready to be run in a terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpawnInTerminal {
+    pub id: TaskId,
+    pub label: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub env: HashMap<String, String>,
+}
+
+impl TaskTemplate {
+    /// Substitutes the context's variables into the task. Returns `None` if
+    /// the task refers to a variable that the context doesn't have a value
+    /// for, such as `$ZED_FILE` when no file is open.
+    pub fn resolve(&self, id: TaskId, cx: &TaskContext) -> Option<SpawnInTerminal> {
+        let substitute = |text: &str| substitute_variables(text, &cx.variables);
+
+        let cwd = match &self.cwd {
+            Some(cwd) => {
+                let cwd = PathBuf::from(substitute(cwd)?);
+                match &cx.cwd {
+                    Some(context_cwd) if cwd.is_relative() => Some(context_cwd.join(cwd)),
+                    _ => Some(cwd),
+                }
+            }
+            None => cx.cwd.clone(),
+        };
+
+        let mut env = cx
+            .variables
+            .iter()
+            .map(|(variable, value)| (variable.name().to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        for (name, value) in &self.env {
+            env.insert(name.clone(), substitute(value)?);
+        }
+
+        Some(SpawnInTerminal {
+            id,
+            label: substitute(&self.label)?,
+            command: substitute(&self.command)?,
+            args: self
+                .args
+                .iter()
+                .map(|arg| substitute(arg))
+                .collect::<Option<_>>()?,
+            cwd,
+            env,
+        })
+    }
+}
+
+/// Replaces the `$ZED_*` and `${ZED_*}` variables in the given text. Other
+/// variables are left as they are.
+fn substitute_variables(text: &str, variables: &TaskVariables) -> Option<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(dollar_ix) = rest.find('$') {
+        result.push_str(&rest[..dollar_ix]);
+        rest = &rest[dollar_ix + 1..];
+
+        let (name, remainder) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", rest),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+
+        if name.starts_with("ZED_") {
+            let variable = VariableName::from_name(name)?;
+            result.push_str(variables.get(variable)?);
+            rest = remainder;
+        } else {
+            result.push('$');
+        }
+    }
+    result.push_str(rest);
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_templates() {
+        let templates: TaskTemplates = serde_json::from_str(
+            r#"[
+                {
+                    "label": "test current file",
+                    "command": "cargo",
+                    "args": ["test", "--", "$ZED_FILE"],
+                    "env": { "RUST_LOG": "info" }
+                },
+                { "label": "dev server", "command": "npm", "cwd": "web" }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            templates,
+            TaskTemplates(vec![
+                TaskTemplate {
+                    label: "test current file".into(),
+                    command: "cargo".into(),
+                    args: vec!["test".into(), "--".into(), "$ZED_FILE".into()],
+                    cwd: None,
+                    env: HashMap::from_iter([("RUST_LOG".into(), "info".into())]),
+                },
+                TaskTemplate {
+                    label: "dev server".into(),
+                    command: "npm".into(),
+                    args: Vec::new(),
+                    cwd: Some("web".into()),
+                    env: HashMap::default(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_resolving_templates() {
+        let context = TaskContext {
+            cwd: Some(PathBuf::from("/project")),
+            variables: TaskVariables::from_iter([
+                (VariableName::File, "/project/src/main.rs".into()),
+                (VariableName::Row, "12".into()),
+                (VariableName::WorktreeRoot, "/project".into()),
+            ]),
+        };
+
+        let template = TaskTemplate {
+            label: "check ${ZED_FILE}".into(),
+            command: "cargo".into(),
+            args: vec![
+                "check".into(),
+                "$ZED_FILE:$ZED_ROW".into(),
+                "$HOME".into(),
+                "cost: $5".into(),
+            ],
+            cwd: Some("crates".into()),
+            env: HashMap::from_iter([("ROOT".into(), "$ZED_WORKTREE_ROOT".into())]),
+        };
+        let resolved = template.resolve(TaskId("check".into()), &context).unwrap();
+        assert_eq!(resolved.label, "check /project/src/main.rs");
+        assert_eq!(
+            resolved.args,
+            ["check", "/project/src/main.rs:12", "$HOME", "cost: $5"]
+        );
+        assert_eq!(resolved.cwd, Some(PathBuf::from("/project/crates")));
+        assert_eq!(resolved.env["ROOT"], "/project");
+        assert_eq!(resolved.env["ZED_FILE"], "/project/src/main.rs");
+        assert_eq!(resolved.env["ZED_ROW"], "12");
+
+        // Tasks that need a variable the context doesn't have can't be resolved.
+        let template = TaskTemplate {
+            label: "echo selection".into(),
+            command: "echo".into(),
+            args: vec!["$ZED_SELECTED_TEXT".into()],
+            ..Default::default()
+        };
+        assert_eq!(template.resolve(TaskId("echo".into()), &context), None);
+        let template = TaskTemplate {
+            label: "unknown".into(),
+            command: "${ZED_UNKNOWN}".into(),
+            ..Default::default()
+        };
+        assert_eq!(template.resolve(TaskId("unknown".into()), &context), None);
+
+        // Tasks without a working directory run in the context's one.
+        let template = TaskTemplate {
+            label: "ls".into(),
+            command: "ls".into(),
+            ..Default::default()
+        };
+        let resolved = template.resolve(TaskId("ls".into()), &context).unwrap();
+        assert_eq!(resolved.cwd, Some(PathBuf::from("/project")));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::schedule_task;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, Task, View, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{
+    task_inventory::{Inventory, TaskSourceKind},
+    WorktreeId,
+};
+use std::sync::Arc;
+use task::{TaskContext, TaskTemplate};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Toast, Workspace};
+
+const TASK_ERROR_TOAST_ID: usize = 5120;
+
+/// Lists the tasks that can be spawned for the active editor.
+pub(crate) struct TasksModal {
+    picker: View<Picker<TasksModalDelegate>>,
+    _subscription: Subscription,
+}
+
+impl TasksModal {
+    pub(crate) fn new(
+        inventory: Model<Inventory>,
+        task_context: TaskContext,
+        worktree_id: Option<WorktreeId>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let candidates = inventory.read(cx).list_templates(worktree_id);
+        let delegate = TasksModalDelegate {
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+            task_context,
+            workspace,
+        };
+        let picker = cx.new_view(|cx| Picker::new(delegate, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for TasksModal {}
+impl EventEmitter<DismissEvent> for TasksModal {}
+
+impl FocusableView for TasksModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for TasksModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|this, _, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.cancel(&Default::default(), cx);
+                })
+            }))
+    }
+}
+
+pub(crate) struct TasksModalDelegate {
+    candidates: Vec<(TaskSourceKind, TaskTemplate)>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    task_context: TaskContext,
+    workspace: WeakView<Workspace>,
+}
+
+impl PickerDelegate for TasksModalDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self) -> Arc<str> {
+        "Select a task to spawn...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(ix, (_, template))| StringMatchCandidate::new(ix, template.label.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(|picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let (kind, template) = &self.candidates[mat.candidate_id];
+        let task = template.resolve(kind.task_id(template), &self.task_context);
+        let message = format!(
+            "Task \"{}\" uses a variable that isn't available in the active editor",
+            template.label
+        );
+        self.workspace
+            .update(cx, |workspace, cx| match task {
+                Some(task) => schedule_task(workspace, task, cx),
+                None => workspace.show_toast(Toast::new(TASK_ERROR_TOAST_ID, message), cx),
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let (kind, _) = &self.candidates[mat.candidate_id];
+        Some(
+            ListItem::new(SharedString::from(format!("tasks-modal-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(
+                    Label::new(kind.label())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+}
@@ -0,0 +1,187 @@
+mod modal;
+
+use editor::Editor;
+use gpui::{actions, AppContext, ViewContext, WindowContext};
+use language::Point;
+use modal::TasksModal;
+use project::WorktreeId;
+use task::{SpawnInTerminal, TaskContext, TaskVariables, VariableName};
+use terminal_view::terminal_panel::TerminalPanel;
+use workspace::Workspace;
+
+actions!(task, [Spawn, Rerun]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace
+                .register_action(|workspace, _: &Spawn, cx| toggle_modal(workspace, cx))
+                .register_action(|workspace, _: &Rerun, cx| {
+                    let last_task = workspace
+                        .project()
+                        .read(cx)
+                        .task_inventory()
+                        .read(cx)
+                        .last_scheduled_task()
+                        .cloned();
+                    // Without a task to rerun, let the user pick one instead.
+                    match last_task {
+                        Some(task) => schedule_task(workspace, task, cx),
+                        None => toggle_modal(workspace, cx),
+                    }
+                });
+        },
+    )
+    .detach();
+}
+
+fn toggle_modal(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    let (task_context, worktree_id) = task_context(workspace, cx);
+    let workspace_handle = cx.view().downgrade();
+    workspace.toggle_modal(cx, |cx| {
+        TasksModal::new(inventory, task_context, worktree_id, workspace_handle, cx)
+    });
+}
+
+/// Runs a resolved task in the terminal panel, and remembers it so that it
+/// can be rerun.
+fn schedule_task(
+    workspace: &mut Workspace,
+    task: SpawnInTerminal,
+    cx: &mut ViewContext<Workspace>,
+) {
+    workspace
+        .project()
+        .read(cx)
+        .task_inventory()
+        .clone()
+        .update(cx, |inventory, _| inventory.task_scheduled(task.clone()));
+    if let Some(terminal_panel) = workspace.focus_panel::<TerminalPanel>(cx) {
+        terminal_panel.update(cx, |terminal_panel, cx| terminal_panel.spawn_task(task, cx));
+    }
+}
+
+/// Captures the state of the active editor for substituting into tasks, along
+/// with the worktree whose tasks should be listed.
+fn task_context(workspace: &Workspace, cx: &WindowContext) -> (TaskContext, Option<WorktreeId>) {
+    let mut variables = TaskVariables::default();
+    let mut worktree = None;
+
+    let editor = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx));
+    if let Some(editor) = editor {
+        let editor = editor.read(cx);
+        if let Some(buffer) = editor.buffer().read(cx).as_singleton() {
+            let selection = editor.selections.newest::<Point>(cx);
+            let buffer = buffer.read(cx);
+            let cursor = selection.head();
+            variables.insert(VariableName::Row, (cursor.row + 1).to_string());
+            variables.insert(VariableName::Column, (cursor.column + 1).to_string());
+            if !selection.is_empty() {
+                let selected_text = buffer.text_for_range(selection.range()).collect();
+                variables.insert(VariableName::SelectedText, selected_text);
+            }
+            if let Some(file) = project::File::from_dyn(buffer.file()) {
+                let abs_path = file.worktree.read(cx).abs_path().join(&file.path);
+                variables.insert(VariableName::File, abs_path.to_string_lossy().into_owned());
+                worktree = Some(file.worktree.clone());
+            }
+        }
+    }
+
+    let worktree = worktree.or_else(|| workspace.project().read(cx).visible_worktrees(cx).next());
+    let mut cwd = None;
+    if let Some(worktree) = &worktree {
+        let root = worktree.read(cx).abs_path();
+        variables.insert(
+            VariableName::WorktreeRoot,
+            root.to_string_lossy().into_owned(),
+        );
+        cwd = Some(root.to_path_buf());
+    }
+
+    (
+        TaskContext { cwd, variables },
+        worktree.map(|worktree| worktree.read(cx).id()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use std::path::PathBuf;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_task_context(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "fn main() {\n    println!(\"hello\");\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        // Without an editor, tasks only know about the worktree.
+        let (context, context_worktree_id) =
+            workspace.update(cx, |workspace, cx| task_context(workspace, cx));
+        assert_eq!(context_worktree_id, Some(worktree_id));
+        assert_eq!(context.cwd, Some(PathBuf::from("/dir")));
+        assert_eq!(
+            context.variables,
+            TaskVariables::from_iter([(VariableName::WorktreeRoot, "/dir".into())])
+        );
+
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.rs"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |selections| {
+                selections.select_ranges([Point::new(1, 4)..Point::new(1, 11)])
+            });
+        });
+
+        let (context, context_worktree_id) =
+            workspace.update(cx, |workspace, cx| task_context(workspace, cx));
+        assert_eq!(context_worktree_id, Some(worktree_id));
+        assert_eq!(context.cwd, Some(PathBuf::from("/dir")));
+        assert_eq!(
+            context.variables,
+            TaskVariables::from_iter([
+                (VariableName::File, "/dir/a.rs".into()),
+                (VariableName::SelectedText, "println".into()),
+                (VariableName::Row, "2".into()),
+                (VariableName::Column, "12".into()),
+                (VariableName::WorktreeRoot, "/dir".into()),
+            ])
+        );
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let app_state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init(app_state, cx);
+            Project::init_settings(cx);
+        });
+    }
+}
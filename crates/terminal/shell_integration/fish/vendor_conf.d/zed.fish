@@ -0,0 +1,26 @@
+# Zed's shell integration for fish.
+#
+# Zed adds this file's data directory to `XDG_DATA_DIRS` to load it. It marks
+# prompts, commands and their output with OSC 133 sequences, which the
+# terminal uses to navigate between them.
+
+if set -q ZED_USER_XDG_DATA_DIRS
+    set -gx XDG_DATA_DIRS (string split : -- $ZED_USER_XDG_DATA_DIRS)
+    set -e ZED_USER_XDG_DATA_DIRS
+else
+    set -e XDG_DATA_DIRS
+end
+
+if status is-interactive
+    function __zed_prompt --on-event fish_prompt
+        printf '\e]133;A\a'
+    end
+
+    function __zed_preexec --on-event fish_preexec
+        printf '\e]133;C\a'
+    end
+
+    function __zed_postexec --on-event fish_postexec
+        printf '\e]133;D;%s\a' $status
+    end
+end
@@ -0,0 +1,48 @@
+# Zed's shell integration for bash.
+#
+# This is sourced from `PROMPT_COMMAND` before the first prompt, once the
+# user's startup files have run. It marks prompts, commands and their output
+# with OSC 133 sequences, which the terminal uses to navigate between them.
+
+if [[ -n "${ZED_BASH_INTEGRATION-}" && -z "${__zed_integrated-}" ]]; then
+    __zed_integrated=1
+
+    __zed_precmd() {
+        local exit_code=$?
+        if [[ -n "${__zed_command_running-}" ]]; then
+            builtin printf '\e]133;D;%s\a' "$exit_code"
+        fi
+        __zed_command_running=
+        __zed_at_prompt=
+        builtin printf '\e]133;A\a'
+        # Prompt frameworks may rewrite the prompt, so mark its end every time.
+        if [[ "$PS1" != *'133;B'* ]]; then
+            PS1="$PS1"'\[\e]133;B\a\]'
+        fi
+    }
+
+    __zed_ready() {
+        __zed_at_prompt=1
+    }
+
+    __zed_preexec() {
+        if [[ -z "${__zed_at_prompt-}" || "$BASH_COMMAND" == __zed_precmd* ]]; then
+            return
+        fi
+        __zed_at_prompt=
+        __zed_command_running=1
+        builtin printf '\e]133;C\a'
+    }
+
+    # Replace the command that loaded this file with the prompt hook, and flag
+    # the end of the prompt commands so that the next one is the user's.
+    __zed_loader='. "$ZED_BASH_INTEGRATION"'
+    PROMPT_COMMAND=${PROMPT_COMMAND/$__zed_loader/__zed_precmd}
+    PROMPT_COMMAND="${PROMPT_COMMAND}; __zed_ready"
+    builtin unset __zed_loader ZED_BASH_INTEGRATION
+    builtin trap '__zed_preexec' DEBUG
+
+    # This prompt's commands are already running, so mark it now.
+    __zed_precmd
+    __zed_ready
+fi
@@ -0,0 +1,273 @@
+//! Saves a terminal's output as text with ANSI escape sequences, so that it can
+//! be printed into another terminal: the scrollback is restored into a new
+//! terminal when a workspace reopens, and the screen of a shared terminal is
+//! redrawn for collaborators.
+
+use alacritty_terminal::{
+    grid::{Dimensions, Row},
+    index::{Column, Line},
+    term::{
+        cell::{Cell, Flags},
+        TermMode,
+    },
+    vte::ansi::{Color, NamedColor, Processor},
+    Term,
+};
+use std::fmt::Write;
+
+/// The text of the last `max_lines` lines above the cursor, including their
+/// colors and styles. The oldest lines are dropped to fit within `max_bytes`.
+///
+/// Returns `None` while a full-screen program is running, as the alternate
+/// screen has no scrollback.
+pub(crate) fn serialize<T>(term: &Term<T>, max_lines: usize, max_bytes: usize) -> Option<String> {
+    if term.mode().contains(TermMode::ALT_SCREEN) {
+        return None;
+    }
+
+    // The line with the cursor usually holds the prompt, which the shell will
+    // print again when the scrollback is restored.
+    let grid = term.grid();
+    let end = grid.cursor.point.line;
+    let start = Line((end.0 - max_lines as i32).max(grid.topmost_line().0));
+
+    let mut lines = Vec::new();
+    for line in start.0..end.0 {
+        let row = &grid[Line(line)];
+        let mut text = serialize_row(row, grid.columns());
+        // Wrapped lines are continued without a line break, so that they
+        // reflow to the width of the terminal they're restored into.
+        let last_cell = &row[Column(grid.columns() - 1)];
+        if !last_cell.flags.contains(Flags::WRAPLINE) {
+            text.push_str("\r\n");
+        }
+        lines.push(text);
+    }
+
+    let mut len = lines.iter().map(String::len).sum::<usize>();
+    let mut first_line = 0;
+    while len > max_bytes && first_line < lines.len() {
+        len -= lines[first_line].len();
+        first_line += 1;
+    }
+    // Start at a line break, rather than in the middle of a wrapped line.
+    while first_line < lines.len() && first_line > 0 && !lines[first_line - 1].ends_with('\n') {
+        first_line += 1;
+    }
+    let text = lines[first_line..].concat();
+    Some(text.trim_start_matches("\r\n").to_string())
+}
+
+/// The visible screen of a terminal, which redraws it when printed into a
+/// terminal of the same size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScreenSnapshot {
+    pub columns: usize,
+    pub screen_lines: usize,
+    pub text: String,
+}
+
+pub(crate) fn serialize_screen<T>(term: &Term<T>) -> ScreenSnapshot {
+    let grid = term.grid();
+    let mut text = String::new();
+    // The modes that change the input sent to the terminal.
+    for (mode, code) in [
+        (TermMode::APP_CURSOR, 1),
+        (TermMode::SHOW_CURSOR, 25),
+        (TermMode::MOUSE_REPORT_CLICK, 1000),
+        (TermMode::MOUSE_DRAG, 1002),
+        (TermMode::MOUSE_MOTION, 1003),
+        (TermMode::FOCUS_IN_OUT, 1004),
+        (TermMode::SGR_MOUSE, 1006),
+        (TermMode::BRACKETED_PASTE, 2004),
+    ] {
+        let action = if term.mode().contains(mode) { 'h' } else { 'l' };
+        write!(text, "\x1b[?{code}{action}").unwrap();
+    }
+    for line in 0..grid.screen_lines() {
+        if line > 0 {
+            text.push_str("\r\n");
+        }
+        text.push_str(&serialize_row(&grid[Line(line as i32)], grid.columns()));
+    }
+    let cursor = grid.cursor.point;
+    write!(text, "\x1b[{};{}H", cursor.line.0 + 1, cursor.column.0 + 1).unwrap();
+
+    ScreenSnapshot {
+        columns: grid.columns(),
+        screen_lines: grid.screen_lines(),
+        text,
+    }
+}
+
+/// The text of a row up to its last non-blank cell, including its colors and
+/// styles.
+fn serialize_row(row: &Row<Cell>, columns: usize) -> String {
+    let mut text = String::new();
+    let mut style = DEFAULT_STYLE;
+    let columns = (0..columns)
+        .rev()
+        .find(|column| !is_blank(&row[Column(*column)]))
+        .map_or(0, |column| column + 1);
+    for column in 0..columns {
+        let cell = &row[Column(column)];
+        if cell.flags.contains(Flags::WIDE_CHAR_SPACER) {
+            continue;
+        }
+        let cell_style = (cell.fg, cell.bg, cell.flags & STYLE_FLAGS);
+        if style != cell_style {
+            text.push_str(&sgr(&cell_style));
+            style = cell_style;
+        }
+        text.push(cell.c);
+        if let Some(zerowidth) = cell.zerowidth() {
+            text.extend(zerowidth);
+        }
+    }
+    if style != DEFAULT_STYLE {
+        text.push_str("\x1b[0m");
+    }
+    text
+}
+
+/// Prints saved scrollback into the terminal, as if it had been output there.
+pub(crate) fn restore<T: alacritty_terminal::event::EventListener>(
+    term: &mut Term<T>,
+    scrollback: &str,
+) {
+    let mut processor: Processor = Processor::new();
+    for byte in scrollback.bytes() {
+        processor.advance(term, byte);
+    }
+}
+
+type Style = (Color, Color, Flags);
+
+const DEFAULT_STYLE: Style = (
+    Color::Named(NamedColor::Foreground),
+    Color::Named(NamedColor::Background),
+    Flags::empty(),
+);
+
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::ALL_UNDERLINES)
+    .union(Flags::INVERSE)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+fn is_blank(cell: &Cell) -> bool {
+    cell.c == ' '
+        && cell.bg == Color::Named(NamedColor::Background)
+        && !cell
+            .flags
+            .intersects(Flags::INVERSE | Flags::ALL_UNDERLINES | Flags::STRIKEOUT)
+}
+
+/// The SGR sequence that resets the style and then sets the given one.
+fn sgr((fg, bg, flags): &Style) -> String {
+    let mut sgr = String::from("\x1b[0");
+    for (flag, code) in [
+        (Flags::BOLD, 1),
+        (Flags::DIM, 2),
+        (Flags::ITALIC, 3),
+        (Flags::ALL_UNDERLINES, 4),
+        (Flags::INVERSE, 7),
+        (Flags::HIDDEN, 8),
+        (Flags::STRIKEOUT, 9),
+    ] {
+        if flags.intersects(flag) {
+            write!(sgr, ";{code}").unwrap();
+        }
+    }
+    write_color(&mut sgr, fg, 30);
+    write_color(&mut sgr, bg, 40);
+    sgr.push('m');
+    sgr
+}
+
+/// Appends the SGR parameters for a foreground (with a `base` of 30) or a
+/// background (with a `base` of 40) color.
+fn write_color(sgr: &mut String, color: &Color, base: u8) {
+    match color {
+        Color::Named(named) => {
+            let index = *named as usize;
+            let dim_black = NamedColor::DimBlack as usize;
+            match index {
+                0..=7 => write!(sgr, ";{}", base as usize + index),
+                8..=15 => write!(sgr, ";{}", base as usize + 60 + index - 8),
+                _ if (dim_black..dim_black + 8).contains(&index) => {
+                    write!(sgr, ";{}", base as usize + index - dim_black)
+                }
+                // The terminal's default colors.
+                _ => Ok(()),
+            }
+            .unwrap();
+        }
+        Color::Indexed(index) => write!(sgr, ";{};5;{index}", base + 8).unwrap(),
+        Color::Spec(rgb) => write!(sgr, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TerminalSize, ZedListener};
+    use alacritty_terminal::term::Config;
+    use futures::channel::mpsc::unbounded;
+
+    fn new_term() -> Term<ZedListener> {
+        let (events_tx, _events_rx) = unbounded();
+        Term::new(
+            Config::default(),
+            &TerminalSize::default(),
+            ZedListener(events_tx),
+        )
+    }
+
+    #[test]
+    fn test_serializing_scrollback() {
+        let mut term = new_term();
+        restore(
+            &mut term,
+            "one\r\n\x1b[1;31mtwo\x1b[0m \x1b[38;5;100mthree\x1b[0m\r\nfour\r\n$ ",
+        );
+
+        let scrollback = serialize(&term, 10, 1024).unwrap();
+        assert_eq!(
+            scrollback,
+            "one\r\n\x1b[0;1;31mtwo\x1b[0m \x1b[0;38;5;100mthree\x1b[0m\r\nfour\r\n"
+        );
+
+        let mut restored = new_term();
+        restore(&mut restored, &scrollback);
+        assert_eq!(serialize(&restored, 10, 1024).unwrap(), scrollback);
+
+        // Lines are dropped from the top to fit the limits.
+        assert_eq!(serialize(&term, 1, 1024).unwrap(), "four\r\n");
+        assert_eq!(serialize(&term, 10, 20).unwrap(), "four\r\n");
+    }
+
+    #[test]
+    fn test_serializing_screen() {
+        let mut term = new_term();
+        restore(&mut term, "one\r\n\x1b[4mtwo\x1b[0m\r\n\x1b[?2004h$ ls");
+
+        let screen = serialize_screen(&term);
+        assert_eq!(screen.columns, term.columns());
+        assert_eq!(screen.screen_lines, term.screen_lines());
+        assert!(screen
+            .text
+            .ends_with("one\r\n\x1b[0;4mtwo\x1b[0m\r\n$ ls\r\n\r\n\r\n\x1b[3;5H"));
+
+        // Redrawing a screen replaces everything that was shown before.
+        let mut redrawn = new_term();
+        restore(&mut redrawn, "\x1b[31mstale\r\noutput");
+        redrawn.grid_mut().reset_region(..);
+        restore(&mut redrawn, "\x1b[0m\x1b[H");
+        restore(&mut redrawn, &screen.text);
+        assert_eq!(serialize_screen(&redrawn), screen);
+        assert!(redrawn.mode().contains(TermMode::BRACKETED_PASTE));
+    }
+}
@@ -0,0 +1,532 @@
+//! Support for semantic prompts, which shells emit as OSC 133 (or VS Code's
+//! OSC 633) sequences around their prompts, commands and the commands' output.
+//!
+//! Alacritty ignores these sequences, so they are rewritten into OSC 8
+//! hyperlinks before the output reaches the terminal. Every cell that is
+//! printed in a marked region then carries a link that identifies the region
+//! and the command it belongs to, which keeps the marks in place as the grid
+//! scrolls, resizes and reflows.
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    term::cell::Hyperlink,
+    tty::{self, ChildEvent, EventedPty, EventedReadWrite},
+};
+use anyhow::Result;
+use polling::{Event, PollMode, Poller};
+use std::{
+    collections::HashMap,
+    env,
+    fs::{self, File},
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use util::ResultExt;
+
+use crate::terminal_settings::Shell;
+
+const MARK_URI_PREFIX: &str = "zed-shell-integration://";
+const OSC_133: &[u8] = b"\x1b]133;";
+const OSC_633: &[u8] = b"\x1b]633;";
+const OSC_8: &[u8] = b"\x1b]8;";
+const LINK_END: &[u8] = b"\x1b]8;;\x1b\\";
+/// Sequences longer than this are passed through without being interpreted,
+/// so that a stray prefix can't hold back the rest of the output.
+const MAX_SEQUENCE_LEN: usize = 4096;
+const READ_BUFFER_SIZE: usize = 0x10000;
+
+/// The part of a command's lifecycle that a cell was printed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkKind {
+    Prompt,
+    Command,
+    Output,
+}
+
+impl MarkKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MarkKind::Prompt => "prompt",
+            MarkKind::Command => "command",
+            MarkKind::Output => "output",
+        }
+    }
+}
+
+/// Identifies the region and command that a cell belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mark {
+    pub kind: MarkKind,
+    pub command_id: u64,
+}
+
+impl Mark {
+    pub fn from_hyperlink(hyperlink: &Hyperlink) -> Option<Self> {
+        let (kind, command_id) = hyperlink
+            .uri()
+            .strip_prefix(MARK_URI_PREFIX)?
+            .split_once('/')?;
+        let kind = match kind {
+            "prompt" => MarkKind::Prompt,
+            "command" => MarkKind::Command,
+            "output" => MarkKind::Output,
+            _ => return None,
+        };
+        Some(Mark {
+            kind,
+            command_id: command_id.parse().ok()?,
+        })
+    }
+
+    fn link_start(&self) -> Vec<u8> {
+        let kind = self.kind.as_str();
+        format!(
+            "\x1b]8;id=zed-{kind}-{id};{MARK_URI_PREFIX}{kind}/{id}\x1b\\",
+            id = self.command_id
+        )
+        .into_bytes()
+    }
+}
+
+/// Whether the hyperlink was added by the shell integration, rather than by
+/// a program running in the terminal.
+pub fn is_mark(hyperlink: &Hyperlink) -> bool {
+    hyperlink.uri().starts_with(MARK_URI_PREFIX)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    Pending,
+    /// The command has finished, with the exit code reported by the shell.
+    Finished(Option<i32>),
+}
+
+/// The status of every command that the shell has reported, shared between
+/// the PTY reader and the terminal.
+#[derive(Clone, Default)]
+pub(crate) struct CommandStatuses(Arc<Mutex<HashMap<u64, CommandStatus>>>);
+
+impl CommandStatuses {
+    pub fn get(&self, command_id: u64) -> CommandStatus {
+        self.0
+            .lock()
+            .unwrap()
+            .get(&command_id)
+            .copied()
+            .unwrap_or(CommandStatus::Pending)
+    }
+
+    fn set(&self, command_id: u64, status: CommandStatus) {
+        self.0.lock().unwrap().insert(command_id, status);
+    }
+}
+
+/// Rewrites the semantic prompt sequences read from a PTY into hyperlinks.
+pub(crate) struct SemanticPromptReader<R> {
+    inner: R,
+    input: Box<[u8]>,
+    /// The start of a sequence that hasn't been completely read yet.
+    pending: Vec<u8>,
+    output: Vec<u8>,
+    output_offset: usize,
+    region: Option<MarkKind>,
+    command_id: u64,
+    statuses: CommandStatuses,
+}
+
+impl<R: Read> SemanticPromptReader<R> {
+    pub fn new(inner: R, statuses: CommandStatuses) -> Self {
+        Self {
+            inner,
+            input: vec![0; READ_BUFFER_SIZE].into_boxed_slice(),
+            pending: Vec::new(),
+            output: Vec::new(),
+            output_offset: 0,
+            region: None,
+            command_id: 0,
+            statuses,
+        }
+    }
+
+    fn process(&mut self, input: &[u8]) {
+        let mut data = std::mem::take(&mut self.pending);
+        data.extend_from_slice(input);
+
+        let mut copied = 0;
+        let mut ix = 0;
+        while let Some(offset) = data[ix..].iter().position(|byte| *byte == 0x1b) {
+            let start = ix + offset;
+            let rest = &data[start..];
+            ix = start + 1;
+
+            let Some(prefix) = [OSC_133, OSC_633, OSC_8]
+                .into_iter()
+                .find(|prefix| rest.starts_with(prefix))
+            else {
+                if [OSC_133, OSC_633, OSC_8]
+                    .iter()
+                    .any(|prefix| prefix.starts_with(rest))
+                {
+                    self.output.extend_from_slice(&data[copied..start]);
+                    self.pending = rest.to_vec();
+                    return;
+                }
+                continue;
+            };
+
+            let body = &rest[prefix.len()..];
+            let terminator = body.iter().enumerate().find_map(|(i, byte)| match byte {
+                0x07 => Some((i, 1)),
+                0x1b if body.get(i + 1) == Some(&b'\\') => Some((i, 2)),
+                _ => None,
+            });
+            let Some((params_len, terminator_len)) = terminator else {
+                if rest.len() <= MAX_SEQUENCE_LEN {
+                    self.output.extend_from_slice(&data[copied..start]);
+                    self.pending = rest.to_vec();
+                    return;
+                }
+                continue;
+            };
+            let params = &body[..params_len];
+            let end = start + prefix.len() + params_len + terminator_len;
+
+            self.output.extend_from_slice(&data[copied..start]);
+            if prefix == OSC_8 {
+                self.output.extend_from_slice(&data[start..end]);
+                // Programs can print their own links in a command's output,
+                // so resume marking the region once their link ends.
+                let ends_link = params
+                    .splitn(2, |byte| *byte == b';')
+                    .nth(1)
+                    .map_or(false, |uri| uri.is_empty());
+                if ends_link {
+                    if let Some(kind) = self.region {
+                        let mark = Mark {
+                            kind,
+                            command_id: self.command_id,
+                        };
+                        self.output.extend_from_slice(&mark.link_start());
+                    }
+                }
+            } else {
+                self.handle_semantic_prompt(params);
+            }
+            copied = end;
+            ix = end;
+        }
+        self.output.extend_from_slice(&data[copied..]);
+    }
+
+    fn handle_semantic_prompt(&mut self, params: &[u8]) {
+        let kind = match params.first() {
+            Some(b'A') => {
+                self.command_id += 1;
+                self.statuses.set(self.command_id, CommandStatus::Pending);
+                MarkKind::Prompt
+            }
+            Some(b'B') => MarkKind::Command,
+            Some(b'C') => MarkKind::Output,
+            Some(b'D') => {
+                if self.region.is_some() {
+                    // Shells report an exit code even when the prompt was
+                    // submitted without a command, which is meaningless.
+                    let exit_code = if self.region == Some(MarkKind::Output) {
+                        std::str::from_utf8(params)
+                            .ok()
+                            .and_then(|params| params.split(';').nth(1))
+                            .and_then(|exit_code| exit_code.parse().ok())
+                    } else {
+                        None
+                    };
+                    self.statuses
+                        .set(self.command_id, CommandStatus::Finished(exit_code));
+                    self.region = None;
+                    self.output.extend_from_slice(LINK_END);
+                }
+                return;
+            }
+            // The other VS Code sequences describe the command line and the
+            // working directory, which the terminal gets elsewhere.
+            _ => return,
+        };
+        if self.command_id == 0 {
+            return;
+        }
+        self.region = Some(kind);
+        let mark = Mark {
+            kind,
+            command_id: self.command_id,
+        };
+        self.output.extend_from_slice(&mark.link_start());
+    }
+}
+
+impl<R: Read> Read for SemanticPromptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Reading zero bytes signals the end of the output, so keep reading
+        // until there's something to return after stripping sequences.
+        while self.output_offset == self.output.len() {
+            self.output.clear();
+            self.output_offset = 0;
+            let len = self.inner.read(&mut self.input)?;
+            if len == 0 {
+                self.output.append(&mut self.pending);
+                if self.output.is_empty() {
+                    return Ok(0);
+                }
+            } else {
+                let input = std::mem::take(&mut self.input);
+                self.process(&input[..len]);
+                self.input = input;
+            }
+        }
+
+        let available = &self.output[self.output_offset..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.output_offset += len;
+        Ok(len)
+    }
+}
+
+/// A PTY whose output is read through a [`SemanticPromptReader`].
+pub(crate) struct ShellIntegrationPty {
+    pty: tty::Pty,
+    reader: SemanticPromptReader<File>,
+}
+
+impl ShellIntegrationPty {
+    pub fn new(pty: tty::Pty, statuses: CommandStatuses) -> io::Result<Self> {
+        let reader = SemanticPromptReader::new(pty.file().try_clone()?, statuses);
+        Ok(Self { pty, reader })
+    }
+}
+
+impl EventedReadWrite for ShellIntegrationPty {
+    type Reader = SemanticPromptReader<File>;
+    type Writer = <tty::Pty as EventedReadWrite>::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for ShellIntegrationPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for ShellIntegrationPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.pty.on_resize(window_size)
+    }
+}
+
+const BASH_SCRIPT: &str = include_str!("../shell_integration/zed.bash");
+const ZSH_SCRIPT: &str = include_str!("../shell_integration/zed.zsh");
+const ZSH_ENV: &str = include_str!("../shell_integration/zsh/.zshenv");
+const FISH_SCRIPT: &str = include_str!("../shell_integration/fish/vendor_conf.d/zed.fish");
+
+/// Returns the environment variables that load the integration script into
+/// the given shell, or `None` if the shell isn't supported.
+pub(crate) fn integration_env(
+    shell: &Shell,
+    env: &HashMap<String, String>,
+) -> Option<HashMap<String, String>> {
+    let program = match shell {
+        Shell::System => env::var("SHELL").ok()?,
+        Shell::Program(program) | Shell::WithArguments { program, .. } => program.clone(),
+    };
+    let var = |name: &str| env.get(name).cloned().or_else(|| env::var(name).ok());
+    let shell_name = Path::new(&program).file_name()?.to_str()?.to_string();
+
+    let dir = install_scripts().log_err()?;
+    let mut integration_env = HashMap::default();
+    match shell_name.as_str() {
+        "bash" => {
+            let loader = ". \"$ZED_BASH_INTEGRATION\"".to_string();
+            let prompt_command = match var("PROMPT_COMMAND") {
+                Some(prompt_command) => format!("{loader}; {prompt_command}"),
+                None => loader,
+            };
+            integration_env.insert(
+                "ZED_BASH_INTEGRATION".into(),
+                dir.join("zed.bash").to_string_lossy().into_owned(),
+            );
+            integration_env.insert("PROMPT_COMMAND".into(), prompt_command);
+        }
+        "zsh" => {
+            if let Some(zdotdir) = var("ZDOTDIR") {
+                integration_env.insert("ZED_USER_ZDOTDIR".into(), zdotdir);
+            }
+            integration_env.insert(
+                "ZDOTDIR".into(),
+                dir.join("zsh").to_string_lossy().into_owned(),
+            );
+        }
+        "fish" => {
+            let data_dirs = match var("XDG_DATA_DIRS") {
+                Some(data_dirs) => {
+                    integration_env.insert("ZED_USER_XDG_DATA_DIRS".into(), data_dirs.clone());
+                    data_dirs
+                }
+                None => "/usr/local/share:/usr/share".into(),
+            };
+            integration_env.insert(
+                "XDG_DATA_DIRS".into(),
+                format!("{}:{data_dirs}", dir.to_string_lossy()),
+            );
+        }
+        _ => return None,
+    }
+    Some(integration_env)
+}
+
+/// Writes the integration scripts to the support directory, so that shells
+/// can source them.
+fn install_scripts() -> Result<PathBuf> {
+    let dir = util::paths::SUPPORT_DIR.join("shell_integration");
+    for (path, content) in [
+        ("zed.bash", BASH_SCRIPT),
+        ("zed.zsh", ZSH_SCRIPT),
+        ("zsh/.zshenv", ZSH_ENV),
+        ("fish/vendor_conf.d/zed.fish", FISH_SCRIPT),
+    ] {
+        let path = dir.join(path);
+        if fs::read_to_string(&path).ok().as_deref() == Some(content) {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, content)?;
+    }
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewrite(chunks: &[&[u8]]) -> (Vec<u8>, CommandStatuses) {
+        let statuses = CommandStatuses::default();
+        let mut reader =
+            SemanticPromptReader::new(ChunkedReader(chunks.to_vec()), statuses.clone());
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        (output, statuses)
+    }
+
+    struct ChunkedReader<'a>(Vec<&'a [u8]>);
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Ok(0);
+            }
+            let chunk = self.0.remove(0);
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    fn link(kind: &str, id: u64) -> String {
+        format!("\x1b]8;id=zed-{kind}-{id};zed-shell-integration://{kind}/{id}\x1b\\")
+    }
+
+    #[test]
+    fn test_rewriting_semantic_prompts() {
+        let (output, statuses) = rewrite(&[
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]1",
+            b"33;C\x07a.rs\r\n\x1b]633;E;ls\x07\x1b]133;D;2\x1b",
+            b"\\\x1b]133;A\x07$ ",
+        ]);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "{}$ {}ls\r\n{}a.rs\r\n\x1b]8;;\x1b\\{}$ ",
+                link("prompt", 1),
+                link("command", 1),
+                link("output", 1),
+                link("prompt", 2),
+            )
+        );
+        assert_eq!(statuses.get(1), CommandStatus::Finished(Some(2)));
+        assert_eq!(statuses.get(2), CommandStatus::Pending);
+    }
+
+    #[test]
+    fn test_empty_commands_have_no_exit_code() {
+        let (_, statuses) = rewrite(&[b"\x1b]133;A\x07$ \x1b]133;B\x07\r\n\x1b]133;D;1\x07"]);
+        assert_eq!(statuses.get(1), CommandStatus::Finished(None));
+    }
+
+    #[test]
+    fn test_resuming_regions_after_program_links() {
+        let (output, _) = rewrite(&[
+            b"\x1b]133;A\x07\x1b]133;C\x07",
+            b"\x1b]8;;https://zed.dev\x07zed\x1b]8;;\x07!\x1b[0m",
+        ]);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "{}{}\x1b]8;;https://zed.dev\x07zed\x1b]8;;\x07{}!\x1b[0m",
+                link("prompt", 1),
+                link("output", 1),
+                link("output", 1),
+            )
+        );
+    }
+
+    #[test]
+    fn test_unrelated_sequences_are_passed_through() {
+        let input: &[u8] = b"\x1b[1mbold\x1b[0m\x1b]0;title\x07\x1b]13";
+        let (output, statuses) = rewrite(&[input]);
+        assert_eq!(output, input);
+        assert_eq!(statuses.get(1), CommandStatus::Pending);
+    }
+
+    #[test]
+    fn test_parsing_marks() {
+        let mark = Mark {
+            kind: MarkKind::Output,
+            command_id: 7,
+        };
+        let hyperlink = Hyperlink::new(Some("zed-output-7"), "zed-shell-integration://output/7");
+        assert!(is_mark(&hyperlink));
+        assert_eq!(Mark::from_hyperlink(&hyperlink), Some(mark));
+
+        let hyperlink = Hyperlink::new(None::<&str>, "https://zed.dev");
+        assert!(!is_mark(&hyperlink));
+        assert_eq!(Mark::from_hyperlink(&hyperlink), None);
+    }
+}
@@ -1,5 +1,8 @@
 pub mod mappings;
 pub use alacritty_terminal;
+mod scrollback;
+pub use scrollback::ScreenSnapshot;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -10,7 +13,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Hyperlink},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -33,13 +36,18 @@ use mappings::mouse::{
 use procinfo::LocalProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{
+    is_mark, CommandStatus, CommandStatuses, Mark, MarkKind, ShellIntegrationPty,
+};
+use task::TaskId;
 use terminal_settings::{AlternateScroll, Shell, TerminalBlink, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use util::truncate_and_trailoff;
 
 use std::{
+    borrow::Cow,
     cmp::{self, min},
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, VecDeque},
     fmt::Display,
     ops::{Deref, Index, RangeInclusive},
     os::unix::prelude::AsRawFd,
@@ -59,7 +67,21 @@ use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
 
 actions!(
     terminal,
-    [Clear, Copy, Paste, ShowCharacterPalette, SearchTest,]
+    [
+        Clear,
+        Copy,
+        Paste,
+        ShowCharacterPalette,
+        SearchTest,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        SelectLastCommandOutput,
+        CopyLastCommandOutput,
+        ShareTerminal,
+        UnshareTerminal,
+        AllowCollaboratorInput,
+        DisallowCollaboratorInput,
+    ]
 );
 
 ///Scrolling is unbearably sluggish by default. Alacritty supports a configurable
@@ -164,6 +186,18 @@ impl TerminalSize {
     pub fn line_height(&self) -> Pixels {
         self.line_height
     }
+
+    /// The size of a grid with the given dimensions, made of cells of this size.
+    fn with_grid_size(self, columns: usize, screen_lines: usize) -> Self {
+        TerminalSize::new(
+            self.line_height,
+            self.cell_width,
+            Size {
+                width: self.cell_width * columns as f32,
+                height: self.line_height * screen_lines as f32,
+            },
+        )
+    }
 }
 impl Default for TerminalSize {
     fn default() -> Self {
@@ -280,6 +314,9 @@ impl TerminalBuilder {
         env: HashMap<String, String>,
         blink_settings: Option<TerminalBlink>,
         alternate_scroll: AlternateScroll,
+        shell_integration: bool,
+        task: Option<TaskState>,
+        restored_scrollback: Option<String>,
         window: AnyWindowHandle,
     ) -> Result<TerminalBuilder> {
         let pty_options = {
@@ -296,10 +333,18 @@ impl TerminalBuilder {
             alacritty_terminal::tty::Options {
                 shell: alac_shell,
                 working_directory: working_directory.clone(),
-                hold: false,
+                // Keep the output of tasks around after they've finished.
+                hold: task.is_some(),
             }
         };
 
+        // Tasks aren't run in an interactive shell, so they have no prompts to mark.
+        let integration_env = if shell_integration && task.is_none() {
+            shell_integration::integration_env(&shell, &env).unwrap_or_default()
+        } else {
+            HashMap::default()
+        };
+
         // First, setup Alacritty's env
         setup_env();
 
@@ -307,6 +352,16 @@ impl TerminalBuilder {
         for (key, value) in env {
             std::env::set_var(key, value);
         }
+
+        // The shell integration variables are only needed to start the shell,
+        // so they're restored once it has been spawned.
+        let previous_env = integration_env
+            .keys()
+            .map(|key| (key.clone(), std::env::var_os(key)))
+            .collect::<Vec<_>>();
+        for (key, value) in integration_env {
+            std::env::set_var(key, value);
+        }
         //TODO: Properly set the current locale,
         std::env::set_var("LC_ALL", "en_US.UTF-8");
         std::env::set_var("ZED_TERM", "true");
@@ -336,14 +391,27 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Print the saved output before the shell starts, so that it's shown
+        // above the new prompt.
+        if let Some(scrollback) = restored_scrollback {
+            scrollback::restore(&mut term, &scrollback);
+        }
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
-        let pty = match tty::new(
+        let pty = tty::new(
             &pty_options,
             TerminalSize::default().into(),
             window.window_id().as_u64(),
-        ) {
+        );
+        for (key, value) in previous_env {
+            match value {
+                Some(value) => std::env::set_var(key, value),
+                None => std::env::remove_var(key),
+            }
+        }
+        let pty = match pty {
             Ok(pty) => pty,
             Err(error) => {
                 bail!(TerminalError {
@@ -356,6 +424,8 @@ impl TerminalBuilder {
 
         let fd = pty.file().as_raw_fd();
         let shell_pid = pty.child().id();
+        let command_statuses = CommandStatuses::default();
+        let pty = ShellIntegrationPty::new(pty, command_statuses.clone())?;
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -370,19 +440,57 @@ impl TerminalBuilder {
         let pty_tx = event_loop.channel();
         let _io_thread = event_loop.spawn(); // DANGER
 
+        let backend = TerminalBackend::Local {
+            pty_tx: Notifier(pty_tx),
+            shell_pid,
+            shell_fd: fd as u32,
+        };
+        Ok(Self::build(
+            term,
+            events_rx,
+            backend,
+            task,
+            command_statuses,
+        ))
+    }
+
+    /// A terminal that shows the screen of a terminal shared by a
+    /// collaborator, and sends its input to `input_tx`.
+    pub fn new_remote(title: String, input_tx: UnboundedSender<Vec<u8>>) -> TerminalBuilder {
+        let (events_tx, events_rx) = unbounded();
+        let term = Term::new(
+            Config::default(),
+            &TerminalSize::default(),
+            ZedListener(events_tx),
+        );
+        let backend = TerminalBackend::Remote { input_tx, title };
+        Self::build(
+            Arc::new(FairMutex::new(term)),
+            events_rx,
+            backend,
+            None,
+            CommandStatuses::default(),
+        )
+    }
+
+    fn build(
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        events_rx: UnboundedReceiver<AlacTermEvent>,
+        backend: TerminalBackend,
+        task: Option<TaskState>,
+        command_statuses: CommandStatuses,
+    ) -> TerminalBuilder {
         let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
         let word_regex = RegexSearch::new(r#"[\w.\[\]:/@\-~]+"#).unwrap();
 
         let terminal = Terminal {
-            pty_tx: Notifier(pty_tx),
+            backend,
             term,
             events: VecDeque::with_capacity(10), //Should never get this high.
             last_content: Default::default(),
             last_mouse: None,
             matches: Vec::new(),
             selection_head: None,
-            shell_fd: fd as u32,
-            shell_pid,
             foreground_process_info: None,
             breadcrumb_text: String::new(),
             scroll_px: px(0.),
@@ -393,12 +501,14 @@ impl TerminalBuilder {
             hovered_word: false,
             url_regex,
             word_regex,
+            task,
+            command_statuses,
         };
 
-        Ok(TerminalBuilder {
+        TerminalBuilder {
             terminal,
             events_rx,
-        })
+        }
     }
 
     pub fn subscribe(mut self, cx: &mut ModelContext<Terminal>) -> Terminal {
@@ -493,6 +603,15 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub prompt_marks: Vec<PromptMark>,
+}
+
+/// The start of a prompt that is visible in the terminal.
+#[derive(Clone, Debug)]
+pub struct PromptMark {
+    pub line: Line,
+    pub command_id: u64,
+    pub status: CommandStatus,
 }
 
 #[derive(Clone)]
@@ -517,6 +636,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            prompt_marks: Vec::new(),
         }
     }
 }
@@ -527,8 +647,54 @@ pub enum SelectionPhase {
     Ended,
 }
 
+/// Where a terminal's input is written to.
+enum TerminalBackend {
+    /// A shell running in a local pty.
+    Local {
+        pty_tx: Notifier,
+        shell_pid: u32,
+        shell_fd: u32,
+    },
+    /// A terminal shared by a collaborator, whose screen is sent to us.
+    Remote {
+        input_tx: UnboundedSender<Vec<u8>>,
+        title: String,
+    },
+}
+
+impl TerminalBackend {
+    fn notify<B: Into<Cow<'static, [u8]>>>(&self, bytes: B) {
+        match self {
+            TerminalBackend::Local { pty_tx, .. } => pty_tx.notify(bytes),
+            TerminalBackend::Remote { input_tx, .. } => {
+                input_tx.unbounded_send(bytes.into().into_owned()).ok();
+            }
+        }
+    }
+}
+
+/// The dimensions of a terminal's grid, without a size in pixels.
+struct GridSize {
+    columns: usize,
+    screen_lines: usize,
+}
+
+impl Dimensions for GridSize {
+    fn total_lines(&self) -> usize {
+        self.screen_lines
+    }
+
+    fn screen_lines(&self) -> usize {
+        self.screen_lines
+    }
+
+    fn columns(&self) -> usize {
+        self.columns
+    }
+}
+
 pub struct Terminal {
-    pty_tx: Notifier,
+    backend: TerminalBackend,
     term: Arc<FairMutex<Term<ZedListener>>>,
     events: VecDeque<InternalEvent>,
     /// This is only used for mouse mode cell change detection
@@ -539,8 +705,6 @@ pub struct Terminal {
     pub last_content: TerminalContent,
     pub selection_head: Option<AlacPoint>,
     pub breadcrumb_text: String,
-    shell_pid: u32,
-    shell_fd: u32,
     pub foreground_process_info: Option<LocalProcessInfo>,
     scroll_px: Pixels,
     next_link_id: usize,
@@ -549,6 +713,15 @@ pub struct Terminal {
     hovered_word: bool,
     url_regex: RegexSearch,
     word_regex: RegexSearch,
+    task: Option<TaskState>,
+    command_statuses: CommandStatuses,
+}
+
+/// The task that a terminal was spawned to run.
+#[derive(Clone, Debug)]
+pub struct TaskState {
+    pub id: TaskId,
+    pub label: String,
 }
 
 impl Terminal {
@@ -604,9 +777,17 @@ impl Terminal {
 
     /// Updates the cached process info, returns whether the Zed-relevant info has changed
     fn update_process_info(&mut self) -> bool {
-        let mut pid = unsafe { libc::tcgetpgrp(self.shell_fd as i32) };
+        let TerminalBackend::Local {
+            shell_pid,
+            shell_fd,
+            ..
+        } = &self.backend
+        else {
+            return false;
+        };
+        let mut pid = unsafe { libc::tcgetpgrp(*shell_fd as i32) };
         if pid < 0 {
-            pid = self.shell_pid as i32;
+            pid = *shell_pid as i32;
         }
 
         if let Some(process_info) = LocalProcessInfo::with_root_pid(pid as u32) {
@@ -644,11 +825,21 @@ impl Terminal {
                 new_size.size.height = cmp::max(new_size.line_height, new_size.height());
                 new_size.size.width = cmp::max(new_size.cell_width, new_size.width());
 
-                self.last_content.size = new_size.clone();
+                match &self.backend {
+                    TerminalBackend::Local { pty_tx, .. } => {
+                        self.last_content.size = new_size.clone();
 
-                self.pty_tx.0.send(Msg::Resize(new_size.into())).ok();
+                        pty_tx.0.send(Msg::Resize(new_size.into())).ok();
 
-                term.resize(new_size);
+                        term.resize(new_size);
+                    }
+                    // The grid of a shared terminal is sized by its host, so
+                    // only the size of its cells can change.
+                    TerminalBackend::Remote { .. } => {
+                        self.last_content.size =
+                            new_size.with_grid_size(term.columns(), term.screen_lines());
+                    }
+                }
             }
             InternalEvent::Clear => {
                 // Clear back buffer
@@ -729,7 +920,11 @@ impl Terminal {
                 )
                 .grid_clamp(term, Boundary::Grid);
 
-                let link = term.grid().index(point).hyperlink();
+                let link = term
+                    .grid()
+                    .index(point)
+                    .hyperlink()
+                    .filter(|link| !is_mark(link));
                 let found_word = if link.is_some() {
                     let mut min_index = point;
                     loop {
@@ -912,6 +1107,116 @@ impl Terminal {
         self.events.push_back(InternalEvent::Copy);
     }
 
+    /// Scrolls the closest prompt above the top of the viewport into view.
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let term = self.term.lock();
+        let display_offset = term.grid().display_offset() as i32;
+        let top = Line(-display_offset);
+        let prompt = command_regions(&term)
+            .into_values()
+            .filter_map(|regions| Some(regions.prompt?.line))
+            .filter(|line| *line < top)
+            .last();
+        drop(term);
+
+        if let Some(line) = prompt {
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                    -line.0 - display_offset,
+                )));
+        }
+    }
+
+    /// Scrolls the closest prompt below the top of the viewport into view, or
+    /// to the bottom if there are no more prompts.
+    pub fn scroll_to_next_prompt(&mut self) {
+        let term = self.term.lock();
+        let display_offset = term.grid().display_offset() as i32;
+        let top = Line(-display_offset);
+        let prompt = command_regions(&term)
+            .into_values()
+            .filter_map(|regions| Some(regions.prompt?.line))
+            .find(|line| *line > top);
+        drop(term);
+
+        let scroll = match prompt {
+            Some(line) => AlacScroll::Delta(-line.0 - display_offset),
+            None => AlacScroll::Bottom,
+        };
+        self.events.push_back(InternalEvent::Scroll(scroll));
+    }
+
+    pub fn select_last_command_output(&mut self) {
+        let term = self.term.lock();
+        let output = last_command_output(&term);
+        drop(term);
+
+        if let Some(output) = output {
+            self.set_selection(Some((make_selection(&output), *output.end())));
+            self.events
+                .push_back(InternalEvent::ScrollToAlacPoint(*output.start()));
+        }
+    }
+
+    /// The styled text of the last `max_lines` lines of output, to be restored
+    /// into a new terminal.
+    pub fn serialize_scrollback(&self, max_lines: usize, max_bytes: usize) -> Option<String> {
+        scrollback::serialize(&self.term.lock(), max_lines, max_bytes)
+    }
+
+    /// The visible screen, to be shown to collaborators.
+    pub fn screen_snapshot(&self) -> ScreenSnapshot {
+        scrollback::serialize_screen(&self.term.lock())
+    }
+
+    /// Redraws a terminal shared by a collaborator with the latest state of
+    /// their screen.
+    pub fn set_remote_screen(
+        &mut self,
+        title: String,
+        screen: &ScreenSnapshot,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let TerminalBackend::Remote {
+            title: remote_title,
+            ..
+        } = &mut self.backend
+        {
+            if *remote_title != title {
+                *remote_title = title;
+                cx.emit(Event::TitleChanged);
+            }
+        }
+
+        let mut term = self.term.lock();
+        if term.columns() != screen.columns || term.screen_lines() != screen.screen_lines {
+            term.resize(GridSize {
+                columns: screen.columns,
+                screen_lines: screen.screen_lines,
+            });
+            self.last_content.size = self
+                .last_content
+                .size
+                .with_grid_size(screen.columns, screen.screen_lines);
+        }
+        term.grid_mut().reset_region(..);
+        scrollback::restore(&mut term, "\x1b[0m\x1b[H");
+        scrollback::restore(&mut term, &screen.text);
+        drop(term);
+
+        cx.emit(Event::Wakeup);
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self.backend, TerminalBackend::Remote { .. })
+    }
+
+    pub fn last_command_output(&self) -> Option<String> {
+        let term = self.term.lock();
+        let output = last_command_output(&term)?;
+        Some(term.bounds_to_string(*output.start(), *output.end()))
+    }
+
     pub fn clear(&mut self) {
         self.events.push_back(InternalEvent::Clear)
     }
@@ -923,11 +1228,11 @@ impl Terminal {
 
     ///Write the Input payload to the tty.
     fn write_to_pty(&self, input: String) {
-        self.pty_tx.notify(input.into_bytes());
+        self.backend.notify(input.into_bytes());
     }
 
     fn write_bytes_to_pty(&self, input: Vec<u8>) {
-        self.pty_tx.notify(input);
+        self.backend.notify(input);
     }
 
     pub fn input(&mut self, input: String) {
@@ -984,11 +1289,17 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content =
+            Self::make_content(&terminal, &self.last_content, &self.command_statuses);
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        last_content: &TerminalContent,
+        command_statuses: &CommandStatuses,
+    ) -> TerminalContent {
         let content = term.renderable_content();
+        let mut prompt_marks = Vec::<PromptMark>::new();
         TerminalContent {
             cells: content
                 .display_iter
@@ -999,9 +1310,27 @@ impl Terminal {
                 //             && ic.c == ' '
                 //             && !ic.flags.contains(Flags::INVERSE))
                 // })
-                .map(|ic| IndexedCell {
-                    point: ic.point,
-                    cell: ic.cell.clone(),
+                .map(|ic| {
+                    let mut cell = ic.cell.clone();
+                    if let Some(mark) = cell.hyperlink().as_ref().and_then(Mark::from_hyperlink) {
+                        // Marks aren't meant to be rendered or opened as links.
+                        cell.set_hyperlink(None);
+                        let is_new_prompt = mark.kind == MarkKind::Prompt
+                            && prompt_marks
+                                .last()
+                                .map_or(true, |prompt| prompt.command_id != mark.command_id);
+                        if is_new_prompt {
+                            prompt_marks.push(PromptMark {
+                                line: ic.point.line,
+                                command_id: mark.command_id,
+                                status: command_statuses.get(mark.command_id),
+                            });
+                        }
+                    }
+                    IndexedCell {
+                        point: ic.point,
+                        cell,
+                    }
                 })
                 .collect::<Vec<IndexedCell>>(),
             mode: content.mode,
@@ -1012,6 +1341,7 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            prompt_marks,
         }
     }
 
@@ -1061,7 +1391,7 @@ impl Terminal {
 
             if self.mouse_changed(point, side) {
                 if let Some(bytes) = mouse_moved_report(point, e, self.last_content.mode) {
-                    self.pty_tx.notify(bytes);
+                    self.backend.notify(bytes);
                 }
             }
         } else if self.cmd_pressed {
@@ -1135,7 +1465,7 @@ impl Terminal {
             if let Some(bytes) =
                 mouse_button_report(point, e.button, e.modifiers, true, self.last_content.mode)
             {
-                self.pty_tx.notify(bytes);
+                self.backend.notify(bytes);
             }
         } else if e.button == MouseButton::Left {
             let position = e.position - origin;
@@ -1182,7 +1512,7 @@ impl Terminal {
             if let Some(bytes) =
                 mouse_button_report(point, e.button, e.modifiers, false, self.last_content.mode)
             {
-                self.pty_tx.notify(bytes);
+                self.backend.notify(bytes);
             }
         } else {
             if e.button == MouseButton::Left && setting.copy_on_select {
@@ -1221,7 +1551,7 @@ impl Terminal {
                     scroll_report(point, scroll_lines as i32, e, self.last_content.mode)
                 {
                     for scroll in scrolls {
-                        self.pty_tx.notify(scroll);
+                        self.backend.notify(scroll);
                     }
                 };
             } else if self
@@ -1230,7 +1560,7 @@ impl Terminal {
                 .contains(TermMode::ALT_SCREEN | TermMode::ALTERNATE_SCROLL)
                 && !e.shift
             {
-                self.pty_tx.notify(alt_scroll(scroll_lines))
+                self.backend.notify(alt_scroll(scroll_lines))
             } else {
                 if scroll_lines != 0 {
                     let scroll = AlacScroll::Delta(scroll_lines);
@@ -1286,6 +1616,21 @@ impl Terminal {
     }
 
     pub fn title(&self, truncate: bool) -> String {
+        if let Some(task) = &self.task {
+            return if truncate {
+                truncate_and_trailoff(&task.label, 50)
+            } else {
+                task.label.clone()
+            };
+        }
+        if let TerminalBackend::Remote { title, .. } = &self.backend {
+            return if truncate {
+                truncate_and_trailoff(title, 50)
+            } else {
+                title.clone()
+            };
+        }
+
         self.foreground_process_info
             .as_ref()
             .map(|fpi| {
@@ -1319,11 +1664,17 @@ impl Terminal {
     pub fn can_navigate_to_selected_word(&self) -> bool {
         self.cmd_pressed && self.hovered_word
     }
+
+    pub fn task(&self) -> Option<&TaskState> {
+        self.task.as_ref()
+    }
 }
 
 impl Drop for Terminal {
     fn drop(&mut self) {
-        self.pty_tx.0.send(Msg::Shutdown).ok();
+        if let TerminalBackend::Local { pty_tx, .. } = &self.backend {
+            pty_tx.0.send(Msg::Shutdown).ok();
+        }
     }
 }
 
@@ -1353,6 +1704,68 @@ pub fn visible_regex_match_iter<'a, T>(
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
+/// The regions of a command that the shell integration marked in the grid.
+#[derive(Default)]
+struct CommandRegions {
+    prompt: Option<AlacPoint>,
+    output: Option<RangeInclusive<AlacPoint>>,
+}
+
+/// Finds the marked regions of every command in the grid, by the order in
+/// which the commands were run.
+fn command_regions<T>(term: &Term<T>) -> BTreeMap<u64, CommandRegions> {
+    let mut commands = BTreeMap::<u64, CommandRegions>::new();
+    let grid = term.grid();
+    // Marks span runs of cells, so they're only parsed where the link changes.
+    let mut previous = None::<(Hyperlink, Option<Mark>)>;
+    for line in grid.topmost_line().0..=grid.bottommost_line().0 {
+        for column in 0..grid.columns() {
+            let point = AlacPoint::new(Line(line), Column(column));
+            let Some(link) = grid[point].hyperlink() else {
+                continue;
+            };
+            let cached_mark = previous
+                .as_ref()
+                .filter(|(previous_link, _)| *previous_link == link)
+                .map(|(_, mark)| *mark);
+            let mark = match cached_mark {
+                Some(mark) => mark,
+                None => {
+                    let mark = Mark::from_hyperlink(&link);
+                    previous = Some((link, mark));
+                    mark
+                }
+            };
+            let Some(mark) = mark else {
+                continue;
+            };
+
+            let regions = commands.entry(mark.command_id).or_default();
+            match mark.kind {
+                MarkKind::Prompt => {
+                    regions.prompt.get_or_insert(point);
+                }
+                MarkKind::Command => {}
+                MarkKind::Output => {
+                    let start = regions
+                        .output
+                        .as_ref()
+                        .map_or(point, |output| *output.start());
+                    regions.output = Some(start..=point);
+                }
+            }
+        }
+    }
+    commands
+}
+
+fn last_command_output<T>(term: &Term<T>) -> Option<RangeInclusive<AlacPoint>> {
+    command_regions(term)
+        .into_values()
+        .rev()
+        .find_map(|regions| regions.output)
+}
+
 fn make_selection(range: &RangeInclusive<AlacPoint>) -> Selection {
     let mut selection = Selection::new(SelectionType::Simple, *range.start(), AlacDirection::Left);
     selection.update(*range.end(), AlacDirection::Right);
@@ -1462,15 +1875,22 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 
 #[cfg(test)]
 mod tests {
+    use std::io::Read;
+
     use alacritty_terminal::{
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, Config},
+        vte::ansi::Processor,
+        Term,
     };
+    use futures::channel::mpsc::unbounded;
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        command_regions, content_index_for_mouse, last_command_output, rgb_for_index,
+        shell_integration::{CommandStatuses, SemanticPromptReader},
+        IndexedCell, TerminalContent, TerminalSize, ZedListener,
     };
 
     #[test]
@@ -1482,6 +1902,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_command_regions() {
+        let input: &[u8] = b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.rs\r\nb.rs\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ ";
+        let mut output = Vec::new();
+        SemanticPromptReader::new(input, CommandStatuses::default())
+            .read_to_end(&mut output)
+            .unwrap();
+
+        let (events_tx, _events_rx) = unbounded();
+        let mut term = Term::new(
+            Config::default(),
+            &TerminalSize::default(),
+            ZedListener(events_tx),
+        );
+        let mut processor: Processor = Processor::new();
+        for byte in output {
+            processor.advance(&mut term, byte);
+        }
+
+        let prompts = command_regions(&term)
+            .into_values()
+            .filter_map(|regions| regions.prompt)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            prompts,
+            [
+                AlacPoint::new(Line(0), Column(0)),
+                AlacPoint::new(Line(3), Column(0))
+            ]
+        );
+        assert_eq!(
+            last_command_output(&term),
+            Some(AlacPoint::new(Line(1), Column(0))..=AlacPoint::new(Line(2), Column(3)))
+        );
+    }
+
     #[test]
     fn test_mouse_to_cell_test() {
         let mut rng = thread_rng();
@@ -30,6 +30,8 @@ pub struct TerminalSettings {
     pub alternate_scroll: AlternateScroll,
     pub option_as_meta: bool,
     pub copy_on_select: bool,
+    pub shell_integration: bool,
+    pub persistent_scrollback_lines: usize,
     pub dock: TerminalDockPosition,
     pub default_width: Pixels,
     pub default_height: Pixels,
@@ -130,6 +132,16 @@ pub struct TerminalSettingsContent {
     ///
     /// Default: false
     pub copy_on_select: Option<bool>,
+    /// Whether to load Zed's shell integration into bash, zsh and fish, so
+    /// that prompts, commands and their exit status are marked in the terminal.
+    ///
+    /// Default: true
+    pub shell_integration: Option<bool>,
+    /// The number of lines of each terminal's scrollback to save, so that
+    /// they're shown again when the workspace is reopened.
+    ///
+    /// Default: 0
+    pub persistent_scrollback_lines: Option<usize>,
     pub dock: Option<TerminalDockPosition>,
     /// Default width when the terminal is docked to the left or right.
     ///
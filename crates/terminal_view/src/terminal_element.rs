@@ -1,11 +1,11 @@
 use editor::{Cursor, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    div, fill, point, px, relative, AnyElement, AvailableSpace, Bounds, DispatchPhase, Element,
-    ElementContext, ElementId, FocusHandle, Font, FontStyle, FontWeight, HighlightStyle, Hsla,
-    InputHandler, InteractiveBounds, InteractiveElement, InteractiveElementState, Interactivity,
-    IntoElement, LayoutId, Model, ModelContext, ModifiersChangedEvent, MouseButton, MouseMoveEvent,
-    Pixels, Point, ShapedLine, StatefulInteractiveElement, Styled, TextRun, TextStyle, TextSystem,
-    UnderlineStyle, WeakView, WhiteSpace, WindowContext,
+    div, fill, point, px, relative, size, AnyElement, AvailableSpace, Bounds, DispatchPhase,
+    Element, ElementContext, ElementId, FocusHandle, Font, FontStyle, FontWeight, HighlightStyle,
+    Hsla, InputHandler, InteractiveBounds, InteractiveElement, InteractiveElementState,
+    Interactivity, IntoElement, LayoutId, Model, ModelContext, ModifiersChangedEvent, MouseButton,
+    MouseMoveEvent, Pixels, Point, ShapedLine, StatefulInteractiveElement, Styled, TextRun,
+    TextStyle, TextSystem, UnderlineStyle, WeakView, WhiteSpace, WindowContext,
 };
 use itertools::Itertools;
 use language::CursorShape;
@@ -17,6 +17,7 @@ use terminal::{
         term::{cell::Flags, TermMode},
         vte::ansi::{Color as AnsiColor, Color::Named, CursorShape as AlacCursorShape, NamedColor},
     },
+    shell_integration::CommandStatus,
     terminal_settings::TerminalSettings,
     IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
@@ -27,6 +28,8 @@ use workspace::Workspace;
 use std::mem;
 use std::{fmt::Debug, ops::RangeInclusive};
 
+const PROMPT_MARKER_WIDTH: Pixels = px(2.);
+
 /// The information generated during layout that is necessary for painting.
 pub struct LayoutState {
     cells: Vec<LayoutCell>,
@@ -39,6 +42,8 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// The display line of each visible prompt, colored by its command's status.
+    prompt_markers: Vec<(i32, Hsla)>,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -494,9 +499,24 @@ impl TerminalElement {
             cursor_char,
             selection,
             cursor,
+            prompt_marks,
             ..
         } = &self.terminal.read(cx).last_content;
 
+        let prompt_markers = prompt_marks
+            .iter()
+            .map(|prompt_mark| {
+                let color = match prompt_mark.status {
+                    CommandStatus::Finished(Some(0)) => theme.status().success,
+                    CommandStatus::Finished(Some(_)) => theme.status().error,
+                    CommandStatus::Finished(None) | CommandStatus::Pending => {
+                        theme.colors().text_muted
+                    }
+                };
+                (prompt_mark.line.0 + *display_offset as i32, color)
+            })
+            .collect();
+
         // searches, highlights to a single range representations
         let mut relative_highlighted_ranges = Vec::new();
         for search_match in search_matches {
@@ -580,6 +600,7 @@ impl TerminalElement {
             display_offset: *display_offset,
             hyperlink_tooltip,
             gutter,
+            prompt_markers,
         }
     }
 
@@ -798,6 +819,15 @@ impl Element for TerminalElement {
                     rect.paint(origin, &layout, cx);
                 }
 
+                for (line, color) in &layout.prompt_markers {
+                    let position = point(
+                        bounds.origin.x,
+                        origin.y + *line as f32 * layout.dimensions.line_height,
+                    );
+                    let marker_size = size(PROMPT_MARKER_WIDTH, layout.dimensions.line_height);
+                    cx.paint_quad(fill(Bounds::new(position, marker_size), *color));
+                }
+
                 cx.with_z_index(1, |cx| {
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
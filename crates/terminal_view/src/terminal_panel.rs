@@ -12,6 +12,7 @@ use project::{Fs, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use task::SpawnInTerminal;
 use terminal::terminal_settings::{TerminalDockPosition, TerminalSettings};
 use ui::{h_flex, ButtonCommon, Clickable, IconButton, IconSize, Selectable, Tooltip};
 use util::{ResultExt, TryFutureExt};
@@ -75,7 +76,7 @@ impl TerminalPanel {
                             .icon_size(IconSize::Small)
                             .on_click(move |_, cx| {
                                 terminal_panel
-                                    .update(cx, |panel, cx| panel.add_terminal(None, cx))
+                                    .update(cx, |panel, cx| panel.add_terminal(None, None, cx))
                                     .log_err();
                             })
                             .tooltip(|cx| Tooltip::text("New Terminal", cx)),
@@ -266,7 +267,7 @@ impl TerminalPanel {
         };
 
         this.update(cx, |this, cx| {
-            this.add_terminal(Some(action.working_directory.clone()), cx)
+            this.add_terminal(Some(action.working_directory.clone()), None, cx)
         })
     }
 
@@ -280,11 +281,23 @@ impl TerminalPanel {
             return;
         };
 
-        this.update(cx, |this, cx| this.add_terminal(None, cx))
+        this.update(cx, |this, cx| this.add_terminal(None, None, cx))
     }
 
-    fn add_terminal(&mut self, working_directory: Option<PathBuf>, cx: &mut ViewContext<Self>) {
+    /// Runs a task in a new terminal, or in place of the terminal that the
+    /// same task ran in before.
+    pub fn spawn_task(&mut self, spawn_task: SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        self.add_terminal(None, Some(spawn_task), cx);
+    }
+
+    fn add_terminal(
+        &mut self,
+        working_directory: Option<PathBuf>,
+        spawn_task: Option<SpawnInTerminal>,
+        cx: &mut ViewContext<Self>,
+    ) {
         let workspace = self.workspace.clone();
+        let task_id = spawn_task.as_ref().map(|spawn_task| spawn_task.id.clone());
         cx.spawn(|this, mut cx| async move {
             let pane = this.update(&mut cx, |this, _| this.pane.clone())?;
             workspace.update(&mut cx, |workspace, cx| {
@@ -299,7 +312,7 @@ impl TerminalPanel {
                 let window = cx.window_handle();
                 if let Some(terminal) = workspace.project().update(cx, |project, cx| {
                     project
-                        .create_terminal(working_directory, window, cx)
+                        .create_terminal(working_directory, spawn_task, None, window, cx)
                         .log_err()
                 }) {
                     let terminal = Box::new(cx.new_view(|cx| {
@@ -312,7 +325,19 @@ impl TerminalPanel {
                     }));
                     pane.update(cx, |pane, cx| {
                         let focus = pane.has_focus(cx);
-                        pane.add_item(terminal, true, focus, None, cx);
+                        let task_index = task_id.and_then(|task_id| {
+                            pane.items().position(|item| {
+                                item.downcast::<TerminalView>()
+                                    .map_or(false, |terminal_view| {
+                                        let terminal = terminal_view.read(cx).terminal().read(cx);
+                                        terminal.task().map_or(false, |task| task.id == task_id)
+                                    })
+                            })
+                        });
+                        if let Some(task_index) = task_index {
+                            pane.remove_item(task_index, false, cx);
+                        }
+                        pane.add_item(terminal, true, focus, task_index, cx);
                     });
                 }
             })?;
@@ -451,10 +476,14 @@ impl Panel for TerminalPanel {
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
         if active && self.pane.read(cx).items_len() == 0 {
-            self.add_terminal(None, cx)
+            self.add_terminal(None, None, cx)
         }
     }
 
+    fn pane(&self) -> Option<View<Pane>> {
+        Some(self.pane.clone())
+    }
+
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
         let count = self.pane.read(cx).items_len();
         if count == 0 {
@@ -2,32 +2,39 @@ mod persistence;
 pub mod terminal_element;
 pub mod terminal_panel;
 
+use client::proto::{self, PeerId};
 use editor::{scroll::Autoscroll, Editor};
 use gpui::{
-    div, impl_actions, overlay, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle,
-    FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton, MouseDownEvent, Pixels,
-    Render, Styled, Subscription, Task, View, VisualContext, WeakView,
+    div, impl_actions, overlay, AnyElement, AppContext, ClipboardItem, DismissEvent, EventEmitter,
+    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
+    MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext, WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
-use project::{search::SearchQuery, LocalWorktree, Project};
+use project::{
+    search::SearchQuery,
+    terminals::{deserialize_terminal_screen, serialize_terminal_screen},
+    LocalWorktree, Project,
+};
 use terminal::{
     alacritty_terminal::{
         index::Point,
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ShowCharacterPalette, Terminal,
+    AllowCollaboratorInput, Clear, Copy, CopyLastCommandOutput, DisallowCollaboratorInput, Event,
+    MaybeNavigationTarget, Paste, ScrollToNextPrompt, ScrollToPreviousPrompt,
+    SelectLastCommandOutput, ShareTerminal, ShowCharacterPalette, Terminal, UnshareTerminal,
 };
 use terminal_element::TerminalElement;
 use ui::{h_flex, prelude::*, ContextMenu, Icon, IconName, Label};
 use util::{paths::PathLikeWithPosition, ResultExt};
 use workspace::{
-    item::{BreadcrumbText, Item, ItemEvent},
+    item::{BreadcrumbText, FollowEvent, FollowableItem, Item, ItemEvent},
     notifications::NotifyResultExt,
-    register_deserializable_item,
+    register_deserializable_item, register_followable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
-    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, Workspace,
+    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, ViewId, Workspace,
     WorkspaceId,
 };
 
@@ -45,6 +52,10 @@ use std::{
 };
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+/// How often a terminal's scrollback is saved while it has new output.
+const SCROLLBACK_SERIALIZATION_DELAY: Duration = Duration::from_secs(5);
+/// The most scrollback that is saved for each terminal.
+const MAX_PERSISTENT_SCROLLBACK_BYTES: usize = 1024 * 1024;
 
 ///Event to transmit the scroll from the element to the view
 #[derive(Clone, Debug, PartialEq)]
@@ -63,6 +74,7 @@ pub fn init(cx: &mut AppContext) {
     terminal::init(cx);
 
     register_deserializable_item::<TerminalView>(cx);
+    register_followable_item::<TerminalView>(cx);
 
     cx.observe_new_views(|workspace: &mut Workspace, _| {
         workspace.register_action(TerminalView::deploy);
@@ -85,6 +97,8 @@ pub struct TerminalView {
     blink_epoch: usize,
     can_navigate_to_selected_word: bool,
     workspace_id: WorkspaceId,
+    scrollback_serialization: Option<Task<()>>,
+    remote_id: Option<ViewId>,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -113,7 +127,7 @@ impl TerminalView {
         let terminal = workspace
             .project()
             .update(cx, |project, cx| {
-                project.create_terminal(working_directory, window, cx)
+                project.create_terminal(working_directory, None, None, window, cx)
             })
             .notify_err(workspace, cx);
 
@@ -143,6 +157,7 @@ impl TerminalView {
                 if !this.focus_handle.is_focused(cx) {
                     this.has_new_content = true;
                 }
+                this.schedule_scrollback_serialization(cx);
                 cx.notify();
                 cx.emit(Event::Wakeup);
                 cx.emit(ItemEvent::UpdateTab);
@@ -292,10 +307,59 @@ impl TerminalView {
             blink_epoch: 0,
             can_navigate_to_selected_word: false,
             workspace_id,
+            scrollback_serialization: None,
+            remote_id: None,
             _subscriptions: vec![focus_in, focus_out],
         }
     }
 
+    /// Saves the scrollback once the output has settled, if the user opted
+    /// into persisting it.
+    fn schedule_scrollback_serialization(&mut self, cx: &mut ViewContext<Self>) {
+        if self.scrollback_serialization.is_some() || !self.persists_scrollback(cx) {
+            return;
+        }
+
+        self.scrollback_serialization = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(SCROLLBACK_SERIALIZATION_DELAY)
+                .await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(task) = this.serialize_scrollback(cx) {
+                    task.detach();
+                }
+            })
+            .ok();
+        }));
+    }
+
+    fn persists_scrollback(&self, cx: &AppContext) -> bool {
+        TerminalSettings::get_global(cx).persistent_scrollback_lines > 0
+            && !self.terminal.read(cx).is_remote()
+    }
+
+    /// Saves the scrollback right away, cancelling any scheduled save.
+    fn serialize_scrollback(&mut self, cx: &mut ViewContext<Self>) -> Option<Task<()>> {
+        self.scrollback_serialization = None;
+        if !self.persists_scrollback(cx) {
+            return None;
+        }
+
+        let max_lines = TerminalSettings::get_global(cx).persistent_scrollback_lines;
+        let scrollback = self
+            .terminal
+            .read(cx)
+            .serialize_scrollback(max_lines, MAX_PERSISTENT_SCROLLBACK_BYTES)?;
+        let item_id = cx.entity_id().as_u64();
+        let workspace_id = self.workspace_id;
+        Some(cx.background_executor().spawn(async move {
+            TERMINAL_DB
+                .save_scrollback(item_id, workspace_id, scrollback)
+                .await
+                .log_err();
+        }))
+    }
+
     pub fn model(&self) -> &Model<Terminal> {
         &self.terminal
     }
@@ -318,9 +382,34 @@ impl TerminalView {
         position: gpui::Point<Pixels>,
         cx: &mut ViewContext<Self>,
     ) {
+        // Terminals can be shared by the host of a shared project, who
+        // decides whether collaborators may type into them.
+        let shared_state = self.workspace.upgrade().and_then(|workspace| {
+            let project = workspace.read(cx).project().read(cx);
+            (project.is_shared() && !self.terminal.read(cx).is_remote()).then(|| {
+                project
+                    .shared_terminal_id(&self.terminal)
+                    .map(|terminal_id| project.is_terminal_writable(terminal_id))
+            })
+        });
         let context_menu = ContextMenu::build(cx, |menu, _| {
-            menu.action("Clear", Box::new(Clear))
-                .action("Close", Box::new(CloseActiveItem { save_intent: None }))
+            let menu = menu.action("Clear", Box::new(Clear));
+            let menu = match shared_state {
+                Some(None) => menu.action("Share With Collaborators", Box::new(ShareTerminal)),
+                Some(Some(writable)) => {
+                    let menu = menu.action("Stop Sharing", Box::new(UnshareTerminal));
+                    if writable {
+                        menu.action(
+                            "Disallow Collaborator Input",
+                            Box::new(DisallowCollaboratorInput),
+                        )
+                    } else {
+                        menu.action("Allow Collaborator Input", Box::new(AllowCollaboratorInput))
+                    }
+                }
+                None => menu,
+            };
+            menu.action("Close", Box::new(CloseActiveItem { save_intent: None }))
         });
 
         cx.focus_view(&context_menu);
@@ -454,6 +543,96 @@ impl TerminalView {
         self.terminal.update(cx, |term, _| term.copy())
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn select_last_command_output(
+        &mut self,
+        _: &SelectLastCommandOutput,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.select_last_command_output());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        if let Some(output) = self.terminal.read(cx).last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new(output));
+        }
+    }
+
+    fn share_terminal(&mut self, _: &ShareTerminal, cx: &mut ViewContext<Self>) {
+        let terminal = self.terminal.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                project
+                    .update(cx, |project, cx| project.share_terminal(&terminal, cx))
+                    .notify_err(workspace, cx);
+            })
+            .ok();
+    }
+
+    fn unshare_terminal(&mut self, _: &UnshareTerminal, cx: &mut ViewContext<Self>) {
+        let terminal = self.terminal.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                project
+                    .update(cx, |project, cx| {
+                        let terminal_id = project
+                            .shared_terminal_id(&terminal)
+                            .ok_or_else(|| anyhow::anyhow!("terminal is not shared"))?;
+                        project.unshare_terminal(terminal_id, cx)
+                    })
+                    .notify_err(workspace, cx);
+            })
+            .ok();
+    }
+
+    fn allow_collaborator_input(&mut self, _: &AllowCollaboratorInput, cx: &mut ViewContext<Self>) {
+        self.set_collaborator_input(true, cx);
+    }
+
+    fn disallow_collaborator_input(
+        &mut self,
+        _: &DisallowCollaboratorInput,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.set_collaborator_input(false, cx);
+    }
+
+    fn set_collaborator_input(&mut self, writable: bool, cx: &mut ViewContext<Self>) {
+        let terminal = self.terminal.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let project = workspace.project().clone();
+                project
+                    .update(cx, |project, cx| {
+                        let terminal_id = project
+                            .shared_terminal_id(&terminal)
+                            .ok_or_else(|| anyhow::anyhow!("terminal is not shared"))?;
+                        project.set_terminal_writable(terminal_id, writable, cx)
+                    })
+                    .notify_err(workspace, cx);
+            })
+            .ok();
+    }
+
     ///Attempt to paste the clipboard into the terminal
     fn paste(&mut self, _: &Paste, cx: &mut ViewContext<Self>) {
         if let Some(item) = cx.read_from_clipboard() {
@@ -653,6 +832,14 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::clear))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::select_last_command_output))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
+            .on_action(cx.listener(TerminalView::share_terminal))
+            .on_action(cx.listener(TerminalView::unshare_terminal))
+            .on_action(cx.listener(TerminalView::allow_collaborator_input))
+            .on_action(cx.listener(TerminalView::disallow_collaborator_input))
             .on_key_down(cx.listener(Self::key_down))
             .on_mouse_down(
                 MouseButton::Right,
@@ -758,6 +945,10 @@ impl Item for TerminalView {
         Some("Terminal")
     }
 
+    fn serialize_on_quit(&mut self, cx: &mut ViewContext<Self>) -> Option<Task<()>> {
+        self.serialize_scrollback(cx)
+    }
+
     fn deserialize(
         project: Model<Project>,
         workspace: WeakView<Workspace>,
@@ -785,8 +976,19 @@ impl Item for TerminalView {
                     .flatten()
                 });
 
+            let persist_scrollback =
+                cx.update(|cx| TerminalSettings::get_global(cx).persistent_scrollback_lines > 0)?;
+            let scrollback = if persist_scrollback {
+                TERMINAL_DB
+                    .get_scrollback(item_id, workspace_id)
+                    .log_err()
+                    .flatten()
+            } else {
+                None
+            };
+
             let terminal = project.update(&mut cx, |project, cx| {
-                project.create_terminal(cwd, window, cx)
+                project.create_terminal(cwd, None, scrollback, window, cx)
             })??;
             pane.update(&mut cx, |_, cx| {
                 cx.new_view(|cx| TerminalView::new(terminal, workspace, workspace_id, cx))
@@ -810,6 +1012,102 @@ impl Item for TerminalView {
     }
 }
 
+impl FollowableItem for TerminalView {
+    fn remote_id(&self) -> Option<ViewId> {
+        self.remote_id
+    }
+
+    fn to_state_proto(&self, cx: &WindowContext) -> Option<proto::view::Variant> {
+        // Only shared terminals can be followed.
+        let workspace = self.workspace.upgrade()?;
+        let project = workspace.read(cx).project().read(cx);
+        let terminal_id = project.shared_terminal_id(&self.terminal)?;
+        let terminal = self.terminal.read(cx);
+        Some(proto::view::Variant::Terminal(proto::view::Terminal {
+            terminal_id,
+            title: terminal.title(false),
+            screen: Some(serialize_terminal_screen(terminal.screen_snapshot())),
+        }))
+    }
+
+    fn from_state_proto(
+        pane: View<Pane>,
+        workspace: View<Workspace>,
+        remote_id: ViewId,
+        state: &mut Option<proto::view::Variant>,
+        cx: &mut WindowContext,
+    ) -> Option<Task<anyhow::Result<View<Self>>>> {
+        let Some(proto::view::Variant::Terminal(_)) = state else {
+            return None;
+        };
+        let Some(proto::view::Variant::Terminal(state)) = state.take() else {
+            unreachable!()
+        };
+
+        let project = workspace.read(cx).project().clone();
+        let workspace_id = workspace.read(cx).database_id();
+        let terminal = project.update(cx, |project, cx| {
+            if let Some(screen) = state.screen {
+                project.update_remote_terminal(
+                    state.terminal_id,
+                    state.title,
+                    deserialize_terminal_screen(screen),
+                    cx,
+                );
+            }
+            project.open_shared_terminal(state.terminal_id, cx)
+        });
+        let terminal = match terminal {
+            Ok(terminal) => terminal,
+            Err(error) => return Some(Task::ready(Err(error))),
+        };
+
+        let view = pane.update(cx, |pane, cx| {
+            let existing_view = pane
+                .items_of_type::<Self>()
+                .find(|view| view.read(cx).terminal == terminal);
+            let view = existing_view.unwrap_or_else(|| {
+                cx.new_view(|cx| {
+                    TerminalView::new(terminal, workspace.downgrade(), workspace_id, cx)
+                })
+            });
+            view.update(cx, |view, _| view.remote_id = Some(remote_id));
+            view
+        });
+        Some(Task::ready(Ok(view)))
+    }
+
+    fn to_follow_event(_: &Self::Event) -> Option<FollowEvent> {
+        None
+    }
+
+    // The screen of a shared terminal is sent to every guest as it changes,
+    // so followers don't need to be sent anything else.
+    fn add_event_to_update_proto(
+        &self,
+        _: &Self::Event,
+        _: &mut Option<proto::update_view::Variant>,
+        _: &WindowContext,
+    ) -> bool {
+        false
+    }
+
+    fn apply_update_proto(
+        &mut self,
+        _: &Model<Project>,
+        _: proto::update_view::Variant,
+        _: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        Task::ready(Ok(()))
+    }
+
+    fn is_project_item(&self, _: &WindowContext) -> bool {
+        true
+    }
+
+    fn set_leader_peer_id(&mut self, _: Option<PeerId>, _: &mut ViewContext<Self>) {}
+}
+
 impl SearchableItem for TerminalView {
     type Match = RangeInclusive<Point>;
 
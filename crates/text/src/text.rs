@@ -23,6 +23,7 @@ use lazy_static::lazy_static;
 use regex::Regex;
 pub use rope::*;
 pub use selection::*;
+use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     cmp::{self, Ordering, Reverse},
@@ -119,6 +120,83 @@ pub struct Transaction {
     pub start: clock::Global,
 }
 
+/// A buffer's undo and redo stacks, described as plain text edits so that they
+/// can be stored and replayed into a buffer loaded in a later session.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedHistory {
+    /// The text before the first transaction was applied.
+    pub base_text: String,
+    /// The edits of each transaction in the order they were applied, with ranges
+    /// expressed in the text preceding that transaction.
+    pub transactions: Vec<Vec<(Range<usize>, String)>>,
+    /// The number of trailing transactions that are undone, forming the redo stack.
+    pub undone_count: usize,
+}
+
+/// A copy of a buffer's history, from which a [`SerializedHistory`] can be built
+/// without holding onto the buffer.
+#[derive(Clone)]
+pub struct HistorySnapshot {
+    replica_id: ReplicaId,
+    remote_id: BufferId,
+    base_text: Rope,
+    operations: TreeMap<clock::Lamport, Operation>,
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+}
+
+impl HistorySnapshot {
+    /// Describes the undo and redo stacks as a sequence of text edits, see [`SerializedHistory`].
+    pub fn serialize(self) -> Result<SerializedHistory> {
+        // Walk the history on a replica, as undoing and redoing requires a buffer.
+        let mut replica = Buffer::new(self.replica_id, self.remote_id, self.base_text.to_string());
+        replica.apply_ops(self.operations.values().cloned())?;
+        if replica.deferred_ops_len() > 0 {
+            return Err(anyhow!("history contains operations that can't be applied"));
+        }
+        replica.history.undo_stack = self.undo_stack;
+        replica.history.redo_stack = self.redo_stack;
+
+        let mut redo_count = 0;
+        while replica.redo().is_some() {
+            redo_count += 1;
+        }
+
+        let mut transactions = Vec::new();
+        let mut undone_count = redo_count;
+        for ix in 0.. {
+            let snapshot = replica.snapshot();
+            if replica.undo().is_none() {
+                break;
+            }
+
+            let edits = replica
+                .edits_since::<usize>(&snapshot.version)
+                .map(|edit| {
+                    (
+                        edit.new,
+                        snapshot.text_for_range(edit.old).collect::<String>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            if edits.is_empty() {
+                if ix < redo_count {
+                    undone_count -= 1;
+                }
+            } else {
+                transactions.push(edits);
+            }
+        }
+        transactions.reverse();
+
+        Ok(SerializedHistory {
+            base_text: replica.text(),
+            transactions,
+            undone_count,
+        })
+    }
+}
+
 impl HistoryEntry {
     pub fn transaction_id(&self) -> TransactionId {
         self.transaction.id
@@ -1254,6 +1332,76 @@ impl Buffer {
         }
     }
 
+    /// Captures the state needed to serialize the undo and redo stacks, which can be
+    /// done on a background thread by calling [`HistorySnapshot::serialize`].
+    pub fn history_snapshot(&self) -> HistorySnapshot {
+        HistorySnapshot {
+            replica_id: self.replica_id,
+            remote_id: self.remote_id,
+            base_text: self.history.base_text.clone(),
+            operations: self.history.operations.clone(),
+            undo_stack: self.history.undo_stack.clone(),
+            redo_stack: self.history.redo_stack.clone(),
+        }
+    }
+
+    /// Rebuilds the undo and redo stacks of a buffer without history, by replaying the
+    /// transactions of a [`SerializedHistory`] on top of its base text. Fails without
+    /// modifying the buffer if the history doesn't lead to the buffer's current text.
+    ///
+    /// The resulting operations are only recorded in the buffer's history, so this must
+    /// only be called on a buffer that hasn't been replicated yet.
+    pub fn restore_history(&mut self, history: SerializedHistory) -> Result<()> {
+        if !self.history.undo_stack.is_empty() || !self.history.redo_stack.is_empty() {
+            return Err(anyhow!("buffer already has an undo history"));
+        }
+
+        let applied_count = history
+            .transactions
+            .len()
+            .checked_sub(history.undone_count)
+            .context("more transactions undone than recorded")?;
+        let (applied, undone) = history.transactions.split_at(applied_count);
+        let mut text = history.base_text.clone();
+        for edits in applied {
+            apply_serialized_edits(&mut text, edits)?;
+        }
+        let current_text = self.text();
+        if text != current_text {
+            return Err(anyhow!("history doesn't match the buffer's text"));
+        }
+        for edits in undone {
+            apply_serialized_edits(&mut text, edits)?;
+        }
+
+        // Only replace the part of the text that differs from the base text, so that
+        // anchors outside of it stay valid.
+        let prefix_len = common_prefix_len(&current_text, &history.base_text);
+        let suffix_len = common_suffix_len(
+            &current_text[prefix_len..],
+            &history.base_text[prefix_len..],
+        );
+        let old_range = prefix_len..current_text.len() - suffix_len;
+        let new_range = prefix_len..history.base_text.len() - suffix_len;
+        if !old_range.is_empty() || !new_range.is_empty() {
+            self.start_transaction();
+            self.edit([(old_range, &history.base_text[new_range])]);
+            if let Some((transaction_id, _)) = self.end_transaction() {
+                self.forget_transaction(transaction_id);
+            }
+        }
+        for edits in history.transactions {
+            self.start_transaction();
+            self.edit(edits);
+            self.end_transaction();
+            self.finalize_last_transaction();
+        }
+        for _ in 0..history.undone_count {
+            self.undo();
+        }
+        Ok(())
+    }
+
     pub fn redo_to_transaction(&mut self, transaction_id: TransactionId) -> Vec<Operation> {
         let transactions = self
             .history
@@ -2709,3 +2857,37 @@ impl LineEnding {
         }
     }
 }
+
+/// Applies edits whose ranges are all expressed in the text preceding them, as
+/// recorded in a [`SerializedHistory`].
+fn apply_serialized_edits(text: &mut String, edits: &[(Range<usize>, String)]) -> Result<()> {
+    let mut max_end = text.len();
+    for (range, new_text) in edits.iter().rev() {
+        if range.start > range.end
+            || range.end > max_end
+            || !text.is_char_boundary(range.start)
+            || !text.is_char_boundary(range.end)
+        {
+            return Err(anyhow!("invalid edit {range:?} in serialized history"));
+        }
+        text.replace_range(range.clone(), new_text);
+        max_end = range.start;
+    }
+    Ok(())
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|((_, a), b)| a != b)
+        .map_or_else(|| a.len().min(b.len()), |((ix, _), _)| ix)
+}
+
+fn common_suffix_len(a: &str, b: &str) -> usize {
+    a.chars()
+        .rev()
+        .zip(b.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum()
+}
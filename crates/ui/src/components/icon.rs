@@ -77,6 +77,7 @@ pub enum IconName {
     Folder,
     FolderOpen,
     FolderX,
+    GitBranch,
     Github,
     Hash,
     InlayHint,
@@ -93,6 +94,8 @@ pub enum IconName {
     Option,
     PageDown,
     PageUp,
+    Pencil,
+    Pin,
     Plus,
     Public,
     Quote,
@@ -108,6 +111,7 @@ pub enum IconName {
     Split,
     Tab,
     Terminal,
+    Trash,
     Update,
     WholeWord,
     XCircle,
@@ -169,6 +173,7 @@ impl IconName {
             IconName::Folder => "icons/file_icons/folder.svg",
             IconName::FolderOpen => "icons/file_icons/folder_open.svg",
             IconName::FolderX => "icons/stop_sharing.svg",
+            IconName::GitBranch => "icons/git_branch.svg",
             IconName::Github => "icons/github.svg",
             IconName::Hash => "icons/hash.svg",
             IconName::InlayHint => "icons/inlay_hint.svg",
@@ -185,6 +190,8 @@ impl IconName {
             IconName::Option => "icons/option.svg",
             IconName::PageDown => "icons/page_down.svg",
             IconName::PageUp => "icons/page_up.svg",
+            IconName::Pencil => "icons/pencil.svg",
+            IconName::Pin => "icons/pin.svg",
             IconName::Plus => "icons/plus.svg",
             IconName::Public => "icons/public.svg",
             IconName::Quote => "icons/quote.svg",
@@ -200,6 +207,7 @@ impl IconName {
             IconName::Split => "icons/split.svg",
             IconName::Tab => "icons/tab.svg",
             IconName::Terminal => "icons/terminal.svg",
+            IconName::Trash => "icons/trash.svg",
             IconName::Update => "icons/update.svg",
             IconName::WholeWord => "icons/word_search.svg",
             IconName::XCircle => "icons/error.svg",
@@ -1,5 +1,6 @@
 use std::{
-    ffi::OsStr,
+    env,
+    ffi::{OsStr, OsString},
     path::{Path, PathBuf},
 };
 
@@ -8,35 +9,191 @@ use serde::{Deserialize, Serialize};
 
 lazy_static::lazy_static! {
     pub static ref HOME: PathBuf = dirs::home_dir().expect("failed to determine home directory");
-    pub static ref CONFIG_DIR: PathBuf = HOME.join(".config").join("zed");
-    pub static ref CONVERSATIONS_DIR: PathBuf = HOME.join(".config/zed/conversations");
-    pub static ref EMBEDDINGS_DIR: PathBuf = HOME.join(".config/zed/embeddings");
-    pub static ref THEMES_DIR: PathBuf = HOME.join(".config/zed/themes");
-    pub static ref LOGS_DIR: PathBuf = HOME.join("Library/Logs/Zed");
-    pub static ref SUPPORT_DIR: PathBuf = HOME.join("Library/Application Support/Zed");
-    pub static ref PLUGINS_DIR: PathBuf = HOME.join("Library/Application Support/Zed/plugins");
-    pub static ref LANGUAGES_DIR: PathBuf = HOME.join("Library/Application Support/Zed/languages");
-    pub static ref COPILOT_DIR: PathBuf = HOME.join("Library/Application Support/Zed/copilot");
-    pub static ref DEFAULT_PRETTIER_DIR: PathBuf = HOME.join("Library/Application Support/Zed/prettier");
-    pub static ref DB_DIR: PathBuf = HOME.join("Library/Application Support/Zed/db");
-    pub static ref CRASHES_DIR: PathBuf = HOME.join("Library/Logs/DiagnosticReports");
-    pub static ref CRASHES_RETIRED_DIR: PathBuf = HOME.join("Library/Logs/DiagnosticReports/Retired");
+    pub static ref CONFIG_DIR: PathBuf = CONFIG_ROOT.resolve();
+    pub static ref CONVERSATIONS_DIR: PathBuf = CONFIG_DIR.join("conversations");
+    pub static ref EMBEDDINGS_DIR: PathBuf = CONFIG_DIR.join("embeddings");
+    pub static ref THEMES_DIR: PathBuf = CONFIG_DIR.join("themes");
+    pub static ref LOGS_DIR: PathBuf = LOGS_ROOT.resolve();
+    pub static ref SUPPORT_DIR: PathBuf = DATA_ROOT.resolve();
+    pub static ref CACHE_DIR: PathBuf = CACHE_ROOT.resolve();
+    pub static ref PLUGINS_DIR: PathBuf = SUPPORT_DIR.join("plugins");
+    pub static ref LANGUAGES_DIR: PathBuf = CACHE_DIR.join("languages");
+    pub static ref COPILOT_DIR: PathBuf = CACHE_DIR.join("copilot");
+    pub static ref DEFAULT_PRETTIER_DIR: PathBuf = CACHE_DIR.join("prettier");
+    pub static ref DB_DIR: PathBuf = SUPPORT_DIR.join("db");
+    pub static ref CRASHES_DIR: PathBuf = if cfg!(target_os = "macos") {
+        HOME.join("Library/Logs/DiagnosticReports")
+    } else {
+        LOGS_DIR.join("crashes")
+    };
+    pub static ref CRASHES_RETIRED_DIR: PathBuf = CRASHES_DIR.join("Retired");
     pub static ref SETTINGS: PathBuf = CONFIG_DIR.join("settings.json");
     pub static ref KEYMAP: PathBuf = CONFIG_DIR.join("keymap.json");
+    pub static ref TASKS: PathBuf = CONFIG_DIR.join("tasks.json");
     pub static ref LAST_USERNAME: PathBuf = CONFIG_DIR.join("last-username.txt");
     pub static ref LOG: PathBuf = LOGS_DIR.join("Zed.log");
     pub static ref OLD_LOG: PathBuf = LOGS_DIR.join("Zed.log.old");
     pub static ref LOCAL_SETTINGS_RELATIVE_PATH: &'static Path = Path::new(".zed/settings.json");
+    pub static ref LOCAL_TASKS_RELATIVE_PATH: &'static Path = Path::new(".zed/tasks.json");
+}
+
+/// Settings, keymaps, themes and other files the user may edit.
+const CONFIG_ROOT: RootDir = RootDir {
+    override_var: "ZED_CONFIG_DIR",
+    xdg_var: "XDG_CONFIG_HOME",
+    xdg_default: ".config",
+    macos: ".config/zed",
+};
+
+/// Data that can't be recreated, such as the database.
+const DATA_ROOT: RootDir = RootDir {
+    override_var: "ZED_DATA_DIR",
+    xdg_var: "XDG_DATA_HOME",
+    xdg_default: ".local/share",
+    macos: "Library/Application Support/Zed",
+};
+
+/// Downloads that Zed can fetch again, such as language servers.
+const CACHE_ROOT: RootDir = RootDir {
+    override_var: "ZED_CACHE_DIR",
+    xdg_var: "XDG_CACHE_HOME",
+    xdg_default: ".cache",
+    macos: "Library/Application Support/Zed",
+};
+
+/// Logs and crash reports.
+const LOGS_ROOT: RootDir = RootDir {
+    override_var: "ZED_LOGS_DIR",
+    xdg_var: "XDG_STATE_HOME",
+    xdg_default: ".local/state",
+    macos: "Library/Logs/Zed",
+};
+
+/// One of the directories under which Zed stores its files.
+struct RootDir {
+    /// The environment variable that overrides the directory on every
+    /// platform, so that separate instances of Zed can be isolated.
+    override_var: &'static str,
+    /// The XDG base directory variable to honor outside of macOS.
+    xdg_var: &'static str,
+    /// The XDG base directory to use when `xdg_var` is unset, relative to
+    /// the home directory.
+    xdg_default: &'static str,
+    /// The directory to use on macOS, relative to the home directory.
+    macos: &'static str,
+}
+
+impl RootDir {
+    fn resolve(&self) -> PathBuf {
+        self.resolve_with(&HOME, cfg!(target_os = "macos"), |var| env::var_os(var))
+    }
+
+    fn resolve_with(
+        &self,
+        home: &Path,
+        is_macos: bool,
+        env_var: impl Fn(&str) -> Option<OsString>,
+    ) -> PathBuf {
+        if let Some(dir) = self.override_dir(&env_var) {
+            dir
+        } else if is_macos {
+            home.join(self.macos)
+        } else {
+            absolute_env_var(self.xdg_var, &env_var)
+                .unwrap_or_else(|| home.join(self.xdg_default))
+                .join("zed")
+        }
+    }
+
+    fn override_dir(&self, env_var: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+        absolute_env_var(self.override_var, env_var)
+    }
+
+    fn is_overridden(&self) -> bool {
+        self.override_dir(&|var| env::var_os(var)).is_some()
+    }
+}
+
+/// Reads a directory from an environment variable. As the XDG specification
+/// requires, relative paths are ignored.
+fn absolute_env_var(var: &str, env_var: &impl Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    Some(PathBuf::from(env_var(var)?)).filter(|path| path.is_absolute())
 }
 
 pub mod legacy {
-    use std::path::PathBuf;
+    use super::{
+        RootDir, CACHE_DIR, CACHE_ROOT, CONFIG_ROOT, DATA_ROOT, HOME, LOGS_DIR, LOGS_ROOT,
+        SUPPORT_DIR,
+    };
+    use anyhow::{Context as _, Result};
+    use std::{fs, path::Path, path::PathBuf};
 
     lazy_static::lazy_static! {
         static ref CONFIG_DIR: PathBuf = super::HOME.join(".zed");
         pub static ref SETTINGS: PathBuf = CONFIG_DIR.join("settings.json");
         pub static ref KEYMAP: PathBuf = CONFIG_DIR.join("keymap.json");
     }
+
+    /// Moves files that earlier versions of Zed stored in macOS locations,
+    /// regardless of the platform, or in `~/.config/zed` regardless of
+    /// `XDG_CONFIG_HOME`, to where they belong on this platform.
+    ///
+    /// Files that already exist in the new location are left alone, as are
+    /// directories overridden via environment variables.
+    pub fn migrate_dirs() -> Result<()> {
+        if cfg!(target_os = "macos") {
+            return Ok(());
+        }
+
+        if !CONFIG_ROOT.is_overridden() {
+            let config_dir = HOME.join(".config/zed");
+            if config_dir != *super::CONFIG_DIR {
+                migrate(&config_dir, &super::CONFIG_DIR)?;
+            }
+        }
+
+        let support_dir = HOME.join("Library/Application Support/Zed");
+        if support_dir.is_dir() {
+            for entry in fs::read_dir(&support_dir)? {
+                let entry = entry?;
+                let (root, new_dir): (&RootDir, &Path) = match entry.file_name().to_str() {
+                    Some("languages" | "copilot" | "prettier") => {
+                        (&CACHE_ROOT, CACHE_DIR.as_path())
+                    }
+                    _ => (&DATA_ROOT, SUPPORT_DIR.as_path()),
+                };
+                if !root.is_overridden() {
+                    migrate(&entry.path(), &new_dir.join(entry.file_name()))?;
+                }
+            }
+        }
+        if !LOGS_ROOT.is_overridden() {
+            migrate(&HOME.join("Library/Logs/Zed"), &LOGS_DIR)?;
+        }
+        Ok(())
+    }
+
+    /// Moves a file or directory to its new path. When a directory already
+    /// exists there, the old directory's entries are moved into it instead.
+    fn migrate(old_path: &Path, new_path: &Path) -> Result<()> {
+        if !old_path.exists() {
+            return Ok(());
+        }
+        if new_path.exists() {
+            if old_path.is_dir() && new_path.is_dir() {
+                for entry in fs::read_dir(old_path)? {
+                    let entry = entry?;
+                    migrate(&entry.path(), &new_path.join(entry.file_name()))?;
+                }
+            }
+            return Ok(());
+        }
+        if let Some(parent) = new_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(old_path, new_path)
+            .with_context(|| format!("failed to move {old_path:?} to {new_path:?}"))
+    }
 }
 
 pub trait PathExt {
@@ -271,6 +428,52 @@ impl PathMatcher {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolving_root_dirs() {
+        let home = Path::new("/home/user");
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |var: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == var)
+                    .map(|(_, value)| OsString::from(value))
+            }
+        };
+
+        assert_eq!(
+            DATA_ROOT.resolve_with(home, true, env(&[("XDG_DATA_HOME", "/xdg/data")])),
+            Path::new("/home/user/Library/Application Support/Zed")
+        );
+        assert_eq!(
+            DATA_ROOT.resolve_with(home, false, env(&[])),
+            Path::new("/home/user/.local/share/zed")
+        );
+        assert_eq!(
+            DATA_ROOT.resolve_with(home, false, env(&[("XDG_DATA_HOME", "/xdg/data")])),
+            Path::new("/xdg/data/zed")
+        );
+        assert_eq!(
+            LOGS_ROOT.resolve_with(home, false, env(&[("XDG_STATE_HOME", "relative/state")])),
+            Path::new("/home/user/.local/state/zed")
+        );
+        assert_eq!(
+            CACHE_ROOT.resolve_with(home, false, env(&[("XDG_CACHE_HOME", "/xdg/cache")])),
+            Path::new("/xdg/cache/zed")
+        );
+        for is_macos in [false, true] {
+            assert_eq!(
+                CONFIG_ROOT.resolve_with(
+                    home,
+                    is_macos,
+                    env(&[
+                        ("XDG_CONFIG_HOME", "/xdg/config"),
+                        ("ZED_CONFIG_DIR", "/tmp/zed-config")
+                    ])
+                ),
+                Path::new("/tmp/zed-config")
+            );
+        }
+    }
+
     type TestPath = PathLikeWithPosition<String>;
 
     fn parse_str(s: &str) -> TestPath {
@@ -1,17 +1,36 @@
+mod parser;
+
+use std::{
+    ops::{Range, RangeInclusive},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
 use command_palette::CommandInterceptResult;
-use editor::actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive};
-use gpui::{impl_actions, Action, AppContext, ViewContext};
+use editor::{
+    actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
+    scroll::Autoscroll,
+    Anchor, Editor, MultiBufferSnapshot, ToOffset, ToPoint,
+};
+use fs::Fs;
+use gpui::{
+    impl_actions, Action, AppContext, AsyncWindowContext, KeyDownEvent, Keystroke, Modifiers,
+    PlatformInput, ViewContext, ViewInputHandler, WeakView,
+};
+use language::Point;
+use regex::{Regex, RegexBuilder};
 use serde_derive::Deserialize;
 use workspace::{SaveIntent, Workspace};
 
+pub use parser::{Address, CommandRange, ExCommand, ExCommandKind, Position, SubstituteFlags};
+
 use crate::{
-    motion::{EndOfDocument, Motion, StartOfDocument},
-    normal::{
-        move_cursor,
-        search::{FindCommand, ReplaceCommand},
-        JoinLines,
-    },
+    motion::Motion,
+    normal::{mark::mark_position, move_cursor, search::FindCommand},
     state::Mode,
+    utils::{copy_selections_content, yank_selections_content},
     Vim,
 };
 
@@ -20,7 +39,7 @@ pub struct GoToLine {
     pub line: u32,
 }
 
-impl_actions!(vim, [GoToLine]);
+impl_actions!(vim, [GoToLine, ExCommand]);
 
 pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, action: &GoToLine, cx| {
@@ -29,18 +48,15 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
             move_cursor(vim, Motion::StartOfDocument, Some(action.line as usize), cx);
         });
     });
+    workspace.register_action(ex_command);
 }
 
 pub fn command_interceptor(mut query: &str, _: &AppContext) -> Option<CommandInterceptResult> {
-    // Note: this is a very poor simulation of vim's command palette.
-    // In the future we should adjust it to handle parsing range syntax,
-    // and then calling the appropriate commands with/without ranges.
-    //
-    // We also need to support passing arguments to commands like :w
-    // (ideally with filename autocompletion).
+    // Commands that don't take a range are mapped directly to the equivalent
+    // workspace actions. Anything else is parsed as `:[range]{command}` and
+    // executed against the active editor by `ex_command`.
     //
-    // For now, you can only do a replace on the % range, and you can
-    // only use a specific line number range to "go to line"
+    // We don't support filename completion for :w and :r yet.
     while query.starts_with(":") {
         query = &query[1..];
     }
@@ -224,44 +240,29 @@ pub fn command_interceptor(mut query: &str, _: &AppContext) -> Option<CommandInt
             ("lNext", editor::actions::GoToPrevDiagnostic.boxed_clone())
         }
 
-        // modify the buffer (should accept [range])
-        "j" | "jo" | "joi" | "join" => ("join", JoinLines.boxed_clone()),
-        "d" | "de" | "del" | "dele" | "delet" | "delete" | "dl" | "dell" | "delel" | "deletl"
-        | "deletel" | "dp" | "dep" | "delp" | "delep" | "deletp" | "deletep" => {
-            ("delete", editor::actions::DeleteLine.boxed_clone())
-        }
-        "sor" | "sor " | "sort" | "sort " => ("sort", SortLinesCaseSensitive.boxed_clone()),
-        "sor i" | "sort i" => ("sort i", SortLinesCaseInsensitive.boxed_clone()),
-
-        // goto (other ranges handled under _ => )
-        "$" => ("$", EndOfDocument.boxed_clone()),
-        "%" => ("%", EndOfDocument.boxed_clone()),
-        "0" => ("0", StartOfDocument.boxed_clone()),
-
-        _ => {
-            if query.starts_with("/") || query.starts_with("?") {
-                (
-                    query,
-                    FindCommand {
-                        query: query[1..].to_string(),
-                        backwards: query.starts_with("?"),
-                    }
-                    .boxed_clone(),
-                )
-            } else if query.starts_with("%") {
-                (
-                    query,
-                    ReplaceCommand {
-                        query: query.to_string(),
-                    }
-                    .boxed_clone(),
-                )
-            } else if let Ok(line) = query.parse::<u32>() {
-                (query, GoToLine { line }.boxed_clone())
-            } else {
-                return None;
-            }
-        }
+        _ => match parser::parse(query).ok()? {
+            // a bare pattern address also becomes the current search, so that `n` continues it
+            ExCommand {
+                range:
+                    Some(CommandRange {
+                        start:
+                            Position {
+                                address: Address::Pattern { pattern, backwards },
+                                offset: 0,
+                            },
+                        end: None,
+                    }),
+                command: ExCommandKind::GoToLine,
+            } => (
+                query,
+                FindCommand {
+                    query: pattern,
+                    backwards,
+                }
+                .boxed_clone(),
+            ),
+            command => (query, command.boxed_clone()),
+        },
     };
 
     let string = ":".to_owned() + name;
@@ -274,6 +275,600 @@ pub fn command_interceptor(mut query: &str, _: &AppContext) -> Option<CommandInt
     })
 }
 
+/// Where the file arguments of `:write` and `:read` are resolved.
+struct FileContext {
+    fs: Arc<dyn Fs>,
+    root: Option<Arc<Path>>,
+}
+
+fn ex_command(workspace: &mut Workspace, action: &ExCommand, cx: &mut ViewContext<Workspace>) {
+    let project = workspace.project().read(cx);
+    let files = FileContext {
+        fs: project.fs().clone(),
+        root: project
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path()),
+    };
+
+    Vim::update(cx, |vim, cx| {
+        let mut command = action.clone();
+        // vim inserts `'<,'>` when `:` is typed in visual mode
+        if vim.state().mode.is_visual() && command.range.is_none() {
+            command.range = Some(CommandRange::last_visual_selection());
+        }
+        vim.switch_mode(Mode::Normal, false, cx);
+
+        let result = vim.update_active_editor(cx, |vim, editor, cx| {
+            let mut result = Ok(());
+            editor.transact(cx, |editor, cx| {
+                result = execute(vim, editor, &command, &files, cx);
+            });
+            result
+        });
+        if let Some(Err(error)) = result {
+            log::error!("{error}");
+        }
+    });
+}
+
+fn execute(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    command: &ExCommand,
+    files: &FileContext,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let range = command
+        .range
+        .clone()
+        .unwrap_or_else(|| match command.command {
+            ExCommandKind::Global { .. }
+            | ExCommandKind::Sort { .. }
+            | ExCommandKind::Write { .. } => CommandRange::whole_buffer(),
+            _ => CommandRange {
+                start: Position {
+                    address: Address::CurrentLine,
+                    offset: 0,
+                },
+                end: None,
+            },
+        });
+    let lines = resolve_range(vim, editor, &range, cx)?;
+    let rows = lines.start().saturating_sub(1)..=lines.end().saturating_sub(1);
+
+    match &command.command {
+        ExCommandKind::GoToLine => move_to_line(editor, *rows.end(), cx),
+        ExCommandKind::Delete { register } => {
+            vim.workspace_state.selected_register = *register;
+            delete_lines(vim, editor, rows, cx);
+        }
+        ExCommandKind::Yank { register } => {
+            vim.workspace_state.selected_register = *register;
+            yank_lines(vim, editor, rows, cx);
+        }
+        ExCommandKind::Join => join_lines(editor, rows, cx),
+        ExCommandKind::Substitute {
+            pattern,
+            replacement,
+            flags,
+        } => substitute(editor, rows, pattern, replacement, flags, cx)?,
+        ExCommandKind::Global {
+            pattern,
+            invert,
+            command,
+        } => {
+            let regex = build_regex(pattern, false)?;
+            let is_marked = |line: &str| regex.is_match(line) != *invert;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let lines = rows
+                .filter(|row| is_marked(&line_text(&snapshot, *row)))
+                .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                .collect::<Vec<_>>();
+            if lines.is_empty() {
+                bail!("E486: Pattern not found: {pattern}");
+            }
+            global(vim, editor, lines, is_marked, command, files, cx);
+        }
+        ExCommandKind::Normal { keys } => {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let lines = rows
+                .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                .collect();
+            normal(lines, keys, cx);
+        }
+        ExCommandKind::Move { destination } => {
+            let destination = resolve_position(vim, editor, destination, cx)?;
+            move_lines(editor, rows, destination, cx)?;
+        }
+        ExCommandKind::Copy { destination } => {
+            let destination = resolve_position(vim, editor, destination, cx)?;
+            copy_lines(editor, rows, destination, cx);
+        }
+        ExCommandKind::Write { path, force } => {
+            let rows = command.range.is_some().then_some(rows);
+            write(editor, rows, path.as_deref(), *force, files, cx)?;
+        }
+        ExCommandKind::Read { path } => read(*lines.end(), path, files, cx)?,
+        ExCommandKind::Sort { ignore_case } => sort_lines(editor, rows, *ignore_case, cx),
+    }
+    Ok(())
+}
+
+/// Resolves a range to 1-based line numbers, where line 0 is before the first line.
+fn resolve_range(
+    vim: &Vim,
+    editor: &Editor,
+    range: &CommandRange,
+    cx: &AppContext,
+) -> Result<RangeInclusive<u32>> {
+    let start = resolve_position(vim, editor, &range.start, cx)?;
+    let end = match &range.end {
+        Some(end) => resolve_position(vim, editor, end, cx)?,
+        None => start,
+    };
+    // vim asks before swapping a backwards range, we just do it.
+    Ok(start.min(end)..=start.max(end))
+}
+
+fn resolve_position(
+    vim: &Vim,
+    editor: &Editor,
+    position: &Position,
+    cx: &AppContext,
+) -> Result<u32> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_row = snapshot.max_buffer_row();
+    let cursor_row = editor.selections.newest::<Point>(cx).head().row;
+    let line = match &position.address {
+        Address::Line(line) => *line,
+        Address::CurrentLine => cursor_row + 1,
+        Address::LastLine => max_row + 1,
+        Address::Mark(mark) => {
+            let anchor = mark_position(vim, editor, *mark, cx)
+                .ok_or_else(|| anyhow!("E20: Mark not set"))?;
+            anchor.to_point(&snapshot).row + 1
+        }
+        Address::Pattern { pattern, backwards } => {
+            let regex = build_regex(pattern, false)?;
+            // searches start next to the cursor and wrap around the end of the buffer
+            let rows: Vec<u32> = if *backwards {
+                (0..cursor_row)
+                    .rev()
+                    .chain((cursor_row..=max_row).rev())
+                    .collect()
+            } else {
+                (cursor_row + 1..=max_row).chain(0..=cursor_row).collect()
+            };
+            let row = rows
+                .into_iter()
+                .find(|row| regex.is_match(&line_text(&snapshot, *row)))
+                .ok_or_else(|| anyhow!("E486: Pattern not found: {pattern}"))?;
+            row + 1
+        }
+    };
+
+    let line = line as i64 + position.offset as i64;
+    if line < 0 || line > max_row as i64 + 1 {
+        bail!("E16: Invalid range");
+    }
+    Ok(line as u32)
+}
+
+fn build_regex(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    if pattern.is_empty() {
+        bail!("E35: No previous regular expression");
+    }
+    let (pattern, case_override) = parser::convert_pattern(pattern);
+    Ok(RegexBuilder::new(&pattern)
+        .case_insensitive(case_override.unwrap_or(ignore_case))
+        .build()?)
+}
+
+fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect()
+}
+
+/// The text of the given lines, each followed by a newline.
+fn lines_text(snapshot: &MultiBufferSnapshot, rows: &RangeInclusive<u32>) -> String {
+    rows.clone()
+        .map(|row| line_text(snapshot, row) + "\n")
+        .collect()
+}
+
+/// The range to remove when deleting whole lines. When the last line is deleted,
+/// the newline before it is removed instead of the one after it.
+fn line_deletion_range(snapshot: &MultiBufferSnapshot, rows: &RangeInclusive<u32>) -> Range<Point> {
+    let (start, end) = (*rows.start(), *rows.end());
+    if end < snapshot.max_buffer_row() {
+        Point::new(start, 0)..Point::new(end + 1, 0)
+    } else if start > 0 {
+        Point::new(start - 1, snapshot.line_len(start - 1))..Point::new(end, snapshot.line_len(end))
+    } else {
+        Point::new(0, 0)..Point::new(end, snapshot.line_len(end))
+    }
+}
+
+/// Where to insert `text` (whole lines, each followed by a newline) so that it ends up below
+/// the given 1-based line. Line 0 inserts above the first line.
+fn line_insertion(snapshot: &MultiBufferSnapshot, line: u32, mut text: String) -> (Point, String) {
+    let max_row = snapshot.max_buffer_row();
+    if line > max_row {
+        text.pop();
+        (
+            Point::new(max_row, snapshot.line_len(max_row)),
+            format!("\n{text}"),
+        )
+    } else {
+        (Point::new(line, 0), text)
+    }
+}
+
+/// Moves the cursor to the first non-blank character of the row, which is where
+/// most Ex commands leave it.
+fn move_to_line(editor: &mut Editor, row: u32, cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let row = row.min(snapshot.max_buffer_row());
+    let column = snapshot
+        .indent_size_for_line(row)
+        .len
+        .min(snapshot.line_len(row).saturating_sub(1));
+    let point = Point::new(row, column);
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges([point..point])
+    });
+}
+
+fn delete_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let range = line_deletion_range(&snapshot, &rows);
+    editor.change_selections(None, cx, |s| s.select_ranges([range.clone()]));
+    copy_selections_content(vim, editor, true, cx);
+    editor.edit([(range, "")], cx);
+    move_to_line(editor, *rows.start(), cx);
+}
+
+fn yank_lines(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let cursor = editor.selections.newest_anchor().head();
+    editor.change_selections(None, cx, |s| {
+        s.select_ranges([line_deletion_range(&snapshot, &rows)])
+    });
+    yank_selections_content(vim, editor, true, cx);
+    editor.change_selections(None, cx, |s| s.select_anchor_ranges([cursor..cursor]));
+}
+
+fn join_lines(editor: &mut Editor, rows: RangeInclusive<u32>, cx: &mut ViewContext<Editor>) {
+    let (start, end) = (*rows.start(), *rows.end());
+    // a single line is joined with the next one
+    editor.change_selections(None, cx, |s| {
+        s.select_ranges([Point::new(start, 0)..Point::new(end, 0)])
+    });
+    editor.join_lines(&editor::actions::JoinLines, cx);
+    move_to_line(editor, start, cx);
+}
+
+fn substitute(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    pattern: &str,
+    replacement: &str,
+    flags: &SubstituteFlags,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let regex = build_regex(pattern, flags.ignore_case)?;
+    let replacement = parser::convert_replacement(replacement);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+
+    let mut edits = Vec::new();
+    let mut match_count = 0;
+    let mut inserted_rows = 0;
+    let mut last_row = None;
+    for row in rows {
+        let line = line_text(&snapshot, row);
+        if !regex.is_match(&line) {
+            continue;
+        }
+        let new_line = if flags.replace_all {
+            match_count += regex.find_iter(&line).count();
+            regex.replace_all(&line, replacement.as_str())
+        } else {
+            match_count += 1;
+            regex.replace(&line, replacement.as_str())
+        };
+        // `\r` in the replacement splits the line, which moves the following lines down
+        inserted_rows += new_line.matches('\n').count() as u32;
+        last_row = Some(row + inserted_rows);
+        edits.push((
+            Point::new(row, 0)..Point::new(row, line.len() as u32),
+            new_line.into_owned(),
+        ));
+    }
+
+    let Some(last_row) = last_row else {
+        bail!("E486: Pattern not found: {pattern}");
+    };
+    if flags.count_only {
+        log::info!("{match_count} matches on {} lines", edits.len());
+        return Ok(());
+    }
+    editor.edit(edits, cx);
+    move_to_line(editor, last_row, cx);
+    Ok(())
+}
+
+/// Runs the command of a `:global` on each of the marked lines.
+fn global(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    lines: Vec<Anchor>,
+    is_marked: impl Fn(&str) -> bool,
+    command: &ExCommand,
+    files: &FileContext,
+    cx: &mut ViewContext<Editor>,
+) {
+    // `:normal` runs asynchronously, so it handles all the lines itself.
+    if let ExCommandKind::Normal { keys } = &command.command {
+        normal(lines, keys, cx);
+        return;
+    }
+
+    for (ix, line) in lines.iter().enumerate() {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        // the anchor of a marked line that was deleted by an earlier command ends up at the
+        // start of the following line, which must not run the command again.
+        let offset = line.to_offset(&snapshot);
+        if lines[ix + 1..]
+            .iter()
+            .any(|next| next.to_offset(&snapshot) == offset)
+        {
+            continue;
+        }
+        let row = line.to_point(&snapshot).row;
+        if !is_marked(&line_text(&snapshot, row)) {
+            continue;
+        }
+
+        move_to_line(editor, row, cx);
+        // like in vim, a command failing on one line (e.g. a substitution that
+        // doesn't match) doesn't stop it from running on the other lines.
+        execute(vim, editor, command, files, cx).ok();
+    }
+}
+
+/// Types `keys` in normal mode at the start of each line. Each keystroke is dispatched
+/// like one typed by the user, so this has to happen asynchronously.
+fn normal(lines: Vec<Anchor>, keys: &str, cx: &mut ViewContext<Editor>) {
+    // an incomplete command is aborted, as if <Esc> was typed
+    let keystrokes = keys
+        .chars()
+        .map(keystroke_for_char)
+        .chain([Keystroke {
+            key: "escape".into(),
+            ..Default::default()
+        }])
+        .collect::<Vec<_>>();
+
+    cx.spawn(|editor, mut cx| async move {
+        for line in lines {
+            editor.update(&mut cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let point = Point::new(line.to_point(&snapshot).row, 0);
+                editor.change_selections(None, cx, |s| s.select_ranges([point..point]));
+            })?;
+            for keystroke in &keystrokes {
+                dispatch_keystroke(&editor, keystroke.clone(), &mut cx)?;
+                // let the editor re-render, so that the next keystroke is matched
+                // against its updated key context.
+                cx.background_executor().timer(Duration::ZERO).await;
+            }
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+fn keystroke_for_char(c: char) -> Keystroke {
+    let key = match c {
+        ' ' => "space".to_string(),
+        '\t' => "tab".to_string(),
+        c => c.to_ascii_lowercase().to_string(),
+    };
+    Keystroke {
+        modifiers: Modifiers {
+            shift: c.is_ascii_uppercase(),
+            ..Default::default()
+        },
+        key,
+        ime_key: Some(c.to_string()),
+    }
+}
+
+fn dispatch_keystroke(
+    editor: &WeakView<Editor>,
+    keystroke: Keystroke,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let text = keystroke.ime_key.clone();
+    let handled = cx.update(|cx| {
+        cx.dispatch_event(PlatformInput::KeyDown(KeyDownEvent {
+            keystroke,
+            is_held: false,
+        }))
+    })?;
+    // keystrokes that aren't bound to an action are typed into the editor, as the platform would do.
+    if let (false, Some(text)) = (handled, text) {
+        editor.update(cx, |editor, cx| {
+            editor.replace_text_in_range(None, &text, cx)
+        })?;
+    }
+    Ok(())
+}
+
+/// `:[range]m {address}` moves the lines below the given line.
+fn move_lines(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    destination: u32,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let (first, last) = (rows.start() + 1, rows.end() + 1);
+    if destination >= first && destination < last {
+        bail!("E134: Cannot move a range of lines into itself");
+    }
+    if destination + 1 == first || destination == last {
+        move_to_line(editor, last - 1, cx);
+        return Ok(());
+    }
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let deletion = line_deletion_range(&snapshot, &rows);
+    let (insertion, text) = line_insertion(&snapshot, destination, lines_text(&snapshot, &rows));
+    // apply the later edit first, so that the position of the other one stays valid
+    if insertion > deletion.start {
+        editor.edit([(insertion..insertion, text)], cx);
+        editor.edit([(deletion, "")], cx);
+    } else {
+        editor.edit([(deletion, "")], cx);
+        editor.edit([(insertion..insertion, text)], cx);
+    }
+
+    let last_moved_line = if destination > last {
+        destination
+    } else {
+        destination + last - first + 1
+    };
+    move_to_line(editor, last_moved_line - 1, cx);
+    Ok(())
+}
+
+/// `:[range]t {address}` copies the lines below the given line.
+fn copy_lines(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    destination: u32,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let (insertion, text) = line_insertion(&snapshot, destination, lines_text(&snapshot, &rows));
+    editor.edit([(insertion..insertion, text)], cx);
+    move_to_line(editor, destination + rows.end() - rows.start(), cx);
+}
+
+fn sort_lines(
+    editor: &mut Editor,
+    rows: RangeInclusive<u32>,
+    ignore_case: bool,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let (start, end) = (*rows.start(), *rows.end());
+    editor.change_selections(None, cx, |s| {
+        s.select_ranges([Point::new(start, 0)..Point::new(end, snapshot.line_len(end))])
+    });
+    if ignore_case {
+        editor.sort_lines_case_insensitive(&SortLinesCaseInsensitive, cx);
+    } else {
+        editor.sort_lines_case_sensitive(&SortLinesCaseSensitive, cx);
+    }
+    move_to_line(editor, start, cx);
+}
+
+fn resolve_path(path: &str, root: Option<&Path>) -> Result<PathBuf> {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    let root = root.ok_or_else(|| anyhow!("no project to resolve {path:?} in"))?;
+    Ok(root.join(path))
+}
+
+/// `:[range]w[!] [file]` writes the buffer, or the given lines, to a file. Without a file
+/// and a range this saves the buffer.
+fn write(
+    editor: &mut Editor,
+    rows: Option<RangeInclusive<u32>>,
+    path: Option<&str>,
+    force: bool,
+    files: &FileContext,
+    cx: &mut ViewContext<Editor>,
+) -> Result<()> {
+    let path = match path {
+        Some(path) => resolve_path(path, files.root.as_deref())?,
+        None if rows.is_none() => {
+            let save_intent = if force {
+                SaveIntent::Overwrite
+            } else {
+                SaveIntent::Save
+            };
+            cx.dispatch_action(
+                workspace::Save {
+                    save_intent: Some(save_intent),
+                }
+                .boxed_clone(),
+            );
+            return Ok(());
+        }
+        None => editor
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .and_then(|buffer| Some(buffer.read(cx).file()?.as_local()?.abs_path(cx)))
+            .ok_or_else(|| anyhow!("E32: No file name"))?,
+    };
+
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let text = match rows {
+        Some(rows) => lines_text(&snapshot, &rows),
+        None => snapshot.text(),
+    };
+    let fs = files.fs.clone();
+    cx.background_executor()
+        .spawn(async move {
+            if !force && fs.is_file(&path).await {
+                bail!("E13: File exists (add ! to override)");
+            }
+            fs.atomic_write(path, text).await
+        })
+        .detach_and_log_err(cx);
+    Ok(())
+}
+
+/// `:[line]r {file}` inserts the contents of a file below the given line.
+fn read(line: u32, path: &str, files: &FileContext, cx: &mut ViewContext<Editor>) -> Result<()> {
+    let path = resolve_path(path, files.root.as_deref())?;
+    let fs = files.fs.clone();
+    cx.spawn(|editor, mut cx| async move {
+        let mut text = fs.load(&path).await?;
+        if text.is_empty() {
+            return Ok(());
+        }
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        editor.update(&mut cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let (insertion, text) = line_insertion(&snapshot, line, text);
+            editor.edit([(insertion..insertion, text)], cx);
+            move_to_line(editor, line, cx);
+        })
+    })
+    .detach_and_log_err(cx);
+    Ok(())
+}
+
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
     let mut positions = Vec::new();
     let mut chars = query.chars().into_iter();
@@ -300,7 +895,10 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
 mod test {
     use std::path::Path;
 
-    use crate::test::{NeovimBackedTestContext, VimTestContext};
+    use crate::{
+        state::Mode,
+        test::{NeovimBackedTestContext, VimTestContext},
+    };
     use gpui::TestAppContext;
     use indoc::indoc;
 
@@ -439,4 +1037,231 @@ mod test {
         cx.simulate_keystrokes([":", "q", "a", "enter"]);
         cx.workspace(|workspace, cx| assert_eq!(workspace.items(cx).count(), 0));
     }
+
+    #[gpui::test]
+    async fn test_command_ranges(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            e
+            f"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "2", ",", "4", "d", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            ˇe
+            f"})
+            .await;
+        cx.simulate_shared_keystrokes([":", ".", ",", "+", "1", "j", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            ˇe f"})
+            .await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes(["j", "m", "a", "j", "j"])
+            .await;
+        cx.simulate_shared_keystrokes([":", "'", "a", ",", ".", "d", "enter"])
+            .await;
+        cx.assert_shared_state("ˇa").await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            b"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "/", "b", "/", "+", "1", "d", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            b
+            ˇb"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_substitute(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇaa
+            ba
+            aa"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "%", "s", "/", "a", "/", "x", "/", "g", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            xx
+            bx
+            ˇxx"})
+            .await;
+        cx.simulate_shared_keystrokes([
+            ":", "2", "s", "/", "\\", "(", "b", "\\", ")", "x", "/", "\\", "1", "\\", "1", "/",
+            "enter",
+        ])
+        .await;
+        cx.assert_shared_state(indoc! {"
+            xx
+            ˇbb
+            xx"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "%", "s", "/", "shift-x", "/", "y", "/", "i", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            yx
+            bb
+            ˇyx"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_global(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            a
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "g", "/", "a", "/", "d", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            b
+            ˇc"})
+            .await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            a
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([
+            ":", "v", "/", "a", "/", "s", "/", "$", "/", "!", "/", "enter",
+        ])
+        .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            b!
+            a
+            ˇc!"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_move_copy(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "m", "$", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            b
+            c
+            d
+            ˇa"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "t", "0", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            ˇa
+            b
+            c
+            d
+            a"})
+            .await;
+        cx.simulate_shared_keystrokes([":", "2", ",", "3", "m", "4", "enter"])
+            .await;
+        cx.assert_shared_state(indoc! {"
+            a
+            d
+            b
+            ˇc
+            a"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = NeovimBackedTestContext::new(cx).await;
+
+        cx.set_shared_state(indoc! {"
+            ˇa
+            b
+            c"})
+            .await;
+        cx.simulate_shared_keystrokes([
+            ":", "%", "n", "o", "r", "m", "space", "shift-a", "x", "enter",
+        ])
+        .await;
+        cx.assert_shared_state(indoc! {"
+            ax
+            bx
+            cˇx"})
+            .await;
+    }
+
+    #[gpui::test]
+    async fn test_command_visual_range(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // commands run from visual mode apply to the selected lines
+        cx.set_state("ˇa\nb\nc\nd", Mode::Normal);
+        cx.simulate_keystrokes(["j", "shift-v", "j", ":", "d", "enter"]);
+        cx.assert_state("a\nˇd", Mode::Normal);
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(["v", "j", "escape"]);
+        cx.simulate_keystrokes([
+            ":", "'", "<", ",", "'", ">", "s", "/", ".", "/", "x", "/", "enter",
+        ]);
+        cx.assert_state("x\nˇx\nc", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_write_read_file(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let path = Path::new("/root/out.txt");
+        let fs = cx.workspace(|workspace, cx| workspace.project().read(cx).fs().clone());
+        let out = ["o", "u", "t", ".", "t", "x", "t"];
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes([":", "2", ",", "3", "w", "space"]);
+        cx.simulate_keystrokes(out);
+        cx.simulate_keystrokes(["enter"]);
+        assert_eq!(fs.load(&path).await.unwrap(), "b\nc\n");
+
+        // existing files are only overwritten with `:w!`
+        cx.simulate_keystrokes([":", "w", "space"]);
+        cx.simulate_keystrokes(out);
+        cx.simulate_keystrokes(["enter"]);
+        assert_eq!(fs.load(&path).await.unwrap(), "b\nc\n");
+        cx.simulate_keystrokes([":", "w", "!", "space"]);
+        cx.simulate_keystrokes(out);
+        cx.simulate_keystrokes(["enter"]);
+        assert_eq!(fs.load(&path).await.unwrap(), "a\nb\nc");
+
+        cx.simulate_keystrokes([":", "1", "r", "space"]);
+        cx.simulate_keystrokes(out);
+        cx.simulate_keystrokes(["enter"]);
+        cx.assert_state("a\nˇa\nb\nc\nb\nc", Mode::Normal);
+    }
 }
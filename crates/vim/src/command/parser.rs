@@ -0,0 +1,655 @@
+use anyhow::{anyhow, bail, Result};
+use serde_derive::Deserialize;
+
+/// A line address in the range of an Ex command.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Address {
+    /// `{number}`: an absolute (1-based) line number. Line 0 is before the first line.
+    Line(u32),
+    /// `.`: the line containing the cursor.
+    CurrentLine,
+    /// `$`: the last line of the buffer.
+    LastLine,
+    /// `'{mark}`: the line containing a mark, including `'<` and `'>` for the last visual selection.
+    Mark(char),
+    /// `/{pattern}/` or `?{pattern}?`: the next (or previous) line matching a pattern.
+    Pattern { pattern: String, backwards: bool },
+}
+
+/// An address followed by any number of `+{count}` and `-{count}` offsets.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Position {
+    pub address: Address,
+    pub offset: i32,
+}
+
+impl Position {
+    fn current_line() -> Self {
+        Self {
+            address: Address::CurrentLine,
+            offset: 0,
+        }
+    }
+}
+
+/// The `[range]` of an Ex command: either a single line, or two lines separated by a comma.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CommandRange {
+    pub start: Position,
+    pub end: Option<Position>,
+}
+
+impl CommandRange {
+    /// `%`, all lines in the buffer.
+    pub fn whole_buffer() -> Self {
+        Self {
+            start: Position {
+                address: Address::Line(1),
+                offset: 0,
+            },
+            end: Some(Position {
+                address: Address::LastLine,
+                offset: 0,
+            }),
+        }
+    }
+
+    /// `'<,'>`, the lines of the last visual selection.
+    pub fn last_visual_selection() -> Self {
+        Self {
+            start: Position {
+                address: Address::Mark('<'),
+                offset: 0,
+            },
+            end: Some(Position {
+                address: Address::Mark('>'),
+                offset: 0,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SubstituteFlags {
+    /// `g`: replace every match in the line, not just the first one.
+    pub replace_all: bool,
+    /// `i` or `I`: ignore (or don't ignore) case.
+    pub ignore_case: bool,
+    /// `n`: only count the matches, without substituting.
+    pub count_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum ExCommandKind {
+    /// A range without a command moves the cursor to the last line of the range.
+    GoToLine,
+    Delete {
+        register: Option<char>,
+    },
+    Yank {
+        register: Option<char>,
+    },
+    Join,
+    Substitute {
+        pattern: String,
+        replacement: String,
+        flags: SubstituteFlags,
+    },
+    /// `:g/{pattern}/{command}` or, when inverted, `:v/{pattern}/{command}`.
+    Global {
+        pattern: String,
+        invert: bool,
+        command: Box<ExCommand>,
+    },
+    Normal {
+        keys: String,
+    },
+    Move {
+        destination: Position,
+    },
+    Copy {
+        destination: Position,
+    },
+    Write {
+        path: Option<String>,
+        force: bool,
+    },
+    Read {
+        path: String,
+    },
+    Sort {
+        ignore_case: bool,
+    },
+}
+
+/// A parsed `:[range]{command}` line.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExCommand {
+    pub range: Option<CommandRange>,
+    pub command: ExCommandKind,
+}
+
+/// Parses an Ex command line (without the leading `:`).
+///
+/// Only commands that take a range or arguments are understood here; commands
+/// like `:quit` or `:split` are mapped directly to workspace actions instead.
+pub fn parse(input: &str) -> Result<ExCommand> {
+    let mut parser = Parser { input, position: 0 };
+    let command = parser.command_line()?;
+    if !parser.rest().trim().is_empty() {
+        bail!("E488: Trailing characters: {}", parser.rest().trim());
+    }
+    Ok(command)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.next();
+        }
+    }
+
+    fn take_rest(&mut self) -> &'a str {
+        let rest = self.rest();
+        self.position = self.input.len();
+        rest
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let digits = self
+            .rest()
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest().len());
+        if digits == 0 {
+            return None;
+        }
+        let number = self.rest()[..digits].parse().ok();
+        self.position += digits;
+        number
+    }
+
+    /// Reads up to the next unescaped `delimiter`, consuming it. Escaped delimiters are unescaped,
+    /// other escape sequences are left for the regex conversion.
+    fn delimited(&mut self, delimiter: char) -> String {
+        let mut result = String::new();
+        while let Some(c) = self.next() {
+            if c == delimiter {
+                break;
+            } else if c == '\\' {
+                match self.next() {
+                    Some(c) if c == delimiter => result.push(c),
+                    Some(c) => {
+                        result.push('\\');
+                        result.push(c);
+                    }
+                    None => result.push('\\'),
+                }
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    fn command_line(&mut self) -> Result<ExCommand> {
+        self.skip_whitespace();
+        let range = self.range()?;
+        self.skip_whitespace();
+        let command = self.command(range.is_some())?;
+        Ok(ExCommand { range, command })
+    }
+
+    fn range(&mut self) -> Result<Option<CommandRange>> {
+        if self.eat('%') {
+            return Ok(Some(CommandRange::whole_buffer()));
+        }
+        let start = self.position()?;
+        self.skip_whitespace();
+        if self.eat(',') {
+            self.skip_whitespace();
+            let end = self.position()?.unwrap_or_else(Position::current_line);
+            return Ok(Some(CommandRange {
+                start: start.unwrap_or_else(Position::current_line),
+                end: Some(end),
+            }));
+        }
+        Ok(start.map(|start| CommandRange { start, end: None }))
+    }
+
+    fn position(&mut self) -> Result<Option<Position>> {
+        let address = self.address()?;
+        let mut offset = 0;
+        let mut has_offset = false;
+        loop {
+            match self.peek() {
+                Some('+') => {
+                    self.next();
+                    offset += self.number().unwrap_or(1) as i32;
+                }
+                Some('-') => {
+                    self.next();
+                    offset -= self.number().unwrap_or(1) as i32;
+                }
+                // `.5` is the same as `.+5`
+                Some(c) if c.is_ascii_digit() && address.is_some() => {
+                    offset += self.number().unwrap_or(0) as i32;
+                }
+                _ => break,
+            }
+            has_offset = true;
+        }
+
+        Ok(match address {
+            Some(address) => Some(Position { address, offset }),
+            None if has_offset => Some(Position {
+                address: Address::CurrentLine,
+                offset,
+            }),
+            None => None,
+        })
+    }
+
+    fn address(&mut self) -> Result<Option<Address>> {
+        let address = match self.peek() {
+            Some(c) if c.is_ascii_digit() => {
+                Address::Line(self.number().ok_or_else(|| anyhow!("E16: Invalid range"))?)
+            }
+            Some('.') => {
+                self.next();
+                Address::CurrentLine
+            }
+            Some('$') => {
+                self.next();
+                Address::LastLine
+            }
+            Some('\'') => {
+                self.next();
+                Address::Mark(self.next().ok_or_else(|| anyhow!("E20: Mark not set"))?)
+            }
+            Some(delimiter @ ('/' | '?')) => {
+                self.next();
+                Address::Pattern {
+                    pattern: self.delimited(delimiter),
+                    backwards: delimiter == '?',
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(address))
+    }
+
+    fn command(&mut self, has_range: bool) -> Result<ExCommandKind> {
+        let name_length = self
+            .rest()
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(self.rest().len());
+        let name = &self.rest()[..name_length];
+        self.position += name_length;
+
+        if name.is_empty() {
+            if has_range && self.rest().trim().is_empty() {
+                return Ok(ExCommandKind::GoToLine);
+            }
+            bail!("E492: Not an editor command: {}", self.input);
+        }
+
+        let bang = self.eat('!');
+        // `:dl` and `:dp` (delete and list or print the next line) behave like `:d`
+        let command = if abbreviates(name.trim_end_matches(['l', 'p']), "delete", 1) {
+            ExCommandKind::Delete {
+                register: self.register(),
+            }
+        } else if abbreviates(name, "yank", 1) {
+            ExCommandKind::Yank {
+                register: self.register(),
+            }
+        } else if abbreviates(name, "join", 1) {
+            ExCommandKind::Join
+        } else if abbreviates(name, "sort", 3) {
+            if bang {
+                bail!("E474: Reverse sorting isn't supported");
+            }
+            self.skip_whitespace();
+            ExCommandKind::Sort {
+                ignore_case: self.eat('i'),
+            }
+        } else if abbreviates(name, "substitute", 1) && !bang {
+            self.substitute()?
+        } else if abbreviates(name, "global", 1) || abbreviates(name, "vglobal", 1) {
+            let invert = bang || name.starts_with('v');
+            let delimiter = self
+                .next()
+                .filter(|c| is_pattern_delimiter(*c))
+                .ok_or_else(|| {
+                    anyhow!("E146: Regular expressions can't be delimited by letters")
+                })?;
+            let pattern = self.delimited(delimiter);
+            let command = if self.rest().trim().is_empty() {
+                ExCommand {
+                    range: None,
+                    command: ExCommandKind::GoToLine,
+                }
+            } else {
+                parse(self.take_rest())?
+            };
+            if matches!(command.command, ExCommandKind::Global { .. }) {
+                bail!("E147: Cannot do :global recursive");
+            }
+            ExCommandKind::Global {
+                pattern,
+                invert,
+                command: Box::new(command),
+            }
+        } else if abbreviates(name, "normal", 4) {
+            self.skip_whitespace();
+            let keys = self.take_rest();
+            if keys.is_empty() {
+                bail!("E471: Argument required");
+            }
+            ExCommandKind::Normal {
+                keys: keys.to_string(),
+            }
+        } else if abbreviates(name, "move", 1) || abbreviates(name, "copy", 2) || name == "t" {
+            self.skip_whitespace();
+            let destination = self
+                .position()?
+                .ok_or_else(|| anyhow!("E14: Invalid address"))?;
+            if name.starts_with('m') {
+                ExCommandKind::Move { destination }
+            } else {
+                ExCommandKind::Copy { destination }
+            }
+        } else if abbreviates(name, "write", 1) {
+            self.skip_whitespace();
+            let path = self.take_rest().trim();
+            if path.starts_with(">>") || path.starts_with('!') {
+                bail!("E494: Appending and filtering aren't supported");
+            }
+            ExCommandKind::Write {
+                path: (!path.is_empty()).then(|| path.to_string()),
+                force: bang,
+            }
+        } else if abbreviates(name, "read", 1) && !bang {
+            self.skip_whitespace();
+            let path = self.take_rest().trim();
+            if path.is_empty() {
+                bail!("E32: No file name");
+            }
+            ExCommandKind::Read {
+                path: path.to_string(),
+            }
+        } else {
+            bail!("E492: Not an editor command: {}", self.input);
+        };
+        Ok(command)
+    }
+
+    fn register(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        let register = self.peek().filter(|c| !c.is_ascii_digit())?;
+        self.next();
+        Some(register)
+    }
+
+    fn substitute(&mut self) -> Result<ExCommandKind> {
+        let delimiter = self
+            .next()
+            .filter(|c| is_pattern_delimiter(*c))
+            .ok_or_else(|| anyhow!("E35: Repeating the last substitute isn't supported"))?;
+        let pattern = self.delimited(delimiter);
+        let replacement = self.delimited(delimiter);
+        let mut flags = SubstituteFlags::default();
+        while let Some(flag) = self.peek() {
+            match flag {
+                'g' => flags.replace_all = true,
+                'i' => flags.ignore_case = true,
+                'I' => flags.ignore_case = false,
+                'n' => flags.count_only = true,
+                'e' => {}
+                'c' => bail!("E488: Confirming substitutions isn't supported"),
+                _ => break,
+            }
+            self.next();
+        }
+        Ok(ExCommandKind::Substitute {
+            pattern,
+            replacement,
+            flags,
+        })
+    }
+}
+
+/// Whether `name` is a valid abbreviation of the command `full_name`, which needs at least
+/// `min_length` characters to be unambiguous (e.g. `:norm` for `:normal`).
+fn abbreviates(name: &str, full_name: &str, min_length: usize) -> bool {
+    name.len() >= min_length && full_name.starts_with(name)
+}
+
+fn is_pattern_delimiter(c: char) -> bool {
+    !c.is_alphanumeric() && !c.is_whitespace() && !matches!(c, '\\' | '"' | '|')
+}
+
+/// Converts a vim pattern (using the default `magic` syntax) into the syntax of the regex crate.
+///
+/// Returns the converted pattern, and whether `\c` or `\C` asked to ignore (or match) case.
+pub fn convert_pattern(pattern: &str) -> (String, Option<bool>) {
+    let mut result = String::new();
+    let mut ignore_case = None;
+    let mut in_braces = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(c @ ('(' | ')' | '|' | '+' | '?')) => result.push(c),
+                Some('=') => result.push('?'),
+                Some('{') => {
+                    in_braces = true;
+                    result.push('{');
+                }
+                Some('<' | '>') => result.push_str("\\b"),
+                Some('c') => ignore_case = Some(true),
+                Some('C') => ignore_case = Some(false),
+                Some(c) => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                None => result.push_str("\\\\"),
+            }
+        } else {
+            match c {
+                '(' | ')' | '|' | '+' | '?' | '{' => {
+                    result.push('\\');
+                    result.push(c);
+                }
+                '}' if in_braces => {
+                    in_braces = false;
+                    result.push('}');
+                }
+                '}' => result.push_str("\\}"),
+                _ => result.push(c),
+            }
+        }
+    }
+    (result, ignore_case)
+}
+
+/// Converts the replacement of a vim `:substitute` into the syntax of the regex crate:
+/// `&` and `\0`..`\9` refer to the match and its groups, and `\r` inserts a new line.
+pub fn convert_replacement(replacement: &str) -> String {
+    let mut result = String::new();
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => {
+                    result.push_str("${");
+                    result.push(digit);
+                    result.push('}');
+                }
+                Some('r' | 'n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('$') => result.push_str("$$"),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            },
+            '&' => result.push_str("${0}"),
+            '$' => result.push_str("$$"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(line: u32) -> Position {
+        Position {
+            address: Address::Line(line),
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_ex_commands() {
+        assert_eq!(
+            parse("5,10d").unwrap(),
+            ExCommand {
+                range: Some(CommandRange {
+                    start: line(5),
+                    end: Some(line(10)),
+                }),
+                command: ExCommandKind::Delete { register: None },
+            }
+        );
+        assert_eq!(
+            parse(".,+3j").unwrap(),
+            ExCommand {
+                range: Some(CommandRange {
+                    start: Position::current_line(),
+                    end: Some(Position {
+                        address: Address::CurrentLine,
+                        offset: 3,
+                    }),
+                }),
+                command: ExCommandKind::Join,
+            }
+        );
+        assert_eq!(
+            parse("'<,'>s/a/b/g").unwrap(),
+            ExCommand {
+                range: Some(CommandRange {
+                    start: Position {
+                        address: Address::Mark('<'),
+                        offset: 0,
+                    },
+                    end: Some(Position {
+                        address: Address::Mark('>'),
+                        offset: 0,
+                    }),
+                }),
+                command: ExCommandKind::Substitute {
+                    pattern: "a".into(),
+                    replacement: "b".into(),
+                    flags: SubstituteFlags {
+                        replace_all: true,
+                        ..Default::default()
+                    },
+                },
+            }
+        );
+        assert_eq!(
+            parse("g!/a\\/b/norm Ax").unwrap(),
+            ExCommand {
+                range: None,
+                command: ExCommandKind::Global {
+                    pattern: "a/b".into(),
+                    invert: true,
+                    command: Box::new(ExCommand {
+                        range: None,
+                        command: ExCommandKind::Normal { keys: "Ax".into() },
+                    }),
+                },
+            }
+        );
+        assert_eq!(
+            parse("/foo/-1m0").unwrap(),
+            ExCommand {
+                range: Some(CommandRange {
+                    start: Position {
+                        address: Address::Pattern {
+                            pattern: "foo".into(),
+                            backwards: false,
+                        },
+                        offset: -1,
+                    },
+                    end: None,
+                }),
+                command: ExCommandKind::Move {
+                    destination: line(0),
+                },
+            }
+        );
+        assert_eq!(
+            parse("w! other.txt").unwrap().command,
+            ExCommandKind::Write {
+                path: Some("other.txt".into()),
+                force: true,
+            }
+        );
+        assert_eq!(parse("$").unwrap().command, ExCommandKind::GoToLine);
+        assert_eq!(
+            parse("y a").unwrap().command,
+            ExCommandKind::Yank {
+                register: Some('a')
+            }
+        );
+
+        assert!(parse("frobnicate").is_err());
+        assert!(parse("s/a/b/c").is_err());
+        assert!(parse("g/a/g/b/d").is_err());
+        assert!(parse("r").is_err());
+    }
+
+    #[test]
+    fn test_convert_vim_regex() {
+        assert_eq!(
+            convert_pattern("\\(a\\|b\\)\\+("),
+            ("(a|b)+\\(".into(), None)
+        );
+        assert_eq!(
+            convert_pattern("a\\{2}\\<\\c"),
+            ("a{2}\\b".into(), Some(true))
+        );
+        assert_eq!(convert_replacement("\\1-&-$\\r"), "${1}-${0}-$$\n");
+    }
+}
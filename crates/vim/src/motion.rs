@@ -1,7 +1,7 @@
 use editor::{
     display_map::{DisplaySnapshot, FoldPoint, ToDisplayPoint},
     movement::{self, find_boundary, find_preceding_boundary, FindRange, TextLayoutDetails},
-    Bias, DisplayPoint, ToOffset,
+    Anchor, Bias, DisplayPoint, ToOffset,
 };
 use gpui::{actions, impl_actions, px, ViewContext, WindowContext};
 use language::{char_kind, CharKind, Point, Selection, SelectionGoal};
@@ -47,6 +47,7 @@ pub enum Motion {
     WindowTop,
     WindowMiddle,
     WindowBottom,
+    Jump { anchor: Anchor, line: bool },
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -302,6 +303,7 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | EndOfParagraph => true,
+            Jump { line, .. } => *line,
             EndOfLine { .. }
             | NextWordEnd { .. }
             | Matching
@@ -325,7 +327,7 @@ impl Motion {
     pub fn infallible(&self) -> bool {
         use Motion::*;
         match self {
-            StartOfDocument | EndOfDocument | CurrentLine => true,
+            StartOfDocument | EndOfDocument | CurrentLine | Jump { .. } => true,
             Down { .. }
             | Up { .. }
             | EndOfLine { .. }
@@ -384,7 +386,8 @@ impl Motion {
             | NextWordStart { .. }
             | PreviousWordStart { .. }
             | FirstNonWhitespace { .. }
-            | FindBackward { .. } => false,
+            | FindBackward { .. }
+            | Jump { .. } => false,
             RepeatFind { last_find: motion } | RepeatFindReversed { last_find: motion } => {
                 motion.inclusive()
             }
@@ -516,6 +519,14 @@ impl Motion {
             WindowTop => window_top(map, point, &text_layout_details),
             WindowMiddle => window_middle(map, point, &text_layout_details),
             WindowBottom => window_bottom(map, point, &text_layout_details),
+            Jump { anchor, line } => {
+                let point = anchor.to_display_point(map);
+                if *line {
+                    (first_non_whitespace(map, false, point), SelectionGoal::None)
+                } else {
+                    (point, SelectionGoal::None)
+                }
+            }
         };
 
         (new_point != point || infallible).then_some((new_point, goal))
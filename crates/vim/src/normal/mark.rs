@@ -0,0 +1,461 @@
+use std::sync::Arc;
+
+use editor::{scroll::Autoscroll, Anchor, Editor};
+use gpui::{AppContext, Model, View, WindowContext};
+use language::{Bias, Buffer, Point};
+use project::{Item as _, ProjectPath};
+use workspace::Workspace;
+
+use crate::{
+    motion::{self, Motion},
+    state::{Mark, MarkScope},
+    Vim,
+};
+
+enum MarkTarget {
+    /// The mark is visible in the active editor.
+    Visible(Anchor),
+    /// The mark is a global mark in a buffer that the active editor doesn't show.
+    OtherBuffer {
+        workspace: View<Workspace>,
+        buffer: Model<Buffer>,
+        anchor: language::Anchor,
+    },
+    /// The mark is a global mark in a file whose buffer has been released.
+    ClosedFile {
+        workspace: View<Workspace>,
+        path: ProjectPath,
+        point: Point,
+    },
+}
+
+/// `m{a-z}` sets a mark in the buffer under the cursor, `m{A-Z}` sets a global mark.
+pub(crate) fn create_mark(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.pop_operator(cx);
+        let Some(mark) = text.chars().next().filter(|c| c.is_ascii_alphabetic()) else {
+            return;
+        };
+        let Some((buffer, anchor)) = vim
+            .update_active_editor(cx, |_, editor, cx| {
+                let head = editor.selections.newest_anchor().head();
+                let (buffer, offset, _) =
+                    editor.buffer().read(cx).point_to_buffer_offset(head, cx)?;
+                let anchor = buffer.read(cx).anchor_before(offset);
+                Some((buffer, anchor))
+            })
+            .flatten()
+        else {
+            return;
+        };
+
+        let new_mark = new_mark(vim, &buffer, anchor, cx);
+        if mark.is_ascii_uppercase() {
+            vim.workspace_state.global_marks.insert(mark, new_mark);
+        } else {
+            vim.workspace_state
+                .marks
+                .entry(mark_scope(&buffer, cx))
+                .or_default()
+                .insert(mark, new_mark);
+        }
+    })
+}
+
+/// `'{mark}` jumps to the first non-blank character of the mark's line, and
+/// `` `{mark} `` jumps to its exact position. Both can be used as motions.
+pub(crate) fn jump_to_mark(text: Arc<str>, line: bool, cx: &mut WindowContext) {
+    let target = Vim::update(cx, |vim, cx| {
+        vim.pop_operator(cx);
+        let mark = text.chars().next()?;
+        if let Some(buffer) = vim
+            .update_active_editor(cx, |_, editor, cx| {
+                let head = editor.selections.newest_anchor().head();
+                let (buffer, _, _) = editor.buffer().read(cx).point_to_buffer_offset(head, cx)?;
+                Some(buffer)
+            })
+            .flatten()
+        {
+            reattach_marks(vim, &buffer, cx);
+        }
+
+        if mark.is_ascii_uppercase() {
+            let Mark {
+                buffer,
+                anchor,
+                path,
+                point,
+            } = vim.workspace_state.global_marks.get(&mark)?.clone();
+            vim.update_active_editor(cx, |_, editor, cx| {
+                let Some(buffer) = buffer.upgrade() else {
+                    return Some(MarkTarget::ClosedFile {
+                        workspace: editor.workspace()?,
+                        path: path?,
+                        point,
+                    });
+                };
+                if let Some(anchor) = anchor_in_editor(editor, &buffer, anchor, cx) {
+                    Some(MarkTarget::Visible(anchor))
+                } else {
+                    Some(MarkTarget::OtherBuffer {
+                        workspace: editor.workspace()?,
+                        buffer,
+                        anchor,
+                    })
+                }
+            })
+            .flatten()
+        } else {
+            vim.update_active_editor(cx, |vim, editor, cx| {
+                mark_position(vim, editor, mark, cx).map(MarkTarget::Visible)
+            })
+            .flatten()
+        }
+    });
+
+    match target {
+        Some(MarkTarget::Visible(anchor)) => motion::motion(Motion::Jump { anchor, line }, cx),
+        Some(MarkTarget::OtherBuffer {
+            workspace,
+            buffer,
+            anchor,
+        }) => {
+            Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+            let editor = workspace.update(cx, |workspace, cx| {
+                workspace.open_project_item::<Editor>(buffer.clone(), cx)
+            });
+            editor.update(cx, |editor, cx| {
+                if let Some(anchor) = anchor_in_editor(editor, &buffer, anchor, cx) {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_anchor_ranges([anchor..anchor])
+                    });
+                }
+            });
+        }
+        Some(MarkTarget::ClosedFile {
+            workspace,
+            path,
+            point,
+        }) => {
+            Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+            let open_path = workspace.update(cx, |workspace, cx| {
+                workspace.open_path(path, None, true, cx)
+            });
+            cx.spawn(|mut cx| async move {
+                let editor = open_path
+                    .await?
+                    .downcast::<Editor>()
+                    .ok_or_else(|| anyhow::anyhow!("marked file didn't open in an editor"))?;
+                editor.update(&mut cx, |editor, cx| {
+                    let point = editor
+                        .buffer()
+                        .read(cx)
+                        .snapshot(cx)
+                        .clip_point(point, Bias::Left);
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                })
+            })
+            .detach_and_log_err(cx);
+        }
+        None => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
+    }
+}
+
+/// Returns the position of a mark in the given editor, if the mark is set and the editor shows it.
+pub(crate) fn mark_position(
+    vim: &Vim,
+    editor: &Editor,
+    mark: char,
+    cx: &AppContext,
+) -> Option<Anchor> {
+    let head = editor.selections.newest_anchor().head();
+    let (buffer, _, _) = editor.buffer().read(cx).point_to_buffer_offset(head, cx)?;
+    let mark = if mark.is_ascii_uppercase() {
+        vim.workspace_state.global_marks.get(&mark)?
+    } else if mark.is_ascii_lowercase() || mark == '<' || mark == '>' {
+        vim.workspace_state
+            .marks
+            .get(&mark_scope(&buffer, cx))?
+            .get(&mark)?
+    } else {
+        return None;
+    };
+
+    if let Some(marked_buffer) = mark.buffer.upgrade() {
+        anchor_in_editor(editor, &marked_buffer, mark.anchor, cx)
+    } else if mark.path.is_some() && mark.path == buffer.read(cx).project_path(cx) {
+        // The file was reopened since the mark was set.
+        let anchor = {
+            let buffer = buffer.read(cx);
+            buffer.anchor_before(buffer.clip_point(mark.point, Bias::Left))
+        };
+        anchor_in_editor(editor, &buffer, anchor, cx)
+    } else {
+        None
+    }
+}
+
+/// Sets the `'<` and `'>` marks to the start and end of the visual selection that is being left.
+pub(crate) fn set_visual_marks(vim: &mut Vim, editor: &Editor, cx: &mut AppContext) {
+    let selections = editor.selections.all::<Point>(cx);
+    let (Some(start), Some(end)) = (
+        selections.iter().map(|selection| selection.start).min(),
+        selections.iter().map(|selection| selection.end).max(),
+    ) else {
+        return;
+    };
+
+    // the end of a visual selection is exclusive, but the `'>` mark is on the last selected character.
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let end = if end.column > 0 {
+        snapshot.clip_point(Point::new(end.row, end.column - 1), Bias::Left)
+    } else if end > start {
+        Point::new(end.row - 1, snapshot.line_len(end.row - 1))
+    } else {
+        end
+    };
+
+    for (mark, point) in [('<', start), ('>', end)] {
+        let Some((buffer, offset, _)) = editor.buffer().read(cx).point_to_buffer_offset(point, cx)
+        else {
+            continue;
+        };
+        let anchor = buffer.read(cx).anchor_before(offset);
+        let new_mark = new_mark(vim, &buffer, anchor, cx);
+        vim.workspace_state
+            .marks
+            .entry(mark_scope(&buffer, cx))
+            .or_default()
+            .insert(mark, new_mark);
+    }
+}
+
+fn mark_scope(buffer: &Model<Buffer>, cx: &AppContext) -> MarkScope {
+    match buffer.read(cx).project_path(cx) {
+        Some(path) => MarkScope::Path(path),
+        None => MarkScope::Buffer(buffer.entity_id()),
+    }
+}
+
+fn new_mark(
+    vim: &mut Vim,
+    buffer: &Model<Buffer>,
+    anchor: language::Anchor,
+    cx: &mut AppContext,
+) -> Mark {
+    observe_marked_buffer(vim, buffer, cx);
+    let snapshot = buffer.read(cx);
+    Mark {
+        buffer: buffer.downgrade(),
+        anchor,
+        path: snapshot.project_path(cx),
+        point: snapshot.summary_for_anchor(&anchor),
+    }
+}
+
+/// Anchors the marks that were set in a file before its buffer was released
+/// in the buffer that the file has been reopened in.
+fn reattach_marks(vim: &mut Vim, buffer: &Model<Buffer>, cx: &mut AppContext) {
+    let Some(path) = buffer.read(cx).project_path(cx) else {
+        return;
+    };
+    let state = &mut vim.workspace_state;
+    let mut reattached = false;
+    for mark in state
+        .marks
+        .get_mut(&MarkScope::Path(path.clone()))
+        .into_iter()
+        .flat_map(|marks| marks.values_mut())
+        .chain(state.global_marks.values_mut())
+    {
+        if mark.path.as_ref() == Some(&path) && mark.buffer.upgrade().is_none() {
+            let snapshot = buffer.read(cx);
+            mark.point = snapshot.clip_point(mark.point, Bias::Left);
+            mark.anchor = snapshot.anchor_before(mark.point);
+            mark.buffer = buffer.downgrade();
+            reattached = true;
+        }
+    }
+    if reattached {
+        observe_marked_buffer(vim, buffer, cx);
+    }
+}
+
+/// When a buffer with marks is released, records where its marks ended up, so
+/// that they can be found when the file is reopened, and drops the marks of
+/// buffers that aren't saved to a file.
+fn observe_marked_buffer(vim: &mut Vim, buffer: &Model<Buffer>, cx: &mut AppContext) {
+    let buffer_id = buffer.entity_id();
+    if !vim.workspace_state.marked_buffers.insert(buffer_id) {
+        return;
+    }
+    cx.observe_release(buffer, move |buffer, cx| {
+        if !cx.has_global::<Vim>() {
+            return;
+        }
+        cx.update_global(|vim: &mut Vim, _| {
+            let state = &mut vim.workspace_state;
+            state.marked_buffers.remove(&buffer_id);
+            state.marks.remove(&MarkScope::Buffer(buffer_id));
+            state
+                .global_marks
+                .retain(|_, mark| mark.path.is_some() || mark.buffer.entity_id() != buffer_id);
+            for mark in state
+                .marks
+                .values_mut()
+                .flat_map(|marks| marks.values_mut())
+                .chain(state.global_marks.values_mut())
+            {
+                if mark.buffer.entity_id() == buffer_id {
+                    mark.point = buffer.summary_for_anchor(&mark.anchor);
+                }
+            }
+        });
+    })
+    .detach();
+}
+
+fn anchor_in_editor(
+    editor: &Editor,
+    buffer: &Model<Buffer>,
+    anchor: language::Anchor,
+    cx: &AppContext,
+) -> Option<Anchor> {
+    let multibuffer = editor.buffer().read(cx);
+    let buffer_snapshot = buffer.read(cx);
+    let (excerpt_id, _) = multibuffer
+        .excerpts_for_buffer(buffer, cx)
+        .into_iter()
+        .find(|(_, range)| {
+            range.context.start.cmp(&anchor, buffer_snapshot).is_le()
+                && range.context.end.cmp(&anchor, buffer_snapshot).is_ge()
+        })?;
+    Some(
+        multibuffer
+            .snapshot(cx)
+            .anchor_in_excerpt(excerpt_id, anchor),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use editor::Editor;
+    use indoc::indoc;
+    use language::Point;
+
+    use crate::{state::Mode, test::VimTestContext, Vim};
+
+    #[gpui::test]
+    async fn test_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["m", "a", "shift-g", "`", "a"]);
+        cx.assert_state(
+            indoc! {"
+            The quˇick brown
+            fox jumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+
+        // marks are anchored, so they move with edits made before them
+        cx.simulate_keystrokes(["shift-o", "a", "escape", "shift-g", "'", "a"]);
+        cx.assert_state(
+            indoc! {"
+            a
+            ˇThe quick brown
+            fox jumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+
+        // marks can be used as motions
+        cx.simulate_keystrokes(["j", "w", "d", "`", "a"]);
+        cx.assert_state(
+            indoc! {"
+            a
+            The quˇjumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+
+        // uppercase marks are global
+        cx.simulate_keystrokes(["m", "shift-b", "g", "g", "`", "shift-b"]);
+        cx.assert_state(
+            indoc! {"
+            a
+            The quˇjumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+
+        // jumping to an unset mark does nothing
+        cx.simulate_keystrokes(["g", "g", "`", "z"]);
+        cx.assert_state(
+            indoc! {"
+            ˇa
+            The qujumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_marks_in_closed_files(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file("/root/dir/other.rs", "one\ntwo\nthree\n".to_string())
+            .await;
+        let worktree_id = cx.workspace(|workspace, cx| {
+            let worktree = workspace.project().read(cx).worktrees().next().unwrap();
+            worktree.read(cx).id()
+        });
+        let open_path = cx.update_workspace(|workspace, cx| {
+            workspace.open_path((worktree_id, "dir/other.rs"), None, true, cx)
+        });
+        open_path.await.unwrap();
+        let cursor_in_active_editor = |cx: &mut VimTestContext| {
+            cx.workspace(|workspace, cx| {
+                let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+                let editor = editor.read(cx);
+                (
+                    editor.buffer().read(cx).snapshot(cx).text(),
+                    editor.selections.newest::<Point>(cx).head(),
+                )
+            })
+        };
+
+        cx.simulate_keystrokes(["j", "l", "m", "shift-a", "m", "a"]);
+        cx.simulate_keystrokes([":", "q", "enter"]);
+        cx.run_until_parked();
+        cx.update(|cx| {
+            let mark = &Vim::read(cx).workspace_state.global_marks[&'A'];
+            assert!(mark.buffer.upgrade().is_none());
+        });
+
+        // jumping to a global mark reopens the file it was set in
+        cx.simulate_keystrokes(["`", "shift-a"]);
+        cx.run_until_parked();
+        assert_eq!(
+            cursor_in_active_editor(&mut cx),
+            ("one\ntwo\nthree\n".to_string(), Point::new(1, 1))
+        );
+
+        // lowercase marks are kept for the file, too
+        cx.simulate_keystrokes(["g", "g", "`", "a"]);
+        assert_eq!(
+            cursor_in_active_editor(&mut cx),
+            ("one\ntwo\nthree\n".to_string(), Point::new(1, 1))
+        );
+    }
+}
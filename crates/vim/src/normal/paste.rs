@@ -28,12 +28,13 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
 fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
     Vim::update(cx, |vim, cx| {
         vim.record_current_action(cx);
-        vim.update_active_editor(cx, |editor, cx| {
+        let selected_register = vim.workspace_state.selected_register.take();
+        vim.update_active_editor(cx, |vim, editor, cx| {
             let text_layout_details = editor.text_layout_details(cx);
             editor.transact(cx, |editor, cx| {
                 editor.set_clip_at_line_ends(false, cx);
 
-                let Some(item) = cx.read_from_clipboard() else {
+                let Some(item) = vim.read_register(selected_register, cx) else {
                     return;
                 };
                 let clipboard_text = Cow::Borrowed(item.text());
@@ -42,7 +43,8 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
                 }
 
                 if !action.preserve_clipboard && vim.state().mode.is_visual() {
-                    copy_selections_content(editor, vim.state().mode == Mode::VisualLine, cx);
+                    let linewise = vim.state().mode == Mode::VisualLine;
+                    copy_selections_content(vim, editor, linewise, cx);
                 }
 
                 // if we are copying from multi-cursor (of visual block mode), we want
@@ -472,4 +474,77 @@ mod test {
             Mode::Normal,
         );
     }
+
+    #[gpui::test]
+    async fn test_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇThe quick brown
+            fox jumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["\"", "a", "y", "w", "j", "y", "y", "j", "d", "d"]);
+        cx.assert_state(
+            indoc! {"
+            The quick brown
+            ˇfox jumps over"},
+            Mode::Normal,
+        );
+
+        // "0 holds the last yank, even after a delete
+        cx.simulate_keystrokes(["\"", "0", "p"]);
+        cx.assert_state(
+            indoc! {"
+            The quick brown
+            fox jumps over
+            ˇfox jumps over"},
+            Mode::Normal,
+        );
+
+        // named registers are not overwritten by later yanks or deletes
+        cx.simulate_keystrokes(["\"", "a", "p"]);
+        cx.assert_state(
+            indoc! {"
+            The quick brown
+            fox jumps over
+            fTheˇ ox jumps over"},
+            Mode::Normal,
+        );
+
+        // the unnamed register holds the last delete
+        cx.simulate_keystrokes(["p"]);
+        cx.assert_state(
+            indoc! {"
+            The quick brown
+            fox jumps over
+            fThe ox jumps over
+            ˇthe lazy dog"},
+            Mode::Normal,
+        );
+
+        // deleting into the black hole register leaves other registers alone
+        cx.simulate_keystrokes(["\"", "_", "d", "d", "\"", "1", "p"]);
+        cx.assert_state(
+            indoc! {"
+            The quick brown
+            fox jumps over
+            fThe ox jumps over
+            ˇthe lazy dog"},
+            Mode::Normal,
+        );
+
+        // uppercase registers append
+        cx.simulate_keystrokes(["g", "g", "\"", "shift-a", "y", "w", "\"", "a", "shift-p"]);
+        cx.assert_state(
+            indoc! {"
+            The Theˇ The quick brown
+            fox jumps over
+            fThe ox jumps over
+            the lazy dog"},
+            Mode::Normal,
+        );
+    }
 }
@@ -1,14 +1,19 @@
 use crate::{
     insert::NormalBefore,
     motion::Motion,
-    state::{Mode, RecordedSelection, ReplayableAction},
+    state::{Mode, Operator, RecordedSelection, ReplayableAction},
     visual::visual_motion,
     Vim,
 };
-use gpui::{actions, Action, ViewContext, WindowContext};
+use anyhow::Result;
+use editor::Editor;
+use gpui::{
+    actions, Action, AnyWindowHandle, AsyncWindowContext, ViewContext, WeakView, WindowContext,
+};
+use std::sync::Arc;
 use workspace::Workspace;
 
-actions!(vim, [Repeat, EndRepeat]);
+actions!(vim, [Repeat, EndRepeat, ToggleRecord]);
 
 fn should_replay(action: &Box<dyn Action>) -> bool {
     // skip so that we don't leave the character palette open
@@ -48,6 +53,99 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
     });
 
     workspace.register_action(|_: &mut Workspace, _: &Repeat, cx| repeat(cx, false));
+
+    workspace.register_action(|_: &mut Workspace, _: &ToggleRecord, cx| {
+        Vim::update(cx, |vim, cx| {
+            if vim.workspace_state.recording_register.take().is_none() {
+                vim.push_operator(Operator::RecordRegister, cx);
+            }
+        })
+    });
+}
+
+/// `q{register}` starts recording a macro. An uppercase register appends to the
+/// existing recording.
+pub(crate) fn record_register(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        vim.pop_operator(cx);
+        let Some(register) = text.chars().next().filter(|c| c.is_ascii_alphanumeric()) else {
+            return;
+        };
+        let lowercase = register.to_ascii_lowercase();
+        if !register.is_ascii_uppercase() {
+            vim.workspace_state.recordings.remove(&lowercase);
+        }
+        vim.workspace_state.recording_register = Some(lowercase);
+        vim.workspace_state.ignore_current_insertion = true;
+    })
+}
+
+/// `@{register}` replays a recorded macro, `@@` replays the last one again.
+pub(crate) fn replay_register(text: Arc<str>, cx: &mut WindowContext) {
+    let Some((actions, editor)) = Vim::update(cx, |vim, cx| {
+        vim.pop_operator(cx);
+        let count = vim.take_count(cx).unwrap_or(1);
+        let register = match text.chars().next()? {
+            '@' => vim.workspace_state.last_replayed_register?,
+            register => register.to_ascii_lowercase(),
+        };
+        let recording = vim.workspace_state.recordings.get(&register)?;
+        let mut actions = Vec::with_capacity(recording.len() * count);
+        for _ in 0..count {
+            actions.extend(recording.iter().cloned());
+        }
+        vim.workspace_state.last_replayed_register = Some(register);
+        Some((actions, vim.active_editor.clone()?))
+    }) else {
+        return;
+    };
+
+    // Don't record the replayed actions into a macro that is being recorded, as
+    // `@{register}` itself has been recorded already.
+    let recording_register =
+        Vim::update(cx, |vim, _| vim.workspace_state.recording_register.take());
+    let window = cx.window_handle();
+    cx.spawn(move |mut cx| async move {
+        let result = replay_actions(actions, &editor, window, &mut cx);
+        window.update(&mut cx, |_, cx| {
+            Vim::update(cx, |vim, _| {
+                vim.workspace_state.recording_register = recording_register;
+            })
+        })?;
+        result
+    })
+    .detach_and_log_err(cx);
+}
+
+fn replay_actions(
+    actions: Vec<ReplayableAction>,
+    editor: &WeakView<Editor>,
+    window: AnyWindowHandle,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    for action in actions {
+        match action {
+            ReplayableAction::Action(action) => {
+                if should_replay(&action) {
+                    window.update(cx, |_, cx| cx.dispatch_action(action.boxed_clone()))?;
+                    // Actions dispatched here are not seen by the keystroke observer, so
+                    // record them for `.` explicitly.
+                    window.update(cx, |_, cx| {
+                        Vim::update(cx, |vim, _| vim.record_action(action))
+                    })
+                } else {
+                    Ok(())
+                }
+            }
+            ReplayableAction::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => editor.update(cx, |editor, cx| {
+                editor.replay_insert_event(&text, utf16_range_to_replace.clone(), cx)
+            }),
+        }?
+    }
+    Ok(())
 }
 
 pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
@@ -171,23 +269,7 @@ pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
         editor.update(&mut cx, |editor, _| {
             editor.show_local_selections = false;
         })?;
-        for action in actions {
-            match action {
-                ReplayableAction::Action(action) => {
-                    if should_replay(&action) {
-                        window.update(&mut cx, |_, cx| cx.dispatch_action(action))
-                    } else {
-                        Ok(())
-                    }
-                }
-                ReplayableAction::Insertion {
-                    text,
-                    utf16_range_to_replace,
-                } => editor.update(&mut cx, |editor, cx| {
-                    editor.replay_insert_event(&text, utf16_range_to_replace.clone(), cx)
-                }),
-            }?
-        }
+        replay_actions(actions, &editor, window, &mut cx)?;
         editor.update(&mut cx, |editor, _| {
             editor.show_local_selections = true;
         })?;
@@ -506,4 +588,75 @@ mod test {
         cx.simulate_shared_keystrokes(["."]).await;
         cx.assert_shared_state("ˇx hello\n").await;
     }
+
+    #[gpui::test]
+    async fn test_record_replay(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three
+            four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["q", "a", "shift-a", "!", "escape", "j", "0", "q"]);
+        cx.assert_state(
+            indoc! {"
+            one!
+            ˇtwo
+            three
+            four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(["@", "a"]);
+        cx.assert_state(
+            indoc! {"
+            one!
+            two!
+            ˇthree
+            four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(["@", "@"]);
+        cx.assert_state(
+            indoc! {"
+            one!
+            two!
+            three!
+            ˇfour"},
+            Mode::Normal,
+        );
+
+        // counts repeat the macro, and `.` repeats the last change it made
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c
+            d"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["2", "@", "a"]);
+        cx.assert_state(
+            indoc! {"
+            a!
+            b!
+            ˇc
+            d"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["."]);
+        cx.assert_state(
+            indoc! {"
+            a!
+            b!
+            cˇ!
+            d"},
+            Mode::Normal,
+        );
+    }
 }
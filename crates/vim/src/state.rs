@@ -1,7 +1,9 @@
 use std::{ops::Range, sync::Arc};
 
-use gpui::{Action, KeyContext};
-use language::CursorShape;
+use collections::{HashMap, HashSet};
+use gpui::{Action, ClipboardItem, EntityId, KeyContext, WeakModel};
+use language::{Anchor, Buffer, CursorShape, Point};
+use project::ProjectPath;
 use serde::{Deserialize, Serialize};
 use workspace::searchable::Direction;
 
@@ -40,6 +42,11 @@ pub enum Operator {
     Object { around: bool },
     FindForward { before: bool },
     FindBackward { after: bool },
+    Register,
+    RecordRegister,
+    ReplayRegister,
+    Mark,
+    Jump { line: bool },
 }
 
 #[derive(Default, Clone)]
@@ -86,6 +93,46 @@ pub struct WorkspaceState {
     pub recorded_count: Option<usize>,
     pub recorded_actions: Vec<ReplayableAction>,
     pub recorded_selection: RecordedSelection,
+
+    /// The register named with `"` for the next yank, delete or paste.
+    pub selected_register: Option<char>,
+    pub registers: HashMap<char, ClipboardItem>,
+
+    /// The register that `q` is currently recording a macro into.
+    pub recording_register: Option<char>,
+    pub last_replayed_register: Option<char>,
+    pub recordings: HashMap<char, Vec<ReplayableAction>>,
+    /// Set when `q{register}` starts recording, so that the register name
+    /// itself is not recorded as part of the macro.
+    pub ignore_current_insertion: bool,
+
+    /// Lowercase marks, and the `'<` and `'>` marks, keyed by the file or
+    /// buffer they were set in.
+    pub marks: HashMap<MarkScope, HashMap<char, Mark>>,
+    /// Uppercase marks, which can be jumped to from any buffer.
+    pub global_marks: HashMap<char, Mark>,
+    /// The buffers that marks were set in whose release is being observed.
+    pub marked_buffers: HashSet<EntityId>,
+}
+
+/// What a lowercase mark belongs to: the file it was set in, or the buffer
+/// if it isn't saved to a file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MarkScope {
+    Path(ProjectPath),
+    Buffer(EntityId),
+}
+
+#[derive(Clone)]
+pub struct Mark {
+    /// The buffer the mark is anchored in, while it's open.
+    pub buffer: WeakModel<Buffer>,
+    pub anchor: Anchor,
+    /// The file the mark was set in, which is reopened to jump to the mark
+    /// once its buffer has been released.
+    pub path: Option<ProjectPath>,
+    /// The mark's position as of when its buffer was released.
+    pub point: Point,
 }
 
 #[derive(Debug)]
@@ -219,15 +266,26 @@ impl Operator {
             Operator::FindForward { before: true } => "t",
             Operator::FindBackward { after: false } => "F",
             Operator::FindBackward { after: true } => "T",
+            Operator::Register => "\"",
+            Operator::RecordRegister => "q",
+            Operator::ReplayRegister => "@",
+            Operator::Mark => "m",
+            Operator::Jump { line: true } => "'",
+            Operator::Jump { line: false } => "`",
         }
     }
 
     pub fn context_flags(&self) -> &'static [&'static str] {
         match self {
             Operator::Object { .. } => &["VimObject"],
-            Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace => {
-                &["VimWaiting"]
-            }
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::Register
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
+            | Operator::Mark
+            | Operator::Jump { .. } => &["VimWaiting"],
             _ => &[],
         }
     }
@@ -2,7 +2,36 @@ use editor::{ClipboardSelection, Editor};
 use gpui::{AppContext, ClipboardItem};
 use language::{CharKind, Point};
 
-pub fn copy_selections_content(editor: &mut Editor, linewise: bool, cx: &mut AppContext) {
+use crate::Vim;
+
+/// Copies the selected text into the selected register (or the unnamed register and `"0`).
+pub fn yank_selections_content(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    linewise: bool,
+    cx: &mut AppContext,
+) {
+    copy_selections_content_internal(vim, editor, linewise, true, cx);
+}
+
+/// Copies the selected text into the selected register (or the unnamed register and
+/// either `"-` or the numbered registers) before it is deleted.
+pub fn copy_selections_content(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    linewise: bool,
+    cx: &mut AppContext,
+) {
+    copy_selections_content_internal(vim, editor, linewise, false, cx);
+}
+
+fn copy_selections_content_internal(
+    vim: &mut Vim,
+    editor: &mut Editor,
+    linewise: bool,
+    is_yank: bool,
+    cx: &mut AppContext,
+) {
     let selections = editor.selections.all_adjusted(cx);
     let buffer = editor.buffer().read(cx).snapshot(cx);
     let mut text = String::new();
@@ -46,7 +75,12 @@ pub fn copy_selections_content(editor: &mut Editor, linewise: bool, cx: &mut App
         }
     }
 
-    cx.write_to_clipboard(ClipboardItem::new(text).with_metadata(clipboard_selections));
+    vim.write_registers(
+        ClipboardItem::new(text).with_metadata(clipboard_selections),
+        linewise,
+        is_yank,
+        cx,
+    );
 }
 
 pub fn coerce_punctuation(kind: CharKind, treat_punctuation_as_word: bool) -> CharKind {
@@ -20,13 +20,17 @@ use command_palette::CommandPaletteInterceptor;
 use copilot::CommandPaletteFilter;
 use editor::{movement, Editor, EditorEvent, EditorMode};
 use gpui::{
-    actions, impl_actions, Action, AppContext, EntityId, Global, KeyContext, Subscription, View,
-    ViewContext, WeakView, WindowContext,
+    actions, impl_actions, Action, AppContext, ClipboardItem, EntityId, Global, KeyContext,
+    Subscription, View, ViewContext, WeakView, WindowContext,
 };
 use language::{CursorShape, Point, Selection, SelectionGoal};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
-use normal::normal_replace;
+use normal::{
+    mark::{create_mark, jump_to_mark, set_visual_marks},
+    normal_replace,
+    repeat::{record_register, replay_register},
+};
 use serde::Deserialize;
 use settings::{update_settings_file, Settings, SettingsStore};
 use state::{EditorState, Mode, Operator, RecordedSelection, WorkspaceState};
@@ -139,16 +143,14 @@ pub fn observe_keystrokes(cx: &mut WindowContext) {
             .map(|action| action.boxed_clone())
         {
             Vim::update(cx, |vim, _| {
-                if vim.workspace_state.recording {
+                if let Some(register) = vim.workspace_state.recording_register {
                     vim.workspace_state
-                        .recorded_actions
+                        .recordings
+                        .entry(register)
+                        .or_default()
                         .push(ReplayableAction::Action(action.boxed_clone()));
-
-                    if vim.workspace_state.stop_recording_after_next_action {
-                        vim.workspace_state.recording = false;
-                        vim.workspace_state.stop_recording_after_next_action = false;
-                    }
                 }
+                vim.record_action(action.boxed_clone());
             });
 
             // Keystroke is handled by the vim system, so continue forward
@@ -161,7 +163,14 @@ pub fn observe_keystrokes(cx: &mut WindowContext) {
 
         Vim::update(cx, |vim, cx| match vim.active_operator() {
             Some(
-                Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace,
+                Operator::FindForward { .. }
+                | Operator::FindBackward { .. }
+                | Operator::Replace
+                | Operator::Register
+                | Operator::RecordRegister
+                | Operator::ReplayRegister
+                | Operator::Mark
+                | Operator::Jump { .. },
             ) => {}
             Some(_) => {
                 vim.clear_operator(cx);
@@ -242,13 +251,23 @@ impl Vim {
         cx: &mut WindowContext,
     ) {
         Vim::update(cx, |vim, _| {
-            if vim.workspace_state.recording {
+            if vim.workspace_state.ignore_current_insertion {
+                vim.workspace_state.ignore_current_insertion = false;
+                return;
+            }
+            let insertion = ReplayableAction::Insertion {
+                text: text.clone(),
+                utf16_range_to_replace: range_to_replace,
+            };
+            if let Some(register) = vim.workspace_state.recording_register {
                 vim.workspace_state
-                    .recorded_actions
-                    .push(ReplayableAction::Insertion {
-                        text: text.clone(),
-                        utf16_range_to_replace: range_to_replace,
-                    });
+                    .recordings
+                    .entry(register)
+                    .or_default()
+                    .push(insertion.clone());
+            }
+            if vim.workspace_state.recording {
+                vim.workspace_state.recorded_actions.push(insertion);
                 if vim.workspace_state.stop_recording_after_next_action {
                     vim.workspace_state.recording = false;
                     vim.workspace_state.stop_recording_after_next_action = false;
@@ -257,13 +276,27 @@ impl Vim {
         });
     }
 
+    /// Records an action that has just been dispatched, so that `.` can replay it.
+    fn record_action(&mut self, action: Box<dyn Action>) {
+        if self.workspace_state.recording {
+            self.workspace_state
+                .recorded_actions
+                .push(ReplayableAction::Action(action));
+
+            if self.workspace_state.stop_recording_after_next_action {
+                self.workspace_state.recording = false;
+                self.workspace_state.stop_recording_after_next_action = false;
+            }
+        }
+    }
+
     fn update_active_editor<S>(
-        &self,
+        &mut self,
         cx: &mut WindowContext,
-        update: impl FnOnce(&mut Editor, &mut ViewContext<Editor>) -> S,
+        update: impl FnOnce(&mut Vim, &mut Editor, &mut ViewContext<Editor>) -> S,
     ) -> Option<S> {
         let editor = self.active_editor.clone()?.upgrade()?;
-        Some(editor.update(cx, update))
+        Some(editor.update(cx, |editor, cx| update(self, editor, cx)))
     }
 
     /// When doing an action that modifies the buffer, we start recording so that `.`
@@ -362,7 +395,11 @@ impl Vim {
         }
 
         // Adjust selections
-        self.update_active_editor(cx, |editor, cx| {
+        self.update_active_editor(cx, |vim, editor, cx| {
+            if last_mode.is_visual() && !mode.is_visual() {
+                set_visual_marks(vim, editor, cx);
+            }
+
             if last_mode != Mode::VisualBlock && last_mode.is_visual() && mode == Mode::VisualBlock
             {
                 visual_block_motion(true, editor, cx, |_, point, goal| Some((point, goal)))
@@ -459,6 +496,7 @@ impl Vim {
     }
     fn clear_operator(&mut self, cx: &mut WindowContext) {
         self.take_count(cx);
+        self.workspace_state.selected_register.take();
         self.update_state(|state| state.operator_stack.clear());
         self.sync_vim_settings(cx);
     }
@@ -467,6 +505,77 @@ impl Vim {
         self.state().operator_stack.last().copied()
     }
 
+    fn select_register(&mut self, register: Arc<str>, cx: &mut WindowContext) {
+        self.pop_operator(cx);
+        self.workspace_state.selected_register = register
+            .chars()
+            .next()
+            .filter(|register| register.is_ascii_alphanumeric() || "\"-+*_".contains(*register));
+    }
+
+    /// Stores yanked or deleted text. The unnamed register is the system clipboard, and
+    /// always receives the text unless the black hole register (`"_`) was selected.
+    /// Without a selected register, yanks also go to `"0`, multi-line deletes shift the
+    /// numbered registers `"1` to `"9`, and smaller deletes go to `"-`.
+    fn write_registers(
+        &mut self,
+        content: ClipboardItem,
+        linewise: bool,
+        is_yank: bool,
+        cx: &mut AppContext,
+    ) {
+        let selected_register = self.workspace_state.selected_register.take();
+        let registers = &mut self.workspace_state.registers;
+        let content = match selected_register {
+            Some('_') => return,
+            Some('"' | '+' | '*') | None => content,
+            Some(register) if register.is_ascii_uppercase() => {
+                let register = register.to_ascii_lowercase();
+                let content = match registers.get(&register) {
+                    Some(existing) => {
+                        ClipboardItem::new(format!("{}{}", existing.text(), content.text()))
+                    }
+                    None => content,
+                };
+                registers.insert(register, content.clone());
+                content
+            }
+            Some(register) => {
+                registers.insert(register, content.clone());
+                content
+            }
+        };
+
+        if selected_register.is_none() {
+            if is_yank {
+                registers.insert('0', content.clone());
+            } else if linewise || content.text().contains('\n') {
+                for register in ('1'..'9').rev() {
+                    if let Some(previous) = registers.remove(&register) {
+                        registers.insert((register as u8 + 1) as char, previous);
+                    }
+                }
+                registers.insert('1', content.clone());
+            } else {
+                registers.insert('-', content.clone());
+            }
+        }
+
+        cx.write_to_clipboard(content);
+    }
+
+    fn read_register(&self, register: Option<char>, cx: &AppContext) -> Option<ClipboardItem> {
+        match register {
+            None | Some('"' | '+' | '*') => cx.read_from_clipboard(),
+            Some('_') => None,
+            Some(register) => self
+                .workspace_state
+                .registers
+                .get(&register.to_ascii_lowercase())
+                .cloned(),
+        }
+    }
+
     fn active_editor_input_ignored(text: Arc<str>, cx: &mut WindowContext) {
         if text.is_empty() {
             return;
@@ -498,6 +607,11 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_replace(text, cx),
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
+            Some(Operator::Register) => Vim::update(cx, |vim, cx| vim.select_register(text, cx)),
+            Some(Operator::RecordRegister) => record_register(text, cx),
+            Some(Operator::ReplayRegister) => replay_register(text, cx),
+            Some(Operator::Mark) => create_mark(text, cx),
+            Some(Operator::Jump { line }) => jump_to_mark(text, line, cx),
             _ => {}
         }
     }
@@ -566,13 +680,11 @@ impl Vim {
         ret
     }
 
-    fn sync_vim_settings(&self, cx: &mut WindowContext) {
-        let state = self.state();
-        let cursor_shape = state.cursor_shape();
-
-        self.update_active_editor(cx, |editor, cx| {
-            if self.enabled && editor.mode() == EditorMode::Full {
-                editor.set_cursor_shape(cursor_shape, cx);
+    fn sync_vim_settings(&mut self, cx: &mut WindowContext) {
+        self.update_active_editor(cx, |vim, editor, cx| {
+            let state = vim.state();
+            if vim.enabled && editor.mode() == EditorMode::Full {
+                editor.set_cursor_shape(state.cursor_shape(), cx);
                 editor.set_clip_at_line_ends(state.clip_at_line_ends(), cx);
                 editor.set_collapse_matches(true);
                 editor.set_input_enabled(!state.vim_controlled());
@@ -584,7 +696,7 @@ impl Vim {
                 // Note: set_collapse_matches is not in unhook_vim_settings, as that method is called on blur,
                 // but we need collapse_matches to persist when the search bar is focused.
                 editor.set_collapse_matches(false);
-                self.unhook_vim_settings(editor, cx);
+                vim.unhook_vim_settings(editor, cx);
             }
         });
     }
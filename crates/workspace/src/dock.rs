@@ -1,6 +1,6 @@
 use crate::persistence::model::DockData;
 use crate::DraggedDock;
-use crate::{status_bar::StatusItemView, Workspace};
+use crate::{status_bar::StatusItemView, Pane, Workspace};
 use gpui::{
     div, px, Action, AnchorCorner, AnyView, AppContext, Axis, ClickEvent, Entity, EntityId,
     EventEmitter, FocusHandle, FocusableView, IntoElement, MouseButton, ParentElement, Render,
@@ -43,6 +43,10 @@ pub trait Panel: FocusableView + EventEmitter<PanelEvent> {
     }
     fn set_zoomed(&mut self, _zoomed: bool, _cx: &mut ViewContext<Self>) {}
     fn set_active(&mut self, _active: bool, _cx: &mut ViewContext<Self>) {}
+    /// The pane holding the panel's items, if the panel shows items.
+    fn pane(&self) -> Option<View<Pane>> {
+        None
+    }
 }
 
 pub trait PanelHandle: Send + Sync {
@@ -60,6 +64,7 @@ pub trait PanelHandle: Send + Sync {
     fn icon_tooltip(&self, cx: &WindowContext) -> Option<&'static str>;
     fn toggle_action(&self, cx: &WindowContext) -> Box<dyn Action>;
     fn icon_label(&self, cx: &WindowContext) -> Option<String>;
+    fn pane(&self, cx: &WindowContext) -> Option<View<Pane>>;
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle;
     fn to_any(&self) -> AnyView;
 }
@@ -124,6 +129,10 @@ where
         self.read(cx).icon_label(cx)
     }
 
+    fn pane(&self, cx: &WindowContext) -> Option<View<Pane>> {
+        self.read(cx).pane()
+    }
+
     fn to_any(&self) -> AnyView {
         self.clone().into()
     }
@@ -461,6 +470,10 @@ impl Dock {
         self.panel_entries.len()
     }
 
+    pub fn panels(&self) -> impl Iterator<Item = &Arc<dyn PanelHandle>> {
+        self.panel_entries.iter().map(|entry| &entry.panel)
+    }
+
     pub fn activate_panel(&mut self, panel_ix: usize, cx: &mut ViewContext<Self>) {
         if panel_ix != self.active_panel_index {
             if let Some(active_panel) = self.panel_entries.get(self.active_panel_index) {
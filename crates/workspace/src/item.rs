@@ -102,6 +102,8 @@ pub trait Item: FocusableView + EventEmitter<Self::Event> {
     type Event;
 
     fn deactivated(&mut self, _: &mut ViewContext<Self>) {}
+    /// Called when the item is closed, as opposed to being dropped along with its workspace.
+    fn closed(&mut self, _: &mut ViewContext<Self>) {}
     fn workspace_deactivated(&mut self, _: &mut ViewContext<Self>) {}
     fn navigate(&mut self, _: Box<dyn Any>, _: &mut ViewContext<Self>) -> bool {
         false
@@ -209,6 +211,21 @@ pub trait Item: FocusableView + EventEmitter<Self::Event> {
             "deserialize() must be implemented if serialized_item_kind() returns Some(_)"
         )
     }
+    /// Persists the item's unsaved changes to the database, so that they can be
+    /// restored by `deserialize` instead of prompting the user to save on quit.
+    /// Returns `None` if the item's changes can't be persisted this way.
+    fn serialize_unsaved_changes(
+        &mut self,
+        _workspace_id: WorkspaceId,
+        _cx: &mut ViewContext<Self>,
+    ) -> Option<Task<Result<()>>> {
+        None
+    }
+    /// Stores the state that the item persists lazily, such as on a timer, so
+    /// that it isn't lost when the application quits.
+    fn serialize_on_quit(&mut self, _cx: &mut ViewContext<Self>) -> Option<Task<()>> {
+        None
+    }
     fn show_toolbar(&self) -> bool {
         true
     }
@@ -251,6 +268,7 @@ pub trait ItemHandle: 'static + Send {
         cx: &mut ViewContext<Workspace>,
     );
     fn deactivated(&self, cx: &mut WindowContext);
+    fn closed(&self, cx: &mut WindowContext);
     fn workspace_deactivated(&self, cx: &mut WindowContext);
     fn navigate(&self, data: Box<dyn Any>, cx: &mut WindowContext) -> bool;
     fn item_id(&self) -> EntityId;
@@ -277,6 +295,12 @@ pub trait ItemHandle: 'static + Send {
     fn breadcrumb_location(&self, cx: &AppContext) -> ToolbarItemLocation;
     fn breadcrumbs(&self, theme: &Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>>;
     fn serialized_item_kind(&self) -> Option<&'static str>;
+    fn serialize_unsaved_changes(
+        &self,
+        workspace_id: WorkspaceId,
+        cx: &mut WindowContext,
+    ) -> Option<Task<Result<()>>>;
+    fn serialize_on_quit(&self, cx: &mut WindowContext) -> Option<Task<()>>;
     fn show_toolbar(&self, cx: &AppContext) -> bool;
     fn pixel_position_of_cursor(&self, cx: &AppContext) -> Option<Point<Pixels>>;
 }
@@ -538,6 +562,10 @@ impl<T: Item> ItemHandle for View<T> {
         self.update(cx, |this, cx| this.deactivated(cx));
     }
 
+    fn closed(&self, cx: &mut WindowContext) {
+        self.update(cx, |this, cx| this.closed(cx));
+    }
+
     fn workspace_deactivated(&self, cx: &mut WindowContext) {
         self.update(cx, |this, cx| this.workspace_deactivated(cx));
     }
@@ -617,6 +645,20 @@ impl<T: Item> ItemHandle for View<T> {
         T::serialized_item_kind()
     }
 
+    fn serialize_unsaved_changes(
+        &self,
+        workspace_id: WorkspaceId,
+        cx: &mut WindowContext,
+    ) -> Option<Task<Result<()>>> {
+        self.update(cx, |item, cx| {
+            item.serialize_unsaved_changes(workspace_id, cx)
+        })
+    }
+
+    fn serialize_on_quit(&self, cx: &mut WindowContext) -> Option<Task<()>> {
+        self.update(cx, |item, cx| item.serialize_on_quit(cx))
+    }
+
     fn show_toolbar(&self, cx: &AppContext) -> bool {
         self.read(cx).show_toolbar()
     }
@@ -790,9 +832,11 @@ pub mod test {
         pub save_count: usize,
         pub save_as_count: usize,
         pub reload_count: usize,
+        pub serialize_on_quit_count: usize,
         pub is_dirty: bool,
         pub is_singleton: bool,
         pub has_conflict: bool,
+        pub supports_hot_exit: bool,
         pub project_items: Vec<Model<TestProjectItem>>,
         pub nav_history: Option<ItemNavHistory>,
         pub tab_descriptions: Option<Vec<&'static str>>,
@@ -843,8 +887,10 @@ pub mod test {
                 save_count: 0,
                 save_as_count: 0,
                 reload_count: 0,
+                serialize_on_quit_count: 0,
                 is_dirty: false,
                 has_conflict: false,
+                supports_hot_exit: false,
                 project_items: Vec::new(),
                 is_singleton: true,
                 nav_history: None,
@@ -881,6 +927,11 @@ pub mod test {
             self
         }
 
+        pub fn with_hot_exit(mut self, supports_hot_exit: bool) -> Self {
+            self.supports_hot_exit = supports_hot_exit;
+            self
+        }
+
         pub fn with_project_items(mut self, items: &[Model<TestProjectItem>]) -> Self {
             self.project_items.clear();
             self.project_items.extend(items.iter().cloned());
@@ -987,9 +1038,11 @@ pub mod test {
                 save_count: self.save_count,
                 save_as_count: self.save_as_count,
                 reload_count: self.reload_count,
+                serialize_on_quit_count: self.serialize_on_quit_count,
                 is_dirty: self.is_dirty,
                 is_singleton: self.is_singleton,
                 has_conflict: self.has_conflict,
+                supports_hot_exit: self.supports_hot_exit,
                 project_items: self.project_items.clone(),
                 nav_history: None,
                 tab_descriptions: None,
@@ -1050,6 +1103,19 @@ pub mod test {
             Some("TestItem")
         }
 
+        fn serialize_unsaved_changes(
+            &mut self,
+            _workspace_id: WorkspaceId,
+            _cx: &mut ViewContext<Self>,
+        ) -> Option<Task<anyhow::Result<()>>> {
+            self.supports_hot_exit.then(|| Task::ready(Ok(())))
+        }
+
+        fn serialize_on_quit(&mut self, _cx: &mut ViewContext<Self>) -> Option<Task<()>> {
+            self.serialize_on_quit_count += 1;
+            Some(Task::ready(()))
+        }
+
         fn deserialize(
             _project: Model<Project>,
             _workspace: WeakView<Workspace>,
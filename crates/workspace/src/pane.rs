@@ -1001,6 +1001,7 @@ impl Pane {
                         .position(|i| i.item_id() == item.item_id())
                     {
                         pane.remove_item(item_ix, false, cx);
+                        item.closed(cx);
                     }
                 })
                 .ok();
@@ -1925,6 +1926,10 @@ impl ItemNavHistory {
     pub fn pop_forward(&mut self, cx: &mut WindowContext) -> Option<NavigationEntry> {
         self.history.pop(NavigationMode::GoingForward, cx)
     }
+
+    pub fn mode(&self) -> NavigationMode {
+        self.history.mode()
+    }
 }
 
 impl NavHistory {
@@ -1210,22 +1210,121 @@ impl Workspace {
                 }
             }
 
+            let hot_exited_items = if quitting {
+                let (serialize_items, serialize_unsaved_items) =
+                    this.update(&mut cx, |this, cx| {
+                        (
+                            this.serialize_items_on_quit(cx),
+                            this.serialize_unsaved_items(cx),
+                        )
+                    })?;
+                serialize_items.await;
+                serialize_unsaved_items.await
+            } else {
+                HashSet::default()
+            };
+
             Ok(this
                 .update(&mut cx, |this, cx| {
-                    this.save_all_internal(SaveIntent::Close, cx)
+                    this.save_dirty_items(SaveIntent::Close, hot_exited_items, cx)
                 })?
                 .await?)
         })
     }
 
+    /// Stores the state that items, including those of docked panels, persist
+    /// lazily, since their pending serialization tasks are dropped on quit.
+    fn serialize_items_on_quit(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
+        let mut panes = self.panes.clone();
+        for dock in [&self.left_dock, &self.bottom_dock, &self.right_dock] {
+            panes.extend(
+                dock.read(cx)
+                    .panels()
+                    .filter_map(|panel| panel.pane(cx))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let items = panes
+            .iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .map(|item| item.boxed_clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let tasks = items
+            .into_iter()
+            .filter_map(|item| item.serialize_on_quit(cx))
+            .collect::<Vec<_>>();
+        cx.background_executor().spawn(async move {
+            futures::future::join_all(tasks).await;
+        })
+    }
+
+    /// Persists the unsaved changes of every dirty item that supports it, returning
+    /// the ids of the items that no longer need to be saved before quitting.
+    fn serialize_unsaved_items(&mut self, cx: &mut ViewContext<Self>) -> Task<HashSet<EntityId>> {
+        if !WorkspaceSettings::get_global(cx).hot_exit
+            || self.project.read(cx).is_remote()
+            || self
+                .location(cx)
+                .map_or(true, |location| location.paths().is_empty())
+        {
+            return Task::ready(HashSet::default());
+        }
+
+        let dirty_items = self
+            .panes
+            .iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .filter(|item| item.is_dirty(cx))
+                    .map(|item| item.boxed_clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let tasks = dirty_items
+            .into_iter()
+            .filter_map(|item| {
+                let task = item.serialize_unsaved_changes(self.database_id, cx)?;
+                Some((item.item_id(), task))
+            })
+            .collect::<Vec<_>>();
+        // The items are restored along with the workspace, so it must be stored
+        // before quitting as well.
+        let serialize_workspace = self.serialize_workspace_internal(cx);
+
+        cx.background_executor().spawn(async move {
+            let mut serialized_items = HashSet::default();
+            for (item_id, task) in tasks {
+                if task.await.log_err().is_some() {
+                    serialized_items.insert(item_id);
+                }
+            }
+            serialize_workspace.await;
+            serialized_items
+        })
+    }
+
     fn save_all(&mut self, action: &SaveAll, cx: &mut ViewContext<Self>) {
         self.save_all_internal(action.save_intent.unwrap_or(SaveIntent::SaveAll), cx)
             .detach_and_log_err(cx);
     }
 
     fn save_all_internal(
+        &mut self,
+        save_intent: SaveIntent,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<bool>> {
+        self.save_dirty_items(save_intent, HashSet::default(), cx)
+    }
+
+    fn save_dirty_items(
         &mut self,
         mut save_intent: SaveIntent,
+        skipped_items: HashSet<EntityId>,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<bool>> {
         if self.project.read(cx).is_disconnected() {
@@ -1236,7 +1335,7 @@ impl Workspace {
             .iter()
             .flat_map(|pane| {
                 pane.read(cx).items().filter_map(|item| {
-                    if item.is_dirty(cx) {
+                    if item.is_dirty(cx) && !skipped_items.contains(&item.item_id()) {
                         Some((pane.downgrade(), item.boxed_clone()))
                     } else {
                         None
@@ -3043,6 +3142,10 @@ impl Workspace {
     }
 
     fn serialize_workspace(&self, cx: &mut WindowContext) {
+        self.serialize_workspace_internal(cx).detach();
+    }
+
+    fn serialize_workspace_internal(&self, cx: &mut WindowContext) -> Task<()> {
         fn serialize_pane_handle(pane_handle: &View<Pane>, cx: &WindowContext) -> SerializedPane {
             let (items, active) = {
                 let pane = pane_handle.read(cx);
@@ -3155,10 +3258,10 @@ impl Workspace {
                     docks,
                 };
 
-                cx.spawn(|_| persistence::DB.save_workspace(serialized_workspace))
-                    .detach();
+                return cx.spawn(|_| persistence::DB.save_workspace(serialized_workspace));
             }
         }
+        Task::ready(())
     }
 
     pub(crate) fn load_workspace(
@@ -4579,6 +4682,63 @@ mod tests {
         assert!(!task.await.unwrap());
     }
 
+    #[gpui::test]
+    async fn test_hot_exit(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({ "one": "" })).await;
+
+        let project = Project::test(fs, ["root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        // Dirty items that can persist their changes don't prompt when quitting.
+        let item1 = cx.new_view(|cx| TestItem::new(cx).with_dirty(true).with_hot_exit(true));
+        workspace.update(cx, |w, cx| w.add_item(Box::new(item1.clone()), cx));
+        let task = workspace.update(cx, |w, cx| w.prepare_to_close(true, cx));
+        cx.executor().run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        assert!(task.await.unwrap());
+        item1.update(cx, |item, _| {
+            assert_eq!(item.save_count, 0);
+            assert_eq!(item.serialize_on_quit_count, 1);
+        });
+
+        // Closing the window still prompts for them.
+        let task = workspace.update(cx, |w, cx| w.prepare_to_close(false, cx));
+        cx.executor().run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(2);
+        cx.executor().run_until_parked();
+        assert!(!task.await.unwrap());
+
+        // Items that can't persist their changes still prompt.
+        let item2 = cx.new_view(|cx| TestItem::new(cx).with_dirty(true));
+        workspace.update(cx, |w, cx| w.add_item(Box::new(item2.clone()), cx));
+        let task = workspace.update(cx, |w, cx| w.prepare_to_close(true, cx));
+        cx.executor().run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(2);
+        cx.executor().run_until_parked();
+        assert!(!task.await.unwrap());
+
+        // Hot exit can be disabled.
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings::<WorkspaceSettings>(cx, |settings| {
+                    settings.hot_exit = Some(false);
+                })
+            });
+        });
+        item2.update(cx, |item, _| item.is_dirty = false);
+        let task = workspace.update(cx, |w, cx| w.prepare_to_close(true, cx));
+        cx.executor().run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(2);
+        cx.executor().run_until_parked();
+        assert!(!task.await.unwrap());
+    }
+
     #[gpui::test]
     async fn test_close_pane_items(cx: &mut TestAppContext) {
         init_test(cx);
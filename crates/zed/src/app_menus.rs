@@ -19,6 +19,7 @@ pub fn app_menus() -> Vec<Menu<'static>> {
                         MenuItem::action("Open Default Settings", super::OpenDefaultSettings),
                         MenuItem::action("Open Default Key Bindings", super::OpenDefaultKeymap),
                         MenuItem::action("Open Local Settings", super::OpenLocalSettings),
+                        MenuItem::action("Open Tasks", super::OpenTasks),
                         MenuItem::action("Select Theme", theme_selector::Toggle),
                     ],
                 }),
@@ -351,6 +351,7 @@ const QUERY_FILENAME_PREFIXES: &[(
     ("embedding", |q| &mut q.embedding),
     ("injections", |q| &mut q.injections),
     ("overrides", |q| &mut q.overrides),
+    ("folds", |q| &mut q.folds),
 ];
 
 fn load_queries(name: &str) -> LanguageQueries {
@@ -84,6 +84,7 @@ impl super::LspAdapter for CLspAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: vec![],
+            env: None,
         })
     }
 
@@ -260,6 +261,7 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
             Ok(LanguageServerBinary {
                 path: clangd_bin,
                 arguments: vec![],
+                env: None,
             })
         } else {
             Err(anyhow!(
@@ -0,0 +1,15 @@
+[
+  (compound_statement)
+  (field_declaration_list)
+  (enumerator_list)
+  (initializer_list)
+] @fold
+
+(comment)+ @fold.comment
+
+(preproc_include)+ @fold.imports
+
+((comment) @fold.region.start
+ (#match? @fold.region.start "^//\\s*#region\\b"))
+((comment) @fold.region.end
+ (#match? @fold.region.end "^//\\s*#endregion\\b"))
@@ -86,6 +86,7 @@ impl super::LspAdapter for OmniSharpAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: server_binary_arguments(),
+            env: None,
         })
     }
 
@@ -130,6 +131,7 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
             Ok(LanguageServerBinary {
                 path,
                 arguments: server_binary_arguments(),
+                env: None,
             })
         } else {
             Err(anyhow!("no cached binary"))
@@ -73,6 +73,7 @@ impl LspAdapter for CssLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -117,6 +118,7 @@ async fn get_cached_server_binary(
             Ok(LanguageServerBinary {
                 path: node.binary_path().await?,
                 arguments: server_binary_arguments(&server_path),
+                env: None,
             })
         } else {
             Err(anyhow!(
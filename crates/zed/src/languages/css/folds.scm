@@ -0,0 +1,8 @@
+(block) @fold
+
+(comment) @fold.comment
+
+((comment) @fold.region.start
+ (#match? @fold.region.start "^/\\*\\s*#region\\b"))
+((comment) @fold.region.end
+ (#match? @fold.region.end "^/\\*\\s*#endregion\\b"))
@@ -128,6 +128,7 @@ impl LspAdapter for DenoLspAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: deno_server_binary_arguments(),
+            env: None,
         })
     }
 
@@ -214,6 +215,7 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
                     return Ok(LanguageServerBinary {
                         path: binary,
                         arguments: deno_server_binary_arguments(),
+                        env: None,
                     });
                 }
             }
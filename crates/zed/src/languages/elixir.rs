@@ -180,6 +180,7 @@ impl LspAdapter for ElixirLspAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: vec![],
+            env: None,
         })
     }
 
@@ -290,6 +291,7 @@ async fn get_cached_server_binary_elixir_ls(
         Some(LanguageServerBinary {
             path: server_path,
             arguments: vec![],
+            env: None,
         })
     } else {
         log::error!("missing executable in directory {:?}", server_path);
@@ -370,6 +372,7 @@ impl LspAdapter for NextLspAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: vec!["--stdio".into()],
+            env: None,
         })
     }
 
@@ -436,6 +439,7 @@ async fn get_cached_server_binary_next(container_dir: PathBuf) -> Option<Languag
             Ok(LanguageServerBinary {
                 path,
                 arguments: Vec::new(),
+                env: None,
             })
         } else {
             Err(anyhow!("no cached binary"))
@@ -477,6 +481,7 @@ impl LspAdapter for LocalLspAdapter {
         Ok(LanguageServerBinary {
             path: PathBuf::from(path.deref()),
             arguments: self.arguments.iter().map(|arg| arg.into()).collect(),
+            env: None,
         })
     }
 
@@ -489,6 +494,7 @@ impl LspAdapter for LocalLspAdapter {
         Some(LanguageServerBinary {
             path: PathBuf::from(path.deref()),
             arguments: self.arguments.iter().map(|arg| arg.into()).collect(),
+            env: None,
         })
     }
 
@@ -497,6 +503,7 @@ impl LspAdapter for LocalLspAdapter {
         Some(LanguageServerBinary {
             path: PathBuf::from(path.deref()),
             arguments: self.arguments.iter().map(|arg| arg.into()).collect(),
+            env: None,
         })
     }
 
@@ -0,0 +1,8 @@
+[
+  (do_block)
+  (anonymous_function)
+  (list)
+  (map)
+] @fold
+
+(comment)+ @fold.comment
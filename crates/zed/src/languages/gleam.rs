@@ -76,6 +76,7 @@ impl LspAdapter for GleamLspAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: server_binary_arguments(),
+            env: None,
         })
     }
 
@@ -111,6 +112,7 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
         anyhow::Ok(LanguageServerBinary {
             path: last.ok_or_else(|| anyhow!("no cached binary"))?,
             arguments: server_binary_arguments(),
+            env: None,
         })
     })
     .await
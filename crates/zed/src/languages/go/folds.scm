@@ -0,0 +1,15 @@
+[
+  (block)
+  (field_declaration_list)
+  (interface_type)
+  (literal_value)
+] @fold
+
+(comment)+ @fold.comment
+
+(import_declaration) @fold.imports
+
+((comment) @fold.region.start
+ (#match? @fold.region.start "^//\\s*#region\\b"))
+((comment) @fold.region.end
+ (#match? @fold.region.end "^//\\s*#endregion\\b"))
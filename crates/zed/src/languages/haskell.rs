@@ -42,6 +42,7 @@ impl LspAdapter for HaskellLanguageServer {
         Some(LanguageServerBinary {
             path: "haskell-language-server-wrapper".into(),
             arguments: vec!["lsp".into()],
+            env: None,
         })
     }
 
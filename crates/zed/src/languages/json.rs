@@ -70,6 +70,13 @@ impl JsonLspAdapter {
                     {
                         "fileMatch": [schema_file_match(&paths::KEYMAP)],
                         "schema": KeymapFile::generate_json_schema(&action_names),
+                    },
+                    {
+                        "fileMatch": [
+                            schema_file_match(&paths::TASKS),
+                            &*paths::LOCAL_TASKS_RELATIVE_PATH,
+                        ],
+                        "schema": schemars::schema_for!(task::TaskTemplates),
                     }
                 ]
             }
@@ -119,6 +126,7 @@ impl LspAdapter for JsonLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -174,6 +182,7 @@ async fn get_cached_server_binary(
             Ok(LanguageServerBinary {
                 path: node.binary_path().await?,
                 arguments: server_binary_arguments(&server_path),
+                env: None,
             })
         } else {
             Err(anyhow!(
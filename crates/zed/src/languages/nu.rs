@@ -42,6 +42,7 @@ impl LspAdapter for NuLanguageServer {
         Some(LanguageServerBinary {
             path: "nu".into(),
             arguments: vec!["--lsp".into()],
+            env: None,
         })
     }
 
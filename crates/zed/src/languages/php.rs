@@ -70,6 +70,7 @@ impl LspAdapter for IntelephenseLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: intelephense_server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -123,6 +124,7 @@ async fn get_cached_server_binary(
             Ok(LanguageServerBinary {
                 path: node.binary_path().await?,
                 arguments: intelephense_server_binary_arguments(&server_path),
+                env: None,
             })
         } else {
             Err(anyhow!(
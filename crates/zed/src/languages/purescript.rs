@@ -73,6 +73,7 @@ impl LspAdapter for PurescriptLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -126,6 +127,7 @@ async fn get_cached_server_binary(
             Ok(LanguageServerBinary {
                 path: node.binary_path().await?,
                 arguments: server_binary_arguments(&server_path),
+                env: None,
             })
         } else {
             Err(anyhow!(
@@ -63,6 +63,7 @@ impl LspAdapter for PythonLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -168,6 +169,7 @@ async fn get_cached_server_binary(
         Some(LanguageServerBinary {
             path: node.binary_path().await.log_err()?,
             arguments: server_binary_arguments(&server_path),
+            env: None,
         })
     } else {
         log::error!("missing executable in directory {:?}", server_path);
@@ -0,0 +1,22 @@
+[
+  (function_definition)
+  (class_definition)
+  (for_statement)
+  (while_statement)
+  (with_statement)
+  (dictionary)
+  (list)
+  (argument_list)
+] @fold
+
+(comment)+ @fold.comment
+
+[
+  (import_statement)
+  (import_from_statement)
+]+ @fold.imports
+
+((comment) @fold.region.start
+ (#match? @fold.region.start "^#\\s*region\\b"))
+((comment) @fold.region.end
+ (#match? @fold.region.end "^#\\s*endregion\\b"))
@@ -40,6 +40,7 @@ impl LspAdapter for RubyLanguageServer {
         Some(LanguageServerBinary {
             path: "solargraph".into(),
             arguments: vec!["stdio".into()],
+            env: None,
         })
     }
 
@@ -0,0 +1,23 @@
+[
+  (block)
+  (declaration_list)
+  (field_declaration_list)
+  (enum_variant_list)
+  (match_block)
+  (field_initializer_list)
+  (use_list)
+  (token_tree)
+  (arguments)
+  (parameters)
+  (array_expression)
+] @fold
+
+(block_comment) @fold.comment
+(line_comment)+ @fold.comment
+
+(use_declaration)+ @fold.imports
+
+((line_comment) @fold.region.start
+ (#match? @fold.region.start "^//\\s*#region\\b"))
+((line_comment) @fold.region.end
+ (#match? @fold.region.end "^//\\s*#endregion\\b"))
@@ -0,0 +1 @@
+(list) @fold
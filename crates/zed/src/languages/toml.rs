@@ -72,6 +72,7 @@ impl LspAdapter for TaploLspAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: vec!["lsp".into(), "stdio".into()],
+            env: None,
         })
     }
 
@@ -107,6 +108,7 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
         anyhow::Ok(LanguageServerBinary {
             path: last.context("no cached binary")?,
             arguments: Default::default(),
+            env: None,
         })
     })
     .await
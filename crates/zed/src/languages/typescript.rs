@@ -98,6 +98,7 @@ impl LspAdapter for TypeScriptLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: typescript_server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -183,11 +184,13 @@ async fn get_cached_ts_server_binary(
             Ok(LanguageServerBinary {
                 path: node.binary_path().await?,
                 arguments: typescript_server_binary_arguments(&new_server_path),
+                env: None,
             })
         } else if old_server_path.exists() {
             Ok(LanguageServerBinary {
                 path: node.binary_path().await?,
                 arguments: typescript_server_binary_arguments(&old_server_path),
+                env: None,
             })
         } else {
             Err(anyhow!(
@@ -293,6 +296,7 @@ impl LspAdapter for EsLintLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: eslint_server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -340,6 +344,7 @@ async fn get_cached_eslint_server_binary(
         Ok(LanguageServerBinary {
             path: node.binary_path().await?,
             arguments: eslint_server_binary_arguments(&server_path),
+            env: None,
         })
     })
     .await
@@ -0,0 +1,20 @@
+[
+  (statement_block)
+  (class_body)
+  (switch_body)
+  (object)
+  (array)
+  (arguments)
+  (formal_parameters)
+  (object_type)
+  (enum_body)
+] @fold
+
+(comment) @fold.comment
+
+(import_statement)+ @fold.imports
+
+((comment) @fold.region.start
+ (#match? @fold.region.start "^//\\s*#region\\b"))
+((comment) @fold.region.end
+ (#match? @fold.region.end "^//\\s*#endregion\\b"))
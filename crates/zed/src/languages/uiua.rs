@@ -42,6 +42,7 @@ impl LspAdapter for UiuaLanguageServer {
         Some(LanguageServerBinary {
             path: "uiua".into(),
             arguments: vec!["lsp".into()],
+            env: None,
         })
     }
 
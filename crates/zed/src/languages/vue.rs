@@ -119,6 +119,7 @@ impl super::LspAdapter for VueLspAdapter {
         Ok(LanguageServerBinary {
             path: self.node.binary_path().await?,
             arguments: vue_server_binary_arguments(&server_path),
+            env: None,
         })
     }
 
@@ -205,6 +206,7 @@ async fn get_cached_server_binary(
                 LanguageServerBinary {
                     path: node.binary_path().await?,
                     arguments: vue_server_binary_arguments(&server_path),
+                    env: None,
                 },
                 typescript_path,
             ))
@@ -71,6 +71,7 @@ impl LspAdapter for ZlsAdapter {
         Ok(LanguageServerBinary {
             path: binary_path,
             arguments: vec![],
+            env: None,
         })
     }
 
@@ -115,6 +116,7 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
             Ok(LanguageServerBinary {
                 path,
                 arguments: Vec::new(),
+                env: None,
             })
         } else {
             Err(anyhow!("no cached binary"))
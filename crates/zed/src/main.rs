@@ -12,7 +12,7 @@ use editor::Editor;
 use env_logger::Builder;
 use fs::RealFs;
 use futures::StreamExt;
-use gpui::{App, AppContext, AsyncAppContext, Context, SemanticVersion, Task};
+use gpui::{App, AppContext, AsyncAppContext, Context, Model, SemanticVersion, Task};
 use isahc::{prelude::Configurable, Request};
 use language::LanguageRegistry;
 use log::LevelFilter;
@@ -20,6 +20,7 @@ use log::LevelFilter;
 use assets::Assets;
 use node_runtime::RealNodeRuntime;
 use parking_lot::Mutex;
+use project::Project;
 use release_channel::{parse_zed_link, AppCommitSha, ReleaseChannel, RELEASE_CHANNEL};
 use serde::{Deserialize, Serialize};
 use settings::{
@@ -50,13 +51,16 @@ use util::{
 };
 use uuid::Uuid;
 use welcome::{show_welcome_view, BaseKeymap, FIRST_OPEN};
-use workspace::{AppState, WorkspaceStore};
+use workspace::{AppState, Workspace, WorkspaceStore};
 use zed::{
     app_menus, build_window_options, ensure_only_instance, handle_cli_connection,
     handle_keymap_file_changes, initialize_workspace, languages, IsOnlyInstance, OpenListener,
     OpenRequest,
 };
 
+const HEADLESS_FLAG: &str = "--headless";
+const CONNECT_FLAG: &str = "--connect";
+
 fn main() {
     menu::init();
     zed_actions::init();
@@ -64,10 +68,20 @@ fn main() {
     init_paths();
     init_logger();
 
+    if let Some(worktree_paths) = headless_paths() {
+        run_headless(worktree_paths);
+        return;
+    }
+
     if ensure_only_instance() != IsOnlyInstance::Yes {
         return;
     }
 
+    // Files are only moved once no other instance of Zed can be using them.
+    paths::legacy::migrate_dirs()
+        .context("failed to migrate legacy directories")
+        .log_err();
+
     log::info!("========== starting zed ==========");
     let app = App::new().with_assets(Assets);
 
@@ -252,15 +266,18 @@ fn main() {
         recent_projects::init(cx);
 
         go_to_line::init(cx);
+        hierarchy_view::init(cx);
         file_finder::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
         project_panel::init(Assets, cx);
+        git_panel::init(cx);
         channel::init(&client, user_store.clone(), cx);
         search::init(cx);
         semantic_index::init(fs.clone(), http.clone(), languages.clone(), cx);
         vim::init(cx);
         terminal_view::init(cx);
+        tasks_ui::init(cx);
 
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
@@ -353,12 +370,17 @@ fn main() {
                 })
                 .detach_and_log_err(cx);
             }
-            Ok(None) | Err(_) => cx
-                .spawn({
-                    let app_state = app_state.clone();
-                    |cx| async move { restore_or_create_workspace(&app_state, cx).await }
-                })
-                .detach(),
+            Ok(None) | Err(_) => {
+                if let Some(command) = connect_command() {
+                    open_headless_project(command, app_state.clone(), cx);
+                } else {
+                    cx.spawn({
+                        let app_state = app_state.clone();
+                        |cx| async move { restore_or_create_workspace(&app_state, cx).await }
+                    })
+                    .detach();
+                }
+            }
         }
 
         let app_state = app_state.clone();
@@ -417,6 +439,103 @@ fn main() {
     });
 }
 
+/// Serves the given paths to a single client over stdin and stdout, without any UI.
+fn run_headless(worktree_paths: Vec<PathBuf>) {
+    log::info!("========== starting headless zed ==========");
+    let app = App::new();
+
+    let fs = Arc::new(RealFs);
+    let user_settings_file_rx = watch_config_file(
+        &app.background_executor(),
+        fs.clone(),
+        paths::SETTINGS.clone(),
+    );
+    let login_shell_env_loaded = app.background_executor().spawn(async {
+        load_login_shell_environment().await.log_err();
+    });
+
+    app.run(move |cx| {
+        ReleaseChannel::init(cx);
+
+        let mut store = SettingsStore::default();
+        store
+            .set_default_settings(default_settings().as_ref(), cx)
+            .unwrap();
+        cx.set_global(store);
+        handle_settings_file_changes(user_settings_file_rx, cx);
+        client::init_settings(cx);
+
+        let http = http::zed_client(&client::ClientSettings::get_global(cx).server_url);
+        let client = Client::new(http.clone(), cx);
+        let mut languages = LanguageRegistry::new(login_shell_env_loaded);
+        languages.set_executor(cx.background_executor().clone());
+        languages.set_language_server_download_dir(paths::LANGUAGES_DIR.clone());
+        let languages = Arc::new(languages);
+        let node_runtime = RealNodeRuntime::new(http);
+
+        language::init(cx);
+        languages::init(languages.clone(), node_runtime.clone(), cx);
+        let user_store = cx.new_model(|cx| UserStore::new(client.clone(), cx));
+        Project::init(&client, cx);
+
+        let project = Project::local(client, node_runtime, user_store, languages, fs, cx);
+        let connection = rpc::Connection::framed(
+            smol::Unblock::new(std::io::stdin()),
+            smol::Unblock::new(std::io::stdout()),
+        );
+        cx.spawn(|cx| async move {
+            if let Err(error) =
+                serve_headless_project(project, worktree_paths, connection, cx.clone()).await
+            {
+                log::error!("error serving headless project: {:?}", error);
+            }
+            cx.update(|cx| cx.quit()).log_err();
+        })
+        .detach();
+    });
+}
+
+async fn serve_headless_project(
+    project: Model<Project>,
+    worktree_paths: Vec<PathBuf>,
+    connection: rpc::Connection,
+    mut cx: AsyncAppContext,
+) -> Result<()> {
+    for path in worktree_paths {
+        project
+            .update(&mut cx, |project, cx| {
+                project.find_or_create_local_worktree(path, true, cx)
+            })?
+            .await?;
+    }
+    cx.update(|cx| headless::serve(project, connection, cx))?
+        .await
+}
+
+/// Joins the project served by a `zed --headless` process started by `command`, and opens it
+/// in a new window.
+fn open_headless_project(command: String, app_state: Arc<AppState>, cx: &mut AppContext) {
+    let project = headless::connect(
+        command,
+        app_state.client.http_client(),
+        app_state.languages.clone(),
+        app_state.fs.clone(),
+        cx,
+    );
+    cx.spawn(|mut cx| async move {
+        let project = project.await?;
+        let window = cx.update(|cx| {
+            let options = (app_state.build_window_options)(None, None, cx);
+            cx.open_window(options, |cx| {
+                cx.new_view(|cx| Workspace::new(0, project, app_state.clone(), cx))
+            })
+        })?;
+        window.update(&mut cx, |_, cx| cx.activate_window())?;
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
 async fn authenticate(client: Arc<Client>, cx: &AsyncAppContext) -> Result<()> {
     if stdout_is_a_pty() {
         if client::IMPERSONATE_LOGIN.is_some() {
@@ -854,9 +973,32 @@ fn stdout_is_a_pty() -> bool {
     std::env::var(FORCE_CLI_MODE_ENV_VAR_NAME).ok().is_none() && std::io::stdout().is_terminal()
 }
 
+/// Returns the paths to serve if Zed was started with `--headless`.
+fn headless_paths() -> Option<Vec<PathBuf>> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if !args.iter().any(|arg| arg == HEADLESS_FLAG) {
+        return None;
+    }
+    Some(
+        args.into_iter()
+            .filter(|arg| arg != HEADLESS_FLAG)
+            .map(|arg| std::fs::canonicalize(&arg).unwrap_or_else(|_| PathBuf::from(arg)))
+            .collect(),
+    )
+}
+
+/// Returns the command given with `--connect`, which starts a headless Zed to join.
+fn connect_command() -> Option<String> {
+    let mut args = env::args().skip_while(|arg| arg != CONNECT_FLAG);
+    args.next()?;
+    args.next()
+}
+
 fn collect_url_args() -> Vec<String> {
+    let connect_command = connect_command();
     env::args()
         .skip(1)
+        .filter(|arg| arg != CONNECT_FLAG && Some(arg) != connect_command.as_ref())
         .filter_map(|arg| match std::fs::canonicalize(Path::new(&arg)) {
             Ok(path) => Some(format!("file://{}", path.to_string_lossy())),
             Err(error) => {
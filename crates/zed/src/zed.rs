@@ -18,12 +18,15 @@ pub use open_listener::*;
 use anyhow::{anyhow, Context as _};
 use assets::Assets;
 use futures::{channel::mpsc, select_biased, StreamExt};
+use git_panel::GitPanel;
 use project_panel::ProjectPanel;
 use quick_action_bar::QuickActionBar;
 use release_channel::{AppCommitSha, ReleaseChannel};
 use rope::Rope;
 use search::project_search::ProjectSearchBar;
-use settings::{initial_local_settings_content, KeymapFile, Settings, SettingsStore};
+use settings::{
+    initial_local_settings_content, watch_config_file, KeymapFile, Settings, SettingsStore,
+};
 use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use util::{
@@ -56,6 +59,7 @@ actions!(
         OpenLicenses,
         OpenLocalSettings,
         OpenLog,
+        OpenTasks,
         OpenTelemetryLog,
         ResetBufferFontSize,
         ResetDatabase,
@@ -139,6 +143,27 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
 
         vim::observe_keystrokes(cx);
 
+        let tasks_file_rx = watch_config_file(
+            cx.background_executor(),
+            app_state.fs.clone(),
+            paths::TASKS.clone(),
+        );
+        let task_inventory = workspace.project().read(cx).task_inventory().downgrade();
+        cx.spawn(|_, mut cx| async move {
+            let mut tasks_file_rx = tasks_file_rx;
+            while let Some(tasks_content) = tasks_file_rx.next().await {
+                let updated = task_inventory.update(&mut cx, |inventory, cx| {
+                    inventory
+                        .set_global_templates(Some(&tasks_content), cx)
+                        .log_err();
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
         let handle = cx.view().downgrade();
         cx.on_window_should_close(move |cx| {
             handle
@@ -162,6 +187,7 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 workspace_handle.clone(),
                 cx.clone(),
             );
+            let git_panel = GitPanel::load(workspace_handle.clone(), cx.clone());
             let (
                 project_panel,
                 terminal_panel,
@@ -169,6 +195,7 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                git_panel,
             ) = futures::try_join!(
                 project_panel,
                 terminal_panel,
@@ -176,10 +203,12 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                git_panel,
             )?;
 
             workspace_handle.update(&mut cx, |workspace, cx| {
                 workspace.add_panel(project_panel, cx);
+                workspace.add_panel(git_panel, cx);
                 workspace.add_panel(terminal_panel, cx);
                 workspace.add_panel(assistant_panel, cx);
                 workspace.add_panel(channels_panel, cx);
@@ -253,6 +282,15 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                     );
                 },
             )
+            .register_action(
+                move |_: &mut Workspace, _: &OpenTasks, cx: &mut ViewContext<Workspace>| {
+                    open_settings_file(
+                        &paths::TASKS,
+                        || settings::initial_tasks_content().as_ref().into(),
+                        cx,
+                    );
+                },
+            )
             .register_action(open_local_settings_file)
             .register_action(
                 move |workspace: &mut Workspace,
@@ -2733,6 +2771,7 @@ mod tests {
             project_panel::init_settings(cx);
             collab_ui::init(&app_state, cx);
             project_panel::init((), cx);
+            git_panel::init(cx);
             terminal_view::init(cx);
             assistant::init(cx);
             initialize_workspace(app_state.clone(), cx);